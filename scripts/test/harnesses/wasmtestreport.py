@@ -70,6 +70,7 @@ DETERMINISTIC_PARENT_NAME = "deterministic"
 FAIL_PARENT_NAME = "fail"
 LIBRARY_PARENT_NAME = "library"
 RDYNAMIC_PARENT_NAME = "rdynamic"
+LIND_RUN_DIRECTIVE = "// lind-run:"
 
 # Maps shared-library filename (e.g. "lib.so") -> Path of compiled native .so.
 # Populated by compile_dylink_libraries(); used to swap native libs in for native test runs.
@@ -206,6 +207,27 @@ def resolve_native_extra_flags(source_file: Path) -> list:
     return []
 
 
+def resolve_lind_runs(source_file: Path) -> list:
+    """Return the arguments of the extra lind_run runs a test asks for.
+
+    Each `// lind-run: <args>` line in the source is one more run after the normal one, with
+    `{wasm}` replaced by the name of the compiled binary and `{module}` by the name of the .wasm
+    it was compiled from, for runs that need the module itself (e.g. `--preinit`). The outputs
+    of all runs are compared together, so a test can check what a run leaves behind for the
+    next one (e.g. a snapshot file for `--restore`).
+    """
+    runs = []
+    try:
+        with open(source_file, 'r') as f:
+            for line in f:
+                line = line.strip()
+                if line.startswith(LIND_RUN_DIRECTIVE):
+                    runs.append(shlex.split(line[len(LIND_RUN_DIRECTIVE):]))
+    except (OSError, UnicodeDecodeError):
+        return []
+    return runs
+
+
 def _find_library_source_files() -> list:
     """Return all .c files living inside any library/ subfolder under TEST_FILE_BASE."""
     found = []
@@ -662,7 +684,7 @@ def compile_c_to_wasm(source_file, allow_precompiled=False):
 #   Since the script outputs the command being run, we ignore 
 #   the first line in stdout by the script which is the command itself
 # ----------------------------------------------------------------------
-def run_compiled_wasm(wasm_file, timeout_sec=DEFAULT_TIMEOUT):
+def run_compiled_wasm(wasm_file, timeout_sec=DEFAULT_TIMEOUT, run_args=None):
     wasm_file = Path(wasm_file)
     run_cmd = [os.path.join(LIND_TOOL_PATH, "lind_run")]
     if GRATE_CHAIN:
@@ -670,7 +692,12 @@ def run_compiled_wasm(wasm_file, timeout_sec=DEFAULT_TIMEOUT):
     elif GRATE_PREFIX:
         run_cmd.append(GRATE_PREFIX)
         run_cmd.extend(GRATE_ARGS)
-    run_cmd.append(wasm_file.name)
+    if run_args is None:
+        run_cmd.append(wasm_file.name)
+    else:
+        module_name = wasm_file.with_suffix(".wasm").name
+        run_cmd.extend(arg.replace("{wasm}", wasm_file.name).replace("{module}", module_name)
+                       for arg in run_args)
     
     logger.debug(f"Running command: {' '.join(map(str, run_cmd))}") 
     if os.path.isfile(os.path.join(LIND_TOOL_PATH, "lind_run")):
@@ -802,8 +829,25 @@ def test_single_file_unified(source_file, result, timeout_sec=DEFAULT_TIMEOUT, t
         handler.add_compile_failure(wasm_compile_error, timing_info=timing_info)
         return
     
+    copied_module = None
     try:
         retcode, wasm_output, wasm_run_time = run_compiled_wasm(wasm_file, timeout_sec)
+        # Extra runs asked for by `// lind-run:` lines, stopping at the first failure
+        lind_runs = resolve_lind_runs(source_file)
+        if any("{module}" in arg for run_args in lind_runs for arg in run_args):
+            # lind_compile only copies the precompiled binary into lindfs
+            module_file = wasm_file.with_suffix(".wasm")
+            lind_module = LINDFS_ROOT / module_file.name
+            if module_file != wasm_file and not lind_module.exists():
+                shutil.copy2(module_file, lind_module)
+                copied_module = lind_module
+        for run_args in lind_runs:
+            if retcode != 0:
+                break
+            retcode, run_output, run_time = run_compiled_wasm(wasm_file, timeout_sec, run_args)
+            wasm_output += run_output
+            if wasm_run_time is not None and run_time is not None:
+                wasm_run_time = round(wasm_run_time + run_time, 6)
         timing_info["wasm_run_time_sec"] = wasm_run_time
         
         # Handle WASM execution result
@@ -827,6 +871,8 @@ def test_single_file_unified(source_file, result, timeout_sec=DEFAULT_TIMEOUT, t
         # Always clean up WASM file
         if wasm_file and wasm_file.exists():
             wasm_file.unlink()
+        if copied_module is not None and copied_module.exists():
+            copied_module.unlink()
 
 # Wrapper functions for deterministic and fail tests
 def test_single_file_deterministic(source_file, result, timeout_sec=DEFAULT_TIMEOUT, allow_precompiled=False):
@@ -902,6 +948,9 @@ def analyze_executable_dependencies(tests_to_run):
             # Pattern matches: execv("path/to/executable", ...), execl("/bin/ls", ...), etc.
             exec_pattern = r'exec[vle]+\s*\(\s*"([^"]+)"'
             matches = re.findall(exec_pattern, content, re.IGNORECASE)
+            # posix_spawn(&pid, "path/to/executable", ...) takes the path second
+            spawn_pattern = r'posix_spawnp?\s*\([^,;]*,\s*"([^"]+)"'
+            matches += re.findall(spawn_pattern, content)
             
             for exec_path in matches:
                 exec_name = Path(exec_path).name
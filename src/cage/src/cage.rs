@@ -1,6 +1,8 @@
 //! This file contains all the implementation related to Cage structure. Including structure
 //! definitions, a global variables that handles cage management, and cage initialization and
 //! finialization required by wasmtime
+use crate::memory::cow::MemorySnapshot;
+use crate::memory::quota::{self, MemQuota};
 use crate::memory::vmmap::*;
 use crate::timer::*;
 use arc_swap::ArcSwapOption;
@@ -9,7 +11,7 @@ use dashmap::DashMap;
 /// interaction and increases efficiency.
 pub use parking_lot::{Mutex, RwLock};
 pub use std::path::{Path, PathBuf};
-pub use std::sync::atomic::{AtomicBool, AtomicI32, AtomicPtr, AtomicU64, Ordering};
+pub use std::sync::atomic::{AtomicBool, AtomicI32, AtomicPtr, AtomicU32, AtomicU64, Ordering};
 pub use std::sync::{Arc, LazyLock};
 use sysdefs::constants::lind_platform_const::MAX_CAGEID;
 use sysdefs::constants::sys_const::EXIT_SUCCESS;
@@ -143,6 +145,23 @@ pub struct Cage {
     pub parent: u64,
     // Current working directory of cage, must be able to be unique from other cages
     pub cwd: RwLock<Arc<PathBuf>>,
+    // File mode creation mask of the cage. The host process runs with a umask of 0, so every
+    // syscall that creates a filesystem object (open, openat, mkdir, mknod, bind on AF_UNIX)
+    // clears these bits from the requested mode itself. Inherited by the child on fork and kept
+    // across exec, matching Linux. Read and replaced by umask_syscall().
+    pub umask: AtomicU32,
+    // Network namespace of the cage (see `rawposix::netns`), or 0 for the host's network stack.
+    // Inherited by the child on fork and kept across exec. Replaced by unshare(CLONE_NEWNET).
+    pub netns: AtomicU64,
+    // UTS namespace of the cage (see `rawposix::uts`), which holds its hostname. Inherited by the
+    // child on fork and kept across exec. Replaced by unshare(CLONE_NEWUTS).
+    pub utsns: AtomicU64,
+    // CPUs the cage may run on, as a bitmask over the virtual CPUs of `rawposix::sched`.
+    // Inherited by the child on fork and kept across exec. Replaced by sched_setaffinity().
+    pub cpu_affinity: RwLock<Vec<u64>>,
+    // Nice value of the cage, from -20 (highest priority) to 19. Inherited by the child on fork
+    // and kept across exec. Read and replaced by getpriority() / setpriority().
+    pub nice: AtomicI32,
     // Reverse mapping for shared memory of addresses in cage to shmid, used for attaching and deattaching
     // shared memory segments
     pub rev_shm: Mutex<Vec<(u64, i32)>>,
@@ -171,6 +190,12 @@ pub struct Cage {
     // threads blocked in host syscalls, interrupting them so they can
     // re-enter wasm and see the epoch kill.
     pub os_tid_map: DashMap<i32, i64>,
+    // robust_list maps Lind thread IDs (key: i32) to the cage address of the
+    // robust_list_head each thread registered with set_robust_list (value:
+    // u64). Walked by lind_thread_exit so that waiters on robust mutexes the
+    // thread still held see EOWNERDEAD. Empty in the child of fork and cleared
+    // by exec, as on Linux.
+    pub robust_list: DashMap<i32, u64>,
     // The kernel thread id of the main thread of current cage, used because when we want to send signals,
     // we want to send to the main thread
     pub main_threadid: RwLock<i32>,
@@ -195,6 +220,14 @@ pub struct Cage {
     pub child_num: AtomicU64,
     // vmmap represents the virtual memory mapping for this cage. More details on `memory::vmmap`
     pub vmmap: RwLock<Vmmap>,
+    // memory_snapshot is the memfd the cage's private anonymous memory was last mapped from
+    // copy-on-write by fork (see `memory::cow`). Shared with the cages forked from the same
+    // snapshot, and dropped by exec.
+    pub memory_snapshot: Mutex<Option<Arc<MemorySnapshot>>>,
+    // mem_quota counts the memory the cage maps and commits, and holds its RLIMIT_AS and
+    // RLIMIT_DATA limits (see `memory::quota`). Limits are inherited by the child on fork and kept
+    // across exec.
+    pub mem_quota: MemQuota,
     // final_exit_status stores the terminal status of the cage once a
     // termination condition has been determined.
     //
@@ -285,7 +318,9 @@ pub fn add_cage(cageid: u64, cage: Cage) {
 pub fn remove_cage(cageid: u64) {
     check_cageid(cageid);
 
-    CAGE_MAP[cageid as usize].store(None);
+    if let Some(cage) = CAGE_MAP[cageid as usize].swap(None) {
+        quota::release(&cage);
+    }
 }
 
 pub fn get_cage(cageid: u64) -> Option<Arc<Cage>> {
@@ -365,14 +400,54 @@ pub fn alloc_cage_id() -> Option<u64> {
     }
 }
 
+/// Gives back `cageid`, which `alloc_cage_id` handed out for a cage that could not be created.
+/// The ID is handed out again unless a later one was allocated in the meantime, as IDs only grow.
+pub fn release_cage_id(cageid: u64) {
+    let _ = NEXT_CAGEID.compare_exchange(cageid, cageid - 1, Ordering::Relaxed, Ordering::Relaxed);
+}
+
+/// Makes `alloc_cage_id` hand out IDs above `cageid` from now on, for cages that are created again
+/// with the IDs they had, such as the cages restored from a checkpoint.
+pub fn claim_cage_id(cageid: u64) {
+    NEXT_CAGEID.fetch_max(cageid, Ordering::Relaxed);
+}
+
+/// Returns the IDs of the live cages below `cageid` in the cage tree, each after its parent.
+pub fn cage_descendants(cageid: u64) -> Vec<u64> {
+    let parents: Vec<(u64, u64)> = CAGE_MAP
+        .iter()
+        .enumerate()
+        .filter_map(|(id, slot)| slot.load().as_ref().map(|cage| (id as u64, cage.parent)))
+        .collect();
+
+    let mut tree = vec![cageid];
+    let mut next = 0;
+    while next < tree.len() {
+        let parent = tree[next];
+        // the init cage is its own parent
+        tree.extend(
+            parents
+                .iter()
+                .filter(|(id, p)| *p == parent && *id != parent)
+                .map(|(id, _)| *id),
+        );
+        next += 1;
+    }
+    tree.remove(0);
+    tree
+}
+
 /// Final cage teardown.  Called from exit_call's OnCalledAction when
 /// the actual last thread finishes its asyncify unwind.
 ///
 /// 1. Spins until `grate_inflight` reaches 0 (all grate dispatches on
 ///    backup VMContexts have returned).
-/// 2. Records a zombie entry in the parent cage and sends SIGCHLD so
+/// 2. Applies the cage's System V semaphore undo adjustments.
+/// 3. Reports the memory the cage still has mapped, if lind-boot asked
+///    for memory reports at exit.
+/// 4. Records a zombie entry in the parent cage and sends SIGCHLD so
 ///    waitpid() in the parent unblocks.
-/// 3. Removes the cage from the fd table and global cage table.
+/// 5. Removes the cage from the fd table and global cage table.
 pub fn cage_finalize(cageid: u64) {
     if let Some(cage) = get_cage(cageid) {
         // Wait for all in-flight grate dispatches to drain.
@@ -380,6 +455,11 @@ pub fn cage_finalize(cageid: u64) {
             std::hint::spin_loop();
         }
 
+        // Undo the cage's SEM_UNDO operations before the parent can see it exited.
+        crate::ipc::exit_sem_undo(cageid);
+
+        crate::memory::introspect::report_exit(&cage);
+
         // Record zombie and notify parent.
         if cage.parent != cageid {
             if let Some(parent) = get_cage(cage.parent) {
@@ -432,17 +512,25 @@ mod tests {
             cageid: 2,
             parent: 1,
             cwd: RwLock::new(Arc::new(PathBuf::from("/"))),
+            umask: AtomicU32::new(0o022),
+            netns: AtomicU64::new(0),
+            utsns: AtomicU64::new(0),
+            cpu_affinity: RwLock::new(vec![1]),
+            nice: AtomicI32::new(0),
             rev_shm: Mutex::new(Vec::new()),
             signalhandler: DashMap::new(),
             sigset: AtomicU64::new(0),
             pending_signals: RwLock::new(vec![]),
             epoch_handler: DashMap::new(),
             os_tid_map: DashMap::new(),
+            robust_list: DashMap::new(),
             main_threadid: RwLock::new(0),
             interval_timer: crate::timer::IntervalTimer::new(2),
             zombies: RwLock::new(vec![]),
             child_num: AtomicU64::new(0),
             vmmap: RwLock::new(crate::memory::vmmap::Vmmap::new()),
+            memory_snapshot: Mutex::new(None),
+            mem_quota: MemQuota::new(2),
             final_exit_status: RwLock::new(None),
             exit_group_initiated: AtomicBool::new(false),
             is_dead: AtomicBool::new(false),
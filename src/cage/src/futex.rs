@@ -0,0 +1,217 @@
+//! Robust futex helpers
+//!
+//! A thread registers the head of its robust list, the list of robust mutexes it holds, with
+//! set_robust_list. When the thread dies, `exit_robust_list` walks that list in the cage's linear
+//! memory, as Linux does in `exit_robust_list()`, and marks every futex word the thread still
+//! owned with FUTEX_OWNER_DIED before waking a waiter, so that the next locker gets EOWNERDEAD
+//! instead of waiting forever.
+//!
+//! Futex words are only touched after checking them against the cage's vmmap, so a corrupt list
+//! or a bogus futex address fails with EFAULT instead of faulting the host.
+//!
+//! Cages that share a SysV segment or a `MAP_SHARED` mapping see it at a different host address
+//! in each linear memory. `futex_shared_mapping` tells `futex_syscall` which words live in such a
+//! mapping, so that their futex operations key waiters by the backing object and offset.
+use crate::cage::Cage;
+use crate::memory::{MemoryBackingType, Vmmap, VmmapOps};
+use std::sync::atomic::{AtomicU32, Ordering};
+use sysdefs::constants::err_const::Errno;
+use sysdefs::constants::fs_const::{MAP_SHARED, PAGESHIFT, PROT_READ, PROT_WRITE};
+use sysdefs::constants::sys_const::{
+    FUTEX_OWNER_DIED, FUTEX_TID_MASK, FUTEX_WAITERS, FUTEX_WAKE, ROBUST_LIST_HEAD_SIZE,
+    ROBUST_LIST_HEAD_SIZE64, ROBUST_LIST_LIMIT,
+};
+
+/// Checks that `len` bytes at cage address `uaddr` are mapped in the cage's vmmap with `prot`,
+/// and returns their host address
+///
+/// # Returns
+/// * `Ok(usize)` - Host address of `uaddr`
+/// * `Err(Errno::EINVAL)` - If `uaddr` is not 32-bit aligned
+/// * `Err(Errno::EFAULT)` - If `uaddr` is NULL or the range is not mapped with `prot`
+pub fn check_futex_uaddr(
+    vmmap: &mut Vmmap,
+    uaddr: u64,
+    len: usize,
+    prot: i32,
+) -> Result<usize, Errno> {
+    if uaddr == 0 || vmmap.base_address.is_none() {
+        return Err(Errno::EFAULT);
+    }
+    if !uaddr.is_multiple_of(4) {
+        return Err(Errno::EINVAL);
+    }
+    let sysaddr = vmmap.user_to_sys(uaddr);
+    let mapped = match prot {
+        PROT_READ => vmmap.check_addr_read(sysaddr as u64, len),
+        PROT_WRITE => vmmap.check_addr_write(sysaddr as u64, len),
+        _ => vmmap.check_addr_rw(sysaddr as u64, len),
+    };
+    if !mapped {
+        return Err(Errno::EFAULT);
+    }
+    Ok(sysaddr)
+}
+
+/// Same as `check_futex_uaddr`, for a host address already translated by glibc
+pub fn check_futex_addr(
+    vmmap: &mut Vmmap,
+    addr: u64,
+    len: usize,
+    prot: i32,
+) -> Result<usize, Errno> {
+    let Some(base) = vmmap.base_address else {
+        return Err(Errno::EFAULT);
+    };
+    match addr.checked_sub(base as u64) {
+        Some(uaddr) if uaddr < vmmap.address_space_size() => {
+            check_futex_uaddr(vmmap, uaddr, len, prot)
+        }
+        _ => Err(Errno::EFAULT),
+    }
+}
+
+/// Returns whether the futex word at host address `addr`, already checked by `check_futex_addr`,
+/// lies in memory shared with other cages: an attached SysV segment, or a `MAP_SHARED` file or
+/// anonymous mapping (which fork shares with the child)
+///
+/// The host keys a shared futex operation on such a page by the backing object and the page's
+/// offset in it rather than by address, so waiters and wakers meet wherever each cage mapped it.
+pub fn futex_shared_mapping(vmmap: &Vmmap, addr: usize) -> bool {
+    let page = (vmmap.sys_to_user(addr) >> PAGESHIFT) as u32;
+    match vmmap.find_page(page) {
+        Some(entry) => match entry.backing {
+            MemoryBackingType::SharedMemory(_) => true,
+            MemoryBackingType::FileDescriptor(_) | MemoryBackingType::Anonymous => {
+                entry.flags as u32 & MAP_SHARED != 0
+            }
+            MemoryBackingType::None => false,
+        },
+        None => false,
+    }
+}
+
+/// Wakes one waiter on the futex word at host address `addr`
+///
+/// Uses a shared wake like Linux does for robust futexes, since glibc always waits on robust
+/// mutexes with shared futex operations.
+pub fn futex_wake_one(addr: usize) {
+    unsafe {
+        libc::syscall(libc::SYS_futex, addr, FUTEX_WAKE, 1);
+    }
+}
+
+/// Releases the futex word at host address `addr` if thread `tid` still owns it, as Linux's
+/// `handle_futex_death()` does
+///
+/// The TID bits are cleared and FUTEX_OWNER_DIED is set, keeping FUTEX_WAITERS so that the next
+/// owner still wakes the others. One waiter is woken if there were any. If `pending` (the entry
+/// was the thread's list_op_pending) and the word is 0, the thread died between unlocking and
+/// waking, so a waiter is woken to not lose that wakeup.
+fn handle_futex_death(addr: usize, tid: u32, pi: bool, pending: bool) {
+    // SAFETY: `addr` was checked by `check_futex_uaddr` to be a 32-bit aligned, writable word of
+    // the cage's linear memory
+    let word = unsafe { &*(addr as *const AtomicU32) };
+    let mut uval = word.load(Ordering::SeqCst);
+    loop {
+        if pending && !pi && uval == 0 {
+            futex_wake_one(addr);
+            return;
+        }
+        if uval & FUTEX_TID_MASK != tid {
+            return;
+        }
+        let mval = (uval & FUTEX_WAITERS) | FUTEX_OWNER_DIED;
+        match word.compare_exchange(uval, mval, Ordering::SeqCst, Ordering::SeqCst) {
+            Ok(_) => break,
+            Err(current) => uval = current,
+        }
+    }
+    if uval & FUTEX_WAITERS != 0 {
+        futex_wake_one(addr);
+    }
+}
+
+/// Reads the pointer-sized word at host address `addr`: 4 bytes in a 32-bit cage, 8 in a 64-bit
+/// one
+///
+/// # Safety
+/// `addr` must be readable for the size of a word.
+unsafe fn read_word(vmmap: &Vmmap, addr: usize) -> u64 {
+    if vmmap.memory64 {
+        (addr as *const u64).read_unaligned()
+    } else {
+        (addr as *const u32).read_unaligned() as u64
+    }
+}
+
+/// Walks the robust list thread `tid` of `cage` registered, and releases every futex it still
+/// owns (see `handle_futex_death`)
+///
+/// The list lives in the cage's linear memory: a `robust_list_head` whose `list.next` starts a
+/// circular list of `robust_list` entries linked by cage addresses, ending back at the head. The
+/// futex word of each entry is `futex_offset` bytes from it, and the low bit of a link marks a PI
+/// mutex. `list_op_pending` is an entry the thread was adding or removing when it died. Called by
+/// `lind_thread_exit` while the cage's memory is still mapped.
+pub fn exit_robust_list(cage: &Cage, tid: i32) {
+    let Some((_, head)) = cage.robust_list.remove(&tid) else {
+        return;
+    };
+    let tid = tid as u32;
+    let mut vmmap = cage.vmmap.write();
+
+    // Links and the futex offset are pointer-sized, and wrap around the cage's pointer width
+    let (head_size, word, mask) = if vmmap.memory64 {
+        (ROBUST_LIST_HEAD_SIZE64, 8, u64::MAX)
+    } else {
+        (ROBUST_LIST_HEAD_SIZE, 4, u32::MAX as u64)
+    };
+    let Ok(head_addr) = check_futex_uaddr(&mut vmmap, head, head_size, PROT_READ) else {
+        return;
+    };
+    // SAFETY: the whole head was checked to be readable above
+    let (next, futex_offset, pending) = unsafe {
+        (
+            read_word(&vmmap, head_addr),
+            read_word(&vmmap, head_addr + word),
+            read_word(&vmmap, head_addr + 2 * word),
+        )
+    };
+
+    let futex_addr = |vmmap: &mut Vmmap, entry: u64| {
+        check_futex_uaddr(
+            vmmap,
+            entry.wrapping_add(futex_offset) & mask,
+            4,
+            PROT_READ | PROT_WRITE,
+        )
+    };
+    let (pending_entry, pending_pi) = (pending & !1, pending & 1 != 0);
+
+    let (mut entry, mut pi) = (next & !1, next & 1 != 0);
+    let mut limit = ROBUST_LIST_LIMIT;
+    while entry != head {
+        // Fetch the next link first, as releasing the futex lets another thread reuse the entry
+        let Ok(entry_addr) = check_futex_uaddr(&mut vmmap, entry, word, PROT_READ) else {
+            break;
+        };
+        // SAFETY: checked to be readable above
+        let next = unsafe { read_word(&vmmap, entry_addr) };
+        if entry != pending_entry {
+            if let Ok(addr) = futex_addr(&mut vmmap, entry) {
+                handle_futex_death(addr, tid, pi, false);
+            }
+        }
+        (entry, pi) = (next & !1, next & 1 != 0);
+        limit -= 1;
+        if limit == 0 {
+            break;
+        }
+    }
+
+    if pending_entry != 0 {
+        if let Ok(addr) = futex_addr(&mut vmmap, pending_entry) {
+            handle_futex_death(addr, tid, pending_pi, true);
+        }
+    }
+}
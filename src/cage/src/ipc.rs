@@ -0,0 +1,780 @@
+//! System V semaphore sets and message queues
+//!
+//! All cages run inside one host process, so semaphore sets and message queues live in host
+//! memory and are shared by every cage, like the shared memory segments in `memory::shared`.
+//! Keys and ids are global: cages related by `fork` and unrelated cages find the same objects.
+//! `IPC_RMID` removes an object at once, and callers blocked on it fail with EIDRM.
+//!
+//! Blocking operations sleep on the object's condition variable in chunks of `WAIT_CHUNK`, and
+//! fail with EINTR once a signal is pending for the calling cage.
+//!
+//! Permissions follow Linux `ipcperms()`, with the host process' effective ids, which is what
+//! `geteuid`/`getegid` report to cages. Adjustments recorded by `SEM_UNDO` belong to the cage
+//! that made the operation and are applied by `exit_sem_undo` when it exits. As on Linux, the
+//! child of `fork` starts without any and `exec` keeps them.
+use crate::memory::timestamp;
+use crate::signal::signal_check_trigger;
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use sysdefs::constants::err_const::Errno;
+use sysdefs::constants::fs_const::{
+    GETALL, GETNCNT, GETPID, GETVAL, GETZCNT, IPC_CREAT, IPC_EXCL, IPC_NOWAIT, IPC_PRIVATE,
+    IPC_RMID, IPC_SET, IPC_STAT, MSGMAX, MSGMNB, MSGMNI, MSG_EXCEPT, MSG_NOERROR, SEMMNI, SEMMSL,
+    SEMOPM, SEMVMX, SEM_UNDO, SETALL, SETVAL,
+};
+use sysdefs::data::fs_struct::{IpcPermStruct, MsqidStruct, SembufStruct, SemidStruct};
+
+/// Blocking operations wake up this often to check for pending signals.
+const WAIT_CHUNK: Duration = Duration::from_millis(100);
+
+/// Permission bits checked by operations that read an object (`S_IRUGO`).
+const PERM_READ: i32 = 0o444;
+/// Permission bits checked by operations that change an object (`S_IWUGO`).
+const PERM_WRITE: i32 = 0o222;
+
+/// The ids of one kind of object, and the objects themselves.
+struct IpcIds<T> {
+    keys: HashMap<i32, i32>,
+    objects: HashMap<i32, Arc<T>>,
+    nextid: i32,
+}
+
+struct IpcTable<T> {
+    ids: Mutex<IpcIds<T>>,
+    max: usize,
+}
+
+impl<T> IpcTable<T> {
+    fn new(max: usize) -> Self {
+        IpcTable {
+            ids: Mutex::new(IpcIds {
+                keys: HashMap::new(),
+                objects: HashMap::new(),
+                nextid: 1,
+            }),
+            max,
+        }
+    }
+
+    /// Looks `key` up, or creates a new object with `create` when `flg` allows it. `existing`
+    /// checks that an object found under the key can be used.
+    ///
+    /// # Returns
+    /// * `Ok(i32)` - The id of the object
+    /// * `Err(Errno::EEXIST)` - If the key exists and `IPC_CREAT | IPC_EXCL` was given
+    /// * `Err(Errno::ENOENT)` - If the key does not exist and `IPC_CREAT` was not given
+    /// * `Err(Errno::ENOSPC)` - If the table is full
+    /// * Any error of `create` or `existing`
+    fn get_or_create(
+        &self,
+        key: i32,
+        flg: i32,
+        create: impl FnOnce(IpcPermStruct) -> Result<T, Errno>,
+        existing: impl FnOnce(&T) -> Result<(), Errno>,
+    ) -> Result<i32, Errno> {
+        let mut ids = self.ids.lock();
+        if key != IPC_PRIVATE {
+            if let Some(&id) = ids.keys.get(&key) {
+                if flg & (IPC_CREAT | IPC_EXCL) == IPC_CREAT | IPC_EXCL {
+                    return Err(Errno::EEXIST);
+                }
+                existing(&ids.objects[&id])?;
+                return Ok(id);
+            }
+            if flg & IPC_CREAT == 0 {
+                return Err(Errno::ENOENT);
+            }
+        }
+        if ids.objects.len() >= self.max {
+            return Err(Errno::ENOSPC);
+        }
+        let (uid, gid) = effective_ids();
+        let perm = IpcPermStruct {
+            __key: key,
+            uid,
+            gid,
+            cuid: uid,
+            cgid: gid,
+            mode: (flg & 0o777) as u16,
+            ..Default::default()
+        };
+        let object = create(perm)?;
+        let id = ids.nextid;
+        ids.nextid += 1;
+        if key != IPC_PRIVATE {
+            ids.keys.insert(key, id);
+        }
+        ids.objects.insert(id, Arc::new(object));
+        Ok(id)
+    }
+
+    fn get(&self, id: i32) -> Result<Arc<T>, Errno> {
+        self.ids
+            .lock()
+            .objects
+            .get(&id)
+            .cloned()
+            .ok_or(Errno::EINVAL)
+    }
+
+    /// Forgets the object with `id`, so that its key can be used for a new one.
+    fn remove(&self, id: i32, key: i32) {
+        let mut ids = self.ids.lock();
+        ids.objects.remove(&id);
+        if ids.keys.get(&key) == Some(&id) {
+            ids.keys.remove(&key);
+        }
+    }
+
+    fn all(&self) -> Vec<Arc<T>> {
+        self.ids.lock().objects.values().cloned().collect()
+    }
+}
+
+fn effective_ids() -> (u32, u32) {
+    unsafe { (libc::geteuid(), libc::getegid()) }
+}
+
+/// Whether the caller may access an object with `perm` as `flag` asks for (Linux `ipcperms()`).
+fn ipcperms(perm: &IpcPermStruct, flag: i32) -> bool {
+    let (euid, egid) = effective_ids();
+    let requested = ((flag >> 6) | (flag >> 3) | flag) & 0o7;
+    let mut granted = perm.mode as i32;
+    if euid == perm.cuid || euid == perm.uid {
+        granted >>= 6;
+    } else if egid == perm.cgid || egid == perm.gid {
+        granted >>= 3;
+    }
+    requested & !granted & 0o7 == 0 || euid == 0
+}
+
+/// Whether the caller may change or remove an object with `perm`: its owner, creator or root.
+fn ipc_owner(perm: &IpcPermStruct) -> bool {
+    let (euid, _) = effective_ids();
+    euid == 0 || euid == perm.uid || euid == perm.cuid
+}
+
+/// Applies the owner, group and mode an `IPC_SET` caller passed in `new`.
+fn ipc_set(perm: &mut IpcPermStruct, new: &IpcPermStruct) {
+    perm.uid = new.uid;
+    perm.gid = new.gid;
+    perm.mode = (perm.mode & !0o777) | (new.mode & 0o777);
+}
+
+/// Sleeps on `cond` for one chunk, or less if `deadline` comes first.
+///
+/// # Returns
+/// * `Ok(())` - After waking up, for the caller to check its condition again
+/// * `Err(Errno::EINTR)` - If a signal is pending for the cage
+/// * `Err(Errno::EAGAIN)` - If `deadline` has passed
+fn wait<T>(
+    cageid: u64,
+    cond: &Condvar,
+    guard: &mut MutexGuard<T>,
+    deadline: Option<Instant>,
+) -> Result<(), Errno> {
+    if signal_check_trigger(cageid) {
+        return Err(Errno::EINTR);
+    }
+    let chunk = match deadline {
+        Some(deadline) => {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Err(Errno::EAGAIN);
+            }
+            left.min(WAIT_CHUNK)
+        }
+        None => WAIT_CHUNK,
+    };
+    cond.wait_for(guard, chunk);
+    Ok(())
+}
+
+static SEM_IDS: LazyLock<IpcTable<SemSet>> = LazyLock::new(|| IpcTable::new(SEMMNI));
+static MSG_IDS: LazyLock<IpcTable<MsgQueue>> = LazyLock::new(|| IpcTable::new(MSGMNI));
+
+#[derive(Clone, Copy, Default)]
+struct Sem {
+    val: i32,
+    // Cage that last changed the value
+    pid: i32,
+    // Callers waiting for the value to grow
+    ncnt: u32,
+    // Callers waiting for the value to reach zero
+    zcnt: u32,
+}
+
+struct SemState {
+    perm: IpcPermStruct,
+    otime: u64,
+    ctime: u64,
+    sems: Vec<Sem>,
+    // SEM_UNDO adjustments of every cage that has any, by cage id
+    undo: HashMap<u64, Vec<i32>>,
+    removed: bool,
+}
+
+/// A System V semaphore set.
+struct SemSet {
+    state: Mutex<SemState>,
+    cond: Condvar,
+}
+
+/// What one pass over the operations of a `semop` call came to.
+enum SemAttempt {
+    Done,
+    // The operation at this index has to wait
+    Block(usize),
+}
+
+impl SemState {
+    /// Applies all of `sops` or none of them (Linux `perform_atomic_semop()`).
+    fn attempt(&mut self, cageid: u64, sops: &[SembufStruct]) -> Result<SemAttempt, Errno> {
+        let saved: Vec<i32> = sops
+            .iter()
+            .map(|sop| self.sems[sop.sem_num as usize].val)
+            .collect();
+        let rollback = |state: &mut SemState, upto: usize| {
+            for (sop, val) in sops[..upto].iter().zip(&saved).rev() {
+                state.sems[sop.sem_num as usize].val = *val;
+            }
+        };
+        for (i, sop) in sops.iter().enumerate() {
+            let sem = &mut self.sems[sop.sem_num as usize];
+            let result = sem.val + sop.sem_op as i32;
+            if (sop.sem_op == 0 && sem.val != 0) || result < 0 {
+                rollback(self, i);
+                return Ok(SemAttempt::Block(i));
+            }
+            if result > SEMVMX {
+                rollback(self, i);
+                return Err(Errno::ERANGE);
+            }
+            sem.val = result;
+        }
+
+        // Every operation can go ahead; check that the undo adjustments stay in range too
+        let nsems = self.sems.len();
+        let mut undo = self.undo.get(&cageid).cloned();
+        for sop in sops.iter().filter(|sop| sop.sem_flg & SEM_UNDO != 0) {
+            let adj = &mut undo.get_or_insert_with(|| vec![0; nsems])[sop.sem_num as usize];
+            *adj -= sop.sem_op as i32;
+            if *adj < -SEMVMX - 1 || *adj > SEMVMX {
+                rollback(self, sops.len());
+                return Err(Errno::ERANGE);
+            }
+        }
+        if let Some(undo) = undo {
+            self.undo.insert(cageid, undo);
+        }
+        for sop in sops {
+            self.sems[sop.sem_num as usize].pid = cageid as i32;
+        }
+        self.otime = timestamp();
+        Ok(SemAttempt::Done)
+    }
+
+    fn stat(&self) -> SemidStruct {
+        SemidStruct {
+            sem_perm: self.perm,
+            sem_otime: self.otime as u32,
+            sem_otime_high: (self.otime >> 32) as u32,
+            sem_ctime: self.ctime as u32,
+            sem_ctime_high: (self.ctime >> 32) as u32,
+            sem_nsems: self.sems.len() as u32,
+            ..Default::default()
+        }
+    }
+}
+
+/// `semget()`: finds or creates the semaphore set for `key`.
+///
+/// # Returns
+/// * `Ok(i32)` - The id of the set
+/// * `Err(Errno::EINVAL)` - If `nsems` is out of range, or larger than an existing set
+/// * `Err(Errno::EACCES)` - If the caller may not access the existing set
+/// * The errors of a key lookup (EEXIST, ENOENT, ENOSPC)
+pub fn semget(key: i32, nsems: i32, semflg: i32) -> Result<i32, Errno> {
+    if !(0..=SEMMSL).contains(&nsems) {
+        return Err(Errno::EINVAL);
+    }
+    SEM_IDS.get_or_create(
+        key,
+        semflg,
+        |perm| {
+            if nsems == 0 {
+                return Err(Errno::EINVAL);
+            }
+            Ok(SemSet {
+                state: Mutex::new(SemState {
+                    perm,
+                    otime: 0,
+                    ctime: timestamp(),
+                    sems: vec![Sem::default(); nsems as usize],
+                    undo: HashMap::new(),
+                    removed: false,
+                }),
+                cond: Condvar::new(),
+            })
+        },
+        |set| {
+            let st = set.state.lock();
+            if nsems as usize > st.sems.len() {
+                return Err(Errno::EINVAL);
+            }
+            if !ipcperms(&st.perm, semflg) {
+                return Err(Errno::EACCES);
+            }
+            Ok(())
+        },
+    )
+}
+
+/// `semtimedop()`: performs `sops` on the set `semid` atomically, waiting up to `timeout` for
+/// them to become possible unless an operation that cannot proceed has `IPC_NOWAIT`.
+///
+/// # Returns
+/// * `Ok(())` - Once every operation was applied
+/// * `Err(Errno::EBIG)` - If there are more than `SEMOPM` operations
+/// * `Err(Errno::EINVAL)` - If there are none, or `semid` does not exist
+/// * `Err(Errno::EFBIG)` - If an operation names a semaphore outside the set
+/// * `Err(Errno::EACCES)` - If the caller may not read (wait for zero) or alter the set
+/// * `Err(Errno::ERANGE)` - If a value or undo adjustment would leave its range
+/// * `Err(Errno::EAGAIN)` - If the operations would block with `IPC_NOWAIT`, or `timeout` passed
+/// * `Err(Errno::EIDRM)` - If the set was removed while waiting
+/// * `Err(Errno::EINTR)` - If a signal arrived while waiting
+pub fn semtimedop(
+    cageid: u64,
+    semid: i32,
+    sops: &[SembufStruct],
+    timeout: Option<Duration>,
+) -> Result<(), Errno> {
+    if sops.len() > SEMOPM {
+        return Err(Errno::EBIG);
+    }
+    if sops.is_empty() {
+        return Err(Errno::EINVAL);
+    }
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let set = SEM_IDS.get(semid)?;
+    let mut st = set.state.lock();
+    if st.removed {
+        return Err(Errno::EIDRM);
+    }
+    if sops.iter().any(|sop| sop.sem_num as usize >= st.sems.len()) {
+        return Err(Errno::EFBIG);
+    }
+    let alter = sops.iter().any(|sop| sop.sem_op != 0);
+    if !ipcperms(&st.perm, if alter { PERM_WRITE } else { PERM_READ }) {
+        return Err(Errno::EACCES);
+    }
+
+    loop {
+        let blocked = match st.attempt(cageid, sops)? {
+            SemAttempt::Done => {
+                drop(st);
+                set.cond.notify_all();
+                return Ok(());
+            }
+            SemAttempt::Block(i) => &sops[i],
+        };
+        if blocked.sem_flg as i32 & IPC_NOWAIT != 0 {
+            return Err(Errno::EAGAIN);
+        }
+
+        let num = blocked.sem_num as usize;
+        let for_zero = blocked.sem_op == 0;
+        if for_zero {
+            st.sems[num].zcnt += 1;
+        } else {
+            st.sems[num].ncnt += 1;
+        }
+        let waited = wait(cageid, &set.cond, &mut st, deadline);
+        if for_zero {
+            st.sems[num].zcnt -= 1;
+        } else {
+            st.sems[num].ncnt -= 1;
+        }
+        if st.removed {
+            return Err(Errno::EIDRM);
+        }
+        waited?;
+    }
+}
+
+/// The number of semaphores in the set `semid`, or EINVAL if there is no such set.
+pub fn sem_nsems(semid: i32) -> Result<usize, Errno> {
+    Ok(SEM_IDS.get(semid)?.state.lock().sems.len())
+}
+
+/// The argument of a `semctl()` command.
+pub enum SemctlArg<'a> {
+    None,
+    Val(i32),
+    Array(&'a mut [u16]),
+    Buf(&'a mut SemidStruct),
+}
+
+/// `semctl()`: `GETVAL`, `SETVAL`, `GETALL`, `SETALL`, `GETPID`, `GETNCNT`, `GETZCNT`,
+/// `IPC_STAT`, `IPC_SET` and `IPC_RMID` on the set `semid`.
+///
+/// # Returns
+/// * `Ok(i32)` - The requested value for the `GET*` commands, otherwise 0
+/// * `Err(Errno::EINVAL)` - If `semid` does not exist, `semnum` is out of range, or `cmd` is
+///   not supported
+/// * `Err(Errno::EACCES)` - If the caller may not read or alter the set
+/// * `Err(Errno::EPERM)` - If an `IPC_SET` or `IPC_RMID` caller does not own the set
+/// * `Err(Errno::ERANGE)` - If a new value is above `SEMVMX`
+/// * `Err(Errno::EFAULT)` - If the command needs an argument that was not given
+pub fn semctl(
+    cageid: u64,
+    semid: i32,
+    semnum: i32,
+    cmd: i32,
+    arg: SemctlArg,
+) -> Result<i32, Errno> {
+    let set = SEM_IDS.get(semid)?;
+    let mut st = set.state.lock();
+    if st.removed {
+        return Err(Errno::EIDRM);
+    }
+    let perm = match cmd {
+        GETVAL | GETALL | GETPID | GETNCNT | GETZCNT | IPC_STAT => PERM_READ,
+        SETVAL | SETALL => PERM_WRITE,
+        IPC_SET | IPC_RMID => {
+            if !ipc_owner(&st.perm) {
+                return Err(Errno::EPERM);
+            }
+            0
+        }
+        _ => return Err(Errno::EINVAL),
+    };
+    if !ipcperms(&st.perm, perm) {
+        return Err(Errno::EACCES);
+    }
+    let sem = match cmd {
+        GETVAL | SETVAL | GETPID | GETNCNT | GETZCNT => {
+            if semnum < 0 || semnum as usize >= st.sems.len() {
+                return Err(Errno::EINVAL);
+            }
+            semnum as usize
+        }
+        _ => 0,
+    };
+
+    match (cmd, arg) {
+        (GETVAL, _) => Ok(st.sems[sem].val),
+        (GETPID, _) => Ok(st.sems[sem].pid),
+        (GETNCNT, _) => Ok(st.sems[sem].ncnt as i32),
+        (GETZCNT, _) => Ok(st.sems[sem].zcnt as i32),
+        (GETALL, SemctlArg::Array(array)) => {
+            for (out, sem) in array.iter_mut().zip(&st.sems) {
+                *out = sem.val as u16;
+            }
+            Ok(0)
+        }
+        (IPC_STAT, SemctlArg::Buf(buf)) => {
+            *buf = st.stat();
+            Ok(0)
+        }
+        (SETVAL, SemctlArg::Val(val)) => {
+            if !(0..=SEMVMX).contains(&val) {
+                return Err(Errno::ERANGE);
+            }
+            st.sems[sem].val = val;
+            st.sems[sem].pid = cageid as i32;
+            for undo in st.undo.values_mut() {
+                undo[sem] = 0;
+            }
+            st.ctime = timestamp();
+            drop(st);
+            set.cond.notify_all();
+            Ok(0)
+        }
+        (SETALL, SemctlArg::Array(array)) => {
+            if array.iter().any(|&val| val as i32 > SEMVMX) {
+                return Err(Errno::ERANGE);
+            }
+            for (sem, &val) in st.sems.iter_mut().zip(array.iter()) {
+                sem.val = val as i32;
+                sem.pid = cageid as i32;
+            }
+            st.undo.clear();
+            st.ctime = timestamp();
+            drop(st);
+            set.cond.notify_all();
+            Ok(0)
+        }
+        (IPC_SET, SemctlArg::Buf(buf)) => {
+            ipc_set(&mut st.perm, &buf.sem_perm);
+            st.ctime = timestamp();
+            Ok(0)
+        }
+        (IPC_RMID, _) => {
+            st.removed = true;
+            let key = st.perm.__key;
+            drop(st);
+            SEM_IDS.remove(semid, key);
+            set.cond.notify_all();
+            Ok(0)
+        }
+        _ => Err(Errno::EFAULT),
+    }
+}
+
+/// Applies and forgets the `SEM_UNDO` adjustments of an exiting cage. Values are clamped to
+/// `0..=SEMVMX`, as on Linux.
+pub fn exit_sem_undo(cageid: u64) {
+    for set in SEM_IDS.all() {
+        let mut st = set.state.lock();
+        let undo = match st.undo.remove(&cageid) {
+            Some(undo) => undo,
+            None => continue,
+        };
+        for (sem, adj) in st.sems.iter_mut().zip(undo) {
+            if adj != 0 {
+                sem.val = (sem.val + adj).clamp(0, SEMVMX);
+                sem.pid = cageid as i32;
+            }
+        }
+        st.otime = timestamp();
+        drop(st);
+        set.cond.notify_all();
+    }
+}
+
+struct MsgState {
+    perm: IpcPermStruct,
+    stime: u64,
+    rtime: u64,
+    ctime: u64,
+    // Messages in the order they were sent, as (type, text)
+    messages: VecDeque<(i32, Vec<u8>)>,
+    cbytes: usize,
+    qbytes: usize,
+    lspid: i32,
+    lrpid: i32,
+    removed: bool,
+}
+
+/// A System V message queue.
+struct MsgQueue {
+    state: Mutex<MsgState>,
+    cond: Condvar,
+}
+
+impl MsgState {
+    /// The index of the message `msgrcv` takes for `msgtyp` and `msgflg`.
+    fn find(&self, msgtyp: i32, msgflg: i32) -> Option<usize> {
+        let mut iter = self.messages.iter().enumerate();
+        match msgtyp {
+            0 => iter.next().map(|(i, _)| i),
+            t if t > 0 && msgflg & MSG_EXCEPT != 0 => iter.find(|(_, m)| m.0 != t).map(|(i, _)| i),
+            t if t > 0 => iter.find(|(_, m)| m.0 == t).map(|(i, _)| i),
+            // The first message of the lowest type at most |msgtyp|
+            t => iter
+                .filter(|(_, m)| m.0 <= t.saturating_neg())
+                .min_by_key(|(i, m)| (m.0, *i))
+                .map(|(i, _)| i),
+        }
+    }
+}
+
+/// `msgget()`: finds or creates the message queue for `key`.
+///
+/// # Returns
+/// * `Ok(i32)` - The id of the queue
+/// * `Err(Errno::EACCES)` - If the caller may not access the existing queue
+/// * The errors of a key lookup (EEXIST, ENOENT, ENOSPC)
+pub fn msgget(key: i32, msgflg: i32) -> Result<i32, Errno> {
+    MSG_IDS.get_or_create(
+        key,
+        msgflg,
+        |perm| {
+            Ok(MsgQueue {
+                state: Mutex::new(MsgState {
+                    perm,
+                    stime: 0,
+                    rtime: 0,
+                    ctime: timestamp(),
+                    messages: VecDeque::new(),
+                    cbytes: 0,
+                    qbytes: MSGMNB,
+                    lspid: 0,
+                    lrpid: 0,
+                    removed: false,
+                }),
+                cond: Condvar::new(),
+            })
+        },
+        |queue| {
+            if !ipcperms(&queue.state.lock().perm, msgflg) {
+                return Err(Errno::EACCES);
+            }
+            Ok(())
+        },
+    )
+}
+
+/// `msgsnd()`: queues a message of type `mtype` holding `text`, waiting for room unless
+/// `msgflg` has `IPC_NOWAIT`.
+///
+/// # Returns
+/// * `Ok(())` - Once the message is queued
+/// * `Err(Errno::EINVAL)` - If `msqid` does not exist, `mtype` is below 1 or `text` is longer
+///   than `MSGMAX`
+/// * `Err(Errno::EACCES)` - If the caller may not write to the queue
+/// * `Err(Errno::EAGAIN)` - If the queue is full and `IPC_NOWAIT` was given
+/// * `Err(Errno::EIDRM)` - If the queue was removed while waiting
+/// * `Err(Errno::EINTR)` - If a signal arrived while waiting
+pub fn msgsnd(cageid: u64, msqid: i32, mtype: i32, text: &[u8], msgflg: i32) -> Result<(), Errno> {
+    if text.len() > MSGMAX || mtype < 1 {
+        return Err(Errno::EINVAL);
+    }
+    let queue = MSG_IDS.get(msqid)?;
+    let mut st = queue.state.lock();
+    loop {
+        if st.removed {
+            return Err(Errno::EIDRM);
+        }
+        if !ipcperms(&st.perm, PERM_WRITE) {
+            return Err(Errno::EACCES);
+        }
+        // Linux also counts messages against the byte limit, so empty messages can't pile up
+        if st.cbytes + text.len() <= st.qbytes && st.messages.len() < st.qbytes {
+            st.messages.push_back((mtype, text.to_vec()));
+            st.cbytes += text.len();
+            st.lspid = cageid as i32;
+            st.stime = timestamp();
+            drop(st);
+            queue.cond.notify_all();
+            return Ok(());
+        }
+        if msgflg & IPC_NOWAIT != 0 {
+            return Err(Errno::EAGAIN);
+        }
+        wait(cageid, &queue.cond, &mut st, None)?;
+    }
+}
+
+/// `msgrcv()`: takes the message `msgtyp` selects off the queue and copies its text into
+/// `text`, waiting for one unless `msgflg` has `IPC_NOWAIT`.
+///
+/// # Returns
+/// * `Ok((i32, usize))` - The type of the message and the number of bytes copied
+/// * `Err(Errno::EINVAL)` - If `msqid` does not exist
+/// * `Err(Errno::EACCES)` - If the caller may not read from the queue
+/// * `Err(Errno::EBIG)` - If the message is longer than `text` and `MSG_NOERROR` was not given
+/// * `Err(Errno::ENOMSG)` - If there is no such message and `IPC_NOWAIT` was given
+/// * `Err(Errno::EIDRM)` - If the queue was removed while waiting
+/// * `Err(Errno::EINTR)` - If a signal arrived while waiting
+pub fn msgrcv(
+    cageid: u64,
+    msqid: i32,
+    text: &mut [u8],
+    msgtyp: i32,
+    msgflg: i32,
+) -> Result<(i32, usize), Errno> {
+    let queue = MSG_IDS.get(msqid)?;
+    let mut st = queue.state.lock();
+    loop {
+        if st.removed {
+            return Err(Errno::EIDRM);
+        }
+        if !ipcperms(&st.perm, PERM_READ) {
+            return Err(Errno::EACCES);
+        }
+        if let Some(i) = st.find(msgtyp, msgflg) {
+            let len = st.messages[i].1.len();
+            if len > text.len() && msgflg & MSG_NOERROR == 0 {
+                return Err(Errno::EBIG);
+            }
+            let (mtype, msg) = st.messages.remove(i).unwrap();
+            let n = len.min(text.len());
+            text[..n].copy_from_slice(&msg[..n]);
+            st.cbytes -= len;
+            st.lrpid = cageid as i32;
+            st.rtime = timestamp();
+            drop(st);
+            queue.cond.notify_all();
+            return Ok((mtype, n));
+        }
+        if msgflg & IPC_NOWAIT != 0 {
+            return Err(Errno::ENOMSG);
+        }
+        wait(cageid, &queue.cond, &mut st, None)?;
+    }
+}
+
+/// `msgctl()`: `IPC_STAT`, `IPC_SET` and `IPC_RMID` on the queue `msqid`.
+///
+/// # Returns
+/// * `Ok(())` - On success
+/// * `Err(Errno::EINVAL)` - If `msqid` does not exist or `cmd` is not supported
+/// * `Err(Errno::EACCES)` - If an `IPC_STAT` caller may not read the queue
+/// * `Err(Errno::EPERM)` - If an `IPC_SET` or `IPC_RMID` caller does not own the queue, or a
+///   caller other than root raises `msg_qbytes` above `MSGMNB`
+/// * `Err(Errno::EFAULT)` - If `IPC_STAT` or `IPC_SET` was not given a buffer
+pub fn msgctl(msqid: i32, cmd: i32, buf: Option<&mut MsqidStruct>) -> Result<(), Errno> {
+    let queue = MSG_IDS.get(msqid)?;
+    let mut st = queue.state.lock();
+    if st.removed {
+        return Err(Errno::EIDRM);
+    }
+    match cmd {
+        IPC_STAT => {
+            if !ipcperms(&st.perm, PERM_READ) {
+                return Err(Errno::EACCES);
+            }
+            let buf = buf.ok_or(Errno::EFAULT)?;
+            *buf = MsqidStruct {
+                msg_perm: st.perm,
+                msg_stime: st.stime as u32,
+                msg_stime_high: (st.stime >> 32) as u32,
+                msg_rtime: st.rtime as u32,
+                msg_rtime_high: (st.rtime >> 32) as u32,
+                msg_ctime: st.ctime as u32,
+                msg_ctime_high: (st.ctime >> 32) as u32,
+                msg_cbytes: st.cbytes as u32,
+                msg_qnum: st.messages.len() as u32,
+                msg_qbytes: st.qbytes as u32,
+                msg_lspid: st.lspid,
+                msg_lrpid: st.lrpid,
+                ..Default::default()
+            };
+            Ok(())
+        }
+        IPC_SET => {
+            if !ipc_owner(&st.perm) {
+                return Err(Errno::EPERM);
+            }
+            let buf = buf.ok_or(Errno::EFAULT)?;
+            let qbytes = buf.msg_qbytes as usize;
+            if qbytes > MSGMNB && effective_ids().0 != 0 {
+                return Err(Errno::EPERM);
+            }
+            ipc_set(&mut st.perm, &buf.msg_perm);
+            st.qbytes = qbytes;
+            st.ctime = timestamp();
+            drop(st);
+            // A larger queue may let blocked senders in
+            queue.cond.notify_all();
+            Ok(())
+        }
+        IPC_RMID => {
+            if !ipc_owner(&st.perm) {
+                return Err(Errno::EPERM);
+            }
+            st.removed = true;
+            st.messages.clear();
+            let key = st.perm.__key;
+            drop(st);
+            MSG_IDS.remove(msqid, key);
+            queue.cond.notify_all();
+            Ok(())
+        }
+        _ => Err(Errno::EINVAL),
+    }
+}
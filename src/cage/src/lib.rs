@@ -1,7 +1,11 @@
 pub mod cage;
+pub mod futex;
+pub mod ipc;
 pub mod memory;
 pub mod signal;
 
 pub use cage::*;
+pub use futex::*;
+pub use ipc::*;
 pub use memory::*;
 pub use signal::*;
@@ -0,0 +1,137 @@
+//! Address space layout randomization of cages
+//!
+//! Without ASLR every run of a program gets the same addresses: `mmap` fills the address space
+//! down from its top, and the heap starts right after the module's initial memory. With ASLR
+//! enabled, three parts of a cage's layout are moved by a random number of pages:
+//! - the top of the mmap area, below which mappings without an address are placed, by up to
+//!   1/16 of the address space (256 MiB in a 32-bit cage),
+//! - the start of the heap, the initial program break, by up to 1/128 of it (32 MiB),
+//! - each thread stack (a `MAP_STACK` mapping), which is placed below a gap of up to 1/4096 of
+//!   it (1 MiB), reserved as `PROT_NONE` until the stack is unmapped.
+//!
+//! Each cage draws from its own generator, seeded from the global seed and its cage id, so runs
+//! given the same seed (`--aslr-seed`) lay out every cage the same way. A forked child keeps the
+//! layout of its parent, as on Linux, and an exec draws a new layout from the cage's generator.
+use crate::memory::vmmap::Vmmap;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The mmap top moves by up to `end_address >> MMAP_RND_SHIFT` pages
+const MMAP_RND_SHIFT: u32 = 4;
+/// The heap start moves by up to `end_address >> BRK_RND_SHIFT` pages
+const BRK_RND_SHIFT: u32 = 7;
+/// A thread stack is placed below a gap of up to `end_address >> STACK_RND_SHIFT` pages
+pub const STACK_RND_SHIFT: u32 = 12;
+
+/// ASLR settings chosen when lind-boot starts (see `configure`).
+#[derive(Debug, Clone, Default)]
+pub struct AslrConfig {
+    /// Randomize the layout of the cages
+    pub enabled: bool,
+    /// Seed of the layouts. A seed is drawn from the host if `None`.
+    pub seed: Option<u64>,
+}
+
+/// Seed of the layouts, or `None` with ASLR disabled.
+static SEED: OnceLock<Option<u64>> = OnceLock::new();
+
+/// Sets the ASLR settings. Must be called before the first cage is created; later calls have no
+/// effect. The seed is always printed to stderr, so that a crash seen under a random layout can
+/// be reproduced with `--aslr-seed`.
+pub fn configure(config: AslrConfig) {
+    let seed = config
+        .enabled
+        .then(|| config.seed.unwrap_or_else(host_seed));
+    if SEED.set(seed).is_ok() {
+        if let Some(seed) = seed {
+            eprintln!(
+                "lind: ASLR layouts are seeded with {} (--aslr-seed {})",
+                seed, seed
+            );
+        }
+    }
+}
+
+fn host_seed() -> u64 {
+    let mut seed = 0u64;
+    let ret = unsafe { libc::getrandom(&mut seed as *mut u64 as *mut libc::c_void, 8, 0) };
+    if ret != 8 {
+        // Fall back to the clock, which is good enough to vary the layout between runs
+        seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_nanos() as u64);
+    }
+    seed
+}
+
+/// Generator of the random offsets of one cage (splitmix64)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AslrRng(u64);
+
+impl AslrRng {
+    pub fn new(seed: u64) -> Self {
+        AslrRng(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A random number of pages, from 0 to `max`
+    pub fn pages(&mut self, max: u32) -> u32 {
+        (self.next() % (max as u64 + 1)) as u32
+    }
+}
+
+/// The generator of a cage, or `None` with ASLR disabled.
+pub fn cage_rng(cageid: u64) -> Option<AslrRng> {
+    let seed = (*SEED.get()?)?;
+    // Spread the cage ids, so that cages with neighbouring ids get unrelated offsets
+    let mut rng = AslrRng::new(seed ^ cageid.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    rng.next();
+    Some(rng)
+}
+
+/// Moves the mmap top of a new `vmmap` and, if `heap` is set, its heap start, when ASLR is
+/// enabled. The generator is kept in the `vmmap` for thread stacks and later execs.
+pub fn randomize_layout(cageid: u64, vmmap: &mut Vmmap, heap: bool) {
+    let Some(mut rng) = vmmap.aslr.or_else(|| cage_rng(cageid)) else {
+        return;
+    };
+    let end = vmmap.end_address;
+    vmmap.mmap_top = end - rng.pages(end >> MMAP_RND_SHIFT);
+    if heap {
+        vmmap.heap_start += rng.pages(end >> BRK_RND_SHIFT);
+    }
+    vmmap.aslr = Some(rng);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let (mut a, mut b) = (AslrRng::new(42), AslrRng::new(42));
+        for _ in 0..16 {
+            assert_eq!(a.pages(1 << 16), b.pages(1 << 16));
+        }
+        assert!((0..64).all(|_| a.pages(255) <= 255));
+    }
+
+    #[test]
+    fn test_randomize_layout_stays_in_range() {
+        let mut vmmap = Vmmap::new();
+        vmmap.aslr = Some(AslrRng::new(7));
+        vmmap.heap_start = 100;
+        randomize_layout(1, &mut vmmap, true);
+
+        let end = vmmap.end_address;
+        assert!(vmmap.mmap_top <= end && vmmap.mmap_top >= end - (end >> MMAP_RND_SHIFT));
+        assert!(vmmap.heap_start >= 100 && vmmap.heap_start <= 100 + (end >> BRK_RND_SHIFT));
+    }
+}
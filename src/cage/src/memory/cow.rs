@@ -0,0 +1,615 @@
+//! Copy-on-write fork of cage memory
+//!
+//! A cage's private anonymous memory starts out as ordinary anonymous host memory. When the cage
+//! forks, `fork_cow` writes the pages it has touched into a memfd snapshot, indexed by user
+//! address, and maps the snapshot `MAP_PRIVATE` over those regions in both parent and child, so
+//! the kernel copies a page only once either side writes it. Fork then costs in proportion to the
+//! pages touched since the cage last forked, not to the size of its memory.
+//!
+//! A later fork must fold the pages the cage wrote since into a snapshot. If no other cage still
+//! maps the cage's snapshot, they are written into it in place. Otherwise the other cages still
+//! read it, and its data is copied into a new snapshot first. `/proc/self/maps` tells which host
+//! mappings come from the old snapshot, and `/proc/self/pagemap` which of their pages were copied
+//! on write since.
+//!
+//! Writing the snapshot and remapping the parent's memory is only safe while no other thread of
+//! the parent can write it, so forks of multi-threaded cages copy memory eagerly instead (see
+//! `fork_vmmap`). And since a snapshot-backed page reads back the snapshot after `MADV_DONTNEED`,
+//! rather than zeros, `discard_pages` replaces such pages with fresh anonymous memory.
+use crate::cage::{get_cage, Cage};
+use crate::memory::Vmmap;
+use nodit::interval::ie;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::fs::FileExt;
+use std::sync::Arc;
+use sysdefs::constants::err_const::get_errno;
+use sysdefs::constants::fs_const::{
+    MAP_ANONYMOUS, MAP_FIXED, MAP_PRIVATE, MAP_SHARED, PAGESHIFT, PAGESIZE,
+};
+use sysdefs::lind_debug_panic;
+
+use super::MemoryBackingType;
+
+/// Name of snapshot memfds, as it appears in `/proc/self/maps`
+const SNAPSHOT_PATH: &str = "/memfd:lind-fork";
+
+// Flags of a /proc/self/pagemap entry
+const PM_PRESENT: u64 = 1 << 63;
+const PM_SWAPPED: u64 = 1 << 62;
+const PM_FILE: u64 = 1 << 61;
+
+/// Pagemap entries read at a time
+const PAGEMAP_BATCH: usize = 512;
+
+/// A memfd holding the private memory of one or more cages as it was when they forked, at the
+/// offsets of its user addresses. Cages only map it `MAP_PRIVATE`, so it does not change once
+/// several cages share it. It spans the whole cage address space, 64 GiB for a memory64 cage, as
+/// a mapped page past its end would raise `SIGBUS`; pages never written take no memory.
+#[derive(Debug)]
+pub struct MemorySnapshot {
+    fd: OwnedFd,
+    ino: u64,
+}
+
+impl MemorySnapshot {
+    fn new(size: u64) -> Option<MemorySnapshot> {
+        let fd = unsafe { libc::memfd_create(c"lind-fork".as_ptr(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return None;
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let mut st: libc::stat = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ftruncate(fd.as_raw_fd(), size as libc::off_t) } < 0
+            || unsafe { libc::fstat(fd.as_raw_fd(), &mut st) } < 0
+        {
+            return None;
+        }
+        Some(MemorySnapshot { fd, ino: st.st_ino })
+    }
+
+    /// Writes `len` bytes of host memory at `addr` to offset `offset`.
+    fn write(&self, addr: usize, len: usize, offset: u64) -> bool {
+        let mut done = 0;
+        while done < len {
+            let ret = unsafe {
+                libc::pwrite(
+                    self.fd.as_raw_fd(),
+                    (addr + done) as *const libc::c_void,
+                    len - done,
+                    (offset + done as u64) as libc::off_t,
+                )
+            };
+            if ret <= 0 {
+                return false;
+            }
+            done += ret as usize;
+        }
+        true
+    }
+
+    /// Frees `[offset, offset + len)`, so that it reads as zeros.
+    fn punch_hole(&self, offset: u64, len: usize) -> bool {
+        unsafe {
+            libc::fallocate(
+                self.fd.as_raw_fd(),
+                libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+                offset as libc::off_t,
+                len as libc::off_t,
+            ) == 0
+        }
+    }
+
+    /// Copies the data in `[offset, offset + len)` into `other`, skipping holes.
+    fn copy_to(&self, other: &MemorySnapshot, offset: u64, len: usize) -> bool {
+        let fd = self.fd.as_raw_fd();
+        let end = (offset + len as u64) as libc::off_t;
+        let mut pos = offset as libc::off_t;
+        while pos < end {
+            let data = unsafe { libc::lseek(fd, pos, libc::SEEK_DATA) };
+            if data < 0 {
+                // ENXIO: no data past `pos`
+                return get_errno() == libc::ENXIO;
+            }
+            if data >= end {
+                break;
+            }
+            let hole = unsafe { libc::lseek(fd, data, libc::SEEK_HOLE) }.min(end);
+            if hole < 0 {
+                return false;
+            }
+            let (mut from, mut to) = (data, data);
+            while from < hole {
+                let ret = unsafe {
+                    libc::copy_file_range(
+                        fd,
+                        &mut from,
+                        other.fd.as_raw_fd(),
+                        &mut to,
+                        (hole - from) as usize,
+                        0,
+                    )
+                };
+                if ret <= 0 {
+                    return false;
+                }
+            }
+            pos = hole;
+        }
+        true
+    }
+}
+
+/// What a host mapping in the parent's memory is backed by
+#[derive(Clone, Copy, PartialEq)]
+enum HostBacking {
+    /// The cage's current snapshot, at the offset of the mapping's user address
+    Snapshot,
+    Anonymous,
+    /// The cage's current snapshot at another offset, as `mremap` may move memory
+    MovedSnapshot,
+    Other,
+}
+
+/// The host mappings overlapping `[start, end)`, clipped to it, from `/proc/self/maps`.
+fn host_mappings(
+    start: usize,
+    end: usize,
+    base: usize,
+    snapshot: Option<&MemorySnapshot>,
+) -> Option<Vec<(usize, usize, HostBacking)>> {
+    let maps = BufReader::new(File::open("/proc/self/maps").ok()?);
+    let mut mappings = Vec::new();
+    for line in maps.lines() {
+        let line = line.ok()?;
+        // start-end perms offset dev inode [path]
+        let mut fields = line.split_whitespace();
+        let (lo, hi) = fields.next()?.split_once('-')?;
+        let lo = usize::from_str_radix(lo, 16).ok()?;
+        let hi = usize::from_str_radix(hi, 16).ok()?;
+        if hi <= start || lo >= end {
+            continue;
+        }
+        let offset = u64::from_str_radix(fields.nth(1)?, 16).ok()?;
+        let inode: u64 = fields.nth(1)?.parse().ok()?;
+        let path = fields.next().unwrap_or("");
+        let backing = match snapshot {
+            _ if inode == 0 => HostBacking::Anonymous,
+            Some(s) if inode == s.ino && path.starts_with(SNAPSHOT_PATH) => {
+                if lo.checked_sub(base) == Some(offset as usize) {
+                    HostBacking::Snapshot
+                } else {
+                    HostBacking::MovedSnapshot
+                }
+            }
+            _ => HostBacking::Other,
+        };
+        mappings.push((lo.max(start), hi.min(end), backing));
+    }
+    Some(mappings)
+}
+
+/// Writes the pages of host range `[start, end)` that `dirty` selects by their pagemap entry to
+/// `snapshot`, at the offsets of their user addresses.
+fn write_pages(
+    pagemap: &File,
+    snapshot: &MemorySnapshot,
+    start: usize,
+    end: usize,
+    base: usize,
+    dirty: impl Fn(u64) -> bool,
+) -> bool {
+    let pagesize = PAGESIZE as usize;
+    let mut entries = [0u8; PAGEMAP_BATCH * 8];
+    let mut run_start = None;
+    let mut page = start;
+    while page < end {
+        let count = ((end - page) / pagesize).min(PAGEMAP_BATCH);
+        let bytes = &mut entries[..count * 8];
+        let pos = (page / pagesize * 8) as u64;
+        if pagemap.read_exact_at(bytes, pos).is_err() {
+            return false;
+        }
+        for entry in bytes.chunks_exact(8) {
+            let entry = u64::from_ne_bytes(entry.try_into().unwrap());
+            match (dirty(entry), run_start) {
+                (true, None) => run_start = Some(page),
+                (false, Some(run)) => {
+                    if !snapshot.write(run, page - run, (run - base) as u64) {
+                        return false;
+                    }
+                    run_start = None;
+                }
+                _ => {}
+            }
+            page += pagesize;
+        }
+    }
+    match run_start {
+        Some(run) => snapshot.write(run, end - run, (run - base) as u64),
+        None => true,
+    }
+}
+
+/// Forks the private anonymous `regions` (user address, length and protection) of `parent` into
+/// `child` copy-on-write, as described in the module documentation.
+///
+/// # Returns
+/// * `true` - If both cages now map the regions from a shared snapshot
+/// * `false` - If the snapshot could not be written, in which case neither cage's memory changed
+///   and the caller must copy the regions itself
+pub fn fork_cow(
+    parent: &Cage,
+    child: &Cage,
+    parent_vmmap: &Vmmap,
+    child_vmmap: &Vmmap,
+    regions: &[(u64, usize, i32)],
+) -> bool {
+    // Other threads could write the parent's memory between writing a page to the snapshot and
+    // remapping it
+    if parent.epoch_handler.len() > 1 {
+        return false;
+    }
+    let Some(&(last_addr, last_len, _)) = regions.last() else {
+        return true;
+    };
+    let base = parent_vmmap.user_to_sys(0);
+    let start = parent_vmmap.user_to_sys(regions[0].0);
+    let end = parent_vmmap.user_to_sys(last_addr) + last_len;
+
+    let mut current = parent.memory_snapshot.lock();
+    let Some(mappings) = host_mappings(start, end, base, current.as_deref()) else {
+        return false;
+    };
+    // Writing the parent's snapshot in place is only safe if no other cage reads it, and if it
+    // is mapped only at its own offsets, so that writing one page cannot change another
+    let in_place = current.as_ref().is_some_and(|s| {
+        Arc::strong_count(s) == 1
+            && mappings
+                .iter()
+                .all(|&(_, _, backing)| backing != HostBacking::MovedSnapshot)
+    });
+    let snapshot = if in_place {
+        current.clone().unwrap()
+    } else {
+        match MemorySnapshot::new(parent_vmmap.address_space_size()) {
+            Some(s) => Arc::new(s),
+            None => return false,
+        }
+    };
+    let Ok(pagemap) = File::open("/proc/self/pagemap") else {
+        return false;
+    };
+
+    for &(addr, len, _) in regions {
+        let region_start = parent_vmmap.user_to_sys(addr);
+        let region_end = region_start + len;
+        for &(lo, hi, backing) in &mappings {
+            let (lo, hi) = (lo.max(region_start), hi.min(region_end));
+            if lo >= hi {
+                continue;
+            }
+            let offset = (lo - base) as u64;
+            let written = match backing {
+                // Pages the parent has not written since the last fork are in the old snapshot
+                HostBacking::Snapshot => {
+                    (in_place
+                        || current
+                            .as_ref()
+                            .unwrap()
+                            .copy_to(&snapshot, offset, hi - lo))
+                        && write_pages(&pagemap, &snapshot, lo, hi, base, |e| {
+                            e & PM_SWAPPED != 0 || e & (PM_PRESENT | PM_FILE) == PM_PRESENT
+                        })
+                }
+                // Anonymous pages never touched read as zeros
+                HostBacking::Anonymous => {
+                    (!in_place || snapshot.punch_hole(offset, hi - lo))
+                        && write_pages(&pagemap, &snapshot, lo, hi, base, |e| {
+                            e & (PM_PRESENT | PM_SWAPPED) != 0
+                        })
+                }
+                HostBacking::MovedSnapshot | HostBacking::Other => {
+                    (!in_place || snapshot.punch_hole(offset, hi - lo))
+                        && snapshot.write(lo, hi - lo, offset)
+                }
+            };
+            if !written {
+                return false;
+            }
+        }
+    }
+
+    // The snapshot now holds the regions, so both cages can map it in their place
+    for &(addr, len, prot) in regions {
+        for sys in [
+            child_vmmap.user_to_sys(addr),
+            parent_vmmap.user_to_sys(addr),
+        ] {
+            let ret = unsafe {
+                libc::mmap(
+                    sys as *mut libc::c_void,
+                    len,
+                    prot,
+                    (MAP_PRIVATE | MAP_FIXED) as i32,
+                    snapshot.fd.as_raw_fd(),
+                    addr as libc::off_t,
+                )
+            };
+            if ret == libc::MAP_FAILED {
+                lind_debug_panic!(
+                    "fork_cow: mmap of snapshot failed with errno {} (addr=0x{:x}, len={})",
+                    get_errno(),
+                    sys,
+                    len,
+                );
+            }
+        }
+    }
+    *child.memory_snapshot.lock() = Some(snapshot.clone());
+    *current = Some(snapshot);
+    true
+}
+
+/// Discards the pages of host range `[addr, addr + len)` of cage `cageid` for `MADV_DONTNEED` or
+/// `MADV_FREE`.
+///
+/// Private anonymous pages of a cage that forked copy-on-write may come from a snapshot, where
+/// the host would give back the snapshot's contents, or refuse `MADV_FREE`. They are replaced by
+/// fresh anonymous memory instead, which reads as zeros as Linux gives for anonymous memory.
+/// Other pages are passed to the host's `madvise`.
+///
+/// # Returns
+/// * `Ok(())` - On success
+/// * `Err(errno)` - The host error of `mmap` or `madvise`
+pub fn discard_pages(cageid: u64, addr: usize, len: usize, advice: i32) -> Result<(), i32> {
+    let cage = get_cage(cageid).unwrap();
+    let host_madvise = |addr: usize, len: usize| {
+        if unsafe { libc::madvise(addr as *mut libc::c_void, len, advice) } < 0 {
+            return Err(get_errno());
+        }
+        Ok(())
+    };
+    if cage.memory_snapshot.lock().is_none() {
+        return host_madvise(addr, len);
+    }
+
+    let vmmap = cage.vmmap.read();
+    let first = (vmmap.sys_to_user(addr) >> PAGESHIFT) as u32;
+    let last = first + (len >> PAGESHIFT) as u32;
+    for (_, entry) in vmmap.entries.overlapping(ie(first, last)) {
+        let lo = vmmap.user_to_sys((entry.page_num.max(first) as u64) << PAGESHIFT);
+        let hi = vmmap.user_to_sys(((entry.page_num + entry.npages).min(last) as u64) << PAGESHIFT);
+        if entry.flags & (MAP_SHARED as i32) != 0 || entry.backing != MemoryBackingType::Anonymous {
+            host_madvise(lo, hi - lo)?;
+            continue;
+        }
+        let ret = unsafe {
+            libc::mmap(
+                lo as *mut libc::c_void,
+                hi - lo,
+                entry.prot,
+                (MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED) as i32,
+                -1,
+                0,
+            )
+        };
+        if ret == libc::MAP_FAILED {
+            return Err(get_errno());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cage::{AtomicBool, AtomicI32, AtomicU32, AtomicU64, Mutex, PathBuf, RwLock};
+    use crate::memory::quota::MemQuota;
+    use crate::timer::IntervalTimer;
+    use dashmap::DashMap;
+    use sysdefs::constants::fs_const::{PROT_READ, PROT_WRITE};
+
+    const NPAGES: usize = 16;
+
+    /// A cage whose user memory is `NPAGES` pages of fresh anonymous memory
+    fn test_cage(cageid: u64) -> Cage {
+        let base = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                NPAGES * PAGESIZE as usize,
+                (PROT_READ | PROT_WRITE) as i32,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(base, libc::MAP_FAILED);
+        cage_at(cageid, base as usize, false)
+    }
+
+    /// User address of the mapping of `test_cage64`, above what 32 bits address
+    const HIGH: usize = 5 << 30;
+
+    /// A memory64 cage whose user memory is `NPAGES` pages of fresh anonymous memory at `HIGH`
+    fn test_cage64(cageid: u64) -> Cage {
+        let len = HIGH + NPAGES * PAGESIZE as usize;
+        let base = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_NONE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_NORESERVE,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(base, libc::MAP_FAILED);
+        let high = (base as usize + HIGH) as *mut libc::c_void;
+        let ret =
+            unsafe { libc::mprotect(high, NPAGES * PAGESIZE as usize, PROT_READ | PROT_WRITE) };
+        assert_eq!(ret, 0);
+        cage_at(cageid, base as usize, true)
+    }
+
+    fn cage_at(cageid: u64, base: usize, memory64: bool) -> Cage {
+        let mut vmmap = Vmmap::new();
+        vmmap.set_base_address(base);
+        vmmap.set_memory64(memory64);
+        Cage {
+            cageid,
+            parent: 1,
+            cwd: RwLock::new(Arc::new(PathBuf::from("/"))),
+            umask: AtomicU32::new(0o022),
+            netns: AtomicU64::new(0),
+            utsns: AtomicU64::new(0),
+            cpu_affinity: RwLock::new(vec![1]),
+            nice: AtomicI32::new(0),
+            rev_shm: Mutex::new(Vec::new()),
+            signalhandler: DashMap::new(),
+            sigset: AtomicU64::new(0),
+            pending_signals: RwLock::new(vec![]),
+            epoch_handler: DashMap::new(),
+            os_tid_map: DashMap::new(),
+            robust_list: DashMap::new(),
+            main_threadid: RwLock::new(0),
+            interval_timer: IntervalTimer::new(cageid),
+            zombies: RwLock::new(vec![]),
+            child_num: AtomicU64::new(0),
+            vmmap: RwLock::new(vmmap),
+            memory_snapshot: Mutex::new(None),
+            mem_quota: MemQuota::new(cageid),
+            final_exit_status: RwLock::new(None),
+            exit_group_initiated: AtomicBool::new(false),
+            is_dead: AtomicBool::new(false),
+            grate_inflight: AtomicU64::new(0),
+        }
+    }
+
+    fn page(cage: &Cage, n: usize) -> *mut u8 {
+        (cage.vmmap.read().user_to_sys(0) + n * PAGESIZE as usize) as *mut u8
+    }
+
+    fn fork(parent: &Cage, child: &Cage) -> bool {
+        let regions = [(
+            0,
+            NPAGES * PAGESIZE as usize,
+            (PROT_READ | PROT_WRITE) as i32,
+        )];
+        fork_cow(
+            parent,
+            child,
+            &parent.vmmap.read(),
+            &child.vmmap.read(),
+            &regions,
+        )
+    }
+
+    #[test]
+    fn test_fork_cow_isolates_parent_and_child() {
+        let parent = test_cage(101);
+        let child = test_cage(102);
+        unsafe {
+            *page(&parent, 0) = 1;
+            *page(&parent, 5) = 5;
+        }
+        assert!(fork(&parent, &child));
+        unsafe {
+            assert_eq!(*page(&child, 0), 1);
+            assert_eq!(*page(&child, 5), 5);
+            assert_eq!(*page(&child, 9), 0);
+            *page(&parent, 0) = 2;
+            *page(&child, 5) = 6;
+            assert_eq!(*page(&child, 0), 1);
+            assert_eq!(*page(&parent, 5), 5);
+        }
+        let snapshot = parent.memory_snapshot.lock().clone().unwrap();
+        assert!(Arc::ptr_eq(
+            &snapshot,
+            child.memory_snapshot.lock().as_ref().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_fork_cow_memory64_above_4gib() {
+        let parent = test_cage64(107);
+        let child = test_cage64(108);
+        let high_page = |cage: &Cage, n: usize| {
+            (cage.vmmap.read().user_to_sys(HIGH as u64) + n * PAGESIZE as usize) as *mut u8
+        };
+        unsafe {
+            *high_page(&parent, 0) = 1;
+            *high_page(&parent, 5) = 5;
+        }
+        let regions = [(
+            HIGH as u64,
+            NPAGES * PAGESIZE as usize,
+            PROT_READ | PROT_WRITE,
+        )];
+        assert!(fork_cow(
+            &parent,
+            &child,
+            &parent.vmmap.read(),
+            &child.vmmap.read(),
+            &regions,
+        ));
+        // Pages past the last one written are within the snapshot too, and read as zeros
+        unsafe {
+            assert_eq!(*high_page(&child, 0), 1);
+            assert_eq!(*high_page(&child, 5), 5);
+            assert_eq!(*high_page(&child, NPAGES - 1), 0);
+            assert_eq!(*high_page(&parent, NPAGES - 1), 0);
+            *high_page(&parent, NPAGES - 1) = 7;
+            *high_page(&child, 0) = 3;
+            assert_eq!(*high_page(&child, NPAGES - 1), 0);
+            assert_eq!(*high_page(&parent, 0), 1);
+        }
+    }
+
+    #[test]
+    fn test_fork_cow_refolds_written_pages() {
+        let parent = test_cage(103);
+        let child = test_cage(104);
+        unsafe { *page(&parent, 1) = 1 };
+        assert!(fork(&parent, &child));
+        let first = parent.memory_snapshot.lock().clone().unwrap();
+
+        // The child still maps the snapshot, so the next fork needs a new one
+        unsafe { *page(&parent, 2) = 2 };
+        let second_child = test_cage(105);
+        assert!(fork(&parent, &second_child));
+        let second = parent.memory_snapshot.lock().clone().unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+        unsafe {
+            assert_eq!(*page(&second_child, 1), 1);
+            assert_eq!(*page(&second_child, 2), 2);
+            assert_eq!(*page(&child, 2), 0);
+        }
+
+        // Once the children exit, the parent's snapshot is written in place
+        drop((first, second));
+        for exited in [child, second_child] {
+            exited.memory_snapshot.lock().take();
+            unsafe {
+                libc::munmap(
+                    page(&exited, 0) as *mut libc::c_void,
+                    NPAGES * PAGESIZE as usize,
+                )
+            };
+        }
+        unsafe { *page(&parent, 3) = 3 };
+        let before = Arc::as_ptr(parent.memory_snapshot.lock().as_ref().unwrap());
+        let third_child = test_cage(106);
+        assert!(fork(&parent, &third_child));
+        assert_eq!(
+            Arc::as_ptr(parent.memory_snapshot.lock().as_ref().unwrap()),
+            before
+        );
+        unsafe {
+            assert_eq!(*page(&third_child, 1), 1);
+            assert_eq!(*page(&third_child, 2), 2);
+            assert_eq!(*page(&third_child, 3), 3);
+        }
+    }
+}
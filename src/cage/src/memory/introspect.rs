@@ -0,0 +1,429 @@
+//! Memory introspection of cages
+//!
+//! The quotas count the pages a cage maps, not the pages it actually uses: a 4 GiB reservation
+//! and a touched heap look the same. This module reports, for each cage, every region of its
+//! `vmmap` in the manner of `/proc/<pid>/smaps`: address range, protection, backing (anonymous,
+//! file or System V shared memory) and the pages resident in host memory, which `mincore` reads
+//! from the host mapping of the cage's linear memory. A cage's RSS is the sum over its regions,
+//! so the resident pages of a shared mapping count in every cage that maps it, as on Linux.
+//!
+//! Reports are written on demand to the output chosen by `configure` (`--memory-report` in
+//! lind-boot): for all cages, largest RSS first, each time lind-boot receives SIGUSR1, and for
+//! each cage when it exits if asked to. What a cage still has mapped when it exits, together with
+//! the most memory it ever committed, is where to look for a leaking guest.
+use crate::cage::{Cage, CAGE_MAP};
+use crate::memory::vmmap::{prot_string, MemoryBackingType, Vmmap};
+use parking_lot::Mutex;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::OnceLock;
+use sysdefs::constants::fs_const::{MAP_SHARED, PAGESHIFT};
+
+/// Most pages `mincore` is asked about at once, which bounds its result vector to 64 KiB
+const MINCORE_CHUNK: u64 = 1 << 16;
+
+/// Memory report settings chosen when lind-boot starts (see `configure`).
+#[derive(Debug, Clone, Default)]
+pub struct ReportConfig {
+    /// File the reports are appended to, or `-` for stderr. Nothing is reported if `None`.
+    pub path: Option<PathBuf>,
+    /// Also report each cage when it exits
+    pub on_exit: bool,
+}
+
+/// Where reports go, if reporting is enabled
+static OUTPUT: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+
+/// Whether each cage is reported when it exits
+static ON_EXIT: AtomicBool = AtomicBool::new(false);
+
+/// Write end of the pipe the SIGUSR1 handler wakes the report thread through
+static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+/// Opens the report output and starts reporting on SIGUSR1. Must be called before lind-boot
+/// chroots into lindfs, as the path is a host path; later calls have no effect.
+pub fn configure(config: ReportConfig) -> io::Result<()> {
+    let Some(path) = config.path else {
+        return Ok(());
+    };
+    let output: Box<dyn Write + Send> = if path.as_os_str() == "-" {
+        Box::new(io::stderr())
+    } else {
+        Box::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .mode(0o644)
+                .open(&path)?,
+        )
+    };
+    if OUTPUT.set(Mutex::new(output)).is_err() {
+        return Ok(());
+    }
+    ON_EXIT.store(config.on_exit, Ordering::Release);
+    start_signal_reports()
+}
+
+/// One region of a cage's `vmmap`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionReport {
+    /// User address of the first page
+    pub start: u64,
+    pub npages: u64,
+    pub prot: i32,
+    pub maxprot: i32,
+    pub flags: i32,
+    pub backing: MemoryBackingType,
+    pub file_offset: i64,
+    /// Pages resident in host memory, or `None` if the host mapping could not be queried
+    pub resident_pages: Option<u64>,
+}
+
+impl RegionReport {
+    fn is_shared(&self) -> bool {
+        self.flags & MAP_SHARED as i32 != 0
+            || matches!(self.backing, MemoryBackingType::SharedMemory(_))
+    }
+}
+
+/// The memory of one cage: its regions and what the quotas count for it.
+#[derive(Debug, Clone)]
+pub struct CageMemoryReport {
+    pub cageid: u64,
+    pub parent: u64,
+    pub regions: Vec<RegionReport>,
+    /// Pages the cage commits (see `quota::is_committed`)
+    pub committed_pages: u64,
+    /// Most pages the cage has committed at once
+    pub peak_committed_pages: u64,
+}
+
+impl CageMemoryReport {
+    /// Pages in the cage's `vmmap`.
+    pub fn mapped_pages(&self) -> u64 {
+        self.regions.iter().map(|region| region.npages).sum()
+    }
+
+    /// Pages of the cage resident in host memory, the RSS of the cage.
+    pub fn resident_pages(&self) -> u64 {
+        self.regions
+            .iter()
+            .filter_map(|region| region.resident_pages)
+            .sum()
+    }
+
+    /// Resident pages of anonymous, file-backed and System V shared memory regions.
+    pub fn resident_by_backing(&self) -> (u64, u64, u64) {
+        let (mut anon, mut file, mut shm) = (0, 0, 0);
+        for region in &self.regions {
+            let resident = region.resident_pages.unwrap_or(0);
+            match region.backing {
+                MemoryBackingType::FileDescriptor(_) => file += resident,
+                MemoryBackingType::SharedMemory(_) => shm += resident,
+                MemoryBackingType::Anonymous | MemoryBackingType::None => anon += resident,
+            }
+        }
+        (anon, file, shm)
+    }
+}
+
+fn kib(pages: u64) -> u64 {
+    pages << (PAGESHIFT - 10)
+}
+
+impl fmt::Display for CageMemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (anon, file, shm) = self.resident_by_backing();
+        writeln!(
+            f,
+            "cage {} (parent {}): Size {} kB, Rss {} kB (anon {} kB, file {} kB, shm {} kB), \
+             Committed {} kB, Peak committed {} kB",
+            self.cageid,
+            self.parent,
+            kib(self.mapped_pages()),
+            kib(self.resident_pages()),
+            kib(anon),
+            kib(file),
+            kib(shm),
+            kib(self.committed_pages),
+            kib(self.peak_committed_pages)
+        )?;
+        for region in &self.regions {
+            writeln!(
+                f,
+                "{:08x}-{:08x} {}{} {:08x} {}",
+                region.start,
+                region.start + (region.npages << PAGESHIFT),
+                prot_string(region.prot),
+                if region.is_shared() { 's' } else { 'p' },
+                region.file_offset,
+                region.backing
+            )?;
+            writeln!(f, "Size:           {:>8} kB", kib(region.npages))?;
+            match region.resident_pages {
+                Some(resident) => writeln!(f, "Rss:            {:>8} kB", kib(resident))?,
+                None => writeln!(f, "Rss:             unknown")?,
+            }
+            writeln!(f, "MaxProt:        {:>8}", prot_string(region.maxprot))?;
+        }
+        Ok(())
+    }
+}
+
+/// Pages of `[addr, addr + npages pages)` in the host address space that are resident in
+/// memory, or `None` if part of the range is not mapped. Lind pages are host pages.
+fn host_resident_pages(addr: usize, npages: u64) -> Option<u64> {
+    let mut vec = vec![0u8; npages.min(MINCORE_CHUNK) as usize];
+    let mut resident = 0;
+    let mut done = 0;
+    while done < npages {
+        let n = (npages - done).min(MINCORE_CHUNK);
+        let chunk = addr + (done << PAGESHIFT) as usize;
+        let ret = unsafe {
+            libc::mincore(
+                chunk as *mut libc::c_void,
+                (n << PAGESHIFT) as usize,
+                vec.as_mut_ptr(),
+            )
+        };
+        if ret < 0 {
+            return None;
+        }
+        resident += vec[..n as usize]
+            .iter()
+            .filter(|page| **page & 1 != 0)
+            .count() as u64;
+        done += n;
+    }
+    Some(resident)
+}
+
+/// The regions of a `vmmap`, in address order. Resident pages are only known once the cage's
+/// linear memory is in place (`base_address` is set).
+pub fn vmmap_regions(vmmap: &Vmmap) -> Vec<RegionReport> {
+    let mut regions = Vec::new();
+    vmmap.visit(|interval, entry| {
+        let start = (interval.start() as u64) << PAGESHIFT;
+        let npages = interval.end() as u64 + 1 - interval.start() as u64;
+        let resident_pages = vmmap
+            .base_address
+            .and_then(|_| host_resident_pages(vmmap.user_to_sys(start), npages));
+        regions.push(RegionReport {
+            start,
+            npages,
+            prot: entry.prot,
+            maxprot: entry.maxprot,
+            flags: entry.flags,
+            backing: entry.backing,
+            file_offset: entry.file_offset,
+            resident_pages,
+        });
+    });
+    regions
+}
+
+/// The memory report of `cage`.
+pub fn cage_report(cage: &Cage) -> CageMemoryReport {
+    let regions = vmmap_regions(&cage.vmmap.read());
+    CageMemoryReport {
+        cageid: cage.cageid,
+        parent: cage.parent,
+        regions,
+        committed_pages: cage.mem_quota.committed_pages(),
+        peak_committed_pages: cage.mem_quota.peak_committed_pages(),
+    }
+}
+
+/// The memory reports of all cages, largest RSS first.
+pub fn all_cage_reports() -> Vec<CageMemoryReport> {
+    let mut reports: Vec<CageMemoryReport> = CAGE_MAP
+        .iter()
+        .filter_map(|slot| slot.load_full())
+        .map(|cage| cage_report(&cage))
+        .collect();
+    reports.sort_by_key(|report| std::cmp::Reverse(report.resident_pages()));
+    reports
+}
+
+fn write_reports(title: &str, reports: &[CageMemoryReport]) {
+    let Some(output) = OUTPUT.get() else {
+        return;
+    };
+    let resident: u64 = reports.iter().map(|report| report.resident_pages()).sum();
+    let committed: u64 = reports.iter().map(|report| report.committed_pages).sum();
+    let mut output = output.lock();
+    let _ = writeln!(
+        output,
+        "=== lind memory report: {}, {} cages, Rss {} kB, Committed {} kB ===",
+        title,
+        reports.len(),
+        kib(resident),
+        kib(committed)
+    );
+    for report in reports {
+        let _ = write!(output, "{}", report);
+    }
+    let _ = output.flush();
+}
+
+/// Writes the report of every cage to the configured output, if reporting is enabled.
+pub fn report_all(title: &str) {
+    if OUTPUT.get().is_some() {
+        write_reports(title, &all_cage_reports());
+    }
+}
+
+/// Writes the report of `cage`, which is exiting, if reports at exit are enabled. Called by
+/// `cage_finalize` before the cage is removed.
+pub fn report_exit(cage: &Cage) {
+    if ON_EXIT.load(Ordering::Acquire) {
+        write_reports(
+            &format!("cage {} exited", cage.cageid),
+            &[cage_report(cage)],
+        );
+    }
+}
+
+extern "C" fn report_signal_handler(_signo: i32) {
+    // Only async-signal-safe work here: wake the report thread
+    let fd = SIGNAL_PIPE.load(Ordering::Relaxed);
+    let byte = 0u8;
+    unsafe {
+        libc::write(fd, &byte as *const u8 as *const libc::c_void, 1);
+    }
+}
+
+/// Starts the thread that writes the reports of all cages each time lind-boot gets SIGUSR1.
+fn start_signal_reports() -> io::Result<()> {
+    let mut fds = [0i32; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let [read_fd, write_fd] = fds;
+    // A burst of signals may fill the pipe, which must not block the handler
+    unsafe { libc::fcntl(write_fd, libc::F_SETFL, libc::O_NONBLOCK) };
+    SIGNAL_PIPE.store(write_fd, Ordering::Relaxed);
+
+    std::thread::Builder::new()
+        .name("lind-memreport".to_string())
+        .spawn(move || {
+            let mut buf = [0u8; 64];
+            loop {
+                let n = unsafe {
+                    libc::read(read_fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len())
+                };
+                if n > 0 {
+                    // Signals that arrived while reporting are served by a single report
+                    report_all("SIGUSR1");
+                } else if n == 0 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted
+                {
+                    break;
+                }
+            }
+        })?;
+
+    unsafe {
+        let mut sa: libc::sigaction = std::mem::zeroed();
+        sa.sa_sigaction = report_signal_handler as extern "C" fn(i32) as usize;
+        sa.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut sa.sa_mask);
+        if libc::sigaction(libc::SIGUSR1, &sa, std::ptr::null_mut()) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VmmapOps;
+    use sysdefs::constants::fs_const::{
+        MAP_ANONYMOUS, MAP_PRIVATE, PAGESIZE, PROT_READ, PROT_WRITE,
+    };
+
+    #[test]
+    fn test_vmmap_regions_count_resident_pages() {
+        let len = 8 * PAGESIZE as usize;
+        let base = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(base, libc::MAP_FAILED);
+        // Touch pages 1 and 2 of the first region and page 6 of the second
+        for page in [1, 2, 6] {
+            unsafe { *(base as *mut u8).add(page * PAGESIZE as usize) = 1 };
+        }
+
+        let mut vmmap = Vmmap::new();
+        vmmap.set_base_address(base as usize);
+        for (page_num, npages, backing) in [
+            (0, 4, MemoryBackingType::Anonymous),
+            (4, 4, MemoryBackingType::SharedMemory(7)),
+        ] {
+            let _ = vmmap.add_entry_with_overwrite(
+                page_num,
+                npages,
+                PROT_READ | PROT_WRITE,
+                PROT_READ | PROT_WRITE,
+                (MAP_PRIVATE | MAP_ANONYMOUS) as i32,
+                backing,
+                0,
+                0,
+                1,
+            );
+        }
+
+        let regions = vmmap_regions(&vmmap);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].start, 0);
+        assert_eq!(regions[0].resident_pages, Some(2));
+        assert_eq!(regions[1].start, 4 << PAGESHIFT);
+        assert_eq!(regions[1].resident_pages, Some(1));
+
+        let report = CageMemoryReport {
+            cageid: 1,
+            parent: 1,
+            regions,
+            committed_pages: 4,
+            peak_committed_pages: 6,
+        };
+        assert_eq!(report.mapped_pages(), 8);
+        assert_eq!(report.resident_pages(), 3);
+        assert_eq!(report.resident_by_backing(), (2, 0, 1));
+        let text = report.to_string();
+        assert!(text.starts_with("cage 1 (parent 1): Size 32 kB, Rss 12 kB"));
+        assert!(text.contains("00004000-00008000 rw-s 00000000 shm 7\n"));
+
+        unsafe { libc::munmap(base, len) };
+    }
+
+    #[test]
+    fn test_vmmap_regions_without_memory() {
+        let mut vmmap = Vmmap::new();
+        let _ = vmmap.add_entry_with_overwrite(
+            16,
+            2,
+            PROT_READ,
+            PROT_READ,
+            (MAP_PRIVATE | MAP_ANONYMOUS) as i32,
+            MemoryBackingType::Anonymous,
+            0,
+            0,
+            1,
+        );
+        let regions = vmmap_regions(&vmmap);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].resident_pages, None);
+    }
+}
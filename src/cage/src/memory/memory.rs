@@ -4,10 +4,11 @@
 //! initializing vmmap, helper functions for handling vmmap during a fork syscall, and
 //! address translation and validation related to vmmap
 use crate::cage::{get_cage, Cage};
-use crate::memory::VmmapOps;
+use crate::memory::{aslr, fork_cow, MemoryBackingType, VmmapOps};
 use sysdefs::constants::err_const::{get_errno, Errno};
 use sysdefs::constants::fs_const::{
-    MAP_SHARED, MREMAP_FIXED, MREMAP_MAYMOVE, PAGESHIFT, PAGESIZE, PROT_NONE, PROT_READ, PROT_WRITE,
+    MADV_DONTNEED, MAP_ANONYMOUS, MAP_FIXED, MAP_NORESERVE, MAP_PRIVATE, MAP_SHARED, MREMAP_FIXED,
+    MREMAP_MAYMOVE, PAGESHIFT, PAGESIZE, PROT_NONE, PROT_READ, PROT_WRITE,
 };
 use sysdefs::{lind_debug_panic, lind_log};
 
@@ -71,11 +72,17 @@ pub fn is_mmap_error(ret: usize) -> bool {
 /// to either `vmmap` do not affect the other.
 ///
 /// The copying behavior varies based on the type of memory region:
-/// 1. **PROT_NONE regions**:
+/// 1. **Private PROT_NONE regions**:
 ///    - No action is taken, as memory regions are already configured with `PROT_NONE` by default.
-/// 2. **Shared memory regions**:
+/// 2. **Shared memory regions** (anonymous, System V, or file-backed `MAP_SHARED`):
 ///    - The function uses the `mremap` syscall to replicate shared memory efficiently. Refer to `man 2 mremap` for details.
-/// 3. **Private memory regions**:
+///      This is done regardless of the current protection, so a shared region that is `PROT_NONE` at fork time
+///      still refers to the same pages in both cages once either side makes it accessible again.
+/// 3. **Private anonymous regions**:
+///    - The function maps them copy-on-write into both cages from a memfd snapshot (see `memory::cow`),
+///      so that only the pages touched since the last fork are copied. If that is not possible, e.g. because
+///      the parent has other threads running, they are copied like the regions below.
+/// 4. **Other private memory regions**:
 ///    - The function uses `process_vm_writev` to copy memory contents from the parent into
 ///      the child's address space.
 ///
@@ -88,73 +95,72 @@ pub fn fork_vmmap(parent_cageid: u64, child_cageid: u64) {
     let child_cage = get_cage(child_cageid).unwrap();
     let parent_vmmap = parent_cage.vmmap.read();
     let child_vmmap = child_cage.vmmap.read();
+    // private anonymous regions to fork copy-on-write, as (user address, length, prot)
+    let mut cow_regions = Vec::new();
 
     // iterate through each vmmap entry
     for (_interval, entry) in parent_vmmap.entries.iter() {
-        // PROT_NONE regions are already configured with PROT_NONE by default,
+        let is_shared = entry.flags & (MAP_SHARED as i32) > 0;
+
+        // Private PROT_NONE regions are already configured with PROT_NONE by default,
         // and reading from a host PROT_NONE page would cause a SIGSEGV
-        if entry.prot == PROT_NONE {
+        if entry.prot == PROT_NONE && !is_shared {
             continue;
         }
 
         // translate page number to user address
-        let addr_st = (entry.page_num << PAGESHIFT) as u32;
-        let addr_len = (entry.npages << PAGESHIFT) as usize;
+        let addr_st = (entry.page_num as u64) << PAGESHIFT;
+        let addr_len = (entry.npages as usize) << PAGESHIFT;
+
+        if !is_shared && entry.backing == MemoryBackingType::Anonymous {
+            cow_regions.push((addr_st, addr_len, entry.prot));
+            continue;
+        }
 
         // translate user address to system address
         let parent_st = parent_vmmap.user_to_sys(addr_st);
         let child_st = child_vmmap.user_to_sys(addr_st);
-        if entry.flags & (MAP_SHARED as i32) > 0 {
+        if is_shared {
             // for shared memory, we are using mremap to fork shared memory
             // See "man 2 mremap" for description of what MREMAP_MAYMOVE does with old_size=0
             // when old_address points to a shared mapping
-            unsafe {
+            let ret = unsafe {
                 libc::mremap(
                     parent_st as *mut libc::c_void,
                     0,
                     addr_len,
                     (MREMAP_MAYMOVE | MREMAP_FIXED) as i32,
                     child_st as *mut libc::c_void,
-                );
+                )
             };
-        } else {
-            unsafe {
-                // temporarily enable write on child's memory region to write parent data
-                libc::mprotect(
-                    child_st as *mut libc::c_void,
+            if ret == libc::MAP_FAILED {
+                lind_debug_panic!(
+                    "fork_vmmap: mremap of shared region failed with errno {} (parent_st=0x{:x}, child_st=0x{:x}, len={})",
+                    get_errno(),
+                    parent_st,
+                    child_st,
                     addr_len,
-                    PROT_READ | PROT_WRITE,
                 );
+            }
+        } else {
+            copy_private_region(parent_st, child_st, addr_len, entry.prot);
+        }
+    }
 
-                // write parent data
-                let local_iov = libc::iovec {
-                    iov_base: parent_st as *mut libc::c_void,
-                    iov_len: addr_len,
-                };
-                let remote_iov = libc::iovec {
-                    iov_base: child_st as *mut libc::c_void,
-                    iov_len: addr_len,
-                };
-                let ret = libc::process_vm_writev(libc::getpid(), &local_iov, 1, &remote_iov, 1, 0);
-                if ret < 0 {
-                    lind_log!(
-                        Default,
-                        "process_vm_writev failed with errno {} (parent_st=0x{:x}, child_st=0x{:x}, len={}), falling back to copy_nonoverlapping",
-                        get_errno(),
-                        parent_st,
-                        child_st,
-                        addr_len,
-                    );
-                    std::ptr::copy_nonoverlapping(
-                        parent_st as *const u8,
-                        child_st as *mut u8,
-                        addr_len,
-                    );
-                }
-
-                // revert child's memory region prot
-                libc::mprotect(child_st as *mut libc::c_void, addr_len, entry.prot);
-            };
+    if !fork_cow(
+        &parent_cage,
+        &child_cage,
+        &parent_vmmap,
+        &child_vmmap,
+        &cow_regions,
+    ) {
+        for &(addr_st, addr_len, prot) in &cow_regions {
+            copy_private_region(
+                parent_vmmap.user_to_sys(addr_st),
+                child_vmmap.user_to_sys(addr_st),
+                addr_len,
+                prot,
+            );
         }
     }
 
@@ -162,6 +168,41 @@ pub fn fork_vmmap(parent_cageid: u64, child_cageid: u64) {
     drop(child_vmmap);
     let mut child_vmmap = child_cage.vmmap.write();
     child_vmmap.set_heap_start(parent_vmmap.heap_start);
+    child_vmmap.mmap_top = parent_vmmap.mmap_top;
+}
+
+/// Copies `len` bytes of a private region at host address `parent_st` into the child's
+/// region at `child_st`, which is then given protection `prot`.
+fn copy_private_region(parent_st: usize, child_st: usize, len: usize, prot: i32) {
+    unsafe {
+        // temporarily enable write on child's memory region to write parent data
+        libc::mprotect(child_st as *mut libc::c_void, len, PROT_READ | PROT_WRITE);
+
+        // write parent data
+        let local_iov = libc::iovec {
+            iov_base: parent_st as *mut libc::c_void,
+            iov_len: len,
+        };
+        let remote_iov = libc::iovec {
+            iov_base: child_st as *mut libc::c_void,
+            iov_len: len,
+        };
+        let ret = libc::process_vm_writev(libc::getpid(), &local_iov, 1, &remote_iov, 1, 0);
+        if ret < 0 {
+            lind_log!(
+                Default,
+                "process_vm_writev failed with errno {} (parent_st=0x{:x}, child_st=0x{:x}, len={}), falling back to copy_nonoverlapping",
+                get_errno(),
+                parent_st,
+                child_st,
+                len,
+            );
+            std::ptr::copy_nonoverlapping(parent_st as *const u8, child_st as *mut u8, len);
+        }
+
+        // revert child's memory region prot
+        libc::mprotect(child_st as *mut libc::c_void, len, prot);
+    };
 }
 
 // set the wasm linear memory base address to vmmap
@@ -172,6 +213,68 @@ pub fn init_vmmap(cageid: u64, base_address: usize, heap_start: Option<u32>) {
     if heap_start.is_some() {
         vmmap.set_heap_start(heap_start.unwrap());
     }
+    aslr::randomize_layout(cageid, &mut vmmap, heap_start.is_some());
+}
+
+// mark the cage as a 64-bit (memory64) cage, before its vmmap is initialized
+pub fn set_memory64(cageid: u64) {
+    let cage = get_cage(cageid).unwrap();
+    cage.vmmap.write().set_memory64(true);
+    cage.mem_quota.set_memory64();
+}
+
+/// Empties the vmmap of a cage that exec-ed, and gives the memory of the old program back to
+/// the host, so that the new program can reuse the cage's linear memory. Every region of the old
+/// vmmap is left inaccessible and reads as zeros:
+/// - private anonymous regions are released with `madvise(MADV_DONTNEED)`, which only costs the
+///   pages the old program touched,
+/// - shared and file-backed regions, and private ones that may come from a copy-on-write
+///   snapshot (see `memory::cow`), are replaced by fresh anonymous memory, since the host would
+///   give back their contents instead of zeros.
+///
+/// Must be called once the old program has unwound, as its stack is released too.
+pub fn reset_vmmap_for_exec(cageid: u64) {
+    let cage = get_cage(cageid).unwrap();
+    let snapshot = cage.memory_snapshot.lock().take();
+    let mut vmmap = cage.vmmap.write();
+    if vmmap.base_address.is_some() {
+        for (_, entry) in vmmap.entries.iter() {
+            let addr = vmmap.user_to_sys((entry.page_num as u64) << PAGESHIFT) as *mut libc::c_void;
+            let len = (entry.npages as usize) << PAGESHIFT;
+            let private_anonymous = entry.flags & (MAP_SHARED as i32) == 0
+                && entry.backing == MemoryBackingType::Anonymous;
+            let ret = unsafe {
+                if private_anonymous && snapshot.is_none() {
+                    libc::madvise(addr, len, MADV_DONTNEED);
+                    libc::mprotect(addr, len, PROT_NONE)
+                } else if libc::mmap(
+                    addr,
+                    len,
+                    PROT_NONE,
+                    (MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED | MAP_NORESERVE) as i32,
+                    -1,
+                    0,
+                ) == libc::MAP_FAILED
+                {
+                    -1
+                } else {
+                    0
+                }
+            };
+            if ret < 0 {
+                // The new program maps its regions afresh anyway; only memory is lost
+                lind_log!(
+                    Default,
+                    "reset_vmmap_for_exec: releasing 0x{:x} (len={}) failed with errno {}",
+                    addr as usize,
+                    len,
+                    get_errno()
+                );
+            }
+        }
+    }
+    vmmap.clear();
+    crate::memory::quota::recount(&cage, &vmmap);
 }
 
 /// Validates and converts a virtual memory address to a physical address with protection checks
@@ -1,8 +1,13 @@
 //! This module is VMMAP specific
+pub mod aslr;
+pub mod cow;
+pub mod introspect;
 pub mod memory;
+pub mod quota;
 pub mod shared;
 pub mod vmmap;
 
+pub use cow::*;
 pub use memory::*;
 pub use shared::*;
 pub use vmmap::*;
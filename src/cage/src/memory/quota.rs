@@ -0,0 +1,723 @@
+//! Memory quotas of cages
+//!
+//! Every cage may map up to 4 GiB of linear memory, and thousands of cages can run at once, so
+//! without limits a few runaway cages exhaust the host. This module counts, for each cage, the
+//! pages of its `vmmap` and the pages it commits: the writable memory only the cage can back,
+//! which is all of it except `PROT_NONE` and read-only mappings, System V shared memory segments
+//! and shared file mappings. `mmap`, `brk`, `mremap` and `mprotect` check growth with
+//! `check_mapping` or `check_protect` before changing the `vmmap`, which reserves the new pages
+//! against every limit at once, and report the new totals with `recount` afterwards.
+//!
+//! Growth fails with `ENOMEM` if it would take:
+//! - the mapped pages of the cage over its `RLIMIT_AS`,
+//! - the committed pages of the cage over its `RLIMIT_DATA`, which starts at the per-cage limit
+//!   lind-boot was given,
+//! - the committed pages of the cage's tree over the per-tree limit. A tree is a child of the
+//!   first cage together with all its descendants, so each program the first cage starts (a
+//!   shell's job, say) is limited as a whole. The first cage is a tree of its own,
+//! - the committed pages of all cages over the global budget. With the OOM killer enabled, the
+//!   cage committing the most memory, other than the first cage, is sent SIGKILL instead, and the
+//!   growth is allowed unless that cage is the caller.
+//!
+//! Pages are counted when they are mapped or made writable, not when they are touched. A
+//! `PROT_NONE` reservation only counts against `RLIMIT_AS` until it is made writable.
+use crate::cage::{get_cage, Cage, CAGE_MAP};
+use crate::memory::vmmap::{MemoryBackingType, Vmmap, VmmapEntry};
+use crate::signal::lind_send_signal;
+use dashmap::DashMap;
+use nodit::interval::ie;
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, OnceLock};
+use sysdefs::constants::err_const::Errno;
+use sysdefs::constants::fs_const::{MAP_SHARED, PAGESHIFT, PROT_WRITE};
+use sysdefs::constants::lind_platform_const::{
+    INIT_CAGEID, MAX_LINEAR_MEMORY64_SIZE, MAX_LINEAR_MEMORY_SIZE,
+};
+use sysdefs::constants::sys_const::SIGKILL;
+use sysdefs::lind_log;
+
+/// Memory limits chosen when lind-boot starts (see `configure`), in bytes. `None` is unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryConfig {
+    /// Initial `RLIMIT_DATA` of every cage.
+    pub cage_limit: Option<u64>,
+    /// Committed memory of each tree of cages.
+    pub tree_limit: Option<u64>,
+    /// Committed memory of all cages together.
+    pub global_limit: Option<u64>,
+    /// SIGKILL the largest cage when the global budget is exceeded, instead of failing with
+    /// `ENOMEM`.
+    pub oom_kill: bool,
+}
+
+static CONFIG: OnceLock<MemoryConfig> = OnceLock::new();
+
+/// Pages committed by all cages.
+static GLOBAL_COMMITTED: AtomicU64 = AtomicU64::new(0);
+
+/// Pages committed by each tree, keyed by the id of its root cage.
+static TREE_COMMITTED: LazyLock<DashMap<u64, u64>> = LazyLock::new(DashMap::new);
+
+/// Sets the memory limits. Must be called before the first cage is created; later calls have no
+/// effect.
+pub fn configure(config: MemoryConfig) {
+    let _ = CONFIG.set(config);
+}
+
+fn config() -> &'static MemoryConfig {
+    CONFIG.get_or_init(MemoryConfig::default)
+}
+
+fn bytes_to_pages(bytes: u64) -> u64 {
+    bytes >> PAGESHIFT
+}
+
+/// The `RLIMIT_AS` and `RLIMIT_DATA` limits of a cage, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRlimits {
+    pub as_cur: u64,
+    pub as_max: u64,
+    pub data_cur: u64,
+    pub data_max: u64,
+}
+
+/// Memory accounting of one cage.
+///
+/// Children inherit the limits on fork, and exec keeps them. The counts start at zero and are set
+/// by `recount`. In between, `check_growth` adds the pages it reserves to them.
+#[derive(Debug)]
+pub struct MemQuota {
+    /// Id of the root cage of the cage's tree
+    pub tree: u64,
+    /// Pages in the cage's `vmmap`
+    mapped: AtomicU64,
+    /// Pages of the cage's `vmmap` the cage commits
+    committed: AtomicU64,
+    /// Most pages the cage has committed at once
+    peak_committed: AtomicU64,
+    rlimits: RwLock<MemoryRlimits>,
+}
+
+impl MemQuota {
+    /// Accounting of the first cage, which is the root of its own tree.
+    pub fn new(cageid: u64) -> Self {
+        let data = config().cage_limit.unwrap_or(MAX_LINEAR_MEMORY_SIZE);
+        MemQuota {
+            tree: cageid,
+            mapped: AtomicU64::new(0),
+            committed: AtomicU64::new(0),
+            peak_committed: AtomicU64::new(0),
+            rlimits: RwLock::new(MemoryRlimits {
+                as_cur: MAX_LINEAR_MEMORY_SIZE,
+                as_max: MAX_LINEAR_MEMORY_SIZE,
+                data_cur: data,
+                data_max: data,
+            }),
+        }
+    }
+
+    /// Accounting of a child cage: the children of the first cage start new trees.
+    pub fn for_child(&self, parent_cageid: u64, child_cageid: u64) -> Self {
+        MemQuota {
+            tree: if parent_cageid == INIT_CAGEID {
+                child_cageid
+            } else {
+                self.tree
+            },
+            mapped: AtomicU64::new(0),
+            committed: AtomicU64::new(0),
+            peak_committed: AtomicU64::new(0),
+            rlimits: RwLock::new(self.rlimits()),
+        }
+    }
+
+    pub fn rlimits(&self) -> MemoryRlimits {
+        *self.rlimits.read()
+    }
+
+    /// Replaces the limits. As the cage is unprivileged, a soft limit may not exceed its hard
+    /// limit (`EINVAL`), and a hard limit may only be lowered (`EPERM`).
+    pub fn set_rlimits(&self, rlimits: MemoryRlimits) -> Result<(), Errno> {
+        let mut current = self.rlimits.write();
+        if rlimits.as_cur > rlimits.as_max || rlimits.data_cur > rlimits.data_max {
+            return Err(Errno::EINVAL);
+        }
+        if rlimits.as_max > current.as_max || rlimits.data_max > current.data_max {
+            return Err(Errno::EPERM);
+        }
+        *current = rlimits;
+        Ok(())
+    }
+
+    /// Raises the default limits of a 64-bit cage to its address space. Limits that were already
+    /// lowered, or set by `--cage-limit`, are kept.
+    pub fn set_memory64(&self) {
+        let mut rlimits = self.rlimits.write();
+        if rlimits.as_max == MAX_LINEAR_MEMORY_SIZE {
+            rlimits.as_cur = MAX_LINEAR_MEMORY64_SIZE;
+            rlimits.as_max = MAX_LINEAR_MEMORY64_SIZE;
+        }
+        if config().cage_limit.is_none() && rlimits.data_max == MAX_LINEAR_MEMORY_SIZE {
+            rlimits.data_cur = MAX_LINEAR_MEMORY64_SIZE;
+            rlimits.data_max = MAX_LINEAR_MEMORY64_SIZE;
+        }
+    }
+
+    /// Pages in the cage's `vmmap`.
+    pub fn mapped_pages(&self) -> u64 {
+        self.mapped.load(Ordering::Acquire)
+    }
+
+    /// Pages the cage commits.
+    pub fn committed_pages(&self) -> u64 {
+        self.committed.load(Ordering::Acquire)
+    }
+
+    /// Most pages the cage has committed at once, across the programs it has run.
+    pub fn peak_committed_pages(&self) -> u64 {
+        self.peak_committed.load(Ordering::Acquire)
+    }
+}
+
+/// Whether the pages of a mapping with these flags, protection and backing are committed by the
+/// cage.
+pub fn is_committed(flags: i32, prot: i32, backing: MemoryBackingType) -> bool {
+    if prot & PROT_WRITE == 0 {
+        return false;
+    }
+    match backing {
+        MemoryBackingType::SharedMemory(_) => false,
+        MemoryBackingType::FileDescriptor(_) => flags & MAP_SHARED as i32 == 0,
+        _ => true,
+    }
+}
+
+fn entry_is_committed(entry: &VmmapEntry) -> bool {
+    is_committed(entry.flags, entry.prot, entry.backing)
+}
+
+/// Mapped and committed pages of `[page_num, page_num + npages)` in a `vmmap`.
+fn range_usage(vmmap: &Vmmap, page_num: u32, npages: u32) -> (u64, u64) {
+    let end = page_num.saturating_add(npages);
+    let (mut mapped, mut committed) = (0, 0);
+    for (interval, entry) in vmmap.entries.overlapping(ie(page_num, end)) {
+        // .end() is inclusive
+        let overlap = interval.end().saturating_add(1).min(end) - interval.start().max(page_num);
+        mapped += overlap as u64;
+        if entry_is_committed(entry) {
+            committed += overlap as u64;
+        }
+    }
+    (mapped, committed)
+}
+
+/// Mapped and committed pages of a whole `vmmap`.
+fn vmmap_usage(vmmap: &Vmmap) -> (u64, u64) {
+    let (mut mapped, mut committed) = (0, 0);
+    for (interval, entry) in vmmap.entries.iter() {
+        let npages = interval.end() as u64 + 1 - interval.start() as u64;
+        mapped += npages;
+        if entry_is_committed(entry) {
+            committed += npages;
+        }
+    }
+    (mapped, committed)
+}
+
+/// Checks that `cage` may replace `[page_num, page_num + npages)` of its `vmmap` with a mapping,
+/// committed or not (see `is_committed`), and reserves the pages it adds (see `check_growth`).
+///
+/// # Returns
+/// * `Err(Errno::ENOMEM)` if the mapping would exceed one of the limits
+pub fn check_mapping(
+    cage: &Cage,
+    vmmap: &Vmmap,
+    page_num: u32,
+    npages: u32,
+    committed: bool,
+) -> Result<(), Errno> {
+    if npages == 0 {
+        return Ok(());
+    }
+    let (old_mapped, old_committed) = range_usage(vmmap, page_num, npages);
+    let added_mapped = (npages as u64).saturating_sub(old_mapped);
+    let added_committed = if committed {
+        (npages as u64).saturating_sub(old_committed)
+    } else {
+        0
+    };
+    check_growth(cage, added_mapped, added_committed)
+}
+
+/// Checks that `cage` may make `[page_num, page_num + npages)` of its `vmmap` accessible with
+/// `prot`, which commits the pages of private mappings it makes writable, and reserves them (see
+/// `check_growth`).
+///
+/// # Returns
+/// * `Err(Errno::ENOMEM)` if the change would exceed one of the limits
+pub fn check_protect(
+    cage: &Cage,
+    vmmap: &Vmmap,
+    page_num: u32,
+    npages: u32,
+    prot: i32,
+) -> Result<(), Errno> {
+    let end = page_num.saturating_add(npages);
+    let mut added = 0;
+    for (interval, entry) in vmmap.entries.overlapping(ie(page_num, end)) {
+        if is_committed(entry.flags, prot, entry.backing) && !entry_is_committed(entry) {
+            // .end() is inclusive
+            added +=
+                (interval.end().saturating_add(1).min(end) - interval.start().max(page_num)) as u64;
+        }
+    }
+    check_growth(cage, 0, added)
+}
+
+/// Checks that `cage` may map `mapped` more pages, `committed` of which it commits, and reserves
+/// them: they count against every limit at once, so that concurrent growth of the cage, its tree
+/// or any other cage sees them. The reservation holds until the next `recount` of the cage, which
+/// the caller runs whether or not the growth went through.
+///
+/// # Returns
+/// * `Err(Errno::ENOMEM)` if the growth would exceed one of the limits, in which case nothing is
+///   reserved
+pub fn check_growth(cage: &Cage, mapped: u64, committed: u64) -> Result<(), Errno> {
+    let quota = &cage.mem_quota;
+    let rlimits = quota.rlimits();
+    reserve(&quota.mapped, mapped, Some(bytes_to_pages(rlimits.as_cur)))?;
+    if committed == 0 {
+        return Ok(());
+    }
+    let reserved = reserve(
+        &quota.committed,
+        committed,
+        Some(bytes_to_pages(rlimits.data_cur)),
+    )
+    .and_then(|()| {
+        reserve_shared(cage.cageid, Some(quota.tree), committed).inspect_err(|_| {
+            quota.committed.fetch_sub(committed, Ordering::AcqRel);
+        })
+    });
+    if reserved.is_err() {
+        quota.mapped.fetch_sub(mapped, Ordering::AcqRel);
+    }
+    reserved
+}
+
+/// Adds `pages` to `count` unless that takes it over `limit`.
+fn reserve(count: &AtomicU64, pages: u64, limit: Option<u64>) -> Result<(), Errno> {
+    count
+        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |old| match limit {
+            Some(limit) if old + pages > limit => None,
+            _ => Some(old + pages),
+        })
+        .map(|_| ())
+        .map_err(|_| Errno::ENOMEM)
+}
+
+/// Reserves `committed` pages in the tree whose root is `tree` and in the global budget, on
+/// behalf of `cageid`. `None` stands for a tree that is about to start, which commits nothing yet.
+fn reserve_shared(cageid: u64, tree: Option<u64>, committed: u64) -> Result<(), Errno> {
+    let config = config();
+    let tree_limit = config.tree_limit.map(bytes_to_pages);
+    let mut tree_pages = match tree {
+        // The tree's entry stays locked until the pages are added to it, so that no other cage of
+        // the tree passes the check in between
+        Some(tree) => {
+            let pages = TREE_COMMITTED.entry(tree).or_insert(0);
+            if tree_limit.is_some_and(|limit| *pages + committed > limit) {
+                drop(pages);
+                TREE_COMMITTED.remove_if(&tree, |_, pages| *pages == 0);
+                return Err(Errno::ENOMEM);
+            }
+            Some(pages)
+        }
+        None if tree_limit.is_some_and(|limit| committed > limit) => return Err(Errno::ENOMEM),
+        None => None,
+    };
+    let global_limit = config.global_limit.map(bytes_to_pages);
+    if reserve(&GLOBAL_COMMITTED, committed, global_limit).is_err() {
+        if let Err(e) = out_of_memory(cageid) {
+            if let (Some(tree), Some(pages)) = (tree, tree_pages.take()) {
+                drop(pages);
+                TREE_COMMITTED.remove_if(&tree, |_, pages| *pages == 0);
+            }
+            return Err(e);
+        }
+        // The victim's memory is released once it exits, and the caller goes ahead meanwhile
+        GLOBAL_COMMITTED.fetch_add(committed, Ordering::AcqRel);
+    }
+    if let Some(mut pages) = tree_pages {
+        *pages += committed;
+    }
+    Ok(())
+}
+
+/// Pages `check_fork` reserved for a child that is not created yet, handed to the child with
+/// `claim_fork`.
+#[must_use]
+#[derive(Debug)]
+pub struct ForkReservation {
+    pages: u64,
+    /// Whether the pages were reserved in the parent's tree, which the child joins
+    in_tree: bool,
+}
+
+/// Checks that a fork of `parent` may commit the same memory as it, and reserves that memory for
+/// the child.
+///
+/// # Returns
+/// * `Err(Errno::ENOMEM)` if the child would exceed the tree or global budget
+pub fn check_fork(parent: &Cage) -> Result<ForkReservation, Errno> {
+    let quota = &parent.mem_quota;
+    let pages = quota.committed_pages();
+    // A child of the first cage starts a tree of its own
+    let tree = (parent.cageid != INIT_CAGEID).then_some(quota.tree);
+    reserve_shared(parent.cageid, tree, pages)?;
+    Ok(ForkReservation {
+        pages,
+        in_tree: tree.is_some(),
+    })
+}
+
+/// Hands the pages `check_fork` reserved to the forked `child`, and counts the memory it
+/// inherited.
+pub fn claim_fork(child: &Cage, reservation: ForkReservation) {
+    let quota = &child.mem_quota;
+    quota.committed.store(reservation.pages, Ordering::Release);
+    if !reservation.in_tree {
+        *TREE_COMMITTED.entry(quota.tree).or_insert(0) += reservation.pages;
+    }
+    recount(child, &child.vmmap.read());
+}
+
+/// Gives back the pages `check_fork` reserved for a child of `parent` that could not be created.
+pub fn cancel_fork(parent: &Cage, reservation: ForkReservation) {
+    GLOBAL_COMMITTED.fetch_sub(reservation.pages, Ordering::AcqRel);
+    if reservation.in_tree {
+        let tree = parent.mem_quota.tree;
+        let mut pages = TREE_COMMITTED.entry(tree).or_insert(0);
+        *pages = pages.saturating_sub(reservation.pages);
+        if *pages == 0 {
+            drop(pages);
+            TREE_COMMITTED.remove_if(&tree, |_, pages| *pages == 0);
+        }
+    }
+}
+
+/// Handles the global budget being exceeded on behalf of `cageid`.
+///
+/// Without the OOM killer, or if the cage chosen last time has yet to exit, the caller gets
+/// `ENOMEM`. Otherwise the cage `oom_victim` picks is sent SIGKILL, and the caller may go on
+/// unless it was that cage.
+fn out_of_memory(cageid: u64) -> Result<(), Errno> {
+    if !config().oom_kill {
+        return Err(Errno::ENOMEM);
+    }
+    let victim = oom_victim(CAGE_MAP.iter().filter_map(|slot| slot.load_full()));
+    let victim = match victim {
+        Some(victim) => victim,
+        None => return Err(Errno::ENOMEM),
+    };
+    if victim.pending_signals.read().contains(&SIGKILL) {
+        return Err(Errno::ENOMEM);
+    }
+    lind_log!(
+        "out of memory: killing cage {} ({} committed pages)",
+        victim.cageid,
+        victim.mem_quota.committed_pages()
+    );
+    lind_send_signal(victim.cageid, SIGKILL);
+    if victim.cageid == cageid {
+        Err(Errno::ENOMEM)
+    } else {
+        Ok(())
+    }
+}
+
+/// The live cage committing the most memory among `cages`. The first cage is never picked, as
+/// it is the init of every other cage.
+fn oom_victim(cages: impl Iterator<Item = Arc<Cage>>) -> Option<Arc<Cage>> {
+    cages
+        .filter(|cage| cage.cageid != INIT_CAGEID && !cage.is_dead.load(Ordering::Acquire))
+        .max_by_key(|cage| cage.mem_quota.committed_pages())
+}
+
+/// Moves `cage`'s committed pages to `committed`, updating its tree and the global count.
+fn set_committed(cage: &Cage, committed: u64) {
+    let old = cage.mem_quota.committed.swap(committed, Ordering::AcqRel);
+    if old == committed {
+        return;
+    }
+    cage.mem_quota
+        .peak_committed
+        .fetch_max(committed, Ordering::AcqRel);
+    if committed > old {
+        GLOBAL_COMMITTED.fetch_add(committed - old, Ordering::AcqRel);
+    } else {
+        GLOBAL_COMMITTED.fetch_sub(old - committed, Ordering::AcqRel);
+    }
+    let mut tree = TREE_COMMITTED.entry(cage.mem_quota.tree).or_insert(0);
+    *tree = (*tree + committed).saturating_sub(old);
+    if *tree == 0 {
+        drop(tree);
+        TREE_COMMITTED.remove_if(&cage.mem_quota.tree, |_, pages| *pages == 0);
+    }
+}
+
+/// Recounts the pages of `cage` from its `vmmap`, after the `vmmap` changed.
+pub fn recount(cage: &Cage, vmmap: &Vmmap) {
+    let (mapped, committed) = vmmap_usage(vmmap);
+    cage.mem_quota.mapped.store(mapped, Ordering::Release);
+    set_committed(cage, committed);
+}
+
+/// Recounts the pages of the cage `cageid`, see `recount`.
+pub fn recount_cage(cageid: u64) {
+    if let Some(cage) = get_cage(cageid) {
+        recount(&cage, &cage.vmmap.read());
+    }
+}
+
+/// Stops counting the memory of a cage that is being removed.
+pub fn release(cage: &Cage) {
+    cage.mem_quota.mapped.store(0, Ordering::Release);
+    set_committed(cage, 0);
+}
+
+/// Pages committed by all cages.
+pub fn global_committed_pages() -> u64 {
+    GLOBAL_COMMITTED.load(Ordering::Acquire)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cage::*;
+    use crate::timer::IntervalTimer;
+    use crate::VmmapOps;
+    use sysdefs::constants::fs_const::{
+        MAP_ANONYMOUS, MAP_PRIVATE, PROT_NONE, PROT_READ, PROT_WRITE,
+    };
+
+    fn test_cage(cageid: u64, parent: u64, tree: u64) -> Cage {
+        Cage {
+            cageid,
+            parent,
+            cwd: RwLock::new(Arc::new(PathBuf::from("/"))),
+            umask: AtomicU32::new(0o022),
+            netns: AtomicU64::new(0),
+            utsns: AtomicU64::new(0),
+            cpu_affinity: RwLock::new(vec![1]),
+            nice: AtomicI32::new(0),
+            rev_shm: Mutex::new(Vec::new()),
+            signalhandler: DashMap::new(),
+            sigset: AtomicU64::new(0),
+            pending_signals: RwLock::new(vec![]),
+            epoch_handler: DashMap::new(),
+            os_tid_map: DashMap::new(),
+            robust_list: DashMap::new(),
+            main_threadid: RwLock::new(0),
+            interval_timer: IntervalTimer::new(cageid),
+            zombies: RwLock::new(vec![]),
+            child_num: AtomicU64::new(0),
+            vmmap: RwLock::new(Vmmap::new()),
+            memory_snapshot: Mutex::new(None),
+            mem_quota: MemQuota::new(tree),
+            final_exit_status: RwLock::new(None),
+            exit_group_initiated: AtomicBool::new(false),
+            is_dead: AtomicBool::new(false),
+            grate_inflight: AtomicU64::new(0),
+        }
+    }
+
+    fn map(cage: &Cage, page_num: u32, npages: u32, flags: i32, backing: MemoryBackingType) {
+        let mut vmmap = cage.vmmap.write();
+        let _ = vmmap.add_entry_with_overwrite(
+            page_num,
+            npages,
+            PROT_READ | PROT_WRITE,
+            PROT_READ | PROT_WRITE,
+            flags,
+            backing,
+            0,
+            0,
+            cage.cageid,
+        );
+        recount(cage, &vmmap);
+    }
+
+    const PRIVATE: i32 = (MAP_PRIVATE | MAP_ANONYMOUS) as i32;
+
+    fn limit_data(cage: &Cage, pages: u64) {
+        let mut rlimits = cage.mem_quota.rlimits();
+        rlimits.data_cur = pages << PAGESHIFT;
+        cage.mem_quota.set_rlimits(rlimits).unwrap();
+    }
+
+    #[test]
+    fn test_recount_skips_shared_memory() {
+        let cage = test_cage(201, 1, 201);
+        map(&cage, 0, 10, PRIVATE, MemoryBackingType::Anonymous);
+        map(&cage, 20, 5, 0, MemoryBackingType::SharedMemory(3));
+        map(
+            &cage,
+            30,
+            4,
+            MAP_SHARED as i32,
+            MemoryBackingType::FileDescriptor(4),
+        );
+        assert_eq!(cage.mem_quota.mapped_pages(), 19);
+        assert_eq!(cage.mem_quota.committed_pages(), 10);
+        assert_eq!(*TREE_COMMITTED.get(&201).unwrap(), 10);
+
+        release(&cage);
+        assert_eq!(cage.mem_quota.committed_pages(), 0);
+        assert_eq!(cage.mem_quota.peak_committed_pages(), 10);
+        assert!(TREE_COMMITTED.get(&201).is_none());
+    }
+
+    #[test]
+    fn test_check_mapping_enforces_rlimits() {
+        let cage = test_cage(202, 1, 202);
+        let mut rlimits = cage.mem_quota.rlimits();
+        rlimits.data_cur = 16 << PAGESHIFT;
+        rlimits.as_cur = 24 << PAGESHIFT;
+        cage.mem_quota.set_rlimits(rlimits).unwrap();
+        map(&cage, 0, 12, PRIVATE, MemoryBackingType::Anonymous);
+
+        // Each check that passes reserves its pages until the recount
+        let vmmap = cage.vmmap.read();
+        assert!(check_mapping(&cage, &vmmap, 12, 4, true).is_ok());
+        recount(&cage, &vmmap);
+        assert_eq!(
+            check_mapping(&cage, &vmmap, 12, 5, true),
+            Err(Errno::ENOMEM)
+        );
+        // Replacing mapped pages commits nothing new
+        assert!(check_mapping(&cage, &vmmap, 4, 12, true).is_ok());
+        recount(&cage, &vmmap);
+        // Shared mappings only count against RLIMIT_AS
+        assert!(check_mapping(&cage, &vmmap, 100, 12, false).is_ok());
+        recount(&cage, &vmmap);
+        assert_eq!(
+            check_mapping(&cage, &vmmap, 100, 13, false),
+            Err(Errno::ENOMEM)
+        );
+        drop(vmmap);
+        release(&cage);
+    }
+
+    #[test]
+    fn test_check_growth_reserves_until_recount() {
+        let cage = test_cage(205, 1, 205);
+        limit_data(&cage, 16);
+
+        // Two growths that each fit cannot both pass before either is mapped
+        assert!(check_growth(&cage, 10, 10).is_ok());
+        assert_eq!(cage.mem_quota.committed_pages(), 10);
+        assert_eq!(*TREE_COMMITTED.get(&205).unwrap(), 10);
+        assert_eq!(check_growth(&cage, 10, 10), Err(Errno::ENOMEM));
+        assert_eq!(cage.mem_quota.mapped_pages(), 10);
+
+        // The recount replaces the reservation with what was mapped
+        map(&cage, 0, 4, PRIVATE, MemoryBackingType::Anonymous);
+        assert_eq!(cage.mem_quota.committed_pages(), 4);
+        assert_eq!(*TREE_COMMITTED.get(&205).unwrap(), 4);
+        assert!(check_growth(&cage, 12, 12).is_ok());
+        release(&cage);
+        assert!(TREE_COMMITTED.get(&205).is_none());
+    }
+
+    #[test]
+    fn test_cancel_fork_gives_back_reservation() {
+        let parent = test_cage(207, 1, 207);
+        map(&parent, 0, 6, PRIVATE, MemoryBackingType::Anonymous);
+        assert_eq!(*TREE_COMMITTED.get(&207).unwrap(), 6);
+
+        let reservation = check_fork(&parent).unwrap();
+        assert_eq!(*TREE_COMMITTED.get(&207).unwrap(), 12);
+        cancel_fork(&parent, reservation);
+        assert_eq!(*TREE_COMMITTED.get(&207).unwrap(), 6);
+        release(&parent);
+        assert!(TREE_COMMITTED.get(&207).is_none());
+    }
+
+    #[test]
+    fn test_prot_none_is_committed_once_writable() {
+        let cage = test_cage(206, 1, 206);
+        limit_data(&cage, 8);
+        {
+            let mut vmmap = cage.vmmap.write();
+            let _ = vmmap.add_entry_with_overwrite(
+                0,
+                64,
+                PROT_NONE,
+                PROT_READ | PROT_WRITE,
+                PRIVATE,
+                MemoryBackingType::Anonymous,
+                0,
+                0,
+                cage.cageid,
+            );
+            recount(&cage, &vmmap);
+        }
+        assert_eq!(cage.mem_quota.mapped_pages(), 64);
+        assert_eq!(cage.mem_quota.committed_pages(), 0);
+
+        let mut vmmap = cage.vmmap.write();
+        assert!(check_protect(&cage, &vmmap, 0, 8, PROT_READ).is_ok());
+        assert_eq!(
+            check_protect(&cage, &vmmap, 0, 9, PROT_READ | PROT_WRITE),
+            Err(Errno::ENOMEM)
+        );
+        assert_eq!(cage.mem_quota.committed_pages(), 0);
+        assert!(check_protect(&cage, &vmmap, 0, 8, PROT_READ | PROT_WRITE).is_ok());
+        vmmap.change_prot(0, 8, PROT_READ | PROT_WRITE);
+        recount(&cage, &vmmap);
+        assert_eq!(cage.mem_quota.committed_pages(), 8);
+        // Pages that are writable already commit nothing new
+        assert!(check_protect(&cage, &vmmap, 0, 8, PROT_READ | PROT_WRITE).is_ok());
+        drop(vmmap);
+        release(&cage);
+    }
+
+    #[test]
+    fn test_oom_victim_spares_first_cage() {
+        let init = Arc::new(test_cage(INIT_CAGEID, 0, INIT_CAGEID));
+        let small = Arc::new(test_cage(207, INIT_CAGEID, 207));
+        let dead = Arc::new(test_cage(208, INIT_CAGEID, 208));
+        map(&init, 0, 30, PRIVATE, MemoryBackingType::Anonymous);
+        map(&small, 0, 10, PRIVATE, MemoryBackingType::Anonymous);
+        map(&dead, 0, 20, PRIVATE, MemoryBackingType::Anonymous);
+        dead.is_dead.store(true, Ordering::Release);
+
+        let cages = [init.clone(), small.clone(), dead.clone()];
+        let victim = oom_victim(cages.iter().cloned()).unwrap();
+        assert_eq!(victim.cageid, 207);
+        assert!(oom_victim([init.clone()].into_iter()).is_none());
+        for cage in &cages {
+            release(cage);
+        }
+    }
+
+    #[test]
+    fn test_set_rlimits_only_lowers_hard_limit() {
+        let cage = test_cage(203, 1, 203);
+        let mut rlimits = cage.mem_quota.rlimits();
+        rlimits.data_max = 1 << 20;
+        assert_eq!(cage.mem_quota.set_rlimits(rlimits), Err(Errno::EINVAL));
+        rlimits.data_cur = 1 << 20;
+        assert!(cage.mem_quota.set_rlimits(rlimits).is_ok());
+        rlimits.data_max = 2 << 20;
+        assert_eq!(cage.mem_quota.set_rlimits(rlimits), Err(Errno::EPERM));
+
+        // Children keep their parent's limits
+        let child = cage.mem_quota.for_child(203, 204);
+        assert_eq!(child.rlimits().data_max, 1 << 20);
+        assert_eq!(child.tree, 203);
+        assert_eq!(cage.mem_quota.for_child(INIT_CAGEID, 204).tree, 204);
+    }
+}
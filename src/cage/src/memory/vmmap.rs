@@ -7,14 +7,18 @@
 //! and searching for memory regions, ensuring proper alignment, protection, and handling of shared
 //! and file-backed memory.
 //! This file defines `vmmap` data structures.
+use crate::memory::aslr::{AslrRng, STACK_RND_SHIFT};
 use fdtables;
 use nodit::NoditMap;
 use nodit::{interval::ie, Interval};
+use std::fmt;
 use std::io;
 use sysdefs::constants::err_const::{syscall_error, Errno};
 use sysdefs::constants::fs_const::{
-    PAGESHIFT, PAGESIZE, PROT_EXEC, PROT_NONE, PROT_READ, PROT_WRITE,
+    MAP_ANONYMOUS, MAP_NORESERVE, MAP_PRIVATE, MAP_STACK, PAGESHIFT, PAGESIZE, PROT_EXEC,
+    PROT_NONE, PROT_READ, PROT_WRITE,
 };
+use sysdefs::constants::lind_platform_const::MAX_LINEAR_MEMORY64_SIZE;
 
 /// Default number of virtual memory pages in a `vmmap`.
 /// Calculated as 2^(32 - PAGESHIFT), which represents the total pages
@@ -23,6 +27,14 @@ use sysdefs::constants::fs_const::{
 /// covering the full 4 GB virtual address space.
 const DEFAULT_VMMAP_SIZE: u32 = 1 << (32 - PAGESHIFT);
 
+/// Number of virtual memory pages in the `vmmap` of a 64-bit (memory64) cage, which covers
+/// `MAX_LINEAR_MEMORY64_SIZE`. Page numbers stay `u32`, as they can address 16 TiB.
+const MEMORY64_VMMAP_SIZE: u32 = ((MAX_LINEAR_MEMORY64_SIZE + 1) >> PAGESHIFT) as u32;
+
+/// `flags` of the entry that reserves the random gap above a thread stack (see
+/// `find_stack_space`), which is also `PROT_NONE` at most
+const STACK_GAP_FLAGS: i32 = (MAP_PRIVATE | MAP_ANONYMOUS | MAP_STACK | MAP_NORESERVE) as i32;
+
 /// Used to identify whether the vmmap entry is backed anonymously,
 /// by an fd, or by a shared memory segment
 ///
@@ -40,6 +52,29 @@ pub enum MemoryBackingType {
     FileDescriptor(u64), // stores file descriptor addr
 }
 
+impl fmt::Display for MemoryBackingType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryBackingType::None => write!(f, "none"),
+            MemoryBackingType::Anonymous => write!(f, "anon"),
+            MemoryBackingType::SharedMemory(shmid) => write!(f, "shm {}", shmid),
+            MemoryBackingType::FileDescriptor(fd) => write!(f, "file fd {}", fd),
+        }
+    }
+}
+
+/// Protection flags in the `rwx` notation of `/proc/<pid>/maps`.
+pub fn prot_string(prot: i32) -> String {
+    let bit = |flag: i32, c: char| if prot & flag != 0 { c } else { '-' };
+    [
+        bit(PROT_READ, 'r'),
+        bit(PROT_WRITE, 'w'),
+        bit(PROT_EXEC, 'x'),
+    ]
+    .iter()
+    .collect()
+}
+
 /// An entry in the virtual memory map that contains fields such as page number, number of pages,
 /// permissions, file offset, file size, shared memory ID, and backing fields to distinguish memory types.
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -251,6 +286,9 @@ pub trait VmmapOps {
 /// - entries: NoditMap storing the memory regions indexed by page number
 /// - cached_entry: Optional cached entry for performance optimization
 /// - base_address: Optional base address for WASM memory
+/// - memory64: Whether the cage has a 64-bit linear memory
+/// - mmap_top: Page below which mappings without an address are placed (see `aslr`)
+/// - aslr: Generator of the cage's random offsets, if ASLR is enabled
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Vmmap {
     pub entries: NoditMap<u32, Interval<u32>, VmmapEntry>, // Keyed by `page_num`
@@ -261,6 +299,9 @@ pub struct Vmmap {
     pub start_address: u32, // start address of valid vmmap address range
     pub end_address: u32,   // end address of valid vmmap address range
     pub heap_start: u32,    // start of heap memory
+    pub memory64: bool,     // 64-bit (memory64) cage
+    pub mmap_top: u32,      // top of the mmap area, end_address unless randomized
+    pub aslr: Option<AslrRng>,
 }
 
 #[allow(dead_code)]
@@ -275,6 +316,9 @@ impl Vmmap {
             start_address: 0,
             end_address: DEFAULT_VMMAP_SIZE,
             heap_start: 0,
+            memory64: false,
+            mmap_top: DEFAULT_VMMAP_SIZE,
+            aslr: None,
         }
     }
 
@@ -290,6 +334,9 @@ impl Vmmap {
         self.start_address = 0;
         self.end_address = DEFAULT_VMMAP_SIZE;
         self.heap_start = 0;
+        self.memory64 = false;
+        // the generator is kept, so that the new program gets a new layout
+        self.mmap_top = DEFAULT_VMMAP_SIZE;
     }
 
     /// Rounds up a page number to the nearest multiple of pages_per_map
@@ -336,13 +383,119 @@ impl Vmmap {
         self.heap_start = heap_start;
     }
 
+    /// Finds aligned space for a mapping between the pages `start` and `end`, at the top of the
+    /// lowest gap that fits
+    fn find_map_space_in(
+        &self,
+        start: u32,
+        end: u32,
+        num_pages: u32,
+        pages_per_map: u32,
+    ) -> Option<Interval<u32>> {
+        if start >= end {
+            return None;
+        }
+
+        let rounded_num_pages = self.round_page_num_up_to_map_multiple(num_pages, pages_per_map);
+
+        for gap in self.entries.gaps_trimmed(ie(start, end)) {
+            let aligned_start_page =
+                self.trunc_page_num_down_to_map_multiple(gap.start(), pages_per_map);
+            let aligned_end_page = self.round_page_num_up_to_map_multiple(gap.end(), pages_per_map);
+
+            let gap_size = aligned_end_page - aligned_start_page;
+            if gap_size >= rounded_num_pages {
+                return Some(ie(aligned_end_page - rounded_num_pages, aligned_end_page));
+            }
+        }
+
+        None
+    }
+
+    /// Finds space for a thread stack of `num_pages` pages for cage `cageid`
+    ///
+    /// With ASLR enabled, the stack is placed below a gap of a random size, which varies its
+    /// address and keeps it apart from the mapping above. The gap is reserved right away as a
+    /// `PROT_NONE` entry that cannot be made accessible, so that no other mapping lands in it
+    /// while the stack is in use; `release_stack_gap` gives it back once the stack is gone.
+    pub fn find_stack_space(&mut self, num_pages: u32, cageid: u64) -> Option<Interval<u32>> {
+        let gap = match self.aslr.as_mut() {
+            Some(rng) => rng.pages(self.end_address >> STACK_RND_SHIFT),
+            None => 0,
+        };
+        if gap > 0 {
+            if let Some(space) = num_pages
+                .checked_add(gap)
+                .and_then(|npages| self.find_map_space(npages, 1))
+            {
+                let stack_end = space.start() + num_pages;
+                self.add_entry(VmmapEntry::new(
+                    stack_end,
+                    gap,
+                    PROT_NONE,
+                    PROT_NONE,
+                    STACK_GAP_FLAGS,
+                    false,
+                    0,
+                    0,
+                    cageid,
+                    MemoryBackingType::Anonymous,
+                ));
+                return Some(ie(space.start(), stack_end));
+            }
+        }
+        let space = self.find_map_space(num_pages, 1)?;
+        Some(ie(space.start(), space.start() + num_pages))
+    }
+
+    /// Removes the gap `find_stack_space` reserved at page `page_num`, above a stack, if there is
+    /// one and the stack below it is no longer mapped
+    ///
+    /// Called once a stack is unmapped, or could not be mapped.
+    pub fn release_stack_gap(&mut self, page_num: u32) {
+        let Some(gap) = self.find_page(page_num) else {
+            return;
+        };
+        if gap.page_num != page_num
+            || gap.flags != STACK_GAP_FLAGS
+            || gap.maxprot != PROT_NONE
+            || (page_num > 0 && self.find_page(page_num - 1).is_some())
+        {
+            return;
+        }
+        let npages = gap.npages;
+        let _ = self.remove_entry(page_num, npages);
+    }
+
+    /// Sets whether the memory is a 64-bit (memory64) linear memory
+    ///
+    /// The address space of a 64-bit cage spans `MAX_LINEAR_MEMORY64_SIZE` instead of 4 GiB.
+    /// Must be called before any entry is added, like `set_base_address`.
+    ///
+    /// Arguments:
+    /// - memory64: true for a 64-bit cage
+    pub fn set_memory64(&mut self, memory64: bool) {
+        self.memory64 = memory64;
+        self.end_address = if memory64 {
+            MEMORY64_VMMAP_SIZE
+        } else {
+            DEFAULT_VMMAP_SIZE
+        };
+        self.mmap_top = self.end_address;
+    }
+
+    /// Returns the size in bytes of the user address space covered by the vmmap
+    pub fn address_space_size(&self) -> u64 {
+        (self.end_address as u64) << PAGESHIFT
+    }
+
     /// Converts a user address to a system address
     ///
     /// Arguments:
     /// - address: User space address to convert
     ///
     /// Returns the corresponding system address
-    pub fn user_to_sys(&self, address: u32) -> usize {
+    pub fn user_to_sys(&self, address: u64) -> usize {
         // Add base address to user address to get system address
         let base = self.base_address.unwrap();
         (address as usize)
@@ -356,38 +509,100 @@ impl Vmmap {
     /// - address: System address to convert
     ///
     /// Returns the corresponding user space address
-    pub fn sys_to_user(&self, address: usize) -> u32 {
+    pub fn sys_to_user(&self, address: usize) -> u64 {
         // Subtract base address from system address to get user address
         let base = self.base_address.unwrap();
         let user = address
             .checked_sub(base)
-            .expect("sys_to_user: address underflow");
+            .expect("sys_to_user: address underflow") as u64;
         assert!(
-            user <= u32::MAX as usize,
-            "sys_to_user: result exceeds 32-bit space"
+            user < self.address_space_size(),
+            "sys_to_user: result exceeds the cage address space"
         );
-        user as u32
+        user
     }
 
-    // Visits each entry in the vmmap, applying a visitor function to each entry
-    //
-    // The visitor function should be used to:
-    // - Validate memory map consistency
-    // - Gather statistics about memory usage
-    // - Perform operations across all entries
-    // - Support debugging and auditing features
-    fn visit() {}
-
-    // Prints detailed debug information about the vmmap's current state
-    //
-    // Should output information including:
-    // - Page ranges and sizes for each mapping
-    // - Protection flags (current and maximum)
-    // - Mapping flags
-    // - Backing store information (Anonymous, File, or Shared Memory)
-    // - File information (offset and size) when applicable
-    // - Any gaps in the address space
-    fn debug() {}
+    /// Converts a page-aligned user address to the return value of a syscall
+    ///
+    /// Syscalls return 32 bits, so `mmap`, `mremap`, `shmat` and `brk` return the page number of
+    /// the address in 64-bit cages, which their glibc shifts back by `PAGESHIFT`.
+    ///
+    /// Arguments:
+    /// - address: User space address to return
+    ///
+    /// Returns the value the syscall returns
+    pub fn user_addr_to_retval(&self, address: u64) -> i32 {
+        if self.memory64 {
+            (address >> PAGESHIFT) as i32
+        } else {
+            address as i32
+        }
+    }
+
+    /// Calls `visitor` on each entry of the vmmap in address order, together with the interval
+    /// of pages the entry covers (whose `.end()` is inclusive).
+    ///
+    /// Used to gather statistics across the mappings of a cage without holding on to the
+    /// `NoditMap` itself, as `introspect` does to report the memory of each cage.
+    pub fn visit<F>(&self, mut visitor: F)
+    where
+        F: FnMut(&Interval<u32>, &VmmapEntry),
+    {
+        for (interval, entry) in self.entries.iter() {
+            visitor(interval, entry);
+        }
+    }
+
+    /// Writes the layout of the vmmap to `out`, one line per entry and per gap between entries.
+    ///
+    /// An entry line gives its user address range, number of pages, current and maximum
+    /// protection, mapping flags, backing, and file offset and size when it maps a file. Gaps
+    /// are only listed between the first and last entry.
+    pub fn debug(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        writeln!(
+            out,
+            "vmmap: {} entries, base {:#x}, heap start page {:#x}, mmap top page {:#x}",
+            self.entries.len(),
+            self.base_address.unwrap_or(0),
+            self.heap_start,
+            self.mmap_top
+        )?;
+        let mut next_page: Option<u32> = None;
+        for (interval, entry) in self.entries.iter() {
+            let start = interval.start();
+            let end = interval.end() as u64 + 1;
+            if let Some(next) = next_page.filter(|next| *next < start) {
+                writeln!(
+                    out,
+                    "{:08x}-{:08x} {:>8} pages free",
+                    (next as u64) << PAGESHIFT,
+                    (start as u64) << PAGESHIFT,
+                    start - next
+                )?;
+            }
+            write!(
+                out,
+                "{:08x}-{:08x} {:>8} pages {}/{} flags {:#x} {}",
+                (start as u64) << PAGESHIFT,
+                end << PAGESHIFT,
+                end - start as u64,
+                prot_string(entry.prot),
+                prot_string(entry.maxprot),
+                entry.flags,
+                entry.backing
+            )?;
+            if let MemoryBackingType::FileDescriptor(_) = entry.backing {
+                write!(
+                    out,
+                    " offset {:#x} size {:#x}",
+                    entry.file_offset, entry.file_size
+                )?;
+            }
+            writeln!(out)?;
+            next_page = interval.end().checked_add(1);
+        }
+        Ok(())
+    }
 
     /// Calculates the page number and number of pages for a given address and length.
     ///
@@ -414,6 +629,25 @@ impl Vmmap {
         Some((page_num, npages))
     }
 
+    /// Checks whether a page range is entirely unmapped
+    ///
+    /// Used by `mremap` to decide whether a mapping can grow in place.
+    ///
+    /// # Arguments
+    /// * `page_num` - Starting page number
+    /// * `npages` - Number of pages in the range
+    ///
+    /// # Returns
+    /// * `true` - If no entry overlaps the range and it fits in the vmmap
+    /// * `false` - Otherwise
+    pub fn is_range_free(&self, page_num: u32, npages: u32) -> bool {
+        let end_page = match page_num.checked_add(npages) {
+            Some(end) if end <= self.end_address => end,
+            _ => return false,
+        };
+        npages == 0 || !self.entries.overlaps(ie(page_num, end_page))
+    }
+
     /// Checks if a given address range is readable
     ///
     /// This helper function checks whether a memory range starting at the given address
@@ -1115,23 +1349,11 @@ impl VmmapOps for Vmmap {
     /// - Rounds page numbers up to alignment boundaries
     /// - Handles alignment constraints for start and end addresses
     fn find_map_space(&self, num_pages: u32, pages_per_map: u32) -> Option<Interval<u32>> {
-        let start = self.start_address;
-        let end = self.end_address;
-
-        let rounded_num_pages = self.round_page_num_up_to_map_multiple(num_pages, pages_per_map);
-
-        for gap in self.entries.gaps_trimmed(ie(start, end)) {
-            let aligned_start_page =
-                self.trunc_page_num_down_to_map_multiple(gap.start(), pages_per_map);
-            let aligned_end_page = self.round_page_num_up_to_map_multiple(gap.end(), pages_per_map);
-
-            let gap_size = aligned_end_page - aligned_start_page;
-            if gap_size >= rounded_num_pages {
-                return Some(ie(aligned_end_page - rounded_num_pages, aligned_end_page));
-            }
-        }
-
-        None
+        // Mappings go below the mmap top, and above it only once the space below is exhausted
+        self.find_map_space_in(self.start_address, self.mmap_top, num_pages, pages_per_map)
+            .or_else(|| {
+                self.find_map_space_in(self.mmap_top, self.end_address, num_pages, pages_per_map)
+            })
     }
 
     /// Finds aligned space above a hint address
@@ -2503,4 +2725,160 @@ mod tests {
         let result = vmmap.calculate_page_range(0, 1);
         assert_eq!(result, Some((0, 1)), "1 byte should still be 1 page");
     }
+
+    /// Test: is_range_free detects overlap and the end of the vmmap
+    /// Expected: Only ranges that touch no entry and fit below end_address are free
+    #[test]
+    fn test_is_range_free() {
+        let mut vmmap = test_vmmap();
+        vmmap.start_address = 0;
+        vmmap.end_address = 100;
+
+        vmmap
+            .add_entry_with_overwrite(
+                10,
+                5,
+                PROT_READ | PROT_WRITE,
+                PROT_READ | PROT_WRITE,
+                0,
+                MemoryBackingType::Anonymous,
+                0,
+                0,
+                0,
+            )
+            .unwrap();
+
+        assert!(
+            vmmap.is_range_free(15, 10),
+            "Range right after entry is free"
+        );
+        assert!(
+            vmmap.is_range_free(0, 10),
+            "Range right before entry is free"
+        );
+        assert!(
+            !vmmap.is_range_free(14, 2),
+            "Range overlapping the entry tail"
+        );
+        assert!(
+            !vmmap.is_range_free(5, 6),
+            "Range overlapping the entry head"
+        );
+        assert!(!vmmap.is_range_free(95, 10), "Range past end_address");
+        assert!(!vmmap.is_range_free(u32::MAX, 2), "Range that overflows");
+    }
+
+    /// Test: set_memory64 widens the address space of the vmmap
+    /// Verifies that a 64-bit cage places mappings above 4 GiB and returns their page number
+    #[test]
+    fn test_memory64_address_space() {
+        let mut vmmap = test_vmmap();
+        assert_eq!(vmmap.address_space_size(), 1 << 32);
+
+        vmmap.set_memory64(true);
+        assert_eq!(vmmap.address_space_size(), MAX_LINEAR_MEMORY64_SIZE + 1);
+
+        // An empty vmmap is filled from the top of the address space
+        let space = vmmap.find_map_space(16, 1).unwrap();
+        let useraddr = (space.start() as u64) << PAGESHIFT;
+        assert!(useraddr >= 1 << 32, "Should map above 4 GiB");
+        assert_eq!(vmmap.user_addr_to_retval(useraddr), space.start() as i32);
+        assert_eq!(vmmap.sys_to_user(vmmap.user_to_sys(useraddr)), useraddr);
+
+        vmmap.set_memory64(false);
+        assert_eq!(vmmap.user_addr_to_retval(0x1000), 0x1000);
+    }
+
+    /// Test: find_map_space places mappings below mmap_top first
+    /// Verifies that the space above mmap_top is only used once the space below is exhausted
+    #[test]
+    fn test_find_map_space_below_mmap_top() {
+        let mut vmmap = test_vmmap();
+        vmmap.end_address = 1000;
+        vmmap.mmap_top = 900;
+
+        let space = vmmap.find_map_space(10, 1).unwrap();
+        assert!(space.start() >= 880 && space.end() < 900);
+
+        vmmap
+            .add_entry_with_overwrite(
+                0,
+                900,
+                PROT_READ,
+                PROT_READ,
+                0,
+                MemoryBackingType::Anonymous,
+                0,
+                0,
+                0,
+            )
+            .unwrap();
+        let space = vmmap.find_map_space(10, 1).unwrap();
+        assert!(space.start() >= 900 && space.end() < 1000);
+    }
+
+    /// Test: find_stack_space leaves a random gap above the stack
+    /// Verifies that the stack is never placed right below the mmap top with a generator
+    #[test]
+    fn test_find_stack_space_leaves_gap() {
+        let mut vmmap = test_vmmap();
+        let top = vmmap.find_map_space(16, 1).unwrap();
+        assert_eq!(vmmap.find_stack_space(16, 1).unwrap(), top);
+        assert!(vmmap.entries.is_empty(), "No gap is reserved without ASLR");
+
+        vmmap.aslr = Some(AslrRng::new(3));
+        let mut gaps = Vec::new();
+        for _ in 0..8 {
+            let stack = vmmap.find_stack_space(16, 1).unwrap();
+            gaps.push(top.start() - stack.start());
+            // The gap is taken until the stack below it is gone
+            let gap = vmmap.find_page(stack.end() + 1).unwrap();
+            assert_eq!((gap.prot, gap.maxprot), (PROT_NONE, PROT_NONE));
+            assert_eq!(gap.page_num + gap.npages, top.end() + 1);
+            assert!(!vmmap.is_range_free(stack.end() + 1, 1));
+            vmmap.release_stack_gap(stack.end() + 1);
+            assert!(
+                vmmap.entries.is_empty(),
+                "The gap is released without a stack"
+            );
+        }
+        assert!(gaps
+            .iter()
+            .all(|&gap| gap <= vmmap.end_address >> STACK_RND_SHIFT));
+        assert!(gaps.iter().any(|&gap| gap != gaps[0]));
+    }
+
+    /// Test: release_stack_gap keeps the gap of a stack that is still mapped
+    #[test]
+    fn test_release_stack_gap_keeps_gap_of_mapped_stack() {
+        let mut vmmap = test_vmmap();
+        vmmap.aslr = Some(AslrRng::new(5));
+        let stack = loop {
+            let stack = vmmap.find_stack_space(16, 1).unwrap();
+            if vmmap.find_page(stack.end() + 1).is_some() {
+                break stack;
+            }
+        };
+        vmmap.add_entry(VmmapEntry::new(
+            stack.start(),
+            16,
+            PROT_READ | PROT_WRITE,
+            PROT_READ | PROT_WRITE,
+            (MAP_PRIVATE | MAP_ANONYMOUS | MAP_STACK) as i32,
+            false,
+            0,
+            0,
+            1,
+            MemoryBackingType::Anonymous,
+        ));
+        vmmap.release_stack_gap(stack.end() + 1);
+        assert!(vmmap.find_page(stack.end() + 1).is_some());
+
+        // Only a page where a gap starts releases it
+        vmmap.remove_entry(stack.start(), 16).unwrap();
+        vmmap.release_stack_gap(stack.end() + 2);
+        assert!(vmmap.find_page(stack.end() + 1).is_some());
+        vmmap.release_stack_gap(stack.end() + 1);
+        assert!(vmmap.entries.is_empty());
+    }
 }
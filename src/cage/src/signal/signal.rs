@@ -1,4 +1,5 @@
 use crate::cage::get_cage;
+use crate::futex::exit_robust_list;
 use std::sync::atomic::{AtomicPtr, Ordering};
 use sysdefs::constants::{SA_NODEFER, SA_RESETHAND, SIG_DFL};
 use sysdefs::lind_log;
@@ -61,6 +62,28 @@ pub fn signal_epoch_trigger(cageid: u64) {
     }
 }
 
+// switch the epoch of the main thread of the cage to "signal" state, and interrupt the host
+// syscall the thread may be blocked in
+// If the thread is blocked in a host syscall (read, write, futex, etc.), the epoch check will
+// never run because wasm isn't executing. SIGUSR2 is sent to the main thread's OS tid to
+// interrupt the blocking syscall with EINTR, allowing the thread to return to wasm and see the
+// epoch change.
+// thread safety: this function could possibly be invoked by multiple threads of the same cage
+pub fn signal_interrupt(cageid: u64) {
+    signal_epoch_trigger(cageid);
+
+    let cage = match get_cage(cageid) {
+        Some(c) => c,
+        None => return,
+    };
+    let main_tid = *cage.main_threadid.read();
+    if let Some(os_tid) = cage.os_tid_map.get(&main_tid) {
+        unsafe {
+            libc::syscall(libc::SYS_tkill, *os_tid as i32, libc::SIGUSR2);
+        }
+    };
+}
+
 // Atomically claim the exit_group for this cage. Returns true if this
 // thread won the race (and should do the full exit_group), false if
 // another thread already initiated exit_group (caller should just
@@ -436,19 +459,7 @@ pub fn lind_send_signal(cageid: u64, signo: i32) -> bool {
 
             // we only trigger epoch if the signal is not blocked
             if !signal_check_block(cageid, signo) {
-                signal_epoch_trigger(cageid);
-
-                // If the target thread is blocked in a host syscall (read, write,
-                // futex, etc.), the epoch check will never run because wasm isn't
-                // executing. Send SIGUSR2 to the main thread's OS tid to interrupt
-                // the blocking syscall with EINTR, allowing the thread to return
-                // to wasm and see the epoch change.
-                let main_tid = *cage.main_threadid.read();
-                if let Some(os_tid) = cage.os_tid_map.get(&main_tid) {
-                    unsafe {
-                        libc::syscall(libc::SYS_tkill, *os_tid as i32, libc::SIGUSR2);
-                    }
-                }
+                signal_interrupt(cageid);
             }
         }
 
@@ -581,6 +592,18 @@ pub fn lind_signal_init(cageid: u64, epoch_handler: *mut u64, threadid: i32, is_
     cage.os_tid_map.insert(threadid, os_tid);
 }
 
+// find the Lind thread ID of the calling thread, from the OS thread ID it registered in
+// lind_signal_init
+pub fn current_thread_id(cageid: u64) -> Option<i32> {
+    let cage = get_cage(cageid)?;
+    let os_tid = unsafe { libc::syscall(libc::SYS_gettid) };
+    let entry = cage
+        .os_tid_map
+        .iter()
+        .find(|entry| *entry.value() == os_tid)?;
+    Some(*entry.key())
+}
+
 // clean up signal stuff for an exited thread
 // return true if this is the last thread in the cage, otherwise return false
 pub fn lind_thread_exit(cageid: u64, thread_id: u64) -> bool {
@@ -595,6 +618,9 @@ pub fn lind_thread_exit(cageid: u64, thread_id: u64) -> bool {
             return false;
         }
     };
+    // release the robust mutexes the thread still holds, so their waiters see EOWNERDEAD
+    exit_robust_list(&cage, thread_id as i32);
+
     // lock the main threadid until all the related fields including epoch_handler finishes its updating
     let mut threadid_guard = cage.main_threadid.write();
     let main_threadid = *threadid_guard as u64;
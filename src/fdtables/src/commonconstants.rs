@@ -1,9 +1,16 @@
 // This file exists to make it easier to vary a single file of constants
 // instead of editing each implementation...
 
-/// Per-process maximum number of fds...
+/// Per-process maximum number of fds...  This is the default soft limit
+/// (`RLIMIT_NOFILE`) each new cage starts with.  It is also the largest `nfds`
+/// supported by select, since an `fd_set` only has room for this many bits.
 pub const FD_PER_PROCESS_MAX: u64 = 1024;
 
+/// The default hard limit on a cage's fds.  A cage may raise its soft limit
+/// up to this value, but may never raise its hard limit (like an unprivileged
+/// process on Linux).  This doubles as the ceiling on any limit (`nr_open`).
+pub const FD_PER_PROCESS_HARD_MAX: u64 = 65536;
+
 // /// Use this to indicate there isn't a real fd backing an item
 //pub const NO_REAL_FD: u64 = 0xff_abcd_ef01;
 
@@ -50,10 +57,10 @@ pub const fn NULL_FUNC(_: FDTableEntry, _: u64) -> Result<(), i32> {
     Ok(())
 }
 
-// BUG / TODO: Use this in some sane way...
-#[allow(dead_code)]
-/// Global maximum number of fds... (checks may not be implemented)
-pub const TOTAL_FD_MAX: u64 = 4096;
+/// Default global maximum number of fds across all cages.  Exceeding this
+/// returns ENFILE.  The embedder may lower it with [`set_total_fd_max`](crate::set_total_fd_max), e.g.,
+/// to match what the host will actually let the process open.
+pub const TOTAL_FD_MAX: u64 = 1 << 20;
 
 // replicating these constants here so this can compile on systems other than
 // Linux...  Copied from Rust's libc.
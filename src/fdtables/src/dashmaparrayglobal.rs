@@ -1,10 +1,17 @@
-//  DashMap<u64,[Option<FDTableEntry>;FD_PER_PROCESSS_MAX]>  Space is ~24KB
+//  DashMap<u64,Box<[Option<FDTableEntry>]>>  Space is ~24KB
 //  per cage w/ 1024 fds?!?
-//      Static DashMap.  Let's see if having the FDTableEntries be a static
-//      array is any faster...
+//      Static DashMap.  Let's see if having the FDTableEntries be a fixed
+//      size array is any faster...  The array starts at FD_PER_PROCESS_MAX
+//      entries and is only reallocated if a cage raises its soft limit and
+//      actually uses fds above it.
 
 use crate::threei;
 
+use crate::fdlimits::{
+    copy_fd_limits, count_fd_added, count_fd_removed, init_fd_limits, remove_fd_limits,
+    reserve_total_fds, reset_fd_limits, soft_fd_limit,
+};
+
 use dashmap::DashMap;
 
 use lazy_static::lazy_static;
@@ -23,10 +30,9 @@ pub use super::commonconstants::*;
 pub const ALGONAME: &str = "DashMapArrayGlobal";
 
 // It's fairly easy to check the fd count on a per-process basis (I just check
-// when I would add a new fd).
-//
-// TODO: I will ignore the total limit for now.  I would ideally do this on
-// every creation, close, fork, etc. but it's a PITA to track this.
+// when I would add a new fd).  The total across all cages is bounded in
+// fdlimits.rs: new fds are reserved against the system-wide limit by
+// reserve_total_fds, which fails with ENFILE once the limit would be passed.
 
 // We will raise a panic anywhere we receive an unknown cageid.  This frankly
 // should not be possible and indicates some sort of internal error in our
@@ -34,8 +40,9 @@ pub const ALGONAME: &str = "DashMapArrayGlobal";
 // cage makes a call, will be handled by returning the appropriate errno.
 
 // In order to store this information, I'm going to use a DashMap which
-// has keys of (cageid:u64) and values that are an array of FD_PER_PROCESS_MAX
-// Option<FDTableEntry> items.
+// has keys of (cageid:u64) and values that are an array of (at least)
+// FD_PER_PROCESS_MAX Option<FDTableEntry> items.  Code below (like select)
+// relies on a row never being shorter than this.
 //
 //
 
@@ -43,7 +50,7 @@ pub const ALGONAME: &str = "DashMapArrayGlobal";
 lazy_static! {
 
     #[derive(Debug)]
-    static ref FDTABLE: DashMap<u64, [Option<FDTableEntry>;FD_PER_PROCESS_MAX as usize]> = {
+    static ref FDTABLE: DashMap<u64, Box<[Option<FDTableEntry>]>> = {
         let m = DashMap::new();
         // Insert a cage so that I have something to fork / test later, if need
         // be. Otherwise, I'm not sure how I get this started. I think this
@@ -68,6 +75,69 @@ lazy_static! {
 
 }
 
+// Allocate a new, empty row for a cage.
+fn new_fdrow() -> Box<[Option<FDTableEntry>]> {
+    vec![None; FD_PER_PROCESS_MAX as usize].into_boxed_slice()
+}
+
+// Reallocate a row so it has at least newlen entries.  Only called once all
+// of the entries below the soft limit have been used, so this is rare.
+fn grow_fdrow(myfdrow: &mut Box<[Option<FDTableEntry>]>, newlen: u64) {
+    if (myfdrow.len() as u64) < newlen {
+        let mut newrow = mem::take(myfdrow).into_vec();
+        newrow.resize(newlen as usize, None);
+        *myfdrow = newrow.into_boxed_slice();
+    }
+}
+
+// Look up an entry, treating fds past the end of the row as unused.
+fn get_fdrow_entry(myfdrow: &[Option<FDTableEntry>], virtualfd: u64) -> Option<FDTableEntry> {
+    myfdrow.get(virtualfd as usize).copied().flatten()
+}
+
+// Finds the lowest unused fd >= startfd and below the cage's soft limit,
+// growing the row if need be, and puts myentry there.
+fn insert_at_unused_fd(
+    cageid: u64,
+    myentry: FDTableEntry,
+    startfd: u64,
+) -> Result<u64, threei::RetVal> {
+    let reservation = reserve_total_fds(1)?;
+
+    let softlimit = soft_fd_limit(cageid);
+    let mut myfdrow = FDTABLE.get_mut(&cageid).unwrap();
+    let rowlen = myfdrow.len() as u64;
+
+    // Check the fds in order.
+    for fdcandidate in startfd..cmp::min(softlimit, rowlen) {
+        // FIXME: This is likely very slow.  Should do something smarter...
+        if myfdrow[fdcandidate as usize].is_none() {
+            // I just checked.  Should not be there...
+            myfdrow[fdcandidate as usize] = Some(myentry);
+            reservation.keep();
+            _increment_fdcount(myentry);
+            return Ok(fdcandidate);
+        }
+    }
+
+    // Everything in the row is used, but the soft limit allows more.  Double
+    // the row (up to the limit) and use the first new entry.
+    let fdcandidate = cmp::max(startfd, rowlen);
+    if fdcandidate < softlimit {
+        grow_fdrow(
+            &mut myfdrow,
+            cmp::min(softlimit, cmp::max(fdcandidate + 1, rowlen * 2)),
+        );
+        myfdrow[fdcandidate as usize] = Some(myentry);
+        reservation.keep();
+        _increment_fdcount(myentry);
+        return Ok(fdcandidate);
+    }
+
+    // I must have checked all fds and failed to find one open.  Fail!
+    Err(threei::Errno::EMFILE as u64)
+}
+
 #[doc = include_str!("../docs/check_cage_exists.md")]
 pub fn check_cage_exists(cageid: u64) -> bool {
     FDTABLE.contains_key(&cageid)
@@ -77,7 +147,8 @@ pub fn check_cage_exists(cageid: u64) -> bool {
 pub fn init_empty_cage(cageid: u64) {
     assert!(!check_cage_exists(cageid), "Known cageid in fdtable access");
 
-    FDTABLE.insert(cageid, [Option::None; FD_PER_PROCESS_MAX as usize]);
+    FDTABLE.insert(cageid, new_fdrow());
+    init_fd_limits(cageid);
 }
 
 #[doc = include_str!("../docs/translate_virtual_fd.md")]
@@ -90,13 +161,8 @@ pub fn translate_virtual_fd(cageid: u64, virtualfd: u64) -> Result<FDTableEntry,
         "Unknown cageid in fdtable access"
     );
 
-    // Below condition checks if the virtualfd is out of bounds and if yes it throws an error
-    // Note that this assumes that all virtualfd numbers returned < FD_PER_PROCESS_MAX
-    if virtualfd >= FD_PER_PROCESS_MAX {
-        return Err(threei::Errno::EBADFD as u64);
-    }
-
-    return match FDTABLE.get(&cageid).unwrap()[virtualfd as usize] {
+    // A virtualfd past the end of the row (out of bounds) is never in use.
+    return match get_fdrow_entry(&FDTABLE.get(&cageid).unwrap(), virtualfd) {
         Some(tableentry) => Ok(tableentry),
         None => Err(threei::Errno::EBADFD as u64),
     };
@@ -131,21 +197,7 @@ pub fn get_unused_virtual_fd(
         perfdinfo,
     };
 
-    let mut myfdrow = FDTABLE.get_mut(&cageid).unwrap();
-
-    // Check the fds in order.
-    for fdcandidate in 0..FD_PER_PROCESS_MAX {
-        // FIXME: This is likely very slow.  Should do something smarter...
-        if myfdrow[fdcandidate as usize].is_none() {
-            // I just checked.  Should not be there...
-            myfdrow[fdcandidate as usize] = Some(myentry);
-            _increment_fdcount(myentry);
-            return Ok(fdcandidate);
-        }
-    }
-
-    // I must have checked all fds and failed to find one open.  Fail!
-    Err(threei::Errno::EMFILE as u64)
+    insert_at_unused_fd(cageid, myentry, 0)
 }
 
 /// This is used to request an unused fd from specific starting position. This is
@@ -174,21 +226,12 @@ pub fn get_unused_virtual_fd_from_startfd(
         perfdinfo,
     };
 
-    let mut myfdrow = FDTABLE.get_mut(&cageid).unwrap();
-
-    // Check the fds in order.
-    for fdcandidate in arg..FD_PER_PROCESS_MAX {
-        // FIXME: This is likely very slow.  Should do something smarter...
-        if myfdrow[fdcandidate as usize].is_none() {
-            // I just checked.  Should not be there...
-            myfdrow[fdcandidate as usize] = Some(myentry);
-            _increment_fdcount(myentry);
-            return Ok(fdcandidate);
-        }
+    // Like F_DUPFD, a starting fd at or above the soft limit is invalid.
+    if arg >= soft_fd_limit(cageid) {
+        return Err(threei::Errno::EINVAL as u64);
     }
 
-    // I must have checked all fds and failed to find one open.  Fail!
-    Err(threei::Errno::EMFILE as u64)
+    insert_at_unused_fd(cageid, myentry, arg)
 }
 
 // This is used for things like dup2, which need a specific fd...
@@ -208,10 +251,9 @@ pub fn get_specific_virtual_fd(
     );
 
     // If you ask for a FD number that is too large, I'm going to reject it.
-    // Note that, I need to use the FD_PER_PROCESS_MAX setting because this
-    // is also how I'm tracking how many values you have open.  If this
-    // changed, then these constants could be decoupled...
-    if requested_virtualfd > FD_PER_PROCESS_MAX {
+    // Note that, I need to use the soft limit here because this is also how
+    // I'm tracking how many values you have open.
+    if requested_virtualfd >= soft_fd_limit(cageid) {
         return Err(threei::Errno::EBADF as u64);
     }
 
@@ -225,14 +267,34 @@ pub fn get_specific_virtual_fd(
         perfdinfo,
     };
 
+    let mut myfdrow = FDTABLE.get_mut(&cageid).unwrap();
+    let myoptionentry = get_fdrow_entry(&myfdrow, requested_virtualfd);
+
+    // Replacing an entry doesn't change the total number of fds...
+    if myoptionentry.is_none() {
+        reserve_total_fds(1)?.keep();
+    } else {
+        // ...the old entry is counted out when it is closed below
+        count_fd_added();
+    }
+
     // This is before the FDTABLE action, so if I decrement the same fd, it
     // calls the intermediate handler instead of the last one.
     _increment_fdcount(myentry);
-    let myoptionentry = FDTABLE.get(&cageid).unwrap()[requested_virtualfd as usize];
     // always add the new entry.  I'm doing this first, before I close
     // the old one because I need to ensure I've cleaned up state correctly
     // before calling the close handlers...
-    FDTABLE.get_mut(&cageid).unwrap()[requested_virtualfd as usize] = Some(myentry);
+    let rowlen = myfdrow.len() as u64;
+    grow_fdrow(
+        &mut myfdrow,
+        cmp::min(
+            soft_fd_limit(cageid),
+            cmp::max(requested_virtualfd + 1, rowlen * 2),
+        ),
+    );
+    myfdrow[requested_virtualfd as usize] = Some(myentry);
+    // Need to drop the lock, before calling the handlers.
+    drop(myfdrow);
 
     // Update the fdcount / close the old entry, if existed
     if let Some(entry) = myoptionentry {
@@ -251,7 +313,7 @@ pub fn set_cloexec(cageid: u64, virtualfd: u64, is_cloexec: bool) -> Result<(),
     );
 
     // return EBADFD, if the fd is missing...
-    if FDTABLE.get(&cageid).unwrap()[virtualfd as usize].is_none() {
+    if get_fdrow_entry(&FDTABLE.get(&cageid).unwrap(), virtualfd).is_none() {
         return Err(threei::Errno::EBADFD as u64);
     }
     // Set the is_cloexec flag
@@ -271,7 +333,7 @@ pub fn set_perfdinfo(cageid: u64, virtualfd: u64, perfdinfo: u64) -> Result<(),
     );
 
     // return EBADFD, if the fd is missing...
-    if FDTABLE.get(&cageid).unwrap()[virtualfd as usize].is_none() {
+    if get_fdrow_entry(&FDTABLE.get(&cageid).unwrap(), virtualfd).is_none() {
         return Err(threei::Errno::EBADFD as u64);
     }
 
@@ -297,19 +359,22 @@ pub fn copy_fdtable_for_cage(srccageid: u64, newcageid: u64) -> Result<(), three
 
     // Insert a copy and ensure it didn't exist...
     // I've checked this should be a copy, not a ref to the same thing.
-    let hmcopy = *FDTABLE.get(&srccageid).unwrap();
+    let hmcopy = FDTABLE.get(&srccageid).unwrap().clone();
+
+    // The new cage's fds all count against the global limit.
+    let numfds = hmcopy.iter().flatten().count() as u64;
+    reserve_total_fds(numfds)
+        .map_err(|_| threei::Errno::ENFILE)?
+        .keep();
 
     // Increment copied items
-    for entry in FDTABLE.get(&srccageid).unwrap().iter() {
-        if entry.is_some() {
-            _increment_fdcount(entry.unwrap());
-        }
+    for entry in hmcopy.iter().flatten() {
+        _increment_fdcount(*entry);
     }
 
     assert!(FDTABLE.insert(newcageid, hmcopy).is_none());
+    copy_fd_limits(srccageid, newcageid);
 
-    // I'm not going to bother to check the number of fds used overall yet...
-    //    Err(threei::Errno::EMFILE as u64),
     Ok(())
 }
 
@@ -320,8 +385,9 @@ pub fn remove_cage_from_fdtable(cageid: u64) {
     // In multi-cage (grate) scenarios, concurrent exit paths may race to
     // remove the same cage. If already removed, there's nothing to clean up.
     if let Some((_, myfdrow)) = FDTABLE.remove(&cageid) {
+        remove_fd_limits(cageid);
         // Take only the Some items in here (clippy suggested)
-        for entry in myfdrow.into_iter().flatten() {
+        for entry in myfdrow.iter().flatten().copied() {
             match _decrement_fdcount(entry) {
                 Ok(()) => (),
                 Err(errno) => panic!(
@@ -347,7 +413,7 @@ pub fn empty_fds_for_exec(cageid: u64) {
     // get vector of them to do the operation on...
     let mut closevec = Vec::new();
 
-    for item in 0..FD_PER_PROCESS_MAX as usize {
+    for item in 0..myfdrow.len() {
         if myfdrow[item].is_some() && myfdrow[item].unwrap().should_cloexec {
             // handle this in a moment...
             closevec.push(myfdrow[item].unwrap());
@@ -386,7 +452,7 @@ pub fn return_fdtable_copy(cageid: u64) -> HashMap<u64, FDTableEntry> {
     let mut myhashmap = HashMap::new();
 
     let myfdrow = FDTABLE.get(&cageid).unwrap();
-    for item in 0..FD_PER_PROCESS_MAX as usize {
+    for item in 0..myfdrow.len() {
         if myfdrow[item].is_some() {
             myhashmap.insert(item as u64, myfdrow[item].unwrap());
         }
@@ -421,12 +487,6 @@ lazy_static! {
 
 #[doc = include_str!("../docs/close_virtualfd.md")]
 pub fn close_virtualfd(cageid: u64, virtfd: u64) -> Result<(), threei::RetVal> {
-    // Below condition checks if the virtualfd is out of bounds and if yes it throws an error
-    // Note that this assumes that all virtualfd numbers returned < FD_PER_PROCESS_MAX
-    if virtfd >= FD_PER_PROCESS_MAX {
-        return Err(threei::Errno::EBADFD as u64);
-    }
-
     assert!(
         check_cage_exists(cageid),
         "Unknown cageid in fdtable access"
@@ -435,7 +495,9 @@ pub fn close_virtualfd(cageid: u64, virtfd: u64) -> Result<(), threei::RetVal> {
     let entry = {
         let mut myfdrow = FDTABLE.get_mut(&cageid).unwrap();
 
-        match myfdrow[virtfd as usize].take() {
+        // Below condition checks if the virtualfd is out of bounds and if
+        // yes it throws an error
+        match myfdrow.get_mut(virtfd as usize).and_then(Option::take) {
             Some(entry) => entry,
             None => return Err(threei::Errno::EBADFD as u64),
         }
@@ -467,6 +529,8 @@ pub fn register_close_handlers(
 fn _decrement_fdcount(entry: FDTableEntry) -> Result<(), i32> {
     let mytuple = (entry.fdkind, entry.underfd);
 
+    count_fd_removed();
+
     let intermediatech;
     let lastch;
 
@@ -615,8 +679,9 @@ pub fn get_bitmask_for_select(
 
     // dashmaps are lockless, but usually I would grab a lock on the fdtable
     // here...
+    // The row is never shorter than FD_PER_PROCESS_MAX, which bounds nfds.
     let binding = FDTABLE.get(&cageid).unwrap();
-    let myfdrow = *binding.value();
+    let myfdrow = binding.value();
 
     // Clippy is somehow missing how the virtualfd is being used throughout
     // here.  It's not just a range value
@@ -789,14 +854,14 @@ pub fn convert_virtualfds_for_poll(
         "Unknown cageid in fdtable access"
     );
 
-    let thefdrow = *FDTABLE.get(&cageid).unwrap();
+    let thefdrow = FDTABLE.get(&cageid).unwrap();
     let mut mappingtable: HashMap<(u32, u64), u64> = HashMap::new();
     let mut rethashmap: HashMap<u32, HashSet<(u64, FDTableEntry)>> = HashMap::new();
 
     // BUG?: I'm ignoring the fact that virtualfds can show up multiple times.
     // I'm not sure this actually matters, but I didn't think hard about it.
     for virtfd in virtualfds {
-        if let Some(entry) = thefdrow[virtfd as usize] {
+        if let Some(entry) = get_fdrow_entry(&thefdrow, virtfd) {
             // Insert an empty HashSet, if needed
             rethashmap.entry(entry.fdkind).or_default();
             mappingtable
@@ -946,7 +1011,7 @@ lazy_static! {
 
 fn _get_epoll_entrynum_or_error(cageid: u64, epfd: u64) -> Result<u64, threei::RetVal> {
     // Is the epfd ok?
-    match FDTABLE.get(&cageid).unwrap()[epfd as usize] {
+    match get_fdrow_entry(&FDTABLE.get(&cageid).unwrap(), epfd) {
         None => Err(threei::Errno::EBADF as u64),
         Some(tableentry) => {
             // You must call this on an epoll fd
@@ -1060,7 +1125,7 @@ pub fn virtualize_epoll_ctl(
 
     let virtfdkind: u32;
 
-    if let Some(tableentry) = FDTABLE.get(&cageid).unwrap()[virtfd as usize] {
+    if let Some(tableentry) = get_fdrow_entry(&FDTABLE.get(&cageid).unwrap(), virtfd) {
         // Right now, I don't support this, so error...
         if tableentry.fdkind == FDT_KINDEPOLL {
             // TODO: support EPOLLFDs...
@@ -1150,10 +1215,8 @@ pub fn get_virtual_epoll_wait_data(
 // This is only used in tests, thus is hidden...
 pub fn refresh() {
     FDTABLE.clear();
-    FDTABLE.insert(
-        threei::TESTING_CAGEID,
-        [Option::None; FD_PER_PROCESS_MAX as usize],
-    );
+    reset_fd_limits();
+    FDTABLE.insert(threei::TESTING_CAGEID, new_fdrow());
     let mut closehandlers = CLOSEHANDLERTABLE.lock().unwrap_or_else(|e| {
         CLOSEHANDLERTABLE.clear_poison();
         e.into_inner()
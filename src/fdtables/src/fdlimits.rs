@@ -0,0 +1,154 @@
+// Per-cage and global limits on the number of fds.  These are shared by all
+// of the fdtable implementations, so they live here rather than being
+// duplicated in each one.  Linux has two errors for this:
+//
+//       EMFILE The per-process limit on the number of open file
+//              descriptors has been reached.
+//
+//       ENFILE The system-wide limit on the total number of open files
+//              has been reached.
+//
+// Each cage has a (soft, hard) pair, like RLIMIT_NOFILE.  The soft limit is
+// the bound on the fd numbers a cage may be handed (so it both caps the count
+// and the value of a new fd).  The global count is the number of virtual fds
+// across all cages, which is what ENFILE is checked against.
+
+use crate::commonconstants::{FD_PER_PROCESS_HARD_MAX, FD_PER_PROCESS_MAX, TOTAL_FD_MAX};
+use crate::threei;
+
+use dashmap::DashMap;
+
+use lazy_static::lazy_static;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+lazy_static! {
+    // cageid -> (soft, hard).  A missing cage uses the defaults.
+    #[derive(Debug)]
+    static ref FDLIMITS: DashMap<u64, (u64, u64)> = {
+        DashMap::new()
+    };
+}
+
+// The number of virtual fds in all cages' tables...
+static TOTAL_FD_COUNT: AtomicU64 = AtomicU64::new(0);
+
+// ...and the most that may be open before we return ENFILE.
+static TOTAL_FD_LIMIT: AtomicU64 = AtomicU64::new(TOTAL_FD_MAX);
+
+#[doc = include_str!("../docs/get_fd_limits.md")]
+#[must_use] // must use the return value if you call it.
+pub fn get_fd_limits(cageid: u64) -> (u64, u64) {
+    FDLIMITS
+        .get(&cageid)
+        .map_or((FD_PER_PROCESS_MAX, FD_PER_PROCESS_HARD_MAX), |limits| {
+            *limits
+        })
+}
+
+#[doc = include_str!("../docs/set_fd_limits.md")]
+pub fn set_fd_limits(cageid: u64, soft: u64, hard: u64) -> Result<(), threei::RetVal> {
+    if soft > hard {
+        return Err(threei::Errno::EINVAL as u64);
+    }
+
+    let (_, oldhard) = get_fd_limits(cageid);
+    // Only a lower (or the same) hard limit is allowed.  There is no notion
+    // of a privileged cage here.
+    if hard > oldhard || hard > FD_PER_PROCESS_HARD_MAX {
+        return Err(threei::Errno::EPERM as u64);
+    }
+
+    FDLIMITS.insert(cageid, (soft, hard));
+    Ok(())
+}
+
+/// Returns the global limit on the number of virtual fds across all cages.
+#[must_use] // must use the return value if you call it.
+pub fn get_total_fd_max() -> u64 {
+    TOTAL_FD_LIMIT.load(Ordering::Relaxed)
+}
+
+/// Sets the global limit on the number of virtual fds across all cages.
+/// Requests that would go over this fail with ENFILE.  Fds that are already
+/// open are not affected if this is lowered below the current count.
+pub fn set_total_fd_max(newmax: u64) {
+    TOTAL_FD_LIMIT.store(newmax, Ordering::Relaxed);
+}
+
+/// Returns the number of virtual fds currently open across all cages.
+#[must_use] // must use the return value if you call it.
+pub fn get_total_fd_count() -> u64 {
+    TOTAL_FD_COUNT.load(Ordering::Relaxed)
+}
+
+// The rest are helpers for the fdtable implementations...
+
+// Set up the default limits for a new cage.
+pub(crate) fn init_fd_limits(cageid: u64) {
+    FDLIMITS.insert(cageid, (FD_PER_PROCESS_MAX, FD_PER_PROCESS_HARD_MAX));
+}
+
+// A forked cage inherits its parent's limits.
+pub(crate) fn copy_fd_limits(srccageid: u64, newcageid: u64) {
+    FDLIMITS.insert(newcageid, get_fd_limits(srccageid));
+}
+
+pub(crate) fn remove_fd_limits(cageid: u64) {
+    FDLIMITS.remove(&cageid);
+}
+
+// The first fd number a cage may not be given.
+pub(crate) fn soft_fd_limit(cageid: u64) -> u64 {
+    get_fd_limits(cageid).0
+}
+
+// Fds reserved against the global limit by reserve_total_fds().  Dropping
+// the reservation gives them back, so that an allocation that fails after
+// reserving (with EMFILE, say) doesn't leak them.  keep() makes them part of
+// the count for good, once the fds are in a table.
+#[must_use]
+pub(crate) struct TotalFdReservation(u64);
+
+impl TotalFdReservation {
+    pub(crate) fn keep(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for TotalFdReservation {
+    fn drop(&mut self) {
+        TOTAL_FD_COUNT.fetch_sub(self.0, Ordering::Relaxed);
+    }
+}
+
+// Reserves newfds more fds, or returns ENFILE if that would exceed the global
+// limit.  The check and the add are a single atomic step, so that cages
+// allocating at the same time can't overshoot the limit together.
+pub(crate) fn reserve_total_fds(newfds: u64) -> Result<TotalFdReservation, threei::RetVal> {
+    let limit = get_total_fd_max();
+    TOTAL_FD_COUNT
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+            count.checked_add(newfds).filter(|total| *total <= limit)
+        })
+        .map(|_| TotalFdReservation(newfds))
+        .map_err(|_| threei::Errno::ENFILE as u64)
+}
+
+// New fds are counted by reserve_total_fds().  This counts an fd that
+// replaces another in a table, which count_fd_removed() counts out when the
+// old one is closed, so the total stays the same.
+pub(crate) fn count_fd_added() {
+    TOTAL_FD_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn count_fd_removed() {
+    TOTAL_FD_COUNT.fetch_sub(1, Ordering::Relaxed);
+}
+
+// Used by refresh() when testing...
+pub(crate) fn reset_fd_limits() {
+    FDLIMITS.clear();
+    TOTAL_FD_COUNT.store(0, Ordering::Relaxed);
+    TOTAL_FD_LIMIT.store(TOTAL_FD_MAX, Ordering::Relaxed);
+}
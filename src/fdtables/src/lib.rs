@@ -100,17 +100,15 @@
 
 // Please see the doc strings for more information about the implementations.
 
-// This library is likely the place in the system where we should consider
-// putting in place limits on file descriptors.  Linux does this through two
-// error codes, one for a per-process limit and the other for an overall system
-// limit.  My thinking currently is that both will be configurable values in
-// the library.
+// This library is where limits on file descriptors are enforced.  Linux does
+// this through two error codes, one for a per-process limit and the other for
+// an overall system limit.  Both are configurable (see fdlimits.rs).
 //
 //       EMFILE The per-process limit on the number of open file
 //              descriptors has been reached.
 //
 //       ENFILE The system-wide limit on the total number of open files
-//              has been reached. (mostly unimplemented)
+//              has been reached.
 
 // The specific implementation of the algorithm is selected via a Cargo feature
 // (see Cargo.toml `[features]`). Exactly one impl feature must be enabled; the
@@ -168,6 +166,13 @@ pub use vanillaglobal::*;
 mod commonconstants;
 pub use commonconstants::*;
 
+// The per-cage and global fd limits.  These are common to all of the
+// implementations above.
+mod fdlimits;
+pub use fdlimits::{
+    get_fd_limits, get_total_fd_count, get_total_fd_max, set_fd_limits, set_total_fd_max,
+};
+
 // This is used everywhere...  Should I re-export more of these symbols?
 pub mod threei;
 /// Error values (matching errno in Linux) for the various call Results
@@ -1411,6 +1416,199 @@ mod tests {
         }
     }
 
+    // Raising the soft limit should let a cage go past FD_PER_PROCESS_MAX...
+    #[test]
+    fn raise_soft_fd_limit_test() {
+        let mut _thelock = TESTMUTEX.lock().unwrap_or_else(|e| {
+            refresh();
+            TESTMUTEX.clear_poison();
+            e.into_inner()
+        });
+        refresh();
+
+        const FD: u64 = 10;
+        let newsoft = FD_PER_PROCESS_MAX * 2;
+        set_fd_limits(threei::TESTING_CAGEID, newsoft, FD_PER_PROCESS_HARD_MAX).unwrap();
+        assert_eq!(
+            get_fd_limits(threei::TESTING_CAGEID),
+            (newsoft, FD_PER_PROCESS_HARD_MAX)
+        );
+
+        for current in 0..newsoft {
+            assert_eq!(
+                get_unused_virtual_fd(threei::TESTING_CAGEID, 0, FD, false, 100).unwrap(),
+                current
+            );
+        }
+        assert_eq!(
+            get_unused_virtual_fd(threei::TESTING_CAGEID, 0, FD, false, 100),
+            Err(threei::Errno::EMFILE as u64)
+        );
+        assert_eq!(get_total_fd_count(), newsoft);
+
+        // dup2 style requests obey the same bound...
+        get_specific_virtual_fd(threei::TESTING_CAGEID, newsoft - 1, 0, FD, false, 5).unwrap();
+        assert_eq!(
+            translate_virtual_fd(threei::TESTING_CAGEID, newsoft - 1)
+                .unwrap()
+                .perfdinfo,
+            5
+        );
+        assert_eq!(
+            get_specific_virtual_fd(threei::TESTING_CAGEID, newsoft, 0, FD, false, 5),
+            Err(threei::Errno::EBADF as u64)
+        );
+
+        // A closed fd above the old limit can be reused...
+        close_virtualfd(threei::TESTING_CAGEID, 1500).unwrap();
+        assert_eq!(
+            get_unused_virtual_fd_from_startfd(threei::TESTING_CAGEID, 0, FD, false, 100, 1100)
+                .unwrap(),
+            1500
+        );
+        refresh();
+    }
+
+    #[test]
+    fn fd_limits_errors_test() {
+        let mut _thelock = TESTMUTEX.lock().unwrap_or_else(|e| {
+            refresh();
+            TESTMUTEX.clear_poison();
+            e.into_inner()
+        });
+        refresh();
+
+        let cageid = threei::TESTING_CAGEID;
+        assert_eq!(
+            set_fd_limits(cageid, 20, 10),
+            Err(threei::Errno::EINVAL as u64)
+        );
+        assert_eq!(
+            set_fd_limits(cageid, 10, FD_PER_PROCESS_HARD_MAX + 1),
+            Err(threei::Errno::EPERM as u64)
+        );
+
+        // Lowering the hard limit can't be undone.
+        set_fd_limits(cageid, 10, 100).unwrap();
+        assert_eq!(
+            set_fd_limits(cageid, 10, 101),
+            Err(threei::Errno::EPERM as u64)
+        );
+        set_fd_limits(cageid, 100, 100).unwrap();
+
+        for _ in 0..8 {
+            get_unused_virtual_fd(cageid, 0, 10, false, 0).unwrap();
+        }
+
+        // Lowering the soft limit below the open fds leaves them open, but no
+        // new ones may be handed out.
+        set_fd_limits(cageid, 4, 100).unwrap();
+        assert!(translate_virtual_fd(cageid, 7).is_ok());
+        assert_eq!(
+            get_unused_virtual_fd(cageid, 0, 10, false, 0),
+            Err(threei::Errno::EMFILE as u64)
+        );
+        assert_eq!(
+            get_unused_virtual_fd_from_startfd(cageid, 0, 10, false, 0, 4),
+            Err(threei::Errno::EINVAL as u64)
+        );
+        assert_eq!(
+            get_specific_virtual_fd(cageid, 4, 0, 10, false, 0),
+            Err(threei::Errno::EBADF as u64)
+        );
+
+        // A forked cage inherits the limits, and they go away with the cage.
+        copy_fdtable_for_cage(cageid, threei::TESTING_CAGEID1).unwrap();
+        assert_eq!(get_fd_limits(threei::TESTING_CAGEID1), (4, 100));
+        remove_cage_from_fdtable(threei::TESTING_CAGEID1);
+        assert_eq!(
+            get_fd_limits(threei::TESTING_CAGEID1),
+            (FD_PER_PROCESS_MAX, FD_PER_PROCESS_HARD_MAX)
+        );
+        refresh();
+    }
+
+    #[test]
+    fn total_fd_max_test() {
+        let mut _thelock = TESTMUTEX.lock().unwrap_or_else(|e| {
+            refresh();
+            TESTMUTEX.clear_poison();
+            e.into_inner()
+        });
+        refresh();
+
+        let cageid = threei::TESTING_CAGEID;
+        let fd0 = get_unused_virtual_fd(cageid, 0, 10, false, 0).unwrap();
+        assert_eq!(get_total_fd_count(), 1);
+
+        set_total_fd_max(2);
+        get_unused_virtual_fd(cageid, 0, 10, false, 0).unwrap();
+        assert_eq!(
+            get_unused_virtual_fd(cageid, 0, 10, false, 0),
+            Err(threei::Errno::ENFILE as u64)
+        );
+        assert_eq!(
+            get_specific_virtual_fd(cageid, 20, 0, 10, false, 0),
+            Err(threei::Errno::ENFILE as u64)
+        );
+        // Replacing an fd doesn't add one, so this is fine...
+        get_specific_virtual_fd(cageid, fd0, 0, 11, false, 0).unwrap();
+        assert_eq!(get_total_fd_count(), 2);
+
+        // Nor is there room to fork...
+        assert_eq!(
+            copy_fdtable_for_cage(cageid, threei::TESTING_CAGEID1),
+            Err(threei::Errno::ENFILE)
+        );
+        assert!(!check_cage_exists(threei::TESTING_CAGEID1));
+
+        // Closing one makes room again.
+        close_virtualfd(cageid, fd0).unwrap();
+        assert_eq!(get_total_fd_count(), 1);
+        assert!(get_unused_virtual_fd(cageid, 0, 10, false, 0).is_ok());
+
+        refresh();
+        assert_eq!(get_total_fd_max(), TOTAL_FD_MAX);
+    }
+
+    #[test]
+    fn total_fd_max_concurrent_test() {
+        let mut _thelock = TESTMUTEX.lock().unwrap_or_else(|e| {
+            refresh();
+            TESTMUTEX.clear_poison();
+            e.into_inner()
+        });
+        refresh();
+
+        let cageid = threei::TESTING_CAGEID;
+        // A failed allocation gives back what it reserved.
+        set_fd_limits(cageid, 1, FD_PER_PROCESS_HARD_MAX).unwrap();
+        get_unused_virtual_fd(cageid, 0, 10, false, 0).unwrap();
+        assert_eq!(
+            get_unused_virtual_fd(cageid, 0, 10, false, 0),
+            Err(threei::Errno::EMFILE as u64)
+        );
+        assert_eq!(get_total_fd_count(), 1);
+        refresh();
+
+        // Allocations racing for the last fds can't go over the limit.
+        set_total_fd_max(100);
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                thread::spawn(move || {
+                    (0..50)
+                        .filter(|_| get_unused_virtual_fd(cageid, 0, 10, false, 0).is_ok())
+                        .count()
+                })
+            })
+            .collect();
+        let allocated: usize = threads.into_iter().map(|t| t.join().unwrap()).sum();
+        assert_eq!(allocated, 100);
+        assert_eq!(get_total_fd_count(), 100);
+
+        refresh();
+    }
+
     #[test]
     // Do we close a virtualfd when we select it?  (Do nothing, but see the
     // next test.)
@@ -1,5 +1,10 @@
 use crate::threei;
 
+use crate::fdlimits::{
+    copy_fd_limits, count_fd_added, count_fd_removed, init_fd_limits, remove_fd_limits,
+    reserve_total_fds, reset_fd_limits, soft_fd_limit,
+};
+
 use lazy_static::lazy_static;
 
 use std::collections::HashMap;
@@ -17,10 +22,8 @@ pub use super::commonconstants::*;
 pub const ALGONAME: &str = "VanillaGlobal";
 
 // It's fairly easy to check the fd count on a per-process basis (I just check
-// when I would add a new fd).
-//
-// BUG: I will ignore the total limit for now.  I would ideally do this on
-// every creation, close, fork, etc. but it's a PITA to track this.
+// when I would add a new fd).  The total count is tracked in fdlimits.rs by
+// _increment_fdcount and _decrement_fdcount, which see every add and remove.
 
 // We will raise a panic anywhere we receive an unknown cageid.  This frankly
 // should not be possible and indicates some sort of internal error in our
@@ -84,6 +87,7 @@ pub fn init_empty_cage(cageid: u64) {
     }
 
     fdtable.insert(cageid, HashMap::new());
+    init_fd_limits(cageid);
 }
 
 #[doc = include_str!("../docs/translate_virtual_fd.md")]
@@ -100,8 +104,8 @@ pub fn translate_virtual_fd(cageid: u64, virtualfd: u64) -> Result<FDTableEntry,
     }
 
     // Below condition checks if the virtualfd is out of bounds and if yes it throws an error
-    // Note that this assumes that all virtualfd numbers returned < FD_PER_PROCESS_MAX
-    if virtualfd >= FD_PER_PROCESS_MAX {
+    // Note that this assumes that all virtualfd numbers returned < FD_PER_PROCESS_HARD_MAX
+    if virtualfd >= FD_PER_PROCESS_HARD_MAX {
         return Err(threei::Errno::EBADFD as u64);
     }
 
@@ -141,15 +145,19 @@ pub fn get_unused_virtual_fd(
         perfdinfo,
     };
 
+    let reservation = reserve_total_fds(1)?;
+    let softlimit = soft_fd_limit(cageid);
+
     let myfdmap = fdtable.get_mut(&cageid).unwrap();
 
     // Check the fds in order.
-    for fdcandidate in 0..FD_PER_PROCESS_MAX {
+    for fdcandidate in 0..softlimit {
         // Get the entry if it's Vacant and assign it to e (so I can fill
         // it in).
         if let std::collections::hash_map::Entry::Vacant(e) = myfdmap.entry(fdcandidate) {
             e.insert(myentry);
             drop(fdtable);
+            reservation.keep();
             _increment_fdcount(myentry);
             return Ok(fdcandidate);
         }
@@ -186,15 +194,23 @@ pub fn get_unused_virtual_fd_from_startfd(
         perfdinfo,
     };
 
+    // Like F_DUPFD, a starting fd at or above the soft limit is invalid.
+    let softlimit = soft_fd_limit(cageid);
+    if arg >= softlimit {
+        return Err(threei::Errno::EINVAL as u64);
+    }
+    let reservation = reserve_total_fds(1)?;
+
     let myfdmap = fdtable.get_mut(&cageid).unwrap();
 
     // Check the fds in order.
-    for fdcandidate in arg..FD_PER_PROCESS_MAX {
+    for fdcandidate in arg..softlimit {
         // Get the entry if it's Vacant and assign it to e (so I can fill
         // it in).
         if let std::collections::hash_map::Entry::Vacant(e) = myfdmap.entry(fdcandidate) {
             e.insert(myentry);
             drop(fdtable);
+            reservation.keep();
             _increment_fdcount(myentry);
             return Ok(fdcandidate);
         }
@@ -222,13 +238,24 @@ pub fn get_specific_virtual_fd(
     }
 
     // If you ask for a FD number that is too large, I'm going to reject it.
-    // Note that, I need to use the FD_PER_PROCESS_MAX setting because this
-    // is also how I'm tracking how many values you have open.  If this
-    // changed, then these constants could be decoupled...
-    if requested_virtualfd > FD_PER_PROCESS_MAX {
+    // Note that, I need to use the soft limit here because this is also how
+    // I'm tracking how many values you have open.
+    if requested_virtualfd >= soft_fd_limit(cageid) {
         return Err(threei::Errno::EBADF as u64);
     }
 
+    // Replacing an entry doesn't change the total number of fds...
+    if !fdtable
+        .get(&cageid)
+        .unwrap()
+        .contains_key(&requested_virtualfd)
+    {
+        reserve_total_fds(1)?.keep();
+    } else {
+        // ...the old entry is counted out when it is closed below
+        count_fd_added();
+    }
+
     // Set up the entry so it has the right info...
     // Note, a HashMap stores its data on the heap!  No need to box it...
     // https://doc.rust-lang.org/book/ch08-03-hash-maps.html#creating-a-new-hash-map
@@ -317,6 +344,11 @@ pub fn copy_fdtable_for_cage(srccageid: u64, newcageid: u64) -> Result<(), three
     // Insert a copy and ensure it didn't exist...
     let hmcopy = fdtable.get(&srccageid).unwrap().clone();
 
+    // The new cage's fds all count against the global limit.
+    reserve_total_fds(hmcopy.len() as u64)
+        .map_err(|_| threei::Errno::ENFILE)?
+        .keep();
+
     // increment the reference to items in the fdtable appropriately...
     for v in fdtable.get(&srccageid).unwrap().values() {
         _increment_fdcount(*v);
@@ -324,9 +356,8 @@ pub fn copy_fdtable_for_cage(srccageid: u64, newcageid: u64) -> Result<(), three
 
     // insert the new table...
     assert!(fdtable.insert(newcageid, hmcopy).is_none());
+    copy_fd_limits(srccageid, newcageid);
     Ok(())
-    // I'm not going to bother to check the number of fds used overall yet...
-    //    Err(threei::Errno::EMFILE as u64),
 }
 
 // This is mostly used in handling exit, etc.  Returns the HashMap
@@ -342,6 +373,7 @@ pub fn remove_cage_from_fdtable(cageid: u64) {
         None => return,
     };
     drop(fdtable);
+    remove_fd_limits(cageid);
 
     // decrement the reference to items in the fdtable appropriately...
     for v in cagetable.values() {
@@ -444,8 +476,8 @@ lazy_static! {
 #[doc = include_str!("../docs/close_virtualfd.md")]
 pub fn close_virtualfd(cageid: u64, virtfd: u64) -> Result<(), threei::RetVal> {
     // Below condition checks if the virtualfd is out of bounds and if yes it throws an error
-    // Note that this assumes that all virtualfd numbers returned < FD_PER_PROCESS_MAX
-    if virtfd >= FD_PER_PROCESS_MAX {
+    // Note that this assumes that all virtualfd numbers returned < FD_PER_PROCESS_HARD_MAX
+    if virtfd >= FD_PER_PROCESS_HARD_MAX {
         return Err(threei::Errno::EBADFD as u64);
     }
 
@@ -487,6 +519,8 @@ pub fn register_close_handlers(
 fn _decrement_fdcount(entry: FDTableEntry) -> Result<(), i32> {
     let mytuple = (entry.fdkind, entry.underfd);
 
+    count_fd_removed();
+
     let intermediatech;
     let lastch;
 
@@ -1160,6 +1194,7 @@ pub fn get_virtual_epoll_wait_data(
 // panic
 #[doc(hidden)]
 pub fn refresh() {
+    reset_fd_limits();
     let mut fdtable = GLOBALFDTABLE.lock().unwrap_or_else(|e| {
         GLOBALFDTABLE.clear_poison();
         e.into_inner()
@@ -139,16 +139,11 @@ int __init_tp(void *p)
 	td->stackblock_size = bounds.size;
 	td->guardsize = 0;
 	/*
-	 * Initialize the TID to a value which doesn't conflict with
-	 * host-allocated TIDs, so that TID-based locks can work.
-	 *
-	 * Note:
-	 * - Host-allocated TIDs range from 1 to 0x1fffffff. (inclusive)
-	 * - __tl_lock and __lockfile uses TID 0 as "unlocked".
-	 * - __lockfile relies on the fact the most significant two bits
-	 *   of TIDs are 0.
+	 * Take the TID RawPOSIX assigned to the main thread, which robust
+	 * and PI mutexes record as their owner, and register its robust
+	 * list.
 	 */
-	td->tid = 0x3fffffff;
+	__lind_init_thread_self();
 #endif
 	return 0;
 }
@@ -200,6 +200,10 @@ void *__force_signal_callback __attribute__((used)) = (void *)signal_callback;
 #endif
 
 int _start() {
+    // A module pre-initialized by `lind-boot --preinit` keeps the values cached while it was
+    // initialized, from another Lind process, so they are always queried again here
+    __lind_base = 0ULL;
+    __lind_cageid = 0ULL;
     __lind_init_addr_translation(); // iniatilize cageids before anything else executes
     __libc_setup_tls();
     __wasi_init_tp();
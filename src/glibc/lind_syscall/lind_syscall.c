@@ -190,3 +190,40 @@ int copy_handler_table_to_cage(uint64_t thiscage, uint64_t targetcage)
         TRANSLATE_ERRNO_OFF /* do not translate errno: return the raw result */
     );
 }
+
+// ---------------------------------------------------------------------------------------------------------------------
+
+// Saves the calling cage and the cages below it to the snapshot file `path` (created or
+// truncated with mode 0600), which `lind-boot --restore <path> <program>` resumes them from.
+// The cages below are stopped while the file is written; one blocked in a syscall runs it
+// again afterwards, but gets EINTR from it when resumed from the file.
+// Returns 0 once the file is written, and 1 when the cage resumes from it. On error, returns -1
+// and sets errno (EOPNOTSUPP if a cage has threads, shared memory, or descriptors other than
+// stdio, regular files and directories, EAGAIN if a cage below exited or forked meanwhile, and
+// EBUSY if one did not stop in time).
+int lind_checkpoint(const char *path)
+{
+    int ret = make_threei_call(
+        CHECKPOINT_SYSCALL,
+        NOTUSED, // callname is not used in the trampoline
+        __lind_cageid, // self_cageid
+        __lind_cageid, // target_cageid
+        TRANSLATE_GUEST_POINTER_TO_HOST(path), __lind_cageid,
+        NOTUSED, NOTUSED,
+        NOTUSED, NOTUSED,
+        NOTUSED, NOTUSED,
+        NOTUSED, NOTUSED,
+        NOTUSED, NOTUSED,
+        TRANSLATE_ERRNO_ON
+    );
+
+    // The restored cage runs in a new Lind process, at a different memory base and
+    // possibly under another cage id, so address translation is set up again
+    if (ret == 1) {
+        __lind_base = 0ULL;
+        __lind_cageid = 0ULL;
+        __lind_init_addr_translation ();
+    }
+
+    return ret;
+}
@@ -49,4 +49,11 @@ int copy_data_between_cages(uint64_t thiscage, uint64_t targetcage,
     uint64_t len, uint64_t copytype);
 
 int copy_handler_table_to_cage(uint64_t srccage, uint64_t targetcage);
+
+/*
+ * Saves the calling cage and the cages below it to a snapshot file that
+ * `lind-boot --restore` resumes them from. Returns 0 once saved, 1 in the
+ * resumed cage, or -1 with errno set.
+ */
+int lind_checkpoint(const char *path);
 #endif // _LIND_SYSCALL_H
@@ -41,6 +41,11 @@
 #define SCHED_YIELD_SYSCALL 24
 #define RT_SIGSUSPEND_SYSCALL 130
 
+#define MREMAP_SYSCALL 25
+#define MSYNC_SYSCALL 26
+#define MINCORE_SYSCALL 27
+#define MADVISE_SYSCALL 28
+
 #define SHMGET_SYSCALL 29
 #define SHMAT_SYSCALL 30
 #define SHMCTL_SYSCALL 31
@@ -51,6 +56,7 @@
 
 #define SETITIMER_SYSCALL 38
 #define GETPID_SYSCALL 39
+#define SENDFILE_SYSCALL 40
 
 #define SOCKET_SYSCALL 41
 #define CONNECT_SYSCALL 42
@@ -75,8 +81,15 @@
 #define EXIT_GROUP_SYSCALL 231
 #define WAITPID_SYSCALL 61
 #define KILL_SYSCALL 62
+#define UNAME_SYSCALL 63
 
+#define SEMGET_SYSCALL 64
+#define SEMCTL_SYSCALL 66
 #define SHMDT_SYSCALL 67
+#define MSGGET_SYSCALL 68
+#define MSGSND_SYSCALL 69
+#define MSGRCV_SYSCALL 70
+#define MSGCTL_SYSCALL 71
 
 #define FCNTL_SYSCALL 72
 #define FLOCK_SYSCALL 73
@@ -101,6 +114,9 @@
 #define FCHMOD_SYSCALL 91
 #define CHOWN_SYSCALL 92
 #define LCHOWN_SYSCALL 94
+#define UMASK_SYSCALL 95
+
+#define SYSINFO_SYSCALL 99
 
 #define GETUID_SYSCALL 102
 #define GETGID_SYSCALL 104
@@ -111,14 +127,43 @@
 #define MKNOD_SYSCALL 133
 #define STATFS_SYSCALL 137
 #define FSTATFS_SYSCALL 138
+#define GETPRIORITY_SYSCALL 140
+#define SETPRIORITY_SYSCALL 141
+#define SCHED_GETSCHEDULER_SYSCALL 145
+#define MLOCK_SYSCALL 149
+#define MUNLOCK_SYSCALL 150
 #define GETHOSTNAME_SYSCALL 170
+/* Linux has no gethostname syscall, and lind gave it sethostname's number,
+   so sethostname is numbered after the Lind-specific calls instead.  */
+#define SETHOSTNAME_SYSCALL 1004
 #define SETXATTR_SYSCALL 188
+#define LSETXATTR_SYSCALL 189
+#define FSETXATTR_SYSCALL 190
+#define GETXATTR_SYSCALL 191
+#define LGETXATTR_SYSCALL 192
+#define FGETXATTR_SYSCALL 193
 #define LISTXATTR_SYSCALL 194
+#define LLISTXATTR_SYSCALL 195
+#define FLISTXATTR_SYSCALL 196
+#define REMOVEXATTR_SYSCALL 197
+#define LREMOVEXATTR_SYSCALL 198
+#define FREMOVEXATTR_SYSCALL 199
 #define FUTEX_SYSCALL 202
+#define SCHED_SETAFFINITY_SYSCALL 203
+#define SCHED_GETAFFINITY_SYSCALL 204
 #define EPOLL_CREATE_SYSCALL 213
+#define SET_TID_ADDRESS_SYSCALL 218
+#define SEMTIMEDOP_SYSCALL 220
+#define FADVISE64_SYSCALL 221
 #define CLOCK_GETTIME_SYSCALL 228
 #define EPOLL_WAIT_SYSCALL 232
 #define EPOLL_CTL_SYSCALL 233
+#define MQ_OPEN_SYSCALL 240
+#define MQ_UNLINK_SYSCALL 241
+#define MQ_TIMEDSEND_SYSCALL 242
+#define MQ_TIMEDRECEIVE_SYSCALL 243
+#define MQ_NOTIFY_SYSCALL 244
+#define MQ_GETSETATTR_SYSCALL 245
 #define OPENAT_SYSCALL 257
 #define FCHOWNAT_SYSCALL 260
 #define NEWFSTATAT_SYSCALL 262
@@ -129,8 +174,15 @@
 #define FCHMODAT_SYSCALL 268
 #define FACCESSAT_SYSCALL 269
 #define PPOLL_SYSCALL 271
+#define UNSHARE_SYSCALL 272
+#define SET_ROBUST_LIST_SYSCALL 273
+#define GET_ROBUST_LIST_SYSCALL 274
+#define SPLICE_SYSCALL 275
+#define TEE_SYSCALL 276
 #define SYNC_FILE_RANGE 277
+#define VMSPLICE_SYSCALL 278
 #define UTIMENSAT_SYSCALL 280
+#define FALLOCATE_SYSCALL 285
 #define ACCEPT4_SYSCALL 288
 #define EPOLL_CREATE1_SYSCALL 291
 #define DUP3_SYSCALL 292
@@ -140,13 +192,17 @@
 #define PREADV_SYSCALL 295
 #define PWRITEV_SYSCALL 296
 #define PRLIMIT64_SYSCALL 302
+#define GETCPU_SYSCALL 309
 #define GETRANDOM_SYSCALL 318
+#define MEMFD_CREATE_SYSCALL 319
 #define COPY_FILE_RANGE_SYSCALL 326
 
 /* Lind-specific syscalls (not part of the Linux syscall table) */
 #define REGISTER_HANDLER_SYSCALL 1001
 #define COPY_DATA_BETWEEN_CAGES_SYSCALL 1002
 #define COPY_HANDLER_TABLE_TO_CAGE_SYSCALL 1003
+#define SPAWN_SYSCALL 1005
+#define CHECKPOINT_SYSCALL 1006
 
 #endif /* _LIND_SYSCALL_NUM_H */
  
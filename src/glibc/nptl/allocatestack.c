@@ -371,7 +371,7 @@ allocate_stack (const struct pthread_attr *attr, struct pthread **pdp,
 	     allocate with PROT_NONE and then reserve with required permission
 	     excluding the guard page.  */
 	  mem = __mmap (NULL, size, (guardsize == 0) ? prot : PROT_NONE,
-			MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+			MAP_PRIVATE | MAP_ANONYMOUS | MAP_STACK, -1, 0);
 
     if (mem == NULL) {
         // Handle memory allocation failure
@@ -26,6 +26,12 @@
 #include <lind_syscall_num.h>
 #include <addr_translation.h>
 
+/* lind-wasm: RawPOSIX reads every futex timeout as a struct __timespec64,
+   so always take the 64-bit paths below.  */
+#ifndef __ASSUME_TIME64_SYSCALLS
+# define __ASSUME_TIME64_SYSCALLS 1
+#endif
+
 #ifndef __ASSUME_TIME64_SYSCALLS
 
 // disable syscal cancel - Dennis
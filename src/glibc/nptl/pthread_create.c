@@ -427,15 +427,9 @@ start_thread (void *arg)
       __libc_fatal ("Fatal glibc error: rseq registration failed\n");
   }
 
-#ifndef __ASSUME_SET_ROBUST_LIST
-  if (__nptl_set_robust_list_avail)
-#endif
-    {
-      /* This call should never fail because the initial call in init.c
-	 succeeded.  */
-      INTERNAL_SYSCALL_CALL (set_robust_list, &pd->robust_head,
-			     sizeof (struct robust_list_head));
-    }
+  /* lind-wasm: mutexes use THREAD_SELF rather than PD, so its TID and
+     robust list are the ones RawPOSIX must know about.  */
+  __lind_init_thread_self ();
 
   /* This is where the try/finally block should be created.  For
      compilers without that support we do use setjmp.  */
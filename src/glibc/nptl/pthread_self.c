@@ -17,6 +17,9 @@
 
 #include "pthreadP.h"
 #include <tls.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 _Thread_local struct pthread __wasilibc_pthread_self;
 
@@ -27,3 +30,35 @@ __pthread_self (void)
 }
 libc_hidden_def (__pthread_self)
 weak_alias (__pthread_self, pthread_self)
+
+/* lind-wasm: THREAD_SELF is the thread-local struct above, not the
+   descriptor pthread_create lays out below the thread's stack, so nothing
+   else fills in the fields the mutex code reads through it.  Record the TID
+   RawPOSIX gave the calling thread, which robust and PI mutexes store as
+   their owner, and register an empty robust list for RawPOSIX to walk when
+   the thread dies.  Called by every thread before it runs user code, and
+   again in the child of fork.  */
+void
+__lind_init_thread_self (void)
+{
+  struct pthread *self = THREAD_SELF;
+
+  self->tid = MAKE_LEGACY_SYSCALL (SET_TID_ADDRESS_SYSCALL,
+				   "syscall|set_tid_address",
+				   (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (&self->tid),
+				   NOTUSED, NOTUSED, NOTUSED, NOTUSED, NOTUSED,
+				   TRANSLATE_ERRNO_OFF);
+
+#if __PTHREAD_MUTEX_HAVE_PREV
+  self->robust_prev = &self->robust_head;
+#endif
+  self->robust_head.list = &self->robust_head;
+  self->robust_head.futex_offset = (offsetof (pthread_mutex_t, __data.__lock)
+				    - offsetof (pthread_mutex_t,
+						__data.__list.__next));
+  self->robust_head.list_op_pending = NULL;
+  MAKE_LEGACY_SYSCALL (SET_ROBUST_LIST_SYSCALL, "syscall|set_robust_list",
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (&self->robust_head),
+		       (uint64_t) sizeof (struct robust_list_head),
+		       NOTUSED, NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_OFF);
+}
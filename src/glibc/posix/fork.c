@@ -84,6 +84,10 @@ __libc_fork (void)
     {
       fork_system_setup ();
 
+      /* The child is a new cage whose only thread has its own TID, and it
+	 starts with no robust mutexes registered.  */
+      __lind_init_thread_self ();
+
       /* Reset the lock state in the multi-threaded case.  */
       if (multiple_threads)
 	{
@@ -107,11 +107,11 @@
 # define lll_futex_wake_unlock(futexp, nr_wake, nr_wake2, futexp2, private) \
   ({                                                                    \
   uint64_t __translated_futexp = TRANSLATE_GUEST_POINTER_TO_HOST(futexp); \
-  uint64_t __translated_futexp2 = TRANSLATE_GUEST_POINTER_TO_HOST(futexp2); \             \
+  uint64_t __translated_futexp2 = TRANSLATE_GUEST_POINTER_TO_HOST(futexp2); \
   MAKE_LEGACY_SYSCALL(FUTEX_SYSCALL, "syscall|futex", __translated_futexp, \
         __lll_private_flag (FUTEX_WAKE_OP, private),     \
         (uint64_t)(nr_wake), (uint64_t)(nr_wake2), __translated_futexp2,         \
-        (uint64_t)(FUTEX_OP_CLEAR_WAKE_IF_GT_ONE));                  \
+        (uint64_t)(FUTEX_OP_CLEAR_WAKE_IF_GT_ONE), TRANSLATE_ERRNO_OFF); \
   })
 
 
@@ -217,6 +217,10 @@ extern bool __nptl_set_robust_list_avail;
 rtld_hidden_proto (__nptl_set_robust_list_avail)
 #endif
 
+/* lind-wasm: record the calling thread's TID in THREAD_SELF and register
+   its robust list with RawPOSIX.  Defined in pthread_self.c.  */
+extern void __lind_init_thread_self (void) attribute_hidden;
+
 /* Thread Priority Protection.  */
 extern int __sched_fifo_min_prio;
 libc_hidden_proto (__sched_fifo_min_prio)
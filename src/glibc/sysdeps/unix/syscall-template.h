@@ -38,3 +38,23 @@
             (translate_errno) \
         ); \
     })
+
+/*
+ * LIND_ADDR_RESULT:
+ *
+ * Decodes the result of a syscall that returns a guest address (mmap,
+ * mremap, shmat, brk).  3i calls return 32 bits, so in a 64-bit (wasm64)
+ * cage rawposix returns the page number of the address, which is shifted
+ * back here.  -1 still reports an error.  In a 32-bit cage the address is
+ * returned as is.
+ */
+#ifdef __wasm64__
+#define LIND_ADDR_RESULT(ret) \
+    ({ \
+        int __ret = (ret); \
+        (void *) (__ret == -1 ? (uintptr_t) -1 \
+                              : (uintptr_t) (uint32_t) __ret << 12); \
+    })
+#else
+#define LIND_ADDR_RESULT(ret) ((void *) (uintptr_t) (ret))
+#endif
@@ -18,13 +18,18 @@
 #include <errno.h>
 #include <fcntl.h>
 #include <sysdep-cancel.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
 
 #ifndef __OFF_T_MATCHES_OFF64_T
 /* Reserve storage for the data of the file associated with FD.  */
 int
 fallocate (int fd, int mode, __off_t offset, __off_t len)
 {
-  return SYSCALL_CANCEL (fallocate, fd, mode,
-			 SYSCALL_LL (offset), SYSCALL_LL (len));
+  return MAKE_LEGACY_SYSCALL (FALLOCATE_SYSCALL, "syscall|fallocate",
+			      (uint64_t) fd, (uint64_t) mode,
+			      (uint64_t) (__off64_t) offset,
+			      (uint64_t) (__off64_t) len, NOTUSED, NOTUSED,
+			      TRANSLATE_ERRNO_ON);
 }
 #endif
@@ -34,14 +34,29 @@
 #  define SHLIB_COMPAT(a, b, c) 0
 # endif
 
+/* prlimit64 always fills in a struct rlimit64, so read into one and narrow
+   it.  Values which do not fit are reported as RLIM_INFINITY.  */
 int
 __new_getrlimit (enum __rlimit_resource resource, struct rlimit *rlim)
 {
-  uint64_t pold = rlim ? TRANSLATE_GUEST_POINTER_TO_HOST(rlim) : 0;
-  return MAKE_LEGACY_SYSCALL(PRLIMIT64_SYSCALL, "syscall|prlimit64",
+  struct rlimit64 rlim64;
+
+  if (rlim == NULL)
+    return INLINE_SYSCALL_ERROR_RETURN_VALUE (EFAULT);
+
+  uint64_t pold = TRANSLATE_GUEST_POINTER_TO_HOST(&rlim64);
+  int res = MAKE_LEGACY_SYSCALL(PRLIMIT64_SYSCALL, "syscall|prlimit64",
       0, (uint64_t) resource,
       0, pold,
       NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
+  if (res != 0)
+    return res;
+
+  rlim->rlim_cur = rlim64.rlim_cur >= RLIM_INFINITY
+		   ? RLIM_INFINITY : rlim64.rlim_cur;
+  rlim->rlim_max = rlim64.rlim_max >= RLIM_INFINITY
+		   ? RLIM_INFINITY : rlim64.rlim_max;
+  return 0;
 }
 weak_alias (__new_getrlimit, __getrlimit)
 hidden_weak (__getrlimit)
@@ -51,11 +66,7 @@ hidden_weak (__getrlimit)
 int
 __old_getrlimit (enum __rlimit_resource resource, struct rlimit *rlim)
 {
-  uint64_t pold = rlim ? TRANSLATE_GUEST_POINTER_TO_HOST(rlim) : 0;
-  return MAKE_LEGACY_SYSCALL(PRLIMIT64_SYSCALL, "syscall|prlimit64",
-      0, (uint64_t) resource,
-      0, pold,
-      NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
+  return __new_getrlimit (resource, rlim);
 }
 symbol (libc, __old_getrlimit, getrlimit, GLIBC_2_0);
 versioned_symbol (libc, __new_getrlimit, getrlimit, GLIBC_2_2);
@@ -28,6 +28,13 @@
 #include <sys/mman.h>
 #include <sys/sysinfo.h>
 #include <sysdep.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* __sysinfo is a variable of csu/__init_tls.c in lind, so the function is
+   called by its own name (see i386/i686/sysinfo.c).  */
+extern int __GI___sysinfo (struct sysinfo *__info);
 
 static int
 __get_nprocs_sched (void)
@@ -40,8 +47,11 @@ __get_nprocs_sched (void)
 
   /* This cannot use malloc because it is used on malloc initialization.  */
   __cpu_mask cpu_bits[cpu_bits_size / sizeof (__cpu_mask)];
-  int r = INTERNAL_SYSCALL_CALL (sched_getaffinity, 0, cpu_bits_size,
-				 cpu_bits);
+  int r = MAKE_LEGACY_SYSCALL (SCHED_GETAFFINITY_SYSCALL,
+			       "syscall|sched_getaffinity", (uint64_t) 0,
+			       (uint64_t) cpu_bits_size,
+			       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (cpu_bits),
+			       NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_OFF);
   if (r > 0)
     return CPU_COUNT_S (r, (cpu_set_t*) cpu_bits);
   else if (r == -EINVAL)
@@ -246,25 +256,24 @@ weak_alias (__get_nprocs_conf, get_nprocs_conf)
    kernel.sys.c:do_sysinfo() it is set to 1 if unsigned long can
    represent all the sizes measured in bytes].  */
 
-// Dennis Edit
-// static long int
-// sysinfo_mempages (unsigned long int num, unsigned int mem_unit)
-// {
-//   unsigned long int ps = __getpagesize ();
-
-//   while (mem_unit > 1 && ps > 1)
-//     {
-//       mem_unit >>= 1;
-//       ps >>= 1;
-//     }
-//   num *= mem_unit;
-//   while (ps > 1)
-//     {
-//       ps >>= 1;
-//       num >>= 1;
-//     }
-//   return num;
-// }
+static long int
+sysinfo_mempages (unsigned long int num, unsigned int mem_unit)
+{
+  unsigned long int ps = __getpagesize ();
+
+  while (mem_unit > 1 && ps > 1)
+    {
+      mem_unit >>= 1;
+      ps >>= 1;
+    }
+  num *= mem_unit;
+  while (ps > 1)
+    {
+      ps >>= 1;
+      num >>= 1;
+    }
+  return num;
+}
 
 /* Return the number of pages of total/available physical memory in
    the system.  This used to be done by parsing /proc/meminfo, but
@@ -274,13 +283,11 @@ weak_alias (__get_nprocs_conf, get_nprocs_conf)
 long int
 __get_phys_pages (void)
 {
-  // Dennis Edit
-  return 0;
+  struct sysinfo info;
 
-  // struct sysinfo info;
-
-  // __sysinfo (&info);
-  // return sysinfo_mempages (info.totalram, info.mem_unit);
+  if (__GI___sysinfo (&info) != 0)
+    return 0;
+  return sysinfo_mempages (info.totalram, info.mem_unit);
 }
 libc_hidden_def (__get_phys_pages)
 weak_alias (__get_phys_pages, get_phys_pages)
@@ -288,13 +295,11 @@ weak_alias (__get_phys_pages, get_phys_pages)
 long int
 __get_avphys_pages (void)
 {
-  // Dennis Edit
-  return 0;
-
-  // struct sysinfo info;
+  struct sysinfo info;
 
-  // __sysinfo (&info);
-  // return sysinfo_mempages (info.freeram, info.mem_unit);
+  if (__GI___sysinfo (&info) != 0)
+    return 0;
+  return sysinfo_mempages (info.freeram, info.mem_unit);
 }
 libc_hidden_def (__get_avphys_pages)
 weak_alias (__get_avphys_pages, get_avphys_pages)
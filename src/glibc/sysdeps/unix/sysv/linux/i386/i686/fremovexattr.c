@@ -0,0 +1,20 @@
+#include <sys/xattr.h>
+#include <errno.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Remove an extended attribute from the open file referred to by fd.
+   Returns 0 on success, or -1 and sets errno on error. */
+int
+__fremovexattr (int fd, const char *name)
+{
+  return MAKE_LEGACY_SYSCALL (FREMOVEXATTR_SYSCALL, "syscall|fremovexattr",
+		       (uint64_t) fd,
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (name),
+		       NOTUSED,
+		       NOTUSED,
+		       NOTUSED,
+		       NOTUSED, TRANSLATE_ERRNO_ON);
+}
+weak_alias(__fremovexattr, fremovexattr)
@@ -0,0 +1,21 @@
+#include <sys/xattr.h>
+#include <errno.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Set extended attributes on the open file referred to by fd.
+   Returns 0 on success, or -1 and sets errno on error. */
+int
+__fsetxattr (int fd, const char *name, const void *value, size_t size,
+             int flags)
+{
+  return MAKE_LEGACY_SYSCALL (FSETXATTR_SYSCALL, "syscall|fsetxattr",
+		       (uint64_t) fd,
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (name),
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (value),
+		       (uint64_t) size,
+		       (uint64_t) flags,
+		       NOTUSED, TRANSLATE_ERRNO_ON);
+}
+weak_alias(__fsetxattr, fsetxattr)
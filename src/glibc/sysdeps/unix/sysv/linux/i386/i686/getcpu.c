@@ -0,0 +1,19 @@
+#include <sched.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Store the virtual CPU the caller runs on in *CPU and its NUMA node in
+   *NODE.  Either pointer may be NULL.
+   Returns 0 on success, or -1 and sets errno on error. */
+int
+__getcpu (unsigned int *cpu, unsigned int *node)
+{
+  return MAKE_LEGACY_SYSCALL (GETCPU_SYSCALL, "syscall|getcpu",
+			      (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (cpu),
+			      (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (node),
+			      NOTUSED, NOTUSED, NOTUSED, NOTUSED,
+			      TRANSLATE_ERRNO_ON);
+}
+weak_alias (__getcpu, getcpu)
+libc_hidden_def (__getcpu)
@@ -0,0 +1,23 @@
+#include <sys/resource.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+
+/* The return value of getpriority syscall is biased by this value
+   to avoid returning negative values.  */
+#define PZERO 20
+
+/* Return the highest priority (lowest nice value) of the cages selected
+   by WHICH and WHO, or -1 and set errno.  A nice value of -1 is told
+   apart from an error only through errno, as on Linux. */
+int
+__getpriority (enum __priority_which which, id_t who)
+{
+  int res = MAKE_LEGACY_SYSCALL (GETPRIORITY_SYSCALL, "syscall|getpriority",
+				 (uint64_t) which, (uint64_t) who, NOTUSED,
+				 NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
+  if (res >= 0)
+    res = PZERO - res;
+  return res;
+}
+libc_hidden_def (__getpriority)
+weak_alias (__getpriority, getpriority)
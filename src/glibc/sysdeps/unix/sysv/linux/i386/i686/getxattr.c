@@ -0,0 +1,21 @@
+#include <sys/xattr.h>
+#include <errno.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Get the value of an extended attribute of the file specified by path.
+   If size is zero, returns the size of the value without copying it.
+   Returns the size of the value, or -1 and sets errno on error. */
+ssize_t
+__getxattr (const char *path, const char *name, void *value, size_t size)
+{
+  return MAKE_LEGACY_SYSCALL (GETXATTR_SYSCALL, "syscall|getxattr",
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (path),
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (name),
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (value),
+		       (uint64_t) size,
+		       NOTUSED,
+		       NOTUSED, TRANSLATE_ERRNO_ON);
+}
+weak_alias(__getxattr, getxattr)
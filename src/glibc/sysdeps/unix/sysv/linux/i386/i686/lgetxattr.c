@@ -0,0 +1,22 @@
+#include <sys/xattr.h>
+#include <errno.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Get the value of an extended attribute of the file specified by path,
+   without following a trailing symbolic link.
+   If size is zero, returns the size of the value without copying it.
+   Returns the size of the value, or -1 and sets errno on error. */
+ssize_t
+__lgetxattr (const char *path, const char *name, void *value, size_t size)
+{
+  return MAKE_LEGACY_SYSCALL (LGETXATTR_SYSCALL, "syscall|lgetxattr",
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (path),
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (name),
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (value),
+		       (uint64_t) size,
+		       NOTUSED,
+		       NOTUSED, TRANSLATE_ERRNO_ON);
+}
+weak_alias(__lgetxattr, lgetxattr)
@@ -0,0 +1,21 @@
+#include <sys/xattr.h>
+#include <errno.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Remove an extended attribute from the file specified by path, without
+   following a trailing symbolic link.
+   Returns 0 on success, or -1 and sets errno on error. */
+int
+__lremovexattr (const char *path, const char *name)
+{
+  return MAKE_LEGACY_SYSCALL (LREMOVEXATTR_SYSCALL, "syscall|lremovexattr",
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (path),
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (name),
+		       NOTUSED,
+		       NOTUSED,
+		       NOTUSED,
+		       NOTUSED, TRANSLATE_ERRNO_ON);
+}
+weak_alias(__lremovexattr, lremovexattr)
@@ -0,0 +1,20 @@
+#include <sys/mman.h>
+#include <errno.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Report which pages of [addr, addr + len) are resident, one byte per
+   page in vec.  Returns 0 on success, or -1 and sets errno on error. */
+int
+__mincore (void *addr, size_t len, unsigned char *vec)
+{
+  return MAKE_LEGACY_SYSCALL (MINCORE_SYSCALL, "syscall|mincore",
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (addr),
+		       (uint64_t) len,
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (vec),
+		       NOTUSED,
+		       NOTUSED,
+		       NOTUSED, TRANSLATE_ERRNO_ON);
+}
+weak_alias(__mincore, mincore)
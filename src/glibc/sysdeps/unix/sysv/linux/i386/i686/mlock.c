@@ -0,0 +1,20 @@
+#include <sys/mman.h>
+#include <errno.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Lock the pages of [addr, addr + len) into memory.
+   Returns 0 on success, or -1 and sets errno on error. */
+int
+__mlock (const void *addr, size_t len)
+{
+  return MAKE_LEGACY_SYSCALL (MLOCK_SYSCALL, "syscall|mlock",
+		       (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (addr),
+		       (uint64_t) len,
+		       NOTUSED,
+		       NOTUSED,
+		       NOTUSED,
+		       NOTUSED, TRANSLATE_ERRNO_ON);
+}
+weak_alias(__mlock, mlock)
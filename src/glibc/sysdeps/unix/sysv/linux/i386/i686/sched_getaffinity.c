@@ -0,0 +1,27 @@
+#include <sched.h>
+#include <string.h>
+#include <sys/param.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Copy the affinity mask of cage PID (0 for the caller) over lind's
+   virtual CPUs into CPUSET, which is CPUSETSIZE bytes long.  RawPOSIX
+   copies as many bytes as the mask needs, and the rest of CPUSET is
+   cleared here.  Returns 0 on success, or -1 and sets errno on error. */
+int
+sched_getaffinity (pid_t pid, size_t cpusetsize, cpu_set_t *cpuset)
+{
+  int res = MAKE_LEGACY_SYSCALL (SCHED_GETAFFINITY_SYSCALL,
+				 "syscall|sched_getaffinity",
+				 (uint64_t) pid,
+				 (uint64_t) MIN (INT_MAX, cpusetsize),
+				 (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (cpuset),
+				 NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
+  if (res != -1)
+    {
+      memset ((char *) cpuset + res, '\0', cpusetsize - res);
+      res = 0;
+    }
+  return res;
+}
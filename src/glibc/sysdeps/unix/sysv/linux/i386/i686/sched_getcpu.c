@@ -0,0 +1,10 @@
+#include <sched.h>
+
+/* Return the virtual CPU the caller runs on, or -1 and set errno.
+   Cages have no rseq area, so this always asks RawPOSIX. */
+int
+sched_getcpu (void)
+{
+  unsigned int cpu;
+  return __getcpu (&cpu, NULL) == -1 ? -1 : (int) cpu;
+}
@@ -1,11 +1,16 @@
-#include <unistd.h>
-#include <lind_debug.h>
+#include <sched.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
 
+/* Return the scheduling policy of cage PID (0 for the caller), which is
+   always SCHED_OTHER, or -1 and set errno. */
 int
 __GI___sched_getscheduler (__pid_t __pid)
 {
-  lind_debug_panic("sched_getscheduler called but not supported!");
-  return -1;
+  return MAKE_LEGACY_SYSCALL (SCHED_GETSCHEDULER_SYSCALL,
+			      "syscall|sched_getscheduler",
+			      (uint64_t) __pid, NOTUSED, NOTUSED, NOTUSED,
+			      NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 
 weak_alias(__GI___sched_getscheduler, __sched_getscheduler)
@@ -0,0 +1,18 @@
+#include <sched.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Set the affinity mask of cage PID (0 for the caller) to CPUSET, which
+   is CPUSETSIZE bytes long.  The mask only changes what the cage is told
+   by sched_getaffinity and getcpu.
+   Returns 0 on success, or -1 and sets errno on error. */
+int
+sched_setaffinity (pid_t pid, size_t cpusetsize, const cpu_set_t *cpuset)
+{
+  return MAKE_LEGACY_SYSCALL (SCHED_SETAFFINITY_SYSCALL,
+			      "syscall|sched_setaffinity",
+			      (uint64_t) pid, (uint64_t) cpusetsize,
+			      (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (cpuset),
+			      NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
+}
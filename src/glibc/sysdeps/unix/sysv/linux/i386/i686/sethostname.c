@@ -0,0 +1,16 @@
+#include <unistd.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Set the hostname of the calling cage's UTS namespace to NAME, which is
+   LEN bytes long.  The host's own hostname is not changed.
+   Returns 0 on success, or -1 and sets errno on error. */
+int
+sethostname (const char *name, size_t len)
+{
+  return MAKE_LEGACY_SYSCALL (SETHOSTNAME_SYSCALL, "syscall|sethostname",
+			      (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (name),
+			      (uint64_t) len, NOTUSED, NOTUSED, NOTUSED, NOTUSED,
+			      TRANSLATE_ERRNO_ON);
+}
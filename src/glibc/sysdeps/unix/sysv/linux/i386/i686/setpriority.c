@@ -1,10 +1,16 @@
-#include <unistd.h>
 #include <sys/resource.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
 
+/* Set the nice value of the cages selected by WHICH and WHO to PRIO.
+   The value is kept by RawPOSIX; cage threads keep the host priority of
+   lind.  Returns 0 on success, or -1 and sets errno on error. */
 int
 __setpriority (enum __priority_which which, id_t who, int prio)
 {
-  return -1;
+  return MAKE_LEGACY_SYSCALL (SETPRIORITY_SYSCALL, "syscall|setpriority",
+			      (uint64_t) which, (uint64_t) who, (uint64_t) prio,
+			      NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
-
-weak_alias(__setpriority, setpriority)
+libc_hidden_def (__setpriority)
+weak_alias (__setpriority, setpriority)
@@ -1,10 +1,21 @@
 #include <sys/sysinfo.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
+/* Fill INFO with the cage's view of the system: memory is counted in
+   pages of its linear memory, and procs is the number of live cages.
+   Returns 0 on success, or -1 and sets errno on error.
+
+   __sysinfo is a variable of csu/__init_tls.c here, so callers inside
+   libc use __GI___sysinfo. */
 int
 __GI___sysinfo (struct sysinfo *__info)
 {
-
-  return 0;
+  return MAKE_LEGACY_SYSCALL (SYSINFO_SYSCALL, "syscall|sysinfo",
+			      (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (__info),
+			      NOTUSED, NOTUSED, NOTUSED, NOTUSED, NOTUSED,
+			      TRANSLATE_ERRNO_ON);
 }
 
-weak_alias(__GI___sysinfo, __sysinfo)
+weak_alias(__GI___sysinfo, sysinfo)
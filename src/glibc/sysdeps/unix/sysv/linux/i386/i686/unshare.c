@@ -0,0 +1,15 @@
+#include <sched.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+
+/* Move the calling cage into new namespaces.  RawPOSIX keeps network and
+   UTS namespaces per cage, so CLONE_NEWNET and CLONE_NEWUTS are the only
+   flags it supports.
+   Returns 0 on success, or -1 and sets errno on error. */
+int
+unshare (int flags)
+{
+  return MAKE_LEGACY_SYSCALL (UNSHARE_SYSCALL, "syscall|unshare",
+			      (uint64_t) flags, NOTUSED, NOTUSED, NOTUSED,
+			      NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
+}
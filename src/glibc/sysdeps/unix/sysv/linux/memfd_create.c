@@ -0,0 +1,32 @@
+/* Create an anonymous file.  Linux version.
+   Copyright (C) 2024 Free Software Foundation, Inc.
+   This file is part of the GNU C Library.
+
+   The GNU C Library is free software; you can redistribute it and/or
+   modify it under the terms of the GNU Lesser General Public
+   License as published by the Free Software Foundation; either
+   version 2.1 of the License, or (at your option) any later version.
+
+   The GNU C Library is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+   Lesser General Public License for more details.
+
+   You should have received a copy of the GNU Lesser General Public
+   License along with the GNU C Library; if not, see
+   <https://www.gnu.org/licenses/>.  */
+
+#include <sys/mman.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
+/* Create an anonymous file named NAME, backed by the memory of the host.  */
+int
+memfd_create (const char *name, unsigned int flags)
+{
+  uint64_t host_name = TRANSLATE_GUEST_POINTER_TO_HOST (name);
+
+  return MAKE_LEGACY_SYSCALL (MEMFD_CREATE_SYSCALL, "syscall|memfd_create", host_name,
+		       (uint64_t) flags, NOTUSED, NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
+}
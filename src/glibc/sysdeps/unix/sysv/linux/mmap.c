@@ -57,10 +57,10 @@
 void *
 __mmap (void *addr, size_t len, int prot, int flags, int fd, off_t offset)
 {
-  return MAKE_LEGACY_SYSCALL (MMAP_SYSCALL, "syscall|mmap",
+  return LIND_ADDR_RESULT (MAKE_LEGACY_SYSCALL (MMAP_SYSCALL, "syscall|mmap",
 			      (uint64_t) (uintptr_t) addr, (uint64_t) len,
 			      (uint64_t) prot, (uint64_t) flags, (uint64_t) fd,
-			      (uint64_t) offset, TRANSLATE_ERRNO_ON);
+			      (uint64_t) offset, TRANSLATE_ERRNO_ON));
 }
 weak_alias (__mmap, mmap)
 libc_hidden_def (__mmap)
@@ -19,13 +19,15 @@
 #include <mqueue.h>
 #include <sysdep.h>
 #include <shlib-compat.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
 
 /* Removes the association between message queue descriptor MQDES and its
    message queue.  */
 int
 __mq_close (mqd_t mqdes)
 {
-  return INLINE_SYSCALL (close, 1, mqdes);
+  return MAKE_LEGACY_SYSCALL (CLOSE_SYSCALL, "syscall|close", (uint64_t) mqdes, NOTUSED, NOTUSED, NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 versioned_symbol (libc, __mq_close, mq_close, GLIBC_2_34);
 #if OTHER_SHLIB_COMPAT (librt, GLIBC_2_3_4, GLIBC_2_34)
@@ -29,6 +29,9 @@
 #include <not-cancel.h>
 #include <pthreadP.h>
 #include <shlib-compat.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 /* Defined in the kernel headers: */
 #define NOTIFY_COOKIE_LEN	32	/* Length of the cookie used.  */
@@ -207,7 +210,7 @@ __mq_notify (mqd_t mqdes, const struct sigevent *notification)
 
   /* Special treatment needed for SIGEV_THREAD.  */
   if (notification == NULL || notification->sigev_notify != SIGEV_THREAD)
-    return INLINE_SYSCALL (mq_notify, 2, mqdes, notification);
+    return MAKE_LEGACY_SYSCALL (MQ_NOTIFY_SYSCALL, "syscall|mq_notify", (uint64_t) mqdes, (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (notification), 0, NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 
   /* The kernel cannot directly start threads.  This will have to be
      done at userlevel.  Since we cannot start threads from signal
@@ -255,8 +258,9 @@ __mq_notify (mqd_t mqdes, const struct sigevent *notification)
   se.sigev_signo = netlink_socket;
   se.sigev_value.sival_ptr = &data;
 
-  /* Tell the kernel.  */
-  int retval = INLINE_SYSCALL (mq_notify, 2, mqdes, &se);
+  /* Tell rawposix.  sival_ptr is a cage address, so the cookie it points
+     to is passed translated as well.  */
+  int retval = MAKE_LEGACY_SYSCALL (MQ_NOTIFY_SYSCALL, "syscall|mq_notify", (uint64_t) mqdes, (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (&se), (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (&data), NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 
   /* If it failed, free the allocated memory.  */
   if (retval != 0 && data.attr != NULL)
@@ -22,6 +22,9 @@
 #include <stdio.h>
 #include <sysdep.h>
 #include <shlib-compat.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 /* Establish connection between a process and a message queue NAME and
    return message queue descriptor or (mqd_t) -1 on error.  OFLAG determines
@@ -48,7 +51,7 @@ __mq_open (const char *name, int oflag, ...)
       va_end (ap);
     }
 
-  return INLINE_SYSCALL (mq_open, 4, name + 1, oflag, mode, attr);
+  return MAKE_LEGACY_SYSCALL (MQ_OPEN_SYSCALL, "syscall|mq_open", (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (name + 1), (uint64_t) oflag, (uint64_t) mode, (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (attr), NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 versioned_symbol (libc, __mq_open, mq_open, GLIBC_2_34);
 #if OTHER_SHLIB_COMPAT (librt, GLIBC_2_3_4, GLIBC_2_34)
@@ -19,12 +19,15 @@
 #include <mqueue.h>
 #include <shlib-compat.h>
 #include <sysdep.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 int
 __mq_setattr (mqd_t mqdes, const struct mq_attr *mqstat,
               struct mq_attr * omqstat)
 {
-  return INLINE_SYSCALL_CALL (mq_getsetattr, mqdes, mqstat, omqstat);
+  return MAKE_LEGACY_SYSCALL (MQ_GETSETATTR_SYSCALL, "syscall|mq_getsetattr", (uint64_t) mqdes, (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (mqstat), (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (omqstat), NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 versioned_symbol (libc, __mq_setattr, mq_setattr, GLIBC_2_34);
 libc_hidden_ver (__mq_setattr, mq_setattr)
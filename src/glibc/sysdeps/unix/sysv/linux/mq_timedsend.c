@@ -19,6 +19,9 @@
 #include <mqueue.h>
 #include <sysdep-cancel.h>
 #include <shlib-compat.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 /* Add message pointed by MSG_PTR to message queue MQDES, stop blocking
    on full message queue if ABS_TIMEOUT expires.  */
@@ -27,36 +30,7 @@ ___mq_timedsend_time64 (mqd_t mqdes, const char *msg_ptr, size_t msg_len,
 			unsigned int msg_prio,
 			const struct __timespec64 *abs_timeout)
 {
-# ifndef __NR_mq_timedsend_time64
-#  define __NR_mq_timedsend_time64 __NR_mq_timedsend
-# endif
-
-#ifdef __ASSUME_TIME64_SYSCALLS
-  return SYSCALL_CANCEL (mq_timedsend_time64, mqdes, msg_ptr, msg_len,
-			 msg_prio, abs_timeout);
-#else
-  bool need_time64 = abs_timeout != NULL
-		     && !in_int32_t_range (abs_timeout->tv_sec);
-  if (need_time64)
-    {
-      int r = SYSCALL_CANCEL (mq_timedsend_time64, mqdes, msg_ptr, msg_len,
-			      msg_prio, abs_timeout);
-      if (r == 0 || errno != ENOSYS)
-	return r;
-      __set_errno (EOVERFLOW);
-      return -1;
-    }
-
-  struct timespec ts32, *pts32 = NULL;
-  if (abs_timeout != NULL)
-    {
-      ts32 = valid_timespec64_to_timespec (*abs_timeout);
-      pts32 = &ts32;
-    }
-
-  return SYSCALL_CANCEL (mq_timedsend, mqdes, msg_ptr, msg_len, msg_prio,
-			 pts32);
-#endif
+  return MAKE_LEGACY_SYSCALL (MQ_TIMEDSEND_SYSCALL, "syscall|mq_timedsend", (uint64_t) mqdes, (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (msg_ptr), (uint64_t) msg_len, (uint64_t) msg_prio, (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (abs_timeout), NOTUSED, TRANSLATE_ERRNO_ON);
 }
 
 #if __TIMESIZE == 64
@@ -19,6 +19,9 @@
 #include <mqueue.h>
 #include <sysdep.h>
 #include <shlib-compat.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 /* Remove message queue named NAME.  */
 int
@@ -27,17 +30,12 @@ __mq_unlink (const char *name)
   if (name[0] != '/')
     return INLINE_SYSCALL_ERROR_RETURN_VALUE (EINVAL);
 
-  int ret = INTERNAL_SYSCALL_CALL (mq_unlink, name + 1);
+  int ret = MAKE_LEGACY_SYSCALL (MQ_UNLINK_SYSCALL, "syscall|mq_unlink", (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (name + 1), NOTUSED, NOTUSED, NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 
   /* While unlink can return either EPERM or EACCES, mq_unlink should
      return just EACCES.  */
-  if (__glibc_unlikely (INTERNAL_SYSCALL_ERROR_P (ret)))
-    {
-      ret = INTERNAL_SYSCALL_ERRNO (ret);
-      if (ret == EPERM)
-	ret = EACCES;
-      return INLINE_SYSCALL_ERROR_RETURN_VALUE (ret);
-    }
+  if (__glibc_unlikely (ret < 0) && errno == EPERM)
+    __set_errno (EACCES);
 
   return ret;
 }
@@ -20,7 +20,12 @@
 #include <sysdep.h>
 #include <stdarg.h>
 #include <stddef.h>
+#include <stdint.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
 
+/* Like mmap, both addresses are guest addresses: rawposix looks them up in
+   the cage's vmmap and returns the guest address of the resized mapping.  */
 void *
 __mremap (void *addr, size_t old_len, size_t new_len, int flags, ...)
 {
@@ -34,8 +39,11 @@ __mremap (void *addr, size_t old_len, size_t new_len, int flags, ...)
       va_end (va);
     }
 
-  return (void *) INLINE_SYSCALL_CALL (mremap, addr, old_len, new_len, flags,
-				       new_addr);
+  return LIND_ADDR_RESULT (MAKE_LEGACY_SYSCALL (MREMAP_SYSCALL,
+		       "syscall|mremap", (uint64_t) (uintptr_t) addr,
+		       (uint64_t) old_len, (uint64_t) new_len,
+		       (uint64_t) flags, (uint64_t) (uintptr_t) new_addr,
+		       NOTUSED, TRANSLATE_ERRNO_ON));
 }
 libc_hidden_def (__mremap)
 weak_alias (__mremap, mremap)
@@ -22,6 +22,10 @@
 #include <errno.h>
 #include <linux/posix_types.h>  /* For __kernel_mode_t.  */
 
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
 /* POSIX states ipc_perm mode should have type of mode_t.  */
 _Static_assert (sizeof ((struct msqid_ds){0}.msg_perm.mode)
 		== sizeof (mode_t),
@@ -74,12 +78,7 @@ typedef struct kernel_msqid64_ds msgctl_arg_t;
 static int
 msgctl_syscall (int msqid, int cmd, msgctl_arg_t *buf)
 {
-#ifdef __ASSUME_DIRECT_SYSVIPC_SYSCALLS
-  return INLINE_SYSCALL_CALL (msgctl, msqid, cmd | __IPC_64, buf);
-#else
-  return INLINE_SYSCALL_CALL (ipc, IPCOP_msgctl, msqid, cmd | __IPC_64, 0,
-			      buf);
-#endif
+	return MAKE_LEGACY_SYSCALL(MSGCTL_SYSCALL, "syscall|msgctl", (uint64_t) msqid, (uint64_t) (cmd | __IPC_64), (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST(buf), NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 
 int
@@ -277,15 +276,7 @@ int
 attribute_compat_text_section
 __old_msgctl (int msqid, int cmd, struct __old_msqid_ds *buf)
 {
-#if defined __ASSUME_DIRECT_SYSVIPC_SYSCALLS \
-    && !defined __ASSUME_SYSVIPC_DEFAULT_IPC_64
-  /* For architecture that have wire-up msgctl but also have __IPC_64 to a
-     value different than default (0x0) it means the compat symbol used the
-     __NR_ipc syscall.  */
-  return INLINE_SYSCALL_CALL (msgctl, msqid, cmd, buf);
-#else
-  return INLINE_SYSCALL_CALL (ipc, IPCOP_msgctl, msqid, cmd, 0, buf);
-#endif
+	return MAKE_LEGACY_SYSCALL(MSGCTL_SYSCALL, "syscall|msgctl", (uint64_t) msqid, (uint64_t) cmd, (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST(buf), NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 compat_symbol (libc, __old_msgctl, msgctl, GLIBC_2_0);
 #endif
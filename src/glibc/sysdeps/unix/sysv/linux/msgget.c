@@ -18,6 +18,8 @@
 #include <ipc_priv.h>
 #include <sysdep.h>
 #include <errno.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
 
 /* Return descriptor for message queue associated with KEY.  The MSGFLG
    parameter describes how to proceed with clashing of key values.  */
@@ -25,9 +27,5 @@
 int
 msgget (key_t key, int msgflg)
 {
-#ifdef __ASSUME_DIRECT_SYSVIPC_SYSCALLS
-  return INLINE_SYSCALL_CALL (msgget, key, msgflg);
-#else
-  return INLINE_SYSCALL_CALL (ipc, IPCOP_msgget, key, msgflg, 0, NULL);
-#endif
+   return MAKE_LEGACY_SYSCALL(MSGGET_SYSCALL, "syscall|msgget", (uint64_t) key, (uint64_t) msgflg, NOTUSED, NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
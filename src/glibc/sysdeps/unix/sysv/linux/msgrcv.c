@@ -17,16 +17,14 @@
 
 #include <ipc_priv.h>
 #include <sysdep-cancel.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 ssize_t
 __libc_msgrcv (int msqid, void *msgp, size_t msgsz, long int msgtyp,
 	       int msgflg)
 {
-#ifdef __ASSUME_DIRECT_SYSVIPC_SYSCALLS
-  return SYSCALL_CANCEL (msgrcv, msqid, msgp, msgsz, msgtyp, msgflg);
-#else
-  return SYSCALL_CANCEL (ipc, IPCOP_msgrcv, msqid, msgsz, msgflg,
-			 MSGRCV_ARGS (msgp, msgtyp));
-#endif
+   return MAKE_LEGACY_SYSCALL(MSGRCV_SYSCALL, "syscall|msgrcv", (uint64_t) msqid, (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST(msgp), (uint64_t) msgsz, (uint64_t) msgtyp, (uint64_t) msgflg, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 weak_alias (__libc_msgrcv, msgrcv)
@@ -17,15 +17,13 @@
 
 #include <ipc_priv.h>
 #include <sysdep-cancel.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 int
 __libc_msgsnd (int msqid, const void *msgp, size_t msgsz, int msgflg)
 {
-#ifdef __ASSUME_DIRECT_SYSVIPC_SYSCALLS
-  return SYSCALL_CANCEL (msgsnd, msqid, msgp, msgsz, msgflg);
-#else
-  return SYSCALL_CANCEL (ipc, IPCOP_msgsnd, msqid, msgsz, msgflg,
-			 msgp);
-#endif
+   return MAKE_LEGACY_SYSCALL(MSGSND_SYSCALL, "syscall|msgsnd", (uint64_t) msqid, (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST(msgp), (uint64_t) msgsz, (uint64_t) msgflg, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 weak_alias (__libc_msgsnd, msgsnd)
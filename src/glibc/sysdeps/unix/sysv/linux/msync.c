@@ -18,9 +18,17 @@
 
 #include <sys/mman.h>
 #include <sysdep-cancel.h>
+#include <stdint.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 int
 msync (void *addr, size_t length, int flags)
 {
-  return SYSCALL_CANCEL (msync, addr, length, flags);
+  uint64_t host_addr = TRANSLATE_GUEST_POINTER_TO_HOST (addr);
+
+  return MAKE_LEGACY_SYSCALL (MSYNC_SYSCALL, "syscall|msync", host_addr,
+			      (uint64_t) length, (uint64_t) flags, NOTUSED,
+			      NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
@@ -18,50 +18,27 @@
 #include <errno.h>
 #include <fcntl.h>
 #include <sysdep.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
 
 /* Advice the system about the expected behaviour of the application with
    respect to the file associated with FD.  */
 
 #ifndef __OFF_T_MATCHES_OFF64_T
 
-/* Default implementation will use __NR_fadvise64 with expected argument
-   positions (for instance i386 and powerpc32 that uses __ALIGNMENT_ARG).
-
-   Second option will be used by arm which define __NR_arm_fadvise64_64
-   (redefined to __NR_fadvise64_64 in kernel-features.h) that behaves as
-   __NR_fadvise64_64 (without the alignment argument required for the ABI).
-
-   Third option will be used by mips o32.  Mips will use a 7 argument
-   syscall with __NR_fadvise64.
-
-   s390 implements fadvice64_64 using a specific struct with arguments
-   packed inside.  This is the only implementation handled in arch-specific
-   code.  */
+/* Lind: posix_fadvise reports failure through its return value rather
+   than errno, so the raw negative errno from 3i is kept and negated.  */
 
 int
 posix_fadvise (int fd, off_t offset, off_t len, int advise)
 {
-# if defined (__NR_fadvise64) && !defined (__ASSUME_FADVISE64_AS_64_64)
-  int ret = INTERNAL_SYSCALL_CALL (fadvise64, fd,
-				   __ALIGNMENT_ARG SYSCALL_LL (offset),
-				   len, advise);
-# else
-#  ifdef __ASSUME_FADVISE64_64_6ARG
-  int ret = INTERNAL_SYSCALL_CALL (fadvise64_64, fd, advise,
-				   SYSCALL_LL (offset), SYSCALL_LL (len));
-#  else
-
-#   ifndef __NR_fadvise64_64
-#    define __NR_fadvise64_64 __NR_fadvise64
-#   endif
-
-  int ret = INTERNAL_SYSCALL_CALL (fadvise64_64, fd,
-				   __ALIGNMENT_ARG SYSCALL_LL (offset),
-				   SYSCALL_LL (len), advise);
-#  endif
-# endif
-  if (INTERNAL_SYSCALL_ERROR_P (ret))
-    return INTERNAL_SYSCALL_ERRNO (ret);
+  int ret = MAKE_LEGACY_SYSCALL (FADVISE64_SYSCALL, "syscall|fadvise64",
+				 (uint64_t) fd, (uint64_t) (__off64_t) offset,
+				 (uint64_t) (__off64_t) len,
+				 (uint64_t) advise, NOTUSED, NOTUSED,
+				 TRANSLATE_ERRNO_OFF);
+  if (ret < 0)
+    return -ret;
   return 0;
 }
 #endif /* __OFF_T_MATCHES_OFF64_T  */
@@ -19,39 +19,26 @@
 #include <fcntl.h>
 #include <sysdep.h>
 #include <shlib-compat.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
 
 int __posix_fadvise64_l64 (int fd, off64_t offset, off64_t len, int advise);
 libc_hidden_proto (__posix_fadvise64_l64)
 
-/* Both arm and powerpc implements fadvise64_64 with last 'advise' argument
-   just after 'fd' to avoid the requirement of implementing 7-arg syscalls.
-   ARM also defines __NR_fadvise64_64 as __NR_arm_fadvise64_64.
-
-   s390 implements fadvice64_64 using a specific struct with arguments
-   packed inside.  This is the only implementation handled in arch-specific
-   code.  */
-
-#ifndef __NR_fadvise64_64
-# define __NR_fadvise64_64 __NR_fadvise64
-#endif
-
 /* Advice the system about the expected behaviour of the application with
-   respect to the file associated with FD.  */
+   respect to the file associated with FD.  As with posix_fadvise, the
+   error number is returned rather than stored in errno.  */
 
 int
 __posix_fadvise64_l64 (int fd, off64_t offset, off64_t len, int advise)
 {
-#ifdef __ASSUME_FADVISE64_64_6ARG
-  int ret = INTERNAL_SYSCALL_CALL (fadvise64_64, fd, advise,
-				   SYSCALL_LL64 (offset), SYSCALL_LL64 (len));
-#else
-  int ret = INTERNAL_SYSCALL_CALL (fadvise64_64, fd,
-				   __ALIGNMENT_ARG SYSCALL_LL64 (offset),
-				   SYSCALL_LL64 (len), advise);
-#endif
-  if (!INTERNAL_SYSCALL_ERROR_P (ret))
+  int ret = MAKE_LEGACY_SYSCALL (FADVISE64_SYSCALL, "syscall|fadvise64",
+				 (uint64_t) fd, (uint64_t) offset,
+				 (uint64_t) len, (uint64_t) advise, NOTUSED,
+				 NOTUSED, TRANSLATE_ERRNO_OFF);
+  if (ret >= 0)
     return 0;
-  return INTERNAL_SYSCALL_ERRNO (ret);
+  return -ret;
 }
 
 /* The type of the len argument was changed from size_t to off_t in
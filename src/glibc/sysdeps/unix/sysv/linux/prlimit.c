@@ -17,6 +17,9 @@
 
 #include <sys/resource.h>
 #include <sysdep.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 /* For ports that support the 64-bit ABI we do not need to define prlimit
    and instead prlimit aliases to prlimit64.  See the prlimit64
@@ -45,8 +48,12 @@ prlimit (__pid_t pid, enum __rlimit_resource resource,
       new_rlimit64 = &new_rlimit64_mem;
     }
 
-  int res = INLINE_SYSCALL (prlimit64, 4, pid, resource, new_rlimit64,
-			    old_rlimit64);
+  uint64_t pnew = new_rlimit64 ? TRANSLATE_GUEST_POINTER_TO_HOST(new_rlimit64) : 0;
+  uint64_t pold = old_rlimit64 ? TRANSLATE_GUEST_POINTER_TO_HOST(old_rlimit64) : 0;
+  int res = MAKE_LEGACY_SYSCALL(PRLIMIT64_SYSCALL, "syscall|prlimit64",
+      (uint64_t) pid, (uint64_t) resource,
+      pnew, pold,
+      NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 
   if (res == 0 && old_rlimit != NULL)
     {
@@ -22,6 +22,10 @@
 #include <shlib-compat.h>
 #include <linux/posix_types.h>             /* For __kernel_mode_t.  */
 
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
+
 /* The struct used to issue the syscall.  For architectures that assume
    64-bit time as default (!__ASSUME_TIME64_SYSCALLS) the syscall will
    split the resulting 64-bit sem_{o,c}time in two fields (sem_{o,c}time
@@ -120,13 +124,9 @@ typedef union ksemun64 semctl_arg_t;
 static int
 semctl_syscall (int semid, int semnum, int cmd, semctl_arg_t arg)
 {
-#ifdef __ASSUME_DIRECT_SYSVIPC_SYSCALLS
-  return INLINE_SYSCALL_CALL (semctl, semid, semnum, cmd | __IPC_64,
-			      arg.array);
-#else
-  return INLINE_SYSCALL_CALL (ipc, IPCOP_semctl, semid, semnum, cmd | __IPC_64,
-			      SEMCTL_ARG_ADDRESS (arg));
-#endif
+	/* SETVAL passes the value itself; every other command passes a pointer or nothing */
+	uint64_t host_arg = cmd == SETVAL ? (uint64_t) arg.val : TRANSLATE_GUEST_POINTER_TO_HOST(arg.array);
+	return MAKE_LEGACY_SYSCALL(SEMCTL_SYSCALL, "syscall|semctl", (uint64_t) semid, (uint64_t) semnum, (uint64_t) (cmd | __IPC_64), host_arg, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 
 /* POSIX states ipc_perm mode should have type of mode_t.  */
@@ -383,16 +383,8 @@ __old_semctl (int semid, int semnum, int cmd, ...)
       break;
     }
 
-#if defined __ASSUME_DIRECT_SYSVIPC_SYSCALLS \
-    && !defined __ASSUME_SYSVIPC_DEFAULT_IPC_64
- /* For architectures that have wire-up semctl but also have __IPC_64 to a
-    value different than default (0x0) it means the compat symbol used the
-    __NR_ipc syscall.  */
-  return INLINE_SYSCALL_CALL (semctl, semid, semnum, cmd, arg.array);
-# else
-  return INLINE_SYSCALL_CALL (ipc, IPCOP_semctl, semid, semnum, cmd,
-			      SEMCTL_ARG_ADDRESS (arg));
-# endif
+	uint64_t host_arg = cmd == SETVAL ? (uint64_t) arg.val : TRANSLATE_GUEST_POINTER_TO_HOST(arg.array);
+	return MAKE_LEGACY_SYSCALL(SEMCTL_SYSCALL, "syscall|semctl", (uint64_t) semid, (uint64_t) semnum, (uint64_t) cmd, host_arg, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 compat_symbol (libc, __old_semctl, semctl, GLIBC_2_0);
 #endif
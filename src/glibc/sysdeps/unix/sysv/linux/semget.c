@@ -19,6 +19,8 @@
 #include <ipc_priv.h>
 #include <sysdep.h>
 #include <errno.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
 
 /* Return identifier for array of NSEMS semaphores associated with
    KEY.  */
@@ -26,9 +28,5 @@
 int
 semget (key_t key, int nsems, int semflg)
 {
-#ifdef __ASSUME_DIRECT_SYSVIPC_SYSCALLS
-  return INLINE_SYSCALL_CALL (semget, key, nsems, semflg);
-#else
-  return INLINE_SYSCALL_CALL (ipc, IPCOP_semget, key, nsems, semflg, NULL);
-#endif
+   return MAKE_LEGACY_SYSCALL(SEMGET_SYSCALL, "syscall|semget", (uint64_t) key, (uint64_t) nsems, (uint64_t) semflg, NOTUSED, NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
@@ -18,21 +18,23 @@
 
 #include <sys/sendfile.h>
 #include <stddef.h>
+#include <stdint.h>
 #include <errno.h>
 #include <sysdep.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 #ifndef __OFF_T_MATCHES_OFF64_T
 
 /* Send COUNT bytes from file associated with IN_FD starting at OFFSET to
-   descriptor OUT_FD.  */
+   descriptor OUT_FD.  The host side always works on a 64-bit offset, so
+   the 32-bit off_t is widened on the way in and narrowed on the way out.  */
 ssize_t
 sendfile (int out_fd, int in_fd, off_t *offset, size_t count)
 {
-# ifdef __NR_sendfile
-  return INLINE_SYSCALL_CALL (sendfile, out_fd, in_fd, offset, count);
-# else
   __off64_t off64;
-  int rc;
+  ssize_t rc;
 
   if (offset != NULL)
     {
@@ -44,12 +46,16 @@ sendfile (int out_fd, int in_fd, off_t *offset, size_t count)
       off64 = *offset;
     }
 
-  rc = INLINE_SYSCALL_CALL (sendfile64, out_fd, in_fd, offset ? &off64 : NULL,
-			    count);
-  if (offset)
+  rc = MAKE_LEGACY_SYSCALL (SENDFILE_SYSCALL, "syscall|sendfile",
+			    (uint64_t) out_fd, (uint64_t) in_fd,
+			    offset == NULL
+			    ? 0
+			    : TRANSLATE_GUEST_POINTER_TO_HOST (&off64),
+			    (uint64_t) count, NOTUSED, NOTUSED,
+			    TRANSLATE_ERRNO_ON);
+  if (offset != NULL)
     *offset = off64;
   return rc;
-# endif
 }
 
 #endif
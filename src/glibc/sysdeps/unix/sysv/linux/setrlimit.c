@@ -19,6 +19,9 @@
 #include <sys/resource.h>
 #include <sysdep.h>
 #include <shlib-compat.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 #if !__RLIM_T_MATCHES_RLIM64_T
 
@@ -44,7 +47,11 @@ __setrlimit (enum __rlimit_resource resource, const struct rlimit *rlim)
   else
     rlim64.rlim_max = rlim->rlim_max;
 
-  return INLINE_SYSCALL_CALL (prlimit64, 0, resource, &rlim64, NULL);
+  uint64_t pnew = TRANSLATE_GUEST_POINTER_TO_HOST(&rlim64);
+  return MAKE_LEGACY_SYSCALL(PRLIMIT64_SYSCALL, "syscall|prlimit64",
+      0, (uint64_t) resource,
+      pnew, 0,
+      NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 
 libc_hidden_def (__setrlimit)
@@ -20,6 +20,9 @@
 #include <sys/types.h>
 #include <sysdep.h>
 #include <shlib-compat.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 /* Add this redirection so the strong_alias for __RLIM_T_MATCHES_RLIM64_T
    linking setrlimit64 to {__}setrlimit does not throw a type error.  */
@@ -37,7 +40,11 @@
 int
 __setrlimit64 (enum __rlimit_resource resource, const struct rlimit64 *rlimits)
 {
-  return INLINE_SYSCALL_CALL (prlimit64, 0, resource, rlimits, NULL);
+  uint64_t pnew = rlimits ? TRANSLATE_GUEST_POINTER_TO_HOST(rlimits) : 0;
+  return MAKE_LEGACY_SYSCALL(PRLIMIT64_SYSCALL, "syscall|prlimit64",
+      0, (uint64_t) resource,
+      pnew, 0,
+      NOTUSED, NOTUSED, TRANSLATE_ERRNO_ON);
 }
 /* Alpha defines a versioned setrlimit{64}.  */
 #ifndef USE_VERSIONED_RLIMIT
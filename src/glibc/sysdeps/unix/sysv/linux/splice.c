@@ -17,11 +17,26 @@
    <https://www.gnu.org/licenses/>.  */
 
 #include <fcntl.h>
+#include <stdint.h>
 #include <sysdep-cancel.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 ssize_t
 splice (int fd_in, loff_t *off_in, int fd_out, loff_t *off_out, size_t len,
 	unsigned int flags)
 {
-  return SYSCALL_CANCEL (splice, fd_in, off_in, fd_out, off_out, len, flags);
+  uint64_t host_off_in = off_in == NULL
+			 ? 0
+			 : TRANSLATE_GUEST_POINTER_TO_HOST (off_in);
+  uint64_t host_off_out = off_out == NULL
+			  ? 0
+			  : TRANSLATE_GUEST_POINTER_TO_HOST (off_out);
+
+  return MAKE_LEGACY_SYSCALL (SPLICE_SYSCALL, "syscall|splice",
+			      (uint64_t) fd_in, host_off_in,
+			      (uint64_t) fd_out, host_off_out,
+			      (uint64_t) len, (uint64_t) flags,
+			      TRANSLATE_ERRNO_ON);
 }
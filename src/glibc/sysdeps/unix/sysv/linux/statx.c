@@ -17,17 +17,25 @@
    <https://www.gnu.org/licenses/>.  */
 
 #include <errno.h>
+#include <stdint.h>
 #include <sys/stat.h>
 #include <sysdep.h>
-#include "statx_generic.c"
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
-/* Lind: there is no kernel statx wired through 3i, so always use the
-   fstatat-based generic fallback (which is in turn routed via 3i to
-   NEWFSTATAT_SYSCALL).  Without this, the broken INLINE_SYSCALL_CALL
-   path silently returns success with a zero-filled buffer.  */
+/* Lind: statx is serviced by the host statx through 3i.  struct statx has
+   the same layout on wasm32 and the host, so the result is written
+   straight into the guest buffer.  */
 int
 statx (int fd, const char *path, int flags,
        unsigned int mask, struct statx *buf)
 {
-  return statx_generic (fd, path, flags, mask, buf);
+  uint64_t host_path = TRANSLATE_GUEST_POINTER_TO_HOST (path);
+  uint64_t host_buf = TRANSLATE_GUEST_POINTER_TO_HOST (buf);
+
+  return MAKE_LEGACY_SYSCALL (STATX_SYSCALL, "syscall|statx",
+			      (uint64_t) fd, host_path, (uint64_t) flags,
+			      (uint64_t) mask, host_buf, NOTUSED,
+			      TRANSLATE_ERRNO_ON);
 }
@@ -18,9 +18,15 @@
 
 #include <fcntl.h>
 #include <sysdep-cancel.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 ssize_t
 tee (int src, int dest, size_t len, unsigned int flags)
 {
-  return SYSCALL_CANCEL (tee, src, dest, len, flags);
+  return MAKE_LEGACY_SYSCALL (TEE_SYSCALL, "syscall|tee", (uint64_t) src,
+			      (uint64_t) dest, (uint64_t) len,
+			      (uint64_t) flags, NOTUSED, NOTUSED,
+			      TRANSLATE_ERRNO_ON);
 }
@@ -1,9 +1,16 @@
-#include <unistd.h>
-#include <fcntl.h>
-#include <string.h>
+#include <sys/stat.h>
+#include <sys/types.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
 
+/* Set the file creation mask to MASK, returning the old mask.  The mask is
+   kept per cage by RawPOSIX, so this cannot fail.  */
 mode_t
-umask (mode_t mask)
+__umask (mode_t mask)
 {
-  return -1;
+  return MAKE_LEGACY_SYSCALL (UMASK_SYSCALL, "syscall|umask", (uint64_t) mask,
+			      NOTUSED, NOTUSED, NOTUSED, NOTUSED, NOTUSED,
+			      TRANSLATE_ERRNO_OFF);
 }
+
+weak_alias (__umask, umask)
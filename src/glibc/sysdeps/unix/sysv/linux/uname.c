@@ -1,49 +1,25 @@
-#include <unistd.h>
-#include <sysdep-cancel.h>
+#include <errno.h>
 #include <sys/utsname.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
-/* Hardcoded uname values for Lind-Wasm environment
-   TODO: These should eventually be retrieved from the runtime environment */
-#define UNAME_SYSNAME "Linux"
-#define UNAME_RELEASE "unknown"
-#define UNAME_VERSION "unknown"
-#define UNAME_MACHINE "x86_64"
-
+/* Put information about the system in NAME.  RawPOSIX fills in the
+   host's description, with the cage's hostname as the nodename and the
+   kernel release configured for lind.  */
 int
 __GI___uname (struct utsname *name)
 {
-  // lind-wasm: copied from posix/uname.c
-  int save;
-
   if (name == NULL)
     {
-      __set_errno (EINVAL);
+      __set_errno (EFAULT);
       return -1;
     }
 
-  save = errno;
-  if (__gethostname (name->nodename, sizeof (name->nodename)) < 0)
-    {
-      if (errno == ENOSYS)
-	{
-	  /* Hostname is meaningless for this machine.  */
-	  name->nodename[0] = '\0';
-	  __set_errno (save);
-	}
-#ifdef	ENAMETOOLONG
-      else if (errno == ENAMETOOLONG)
-	/* The name was truncated.  */
-	__set_errno (save);
-#endif
-      else
-	return -1;
-    }
-  strncpy (name->sysname, UNAME_SYSNAME, sizeof (name->sysname));
-  strncpy (name->release, UNAME_RELEASE, sizeof (name->release));
-  strncpy (name->version, UNAME_VERSION, sizeof (name->version));
-  strncpy (name->machine, UNAME_MACHINE, sizeof (name->machine));
-
-  return 0;
+  return MAKE_LEGACY_SYSCALL (UNAME_SYSCALL, "syscall|uname",
+			      (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST (name),
+			      NOTUSED, NOTUSED, NOTUSED, NOTUSED, NOTUSED,
+			      TRANSLATE_ERRNO_ON);
 }
 
 weak_alias(__GI___uname, __uname)
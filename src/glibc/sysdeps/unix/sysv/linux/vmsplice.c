@@ -19,9 +19,19 @@
 #include <fcntl.h>
 #include <sys/uio.h>
 #include <sysdep-cancel.h>
+#include <syscall-template.h>
+#include <lind_syscall_num.h>
+#include <addr_translation.h>
 
 ssize_t
 vmsplice (int fd, const struct iovec *iov, size_t count, unsigned int flags)
 {
-  return SYSCALL_CANCEL (vmsplice, fd, iov, count, flags);
+  struct iovec host_iov[count];
+  __lind_translate_iov (iov, host_iov, count);
+
+  return MAKE_LEGACY_SYSCALL (VMSPLICE_SYSCALL, "syscall|vmsplice",
+			      (uint64_t) fd,
+			      (uint64_t) TRANSLATE_GUEST_POINTER_TO_HOST ((uintptr_t) host_iov),
+			      (uint64_t) count, (uint64_t) flags, NOTUSED,
+			      NOTUSED, TRANSLATE_ERRNO_ON);
 }
@@ -1,7 +1,14 @@
 use std::path::PathBuf;
 
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow, bail};
+use cage::memory::aslr::AslrConfig;
+use cage::memory::introspect::ReportConfig;
+use cage::memory::quota::MemoryConfig;
 use clap::*;
+use rawposix::netns::{NetConfig, NetRule, PortMapping};
+use rawposix::sched::SchedConfig;
+use rawposix::uts::{self, HostEntry, UtsConfig};
+use std::net::IpAddr;
 
 fn parse_preloads(s: &str) -> Result<(String, PathBuf)> {
     let parts: Vec<&str> = s.splitn(2, '=').collect();
@@ -11,6 +18,46 @@ fn parse_preloads(s: &str) -> Result<(String, PathBuf)> {
     Ok((parts[0].into(), parts[1].into()))
 }
 
+fn parse_publish(s: &str) -> Result<PortMapping> {
+    s.parse().map_err(|e| anyhow!("{}", e))
+}
+
+fn parse_net_rule(s: &str) -> Result<NetRule> {
+    s.parse().map_err(|e| anyhow!("{}", e))
+}
+
+fn parse_hostname(s: &str) -> Result<String> {
+    uts::check_hostname(s).map_err(|e| anyhow!("{}", e))?;
+    Ok(s.to_string())
+}
+
+fn parse_host_entry(s: &str) -> Result<HostEntry> {
+    s.parse().map_err(|e| anyhow!("{}", e))
+}
+
+fn parse_cpus(s: &str) -> Result<usize> {
+    match s.parse::<usize>() {
+        Ok(n) if n >= 1 => Ok(n),
+        _ => bail!("must be a positive number of CPUs"),
+    }
+}
+
+/// Parses a size in bytes, with an optional K, M or G suffix (powers of 1024)
+fn parse_size(s: &str) -> Result<u64> {
+    let (digits, shift) = match s.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&s[..s.len() - 1], 10),
+        Some('M') => (&s[..s.len() - 1], 20),
+        Some('G') => (&s[..s.len() - 1], 30),
+        _ => (s, 0),
+    };
+    match digits.parse::<u64>() {
+        Ok(n) if n >= 1 => n
+            .checked_mul(1 << shift)
+            .ok_or_else(|| anyhow!("size is too large")),
+        _ => bail!("must be a positive size in bytes, optionally with a K, M or G suffix"),
+    }
+}
+
 #[derive(Debug, Parser, Clone)]
 #[command(name = "lind-boot")]
 pub struct CliOptions {
@@ -47,6 +94,24 @@ pub struct CliOptions {
     #[arg(skip)]
     pub wasm_bytes: Option<Vec<u8>>,
 
+    /// Resume the cages saved by lind_checkpoint() in FILE instead of starting WASM_FILE, which
+    /// must be the program that saved it
+    #[arg(long = "restore", value_name = "FILE")]
+    pub restore: Option<PathBuf>,
+
+    /// Run the init function of WASM_FILE in a cage and write the initialized module to OUT
+    /// (precompiled if OUT ends in .cwasm), so that cages loading it skip the initialization
+    #[arg(long = "preinit", value_name = "OUT", conflicts_with = "restore")]
+    pub preinit: Option<PathBuf>,
+
+    /// Export run by --preinit, which takes no arguments and returns nothing
+    #[arg(
+        long = "init-func",
+        value_name = "NAME",
+        default_value = "wizer-initialize"
+    )]
+    pub init_func: String,
+
     /// First item is WASM file (argv[0]), rest are program args (argv[1..])
     ///
     /// Example:
@@ -81,6 +146,105 @@ pub struct CliOptions {
     /// Internal memory reservation for Lind's own use (e.g., GC Heap) in bytes.
     #[arg(long = "lind-internal-memory-reservation", default_value_t = 10 * 1024 * 1024)]
     pub lind_internal_memory_reservation: u64,
+
+    /// Run the first cage in a private network namespace, with its own ports and loopback,
+    /// instead of directly on the host's network stack
+    #[arg(long = "private-net")]
+    pub private_net: bool,
+
+    /// Publish a port of the private network namespace on the host. Implies --private-net.
+    ///
+    /// Example:
+    ///   --publish 8080:80 --publish 5353:53/udp
+    #[arg(
+        long = "publish",
+        number_of_values = 1,
+        value_name = "HOST_PORT:GUEST_PORT[/tcp|udp]",
+        value_parser = parse_publish,
+    )]
+    pub publish: Vec<PortMapping>,
+
+    /// Only let cages connect or send to destinations matching one of these rules
+    #[arg(
+        long = "net-allow",
+        number_of_values = 1,
+        value_name = "ADDR[/PREFIX][:PORT[-PORT]]",
+        value_parser = parse_net_rule,
+    )]
+    pub net_allow: Vec<NetRule>,
+
+    /// Refuse connections and datagrams from cages to destinations matching this rule, even if
+    /// allowed by --net-allow
+    #[arg(
+        long = "net-deny",
+        number_of_values = 1,
+        value_name = "ADDR[/PREFIX][:PORT[-PORT]]",
+        value_parser = parse_net_rule,
+    )]
+    pub net_deny: Vec<NetRule>,
+
+    /// Hostname of the first cage, instead of the host's. Also added to /etc/hosts as 127.0.1.1
+    #[arg(long = "hostname", value_name = "NAME", value_parser = parse_hostname)]
+    pub hostname: Option<String>,
+
+    /// Kernel release reported to cages by uname, instead of the host's
+    #[arg(long = "kernel-release", value_name = "RELEASE")]
+    pub kernel_release: Option<String>,
+
+    /// Add an entry to the /etc/hosts that cages read
+    ///
+    /// Example:
+    ///   --add-host db:10.0.0.5 --add-host db6:fd00::5
+    #[arg(
+        long = "add-host",
+        number_of_values = 1,
+        value_name = "NAME:IP",
+        value_parser = parse_host_entry,
+    )]
+    pub add_hosts: Vec<HostEntry>,
+
+    /// Name server for cages, replacing those of /etc/resolv.conf
+    #[arg(long = "dns", number_of_values = 1, value_name = "IP")]
+    pub dns: Vec<IpAddr>,
+
+    /// Number of CPUs cages see (sched_getaffinity, nproc), instead of the host's
+    #[arg(long = "cpus", value_name = "N", value_parser = parse_cpus)]
+    pub cpus: Option<usize>,
+
+    /// Memory each cage may commit, as its initial RLIMIT_DATA (e.g. 512M)
+    #[arg(long = "cage-memory-limit", value_name = "SIZE", value_parser = parse_size)]
+    pub cage_memory_limit: Option<u64>,
+
+    /// Memory each child of the first cage may commit together with its descendants
+    #[arg(long = "tree-memory-limit", value_name = "SIZE", value_parser = parse_size)]
+    pub tree_memory_limit: Option<u64>,
+
+    /// Memory all cages may commit together
+    #[arg(long = "memory-budget", value_name = "SIZE", value_parser = parse_size)]
+    pub memory_budget: Option<u64>,
+
+    /// Kill the cage committing the most memory when the memory budget is exceeded, instead of
+    /// failing the allocation with ENOMEM
+    #[arg(long = "oom-kill", requires = "memory_budget")]
+    pub oom_kill: bool,
+
+    /// Randomize the mmap area, heap start and thread stacks of each cage
+    #[arg(long = "aslr")]
+    pub aslr: bool,
+
+    /// Seed the randomized layouts, so that a run can be reproduced. Implies --aslr.
+    #[arg(long = "aslr-seed", value_name = "SEED")]
+    pub aslr_seed: Option<u64>,
+
+    /// Append a report of the memory of every cage to FILE (`-` for stderr) each time lind-boot
+    /// receives SIGUSR1: the regions of each cage with their backing, protection and resident
+    /// size, largest cage first
+    #[arg(long = "memory-report", value_name = "FILE")]
+    pub memory_report: Option<PathBuf>,
+
+    /// Also report each cage when it exits, with the memory it still has mapped
+    #[arg(long = "memory-report-on-exit", requires = "memory_report")]
+    pub memory_report_on_exit: bool,
 }
 
 pub fn parse_env_var(s: &str) -> Result<(String, Option<String>), String> {
@@ -95,4 +259,55 @@ impl CliOptions {
     pub fn wasm_file(&self) -> &str {
         &self.args[0]
     }
+
+    /// Network namespace and policy settings for RawPOSIX
+    pub fn net_config(&self) -> NetConfig {
+        NetConfig {
+            private: self.private_net || !self.publish.is_empty(),
+            publish: self.publish.clone(),
+            allow: self.net_allow.clone(),
+            deny: self.net_deny.clone(),
+        }
+    }
+
+    /// Hostname and resolver settings for RawPOSIX
+    pub fn uts_config(&self) -> UtsConfig {
+        UtsConfig {
+            hostname: self.hostname.clone(),
+            release: self.kernel_release.clone(),
+            hosts: self.add_hosts.clone(),
+            dns: self.dns.clone(),
+        }
+    }
+
+    /// Virtual CPU settings for RawPOSIX
+    pub fn sched_config(&self) -> SchedConfig {
+        SchedConfig { cpus: self.cpus }
+    }
+
+    /// Memory limits for the cages
+    pub fn memory_config(&self) -> MemoryConfig {
+        MemoryConfig {
+            cage_limit: self.cage_memory_limit,
+            tree_limit: self.tree_memory_limit,
+            global_limit: self.memory_budget,
+            oom_kill: self.oom_kill,
+        }
+    }
+
+    /// Address space layout randomization of the cages
+    pub fn aslr_config(&self) -> AslrConfig {
+        AslrConfig {
+            enabled: self.aslr || self.aslr_seed.is_some(),
+            seed: self.aslr_seed,
+        }
+    }
+
+    /// Memory reports of the cages
+    pub fn report_config(&self) -> ReportConfig {
+        ReportConfig {
+            path: self.memory_report.clone(),
+            on_exit: self.memory_report_on_exit,
+        }
+    }
 }
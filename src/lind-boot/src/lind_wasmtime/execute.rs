@@ -3,10 +3,13 @@ use crate::lind_wasmtime::host::{
     cleanup_grate_handler, init_grate_pool, register_grate_handler_for_cage,
     unregister_grate_handler,
 };
+use crate::lind_wasmtime::preinit::Preinit;
+use crate::lind_wasmtime::restore::Restore;
 use crate::{cli::CliOptions, lind_wasmtime::host::HostCtx, lind_wasmtime::trampoline::*};
 use anyhow::{Context, Result, anyhow, bail};
 use cage::signal::{lind_signal_init, signal_may_trigger};
 use cfg_if::cfg_if;
+use rawposix::checkpoint::MemoryImage;
 use std::os::unix::fs::MetadataExt;
 use std::path::Path;
 use std::ptr::NonNull;
@@ -15,7 +18,9 @@ use std::{ffi::c_void, sync::Mutex};
 use sysdefs::constants::lind_platform_const::{
     INSTANCE_NUMBER, RAWPOSIX_CAGEID, UNUSED_ARG, UNUSED_ID, WASMTIME_CAGEID,
 };
-use sysdefs::constants::syscall_const::{CLONE_SYSCALL, EXEC_SYSCALL, EXIT_SYSCALL};
+use sysdefs::constants::syscall_const::{
+    CHECKPOINT_SYSCALL, CLONE_SYSCALL, EXEC_SYSCALL, EXIT_SYSCALL, SPAWN_SYSCALL,
+};
 use sysdefs::constants::{DEFAULT_STACKSIZE, DylinkErrorCode, GUARD_SIZE, TABLE_START_INDEX};
 use sysdefs::lind_debug_panic;
 use sysdefs::lind_log;
@@ -29,6 +34,7 @@ use wasmtime_lind_common::LindEnviron;
 use wasmtime_lind_dylink::DynamicLoader;
 use wasmtime_lind_multi_process::{
     CAGE_START_ID, LindCtx, THREAD_START_ID, attach_shared_memory, early_init_stack,
+    restore_checkpoint,
 };
 use wasmtime_lind_utils::symbol_table::SymbolMap;
 use wasmtime_lind_utils::{LindCageManager, LindGOT};
@@ -47,8 +53,8 @@ use wasmtime_lind_utils::{LindCageManager, LindGOT};
 /// during *grate calls*. It also registers the Wasmtime-specific 3i trampoline, which
 /// serves as the unified callback path for interposed syscalls routed through 3i.
 ///
-/// Third, it registers the syscall handlers (clone/exec/exit) with 3i exactly once
-/// during the initial boot. This is intentional: during `fork()`, RawPOSIX clones
+/// Third, it registers the syscall handlers (clone/exec/exit/spawn/checkpoint) with 3i exactly
+/// once during the initial boot. This is intentional: during `fork()`, RawPOSIX clones
 /// the parent process's handler table into the child, so children automatically
 /// inherit all registered handlers without additional registration. In contrast,
 /// `exec()` replaces the guest program within an existing cage and does not require
@@ -77,9 +83,11 @@ pub fn execute_wasmtime(lindboot_cli: CliOptions) -> anyhow::Result<i32> {
         grate_cleanup_funcptr,
     );
 
-    // Register syscall handlers (clone/exec/exit) with 3i
+    // Register syscall handlers (clone/exec/exit/spawn/checkpoint) with 3i
     if !register_wasmtime_syscall_entry() {
-        panic!("[lind-boot] register syscall handlers (clone/exec/exit) with 3i failed");
+        panic!(
+            "[lind-boot] register syscall handlers (clone/exec/exit/spawn/checkpoint) with 3i failed"
+        );
     }
 
     // initialize the vmctx pool for exit/exec/clone reentry into wasmtime runtime
@@ -94,7 +102,14 @@ pub fn execute_wasmtime(lindboot_cli: CliOptions) -> anyhow::Result<i32> {
     let engine = Engine::new(&wt_config)
         .map_err(anyhow::Error::from)
         .context("failed to create execution engine")?;
-    let module = read_main_wasm_or_cwasm(&engine, &lindboot_cli)?;
+    // `--preinit` runs the module instrumented by Wizer instead
+    let preinit = lindboot_cli.preinit.is_some();
+    let module = match Preinit::new(&lindboot_cli)? {
+        Some(preinit) => preinit.module(&engine)?,
+        None => read_main_wasm_or_cwasm(&engine, &lindboot_cli)?,
+    };
+    // `--restore`: the cage resumes from a snapshot file instead of starting the program
+    let restore = Restore::new(&lindboot_cli, &lind_manager)?;
 
     // -- Run the first module in the first cage --
     let result = execute_with_lind(
@@ -103,9 +118,16 @@ pub fn execute_wasmtime(lindboot_cli: CliOptions) -> anyhow::Result<i32> {
         engine,
         module,
         CAGE_START_ID as u64,
+        restore,
     );
 
     match result {
+        // The init function returned with the cage still alive, so it exits here once the
+        // initialized module is written
+        Ok(_) if preinit => {
+            finalize_first_cage(&lind_manager, 0);
+            Ok(0)
+        }
         Ok(ref ret_vals) => {
             // we wait until all other cage exits
             lind_manager.wait();
@@ -124,22 +146,30 @@ pub fn execute_wasmtime(lindboot_cli: CliOptions) -> anyhow::Result<i32> {
             // Initial cage crashed.  Do the same cleanup as the
             // fork-crash and signal-handler error paths so child
             // cages see proper termination and resources are freed.
-            let cageid = CAGE_START_ID as u64;
-            cage::cage_record_exit_status(cageid, cage::ExitStatus::Exited(1));
-            if let Some(c) = cage::get_cage(cageid) {
-                c.is_dead.store(true, std::sync::atomic::Ordering::Release);
-            }
-            threei::EXITING_TABLE.insert(cageid);
-            threei::handler_table::_rm_grate_from_handler(cageid);
-            cage::signal::lind_thread_exit(cageid, THREAD_START_ID as u64);
-            cage::cage_finalize(cageid);
-            lind_manager.decrement();
-            lind_manager.wait();
+            finalize_first_cage(&lind_manager, 1);
             return Err(e);
         }
     }
 }
 
+/// Exits the first cage from the host, when its entry point did not, and waits for the other
+/// cages to exit.
+fn finalize_first_cage(lind_manager: &LindCageManager, status: i32) {
+    let cageid = CAGE_START_ID as u64;
+    cage::cage_record_exit_status(cageid, cage::ExitStatus::Exited(status));
+    if let Some(c) = cage::get_cage(cageid) {
+        c.is_dead.store(true, std::sync::atomic::Ordering::Release);
+        // Its memory may already be unmapped, so lind_thread_exit must not walk its robust list
+        c.robust_list.clear();
+    }
+    threei::EXITING_TABLE.insert(cageid);
+    threei::handler_table::_rm_grate_from_handler(cageid);
+    cage::signal::lind_thread_exit(cageid, THREAD_START_ID as u64);
+    cage::cage_finalize(cageid);
+    lind_manager.decrement();
+    lind_manager.wait();
+}
+
 /// Executes a Wasm program *within an existing Lind runtime* as part of an `exec()` path.
 ///
 /// This function is not used for the initial launch. Instead, it is invoked only when
@@ -159,6 +189,7 @@ pub fn execute_with_lind(
     engine: Engine,
     module: Module,
     cageid: u64,
+    restore: Option<Restore>,
 ) -> Result<Vec<Val>> {
     // -- Initialize the Wasmtime execution environment --
     let args = lind_boot.args.clone();
@@ -235,8 +266,13 @@ pub fn execute_with_lind(
     }
 
     // Load the preload wasm modules.
+    // the path of the main module is what restores the cage from a snapshot file
     let mut modules = Vec::new();
-    modules.push((String::new(), String::new(), module.clone()));
+    modules.push((
+        String::new(),
+        lind_boot.wasm_file().to_string(),
+        module.clone(),
+    ));
     for (name, path) in lind_boot.preloads.iter() {
         let module = read_wasm_or_cwasm(&engine, path)?;
         modules.push((
@@ -349,6 +385,8 @@ pub fn execute_with_lind(
         got_guard.warning_undefined();
     }
 
+    let preinit = Preinit::new(&lind_boot)?;
+
     // -- Run the module in the cage --
     let result = wasmtime_wasi::runtime::with_ambient_tokio_runtime(|| {
         load_main_module(
@@ -358,6 +396,8 @@ pub fn execute_with_lind(
             cageid as u64,
             &args,
             dylink_metadata,
+            restore,
+            preinit,
         )
         .with_context(|| format!("failed to run main module"))
     });
@@ -387,6 +427,8 @@ pub fn execute_with_lind(
 /// - `clone` (56): fork / pthread_create completion in Wasmtime
 /// - `exec`  (59): exec completion in Wasmtime (instance replacement / image switch)
 /// - `exit`  (60): thread/process termination completion in Wasmtime
+/// - `spawn` (1005): posix_spawn completion in Wasmtime (program start in a new cage)
+/// - `checkpoint` (1006): cage unwind and snapshot file writing in Wasmtime
 fn register_wasmtime_syscall_entry() -> bool {
     // Register clone trampoline (syscall 56).
     let fp_clone = clone_syscall_entry;
@@ -448,8 +490,48 @@ fn register_wasmtime_syscall_entry() -> bool {
         UNUSED_ID,
     );
 
+    // Register spawn trampoline (Lind-specific syscall 1005).
+    let fp_spawn = spawn_syscall_entry;
+    let spawn_call_u64: u64 = fp_spawn as *const () as usize as u64;
+    let spawn_ret = threei::register_handler(
+        UNUSED_ID,
+        WASMTIME_CAGEID,                     // target cageid for this syscall handler
+        RAWPOSIX_CAGEID,                     // cage to modify: current cageid
+        SPAWN_SYSCALL as u64,                // spawn syscall number
+        threei_const::RUNTIME_TYPE_WASMTIME, // runtime id
+        WASMTIME_CAGEID,                     // handler function is in the 3i
+        spawn_call_u64,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+    );
+
+    // Register checkpoint trampoline (Lind-specific syscall 1006).
+    let fp_checkpoint = checkpoint_syscall_entry;
+    let checkpoint_call_u64: u64 = fp_checkpoint as *const () as usize as u64;
+    let checkpoint_ret = threei::register_handler(
+        UNUSED_ID,
+        WASMTIME_CAGEID,                     // target cageid for this syscall handler
+        RAWPOSIX_CAGEID,                     // cage to modify: current cageid
+        CHECKPOINT_SYSCALL as u64,           // checkpoint syscall number
+        threei_const::RUNTIME_TYPE_WASMTIME, // runtime id
+        WASMTIME_CAGEID,                     // handler function is in the 3i
+        checkpoint_call_u64,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+    );
+
     // Return false if registration failed
-    if (clone_ret | exec_ret | exit_ret) != 0 {
+    if (clone_ret | exec_ret | exit_ret | spawn_ret | checkpoint_ret) != 0 {
         return false;
     };
     // Succeed
@@ -558,6 +640,9 @@ fn attach_api(
         |lindboot_cli, path, args, engine, module, cageid, lind_manager, envs| {
             let mut new_lindboot_cli = lindboot_cli.clone();
             new_lindboot_cli.args = vec![String::from(path)];
+            // only the first cage reads the snapshot file or is pre-initialized
+            new_lindboot_cli.restore = None;
+            new_lindboot_cli.preinit = None;
             // new_lindboot_cli.wasm_file = path.to_string();
             if let Some(envs) = envs {
                 new_lindboot_cli.vars = envs.clone();
@@ -572,6 +657,7 @@ fn attach_api(
                 engine,
                 module,
                 cageid as u64,
+                None,
             )
         },
     )?);
@@ -583,7 +669,9 @@ fn attach_api(
 
 /// This function takes a compiled module, instantiates it with the current store and linker,
 /// and executes its entry point. This is the point where the Wasm "process" actually starts
-/// executing.
+/// executing. With a `checkpoint`, the module is not initialized: the cage is restored from it
+/// and its entry point resumes where the cage was saved. With `preinit`, the init function runs
+/// instead of the entry point, and the initialized module is written once it returns.
 fn load_main_module(
     mut store: &mut Store<HostCtx>,
     linker: &mut Arc<Mutex<Linker<HostCtx>>>,
@@ -591,6 +679,8 @@ fn load_main_module(
     cageid: u64,
     args: &[String],
     mut dylink_metadata: DylinkMetadata,
+    restore: Option<Restore>,
+    preinit: Option<Preinit>,
 ) -> Result<Vec<Val>> {
     let mut linker_guard = linker.lock().unwrap();
 
@@ -613,17 +703,34 @@ fn load_main_module(
             .register_named_instance(name.to_string(), cage_instanceid);
     }
 
+    // A pre-initialized module brings the memory its init function left in place of its own
+    if let Some(bytes) = module.lind_preinit() {
+        MemoryImage::from_bytes(bytes)
+            .and_then(|memory| memory.restore(cageid))
+            .map_err(|errno| anyhow!("failed to restore pre-initialized memory: {:?}", errno))?;
+    }
+
     // If `_initialize` is present, meaning a reactor, then invoke
-    // the function.
-    if let Some(func) = instance.get_func(&mut *store, "_initialize") {
+    // the function. A restored cage was already initialized when it was saved.
+    if let Some(func) = instance
+        .get_func(&mut *store, "_initialize")
+        .filter(|_| restore.is_none())
+    {
         func.typed::<(), ()>(&store)?.call(&mut *store, ())?;
     }
 
     // Look for the specific function provided or otherwise look for
     // "" or "_start" exports to run as a "main" function.
-    let func = instance
-        .get_func(&mut *store, "")
-        .or_else(|| instance.get_func(&mut *store, "_start"));
+    let func = match preinit.as_ref() {
+        Some(preinit) => Some(
+            instance
+                .get_func(&mut *store, preinit.init_func())
+                .ok_or_else(|| anyhow!("module does not export `{}`", preinit.init_func()))?,
+        ),
+        None => instance
+            .get_func(&mut *store, "")
+            .or_else(|| instance.get_func(&mut *store, "_start")),
+    };
 
     if !dylink_metadata.dylink_enabled {
         let stack_low = instance.get_stack_low(store.as_context_mut()).unwrap();
@@ -659,6 +766,12 @@ fn load_main_module(
         ctx.attach_linker(linker_guard.clone());
     }
 
+    // restore the saved cage before its signals are set up, so that pending ones are delivered
+    if let Some(restore) = restore.as_ref() {
+        restore_checkpoint(store, &instance, module, cageid, restore.cage())
+            .context("failed to restore checkpoint")?;
+    }
+
     cfg_if! {
         // The disable_signals feature allows Wasmtime to run Lind binaries without inserting an epoch.
         // It sets the signal pointer to 0, so any signals will trigger a fault in RawPOSIX.
@@ -736,11 +849,23 @@ fn load_main_module(
     drop(linker);
     drop(linker_guard);
 
+    // the saved children of a restored cage resume once it can receive their signals
+    if let Some(restore) = restore.as_ref() {
+        restore.resume_children(store.engine(), cageid)?;
+    }
+
     let ret = match func {
         Some(func) => invoke_func(store, func, &args),
         None => Ok(vec![]),
     };
 
+    // a failed init function leaves nothing to save
+    if let Some(preinit) = preinit.as_ref()
+        && ret.is_ok()
+    {
+        preinit.save(store, instance, cageid)?;
+    }
+
     ret
 }
 
@@ -946,7 +1071,7 @@ pub(crate) fn read_wasm_or_cwasm_bytes(
 /// The function probes the file header via `Engine::detect_precompiled_file`.
 /// If the file is a precompiled module it is deserialized directly (skipping
 /// compilation). Otherwise it is compiled from source via `Module::from_file`.
-fn read_wasm_or_cwasm(engine: &Engine, path: &Path) -> Result<Module> {
+pub(crate) fn read_wasm_or_cwasm(engine: &Engine, path: &Path) -> Result<Module> {
     // `detect_precompiled_file` *expects* input to already be an ELF file. It is used to detect
     // whether this ELF matches the current host architecture.
     //
@@ -1,5 +1,7 @@
 pub mod execute;
 pub mod host;
+pub mod preinit;
+pub mod restore;
 pub mod trampoline;
 
 pub use execute::{execute_wasmtime, precompile_module};
@@ -0,0 +1,156 @@
+//! `lind-boot --preinit`: pre-initialized cage images.
+//!
+//! The main module is instrumented by Wizer and runs its init function in the first cage, under
+//! the whole Lind runtime, so that the function can make syscalls. Wizer then bakes the globals
+//! the function left into a copy of the module, and the cage memory is appended to it as a
+//! `lind.preinit` custom section, with its `vmmap` layout and heap start. `load_main_module`
+//! restores that memory when a cage loads the module, instead of initializing it.
+use crate::cli::CliOptions;
+use crate::lind_wasmtime::host::HostCtx;
+use anyhow::{Context as _, Result, anyhow, bail};
+use rawposix::checkpoint::MemoryImage;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+use sysdefs::constants::lind_platform_const::LIND_PREINIT_SECTION;
+use wasm_encoder::{CustomSection, Section};
+use wasmtime::{Engine, Instance, Module, Store, Val};
+use wasmtime_wizer::{InstanceState, SnapshotVal, ValType, Wizer};
+
+/// A `--preinit` run: the module to initialize and where to write the result.
+pub struct Preinit {
+    wizer: Wizer,
+    wasm: Vec<u8>,
+    output: PathBuf,
+}
+
+impl Preinit {
+    /// Reads the main module if `--preinit` was given.
+    pub fn new(cli: &CliOptions) -> Result<Option<Preinit>> {
+        let Some(output) = cli.preinit.clone() else {
+            return Ok(None);
+        };
+        let wasm = match cli.wasm_bytes.as_ref() {
+            Some(bytes) => bytes.clone(),
+            None => std::fs::read(cli.wasm_file())
+                .with_context(|| format!("failed to read {}", cli.wasm_file()))?,
+        };
+        if Engine::detect_precompiled(&wasm).is_some() {
+            bail!("--preinit needs a .wasm module, not a precompiled one");
+        }
+
+        let mut wizer = Wizer::new();
+        wizer
+            .init_func(cli.init_func.as_str())
+            .allow_imported_memory(true);
+        Ok(Some(Preinit {
+            wizer,
+            wasm,
+            output,
+        }))
+    }
+
+    pub fn init_func(&self) -> &str {
+        self.wizer.get_init_func()
+    }
+
+    /// Compiles the module instrumented for the snapshot, which the first cage runs.
+    pub fn module(&self, engine: &Engine) -> Result<Module> {
+        let (_, instrumented) = self
+            .wizer
+            .instrument(&self.wasm)
+            .map_err(anyhow::Error::from)
+            .context("failed to instrument module")?;
+        let module = Module::from_binary(engine, &instrumented)
+            .map_err(anyhow::Error::from)
+            .context("failed to compile instrumented module")?;
+        if module.dylink_meminfo().is_some() {
+            bail!("dynamically linked modules cannot be pre-initialized");
+        }
+        Ok(module)
+    }
+
+    /// Writes the initialized module once the init function of the cage has returned.
+    pub fn save(&self, store: &mut Store<HostCtx>, instance: Instance, cageid: u64) -> Result<()> {
+        let memory = MemoryImage::capture_preinit(cageid).map_err(|errno| {
+            anyhow!(
+                "`{}` left state that cannot be saved into a module (children, threads, signal \
+                 handlers, descriptors other than stdio or shared mappings): {:?}",
+                self.init_func(),
+                errno
+            )
+        })?;
+
+        // The context is rebuilt from the original module, as it borrows its bytes
+        let (cx, _) = self
+            .wizer
+            .instrument(&self.wasm)
+            .map_err(anyhow::Error::from)?;
+        let mut state = LindInstance { store, instance };
+        let mut wasm = block_on(self.wizer.snapshot(cx, &mut state))
+            .map_err(anyhow::Error::from)
+            .context("failed to snapshot module")?;
+        CustomSection {
+            name: LIND_PREINIT_SECTION.into(),
+            data: memory.to_bytes().into(),
+        }
+        .append_to(&mut wasm);
+
+        if self.output.extension().is_some_and(|ext| ext == "cwasm") {
+            wasm = store
+                .engine()
+                .precompile_module(&wasm)
+                .map_err(anyhow::Error::from)
+                .context("failed to precompile module")?;
+        }
+        // With an explicit mode, as the host umask is 0 once rawposix has started
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o644)
+            .open(&self.output)
+            .and_then(|mut file| file.write_all(&wasm))
+            .with_context(|| format!("failed to write {}", self.output.display()))?;
+
+        eprintln!("OK: {}", self.output.display());
+        Ok(())
+    }
+}
+
+/// Wizer's view of the initialized instance. The shared memory of the cage is not exported to
+/// Wizer, as its contents are saved as a `MemoryImage` instead.
+struct LindInstance<'a> {
+    store: &'a mut Store<HostCtx>,
+    instance: Instance,
+}
+
+impl InstanceState for LindInstance<'_> {
+    async fn global_get(&mut self, name: &str, _: ValType) -> SnapshotVal {
+        let global = self.instance.get_global(&mut *self.store, name).unwrap();
+        match global.get(&mut *self.store) {
+            Val::I32(x) => SnapshotVal::I32(x),
+            Val::I64(x) => SnapshotVal::I64(x),
+            Val::F32(x) => SnapshotVal::F32(x),
+            Val::F64(x) => SnapshotVal::F64(x),
+            Val::V128(x) => SnapshotVal::V128(x.as_u128()),
+            _ => panic!("unsupported global value type"),
+        }
+    }
+
+    async fn memory_contents(&mut self, name: &str, contents: impl FnOnce(&[u8]) + Send) {
+        let memory = self.instance.get_memory(&mut *self.store, name).unwrap();
+        contents(memory.data(&*self.store))
+    }
+}
+
+/// Runs a future that never waits: `LindInstance` answers Wizer right away.
+fn block_on<F: Future>(future: F) -> F::Output {
+    match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => unreachable!("snapshot futures do not wait"),
+    }
+}
@@ -0,0 +1,109 @@
+//! `lind-boot --restore`: resuming cages from a snapshot file.
+//!
+//! The file holds the cage that called `lind_checkpoint()` and the cages below it. The first
+//! cage resumes as that cage. Once a cage is restored, `Restore::resume_children` creates its
+//! saved children again, with the IDs they had, and starts each one in a thread of its own as
+//! spawn does, so that every cage resumes after its parent.
+use crate::cli::CliOptions;
+use crate::lind_wasmtime::execute::{execute_with_lind, read_wasm_or_cwasm};
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use sysdefs::lind_log;
+use wasmtime::Engine;
+use wasmtime_lind_3i::rm_vmctx_thread;
+use wasmtime_lind_multi_process::{CageCheckpoint, CheckpointImage, THREAD_START_ID};
+use wasmtime_lind_utils::LindCageManager;
+
+/// A cage to resume from a snapshot file.
+#[derive(Clone)]
+pub struct Restore {
+    image: Arc<CheckpointImage>,
+    // position of the cage in the image
+    index: usize,
+    // used to start the saved children of the cage
+    cli: CliOptions,
+    lind_manager: Arc<LindCageManager>,
+}
+
+impl Restore {
+    /// Reads the snapshot file if `--restore` was given, for the first cage.
+    pub fn new(cli: &CliOptions, lind_manager: &Arc<LindCageManager>) -> Result<Option<Restore>> {
+        let Some(path) = cli.restore.as_deref() else {
+            return Ok(None);
+        };
+        Ok(Some(Restore {
+            image: Arc::new(CheckpointImage::read(path)?),
+            index: 0,
+            cli: cli.clone(),
+            lind_manager: lind_manager.clone(),
+        }))
+    }
+
+    /// The saved state of the cage.
+    pub fn cage(&self) -> &CageCheckpoint {
+        &self.image.cages()[self.index]
+    }
+
+    /// Creates the saved children of the cage, now restored as `cageid`, and starts them.
+    pub fn resume_children(&self, engine: &Engine, cageid: u64) -> Result<()> {
+        let (cli, lind_manager) = (&self.cli, &self.lind_manager);
+        let saved_cageid = self.cage().cageid();
+        let children = self.image.cages().iter().enumerate().skip(1);
+        for (index, child) in children.filter(|(_, child)| child.parent() == saved_cageid) {
+            let child_cageid = child.cageid();
+            let module = read_wasm_or_cwasm(engine, Path::new(child.program()))?;
+            child.create_cage(cageid)?;
+
+            // the memory of the cage holds its arguments already
+            let mut child_cli = cli.clone();
+            child_cli.args = vec![child.program().to_string()];
+            child_cli.restore = None;
+            child_cli.preinit = None;
+            let restore = Restore {
+                index,
+                ..self.clone()
+            };
+            let engine = engine.clone();
+            let child_lind_manager = lind_manager.clone();
+
+            // new cage created, increment the cage counter before it can exit
+            lind_manager.increment();
+            thread::Builder::new()
+                .name(format!("lind-restore-{}", child_cageid))
+                .stack_size(cli.thread_stack_size)
+                .spawn(move || {
+                    let ret = execute_with_lind(
+                        child_cli,
+                        child_lind_manager.clone(),
+                        engine,
+                        module,
+                        child_cageid,
+                        Some(restore),
+                    );
+
+                    // the same cleanup as for a spawned cage that crashed
+                    if let Err(_err) = ret {
+                        lind_log!(Default, "Restored Cage Error: {:?}", _err);
+                        cage::cage_record_exit_status(child_cageid, cage::ExitStatus::Exited(1));
+                        if let Some(c) = cage::get_cage(child_cageid) {
+                            c.is_dead.store(true, std::sync::atomic::Ordering::Release);
+                        }
+                        threei::EXITING_TABLE.insert(child_cageid);
+                        threei::handler_table::_rm_grate_from_handler(child_cageid);
+                        cage::signal::lind_thread_exit(child_cageid, THREAD_START_ID as u64);
+                        cage::cage_finalize(child_cageid);
+                        rm_vmctx_thread(child_cageid, 0);
+                        child_lind_manager.decrement();
+                    }
+                })
+                .map_err(|e| {
+                    lind_manager.decrement();
+                    anyhow!("failed to start cage {}: {}", child_cageid, e)
+                })
+                .with_context(|| format!("failed to restore {}", child.program()))?;
+        }
+        Ok(())
+    }
+}
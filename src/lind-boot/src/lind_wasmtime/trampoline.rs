@@ -68,7 +68,7 @@ pub extern "C" fn grate_callback_trampoline(
 /// not require rebuilding or modifying the handler table in the lind runtime.
 ///
 /// All syscalls in Lind first pass through RawPOSIX and 3i. For syscalls
-/// such as `clone`, `exec`, and `exit`, RawPOSIX alone is insufficient,
+/// such as `clone`, `exec`, `spawn`, `checkpoint` and `exit`, RawPOSIX alone is insufficient,
 /// because correct semantics require coordinated interaction with the
 /// Wasmtime runtime (e.g., process creation, re-instantiation, or teardown
 /// of execution state). These entry functions explicitly bridge that gap
@@ -143,6 +143,70 @@ pub extern "C" fn exec_syscall_entry(
     )
 }
 
+pub extern "C" fn spawn_syscall_entry(
+    cageid: u64,
+    path_arg: u64,
+    parent_cageid: u64,
+    argv: u64,
+    argv_cageid: u64,
+    envs: u64,
+    envs_cageid: u64,
+    child_cageid: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    wasmtime_lind_multi_process::spawn_syscall::<HostCtx, CliOptions>(
+        cageid,
+        path_arg,
+        parent_cageid,
+        argv,
+        argv_cageid,
+        envs,
+        envs_cageid,
+        child_cageid,
+        arg4_cageid,
+        arg5,
+        arg5_cageid,
+        arg6,
+        arg6_cageid,
+    )
+}
+
+pub extern "C" fn checkpoint_syscall_entry(
+    cageid: u64,
+    path_arg: u64,
+    path_arg_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    wasmtime_lind_multi_process::checkpoint_syscall::<HostCtx, CliOptions>(
+        cageid,
+        path_arg,
+        path_arg_cageid,
+        arg2,
+        arg2_cageid,
+        arg3,
+        arg3_cageid,
+        arg4,
+        arg4_cageid,
+        arg5,
+        arg5_cageid,
+        arg6,
+        arg6_cageid,
+    )
+}
+
 pub extern "C" fn exit_syscall_entry(
     cageid: u64,
     exit_code: u64,
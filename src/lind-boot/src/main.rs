@@ -70,9 +70,20 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    // The memory report file is a host path, so it is opened before the chroot
+    cage::memory::introspect::configure(lindboot_cli.report_config())?;
+
     // Not a precompile command, chroot to lindfs
     chroot_to_lindfs();
 
+    // Network namespace, hostname, CPU, memory and ASLR settings must be in place before the
+    // first cage is created
+    rawposix::netns::configure(lindboot_cli.net_config());
+    rawposix::uts::configure(lindboot_cli.uts_config());
+    rawposix::sched::configure(lindboot_cli.sched_config());
+    cage::memory::quota::configure(lindboot_cli.memory_config());
+    cage::memory::aslr::configure(lindboot_cli.aslr_config());
+
     // Initialize RawPOSIX and register RawPOSIX syscalls with 3i
     rawposix_start(0);
 
@@ -0,0 +1,852 @@
+//! Cage checkpoints
+//!
+//! `lind_checkpoint(path)` saves the calling cage, with the cages below it, to a snapshot file,
+//! and `lind-boot --restore` starts a new Lind process from it, in which the cage resumes as the
+//! first cage and its descendants resume under it with the cage IDs they had. This module holds
+//! the RawPOSIX part of the snapshot, a `CageImage` per cage, and the little-endian encoding the
+//! whole file uses. Wasmtime adds the state of each Wasm instance (globals and the Asyncify unwind
+//! location), which it captures by unwinding the cages as for fork.
+//!
+//! A `CageImage` holds:
+//! - the cage's ID, its parent and its zombies.
+//! - every `vmmap` region with its protection and the contents of its non-zero pages. Private
+//!   file mappings are saved as anonymous memory holding the same data. Regions the cage cannot
+//!   read are made readable while their pages are saved, as the cage may have written to them
+//!   before it changed their protection.
+//! - the fd table. Descriptors on lind-boot's stdio stay on the stdio of the restoring process.
+//!   Regular files and directories are reopened by path at the same offset, so the path must
+//!   still exist, and `/proc` must be mounted in lindfs to find it.
+//! - the cwd, umask, signal mask, handlers and pending signals, and the `ITIMER_REAL` timer.
+//!
+//! Every saved cage must be single-threaded, and shared memory, shared file mappings and other
+//! kinds of descriptors (pipes, sockets, memfds...) fail with `EOPNOTSUPP`.
+//!
+//! `lind-boot --preinit` saves only the memory part of the image, a `MemoryImage`, into the
+//! module it pre-initializes, and every cage that loads the module restores it in place of the
+//! module's own initial memory.
+use crate::fs_calls::close_syscall;
+use cage::memory::quota;
+use cage::timer::IntervalTimer;
+use cage::{
+    add_cage, get_cage, Cage, ExitStatus, MemoryBackingType, Vmmap, VmmapEntry, VmmapOps, Zombie,
+};
+use dashmap::DashMap;
+use fdtables;
+use parking_lot::{Mutex, RwLock};
+use std::ffi::{CString, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering::*;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64};
+use std::sync::Arc;
+use std::time::Duration;
+use sysdefs::constants::err_const::{get_errno, Errno};
+use sysdefs::constants::fs_const::{
+    MAP_ANONYMOUS, MAP_FIXED, MAP_PRIVATE, MAP_SHARED, O_CREAT, O_EXCL, O_TRUNC, PAGESHIFT,
+    PAGESIZE, PROT_NONE, PROT_READ, PROT_WRITE, SEEK_SET,
+};
+use sysdefs::constants::lind_platform_const::{
+    FDKIND_KERNEL, MAX_LINEAR_MEMORY64_SIZE, UNUSED_ARG, UNUSED_ID,
+};
+use sysdefs::data::fs_struct::SigactionStruct;
+
+/// First bytes of every snapshot file.
+pub const CHECKPOINT_MAGIC: &[u8; 8] = b"LINDCKPT";
+
+/// Version of the snapshot format, bumped whenever the encoding changes.
+pub const CHECKPOINT_VERSION: u32 = 2;
+
+/// Host fds below this one are lind-boot's stdio.
+const STDIO_FDS: u64 = 3;
+
+fn host_errno() -> Errno {
+    Errno::from_discriminant(get_errno()).unwrap_or(Errno::EIO)
+}
+
+/// Encodes a snapshot file, starting with its magic and version.
+pub struct ImageWriter {
+    buf: Vec<u8>,
+}
+
+impl ImageWriter {
+    pub fn new() -> Self {
+        let mut writer = ImageWriter { buf: Vec::new() };
+        writer.buf.extend_from_slice(CHECKPOINT_MAGIC);
+        writer.put_u32(CHECKPOINT_VERSION);
+        writer
+    }
+
+    pub fn put_u32(&mut self, value: u32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_i32(&mut self, value: i32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_u64(&mut self, value: u64) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_i64(&mut self, value: i64) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Writes `bytes` preceded by their length.
+    pub fn put_bytes(&mut self, bytes: &[u8]) {
+        self.put_u64(bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl Default for ImageWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decodes a snapshot file written by `ImageWriter`.
+///
+/// Every getter fails with `EINVAL` once the file is exhausted.
+pub struct ImageReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ImageReader<'a> {
+    /// # Returns
+    /// * `Err(EINVAL)` if `buf` does not start with the magic and version of this format
+    pub fn new(buf: &'a [u8]) -> Result<Self, Errno> {
+        let mut reader = ImageReader { buf, pos: 0 };
+        if reader.take(CHECKPOINT_MAGIC.len())? != CHECKPOINT_MAGIC
+            || reader.get_u32()? != CHECKPOINT_VERSION
+        {
+            return Err(Errno::EINVAL);
+        }
+        Ok(reader)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Errno> {
+        let end = self.pos.checked_add(len).ok_or(Errno::EINVAL)?;
+        let bytes = self.buf.get(self.pos..end).ok_or(Errno::EINVAL)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    pub fn get_u32(&mut self) -> Result<u32, Errno> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn get_i32(&mut self) -> Result<i32, Errno> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn get_u64(&mut self) -> Result<u64, Errno> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn get_i64(&mut self) -> Result<i64, Errno> {
+        Ok(i64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn get_bytes(&mut self) -> Result<&'a [u8], Errno> {
+        let len = usize::try_from(self.get_u64()?).map_err(|_| Errno::EINVAL)?;
+        self.take(len)
+    }
+
+    /// Whether the whole file has been read.
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.buf.len()
+    }
+}
+
+/// Where a saved descriptor is reopened from.
+enum FdSource {
+    /// One of lind-boot's stdio fds.
+    Stdio(u64),
+    /// A regular file or directory, with its `F_GETFL` flags and offset.
+    Path {
+        path: PathBuf,
+        flags: i32,
+        offset: i64,
+    },
+}
+
+struct FdImage {
+    fd: u64,
+    source: FdSource,
+    cloexec: bool,
+    perfdinfo: u64,
+}
+
+/// A `vmmap` region and its non-zero pages, as runs of consecutive pages keyed by their index
+/// in the region.
+struct RegionImage {
+    page_num: u32,
+    npages: u32,
+    prot: i32,
+    maxprot: i32,
+    flags: i32,
+    runs: Vec<(u32, Vec<u8>)>,
+}
+
+/// The memory of a saved cage: its `vmmap` regions and where its heap starts.
+pub struct MemoryImage {
+    heap_start: u32,
+    regions: Vec<RegionImage>,
+}
+
+/// The RawPOSIX state of a saved cage. See the module documentation for what it covers.
+pub struct CageImage {
+    cageid: u64,
+    parent: u64,
+    zombies: Vec<Zombie>,
+    cwd: PathBuf,
+    umask: u32,
+    sigset: u64,
+    signal_handlers: Vec<(i32, SigactionStruct)>,
+    pending_signals: Vec<i32>,
+    itimer: (Duration, Duration),
+    memory: MemoryImage,
+    fds: Vec<FdImage>,
+}
+
+impl CageImage {
+    /// Saves the state of a cage. The cage must not run while it is saved, and its children are
+    /// saved on their own.
+    ///
+    /// # Returns
+    /// * `Err(EOPNOTSUPP)` if the cage has several threads, shared memory, shared file mappings or
+    ///   descriptors other than stdio, regular files and directories
+    pub fn capture(cageid: u64) -> Result<CageImage, Errno> {
+        let cage = get_cage(cageid).ok_or(Errno::ESRCH)?;
+        if cage.epoch_handler.len() > 1 {
+            return Err(Errno::EOPNOTSUPP);
+        }
+
+        let mut signal_handlers: Vec<_> = cage
+            .signalhandler
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect();
+        signal_handlers.sort_by_key(|(signo, _)| *signo);
+
+        let cwd = cage.cwd.read().as_ref().clone();
+        let pending_signals = cage.pending_signals.read().clone();
+        let zombies = cage.zombies.read().clone();
+        Ok(CageImage {
+            cageid,
+            parent: cage.parent,
+            zombies,
+            cwd,
+            umask: cage.umask.load(Relaxed),
+            sigset: cage.sigset.load(Relaxed),
+            signal_handlers,
+            pending_signals,
+            itimer: cage.interval_timer.get_itimer(),
+            memory: capture_memory(&cage)?,
+            fds: capture_fds(cageid)?,
+        })
+    }
+
+    /// The ID the cage had when it was saved.
+    pub fn cageid(&self) -> u64 {
+        self.cageid
+    }
+
+    /// The parent the cage had when it was saved.
+    pub fn parent(&self) -> u64 {
+        self.parent
+    }
+
+    /// Creates the cage of a saved descendant under `parent`, with the ID it had, so that the
+    /// pids its parent holds still name it. `restore` then fills it in once its program is loaded.
+    ///
+    /// # Returns
+    /// * `Err(EEXIST)` if a cage already has the ID
+    pub fn create_cage(&self, parent: u64) -> Result<(), Errno> {
+        let parentcage = get_cage(parent).ok_or(Errno::ESRCH)?;
+        if get_cage(self.cageid).is_some() {
+            return Err(Errno::EEXIST);
+        }
+        cage::claim_cage_id(self.cageid);
+        fdtables::init_empty_cage(self.cageid);
+
+        let cageobj = Cage {
+            cageid: self.cageid,
+            cwd: RwLock::new(Arc::new(self.cwd.clone())),
+            umask: AtomicU32::new(self.umask),
+            netns: AtomicU64::new(parentcage.netns.load(Relaxed)),
+            utsns: AtomicU64::new(parentcage.utsns.load(Relaxed)),
+            cpu_affinity: RwLock::new(parentcage.cpu_affinity.read().clone()),
+            nice: AtomicI32::new(parentcage.nice.load(Relaxed)),
+            parent,
+            rev_shm: Mutex::new(Vec::new()),
+            main_threadid: RwLock::new(0),
+            interval_timer: IntervalTimer::new(self.cageid),
+            epoch_handler: DashMap::new(),
+            os_tid_map: DashMap::new(),
+            robust_list: DashMap::new(),
+            pending_signals: RwLock::new(vec![]),
+            signalhandler: DashMap::new(),
+            sigset: AtomicU64::new(0),
+            zombies: RwLock::new(vec![]),
+            child_num: AtomicU64::new(0),
+            vmmap: RwLock::new(Vmmap::new()),
+            memory_snapshot: Mutex::new(None),
+            mem_quota: parentcage.mem_quota.for_child(parent, self.cageid),
+            final_exit_status: RwLock::new(None),
+            exit_group_initiated: AtomicBool::new(false),
+            is_dead: AtomicBool::new(false),
+            grate_inflight: AtomicU64::new(0),
+        };
+
+        parentcage.child_num.fetch_add(1, SeqCst);
+        add_cage(self.cageid, cageobj);
+
+        // The cage keeps its parent's syscall routing, as after fork
+        threei::copy_handler_table_to_cage(
+            UNUSED_ARG,
+            UNUSED_ARG,
+            parent,
+            self.cageid,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+        );
+        Ok(())
+    }
+
+    /// Replaces the state of a freshly loaded cage with the saved one.
+    ///
+    /// The cage must run the program the image was saved from, and must not have started yet.
+    pub fn restore(&self, cageid: u64) -> Result<(), Errno> {
+        let cage = get_cage(cageid).ok_or(Errno::ESRCH)?;
+
+        restore_memory(&cage, &self.memory)?;
+        restore_fds(cageid, &self.fds)?;
+
+        // The pids of the zombies must not be handed out again before they are waited for
+        for zombie in &self.zombies {
+            cage::claim_cage_id(zombie.cageid);
+        }
+        *cage.zombies.write() = self.zombies.clone();
+
+        *cage.cwd.write() = Arc::new(self.cwd.clone());
+        cage.umask.store(self.umask, Relaxed);
+        cage.sigset.store(self.sigset, Relaxed);
+        cage.signalhandler.clear();
+        for (signo, action) in &self.signal_handlers {
+            cage.signalhandler.insert(*signo, *action);
+        }
+        *cage.pending_signals.write() = self.pending_signals.clone();
+        let (curr, next) = self.itimer;
+        cage.interval_timer.set_itimer(curr, next);
+        Ok(())
+    }
+
+    pub fn write_to(&self, writer: &mut ImageWriter) {
+        writer.put_u64(self.cageid);
+        writer.put_u64(self.parent);
+        writer.put_u32(self.zombies.len() as u32);
+        for zombie in &self.zombies {
+            writer.put_u64(zombie.cageid);
+            match zombie.exit_code {
+                ExitStatus::Exited(code) => {
+                    writer.put_u32(0);
+                    writer.put_i32(code);
+                }
+                ExitStatus::Signaled(signo, core_dump) => {
+                    writer.put_u32(if core_dump { 2 } else { 1 });
+                    writer.put_i32(signo);
+                }
+            }
+        }
+        writer.put_bytes(self.cwd.as_os_str().as_bytes());
+        writer.put_u32(self.umask);
+        writer.put_u64(self.sigset);
+        writer.put_u32(self.signal_handlers.len() as u32);
+        for (signo, action) in &self.signal_handlers {
+            writer.put_i32(*signo);
+            writer.put_u32(action.sa_handler);
+            writer.put_u64(action.sa_mask);
+            writer.put_i32(action.sa_flags);
+        }
+        writer.put_u32(self.pending_signals.len() as u32);
+        for signo in &self.pending_signals {
+            writer.put_i32(*signo);
+        }
+        for duration in [self.itimer.0, self.itimer.1] {
+            writer.put_u64(duration.as_secs());
+            writer.put_u32(duration.subsec_nanos());
+        }
+
+        self.memory.write_to(writer);
+
+        writer.put_u32(self.fds.len() as u32);
+        for fd in &self.fds {
+            writer.put_u64(fd.fd);
+            writer.put_u32(fd.cloexec as u32);
+            writer.put_u64(fd.perfdinfo);
+            match &fd.source {
+                FdSource::Stdio(underfd) => {
+                    writer.put_u32(0);
+                    writer.put_u64(*underfd);
+                }
+                FdSource::Path {
+                    path,
+                    flags,
+                    offset,
+                } => {
+                    writer.put_u32(1);
+                    writer.put_bytes(path.as_os_str().as_bytes());
+                    writer.put_i32(*flags);
+                    writer.put_i64(*offset);
+                }
+            }
+        }
+    }
+
+    pub fn read_from(reader: &mut ImageReader) -> Result<CageImage, Errno> {
+        let cageid = reader.get_u64()?;
+        let parent = reader.get_u64()?;
+        let mut zombies = Vec::new();
+        for _ in 0..reader.get_u32()? {
+            let zombie_id = reader.get_u64()?;
+            let exit_code = match (reader.get_u32()?, reader.get_i32()?) {
+                (0, code) => ExitStatus::Exited(code),
+                (1, signo) => ExitStatus::Signaled(signo, false),
+                (2, signo) => ExitStatus::Signaled(signo, true),
+                _ => return Err(Errno::EINVAL),
+            };
+            zombies.push(Zombie {
+                cageid: zombie_id,
+                exit_code,
+            });
+        }
+        let cwd = PathBuf::from(OsStr::from_bytes(reader.get_bytes()?));
+        let umask = reader.get_u32()?;
+        let sigset = reader.get_u64()?;
+        let mut signal_handlers = Vec::new();
+        for _ in 0..reader.get_u32()? {
+            let signo = reader.get_i32()?;
+            let action = SigactionStruct {
+                sa_handler: reader.get_u32()?,
+                sa_mask: reader.get_u64()?,
+                sa_flags: reader.get_i32()?,
+                ..Default::default()
+            };
+            signal_handlers.push((signo, action));
+        }
+        let mut pending_signals = Vec::new();
+        for _ in 0..reader.get_u32()? {
+            pending_signals.push(reader.get_i32()?);
+        }
+        let mut durations = [Duration::ZERO; 2];
+        for duration in durations.iter_mut() {
+            let secs = reader.get_u64()?;
+            let nanos = reader.get_u32()?;
+            if nanos >= 1_000_000_000 {
+                return Err(Errno::EINVAL);
+            }
+            *duration = Duration::new(secs, nanos);
+        }
+
+        let memory = MemoryImage::read_from(reader)?;
+
+        let mut fds = Vec::new();
+        for _ in 0..reader.get_u32()? {
+            let fd = reader.get_u64()?;
+            let cloexec = reader.get_u32()? != 0;
+            let perfdinfo = reader.get_u64()?;
+            let source = match reader.get_u32()? {
+                0 => FdSource::Stdio(reader.get_u64()?),
+                1 => FdSource::Path {
+                    path: PathBuf::from(OsStr::from_bytes(reader.get_bytes()?)),
+                    flags: reader.get_i32()?,
+                    offset: reader.get_i64()?,
+                },
+                _ => return Err(Errno::EINVAL),
+            };
+            fds.push(FdImage {
+                fd,
+                source,
+                cloexec,
+                perfdinfo,
+            });
+        }
+
+        Ok(CageImage {
+            cageid,
+            parent,
+            zombies,
+            cwd,
+            umask,
+            sigset,
+            signal_handlers,
+            pending_signals,
+            itimer: (durations[0], durations[1]),
+            memory,
+            fds,
+        })
+    }
+}
+
+impl MemoryImage {
+    /// Saves the memory of a cage whose initialization `lind-boot --preinit` bakes into its
+    /// module. The cage must not run while it is saved.
+    ///
+    /// Only memory is carried over to the cages that load the module, so the cage must not hold
+    /// anything else a freshly started cage would not.
+    ///
+    /// # Returns
+    /// * `Err(EOPNOTSUPP)` if the cage has children, several threads, signal handlers, shared
+    ///   memory, shared file mappings or descriptors other than stdio
+    pub fn capture_preinit(cageid: u64) -> Result<MemoryImage, Errno> {
+        let cage = get_cage(cageid).ok_or(Errno::ESRCH)?;
+        if cage.child_num.load(Acquire) > 0
+            || cage.epoch_handler.len() > 1
+            || !cage.signalhandler.is_empty()
+        {
+            return Err(Errno::EOPNOTSUPP);
+        }
+        let stdio_only = fdtables::return_fdtable_copy(cageid)
+            .into_values()
+            .all(|entry| entry.fdkind == FDKIND_KERNEL && entry.underfd < STDIO_FDS);
+        if !stdio_only {
+            return Err(Errno::EOPNOTSUPP);
+        }
+        capture_memory(&cage)
+    }
+
+    /// Replaces the memory the loader mapped in a cage that has not started yet.
+    pub fn restore(&self, cageid: u64) -> Result<(), Errno> {
+        let cage = get_cage(cageid).ok_or(Errno::ESRCH)?;
+        restore_memory(&cage, self)
+    }
+
+    /// Encodes the image on its own, with the magic and version of snapshot files.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = ImageWriter::new();
+        self.write_to(&mut writer);
+        writer.into_bytes()
+    }
+
+    /// Decodes an image encoded by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<MemoryImage, Errno> {
+        let mut reader = ImageReader::new(bytes)?;
+        let memory = MemoryImage::read_from(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(Errno::EINVAL);
+        }
+        Ok(memory)
+    }
+
+    pub fn write_to(&self, writer: &mut ImageWriter) {
+        writer.put_u32(self.heap_start);
+        writer.put_u32(self.regions.len() as u32);
+        for region in &self.regions {
+            writer.put_u32(region.page_num);
+            writer.put_u32(region.npages);
+            writer.put_i32(region.prot);
+            writer.put_i32(region.maxprot);
+            writer.put_i32(region.flags);
+            writer.put_u32(region.runs.len() as u32);
+            for (page, data) in &region.runs {
+                writer.put_u32(*page);
+                writer.put_bytes(data);
+            }
+        }
+    }
+
+    pub fn read_from(reader: &mut ImageReader) -> Result<MemoryImage, Errno> {
+        let heap_start = reader.get_u32()?;
+        let mut regions = Vec::new();
+        for _ in 0..reader.get_u32()? {
+            let mut region = RegionImage {
+                page_num: reader.get_u32()?,
+                npages: reader.get_u32()?,
+                prot: reader.get_i32()?,
+                maxprot: reader.get_i32()?,
+                flags: reader.get_i32()?,
+                runs: Vec::new(),
+            };
+            // checked against the address space of the cage on restore
+            let end = (region.page_num as u64) + (region.npages as u64);
+            if region.npages == 0 || end > (MAX_LINEAR_MEMORY64_SIZE + 1) >> PAGESHIFT {
+                return Err(Errno::EINVAL);
+            }
+            for _ in 0..reader.get_u32()? {
+                let page = reader.get_u32()?;
+                let data = reader.get_bytes()?.to_vec();
+                let pages = data.len() as u64 / PAGESIZE as u64;
+                if data.len() % PAGESIZE as usize != 0 || page as u64 + pages > region.npages as u64
+                {
+                    return Err(Errno::EINVAL);
+                }
+                region.runs.push((page, data));
+            }
+            regions.push(region);
+        }
+        Ok(MemoryImage {
+            heap_start,
+            regions,
+        })
+    }
+}
+
+/// Saves every region of the cage's `vmmap` with its non-zero pages.
+fn capture_memory(cage: &Cage) -> Result<MemoryImage, Errno> {
+    let vmmap = cage.vmmap.read();
+    let mut regions = Vec::new();
+    for (interval, entry) in vmmap.double_ended_iter() {
+        let shared = entry.flags & MAP_SHARED as i32 != 0;
+        let flags = match entry.backing {
+            MemoryBackingType::SharedMemory(_) => return Err(Errno::EOPNOTSUPP),
+            MemoryBackingType::FileDescriptor(_) if shared => return Err(Errno::EOPNOTSUPP),
+            // The data of a private file mapping is kept, not its link to the file
+            MemoryBackingType::FileDescriptor(_) => entry.flags | MAP_ANONYMOUS as i32,
+            _ => entry.flags,
+        };
+
+        // Intervals are inclusive, and an entry's own page range may be stale after a split
+        let page_num = interval.start();
+        let npages = interval.end() - interval.start() + 1;
+        let base = vmmap.user_to_sys((page_num as u64) << PAGESHIFT) as *mut u8;
+        let len = (npages as usize) << PAGESHIFT;
+        let unreadable = entry.prot & PROT_READ == 0;
+        if unreadable && unsafe { libc::mprotect(base as *mut libc::c_void, len, PROT_READ) } < 0 {
+            return Err(host_errno());
+        }
+
+        let mut runs: Vec<(u32, Vec<u8>)> = Vec::new();
+        let mut run: Option<(u32, Vec<u8>)> = None;
+        for page in 0..npages {
+            let data = unsafe {
+                std::slice::from_raw_parts(
+                    base.add((page as usize) << PAGESHIFT),
+                    PAGESIZE as usize,
+                )
+            };
+            if data.iter().all(|&byte| byte == 0) {
+                runs.extend(run.take());
+            } else {
+                run.get_or_insert_with(|| (page, Vec::new()))
+                    .1
+                    .extend_from_slice(data);
+            }
+        }
+        runs.extend(run);
+
+        if unreadable && unsafe { libc::mprotect(base as *mut libc::c_void, len, entry.prot) } < 0 {
+            return Err(host_errno());
+        }
+
+        regions.push(RegionImage {
+            page_num,
+            npages,
+            prot: entry.prot,
+            maxprot: entry.maxprot,
+            flags,
+            runs,
+        });
+    }
+    Ok(MemoryImage {
+        heap_start: vmmap.heap_start,
+        regions,
+    })
+}
+
+/// Unmaps everything the loader mapped in the cage and maps the saved regions instead.
+fn restore_memory(cage: &Cage, memory: &MemoryImage) -> Result<(), Errno> {
+    let mut vmmap = cage.vmmap.write();
+    // a 64-bit cage's memory does not fit in a 32-bit one
+    let end_address = vmmap.end_address as u64;
+    if memory
+        .regions
+        .iter()
+        .any(|region| region.page_num as u64 + region.npages as u64 > end_address)
+    {
+        return Err(Errno::EINVAL);
+    }
+
+    let current: Vec<(u32, u32)> = vmmap
+        .double_ended_iter()
+        .map(|(interval, _)| (interval.start(), interval.end() - interval.start() + 1))
+        .collect();
+    for (page_num, npages) in current {
+        let addr = vmmap.user_to_sys((page_num as u64) << PAGESHIFT);
+        let ret = unsafe {
+            libc::mmap(
+                addr as *mut libc::c_void,
+                (npages as usize) << PAGESHIFT,
+                PROT_NONE,
+                (MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED) as i32,
+                -1,
+                0,
+            )
+        };
+        if ret == libc::MAP_FAILED {
+            return Err(host_errno());
+        }
+        let _ = vmmap.remove_entry(page_num, npages);
+    }
+
+    vmmap.set_heap_start(memory.heap_start);
+    for region in &memory.regions {
+        let addr = vmmap.user_to_sys((region.page_num as u64) << PAGESHIFT) as *mut u8;
+        let len = (region.npages as usize) << PAGESHIFT;
+        let sharing = region.flags & (MAP_SHARED | MAP_PRIVATE) as i32;
+        let ret = unsafe {
+            libc::mmap(
+                addr as *mut libc::c_void,
+                len,
+                PROT_READ | PROT_WRITE,
+                sharing | (MAP_ANONYMOUS | MAP_FIXED) as i32,
+                -1,
+                0,
+            )
+        };
+        if ret == libc::MAP_FAILED {
+            return Err(host_errno());
+        }
+        for (page, data) in &region.runs {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    data.as_ptr(),
+                    addr.add((*page as usize) << PAGESHIFT),
+                    data.len(),
+                );
+            }
+        }
+        if region.prot != PROT_READ | PROT_WRITE
+            && unsafe { libc::mprotect(addr as *mut libc::c_void, len, region.prot) } < 0
+        {
+            return Err(host_errno());
+        }
+
+        vmmap.add_entry(VmmapEntry::new(
+            region.page_num,
+            region.npages,
+            region.prot,
+            region.maxprot,
+            region.flags,
+            false,
+            0,
+            0,
+            cage.cageid,
+            MemoryBackingType::Anonymous,
+        ));
+    }
+    quota::recount(cage, &vmmap);
+    Ok(())
+}
+
+/// Saves the descriptors of the cage, finding the path of files through `/proc/self/fd`.
+fn capture_fds(cageid: u64) -> Result<Vec<FdImage>, Errno> {
+    let mut table: Vec<_> = fdtables::return_fdtable_copy(cageid).into_iter().collect();
+    table.sort_by_key(|(fd, _)| *fd);
+
+    let mut fds = Vec::new();
+    for (fd, entry) in table {
+        if entry.fdkind != FDKIND_KERNEL {
+            return Err(Errno::EOPNOTSUPP);
+        }
+        let source = if entry.underfd < STDIO_FDS {
+            FdSource::Stdio(entry.underfd)
+        } else {
+            capture_file(entry.underfd as i32)?
+        };
+        fds.push(FdImage {
+            fd,
+            source,
+            cloexec: entry.should_cloexec,
+            perfdinfo: entry.perfdinfo,
+        });
+    }
+    Ok(fds)
+}
+
+fn capture_file(hostfd: i32) -> Result<FdSource, Errno> {
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstat(hostfd, &mut stat) } < 0 {
+        return Err(host_errno());
+    }
+    let kind = stat.st_mode & libc::S_IFMT;
+    // Unlinked files, memfds included, cannot be reopened
+    if (kind != libc::S_IFREG && kind != libc::S_IFDIR) || stat.st_nlink == 0 {
+        return Err(Errno::EOPNOTSUPP);
+    }
+
+    let path =
+        std::fs::read_link(format!("/proc/self/fd/{}", hostfd)).map_err(|_| Errno::EOPNOTSUPP)?;
+    let flags = unsafe { libc::fcntl(hostfd, libc::F_GETFL) };
+    let offset = unsafe { libc::lseek(hostfd, 0, libc::SEEK_CUR) };
+    if flags < 0 || offset < 0 {
+        return Err(host_errno());
+    }
+    Ok(FdSource::Path {
+        path,
+        flags,
+        offset,
+    })
+}
+
+/// Closes the descriptors the loader left in the cage and reopens the saved ones.
+fn restore_fds(cageid: u64, fds: &[FdImage]) -> Result<(), Errno> {
+    for fd in fdtables::return_fdtable_copy(cageid).into_keys() {
+        close_syscall(
+            cageid, fd, cageid, UNUSED_ARG, UNUSED_ID, UNUSED_ARG, UNUSED_ID, UNUSED_ARG,
+            UNUSED_ID, UNUSED_ARG, UNUSED_ID, UNUSED_ARG, UNUSED_ID,
+        );
+    }
+
+    for image in fds {
+        let underfd = match &image.source {
+            FdSource::Stdio(underfd) => *underfd,
+            FdSource::Path {
+                path,
+                flags,
+                offset,
+            } => {
+                let cpath = CString::new(path.as_os_str().as_bytes()).map_err(|_| Errno::EINVAL)?;
+                let hostfd =
+                    unsafe { libc::open(cpath.as_ptr(), flags & !(O_CREAT | O_EXCL | O_TRUNC)) };
+                if hostfd < 0 {
+                    return Err(host_errno());
+                }
+                if unsafe { libc::lseek(hostfd, *offset, SEEK_SET) } < 0 {
+                    let errno = host_errno();
+                    unsafe { libc::close(hostfd) };
+                    return Err(errno);
+                }
+                hostfd as u64
+            }
+        };
+        if let Err(e) = fdtables::get_specific_virtual_fd(
+            cageid,
+            image.fd,
+            FDKIND_KERNEL,
+            underfd,
+            image.cloexec,
+            image.perfdinfo,
+        ) {
+            if underfd >= STDIO_FDS {
+                unsafe { libc::close(underfd as i32) };
+            }
+            return Err(Errno::from_discriminant(e as i32).unwrap_or(Errno::EBADF));
+        }
+    }
+    Ok(())
+}
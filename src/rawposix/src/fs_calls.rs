@@ -1,22 +1,36 @@
+use crate::futex;
+use crate::inproc_socket;
+use crate::mqueue;
+use cage::memory::quota;
 use cage::{
-    get_cage, get_shm_length, is_mmap_error, new_shm_segment, round_up_page, shmat_helper,
-    shmdt_helper, signal::signal::lind_send_signal, MemoryBackingType, VmmapOps, HEAP_ENTRY_INDEX,
-    SHM_METADATA,
+    check_futex_addr, futex_shared_mapping, get_cage, get_shm_length, is_mmap_error,
+    new_shm_segment, round_up_page, shmat_helper, shmdt_helper, signal::current_thread_id,
+    signal::signal::lind_send_signal, MemoryBackingType, SemctlArg, Vmmap, VmmapOps, SHM_METADATA,
 };
 use dashmap::mapref::entry::Entry::{Occupied, Vacant};
 use fdtables;
 use libc::c_void;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use sysdefs::constants::err_const::{get_errno, handle_errno, syscall_error, Errno};
 use sysdefs::constants::fs_const::{
-    AT_FDCWD, FIOASYNC, FIONBIO, FIONREAD, F_GETLK64, F_SETLK64, F_SETLKW64, MAP_ANONYMOUS,
-    MAP_FIXED, MAP_POPULATE, MAP_PRIVATE, MAP_SHARED, O_CLOEXEC, PAGESHIFT, PAGESIZE, PROT_EXEC,
-    PROT_NONE, PROT_READ, PROT_WRITE, SHMMAX, SHMMIN, SHM_DEST, SHM_RDONLY, STDERR_FILENO,
-    STDIN_FILENO, STDOUT_FILENO, TIOCGWINSZ,
+    AT_FDCWD, FIOASYNC, FIONBIO, FIONREAD, F_GETLK64, F_SETLK64, F_SETLKW64, IPC_64, MADV_DONTNEED,
+    MADV_FREE, MADV_HUGEPAGE, MADV_NOHUGEPAGE, MADV_NORMAL, MADV_RANDOM, MADV_SEQUENTIAL,
+    MADV_WILLNEED, MAP_ANONYMOUS, MAP_FIXED, MAP_GROWSDOWN, MAP_NORESERVE, MAP_POPULATE,
+    MAP_PRIVATE, MAP_SHARED, MAP_STACK, MFD_ALLOW_SEALING, MFD_CLOEXEC, MFD_NAME_MAX,
+    MREMAP_DONTUNMAP, MREMAP_FIXED, MREMAP_MAYMOVE, MSGMAX, MS_ASYNC, MS_INVALIDATE, MS_SYNC,
+    O_CLOEXEC, PAGESHIFT, PAGESIZE, PROT_EXEC, PROT_NONE, PROT_READ, PROT_WRITE, SEMOPM, SHMMAX,
+    SHMMIN, SHM_DEST, SHM_RDONLY, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, S_IRWXA, TIOCGWINSZ,
 };
 
-use sysdefs::constants::lind_platform_const::{FDKIND_KERNEL, MAXFD, UNUSED_ARG, UNUSED_ID};
-use sysdefs::constants::sys_const::{DEFAULT_GID, DEFAULT_UID, SIGPIPE};
+use sysdefs::constants::lind_platform_const::{FDKIND_KERNEL, UNUSED_ARG, UNUSED_ID};
+use sysdefs::constants::sys_const::{
+    DEFAULT_GID, DEFAULT_UID, FUTEX_CLOCK_REALTIME, FUTEX_CMD_MASK, FUTEX_CMP_REQUEUE,
+    FUTEX_CMP_REQUEUE_PI, FUTEX_LOCK_PI, FUTEX_LOCK_PI2, FUTEX_PRIVATE_FLAG, FUTEX_REQUEUE,
+    FUTEX_TRYLOCK_PI, FUTEX_UNLOCK_PI, FUTEX_WAIT, FUTEX_WAIT_BITSET, FUTEX_WAIT_REQUEUE_PI,
+    FUTEX_WAKE_OP, ROBUST_LIST_HEAD_SIZE, ROBUST_LIST_HEAD_SIZE64, SIGPIPE,
+};
+use sysdefs::data::fs_struct::{MsqidStruct, SembufStruct, SemidStruct};
 use sysdefs::lind_debug_panic;
 use typemap::cage_helpers::*;
 use typemap::datatype_conversion::*;
@@ -37,14 +51,30 @@ pub fn kernel_close(fdentry: fdtables::FDTableEntry, _count: u64) -> Result<(),
         return Ok(());
     }
 
+    crate::net_calls::untrack_unix_socket(kernel_fd);
+    crate::netns::release_socket(kernel_fd);
+
     let ret = unsafe { libc::close(kernel_fd) };
     if ret < 0 {
         return Err(handle_errno(get_errno(), "close_syscall"));
     }
+    crate::rtnetlink::reap_closed();
 
     Ok(())
 }
 
+/// Helper for the syscalls that create filesystem objects (open/openat/mkdir/mknod/bind).
+///
+/// The host process runs with a umask of 0 (set in `rawposix_start`), so the calling cage's own
+/// file creation mask has to be cleared from the requested mode before it reaches the kernel.
+/// File type bits (e.g. `S_IFIFO` for mknod) are never part of the mask and pass through.
+pub fn apply_cage_umask(cageid: u64, mode: u32) -> u32 {
+    match get_cage(cageid) {
+        Some(cage) => mode & !cage.umask.load(Ordering::Relaxed),
+        None => mode,
+    }
+}
+
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/openat2.2.html
 ///
 /// Linux `openat` opens the file specified by the path. If path is relative, then
@@ -59,7 +89,8 @@ pub fn kernel_close(fdentry: fdtables::FDTableEntry, _count: u64) -> Result<(),
 ///     - oflag_arg: This argument contains the file status flags and file access modes which will be alloted to
 ///                 the open file description. The flags are combined together using a bitwise-inclusive-OR and the
 ///                 result is passed as an argument to the function. We need to check if `O_CLOEXEC` has been set.
-///     - mode_arg: This represents the permission of the newly created file. Directly passing to kernel.
+///     - mode_arg: This represents the permission of the newly created file. The cage's umask is
+///                 cleared from it before passing to kernel.
 ///
 /// ## Returns:
 /// On success, a new file descriptor is returned.  On error, -1 is
@@ -128,8 +159,19 @@ pub extern "C" fn openat_syscall(
             Err(_) => return syscall_error(Errno::EINVAL, "openat", "invalid path"),
         };
 
-        let kernel_fd =
-            unsafe { libc::openat(host_fd, c_path.as_ptr(), oflag, mode as libc::mode_t) };
+        let mode = apply_cage_umask(cageid, mode);
+        // An absolute path ignores the dirfd, and may name an overlaid file or a shared memory
+        // object
+        let overlay = if raw_path.starts_with('/') {
+            crate::uts::open_overlay(&path, oflag).or_else(|| crate::shm::open(&path, oflag, mode))
+        } else {
+            None
+        };
+        let kernel_fd = match overlay {
+            Some(Ok(fd)) => fd,
+            Some(Err(e)) => return syscall_error(e, "openat", "could not open overlaid file"),
+            None => unsafe { libc::openat(host_fd, c_path.as_ptr(), oflag, mode as libc::mode_t) },
+        };
         if kernel_fd < 0 {
             return handle_errno(get_errno(), "openat_syscall");
         }
@@ -143,7 +185,11 @@ pub extern "C" fn openat_syscall(
             0,
         ) {
             Ok(vfd) => vfd as i32,
-            Err(_) => syscall_error(Errno::EMFILE, "openat_syscall", "Too many files opened"),
+            Err(e) => {
+                // EMFILE or ENFILE from fdtables; don't leak the kernel fd
+                unsafe { libc::close(kernel_fd) };
+                handle_errno(e as i32, "openat_syscall")
+            }
         }
     }
 }
@@ -162,7 +208,8 @@ pub extern "C" fn openat_syscall(
 ///     - oflag_arg: This argument contains the file status flags and file access modes which will be alloted to
 ///                 the open file description. The flags are combined together using a bitwise-inclusive-OR and the
 ///                 result is passed as an argument to the function. We need to check if `O_CLOEXEC` has been set.
-///     - mode_arg: This represents the permission of the newly created file. Directly passing to kernel.
+///     - mode_arg: This represents the permission of the newly created file. The cage's umask is
+///                 cleared from it before passing to kernel.
 ///
 /// ## Returns:
 /// same with man page
@@ -201,8 +248,19 @@ pub extern "C" fn open_syscall(
         );
     }
 
-    // Get the kernel fd first
-    let kernel_fd = unsafe { libc::open(path.as_ptr(), oflag, mode) };
+    // Get the kernel fd first, with the cage's umask applied to the creation mode. Reading
+    // /etc/hosts or /etc/resolv.conf may give lind-boot's overlay instead (see `uts`), and paths
+    // under /dev/shm name shared memory objects (see `shm`).
+    let mode = apply_cage_umask(cageid, mode);
+    let kernel_fd = match crate::uts::open_overlay(&path, oflag) {
+        Some(Ok(fd)) => fd,
+        Some(Err(e)) => return syscall_error(e, "open", "could not build overlay file"),
+        None => match crate::shm::open(&path, oflag, mode) {
+            Some(Ok(fd)) => fd,
+            Some(Err(e)) => return syscall_error(e, "open", "could not open shared memory"),
+            None => unsafe { libc::open(path.as_ptr(), oflag, mode) },
+        },
+    };
 
     if kernel_fd < 0 {
         return handle_errno(get_errno(), "open_syscall");
@@ -220,7 +278,11 @@ pub extern "C" fn open_syscall(
         0,
     ) {
         Ok(vfd) => vfd as i32,
-        Err(_) => syscall_error(Errno::EMFILE, "open_syscall", "Too many files opened"),
+        Err(e) => {
+            // EMFILE or ENFILE from fdtables; don't leak the kernel fd
+            unsafe { libc::close(kernel_fd) };
+            handle_errno(e as i32, "open_syscall")
+        }
     }
 }
 
@@ -276,6 +338,23 @@ pub extern "C" fn read_syscall(
         );
     }
 
+    if let Some(sock) = inproc_socket::lookup(vfd_cageid, vfd_arg) {
+        let iov = [libc::iovec {
+            iov_base: buf as *mut c_void,
+            iov_len: count,
+        }];
+        return match sock.recv(cageid, &iov, 0) {
+            Ok(n) => n as i32,
+            Err(e) => syscall_error(e, "read", "in-process socket read failed"),
+        };
+    }
+
+    // Reading a message queue descriptor gives its status line, as in /dev/mqueue
+    if let Some(desc) = mqueue::lookup(vfd_cageid, vfd_arg) {
+        let buf = unsafe { std::slice::from_raw_parts_mut(buf as *mut u8, count) };
+        return desc.read_status(buf) as i32;
+    }
+
     // Call the underlying libc read.
     let ret = unsafe { libc::read(kernel_fd, buf as *mut c_void, count) as i32 };
     if ret < 0 {
@@ -345,8 +424,12 @@ pub extern "C" fn close_syscall(
 ///
 /// The Linux `futex()` syscall provides a mechanism for fast user-space locking. It allows a process or thread
 /// to wait for or wake another process or thread on a shared memory location without invoking heavy kernel-side
-/// synchronization primitives unless contention arises. This implementation wraps the futex syscall, allowing
-/// direct invocation with the relevant arguments passed from the current cage context.
+/// synchronization primitives unless contention arises. This implementation checks the futex words against the
+/// cage's vmmap, then runs the operation on the host futex of the translated address. Timeouts are converted from
+/// the cage's `struct __timespec64`, and PI futexes, which hold lind thread IDs, are emulated (see `crate::futex`).
+/// Futex words in memory shared between cages always use shared host operations, so that process-shared
+/// primitives work across cages that map the memory at different addresses (see `cage::futex_shared_mapping`).
+/// FUTEX_WAIT_REQUEUE_PI and FUTEX_CMP_REQUEUE_PI are not supported.
 ///
 /// Input:
 ///     - cageid: current cageid
@@ -376,13 +459,86 @@ pub extern "C" fn futex_syscall(
     val3_cageid: u64,
 ) -> i32 {
     let uaddr = uaddr_arg;
-    let futex_op = sc_convert_sysarg_to_u32(futex_op_arg, futex_op_cageid, cageid);
+    let futex_op = sc_convert_sysarg_to_i32(futex_op_arg, futex_op_cageid, cageid);
     let val = sc_convert_sysarg_to_u32(val_arg, val_cageid, cageid);
     let timeout = timeout_arg;
     let uaddr2 = uaddr2_arg;
     let val3 = sc_convert_sysarg_to_u32(val3_arg, val3_cageid, cageid);
 
-    let ret = unsafe { syscall(SYS_futex, uaddr, futex_op, val, timeout, uaddr2, val3) as i32 };
+    let cmd = futex_op & FUTEX_CMD_MASK;
+    let (prot, prot2) = match cmd {
+        FUTEX_WAKE_OP => (PROT_READ | PROT_WRITE, Some(PROT_READ | PROT_WRITE)),
+        FUTEX_REQUEUE | FUTEX_CMP_REQUEUE => (PROT_READ, Some(PROT_READ)),
+        FUTEX_LOCK_PI | FUTEX_LOCK_PI2 | FUTEX_TRYLOCK_PI | FUTEX_UNLOCK_PI => {
+            (PROT_READ | PROT_WRITE, None)
+        }
+        FUTEX_WAIT_REQUEUE_PI | FUTEX_CMP_REQUEUE_PI => {
+            return syscall_error(
+                Errno::ENOSYS,
+                "futex",
+                "requeueing to PI futexes is not supported",
+            );
+        }
+        _ => (PROT_READ, None),
+    };
+
+    // Check the futex words are mapped in the cage before the host touches them
+    let cage = get_cage(cageid).unwrap();
+    let (addr, addr2, shared) = {
+        let mut vmmap = cage.vmmap.write();
+        let addr = match check_futex_addr(&mut vmmap, uaddr, 4, prot) {
+            Ok(addr) => addr,
+            Err(e) => return syscall_error(e, "futex", "invalid futex address"),
+        };
+        let addr2 = match prot2.map(|prot2| check_futex_addr(&mut vmmap, uaddr2, 4, prot2)) {
+            Some(Ok(addr2)) => addr2,
+            Some(Err(e)) => return syscall_error(e, "futex", "invalid second futex address"),
+            None => 0,
+        };
+        (addr, addr2, futex_shared_mapping(&vmmap, addr))
+    };
+
+    // Each cage maps shared memory at its own host address, so a private operation there would
+    // only meet waiters of the same cage. A shared operation is keyed by the backing object and
+    // offset instead. Like Linux, a second futex word is keyed the same way as the first.
+    let futex_op = if shared {
+        futex_op & !FUTEX_PRIVATE_FLAG
+    } else {
+        futex_op
+    };
+
+    match cmd {
+        FUTEX_LOCK_PI | FUTEX_LOCK_PI2 | FUTEX_TRYLOCK_PI | FUTEX_UNLOCK_PI => {
+            let tid = match current_thread_id(cageid) {
+                Some(tid) => tid as u32,
+                None => return syscall_error(Errno::ESRCH, "futex", "unknown calling thread"),
+            };
+            return match cmd {
+                FUTEX_UNLOCK_PI => futex::unlock_pi(addr, tid),
+                FUTEX_TRYLOCK_PI => futex::lock_pi(cageid, addr, tid, None, false, true),
+                _ => {
+                    // FUTEX_LOCK_PI always measures its timeout against CLOCK_REALTIME
+                    let realtime = cmd == FUTEX_LOCK_PI || futex_op & FUTEX_CLOCK_REALTIME != 0;
+                    let abstime = futex::guest_timespec(timeout);
+                    futex::lock_pi(cageid, addr, tid, abstime, realtime, false)
+                }
+            };
+        }
+        _ => {}
+    }
+
+    // The fourth argument is a timeout only for the wait operations, and a count otherwise
+    let timespec = match cmd {
+        FUTEX_WAIT | FUTEX_WAIT_BITSET => futex::guest_timespec(timeout),
+        _ => None,
+    };
+    let val2 = match timespec {
+        Some(ref ts) => ts as *const libc::timespec as u64,
+        None if matches!(cmd, FUTEX_WAIT | FUTEX_WAIT_BITSET) => 0,
+        None => timeout,
+    };
+
+    let ret = unsafe { syscall(SYS_futex, addr, futex_op, val, val2, addr2, val3) as i32 };
     if ret < 0 {
         let errno = get_errno();
         return handle_errno(errno, "futex");
@@ -390,6 +546,203 @@ pub extern "C" fn futex_syscall(
     ret
 }
 
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/set_tid_address.2.html
+///
+/// Linux `set_tid_address()` sets the word the kernel clears when the calling thread exits, and
+/// returns the thread's TID. glibc calls it as each thread starts, to learn the TID it records as
+/// the owner of robust and PI mutexes. This implementation returns the lind thread ID of the
+/// caller and does not record `tidptr`: the TID word `pthread_join` waits on is cleared by the
+/// clone call that created the thread.
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - tidptr_arg: pointer to the TID word, ignored
+///
+/// ## Returns:
+///     - the caller's thread ID
+pub extern "C" fn set_tid_address_syscall(
+    cageid: u64,
+    _tidptr_arg: u64,
+    _tidptr_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "set_tid_address_syscall"
+        );
+    }
+
+    match current_thread_id(cageid) {
+        Some(tid) => tid,
+        None => syscall_error(Errno::ESRCH, "set_tid_address", "unknown calling thread"),
+    }
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/set_robust_list.2.html
+///
+/// Linux `set_robust_list()` registers the head of the calling thread's list of robust mutexes,
+/// which the kernel walks when the thread dies. This implementation records the cage address of
+/// the head for the calling thread; `lind_thread_exit` walks it (see `cage::futex`).
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - head_arg: pointer to the `struct robust_list_head`
+///     - len_arg: size of the head, which must be 12 in a cage (24 in a 64-bit cage)
+///
+/// ## Returns:
+///     - 0 on success
+///     - EINVAL if `len_arg` is not the size of the head
+///     - EFAULT if `head_arg` lies outside the cage's memory
+pub extern "C" fn set_robust_list_syscall(
+    cageid: u64,
+    head_arg: u64,
+    _head_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "set_robust_list_syscall"
+        );
+    }
+
+    let cage = get_cage(cageid).unwrap();
+    let (base, size, head_size) = {
+        let vmmap = cage.vmmap.read();
+        let head_size = if vmmap.memory64 {
+            ROBUST_LIST_HEAD_SIZE64
+        } else {
+            ROBUST_LIST_HEAD_SIZE
+        };
+        (
+            vmmap.base_address.unwrap() as u64,
+            vmmap.address_space_size(),
+            head_size,
+        )
+    };
+    if len != head_size {
+        return syscall_error(Errno::EINVAL, "set_robust_list", "invalid head size");
+    }
+    let tid = match current_thread_id(cageid) {
+        Some(tid) => tid,
+        None => return syscall_error(Errno::ESRCH, "set_robust_list", "unknown calling thread"),
+    };
+
+    if head_arg == 0 {
+        cage.robust_list.remove(&tid);
+        return 0;
+    }
+    match head_arg.checked_sub(base) {
+        Some(head) if head < size => {
+            cage.robust_list.insert(tid, head);
+            0
+        }
+        _ => syscall_error(Errno::EFAULT, "set_robust_list", "head outside the cage"),
+    }
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/get_robust_list.2.html
+///
+/// Linux `get_robust_list()` returns the robust list head registered by a thread. This
+/// implementation looks up a thread of the calling cage, since lind thread IDs are only unique
+/// within a cage, and stores the cage address of its head (null if none) and the head's size.
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - pid_arg: thread ID, or 0 for the calling thread
+///     - head_ptr_arg: pointer to receive the head pointer
+///     - len_ptr_arg: pointer to receive the head's size
+///
+/// ## Returns:
+///     - 0 on success
+///     - ESRCH if the cage has no thread `pid_arg`
+///     - EFAULT if a result pointer is null
+pub extern "C" fn get_robust_list_syscall(
+    cageid: u64,
+    pid_arg: u64,
+    pid_cageid: u64,
+    head_ptr_arg: u64,
+    _head_ptr_cageid: u64,
+    len_ptr_arg: u64,
+    _len_ptr_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let pid = sc_convert_sysarg_to_i32(pid_arg, pid_cageid, cageid);
+    let head_ptr = head_ptr_arg as *mut u8;
+    let len_ptr = len_ptr_arg as *mut u8;
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "get_robust_list_syscall"
+        );
+    }
+
+    let cage = get_cage(cageid).unwrap();
+    let tid = if pid == 0 {
+        current_thread_id(cageid)
+    } else {
+        cage.os_tid_map.contains_key(&pid).then_some(pid)
+    };
+    let Some(tid) = tid else {
+        return syscall_error(Errno::ESRCH, "get_robust_list", "no such thread");
+    };
+    if head_ptr.is_null() || len_ptr.is_null() {
+        return syscall_error(Errno::EFAULT, "get_robust_list", "null result pointer");
+    }
+
+    // The head pointer and its size_t length are 64-bit in a 64-bit cage
+    let head = cage.robust_list.get(&tid).map_or(0, |head| *head);
+    unsafe {
+        if cage.vmmap.read().memory64 {
+            std::ptr::write_unaligned(head_ptr as *mut u64, head);
+            std::ptr::write_unaligned(len_ptr as *mut u64, ROBUST_LIST_HEAD_SIZE64 as u64);
+        } else {
+            std::ptr::write_unaligned(head_ptr as *mut u32, head as u32);
+            std::ptr::write_unaligned(len_ptr as *mut u32, ROBUST_LIST_HEAD_SIZE as u32);
+        }
+    }
+    0
+}
+
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/write.2.html
 ///
 /// Linux `write()` syscall attempts to write `count` bytes from the buffer pointed to by `buf` to the file associated
@@ -440,6 +793,37 @@ pub extern "C" fn write_syscall(
         );
     }
 
+    if let Some(sock) = inproc_socket::lookup(vfd_cageid, vfd_arg) {
+        let iov = [libc::iovec {
+            iov_base: buf as *mut c_void,
+            iov_len: count,
+        }];
+        // send() raises SIGPIPE itself on a broken connection
+        return match sock.send(cageid, &iov, 0) {
+            Ok(n) => n as i32,
+            Err(e) => syscall_error(e, "write", "in-process socket write failed"),
+        };
+    }
+
+    if mqueue::lookup(vfd_cageid, vfd_arg).is_some() {
+        return syscall_error(
+            Errno::EINVAL,
+            "write",
+            "message queues are written with mq_send",
+        );
+    }
+
+    if crate::rtnetlink::is_route_socket(kernel_fd) {
+        let iov = [libc::iovec {
+            iov_base: buf as *mut c_void,
+            iov_len: count,
+        }];
+        return match crate::rtnetlink::send(cageid, kernel_fd, &iov, std::ptr::null(), 0) {
+            Ok(n) => n as i32,
+            Err(e) => syscall_error(e, "write", "route netlink request failed"),
+        };
+    }
+
     let ret = unsafe { libc::write(kernel_fd, buf as *const c_void, count) as i32 };
 
     if ret < 0 {
@@ -463,7 +847,8 @@ pub extern "C" fn write_syscall(
 /// ## Arguments:
 ///     - cageid: current cageid
 ///     - path_arg: This argument points to a pathname naming the file. User's perspective.
-///     - mode_arg: This represents the permission of the newly created file. Directly passing to kernel.
+///     - mode_arg: This represents the permission of the newly created directory. The cage's umask
+///                 is cleared from it before passing to kernel.
 ///
 /// ## Returns:
 ///     - return zero on success.  On error, -1 is returned and errno is set to indicate the error.
@@ -502,6 +887,7 @@ pub extern "C" fn mkdir_syscall(
         );
     }
 
+    let mode = apply_cage_umask(cageid, mode);
     let ret = unsafe { libc::mkdir(path.as_ptr(), mode) };
     // Error handling
     if ret < 0 {
@@ -520,7 +906,8 @@ pub extern "C" fn mkdir_syscall(
 /// ## Arguments:
 ///     - cageid: current cageid
 ///     - path_arg: This argument points to a pathname naming the file. User's perspective.
-///     - mode_arg: This represents both the file mode to use and the type of node to be created
+///     - mode_arg: This represents both the file mode to use and the type of node to be created.
+///                 The cage's umask is cleared from the permission bits.
 ///     - dev_arg: If the file type is S_IFCHR or S_IFBLK, dev specifies the major and minor numbers
 ///                of the newly created device special file; otherwise it is ignored.
 ///
@@ -558,6 +945,7 @@ pub extern "C" fn mknod_syscall(
         );
     }
 
+    let mode = apply_cage_umask(cageid, mode);
     let ret = unsafe { libc::mknod(path.as_ptr(), mode, dev) };
     if ret < 0 {
         let errno = get_errno();
@@ -628,16 +1016,12 @@ pub extern "C" fn pipe_syscall(
         0,
     ) {
         Ok(fd) => fd as i32,
-        Err(_e) => {
+        Err(e) => {
             unsafe {
                 libc::close(kernel_fds[0]);
                 libc::close(kernel_fds[1]);
             }
-            return syscall_error(
-                Errno::EMFILE,
-                "pipe_syscall",
-                "Failed to get virtual file descriptor",
-            );
+            return handle_errno(e as i32, "pipe_syscall");
         }
     };
 
@@ -650,18 +1034,15 @@ pub extern "C" fn pipe_syscall(
         0,
     ) {
         Ok(fd) => fd as i32,
-        Err(_e) => {
+        Err(e) => {
             // close the kernel pipefd if there's an error
-            // on getting virtual fd
+            // on getting virtual fd.  The read end is already in the
+            // fdtable, so closing its virtual fd closes the kernel fd.
+            let _ = fdtables::close_virtualfd(cageid, read_vfd as u64);
             unsafe {
-                libc::close(kernel_fds[0]);
                 libc::close(kernel_fds[1]);
             }
-            return syscall_error(
-                Errno::EMFILE,
-                "pipe_syscall",
-                "Failed to get virtual file descriptor",
-            );
+            return handle_errno(e as i32, "pipe_syscall");
         }
     };
 
@@ -748,18 +1129,14 @@ pub extern "C" fn pipe2_syscall(
         0,
     ) {
         Ok(fd) => fd as i32,
-        Err(_e) => {
+        Err(e) => {
             // close the kernel pipefd if there's an error
             // on getting virtual fd
             unsafe {
                 libc::close(kernel_fds[0]);
                 libc::close(kernel_fds[1]);
             }
-            return syscall_error(
-                Errno::EMFILE,
-                "pipe2_syscall",
-                "Failed to get virtual file descriptor",
-            );
+            return handle_errno(e as i32, "pipe2_syscall");
         }
     };
 
@@ -772,16 +1149,14 @@ pub extern "C" fn pipe2_syscall(
         0,
     ) {
         Ok(fd) => fd as i32,
-        Err(_e) => {
+        Err(e) => {
+            // The read end is already in the fdtable, so closing its virtual
+            // fd closes the kernel fd.
+            let _ = fdtables::close_virtualfd(cageid, read_vfd as u64);
             unsafe {
-                libc::close(kernel_fds[0]);
                 libc::close(kernel_fds[1]);
             }
-            return syscall_error(
-                Errno::EMFILE,
-                "pipe2_syscall",
-                "Failed to get virtual file descriptor",
-            );
+            return handle_errno(e as i32, "pipe2_syscall");
         }
     };
 
@@ -796,7 +1171,11 @@ pub extern "C" fn pipe2_syscall(
 ///
 /// This function processes the `mmap_syscall` by updating the `vmmap` entries and performing
 /// the necessary mmap operations. The handling logic is as follows:
-/// 1. Restrict allowed flags to `MAP_FIXED`, `MAP_SHARED`, `MAP_PRIVATE`, and `MAP_ANONYMOUS`.
+/// 1. Restrict allowed flags to `MAP_FIXED`, `MAP_SHARED`, `MAP_PRIVATE`, `MAP_ANONYMOUS`,
+///    `MAP_POPULATE`, `MAP_NORESERVE`, `MAP_STACK` and `MAP_GROWSDOWN`, which are all passed
+///    through to the host and kept in the `vmmap` entry. A `MAP_GROWSDOWN` mapping never grows:
+///    the pages below it are the cage's own `PROT_NONE` reservation, so a fault there is an
+///    ordinary `SIGSEGV` and the region behaves like a fixed-size mapping.
 /// 2. Disallow `PROT_EXEC`; return `EINVAL` if the `prot` argument includes `PROT_EXEC`.
 /// 3. If `MAP_FIXED` is not specified, query the `vmmap` structure to locate an available memory region.
 ///    Otherwise, use the address provided by the user.
@@ -813,7 +1192,8 @@ pub extern "C" fn pipe2_syscall(
 /// * `off` - Offset within the file, if applicable.
 ///
 /// # Returns
-/// * `u32` - Result of the `mmap` operation. See "man mmap" for details
+/// * `u32` - Result of the `mmap` operation. See "man mmap" for details. 64-bit cages get the
+///   page number of the mapping instead (see `Vmmap::user_addr_to_retval`)
 pub extern "C" fn mmap_syscall(
     cageid: u64,
     addr_arg: u64,
@@ -859,7 +1239,10 @@ pub extern "C" fn mmap_syscall(
         | MAP_SHARED as i32
         | MAP_PRIVATE as i32
         | MAP_ANONYMOUS as i32
-        | MAP_POPULATE as i32;
+        | MAP_POPULATE as i32
+        | MAP_NORESERVE as i32
+        | MAP_STACK as i32
+        | MAP_GROWSDOWN as i32;
     if flags & !allowed_flags != 0 {
         lind_debug_panic!(
             "mmap: unsupported flags {:#x} (allowed: {:#x})",
@@ -875,7 +1258,7 @@ pub extern "C" fn mmap_syscall(
     // check if the provided address is multiple of pages
     let rounded_addr = round_up_page(addr as u64);
     if rounded_addr != addr as u64 {
-        return syscall_error(Errno::EINVAL, "mmap", "address it not aligned");
+        return syscall_error(Errno::EINVAL, "mmap", "address is not aligned");
     }
 
     // offset should be non-negative and multiple of pages
@@ -884,27 +1267,38 @@ pub extern "C" fn mmap_syscall(
     }
     let rounded_off = round_up_page(off as u64);
     if rounded_off != off as u64 {
-        return syscall_error(Errno::EINVAL, "mmap", "offset it not aligned");
+        return syscall_error(Errno::EINVAL, "mmap", "offset is not aligned");
     }
 
     // round up length to be multiple of pages
     let rounded_length = round_up_page(len as u64);
 
-    let mut useraddr = addr as u32;
+    let mut useraddr = addr as u64;
+    // page above a new thread stack, where find_stack_space may have reserved a gap
+    let mut stack_end = None;
+    // gives the gap back if the stack below it does not get mapped
+    let release_stack_gap = |vmmap: &mut Vmmap, stack_end: Option<u32>| {
+        if let Some(page_num) = stack_end {
+            vmmap.release_stack_gap(page_num);
+        }
+    };
     // if MAP_FIXED is not set, then we need to find an address for the user
     if flags & MAP_FIXED as i32 == 0 {
-        let vmmap = cage.vmmap.write();
+        let mut vmmap = cage.vmmap.write();
         let result;
 
         // pick an address of appropriate size, anywhere
-        if useraddr == 0 {
+        if useraddr == 0 && flags & MAP_STACK as i32 != 0 {
+            // thread stacks get a randomized placement with ASLR
+            result = vmmap.find_stack_space((rounded_length >> PAGESHIFT) as u32, cageid);
+        } else if useraddr == 0 {
             result = vmmap.find_map_space((rounded_length >> PAGESHIFT) as u32, 1);
         } else {
             // use address user provided as hint to find address
             result = vmmap.find_map_space_with_hint(
                 (rounded_length >> PAGESHIFT) as u32,
                 1,
-                addr as u32 >> PAGESHIFT,
+                (useraddr >> PAGESHIFT) as u32,
             );
         }
 
@@ -914,14 +1308,18 @@ pub extern "C" fn mmap_syscall(
         }
 
         let space = result.unwrap();
-        useraddr = (space.start() << PAGESHIFT) as u32;
+        useraddr = (space.start() as u64) << PAGESHIFT;
+        if flags & MAP_STACK as i32 != 0 {
+            stack_end = Some(space.end() + 1);
+        }
     }
 
-    if (useraddr as u64) + rounded_length > 0x100000000u64 {
+    if useraddr + rounded_length > cage.vmmap.read().address_space_size() {
+        release_stack_gap(&mut cage.vmmap.write(), stack_end);
         return syscall_error(
             Errno::EINVAL,
             "mmap",
-            "address range overflows 32-bit space",
+            "address range overflows the cage address space",
         );
     }
 
@@ -929,11 +1327,30 @@ pub extern "C" fn mmap_syscall(
 
     // either MAP_PRIVATE or MAP_SHARED should be set, but not both
     if (flags & MAP_PRIVATE as i32 == 0) == (flags & MAP_SHARED as i32 == 0) {
+        release_stack_gap(&mut cage.vmmap.write(), stack_end);
         return syscall_error(Errno::EINVAL, "mmap", "invalid flags");
     }
 
     let vmmap = cage.vmmap.read();
 
+    // Refuse the mapping before the host maps anything if it would exceed the memory limits
+    let backing = if flags & MAP_ANONYMOUS as i32 > 0 {
+        MemoryBackingType::Anonymous
+    } else {
+        MemoryBackingType::FileDescriptor(fildes as u64)
+    };
+    if let Err(e) = quota::check_mapping(
+        &cage,
+        &vmmap,
+        (useraddr >> PAGESHIFT) as u32,
+        (rounded_length >> PAGESHIFT) as u32,
+        quota::is_committed(flags, prot, backing),
+    ) {
+        drop(vmmap);
+        release_stack_gap(&mut cage.vmmap.write(), stack_end);
+        return syscall_error(e, "mmap", "memory limit exceeded");
+    }
+
     let sysaddr = vmmap.user_to_sys(useraddr);
 
     drop(vmmap);
@@ -956,6 +1373,10 @@ pub extern "C" fn mmap_syscall(
         // Check for error BEFORE sys_to_user conversion
         if is_mmap_error(result) {
             let errno = get_errno();
+            let mut vmmap = cage.vmmap.write();
+            release_stack_gap(&mut vmmap, stack_end);
+            // Give back the pages check_mapping reserved
+            quota::recount(&cage, &vmmap);
             return handle_errno(errno, "mmap");
         }
 
@@ -991,6 +1412,8 @@ pub extern "C" fn mmap_syscall(
                         0,
                     );
                     if flags < 0 {
+                        release_stack_gap(&mut vmmap, stack_end);
+                        quota::recount(&cage, &vmmap);
                         return syscall_error(Errno::EINVAL, "mmap", "invalid file descriptor")
                             as i32;
                     }
@@ -1001,7 +1424,7 @@ pub extern "C" fn mmap_syscall(
 
             // update vmmap entry
             let _ = vmmap.add_entry_with_overwrite(
-                useraddr >> PAGESHIFT,
+                (useraddr >> PAGESHIFT) as u32,
                 (rounded_length >> PAGESHIFT) as u32,
                 prot,
                 maxprot,
@@ -1011,10 +1434,12 @@ pub extern "C" fn mmap_syscall(
                 len as i64,
                 cageid,
             );
+            quota::recount(&cage, &vmmap);
         }
     }
 
-    useraddr as i32
+    let retval = cage.vmmap.read().user_addr_to_retval(useraddr);
+    retval
 }
 
 /// Helper function for `mmap` / `munmap`
@@ -1119,7 +1544,7 @@ pub extern "C" fn munmap_syscall(
     // check if the provided address is multiple of pages
     let rounded_addr = round_up_page(addr as u64) as usize;
     if rounded_addr != addr as usize {
-        return syscall_error(Errno::EINVAL, "munmap", "address it not aligned");
+        return syscall_error(Errno::EINVAL, "munmap", "address is not aligned");
     }
 
     let rounded_length = round_up_page(len as u64) as usize;
@@ -1134,7 +1559,7 @@ pub extern "C" fn munmap_syscall(
             return syscall_error(
                 Errno::EINVAL,
                 "munmap",
-                "address range overflows 32-bit space",
+                "address range overflows the cage address space",
             )
         }
     };
@@ -1143,7 +1568,7 @@ pub extern "C" fn munmap_syscall(
 
     for (act_start, act_end) in overlaps {
         let (act_start, act_end) = (act_start as usize, act_end as usize);
-        let act_start_addr = vmmap.user_to_sys((act_start as u32) << PAGESHIFT);
+        let act_start_addr = vmmap.user_to_sys((act_start as u64) << PAGESHIFT);
         let act_len = ((act_end - act_start) as usize) << PAGESHIFT;
         let result = unsafe {
             libc::mmap(
@@ -1169,6 +1594,9 @@ pub extern "C" fn munmap_syscall(
     }
 
     vmmap.remove_entry(req_start, req_end - req_start);
+    // Unmapping a thread stack frees the gap find_stack_space reserved above it
+    vmmap.release_stack_gap(req_end);
+    quota::recount(&cage, &vmmap);
 
     0
 }
@@ -1208,7 +1636,7 @@ pub extern "C" fn brk_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let brk = sc_convert_sysarg_to_i32(brk_arg, brk_cageid, cageid);
+    let brk = sc_convert_sysarg_to_uaddr(brk_arg, brk_cageid, cageid);
     // would sometimes check, sometimes be a no-op depending on the compiler settings
     if !(sc_unusedarg(arg2, arg2_cageid)
         && sc_unusedarg(arg3, arg3_cageid)
@@ -1251,10 +1679,10 @@ pub extern "C" fn brk_syscall(
 
     // passing 0 to brk will always return the current brk
     if brk == 0 {
-        return (PAGESIZE * old_brk_page) as i32;
+        return vmmap.user_addr_to_retval((old_brk_page as u64) << PAGESHIFT);
     }
     // round up the break to multiple of pages
-    let brk_page = (round_up_page(brk as u64) >> PAGESHIFT) as u32;
+    let brk_page = (round_up_page(brk) >> PAGESHIFT) as u32;
 
     // shrink heap below heap start is not allowed
     if brk_page < vmmap.heap_start {
@@ -1266,6 +1694,11 @@ pub extern "C" fn brk_syscall(
         if vmmap.check_existing_mapping(old_brk_page, brk_page - old_brk_page, 0) {
             return syscall_error(Errno::ENOMEM, "brk", "no memory");
         }
+        if let Err(e) =
+            quota::check_mapping(&cage, &vmmap, old_brk_page, brk_page - old_brk_page, true)
+        {
+            return syscall_error(e, "brk", "memory limit exceeded");
+        }
     }
 
     // remove the old entries since new entry is overlapping with it.
@@ -1283,24 +1716,24 @@ pub extern "C" fn brk_syscall(
         heap.file_size,
         heap.cage_id,
     );
+    quota::recount(&cage, &vmmap);
 
     let old_heap_end_u64 = (old_brk_page as u64) * (PAGESIZE as u64);
     let new_heap_end_u64 = (brk_page as u64) * (PAGESIZE as u64);
 
-    // Prevent 32-bit address-space overflow
-    if old_heap_end_u64 > 0xFFFF_FFFFu64 || new_heap_end_u64 > 0xFFFF_FFFFu64 {
+    // Prevent overflowing the cage address space
+    let size = vmmap.address_space_size();
+    if old_heap_end_u64 >= size || new_heap_end_u64 >= size {
         return syscall_error(
             Errno::ENOMEM,
             "brk",
-            "heap end address overflows 32-bit space",
+            "heap end address overflows the cage address space",
         );
     }
 
-    let old_heap_end_usr = old_heap_end_u64 as u32;
-    let old_heap_end_sys = vmmap.user_to_sys(old_heap_end_usr) as *mut u8;
-
-    let new_heap_end_usr = new_heap_end_u64 as u32;
-    let new_heap_end_sys = vmmap.user_to_sys(new_heap_end_usr) as *mut u8;
+    let old_heap_end_sys = vmmap.user_to_sys(old_heap_end_u64) as *mut u8;
+    let new_heap_end_sys = vmmap.user_to_sys(new_heap_end_u64) as *mut u8;
+    let retval = vmmap.user_addr_to_retval(new_heap_end_u64);
 
     drop(vmmap);
 
@@ -1345,244 +1778,2723 @@ pub extern "C" fn brk_syscall(
     }
 
     // return brk address
-    (PAGESIZE * brk_page) as i32
-}
-
-//------------------------------------FCNTL SYSCALL------------------------------------
-/// This function will be different in new code base (when splitting out type conversion function)
-/// since the conversion from u64 -> i32 in negative number will be different. These lines are repeated
-/// in 5 out of 6 fcntl_syscall cases, so wrapped these loc into helper functions to make code cleaner.
-///
-/// ## Arguments
-/// cageid: cage ID associate with virtual file descriptor
-/// vfd_arg: virtual file descriptor
-///
-/// ## Return Type
-/// On success:
-/// Return corresponding FDTableEntry that contains
-/// (1) underlying kernel fd.
-/// (2) file descriptor kind.
-/// (3) O_CLOEXEC flag.
-/// (4) file descriptor specific extra information.
-///
-/// On error:
-/// Return error num EBADF(Bad File Descriptor)
-pub fn _fcntl_helper(cageid: u64, vfd_arg: u64) -> Result<fdtables::FDTableEntry, Errno> {
-    if vfd_arg > MAXFD as u64 {
-        return Err(Errno::EBADF);
-    }
-    // Get underlying kernel fd
-    let wrappedvfd = fdtables::translate_virtual_fd(cageid, vfd_arg);
-    if wrappedvfd.is_err() {
-        return Err(Errno::EBADF);
-    }
-    Ok(wrappedvfd.unwrap())
+    retval
 }
 
-/// Reference: https://man7.org/linux/man-pages/man2/fcntl.2.html
-///
-/// Due to the design of `fdtables` library, different virtual fds created by `dup`/`dup2` are
-/// actually refer to the same underlying kernel fd. Therefore, in `fcntl_syscall` we need to
-/// handle the cases of `F_DUPFD`, `F_DUPFD_CLOEXEC`, `F_GETFD`, and `F_SETFD` separately.
+/// Handles the `mremap_syscall`, interacting with the `vmmap` structure.
 ///
-/// Among these, `F_DUPFD` and `F_DUPFD_CLOEXEC` cannot directly use the `dup_syscall` because,
-/// in `fcntl`, the duplicated fd is assigned to the lowest available number starting from `arg`,
-/// whereas the `dup_syscall` does not have this restriction and instead assigns the lowest
-/// available fd number globally.
+/// Reference: https://man7.org/linux/man-pages/man2/mremap.2.html
 ///
-/// Additionally, `F_DUPFD_CLOEXEC` and `F_SETFD` require updating the fd flag information
-/// (`O_CLOEXEC`) in fdtables after modifying the underlying kernel fd.
+/// This function resizes, and optionally moves, an existing mapping. As on Linux, the old range
+/// must lie inside a single mapping, here a single `vmmap` entry. The handling logic is:
+/// 1. Shrinking releases the tail pages back to `PROT_NONE`, the same way `munmap` does.
+/// 2. Growing first tries to extend the mapping in place if the pages that follow it are free
+///    in the `vmmap`. The host mapping is parked in a scratch reservation with
+///    `MREMAP_DONTUNMAP` and moved back, grown, with `MREMAP_FIXED`, which replaces the
+///    `PROT_NONE` reservation after it atomically. File-backed and shared mappings stay one
+///    host mapping and keep sharing pages with forked cages.
+/// 3. Otherwise, with `MREMAP_MAYMOVE`, a new region is chosen by `find_map_space` (or taken
+///    from `new_addr` when `MREMAP_FIXED` is set), the host `mremap` moves the pages there, and
+///    the old range is reserved again as `PROT_NONE`.
 ///
-/// For all other command operations, after translating the virtual fd to the corresponding
-/// kernel fd, they are redirected to the kernel `fcntl` syscall.
+/// Like `mmap`, addresses are guest addresses. Duplicating a shared mapping (`old_size == 0`),
+/// `MREMAP_DONTUNMAP`, the heap managed by `brk`, and System V shared memory segments are not
+/// supported and return `EINVAL`.
 ///
-/// ## Arguments
-/// vfd_arg: virtual file descriptor
-/// cmd: The operation
-/// arg: an optional third argument.  Whether or not this argument is required is determined by op.  
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - old_addr_arg: guest address of the mapping to resize
+///     - old_size_arg: current size of the mapping in bytes
+///     - new_size_arg: requested size in bytes
+///     - flags_arg: `MREMAP_MAYMOVE` and/or `MREMAP_FIXED`
+///     - new_addr_arg: guest destination address, only read when `MREMAP_FIXED` is set
+///     - arg6, arg6_cageid: unused argument and its cage ID
 ///
 /// ## Returns:
-///     - For a successful call, the return value depends on the operation:
-///       - `F_DUPFD`: The new file descriptor.
-///       - `F_GETFD`: Value of file descriptor flags.
-///       - `F_GETFL`: Value of file status flags.
-///       - `F_GETLEASE`: Type of lease held on file descriptor.
-///       - `F_GETOWN`: Value of file descriptor owner.
-///       - `F_GETSIG`: Value of signal sent when read or write becomes possible, or zero for traditional SIGIO behavior.
-///       - `F_GETPIPE_SZ`, `F_SETPIPE_SZ`: The pipe capacity.
-///       - `F_GET_SEALS`: A bit mask identifying the seals that have been set for the inode referred to by fd.
-///       - All other commands: Zero.
-///     - On error, -1 is returned and errno is set to indicate the error.
-///
-/// TODO: `F_GETOWN`, `F_SETOWN`, `F_GETOWN_EX`, `F_SETOWN_EX`, `F_GETSIG`, and `F_SETSIG` are used to manage I/O availability signals.
-pub extern "C" fn fcntl_syscall(
+///     - guest address of the resized mapping on success (its page number in a 64-bit cage)
+///     - -1 on error with appropriate errno set
+pub extern "C" fn mremap_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    _vfd_cageid: u64,
-    cmd_arg: u64,
-    cmd_cageid: u64,
-    int_arg: u64, // arg3: integer value (for F_DUPFD, F_SETFD, F_GETFL, etc.)
-    _int_arg_cageid: u64,
-    ptr_arg: u64, // arg4: translated host pointer (for F_GETLK, F_SETLK, etc.)
-    _ptr_arg_cageid: u64,
-    arg5: u64,
-    arg5_cageid: u64,
+    old_addr_arg: u64,
+    old_addr_cageid: u64,
+    old_size_arg: u64,
+    old_size_cageid: u64,
+    new_size_arg: u64,
+    new_size_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    new_addr_arg: u64,
+    new_addr_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let cmd = sc_convert_sysarg_to_i32(cmd_arg, cmd_cageid, cageid);
-
-    // Convert int value only, we handle the pointer args if it's a lock operation
-    let arg = int_arg as i32;
-
-    // Validate unused arguments
-    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+    let old_addr = sc_convert_sysarg_to_uaddr(old_addr_arg, old_addr_cageid, cageid);
+    let old_size = sc_convert_sysarg_to_usize(old_size_arg, old_size_cageid, cageid);
+    let new_size = sc_convert_sysarg_to_usize(new_size_arg, new_size_cageid, cageid);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+    let new_addr = sc_convert_sysarg_to_uaddr(new_addr_arg, new_addr_cageid, cageid);
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !sc_unusedarg(arg6, arg6_cageid) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "fcntl_syscall"
+            "mremap_syscall"
         );
     }
 
-    match (cmd, arg) {
-        // Duplicate the file descriptor `vfd_arg` using the lowest-numbered
-        // available file descriptor greater than or equal to `arg`. The operation here
-        // is quite similar to `dup_syscall`, for specific operation explanation, see
-        // comments on `dup_syscall`.
-        (F_DUPFD, arg) => {
-            // Get fdtable entry
-            let vfd = match _fcntl_helper(cageid, vfd_arg) {
-                Ok(entry) => entry,
-                Err(e) => return syscall_error(e, "fcntl", "Bad File Descriptor"),
-            };
-            // Get lowest-numbered available file descriptor greater than or equal to `arg`
-            match fdtables::get_unused_virtual_fd_from_startfd(
-                cageid,
-                vfd.fdkind,
-                vfd.underfd,
-                false,
-                0,
-                arg as u64,
-            ) {
-                Ok(new_vfd) => return new_vfd as i32,
-                Err(_) => return syscall_error(Errno::EBADF, "fcntl", "Bad File Descriptor"),
-            }
+    let maymove = flags & MREMAP_MAYMOVE as i32 != 0;
+    let fixed = flags & MREMAP_FIXED as i32 != 0;
+    if flags & !((MREMAP_MAYMOVE | MREMAP_FIXED) as i32) != 0 || (fixed && !maymove) {
+        return syscall_error(Errno::EINVAL, "mremap", "invalid flags");
+    }
+    if old_addr != round_up_page(old_addr) {
+        return syscall_error(Errno::EINVAL, "mremap", "address is not aligned");
+    }
+    if old_size == 0 {
+        return syscall_error(
+            Errno::EINVAL,
+            "mremap",
+            "duplicating a shared mapping is not supported",
+        );
+    }
+    if new_size == 0 {
+        return syscall_error(Errno::EINVAL, "mremap", "new size cannot be zero");
+    }
+
+    let cage = get_cage(cageid).unwrap();
+    let mut vmmap = cage.vmmap.write();
+
+    let old_len = round_up_page(old_size as u64);
+    let new_len = round_up_page(new_size as u64);
+    let size = vmmap.address_space_size();
+    if old_addr + old_len > size || new_len > size {
+        return syscall_error(
+            Errno::EINVAL,
+            "mremap",
+            "address range overflows the cage address space",
+        );
+    }
+    let old_start = (old_addr >> PAGESHIFT) as u32;
+    let old_pages = (old_len >> PAGESHIFT) as u32;
+    let new_pages = (new_len >> PAGESHIFT) as u32;
+
+    // The old range has to be covered by a single entry, just like Linux
+    // requires it to be a single VMA.
+    let entry = match vmmap.find_page(old_start) {
+        Some(entry) if entry.page_num + entry.npages >= old_start + old_pages => entry.clone(),
+        _ => return syscall_error(Errno::EFAULT, "mremap", "old range is not one mapping"),
+    };
+    if entry.page_num == vmmap.heap_start {
+        return syscall_error(Errno::EINVAL, "mremap", "heap is managed by brk");
+    }
+    if matches!(entry.backing, MemoryBackingType::SharedMemory(_)) {
+        return syscall_error(
+            Errno::EINVAL,
+            "mremap",
+            "shared memory segments cannot be remapped",
+        );
+    }
+    let file_offset = entry.file_offset + (((old_start - entry.page_num) as i64) << PAGESHIFT);
+
+    let old_sys = vmmap.user_to_sys(old_addr);
+
+    // Pick the destination: the new address for MREMAP_FIXED, the same address
+    // when shrinking or growing in place, or a fresh region otherwise.
+    let new_start = if fixed {
+        if new_addr != round_up_page(new_addr) || new_addr + new_len > size {
+            return syscall_error(Errno::EINVAL, "mremap", "invalid new address");
         }
-        // As for `F_DUPFD`, but additionally set the close-on-exec flag
-        // for the duplicate file descriptor.
-        (F_DUPFD_CLOEXEC, arg) => {
-            // Get fdtable entry
-            let vfd = match _fcntl_helper(cageid, vfd_arg) {
-                Ok(entry) => entry,
-                Err(e) => return syscall_error(e, "fcntl", "Bad File Descriptor"),
-            };
-            // Get lowest-numbered available file descriptor greater than or equal to `arg`
-            // and set the `O_CLOEXEC` flag
-            match fdtables::get_unused_virtual_fd_from_startfd(
-                cageid,
-                vfd.fdkind,
-                vfd.underfd,
-                true,
-                0,
-                arg as u64,
-            ) {
-                Ok(new_vfd) => return new_vfd as i32,
-                Err(_) => return syscall_error(Errno::EBADF, "fcntl", "Bad File Descriptor"),
-            }
+        let new_start = (new_addr >> PAGESHIFT) as u32;
+        if new_start < old_start + old_pages && old_start < new_start + new_pages {
+            return syscall_error(Errno::EINVAL, "mremap", "new range overlaps old range");
         }
-        // Return (as the function result) the file descriptor flags.
-        (F_GETFD, ..) => {
-            // Get fdtable entry
-            let vfd = match _fcntl_helper(cageid, vfd_arg) {
-                Ok(entry) => entry,
-                Err(e) => return syscall_error(e, "fcntl", "Bad File Descriptor"),
-            };
-            return vfd.should_cloexec as i32;
+        new_start
+    } else if new_pages <= old_pages {
+        old_start
+    } else if vmmap.is_range_free(old_start + old_pages, new_pages - old_pages) {
+        old_start
+    } else if maymove {
+        match vmmap.find_map_space(new_pages, 1) {
+            Some(space) => space.start(),
+            None => return syscall_error(Errno::ENOMEM, "mremap", "no memory"),
         }
-        // Set the file descriptor flags to the value specified by arg.
-        (F_SETFD, arg) => {
-            // Get fdtable entry
-            let vfd = match _fcntl_helper(cageid, vfd_arg) {
-                Ok(entry) => entry,
-                Err(e) => return syscall_error(e, "fcntl", "Bad File Descriptor"),
-            };
-            // Set underlying kernel fd flag
-            let ret = unsafe { libc::fcntl(vfd.underfd as i32, cmd, arg) };
-            if ret < 0 {
+    } else {
+        return syscall_error(Errno::ENOMEM, "mremap", "cannot grow mapping in place");
+    };
+
+    // Growing maps more pages, which the memory limits have to allow
+    if new_pages > old_pages {
+        let added = (new_pages - old_pages) as u64;
+        let committed = if quota::is_committed(entry.flags, entry.prot, entry.backing) {
+            added
+        } else {
+            0
+        };
+        if let Err(e) = quota::check_growth(&cage, added, committed) {
+            return syscall_error(e, "mremap", "memory limit exceeded");
+        }
+    }
+
+    if new_start == old_start {
+        if new_pages < old_pages {
+            // Shrink: give the tail back to the PROT_NONE reservation
+            let tail_sys = vmmap.user_to_sys(((old_start + new_pages) as u64) << PAGESHIFT);
+            let ret = mmap_inner(
+                cageid,
+                tail_sys as *mut u8,
+                ((old_pages - new_pages) as usize) << PAGESHIFT,
+                PROT_NONE,
+                (MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED) as i32,
+                -1,
+                0,
+            );
+            if is_mmap_error(ret) {
                 let errno = get_errno();
-                return handle_errno(errno, "fcntl");
+                return handle_errno(errno, "mremap");
             }
-            // Set virtual fd flag
-            let cloexec_flag: bool = arg != 0;
-            match fdtables::set_cloexec(cageid, vfd_arg as u64, cloexec_flag) {
-                Ok(_) => return 0,
-                Err(_e) => return syscall_error(Errno::EBADF, "fcntl", "Bad File Descriptor"),
+            let _ = vmmap.remove_entry(old_start + new_pages, old_pages - new_pages);
+        } else if new_pages > old_pages {
+            // Grow in place: the pages after the mapping hold the PROT_NONE
+            // reservation, so the host mapping is moved onto its own address
+            // and replaces that reservation.
+            if let Err(errno) = remap_fixed(old_sys, old_len as usize, new_len as usize, old_sys) {
+                quota::recount(&cage, &vmmap);
+                return handle_errno(errno, "mremap");
             }
+            let _ = vmmap.add_entry_with_overwrite(
+                old_start,
+                new_pages,
+                entry.prot,
+                entry.maxprot,
+                entry.flags,
+                entry.backing,
+                file_offset,
+                new_size as i64,
+                cageid,
+            );
         }
-        // todo: F_GETOWN and F_SETOWN commands are not implemented yet
-        (F_GETOWN, ..) => DEFAULT_GID as i32,
-        (F_SETOWN, arg) if arg >= 0 => 0,
-        _ => {
-            // Get fdtable entry
-            let vfd = match _fcntl_helper(cageid, vfd_arg) {
-                Ok(entry) => entry,
-                Err(e) => return syscall_error(e, "fcntl", "Bad File Descriptor"),
-            };
-            let is_lock_op = cmd == F_GETLK
-                || cmd == F_SETLK
-                || cmd == F_SETLKW
-                || cmd == F_GETLK64
-                || cmd == F_SETLK64
-                || cmd == F_SETLKW64;
+        quota::recount(&cage, &vmmap);
+        return vmmap.user_addr_to_retval(old_addr);
+    }
 
-            let ret = if is_lock_op {
-                // Lock operation - use ptr_arg (arg4)
-                unsafe { libc::fcntl(vfd.underfd as i32, cmd, ptr_arg as *mut c_void) }
-            } else {
-                // Other operations - use int_arg (arg3)
-                unsafe { libc::fcntl(vfd.underfd as i32, cmd, arg) }
-            };
+    // Move: the pages left behind in the old range are reserved again.
+    let new_sys = vmmap.user_to_sys((new_start as u64) << PAGESHIFT);
+    if let Err(errno) = remap_fixed(old_sys, old_len as usize, new_len as usize, new_sys) {
+        quota::recount(&cage, &vmmap);
+        return handle_errno(errno, "mremap");
+    }
+    let ret = mmap_inner(
+        cageid,
+        old_sys as *mut u8,
+        old_len as usize,
+        PROT_NONE,
+        (MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED) as i32,
+        -1,
+        0,
+    );
+    if is_mmap_error(ret) {
+        lind_debug_panic!("mremap: failed to reserve old range again");
+    }
+
+    let _ = vmmap.remove_entry(old_start, old_pages);
+    let _ = vmmap.add_entry_with_overwrite(
+        new_start,
+        new_pages,
+        entry.prot,
+        entry.maxprot,
+        entry.flags,
+        entry.backing,
+        file_offset,
+        new_size as i64,
+        cageid,
+    );
+    quota::recount(&cage, &vmmap);
 
-            if ret < 0 {
-                let errno = get_errno();
-                return handle_errno(errno, "fcntl");
-            }
-            ret
+    vmmap.user_addr_to_retval((new_start as u64) << PAGESHIFT)
+}
+
+/// Helper for `mremap`
+///
+/// Moves the host mapping at `old_sys` onto `new_sys` and resizes it to `new_len`, replacing
+/// whatever the cage has reserved there. `new_sys` may be `old_sys` itself, which the host
+/// `mremap` refuses since the ranges overlap, so the mapping is first parked in a scratch
+/// reservation outside the cage. `MREMAP_DONTUNMAP` keeps the old range mapped while it is
+/// parked, so the cage's range is never left unmapped for another thread's host `mmap` to take.
+///
+/// On success the part of the old range outside the new one still holds emptied pages, which
+/// the caller reserves again. On failure the mapping is back at `old_sys` and the host errno
+/// is returned.
+fn remap_fixed(old_sys: usize, old_len: usize, new_len: usize, new_sys: usize) -> Result<(), i32> {
+    let scratch_len = old_len.max(new_len);
+    let scratch = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            scratch_len,
+            PROT_NONE,
+            (MAP_PRIVATE | MAP_ANONYMOUS | MAP_NORESERVE) as i32,
+            -1,
+            0,
+        )
+    };
+    if scratch == libc::MAP_FAILED {
+        return Err(get_errno());
+    }
+    let parked = unsafe {
+        libc::mremap(
+            old_sys as *mut c_void,
+            old_len,
+            old_len,
+            (MREMAP_MAYMOVE | MREMAP_FIXED | MREMAP_DONTUNMAP) as i32,
+            scratch,
+        )
+    };
+    if parked == libc::MAP_FAILED {
+        let errno = get_errno();
+        unsafe { libc::munmap(scratch, scratch_len) };
+        return Err(errno);
+    }
+    let ret = unsafe {
+        libc::mremap(
+            scratch,
+            old_len,
+            new_len,
+            (MREMAP_MAYMOVE | MREMAP_FIXED) as i32,
+            new_sys as *mut c_void,
+        )
+    };
+    let errno = get_errno();
+    if ret == libc::MAP_FAILED {
+        // Put the pages back where they were, untouched
+        let back = unsafe {
+            libc::mremap(
+                scratch,
+                old_len,
+                old_len,
+                (MREMAP_MAYMOVE | MREMAP_FIXED) as i32,
+                old_sys as *mut c_void,
+            )
+        };
+        if back == libc::MAP_FAILED {
+            lind_debug_panic!("mremap: failed to restore mapping after a failed move");
+        }
+    }
+    // Past the parked pages, the scratch range is still the reservation when
+    // growing. The parked pages themselves are gone and their addresses may
+    // already belong to someone else, as may a shrunk tail the host unmapped.
+    if scratch_len > old_len {
+        unsafe {
+            libc::munmap(
+                (scratch as usize + old_len) as *mut c_void,
+                scratch_len - old_len,
+            )
+        };
+    }
+    if ret == libc::MAP_FAILED {
+        return Err(errno);
+    }
+    Ok(())
+}
+
+/// Helper for `msync`, `madvise`, `mincore`, `mlock` and `munlock`
+///
+/// Checks that `addr` (a host address) is page aligned and that every page of
+/// `[addr, addr + len)` is mapped in the cage's `vmmap`, which Linux requires for these calls.
+///
+/// ## Returns
+/// `Ok(rounded_len)` on success. `Err(Errno::EINVAL)` if the address is not aligned, or
+/// `Err(Errno::ENOMEM)` if part of the range is not mapped.
+fn check_mapped_range(cageid: u64, addr: *mut u8, len: usize) -> Result<usize, Errno> {
+    if addr as u64 != round_up_page(addr as u64) {
+        return Err(Errno::EINVAL);
+    }
+    let rounded_length = round_up_page(len as u64) as usize;
+    if rounded_length == 0 {
+        return Ok(0);
+    }
+
+    let cage = get_cage(cageid).unwrap();
+    let vmmap = cage.vmmap.read();
+    let user_addr = match (addr as usize).checked_sub(vmmap.base_address.unwrap()) {
+        Some(user_addr)
+            if user_addr as u64 + rounded_length as u64 <= vmmap.address_space_size() =>
+        {
+            user_addr
         }
+        _ => return Err(Errno::ENOMEM),
+    };
+    if !vmmap.check_existing_mapping(
+        (user_addr >> PAGESHIFT) as u32,
+        (rounded_length >> PAGESHIFT) as u32,
+        0,
+    ) {
+        return Err(Errno::ENOMEM);
     }
+    Ok(rounded_length)
 }
 
-//------------------------------------LINK SYSCALL------------------------------------
-/// Reference: https://man7.org/linux/man-pages/man2/link.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/msync.2.html
 ///
-/// `link_syscall` creates a new link (hard link) to an existing file.
+/// Flushes changes made to a file-backed `MAP_SHARED` mapping back to the file. The range
+/// must be mapped in the cage's `vmmap`; the flush itself is done by the host `msync`.
 ///
 /// ## Arguments:
-///  - `cageid`: Identifier of the calling Cage (namespace / process-like container).
-///  - `oldpath_arg`: Address of the existing pathname in the caller's address space.
-///  - `oldpath_cageid`: Cage ID associated with `oldpath_arg`.
-///  - `newpath_arg`: Address of the new pathname in the caller's address space.
-///  - `newpath_cageid`: Cage ID associated with `newpath_arg`.
-///  - `arg3`–`arg6` and their corresponding `_cageid`: Reserved arguments (must be unused).
-///
-/// ## Implementation Details:
-///  - The path arguments are translated from the RawPOSIX perspective into host kernel paths
-///    using `sc_convert_path_to_host`, which applies path normalization relative to the cage's CWD.
-///  - The unused arguments are validated with `sc_unusedarg`; any unexpected values are treated
-///    as a security violation.
-///  - The underlying `libc::link()` is invoked with the translated paths.
-///  - On failure, `errno` is retrieved via `get_errno()` and normalized through `handle_errno()`.
+///     - cageid: current cage identifier
+///     - addr_arg: host address of the start of the range
+///     - len_arg: length of the range in bytes
+///     - flags_arg: `MS_ASYNC` or `MS_SYNC`, optionally with `MS_INVALIDATE`
+///     - arg4, arg4_cageid: unused argument and its cage ID
+///     - arg5, arg5_cageid: unused argument and its cage ID
+///     - arg6, arg6_cageid: unused argument and its cage ID
 ///
-/// ## Return Value:
-///  - `0` on success.
-///  - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn link_syscall(
+/// ## Returns:
+///     - 0 on success
+///     - -1 on error with appropriate errno set
+pub extern "C" fn msync_syscall(
+    cageid: u64,
+    addr_arg: u64,
+    addr_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let addr = sc_convert_to_u8_mut(addr_arg, addr_cageid, cageid);
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "msync_syscall"
+        );
+    }
+
+    if flags & !(MS_ASYNC | MS_INVALIDATE | MS_SYNC) != 0
+        || (flags & MS_ASYNC != 0 && flags & MS_SYNC != 0)
+    {
+        return syscall_error(Errno::EINVAL, "msync", "invalid flags");
+    }
+    if let Err(e) = check_mapped_range(cageid, addr, len) {
+        return syscall_error(e, "msync", "range is not mapped");
+    }
+
+    let ret = unsafe { libc::msync(addr as *mut c_void, len, flags) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "msync");
+    }
+    ret
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/madvise.2.html
+///
+/// Gives the host advice about a range of the cage's memory. `MADV_DONTNEED` and `MADV_FREE`
+/// release the host pages backing the range, so a cage can hand freed heap memory back to the
+/// system; private anonymous pages read as zeros afterwards even if fork mapped them
+/// copy-on-write (see `cage::memory::cow`). The access-pattern and huge page hints are passed
+/// through as is. Any other advice (e.g. `MADV_DONTFORK`, which would break how `fork` copies
+/// memory) returns `EINVAL`.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - addr_arg: host address of the start of the range
+///     - len_arg: length of the range in bytes
+///     - advice_arg: the advice
+///     - arg4, arg4_cageid: unused argument and its cage ID
+///     - arg5, arg5_cageid: unused argument and its cage ID
+///     - arg6, arg6_cageid: unused argument and its cage ID
+///
+/// ## Returns:
+///     - 0 on success
+///     - -1 on error with appropriate errno set
+pub extern "C" fn madvise_syscall(
+    cageid: u64,
+    addr_arg: u64,
+    addr_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    advice_arg: u64,
+    advice_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let addr = sc_convert_to_u8_mut(addr_arg, addr_cageid, cageid);
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    let advice = sc_convert_sysarg_to_i32(advice_arg, advice_cageid, cageid);
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "madvise_syscall"
+        );
+    }
+
+    match advice {
+        MADV_NORMAL | MADV_RANDOM | MADV_SEQUENTIAL | MADV_WILLNEED | MADV_HUGEPAGE
+        | MADV_NOHUGEPAGE | MADV_DONTNEED | MADV_FREE => {}
+        _ => return syscall_error(Errno::EINVAL, "madvise", "advice is not supported"),
+    }
+    let rounded_length = match check_mapped_range(cageid, addr, len) {
+        Ok(rounded_length) => rounded_length,
+        Err(e) => return syscall_error(e, "madvise", "range is not mapped"),
+    };
+
+    // Memory forked copy-on-write must not read back the fork snapshot once discarded
+    if advice == MADV_DONTNEED || advice == MADV_FREE {
+        return match cage::discard_pages(cageid, addr as usize, rounded_length, advice) {
+            Ok(()) => 0,
+            Err(errno) => handle_errno(errno, "madvise"),
+        };
+    }
+    let ret = unsafe { libc::madvise(addr as *mut c_void, rounded_length, advice) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "madvise");
+    }
+    ret
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/mincore.2.html
+///
+/// Reports which pages of a mapped range are resident in host memory, one byte per page in
+/// `vec`.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - addr_arg: host address of the start of the range
+///     - len_arg: length of the range in bytes
+///     - vec_arg: host pointer to a buffer of at least `(len + PAGESIZE - 1) / PAGESIZE` bytes
+///     - arg4, arg4_cageid: unused argument and its cage ID
+///     - arg5, arg5_cageid: unused argument and its cage ID
+///     - arg6, arg6_cageid: unused argument and its cage ID
+///
+/// ## Returns:
+///     - 0 on success
+///     - -1 on error with appropriate errno set
+pub extern "C" fn mincore_syscall(
+    cageid: u64,
+    addr_arg: u64,
+    addr_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    vec_arg: u64,
+    vec_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let addr = sc_convert_to_u8_mut(addr_arg, addr_cageid, cageid);
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    let vec = sc_convert_to_u8_mut(vec_arg, vec_cageid, cageid);
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "mincore_syscall"
+        );
+    }
+
+    if vec.is_null() {
+        return syscall_error(Errno::EFAULT, "mincore", "vec is null");
+    }
+    if let Err(e) = check_mapped_range(cageid, addr, len) {
+        return syscall_error(e, "mincore", "range is not mapped");
+    }
+
+    let ret = unsafe { libc::mincore(addr as *mut c_void, len, vec as *mut libc::c_uchar) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "mincore");
+    }
+    ret
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/mlock.2.html
+///
+/// Locks the pages of a mapped range into host memory. As on Linux, `addr` is rounded down
+/// to a page boundary. The host's `RLIMIT_MEMLOCK` still applies, so the call may fail with
+/// `ENOMEM` or `EPERM`.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - addr_arg: host address of the start of the range
+///     - len_arg: length of the range in bytes
+///     - arg3..arg6 and their cage IDs: unused
+///
+/// ## Returns:
+///     - 0 on success
+///     - -1 on error with appropriate errno set
+pub extern "C" fn mlock_syscall(
+    cageid: u64,
+    addr_arg: u64,
+    addr_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let addr = sc_convert_to_u8_mut(addr_arg, addr_cageid, cageid);
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "mlock_syscall"
+        );
+    }
+
+    let page_offset = addr as usize % PAGESIZE as usize;
+    let addr = addr.wrapping_sub(page_offset);
+    if let Err(e) = check_mapped_range(cageid, addr, len + page_offset) {
+        return syscall_error(e, "mlock", "range is not mapped");
+    }
+
+    let ret = unsafe { libc::mlock(addr as *const c_void, len + page_offset) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "mlock");
+    }
+    ret
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/munlock.2.html
+///
+/// Unlocks the pages of a mapped range previously locked with `mlock`. As on Linux, `addr`
+/// is rounded down to a page boundary.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - addr_arg: host address of the start of the range
+///     - len_arg: length of the range in bytes
+///     - arg3..arg6 and their cage IDs: unused
+///
+/// ## Returns:
+///     - 0 on success
+///     - -1 on error with appropriate errno set
+pub extern "C" fn munlock_syscall(
+    cageid: u64,
+    addr_arg: u64,
+    addr_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let addr = sc_convert_to_u8_mut(addr_arg, addr_cageid, cageid);
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "munlock_syscall"
+        );
+    }
+
+    let page_offset = addr as usize % PAGESIZE as usize;
+    let addr = addr.wrapping_sub(page_offset);
+    if let Err(e) = check_mapped_range(cageid, addr, len + page_offset) {
+        return syscall_error(e, "munlock", "range is not mapped");
+    }
+
+    let ret = unsafe { libc::munlock(addr as *const c_void, len + page_offset) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "munlock");
+    }
+    ret
+}
+
+//------------------------------------FCNTL SYSCALL------------------------------------
+/// This function will be different in new code base (when splitting out type conversion function)
+/// since the conversion from u64 -> i32 in negative number will be different. These lines are repeated
+/// in 5 out of 6 fcntl_syscall cases, so wrapped these loc into helper functions to make code cleaner.
+///
+/// ## Arguments
+/// cageid: cage ID associate with virtual file descriptor
+/// vfd_arg: virtual file descriptor
+///
+/// ## Return Type
+/// On success:
+/// Return corresponding FDTableEntry that contains
+/// (1) underlying kernel fd.
+/// (2) file descriptor kind.
+/// (3) O_CLOEXEC flag.
+/// (4) file descriptor specific extra information.
+///
+/// On error:
+/// Return error num EBADF(Bad File Descriptor)
+pub fn _fcntl_helper(cageid: u64, vfd_arg: u64) -> Result<fdtables::FDTableEntry, Errno> {
+    if vfd_arg >= fdtables::FD_PER_PROCESS_HARD_MAX {
+        return Err(Errno::EBADF);
+    }
+    // Get underlying kernel fd
+    let wrappedvfd = fdtables::translate_virtual_fd(cageid, vfd_arg);
+    if wrappedvfd.is_err() {
+        return Err(Errno::EBADF);
+    }
+    Ok(wrappedvfd.unwrap())
+}
+
+/// Reference: https://man7.org/linux/man-pages/man2/fcntl.2.html
+///
+/// Due to the design of `fdtables` library, different virtual fds created by `dup`/`dup2` are
+/// actually refer to the same underlying kernel fd. Therefore, in `fcntl_syscall` we need to
+/// handle the cases of `F_DUPFD`, `F_DUPFD_CLOEXEC`, `F_GETFD`, and `F_SETFD` separately.
+///
+/// Among these, `F_DUPFD` and `F_DUPFD_CLOEXEC` cannot directly use the `dup_syscall` because,
+/// in `fcntl`, the duplicated fd is assigned to the lowest available number starting from `arg`,
+/// whereas the `dup_syscall` does not have this restriction and instead assigns the lowest
+/// available fd number globally.
+///
+/// Additionally, `F_DUPFD_CLOEXEC` and `F_SETFD` require updating the fd flag information
+/// (`O_CLOEXEC`) in fdtables after modifying the underlying kernel fd.
+///
+/// For all other command operations, after translating the virtual fd to the corresponding
+/// kernel fd, they are redirected to the kernel `fcntl` syscall.
+///
+/// ## Arguments
+/// vfd_arg: virtual file descriptor
+/// cmd: The operation
+/// arg: an optional third argument.  Whether or not this argument is required is determined by op.  
+///
+/// ## Returns:
+///     - For a successful call, the return value depends on the operation:
+///       - `F_DUPFD`: The new file descriptor.
+///       - `F_GETFD`: Value of file descriptor flags.
+///       - `F_GETFL`: Value of file status flags.
+///       - `F_GETLEASE`: Type of lease held on file descriptor.
+///       - `F_GETOWN`: Value of file descriptor owner.
+///       - `F_GETSIG`: Value of signal sent when read or write becomes possible, or zero for traditional SIGIO behavior.
+///       - `F_GETPIPE_SZ`, `F_SETPIPE_SZ`: The pipe capacity.
+///       - `F_GET_SEALS`: A bit mask identifying the seals that have been set for the inode referred to by fd.
+///       - All other commands: Zero.
+///     - On error, -1 is returned and errno is set to indicate the error.
+///
+/// TODO: `F_GETOWN`, `F_SETOWN`, `F_GETOWN_EX`, `F_SETOWN_EX`, `F_GETSIG`, and `F_SETSIG` are used to manage I/O availability signals.
+pub extern "C" fn fcntl_syscall(
+    cageid: u64,
+    vfd_arg: u64,
+    _vfd_cageid: u64,
+    cmd_arg: u64,
+    cmd_cageid: u64,
+    int_arg: u64, // arg3: integer value (for F_DUPFD, F_SETFD, F_GETFL, etc.)
+    _int_arg_cageid: u64,
+    ptr_arg: u64, // arg4: translated host pointer (for F_GETLK, F_SETLK, etc.)
+    _ptr_arg_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let cmd = sc_convert_sysarg_to_i32(cmd_arg, cmd_cageid, cageid);
+
+    // Convert int value only, we handle the pointer args if it's a lock operation
+    let arg = int_arg as i32;
+
+    // Validate unused arguments
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "fcntl_syscall"
+        );
+    }
+
+    match (cmd, arg) {
+        // Duplicate the file descriptor `vfd_arg` using the lowest-numbered
+        // available file descriptor greater than or equal to `arg`. The operation here
+        // is quite similar to `dup_syscall`, for specific operation explanation, see
+        // comments on `dup_syscall`.
+        (F_DUPFD, arg) => {
+            // Get fdtable entry
+            let vfd = match _fcntl_helper(cageid, vfd_arg) {
+                Ok(entry) => entry,
+                Err(e) => return syscall_error(e, "fcntl", "Bad File Descriptor"),
+            };
+            // Get lowest-numbered available file descriptor greater than or equal to `arg`
+            match fdtables::get_unused_virtual_fd_from_startfd(
+                cageid,
+                vfd.fdkind,
+                vfd.underfd,
+                false,
+                0,
+                arg as u64,
+            ) {
+                Ok(new_vfd) => return new_vfd as i32,
+                // EINVAL if `arg` is past the soft limit, else EMFILE or ENFILE
+                Err(e) => return handle_errno(e as i32, "fcntl"),
+            }
+        }
+        // As for `F_DUPFD`, but additionally set the close-on-exec flag
+        // for the duplicate file descriptor.
+        (F_DUPFD_CLOEXEC, arg) => {
+            // Get fdtable entry
+            let vfd = match _fcntl_helper(cageid, vfd_arg) {
+                Ok(entry) => entry,
+                Err(e) => return syscall_error(e, "fcntl", "Bad File Descriptor"),
+            };
+            // Get lowest-numbered available file descriptor greater than or equal to `arg`
+            // and set the `O_CLOEXEC` flag
+            match fdtables::get_unused_virtual_fd_from_startfd(
+                cageid,
+                vfd.fdkind,
+                vfd.underfd,
+                true,
+                0,
+                arg as u64,
+            ) {
+                Ok(new_vfd) => return new_vfd as i32,
+                // EINVAL if `arg` is past the soft limit, else EMFILE or ENFILE
+                Err(e) => return handle_errno(e as i32, "fcntl"),
+            }
+        }
+        // Return (as the function result) the file descriptor flags.
+        (F_GETFD, ..) => {
+            // Get fdtable entry
+            let vfd = match _fcntl_helper(cageid, vfd_arg) {
+                Ok(entry) => entry,
+                Err(e) => return syscall_error(e, "fcntl", "Bad File Descriptor"),
+            };
+            return vfd.should_cloexec as i32;
+        }
+        // Set the file descriptor flags to the value specified by arg.
+        (F_SETFD, arg) => {
+            // Get fdtable entry
+            let vfd = match _fcntl_helper(cageid, vfd_arg) {
+                Ok(entry) => entry,
+                Err(e) => return syscall_error(e, "fcntl", "Bad File Descriptor"),
+            };
+            // Set underlying kernel fd flag
+            let ret = unsafe { libc::fcntl(vfd.underfd as i32, cmd, arg) };
+            if ret < 0 {
+                let errno = get_errno();
+                return handle_errno(errno, "fcntl");
+            }
+            // Set virtual fd flag
+            let cloexec_flag: bool = arg != 0;
+            match fdtables::set_cloexec(cageid, vfd_arg as u64, cloexec_flag) {
+                Ok(_) => return 0,
+                Err(_e) => return syscall_error(Errno::EBADF, "fcntl", "Bad File Descriptor"),
+            }
+        }
+        // todo: F_GETOWN and F_SETOWN commands are not implemented yet
+        (F_GETOWN, ..) => DEFAULT_GID as i32,
+        (F_SETOWN, arg) if arg >= 0 => 0,
+        _ => {
+            // Get fdtable entry
+            let vfd = match _fcntl_helper(cageid, vfd_arg) {
+                Ok(entry) => entry,
+                Err(e) => return syscall_error(e, "fcntl", "Bad File Descriptor"),
+            };
+            let is_lock_op = cmd == F_GETLK
+                || cmd == F_SETLK
+                || cmd == F_SETLKW
+                || cmd == F_GETLK64
+                || cmd == F_SETLK64
+                || cmd == F_SETLKW64;
+
+            let ret = if is_lock_op {
+                // Lock operation - use ptr_arg (arg4)
+                unsafe { libc::fcntl(vfd.underfd as i32, cmd, ptr_arg as *mut c_void) }
+            } else {
+                // Other operations - use int_arg (arg3)
+                unsafe { libc::fcntl(vfd.underfd as i32, cmd, arg) }
+            };
+
+            if ret < 0 {
+                let errno = get_errno();
+                return handle_errno(errno, "fcntl");
+            }
+            ret
+        }
+    }
+}
+
+//------------------------------------LINK SYSCALL------------------------------------
+/// Reference: https://man7.org/linux/man-pages/man2/link.2.html
+///
+/// `link_syscall` creates a new link (hard link) to an existing file.
+///
+/// ## Arguments:
+///  - `cageid`: Identifier of the calling Cage (namespace / process-like container).
+///  - `oldpath_arg`: Address of the existing pathname in the caller's address space.
+///  - `oldpath_cageid`: Cage ID associated with `oldpath_arg`.
+///  - `newpath_arg`: Address of the new pathname in the caller's address space.
+///  - `newpath_cageid`: Cage ID associated with `newpath_arg`.
+///  - `arg3`–`arg6` and their corresponding `_cageid`: Reserved arguments (must be unused).
+///
+/// ## Implementation Details:
+///  - The path arguments are translated from the RawPOSIX perspective into host kernel paths
+///    using `sc_convert_path_to_host`, which applies path normalization relative to the cage's CWD.
+///  - The unused arguments are validated with `sc_unusedarg`; any unexpected values are treated
+///    as a security violation.
+///  - Paths under `/dev/shm` are linked in the shared memory namespace of `crate::shm`.
+///  - Otherwise the underlying `libc::link()` is invoked with the translated paths.
+///  - On failure, `errno` is retrieved via `get_errno()` and normalized through `handle_errno()`.
+///
+/// ## Return Value:
+///  - `0` on success.
+///  - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn link_syscall(
+    cageid: u64,
+    oldpath_arg: u64,
+    oldpath_cageid: u64,
+    newpath_arg: u64,
+    newpath_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let oldpath = match sc_convert_path_to_host(oldpath_arg, oldpath_cageid, cageid) {
+        Ok(oldpath) => oldpath,
+        Err(e) => return syscall_error(e, "link", "path conversion failed"),
+    };
+    let newpath = match sc_convert_path_to_host(newpath_arg, newpath_cageid, cageid) {
+        Ok(newpath) => newpath,
+        Err(e) => return syscall_error(e, "link", "path conversion failed"),
+    };
+
+    // Validate unused args
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "link_syscall"
+        );
+    }
+
+    // sem_open() links a new semaphore to its name under /dev/shm
+    if let Some(ret) = crate::shm::link(&oldpath, &newpath) {
+        return match ret {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "link", "could not link shared memory"),
+        };
+    }
+
+    let ret = unsafe { libc::link(oldpath.as_ptr(), newpath.as_ptr()) };
+
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "link");
+    }
+    ret
+}
+
+//------------------------------------XSTAT SYSCALL------------------------------------
+/// `xstat` retrieves file status information (versioned stat interface).
+/// Reference: https://man7.org/linux/man-pages/man2/stat.2.html
+///
+/// ## Arguments:
+///  - `vers`: Version parameter for stat structure compatibility.
+///  - `pathname`: Path to the file to get status information for.
+///  - `statbuf`: Buffer to store the file status information.
+///
+/// ## Implementation Details:
+///  - The path is converted from the RawPOSIX perspective to the host kernel perspective
+///    using `sc_convert_path_to_host`, which handles path normalization relative to the cage's CWD.
+///  - The underlying libc::stat() is called and results are copied to the user buffer.
+///
+/// ## Return Value:
+///  - `0` on success.
+///  - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn stat_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    statbuf_arg: u64,
+    statbuf_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "stat", "path conversion failed"),
+    };
+
+    // Validate unused args
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "stat_syscall"
+        );
+    }
+
+    // Declare statbuf by ourselves
+    let mut libc_statbuf: stat = unsafe { std::mem::zeroed() };
+    let libcret = unsafe { libc::stat(path.as_ptr(), &mut libc_statbuf) };
+
+    if libcret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "xstat");
+    }
+
+    // Convert libc stat to StatData and copy to user buffer
+    match sc_convert_addr_to_statdata(statbuf_arg, statbuf_cageid, cageid) {
+        Ok(statbuf_addr) => convert_statdata_to_user(statbuf_addr, libc_statbuf),
+        Err(e) => return syscall_error(e, "xstat", "Bad address"),
+    }
+
+    libcret
+}
+
+//------------------------------------LSTAT SYSCALL------------------------------------
+/// `lstat` retrieves file status information without following symlinks.
+/// Reference: https://man7.org/linux/man-pages/man2/lstat.2.html
+///
+/// ## Arguments:
+///  - `pathname`: Path to the file or symlink to get status information for.
+///  - `statbuf`: Buffer to store the file status information.
+///
+/// ## Implementation Details:
+///  - Identical to `stat_syscall`, except `libc::lstat` is used instead of `libc::stat`,
+///    so symlinks are stat'd directly rather than following to the target.
+///
+/// ## Return Value:
+///  - `0` on success.
+///  - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn lstat_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    statbuf_arg: u64,
+    statbuf_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "lstat", "path conversion failed"),
+    };
+
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "lstat_syscall"
+        );
+    }
+
+    let mut libc_statbuf: stat = unsafe { std::mem::zeroed() };
+    let libcret = unsafe { libc::lstat(path.as_ptr(), &mut libc_statbuf) }; // <-- only change
+
+    if libcret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "lstat");
+    }
+
+    match sc_convert_addr_to_statdata(statbuf_arg, statbuf_cageid, cageid) {
+        Ok(statbuf_addr) => convert_statdata_to_user(statbuf_addr, libc_statbuf),
+        Err(e) => return syscall_error(e, "lstat", "Bad address"),
+    }
+
+    libcret
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/statfs.2.html
+///
+/// Linux `statfs()` syscall returns information about a mounted filesystem
+/// that contains the file or directory specified by `path`.  
+/// In RawPOSIX, because each Cage has its own virtualized filesystem view,
+/// the path is first translated from the Cage's namespace into the host
+/// kernel namespace using `sc_convert_path_to_host`.  
+/// After translation, the kernel's `libc::statfs()` is invoked to obtain
+/// the filesystem information. The resulting `statfs` structure is then
+/// converted into our ABI-stable `FStatData` format and copied into the
+/// user-provided buffer in Cage memory.
+///
+/// ## Input:
+/// - `cageid`: Identifier of the current Cage
+/// - `path_arg`: Wasm address of the pathname string
+/// - `path_cageid`: Cage ID associated with `path_arg`
+/// - `statbuf_arg`: Wasm address of the buffer where results will be stored
+/// - `statbuf_cageid`: Cage ID associated with `statbuf_arg`
+/// - `arg3`–`arg6`: Unused arguments (validated for security)
+///
+/// ## Return Value:
+/// - `0` on success  
+/// - `-1` on failure, with `errno` set appropriately
+pub extern "C" fn statfs_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    statbuf_arg: u64,
+    statbuf_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "statfs", "path conversion failed"),
+    };
+
+    // Validate unused args
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "statfs_syscall"
+        );
+    }
+
+    // Call host statfs into a local host-side buffer first. Do not let host libc
+    // write directly into guest memory, since host libc::statfs layout may differ
+    // from the wasm32 statfs layout.
+    let mut host_statfs: libc::statfs = unsafe { std::mem::zeroed() };
+    let ret = unsafe { libc::statfs(path.as_ptr(), &mut host_statfs) };
+
+    if ret < 0 {
+        return handle_errno(get_errno(), "statfs");
+    }
+
+    match sc_convert_addr_to_fstatdata(statbuf_arg, statbuf_cageid, cageid) {
+        Ok(statbuf_addr) => {
+            convert_fstatdata_to_user(statbuf_addr, host_statfs);
+            ret
+        }
+        Err(e) => syscall_error(e, "statfs", "Bad address"),
+    }
+}
+
+//------------------------------------FSYNC SYSCALL------------------------------------
+/// `fsync` synchronizes a file's in-core state with storage device.
+/// Reference: https://man7.org/linux/man-pages/man2/fsync.2.html
+///
+/// ## Arguments:
+///  - `fd`: File descriptor to synchronize.
+///
+/// ## Implementation Details:
+///  - The virtual file descriptor is converted to a kernel file descriptor using `convert_fd_to_host`.
+///  - This ensures proper translation between RawPOSIX virtual fds and host kernel fds.
+///  - The underlying libc::fsync() is called, which synchronizes both file data and metadata.
+///
+/// ## Return Value:
+///  - `0` on success.
+///  - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn fsync_syscall(
+    cageid: u64,
+    fd_arg: u64,
+    fd_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let virtual_fd = sc_convert_sysarg_to_i32(fd_arg, fd_cageid, cageid);
+
+    // Validate unused args
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "fsync_syscall"
+        );
+    }
+
+    let kernel_fd = convert_fd_to_host(virtual_fd as u64, fd_cageid, cageid);
+    // convert_fd_to_host returns negative errno values on error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "read");
+    }
+
+    let ret = unsafe { libc::fsync(kernel_fd) };
+
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "fsync");
+    }
+    return ret;
+}
+
+//------------------------------------FDATASYNC SYSCALL------------------------------------
+/// `fdatasync` synchronizes a file's data to storage device (but not metadata).
+/// Reference: https://man7.org/linux/man-pages/man2/fdatasync.2.html
+///
+/// ## Arguments:
+///  - `fd`: File descriptor to synchronize.
+///
+/// ## Implementation Details:
+///  - The virtual file descriptor is converted to a kernel file descriptor using `convert_fd_to_host`.
+///  - This ensures proper translation between RawPOSIX virtual fds and host kernel fds.
+///  - The underlying libc::fdatasync() is called, which synchronizes only file data (not metadata
+///    like timestamps), making it potentially faster than fsync().
+///
+/// ## Return Value:
+///  - `0` on success.
+///  - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn fdatasync_syscall(
+    cageid: u64,
+    fd_arg: u64,
+    fd_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let virtual_fd = sc_convert_sysarg_to_i32(fd_arg, fd_cageid, cageid);
+
+    // Validate unused args
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "fdatasync_syscall"
+        );
+    }
+
+    let kernel_fd = convert_fd_to_host(virtual_fd as u64, fd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "read");
+    }
+
+    let ret = unsafe { libc::fdatasync(kernel_fd) };
+
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "fdatasync");
+    }
+    return ret;
+}
+
+//------------------------------------SYNC_FILE_RANGE SYSCALL------------------------------------
+/// `sync_file_range` synchronizes a specific range of bytes in a file to storage device.
+/// Reference: https://man7.org/linux/man-pages/man2/sync_file_range.2.html
+///
+/// ## Arguments:
+///  - `fd`: File descriptor to synchronize.
+///  - `offset`: Starting byte offset for the range to sync.
+///  - `nbytes`: Number of bytes to synchronize.
+///  - `flags`: Flags controlling the synchronization behavior.
+///
+/// ## Implementation Details:
+///  - The virtual file descriptor is converted to a kernel file descriptor using `convert_fd_to_host`.
+///  - This ensures proper translation between RawPOSIX virtual fds and host kernel fds.
+///  - The underlying libc::sync_file_range() is called with the specified byte range and flags.
+///  - This is more efficient than fsync() for large files when only a specific range needs syncing.
+///
+/// ## Return Value:
+///  - `0` on success.
+///  - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn sync_file_range_syscall(
+    cageid: u64,
+    fd_arg: u64,
+    fd_cageid: u64,
+    offset_arg: u64,
+    offset_cageid: u64,
+    nbytes_arg: u64,
+    nbytes_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let virtual_fd = sc_convert_sysarg_to_i32(fd_arg, fd_cageid, cageid);
+    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
+    let nbytes = sc_convert_sysarg_to_i64(nbytes_arg, nbytes_cageid, cageid);
+    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_cageid, cageid);
+
+    // Validate unused args
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "sync_file_range_syscall"
+        );
+    }
+
+    let kernel_fd = convert_fd_to_host(virtual_fd as u64, fd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "read");
+    }
+
+    let ret = unsafe { libc::sync_file_range(kernel_fd, offset, nbytes, flags) };
+
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "sync_file_range");
+    }
+    ret
+}
+
+//------------------------------------READLINK & READLINKAT SYSCALL------------------------------------
+/// Reference: https://man7.org/linux/man-pages/man2/readlink.2.html
+///
+/// The return value of the readlink syscall indicates the number of bytes written into the buf and -1 if
+/// error. The contents of the buf represent the file path that the symbolic link points to. Since the file
+/// path perspectives differ between the user application and the host Linux, the readlink implementation
+/// requires handling the paths for both the input passed to the Rust kernel libc and the output buffer
+/// returned by the kernel libc.
+///
+/// For the input path, the transformation is straightforward: we normalize it relative to the cage's CWD
+/// to convert the user's relative path into an absolute path within the chroot jail.
+/// For the output buffer, we need to first verify whether the path written to buf is an absolute
+/// path. If it is not, we prepend the current working directory to make it absolute. The result
+/// is then truncated to fit within the user-provided buflen, ensuring compliance with the behavior
+/// described in the Linux readlink man page, which states that truncation is performed silently if
+/// the buffer is too small.
+///
+/// ## Input:
+/// - `cageid`: Identifier of the current Cage
+/// - `path_arg`: Address of the symbolic link pathname in Wasm memory
+/// - `path_cageid`: Cage ID associated with `path_arg`
+/// - `buf_arg`: Address of the user buffer to store the link target
+/// - `buf_cageid`: Cage ID associated with `buf_arg`
+/// - `buflen_arg`: Size of the user buffer
+/// - `buflen_cageid`: Cage ID associated with `buflen_arg`
+/// - `arg4`–`arg6`: Unused arguments (validated for security)
+///
+/// ## Return:
+/// - On success: number of bytes placed in `buf` (not null-terminated)  
+/// - On failure: `-1`, with `errno` set appropriately
+pub extern "C" fn readlink_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    buf_arg: u64,
+    _buf_cageid: u64,
+    buflen_arg: u64,
+    buflen_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "readlink", "path conversion failed"),
+    };
+    let buf = buf_arg as *mut u8;
+    let buflen = sc_convert_sysarg_to_usize(buflen_arg, buflen_cageid, cageid);
+
+    // Validate unused args
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "readlink_syscall"
+        );
+    }
+
+    // Call to kernel readlink
+    let bytes_written = unsafe { libc::readlink(path.as_ptr(), buf as *mut libc::c_char, buflen) };
+
+    if bytes_written < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "readlink");
+    }
+
+    bytes_written as i32
+}
+
+/// `readlinkat` reads the value of a symbolic link relative to a directory file descriptor.
+/// Reference: https://man7.org/linux/man-pages/man2/readlinkat.2.html
+///
+/// ## Arguments:
+///  - `dirfd`: Directory file descriptor. If `AT_FDCWD`, it uses the current working directory.
+///  - `pathname`: Path to the symbolic link (relative to dirfd).
+///  - `buf`: Buffer to store the link target.
+///  - `bufsiz`: Size of the buffer.
+///
+/// There are two cases:
+/// Case 1: When `dirfd` is AT_FDCWD:
+///   - The path is converted using `sc_convert_path_to_host` and libc::readlink() is called.
+///   - This uses the current working directory as the base for relative paths.
+///
+/// Case 2: When `dirfd` is not AT_FDCWD:
+///   - The virtual file descriptor is converted to a kernel file descriptor using `convert_fd_to_host`.
+///   - The path is converted using `sc_convert_path_to_host` and libc::readlinkat() is called.
+///   - This reads the symlink relative to the specified directory.
+///
+/// ## Return Value:
+///  - Number of bytes placed in `buf` on success.
+///  - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn readlinkat_syscall(
+    cageid: u64,
+    dirfd_arg: u64,
+    dirfd_cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    buf_arg: u64,
+    buf_cageid: u64,
+    buflen_arg: u64,
+    buflen_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let virtual_fd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "readlinkat", "path conversion failed"),
+    };
+    let buf = sc_convert_to_cchar_mut(buf_arg, buf_cageid, cageid);
+    let buflen = sc_convert_sysarg_to_usize(buflen_arg, buflen_cageid, cageid);
+
+    // Validate unused args
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "readlinkat_syscall"
+        );
+    }
+
+    let ret = if virtual_fd == libc::AT_FDCWD {
+        unsafe { libc::readlink(path.as_ptr(), buf, buflen) }
+    } else {
+        // Case 2: Specific directory fd
+        let kernel_fd = convert_fd_to_host(virtual_fd as u64, dirfd_cageid, cageid);
+        // Return error
+        if kernel_fd < 0 {
+            return handle_errno(-kernel_fd, "readlinkat");
+        }
+
+        let raw_path = match get_cstr(path_arg) {
+            Ok(p) => p,
+            Err(_) => {
+                return syscall_error(
+                    Errno::EINVAL,
+                    "readlinkat",
+                    "invalid  
+        path",
+                )
+            }
+        };
+
+        unsafe { libc::readlinkat(kernel_fd, raw_path.as_ptr() as *const c_char, buf, buflen) }
+    };
+
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "readlinkat");
+    }
+
+    ret as i32
+}
+
+//------------------RENAME SYSCALL------------------
+/// `rename` changes the name or location of a file.
+/// Reference: https://man7.org/linux/man-pages/man2/rename.2.html
+///
+/// ## Arguments:
+///  - `oldpath`: Current path of the file.
+///  - `newpath`: New path for the file.
+///
+/// ## Implementation Details:
+///  - Both paths are converted from the RawPOSIX perspective to the host kernel perspective
+///    using `sc_convert_path_to_host`, which handles path normalization relative to the cage's CWD.
+///  - The underlying libc::rename() is called with both converted paths.
+///  - This can move files across directories within the same filesystem.
+///
+/// ## Return Value:
+///  - `0` on success.
+///  - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn rename_syscall(
+    cageid: u64,
+    oldpath_arg: u64,
+    oldpath_cageid: u64,
+    newpath_arg: u64,
+    newpath_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let oldpath = match sc_convert_path_to_host(oldpath_arg, oldpath_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "rename", "path conversion failed"),
+    };
+    let newpath = match sc_convert_path_to_host(newpath_arg, newpath_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "rename", "path conversion failed"),
+    };
+
+    // Validate unused args
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "rename_syscall"
+        );
+    }
+
+    let ret = unsafe { libc::rename(oldpath.as_ptr(), newpath.as_ptr()) };
+
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "rename");
+    }
+    ret
+}
+
+//------------------------------------UNLINK & UNLINAT SYSCALL------------------------------------
+/// `unlink` removes a file from the filesystem.
+/// Reference: https://man7.org/linux/man-pages/man2/unlink.2.html
+///
+/// ## Arguments:
+///  - `pathname`: Path to the file to be removed.
+///
+/// ## Implementation Details:
+///  - The path is converted from the RawPOSIX perspective to the host kernel perspective
+///    using `sc_convert_path_to_host`, which handles path normalization relative to the cage's CWD.
+///  - The underlying libc::unlink() is called with the converted path.
+///
+/// ## Return Value:
+///  - `0` on success.
+///  - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn unlink_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "unlink", "path conversion failed"),
+    };
+
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "unlink_syscall"
+        );
+    }
+
+    // shm_unlink() unlinks a path under /dev/shm
+    if let Some(ret) = crate::shm::unlink(&path) {
+        return match ret {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "unlink", "could not remove shared memory"),
+        };
+    }
+
+    let ret = unsafe { libc::unlink(path.as_ptr()) };
+
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "unlink");
+    }
+
+    ret
+}
+
+/// Reference: https://man7.org/linux/man-pages/man2/unlink.2.html
+///
+/// `unlinkat` removes a file or directory relative to a directory file descriptor.
+///
+/// ## Arguments:
+/// - `dirfd`: Directory file descriptor. If `AT_FDCWD`, it uses the current working directory.
+/// - `pathname`: Path of the file/directory to be removed.
+/// - `flags`: Can include `AT_REMOVEDIR` to indicate directory removal.
+///
+/// There are two cases:
+/// Case 1: When `dirfd` is AT_FDCWD:
+/// - RawPOSIX maintains its own notion of the current working directory.
+/// - We convert the provided relative `pathname` (using `convpath` and `normpath`) into an absolute
+///   path relative to the cage's CWD within the chroot jail.
+/// - After this conversion, the path is already absolute from the host’s perspective, so `AT_FDCWD`
+///   doesn't actually rely on the host’s working directory. This avoids mismatches between RawPOSIX
+///   and the host environment.
+///
+/// Case 2: When `dirfd` is not AT_FDCWD:
+/// - We translate the RawPOSIX virtual file descriptor to the corresponding kernel file descriptor.
+/// - In this case, we simply create a C string from the provided `pathname` (without further conversion)
+///   and let the underlying kernel call resolve the path relative to the directory represented by that fd.
+///
+/// ## Return Value:
+/// - `0` on success.
+/// - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn unlinkat_syscall(
+    cageid: u64,
+    dirfd_arg: u64,
+    dirfd_cageid: u64,
+    pathname_arg: u64,
+    pathname_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+
+    // Validate unused args - this should never fail in correct implementation
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "unlinkat_syscall"
+        );
+    }
+
+    let c_path;
+    // Determine the appropriate kernel file descriptor and pathname conversion based on dirfd.
+    let kernel_fd = if dirfd == AT_FDCWD {
+        // Case 1: When AT_FDCWD is used.
+        // Convert the provided pathname from the RawPOSIX working directory (which is different from the host's)
+        // into an absolute path within the chroot jail.
+        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
+            Ok(path) => path,
+            Err(e) => return syscall_error(e, "unlinkat", "path conversion failed"),
+        };
+        if flags & libc::AT_REMOVEDIR == 0 {
+            if let Some(ret) = crate::shm::unlink(&c_path) {
+                return match ret {
+                    Ok(()) => 0,
+                    Err(e) => syscall_error(e, "unlinkat", "could not remove shared memory"),
+                };
+            }
+        }
+        AT_FDCWD
+    } else {
+        // Case 2: When a specific directory fd is provided.
+        // Translate the virtual file descriptor to the corresponding kernel file descriptor.
+        let wrappedvfd = fdtables::translate_virtual_fd(cageid, dirfd as u64);
+        if wrappedvfd.is_err() {
+            return syscall_error(Errno::EBADF, "unlinkat", "Bad File Descriptor");
+        }
+        let vfd = wrappedvfd.unwrap();
+        // For this case, we pass the provided pathname directly.
+        let pathname = pathname_arg;
+        let tmp_cstr = get_cstr(pathname).unwrap();
+        c_path = CString::new(tmp_cstr).unwrap();
+        vfd.underfd as i32
+    };
+
+    // Call the underlying libc::unlinkat() function with the fd and pathname.
+    let ret = unsafe { libc::unlinkat(kernel_fd, c_path.as_ptr(), flags) };
+
+    // If the call failed, retrieve and handle the errno
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "unlinkat");
+    }
+    ret
+}
+
+//------------------------------------FCHMODAT SYSCALL------------------------------------
+/// Reference: https://man7.org/linux/man-pages/man2/fchmodat.2.html
+///
+/// `fchmodat` changes the permissions of a file relative to a directory fd.
+/// Modeled on `unlinkat_syscall` for the dirfd handling.
+pub extern "C" fn fchmodat_syscall(
+    cageid: u64,
+    dirfd_arg: u64,
+    dirfd_cageid: u64,
+    pathname_arg: u64,
+    pathname_cageid: u64,
+    mode_arg: u64,
+    mode_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
+    let mode = sc_convert_sysarg_to_u32(mode_arg, mode_cageid, cageid);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "fchmodat_syscall"
+        );
+    }
+
+    let c_path;
+    let kernel_fd = if dirfd == AT_FDCWD {
+        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
+            Ok(path) => path,
+            Err(e) => return syscall_error(e, "fchmodat", "path conversion failed"),
+        };
+        AT_FDCWD
+    } else {
+        let wrappedvfd = fdtables::translate_virtual_fd(cageid, dirfd as u64);
+        if wrappedvfd.is_err() {
+            return syscall_error(Errno::EBADF, "fchmodat", "Bad File Descriptor");
+        }
+        let vfd = wrappedvfd.unwrap();
+        let tmp_cstr = get_cstr(pathname_arg).unwrap();
+        c_path = CString::new(tmp_cstr).unwrap();
+        vfd.underfd as i32
+    };
+
+    let ret = unsafe { libc::fchmodat(kernel_fd, c_path.as_ptr(), mode, flags) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "fchmodat");
+    }
+    ret
+}
+
+//------------------------------------FACCESSAT SYSCALL------------------------------------
+/// Reference: https://man7.org/linux/man-pages/man2/faccessat.2.html
+///
+/// `faccessat` checks accessibility of a file relative to a directory fd.
+pub extern "C" fn faccessat_syscall(
+    cageid: u64,
+    dirfd_arg: u64,
+    dirfd_cageid: u64,
+    pathname_arg: u64,
+    pathname_cageid: u64,
+    mode_arg: u64,
+    mode_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
+    let mode = sc_convert_sysarg_to_i32(mode_arg, mode_cageid, cageid);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "faccessat_syscall"
+        );
+    }
+
+    let c_path;
+    let kernel_fd = if dirfd == AT_FDCWD {
+        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
+            Ok(path) => path,
+            Err(e) => return syscall_error(e, "faccessat", "path conversion failed"),
+        };
+        AT_FDCWD
+    } else {
+        let wrappedvfd = fdtables::translate_virtual_fd(cageid, dirfd as u64);
+        if wrappedvfd.is_err() {
+            return syscall_error(Errno::EBADF, "faccessat", "Bad File Descriptor");
+        }
+        let vfd = wrappedvfd.unwrap();
+        let tmp_cstr = get_cstr(pathname_arg).unwrap();
+        c_path = CString::new(tmp_cstr).unwrap();
+        vfd.underfd as i32
+    };
+
+    let ret = unsafe { libc::faccessat(kernel_fd, c_path.as_ptr(), mode, flags) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "faccessat");
+    }
+    ret
+}
+
+//------------------------------------FSTATAT SYSCALL (newfstatat)------------------------------------
+/// Reference: https://man7.org/linux/man-pages/man2/fstatat.2.html
+///
+/// `fstatat` retrieves stat info for a path relative to a directory fd.
+/// Linux's `newfstatat` (262) is what `fts_open` uses for traversal.
+pub extern "C" fn fstatat_syscall(
+    cageid: u64,
+    dirfd_arg: u64,
+    dirfd_cageid: u64,
+    pathname_arg: u64,
+    pathname_cageid: u64,
+    statbuf_arg: u64,
+    statbuf_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "fstatat_syscall"
+        );
+    }
+
+    let c_path;
+    let kernel_fd = if dirfd == AT_FDCWD {
+        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
+            Ok(path) => path,
+            Err(e) => return syscall_error(e, "fstatat", "path conversion failed"),
+        };
+        AT_FDCWD
+    } else {
+        let wrappedvfd = fdtables::translate_virtual_fd(cageid, dirfd as u64);
+        if wrappedvfd.is_err() {
+            return syscall_error(Errno::EBADF, "fstatat", "Bad File Descriptor");
+        }
+        let vfd = wrappedvfd.unwrap();
+        let tmp_cstr = get_cstr(pathname_arg).unwrap();
+        c_path = CString::new(tmp_cstr).unwrap();
+        vfd.underfd as i32
+    };
+
+    let mut libc_statbuf: stat = unsafe { std::mem::zeroed() };
+    let ret = unsafe { libc::fstatat(kernel_fd, c_path.as_ptr(), &mut libc_statbuf, flags) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "fstatat");
+    }
+
+    match sc_convert_addr_to_statdata(statbuf_arg, statbuf_cageid, cageid) {
+        Ok(statbuf_addr) => convert_statdata_to_user(statbuf_addr, libc_statbuf),
+        Err(e) => return syscall_error(e, "fstatat", "Bad address"),
+    }
+
+    ret
+}
+
+//------------------------------------STATX SYSCALL------------------------------------
+/// Reference: https://man7.org/linux/man-pages/man2/statx.2.html
+///
+/// `statx` retrieves extended file status for a path relative to a directory fd. Unlike
+/// `stat`, `struct statx` is defined with fixed-width fields and explicit padding, so the
+/// layout glibc uses inside the cage is identical to the host kernel's and the buffer can
+/// be filled in place without a `convert_*_to_user` step.
+///
+/// Path handling mirrors `fstatat_syscall`: with `AT_FDCWD` the path is normalized against
+/// the cage's cwd; otherwise the raw path is resolved by the kernel relative to the
+/// translated dirfd (which also covers `AT_EMPTY_PATH` with an empty path).
+pub extern "C" fn statx_syscall(
+    cageid: u64,
+    dirfd_arg: u64,
+    dirfd_cageid: u64,
+    pathname_arg: u64,
+    pathname_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    mask_arg: u64,
+    mask_cageid: u64,
+    statxbuf_arg: u64,
+    statxbuf_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+    let mask = sc_convert_sysarg_to_u32(mask_arg, mask_cageid, cageid);
+
+    if !sc_unusedarg(arg6, arg6_cageid) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "statx_syscall"
+        );
+    }
+
+    let c_path;
+    let kernel_fd = if dirfd == AT_FDCWD {
+        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
+            Ok(path) => path,
+            Err(e) => return syscall_error(e, "statx", "path conversion failed"),
+        };
+        AT_FDCWD
+    } else {
+        let host_fd = convert_fd_to_host(dirfd as u64, dirfd_cageid, cageid);
+        if host_fd < 0 {
+            return handle_errno(-host_fd, "statx");
+        }
+        let raw_path = match get_cstr(pathname_arg) {
+            Ok(p) => p,
+            Err(_) => return syscall_error(Errno::EFAULT, "statx", "invalid path"),
+        };
+        c_path = match CString::new(raw_path) {
+            Ok(c) => c,
+            Err(_) => return syscall_error(Errno::EINVAL, "statx", "invalid path"),
+        };
+        host_fd
+    };
+
+    let statxbuf = sc_convert_to_u8_mut(statxbuf_arg, statxbuf_cageid, cageid) as *mut libc::statx;
+    let ret = unsafe { libc::statx(kernel_fd, c_path.as_ptr(), flags, mask, statxbuf) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "statx");
+    }
+
+    ret
+}
+
+//------------------------------------RENAMEAT / RENAMEAT2 SYSCALLS------------------------------------
+/// Internal helper used by both renameat and renameat2.
+fn renameat_inner(
+    cageid: u64,
+    olddirfd: i32,
+    oldpath_arg: u64,
+    oldpath_cageid: u64,
+    newdirfd: i32,
+    newpath_arg: u64,
+    newpath_cageid: u64,
+    flags: u32,
+    use_renameat2: bool,
+    label: &'static str,
+) -> i32 {
+    let c_oldpath;
+    let old_kernel_fd = if olddirfd == AT_FDCWD {
+        c_oldpath = match sc_convert_path_to_host(oldpath_arg, oldpath_cageid, cageid) {
+            Ok(path) => path,
+            Err(e) => return syscall_error(e, label, "old path conversion failed"),
+        };
+        AT_FDCWD
+    } else {
+        let wrappedvfd = fdtables::translate_virtual_fd(cageid, olddirfd as u64);
+        if wrappedvfd.is_err() {
+            return syscall_error(Errno::EBADF, label, "Bad olddirfd");
+        }
+        let vfd = wrappedvfd.unwrap();
+        let tmp_cstr = get_cstr(oldpath_arg).unwrap();
+        c_oldpath = CString::new(tmp_cstr).unwrap();
+        vfd.underfd as i32
+    };
+
+    let c_newpath;
+    let new_kernel_fd = if newdirfd == AT_FDCWD {
+        c_newpath = match sc_convert_path_to_host(newpath_arg, newpath_cageid, cageid) {
+            Ok(path) => path,
+            Err(e) => return syscall_error(e, label, "new path conversion failed"),
+        };
+        AT_FDCWD
+    } else {
+        let wrappedvfd = fdtables::translate_virtual_fd(cageid, newdirfd as u64);
+        if wrappedvfd.is_err() {
+            return syscall_error(Errno::EBADF, label, "Bad newdirfd");
+        }
+        let vfd = wrappedvfd.unwrap();
+        let tmp_cstr = get_cstr(newpath_arg).unwrap();
+        c_newpath = CString::new(tmp_cstr).unwrap();
+        vfd.underfd as i32
+    };
+
+    let ret = unsafe {
+        if use_renameat2 {
+            libc::syscall(
+                libc::SYS_renameat2,
+                old_kernel_fd,
+                c_oldpath.as_ptr(),
+                new_kernel_fd,
+                c_newpath.as_ptr(),
+                flags,
+            ) as libc::c_int
+        } else {
+            libc::renameat(
+                old_kernel_fd,
+                c_oldpath.as_ptr(),
+                new_kernel_fd,
+                c_newpath.as_ptr(),
+            )
+        }
+    };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, label);
+    }
+    ret
+}
+
+pub extern "C" fn renameat_syscall(
+    cageid: u64,
+    olddirfd_arg: u64,
+    olddirfd_cageid: u64,
+    oldpath_arg: u64,
+    oldpath_cageid: u64,
+    newdirfd_arg: u64,
+    newdirfd_cageid: u64,
+    newpath_arg: u64,
+    newpath_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let olddirfd = sc_convert_sysarg_to_i32(olddirfd_arg, olddirfd_cageid, cageid);
+    let newdirfd = sc_convert_sysarg_to_i32(newdirfd_arg, newdirfd_cageid, cageid);
+
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "renameat_syscall"
+        );
+    }
+
+    renameat_inner(
+        cageid,
+        olddirfd,
+        oldpath_arg,
+        oldpath_cageid,
+        newdirfd,
+        newpath_arg,
+        newpath_cageid,
+        0,
+        false,
+        "renameat",
+    )
+}
+
+pub extern "C" fn renameat2_syscall(
+    cageid: u64,
+    olddirfd_arg: u64,
+    olddirfd_cageid: u64,
+    oldpath_arg: u64,
+    oldpath_cageid: u64,
+    newdirfd_arg: u64,
+    newdirfd_cageid: u64,
+    newpath_arg: u64,
+    newpath_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let olddirfd = sc_convert_sysarg_to_i32(olddirfd_arg, olddirfd_cageid, cageid);
+    let newdirfd = sc_convert_sysarg_to_i32(newdirfd_arg, newdirfd_cageid, cageid);
+    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_cageid, cageid);
+
+    if !sc_unusedarg(arg6, arg6_cageid) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "renameat2_syscall"
+        );
+    }
+
+    renameat_inner(
+        cageid,
+        olddirfd,
+        oldpath_arg,
+        oldpath_cageid,
+        newdirfd,
+        newpath_arg,
+        newpath_cageid,
+        flags,
+        true,
+        "renameat2",
+    )
+}
+
+//------------------------------------CHOWN / LCHOWN / FCHOWNAT SYSCALLS------------------------------------
+/// `fchownat` changes the ownership of a file relative to a directory fd.
+/// Linux's `chown(path, uid, gid)` is implemented as `fchownat(AT_FDCWD, path, uid, gid, 0)`,
+/// and `lchown` as `fchownat(AT_FDCWD, path, uid, gid, AT_SYMLINK_NOFOLLOW)` — but glibc may
+/// route them via dedicated syscalls, so we expose all three.
+pub extern "C" fn fchownat_syscall(
+    cageid: u64,
+    dirfd_arg: u64,
+    dirfd_cageid: u64,
+    pathname_arg: u64,
+    pathname_cageid: u64,
+    owner_arg: u64,
+    owner_cageid: u64,
+    group_arg: u64,
+    group_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
+    let owner = sc_convert_sysarg_to_u32(owner_arg, owner_cageid, cageid);
+    let group = sc_convert_sysarg_to_u32(group_arg, group_cageid, cageid);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+
+    if !sc_unusedarg(arg6, arg6_cageid) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "fchownat_syscall"
+        );
+    }
+
+    let c_path;
+    let kernel_fd = if dirfd == AT_FDCWD {
+        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
+            Ok(path) => path,
+            Err(e) => return syscall_error(e, "fchownat", "path conversion failed"),
+        };
+        AT_FDCWD
+    } else {
+        let wrappedvfd = fdtables::translate_virtual_fd(cageid, dirfd as u64);
+        if wrappedvfd.is_err() {
+            return syscall_error(Errno::EBADF, "fchownat", "Bad File Descriptor");
+        }
+        let vfd = wrappedvfd.unwrap();
+        let tmp_cstr = get_cstr(pathname_arg).unwrap();
+        c_path = CString::new(tmp_cstr).unwrap();
+        vfd.underfd as i32
+    };
+
+    let ret = unsafe { libc::fchownat(kernel_fd, c_path.as_ptr(), owner, group, flags) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "fchownat");
+    }
+    ret
+}
+
+pub extern "C" fn chown_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    owner_arg: u64,
+    owner_cageid: u64,
+    group_arg: u64,
+    group_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "chown", "path conversion failed"),
+    };
+    let owner = sc_convert_sysarg_to_u32(owner_arg, owner_cageid, cageid);
+    let group = sc_convert_sysarg_to_u32(group_arg, group_cageid, cageid);
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "chown_syscall"
+        );
+    }
+
+    let ret = unsafe { libc::chown(path.as_ptr(), owner, group) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "chown");
+    }
+    ret
+}
+
+pub extern "C" fn lchown_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    owner_arg: u64,
+    owner_cageid: u64,
+    group_arg: u64,
+    group_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "lchown", "path conversion failed"),
+    };
+    let owner = sc_convert_sysarg_to_u32(owner_arg, owner_cageid, cageid);
+    let group = sc_convert_sysarg_to_u32(group_arg, group_cageid, cageid);
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "lchown_syscall"
+        );
+    }
+
+    let ret = unsafe { libc::lchown(path.as_ptr(), owner, group) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "lchown");
+    }
+    ret
+}
+
+//------------------------------------ACCESS SYSCALL------------------------------------
+/// `access` checks whether the calling process can access the file pathname.
+/// Reference: https://man7.org/linux/man-pages/man2/access.2.html
+/// ## Arguments:
+///  - `pathname`: Path to the file to check accessibility.
+///  - `mode`: Accessibility check mode (F_OK, R_OK, W_OK, X_OK or combinations).
+/// ## Implementation Details:
+///  - The path is converted from the RawPOSIX perspective to the host kernel perspective
+///    using `sc_convert_path_to_host`, which handles path normalization relative to the cage's CWD.
+///  - The mode parameter is passed directly to the underlying libc::access() call.
+/// ## Return Value:
+///  - `0` on success (file is accessible in the requested mode).
+///  - `-1` on failure, with `errno` set appropriately.
+pub extern "C" fn access_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    amode_arg: u64,
+    amode_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // Type conversion
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "access", "path conversion failed"),
+    };
+    let amode = sc_convert_sysarg_to_i32(amode_arg, amode_cageid, cageid);
+
+    // Validate unused args
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "access_syscall"
+        );
+    }
+
+    let ret = unsafe { libc::access(path.as_ptr(), amode) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "access");
+    }
+    ret
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/clock_gettime.2.html
+///
+/// `clock_gettime_syscall` retrieves the time of the specified clock and
+/// stores it in a user-provided `timespec` structure.  
+///
+/// ## Implementation Details:
+/// - The `clockid` argument is converted from the Cage's virtual argument
+///   into a host `u32`.
+/// - The `tp` pointer (destination for the `timespec` result) is translated
+///   from Wasm linear memory into a host address via `sc_convert_addr_to_host`.
+/// - Unused arguments `arg3`–`arg6` are validated with `sc_unusedarg`.
+/// - The underlying `SYS_clock_gettime` syscall is invoked directly with the
+///   converted arguments.
+/// - On error, `errno` is retrieved with `get_errno()` and normalized through
+///   `handle_errno()`.
+///
+/// ## Arguments:
+/// - `cageid`: Identifier of the calling Cage
+/// - `clockid_arg`: The clock to be queried (e.g., `CLOCK_REALTIME`)
+/// - `tp_arg`: Address of the user buffer for the result `timespec`
+/// - `arg3`–`arg6`: Reserved, must be unused
+///
+/// ## Returns:
+///     - 0 on success.
+///     - -1 on failure, with errno set appropriately.
+pub extern "C" fn clock_gettime_syscall(
+    cageid: u64,
+    clockid_arg: u64,
+    clockid_cageid: u64,
+    tp_arg: u64,
+    _tp_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let clockid = sc_convert_sysarg_to_u32(clockid_arg, clockid_cageid, cageid);
+    let tp = tp_arg as *mut u8;
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "clock_gettime_syscall"
+        );
+    }
+
+    let ret = unsafe { syscall(SYS_clock_gettime, clockid, tp) as i32 };
+
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "clock_gettime");
+    }
+
+    ret
+}
+
+/// Linux Reference: https://man7.org/linux/man-pages/man2/dup.2.html
+///
+/// Since the two file descriptors refer to the same open file description, they share file offset
+/// and file status flags. Then, in RawPOSIX, we mapped duplicated file descriptor to same underlying
+/// kernel fd.
+///
+/// ## Arguments:
+/// - `virtual_fd`: virtual file descriptor
+///
+/// ## Returns:
+///     - On success, the new file descriptor is returned.
+///     - On error, -1 is returned and errno is set to indicate the error.
+pub extern "C" fn dup_syscall(
+    cageid: u64,
+    vfd_arg: u64,
+    _vfd_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "dup_syscall"
+        );
+    }
+
+    let wrappedvfd = fdtables::translate_virtual_fd(cageid, vfd_arg as u64);
+    if wrappedvfd.is_err() {
+        return syscall_error(Errno::EBADF, "dup", "Bad File Descriptor");
+    }
+    let vfd = wrappedvfd.unwrap();
+    // Only kernel fds get a host-level duplicate; other kinds are identified by their underfd,
+    // so the copy shares it
+    if vfd.fdkind != FDKIND_KERNEL {
+        return match fdtables::get_unused_virtual_fd(
+            cageid,
+            vfd.fdkind,
+            vfd.underfd,
+            false,
+            vfd.perfdinfo,
+        ) {
+            Ok(ret_vfd) => ret_vfd as i32,
+            Err(e) => handle_errno(e as i32, "dup"),
+        };
+    }
+    let ret_kernelfd = unsafe { libc::dup(vfd.underfd as i32) };
+    if ret_kernelfd < 0 {
+        return handle_errno(get_errno(), "dup");
+    }
+    match fdtables::get_unused_virtual_fd(cageid, vfd.fdkind, ret_kernelfd as u64, false, 0) {
+        Ok(ret_vfd) => ret_vfd as i32,
+        Err(e) => {
+            unsafe { libc::close(ret_kernelfd) };
+            handle_errno(e as i32, "dup")
+        }
+    }
+}
+
+/// dup2() performs the same task as dup(), so we utilize dup() here and mapping underlying kernel
+/// fd with specific `new_virutalfd`
+///
+/// ## Arguments:
+/// - `old_virtualfd`: original virtual file descriptor
+/// - `new_virtualfd`: specified new virtual file descriptor
+///
+/// ## Returns:
+///     - On success, returns the new file descriptor.
+///     - On error, -1 is returned and errno is set to indicate the error.
+pub extern "C" fn dup2_syscall(
+    cageid: u64,
+    old_vfd_arg: u64,
+    _old_vfd_cageid: u64,
+    new_vfd_arg: u64,
+    _new_vfd_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "dup2_syscall"
+        );
+    }
+
+    // Validate both virtual fds.  newfd must also be below the cage's
+    // RLIMIT_NOFILE soft limit.
+    if old_vfd_arg >= fdtables::FD_PER_PROCESS_HARD_MAX
+        || new_vfd_arg >= fdtables::get_fd_limits(cageid).0
+    {
+        return syscall_error(Errno::EBADF, "dup2", "Bad File Descriptor");
+    } else if old_vfd_arg == new_vfd_arg {
+        // Does nothing
+        return new_vfd_arg as i32;
+    }
+
+    // If the file descriptor newfd was previously open, it is closed before being reused; the
+    // close is performed silently (i.e., any errors during the close are not reported by dup2()).
+    // This step is handled inside `fdtables`
+    match fdtables::translate_virtual_fd(cageid, old_vfd_arg) {
+        Ok(old_vfd) => {
+            // Request another virtual fd to refer to same underlying kernel fd as `virtual_fd`
+            // from input.
+            // The two file descriptors do not share file descriptor flags (the
+            // close-on-exec flag).  The close-on-exec flag (FD_CLOEXEC; see fcntl_syscall())
+            // for the duplicate descriptor is off
+            // This can still fail with ENFILE if newfd is not already open.
+            if let Err(e) = fdtables::get_specific_virtual_fd(
+                cageid,
+                new_vfd_arg,
+                old_vfd.fdkind,
+                old_vfd.underfd,
+                false,
+                old_vfd.perfdinfo,
+            ) {
+                return handle_errno(e as i32, "dup2");
+            }
+
+            return new_vfd_arg as i32;
+        }
+        Err(_e) => {
+            return syscall_error(Errno::EBADF, "dup2", "Bad File Descriptor");
+        }
+    }
+}
+
+/// dup3() duplicates `old_virtualfd` to `new_virtualfd`, similar to dup2(),
+/// but requires the two descriptors to differ and allows setting FD_CLOEXEC via `flags`.
+/// It first calls `dup2_syscall` to copy the file descriptor, then sets the close-on-exec flag if requested.
+
+/// ## Arguments:
+/// - `old_virtualfd`: source virtual file descriptor
+/// - `new_virtualfd`: target virtual file descriptor
+/// - `flags`: must be 0 or O_CLOEXEC
+///
+/// ## Returns:
+///     - On success, returns the new file descriptor.
+///     - On error, -1 is returned and errno is set to indicate the error (EBADF or EINVAL).
+pub extern "C" fn dup3_syscall(
+    cageid: u64,
+    old_vfd_arg: u64,
+    old_vfd_cageid: u64,
+    new_vfd_arg: u64,
+    new_vfd_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "dup3_syscall"
+        );
+    }
+
+    if old_vfd_arg >= fdtables::FD_PER_PROCESS_HARD_MAX
+        || new_vfd_arg >= fdtables::get_fd_limits(cageid).0
+    {
+        return syscall_error(Errno::EBADF, "dup3", "Bad File Descriptor");
+    }
+
+    if old_vfd_arg == new_vfd_arg {
+        return syscall_error(Errno::EINVAL, "dup3", "oldfd and newfd must be different");
+    }
+
+    if flags != 0 && flags != O_CLOEXEC {
+        return syscall_error(Errno::EINVAL, "dup3", "Invalid flags");
+    }
+
+    let ret = dup2_syscall(
+        cageid,
+        old_vfd_arg,
+        old_vfd_cageid,
+        new_vfd_arg,
+        new_vfd_cageid,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+    );
+    if ret < 0 {
+        return ret;
+    }
+
+    if flags == O_CLOEXEC {
+        let _ = fdtables::set_cloexec(cageid, new_vfd_arg, true);
+    }
+
+    return new_vfd_arg as i32;
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/fchdir.2.html
+///
+/// Linux `fchdir()` syscall changes the current working directory of the calling process to the
+/// directory referred to by the open file descriptor `fd`. Since we implement a file descriptor
+/// management subsystem (called `fdtables`), we first translate the virtual file descriptor to the
+/// corresponding kernel file descriptor before invoking the kernel's `libc::fchdir()` function.
+///
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment referring to a directory
+///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+///
+/// ## Returns:
+///     - 0 on success.
+///     - -1 on error, with errno set to indicate the error.
+pub extern "C" fn fchdir_syscall(
+    cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "fchdir");
+    }
+
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "fchdir_syscall"
+        );
+    }
+
+    let ret = unsafe { libc::fchdir(kernel_fd) };
+    if ret < 0 {
+        return handle_errno(get_errno(), "fchdir");
+    }
+
+    // Update the cage's current working directory
+    // We need to get the current working directory from the kernel to update the cage
+    let mut cwd_buf = [0u8; PATH_MAX as usize];
+    let cwd_ptr = unsafe { libc::getcwd(cwd_buf.as_mut_ptr() as *mut i8, cwd_buf.len()) };
+    if !cwd_ptr.is_null() {
+        if let Some(cage) = get_cage(cageid) {
+            let host_path = unsafe { std::ffi::CStr::from_ptr(cwd_ptr) }.to_string_lossy();
+            let user_path = PathBuf::from(host_path.as_ref());
+            let mut cwd = cage.cwd.write();
+            *cwd = Arc::new(user_path);
+        }
+    }
+
+    ret
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/writev.2.html
+///
+/// Linux `writev()` syscall performs scatter-gather output by writing data from multiple buffers
+/// to a file descriptor in a single operation. Since we implement a file descriptor management
+/// subsystem (called `fdtables`), we first translate the virtual file descriptor to the corresponding
+/// kernel file descriptor, then translate the iovec array pointer from cage virtual memory to host
+/// memory before invoking the kernel's `libc::writev()` function.
+///
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
+///     - iov_arg: pointer to an array of iovec structures describing the buffers (user's perspective)
+///     - iovcnt_arg: number of iovec structures in the array
+///     - arg4, arg5, arg6: additional arguments which are expected to be unused
+///
+/// ## Returns:
+///     - On success, the number of bytes written is returned.
+///     - On error, -1 is returned and errno is set to indicate the error.
+pub extern "C" fn writev_syscall(
     cageid: u64,
-    oldpath_arg: u64,
-    oldpath_cageid: u64,
-    newpath_arg: u64,
-    newpath_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    iov_arg: u64,
+    iov_cageid: u64,
+    iovcnt_arg: u64,
+    iovcnt_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -1590,62 +4502,78 @@ pub extern "C" fn link_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let oldpath = match sc_convert_path_to_host(oldpath_arg, oldpath_cageid, cageid) {
-        Ok(oldpath) => oldpath,
-        Err(e) => return syscall_error(e, "link", "path conversion failed"),
-    };
-    let newpath = match sc_convert_path_to_host(newpath_arg, newpath_cageid, cageid) {
-        Ok(newpath) => newpath,
-        Err(e) => return syscall_error(e, "link", "path conversion failed"),
-    };
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "writev");
+    }
 
-    // Validate unused args
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
+    let iovcnt = sc_convert_sysarg_to_i32(iovcnt_arg, iovcnt_cageid, cageid);
+    let iov_ptr = sc_convert_buf(iov_arg, iov_cageid, cageid);
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "link_syscall"
+            "writev"
         );
     }
 
-    let ret = unsafe { libc::link(oldpath.as_ptr(), newpath.as_ptr()) };
+    if let Some(sock) = inproc_socket::lookup(vfd_cageid, vfd_arg) {
+        if iovcnt < 0 {
+            return syscall_error(Errno::EINVAL, "writev", "Invalid iovcnt");
+        }
+        let iov =
+            unsafe { std::slice::from_raw_parts(iov_ptr as *const libc::iovec, iovcnt as usize) };
+        return match sock.send(cageid, iov, 0) {
+            Ok(n) => n as i32,
+            Err(e) => syscall_error(e, "writev", "in-process socket write failed"),
+        };
+    }
+
+    if mqueue::lookup(vfd_cageid, vfd_arg).is_some() {
+        return syscall_error(
+            Errno::EINVAL,
+            "writev",
+            "message queues are written with mq_send",
+        );
+    }
 
+    let ret = unsafe { libc::writev(kernel_fd, iov_ptr as *const libc::iovec, iovcnt) as i32 };
     if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "link");
+        return handle_errno(get_errno(), "writev");
     }
     ret
 }
 
-//------------------------------------XSTAT SYSCALL------------------------------------
-/// `xstat` retrieves file status information (versioned stat interface).
-/// Reference: https://man7.org/linux/man-pages/man2/stat.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/readv.2.html
 ///
-/// ## Arguments:
-///  - `vers`: Version parameter for stat structure compatibility.
-///  - `pathname`: Path to the file to get status information for.
-///  - `statbuf`: Buffer to store the file status information.
+/// Linux `readv()` syscall performs scatter input by reading data from a file descriptor
+/// into multiple buffers in a single operation. Since we implement a file descriptor management
+/// subsystem (called `fdtables`), we first translate the virtual file descriptor to the corresponding
+/// kernel file descriptor, then translate the iovec array pointer from cage virtual memory to host
+/// memory before invoking the kernel's `libc::readv()` function.
 ///
-/// ## Implementation Details:
-///  - The path is converted from the RawPOSIX perspective to the host kernel perspective
-///    using `sc_convert_path_to_host`, which handles path normalization relative to the cage's CWD.
-///  - The underlying libc::stat() is called and results are copied to the user buffer.
+/// ## Input:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
+///     - iov_arg: pointer to an array of iovec structures describing the buffers
+///     - iovcnt_arg: number of iovec structures in the array
+///     - arg4, arg5, arg6: additional arguments which are expected to be unused
 ///
-/// ## Return Value:
-///  - `0` on success.
-///  - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn stat_syscall(
+/// ## Returns:
+///     - On success, the number of bytes read is returned.
+///     - On error, -1 is returned and errno is set to indicate the error.
+pub extern "C" fn readv_syscall(
     cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    statbuf_arg: u64,
-    statbuf_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    iov_arg: u64,
+    iov_cageid: u64,
+    iovcnt_arg: u64,
+    iovcnt_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -1653,200 +4581,190 @@ pub extern "C" fn stat_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "stat", "path conversion failed"),
-    };
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "readv");
+    }
 
-    // Validate unused args
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
+    let iovcnt = sc_convert_sysarg_to_i32(iovcnt_arg, iovcnt_cageid, cageid);
+    let iov_ptr = sc_convert_buf(iov_arg, iov_cageid, cageid);
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "stat_syscall"
+            "readv"
         );
     }
 
-    // Declare statbuf by ourselves
-    let mut libc_statbuf: stat = unsafe { std::mem::zeroed() };
-    let libcret = unsafe { libc::stat(path.as_ptr(), &mut libc_statbuf) };
-
-    if libcret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "xstat");
+    if let Some(sock) = inproc_socket::lookup(vfd_cageid, vfd_arg) {
+        if iovcnt < 0 {
+            return syscall_error(Errno::EINVAL, "readv", "Invalid iovcnt");
+        }
+        let iov =
+            unsafe { std::slice::from_raw_parts(iov_ptr as *const libc::iovec, iovcnt as usize) };
+        return match sock.recv(cageid, iov, 0) {
+            Ok(n) => n as i32,
+            Err(e) => syscall_error(e, "readv", "in-process socket read failed"),
+        };
     }
 
-    // Convert libc stat to StatData and copy to user buffer
-    match sc_convert_addr_to_statdata(statbuf_arg, statbuf_cageid, cageid) {
-        Ok(statbuf_addr) => convert_statdata_to_user(statbuf_addr, libc_statbuf),
-        Err(e) => return syscall_error(e, "xstat", "Bad address"),
+    if let Some(desc) = mqueue::lookup(vfd_cageid, vfd_arg) {
+        if iovcnt < 0 {
+            return syscall_error(Errno::EINVAL, "readv", "Invalid iovcnt");
+        }
+        let iov =
+            unsafe { std::slice::from_raw_parts(iov_ptr as *const libc::iovec, iovcnt as usize) };
+        let mut total = 0;
+        for v in iov {
+            let buf = unsafe { std::slice::from_raw_parts_mut(v.iov_base as *mut u8, v.iov_len) };
+            let n = desc.read_status(buf);
+            total += n;
+            if n < v.iov_len {
+                break;
+            }
+        }
+        return total as i32;
     }
 
-    libcret
+    let ret = unsafe { libc::readv(kernel_fd, iov_ptr as *const libc::iovec, iovcnt) as i32 };
+    if ret < 0 {
+        return handle_errno(get_errno(), "readv");
+    }
+    ret
 }
 
-//------------------------------------LSTAT SYSCALL------------------------------------
-/// `lstat` retrieves file status information without following symlinks.
-/// Reference: https://man7.org/linux/man-pages/man2/lstat.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/preadv.2.html
 ///
-/// ## Arguments:
-///  - `pathname`: Path to the file or symlink to get status information for.
-///  - `statbuf`: Buffer to store the file status information.
+/// `preadv()` combines the functionality of `readv()` and `pread()`: it performs
+/// scatter input from the file at the given offset without changing the file pointer.
 ///
-/// ## Implementation Details:
-///  - Identical to `stat_syscall`, except `libc::lstat` is used instead of `libc::stat`,
-///    so symlinks are stat'd directly rather than following to the target.
+/// ## Arguments:
+///     - vfd_arg: virtual file descriptor
+///     - iov_arg: pointer to an array of iovec structures
+///     - iovcnt_arg: number of iovec structures
+///     - offset_arg: file offset to read from
 ///
-/// ## Return Value:
-///  - `0` on success.
-///  - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn lstat_syscall(
+/// ## Returns:
+///     - On success, the number of bytes read.
+///     - On error, -1 with errno set.
+pub extern "C" fn preadv_syscall(
     cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    statbuf_arg: u64,
-    statbuf_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    iov_arg: u64,
+    iov_cageid: u64,
+    iovcnt_arg: u64,
+    iovcnt_cageid: u64,
+    offset_arg: u64,
+    offset_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "lstat", "path conversion failed"),
-    };
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "preadv");
+    }
 
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
+    let iovcnt = sc_convert_sysarg_to_i32(iovcnt_arg, iovcnt_cageid, cageid);
+    let iov_ptr = sc_convert_buf(iov_arg, iov_cageid, cageid);
+    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
+
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "lstat_syscall"
+            "preadv_syscall"
         );
     }
 
-    let mut libc_statbuf: stat = unsafe { std::mem::zeroed() };
-    let libcret = unsafe { libc::lstat(path.as_ptr(), &mut libc_statbuf) }; // <-- only change
-
-    if libcret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "lstat");
-    }
-
-    match sc_convert_addr_to_statdata(statbuf_arg, statbuf_cageid, cageid) {
-        Ok(statbuf_addr) => convert_statdata_to_user(statbuf_addr, libc_statbuf),
-        Err(e) => return syscall_error(e, "lstat", "Bad address"),
+    let ret =
+        unsafe { libc::preadv(kernel_fd, iov_ptr as *const libc::iovec, iovcnt, offset) as i32 };
+    if ret < 0 {
+        return handle_errno(get_errno(), "preadv");
     }
-
-    libcret
+    ret
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/statfs.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/pwritev.2.html
 ///
-/// Linux `statfs()` syscall returns information about a mounted filesystem
-/// that contains the file or directory specified by `path`.  
-/// In RawPOSIX, because each Cage has its own virtualized filesystem view,
-/// the path is first translated from the Cage's namespace into the host
-/// kernel namespace using `sc_convert_path_to_host`.  
-/// After translation, the kernel's `libc::statfs()` is invoked to obtain
-/// the filesystem information. The resulting `statfs` structure is then
-/// converted into our ABI-stable `FStatData` format and copied into the
-/// user-provided buffer in Cage memory.
+/// `pwritev()` combines the functionality of `writev()` and `pwrite()`: it performs
+/// gather output to the file at the given offset without changing the file pointer.
 ///
-/// ## Input:
-/// - `cageid`: Identifier of the current Cage
-/// - `path_arg`: Wasm address of the pathname string
-/// - `path_cageid`: Cage ID associated with `path_arg`
-/// - `statbuf_arg`: Wasm address of the buffer where results will be stored
-/// - `statbuf_cageid`: Cage ID associated with `statbuf_arg`
-/// - `arg3`–`arg6`: Unused arguments (validated for security)
+/// ## Arguments:
+///     - vfd_arg: virtual file descriptor
+///     - iov_arg: pointer to an array of iovec structures
+///     - iovcnt_arg: number of iovec structures
+///     - offset_arg: file offset to write at
 ///
-/// ## Return Value:
-/// - `0` on success  
-/// - `-1` on failure, with `errno` set appropriately
-pub extern "C" fn statfs_syscall(
-    cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    statbuf_arg: u64,
-    statbuf_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
+/// ## Returns:
+///     - On success, the number of bytes written.
+///     - On error, -1 with errno set.
+pub extern "C" fn pwritev_syscall(
+    cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    iov_arg: u64,
+    iov_cageid: u64,
+    iovcnt_arg: u64,
+    iovcnt_cageid: u64,
+    offset_arg: u64,
+    offset_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "statfs", "path conversion failed"),
-    };
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "pwritev");
+    }
 
-    // Validate unused args
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
+    let iovcnt = sc_convert_sysarg_to_i32(iovcnt_arg, iovcnt_cageid, cageid);
+    let iov_ptr = sc_convert_buf(iov_arg, iov_cageid, cageid);
+    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
+
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "statfs_syscall"
+            "pwritev_syscall"
         );
     }
 
-    // Call host statfs into a local host-side buffer first. Do not let host libc
-    // write directly into guest memory, since host libc::statfs layout may differ
-    // from the wasm32 statfs layout.
-    let mut host_statfs: libc::statfs = unsafe { std::mem::zeroed() };
-    let ret = unsafe { libc::statfs(path.as_ptr(), &mut host_statfs) };
-
+    let ret =
+        unsafe { libc::pwritev(kernel_fd, iov_ptr as *const libc::iovec, iovcnt, offset) as i32 };
     if ret < 0 {
-        return handle_errno(get_errno(), "statfs");
-    }
-
-    match sc_convert_addr_to_fstatdata(statbuf_arg, statbuf_cageid, cageid) {
-        Ok(statbuf_addr) => {
-            convert_fstatdata_to_user(statbuf_addr, host_statfs);
-            ret
-        }
-        Err(e) => syscall_error(e, "statfs", "Bad address"),
+        return handle_errno(get_errno(), "pwritev");
     }
+    ret
 }
 
-//------------------------------------FSYNC SYSCALL------------------------------------
-/// `fsync` synchronizes a file's in-core state with storage device.
-/// Reference: https://man7.org/linux/man-pages/man2/fsync.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/fstat.2.html
 ///
-/// ## Arguments:
-///  - `fd`: File descriptor to synchronize.
+/// Linux `fstat()` syscall retrieves information about the file referred to by the open file descriptor `fd`.
+/// Since we implement a file descriptor management subsystem (called `fdtables`), we first translate the virtual
+/// file descriptor to the corresponding kernel file descriptor, then call the kernel's `libc::fstat()` function.
+/// The returned stat structure is converted to our ABI-stable StatData format and copied to the user's buffer.
 ///
-/// ## Implementation Details:
-///  - The virtual file descriptor is converted to a kernel file descriptor using `convert_fd_to_host`.
-///  - This ensures proper translation between RawPOSIX virtual fds and host kernel fds.
-///  - The underlying libc::fsync() is called, which synchronizes both file data and metadata.
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
+///     - stat_arg: pointer to a stat structure where the file information will be stored (user's perspective)
+///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
 ///
-/// ## Return Value:
-///  - `0` on success.
-///  - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn fsync_syscall(
+/// ## Returns:
+///     - 0 on success.
+///     - -1 on error, with errno set to indicate the error.
+pub extern "C" fn fstat_syscall(
     cageid: u64,
-    fd_arg: u64,
-    fd_cageid: u64,
-    arg2: u64,
-    arg2_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    statbuf_arg: u64,
+    statbuf_cageid: u64,
     arg3: u64,
     arg3_cageid: u64,
     arg4: u64,
@@ -1856,59 +4774,71 @@ pub extern "C" fn fsync_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let virtual_fd = sc_convert_sysarg_to_i32(fd_arg, fd_cageid, cageid);
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "fstat");
+    }
 
-    // Validate unused args
-    if !(sc_unusedarg(arg2, arg2_cageid)
-        && sc_unusedarg(arg3, arg3_cageid)
+    if !(sc_unusedarg(arg3, arg3_cageid)
         && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "fsync_syscall"
+            "fstat_syscall"
         );
     }
 
-    let kernel_fd = convert_fd_to_host(virtual_fd as u64, fd_cageid, cageid);
-    // convert_fd_to_host returns negative errno values on error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "read");
+    // Cast directly to libc::stat and write kernel data into buffer.
+    let mut host_stat: libc::stat = unsafe { std::mem::zeroed() };
+    let ret = unsafe { libc::fstat(kernel_fd, &mut host_stat as *mut libc::stat) };
+    if ret < 0 {
+        return handle_errno(get_errno(), "fstat");
+    }
+    // The host fd behind an in-process socket is its readiness eventfd
+    if inproc_socket::lookup(vfd_cageid, vfd_arg).is_some() {
+        host_stat.st_mode = libc::S_IFSOCK | 0o777;
+    }
+    // and the one behind a message queue descriptor stands in for its file in /dev/mqueue
+    if let Some(desc) = mqueue::lookup(vfd_cageid, vfd_arg) {
+        host_stat.st_mode = libc::S_IFREG | desc.mode();
     }
 
-    let ret = unsafe { libc::fsync(kernel_fd) };
-
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "fsync");
+    // Validate guest buffer range and writability
+    match sc_convert_addr_to_statdata(statbuf_arg, statbuf_cageid, cageid) {
+        // 3) Populate StatData directly
+        Ok(statbuf_addr) => convert_statdata_to_user(statbuf_addr, host_stat),
+        Err(e) => return syscall_error(e, "fstat", "Bad address"),
     }
-    return ret;
+
+    ret
 }
 
-//------------------------------------FDATASYNC SYSCALL------------------------------------
-/// `fdatasync` synchronizes a file's data to storage device (but not metadata).
-/// Reference: https://man7.org/linux/man-pages/man2/fdatasync.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/ftruncate.2.html
 ///
-/// ## Arguments:
-///  - `fd`: File descriptor to synchronize.
+/// Linux `ftruncate()` syscall truncates the file referred to by the file descriptor `fd` to be at most
+/// `length` bytes in size. Since we implement a file descriptor management subsystem (called `fdtables`),
+/// we first translate the virtual file descriptor to the corresponding kernel file descriptor, then convert
+/// the length argument from u64 to i64 type before invoking the kernel's `libc::ftruncate()` function.
 ///
-/// ## Implementation Details:
-///  - The virtual file descriptor is converted to a kernel file descriptor using `convert_fd_to_host`.
-///  - This ensures proper translation between RawPOSIX virtual fds and host kernel fds.
-///  - The underlying libc::fdatasync() is called, which synchronizes only file data (not metadata
-///    like timestamps), making it potentially faster than fsync().
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
+///     - length_arg: the desired length in bytes for the file truncation
+///     - arg4, arg5, arg6: additional arguments which are expected to be unused
 ///
-/// ## Return Value:
-///  - `0` on success.
-///  - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn fdatasync_syscall(
+/// ## Returns:
+///     - 0 on success.
+///     - -1 on error, with errno set to indicate the error.
+pub extern "C" fn ftruncate_syscall(
     cageid: u64,
-    fd_arg: u64,
-    fd_cageid: u64,
-    arg2: u64,
-    arg2_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    length_arg: u64,
+    length_cageid: u64,
     arg3: u64,
     arg3_cageid: u64,
     arg4: u64,
@@ -1918,138 +4848,126 @@ pub extern "C" fn fdatasync_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let virtual_fd = sc_convert_sysarg_to_i32(fd_arg, fd_cageid, cageid);
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "ftruncate");
+    }
 
-    // Validate unused args
-    if !(sc_unusedarg(arg2, arg2_cageid)
-        && sc_unusedarg(arg3, arg3_cageid)
+    let length = sc_convert_sysarg_to_i64(length_arg, length_cageid, cageid);
+
+    if !(sc_unusedarg(arg3, arg3_cageid)
         && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "fdatasync_syscall"
+            "ftruncate_syscall"
         );
     }
 
-    let kernel_fd = convert_fd_to_host(virtual_fd as u64, fd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "read");
-    }
-
-    let ret = unsafe { libc::fdatasync(kernel_fd) };
-
+    let ret = unsafe { libc::ftruncate(kernel_fd, length) };
     if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "fdatasync");
+        return handle_errno(get_errno(), "ftruncate");
     }
-    return ret;
+    ret
 }
 
-//------------------------------------SYNC_FILE_RANGE SYSCALL------------------------------------
-/// `sync_file_range` synchronizes a specific range of bytes in a file to storage device.
-/// Reference: https://man7.org/linux/man-pages/man2/sync_file_range.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/fstatfs.2.html
 ///
-/// ## Arguments:
-///  - `fd`: File descriptor to synchronize.
-///  - `offset`: Starting byte offset for the range to sync.
-///  - `nbytes`: Number of bytes to synchronize.
-///  - `flags`: Flags controlling the synchronization behavior.
+/// Linux `fstatfs()` syscall returns information about a mounted filesystem referred to by the open file
+/// descriptor `fd`. Since we implement a file descriptor management subsystem (called `fdtables`), we first
+/// translate the virtual file descriptor to the corresponding kernel file descriptor, then call the kernel's
+/// `libc::fstatfs()` function. The returned statfs structure is converted to our ABI-stable FSData format
+/// and copied to the user's buffer.
 ///
-/// ## Implementation Details:
-///  - The virtual file descriptor is converted to a kernel file descriptor using `convert_fd_to_host`.
-///  - This ensures proper translation between RawPOSIX virtual fds and host kernel fds.
-///  - The underlying libc::sync_file_range() is called with the specified byte range and flags.
-///  - This is more efficient than fsync() for large files when only a specific range needs syncing.
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
+///     - statfs_arg: pointer to a statfs structure where the filesystem information will be stored (user's perspective)
+///     - arg4, arg5, arg6: additional arguments which are expected to be unused
 ///
-/// ## Return Value:
-///  - `0` on success.
-///  - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn sync_file_range_syscall(
+/// ## Returns:
+///     - 0 on success.
+///     - -1 on error, with errno set to indicate the error.
+pub extern "C" fn fstatfs_syscall(
     cageid: u64,
-    fd_arg: u64,
-    fd_cageid: u64,
-    offset_arg: u64,
-    offset_cageid: u64,
-    nbytes_arg: u64,
-    nbytes_cageid: u64,
-    flags_arg: u64,
-    flags_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    statfs_arg: u64,
+    statfs_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let virtual_fd = sc_convert_sysarg_to_i32(fd_arg, fd_cageid, cageid);
-    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
-    let nbytes = sc_convert_sysarg_to_i64(nbytes_arg, nbytes_cageid, cageid);
-    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_cageid, cageid);
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "fstatfs");
+    }
 
-    // Validate unused args
-    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "sync_file_range_syscall"
+            "fstatfs_syscall"
         );
     }
 
-    let kernel_fd = convert_fd_to_host(virtual_fd as u64, fd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "read");
+    // 1) Call host fstatfs into a local host variable
+    let mut host_statfs: libc::statfs = unsafe { std::mem::zeroed() };
+    let ret = unsafe { libc::fstatfs(kernel_fd, &mut host_statfs) };
+    if ret < 0 {
+        return handle_errno(get_errno(), "fstatfs");
     }
 
-    let ret = unsafe { libc::sync_file_range(kernel_fd, offset, nbytes, flags) };
-
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "sync_file_range");
+    // 2) Validate guest buffer range and writability
+    match sc_convert_addr_to_fstatdata(statfs_arg, statfs_cageid, cageid) {
+        // 3) Populate StatData directly
+        Ok(statbuf_addr) => convert_fstatdata_to_user(statbuf_addr, host_statfs),
+        Err(e) => return syscall_error(e, "fstatfs", "Bad address"),
     }
+
     ret
 }
 
-//------------------------------------READLINK & READLINKAT SYSCALL------------------------------------
-/// Reference: https://man7.org/linux/man-pages/man2/readlink.2.html
-///
-/// The return value of the readlink syscall indicates the number of bytes written into the buf and -1 if
-/// error. The contents of the buf represent the file path that the symbolic link points to. Since the file
-/// path perspectives differ between the user application and the host Linux, the readlink implementation
-/// requires handling the paths for both the input passed to the Rust kernel libc and the output buffer
-/// returned by the kernel libc.
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/getdents64.2.html
 ///
-/// For the input path, the transformation is straightforward: we normalize it relative to the cage's CWD
-/// to convert the user's relative path into an absolute path within the chroot jail.
-/// For the output buffer, we need to first verify whether the path written to buf is an absolute
-/// path. If it is not, we prepend the current working directory to make it absolute. The result
-/// is then truncated to fit within the user-provided buflen, ensuring compliance with the behavior
-/// described in the Linux readlink man page, which states that truncation is performed silently if
-/// the buffer is too small.
+/// Linux `getdents()` syscall reads several directory entries from the directory referred to by the open file
+/// descriptor `fd` into the buffer pointed to by `dirp`. Since we implement a file descriptor management
+/// subsystem (called `fdtables`), we first translate the virtual file descriptor to the corresponding kernel
+/// file descriptor, then call the kernel's `getdents64` syscall directly to retrieve directory entries.
 ///
 /// ## Input:
-/// - `cageid`: Identifier of the current Cage
-/// - `path_arg`: Address of the symbolic link pathname in Wasm memory
-/// - `path_cageid`: Cage ID associated with `path_arg`
-/// - `buf_arg`: Address of the user buffer to store the link target
-/// - `buf_cageid`: Cage ID associated with `buf_arg`
-/// - `buflen_arg`: Size of the user buffer
-/// - `buflen_cageid`: Cage ID associated with `buflen_arg`
-/// - `arg4`–`arg6`: Unused arguments (validated for security)
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment referring to a directory
+///     - dirp_arg: pointer to a buffer where the directory entries will be stored (user's perspective)
+///     - count_arg: size of the buffer pointed to by dirp
+///     - arg4, arg5, arg6: additional arguments which are expected to be unused
 ///
-/// ## Return:
-/// - On success: number of bytes placed in `buf` (not null-terminated)  
-/// - On failure: `-1`, with `errno` set appropriately
-pub extern "C" fn readlink_syscall(
+/// ## Returns:
+///     - On success, the number of bytes read is returned.
+///     - On end of directory, 0 is returned.
+///     - On error, -1 is returned and errno is set to indicate the error.
+pub extern "C" fn getdents_syscall(
     cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    buf_arg: u64,
-    _buf_cageid: u64,
-    buflen_arg: u64,
-    buflen_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    dirp_arg: u64,
+    dirp_cageid: u64,
+    count_arg: u64,
+    count_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -2057,463 +4975,433 @@ pub extern "C" fn readlink_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "readlink", "path conversion failed"),
-    };
-    let buf = buf_arg as *mut u8;
-    let buflen = sc_convert_sysarg_to_usize(buflen_arg, buflen_cageid, cageid);
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "getdents");
+    }
+
+    let dirp = sc_convert_buf(dirp_arg, dirp_cageid, cageid);
+    let count = sc_convert_sysarg_to_usize(count_arg, count_cageid, cageid);
 
-    // Validate unused args
     if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "readlink_syscall"
+            "getdents_syscall"
         );
     }
 
-    // Call to kernel readlink
-    let bytes_written = unsafe { libc::readlink(path.as_ptr(), buf as *mut libc::c_char, buflen) };
-
-    if bytes_written < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "readlink");
-    }
+    let ret =
+        unsafe { libc::syscall(libc::SYS_getdents64 as libc::c_long, kernel_fd, dirp, count) };
 
-    bytes_written as i32
+    ret as i32
 }
 
-/// `readlinkat` reads the value of a symbolic link relative to a directory file descriptor.
-/// Reference: https://man7.org/linux/man-pages/man2/readlinkat.2.html
-///
-/// ## Arguments:
-///  - `dirfd`: Directory file descriptor. If `AT_FDCWD`, it uses the current working directory.
-///  - `pathname`: Path to the symbolic link (relative to dirfd).
-///  - `buf`: Buffer to store the link target.
-///  - `bufsiz`: Size of the buffer.
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/lseek.2.html
 ///
-/// There are two cases:
-/// Case 1: When `dirfd` is AT_FDCWD:
-///   - The path is converted using `sc_convert_path_to_host` and libc::readlink() is called.
-///   - This uses the current working directory as the base for relative paths.
+/// Linux `lseek()` syscall repositions the file offset of the open file description associated with the file
+/// descriptor `fd` to the argument `offset` according to the directive `whence`. Since we implement a file
+/// descriptor management subsystem (called `fdtables`), we first translate the virtual file descriptor to the
+/// corresponding kernel file descriptor, then convert the offset and whence parameters from cage memory before
+/// invoking the kernel's `libc::lseek()` function.
 ///
-/// Case 2: When `dirfd` is not AT_FDCWD:
-///   - The virtual file descriptor is converted to a kernel file descriptor using `convert_fd_to_host`.
-///   - The path is converted using `sc_convert_path_to_host` and libc::readlinkat() is called.
-///   - This reads the symlink relative to the specified directory.
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
+///     - offset_arg: the new offset according to the directive whence
+///     - whence_arg: how to interpret the offset (SEEK_SET, SEEK_CUR, or SEEK_END)
+///     - arg4, arg5, arg6: additional arguments which are expected to be unused
 ///
-/// ## Return Value:
-///  - Number of bytes placed in `buf` on success.
-///  - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn readlinkat_syscall(
+/// ## Returns:
+///     - On success, the resulting offset location as measured in bytes from the beginning of the file is returned.
+///     - On error, -1 is returned and errno is set to indicate the error.
+pub extern "C" fn lseek_syscall(
     cageid: u64,
-    dirfd_arg: u64,
-    dirfd_cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    buf_arg: u64,
-    buf_cageid: u64,
-    buflen_arg: u64,
-    buflen_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    offset_arg: u64,
+    offset_cageid: u64,
+    whence_arg: u64,
+    whence_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let virtual_fd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "readlinkat", "path conversion failed"),
-    };
-    let buf = sc_convert_to_cchar_mut(buf_arg, buf_cageid, cageid);
-    let buflen = sc_convert_sysarg_to_usize(buflen_arg, buflen_cageid, cageid);
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "lseek");
+    }
 
-    // Validate unused args
-    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
+    let whence = sc_convert_sysarg_to_i32(whence_arg, whence_cageid, cageid);
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "readlinkat_syscall"
+            "lseek_syscall"
         );
     }
 
-    let ret = if virtual_fd == libc::AT_FDCWD {
-        unsafe { libc::readlink(path.as_ptr(), buf, buflen) }
-    } else {
-        // Case 2: Specific directory fd
-        let kernel_fd = convert_fd_to_host(virtual_fd as u64, dirfd_cageid, cageid);
-        // Return error
-        if kernel_fd < 0 {
-            return handle_errno(-kernel_fd, "readlinkat");
-        }
-
-        let raw_path = match get_cstr(path_arg) {
-            Ok(p) => p,
-            Err(_) => {
-                return syscall_error(
-                    Errno::EINVAL,
-                    "readlinkat",
-                    "invalid  
-        path",
-                )
-            }
-        };
-
-        unsafe { libc::readlinkat(kernel_fd, raw_path.as_ptr() as *const c_char, buf, buflen) }
-    };
-
+    let ret = unsafe { libc::lseek(kernel_fd, offset, whence) };
     if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "readlinkat");
+        return handle_errno(get_errno(), "lseek");
     }
 
     ret as i32
 }
 
-//------------------RENAME SYSCALL------------------
-/// `rename` changes the name or location of a file.
-/// Reference: https://man7.org/linux/man-pages/man2/rename.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/pread.2.html
 ///
-/// ## Arguments:
-///  - `oldpath`: Current path of the file.
-///  - `newpath`: New path for the file.
+/// Linux `pread()` syscall reads up to `count` bytes from the file descriptor `fd` at the
+/// given `offset` without changing the file offset. Since we implement a file descriptor management
+/// subsystem (called `fdtables`), we first translate the virtual file descriptor to the corresponding
+/// kernel file descriptor, then convert the buffer and offset from cage memory before invoking the
+/// kernel's `libc::pread()` function.
 ///
-/// ## Implementation Details:
-///  - Both paths are converted from the RawPOSIX perspective to the host kernel perspective
-///    using `sc_convert_path_to_host`, which handles path normalization relative to the cage's CWD.
-///  - The underlying libc::rename() is called with both converted paths.
-///  - This can move files across directories within the same filesystem.
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
+///     - buf_arg: pointer to a buffer where the read data will be stored (user's perspective)
+///     - count_arg: the maximum number of bytes to read from the file descriptor
+///     - offset_arg: file offset at which the input/output operation takes place
+///     - arg5, arg6: additional arguments which are expected to be unused
 ///
-/// ## Return Value:
-///  - `0` on success.
-///  - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn rename_syscall(
+/// ## Returns:
+///     - On success, the number of bytes read is returned (zero indicates end of file).
+///     - On error, -1 is returned and errno is set to indicate the error.
+pub extern "C" fn pread_syscall(
     cageid: u64,
-    oldpath_arg: u64,
-    oldpath_cageid: u64,
-    newpath_arg: u64,
-    newpath_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    buf_arg: u64,
+    buf_cageid: u64,
+    count_arg: u64,
+    count_cageid: u64,
+    offset_arg: u64,
+    offset_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let oldpath = match sc_convert_path_to_host(oldpath_arg, oldpath_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "rename", "path conversion failed"),
-    };
-    let newpath = match sc_convert_path_to_host(newpath_arg, newpath_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "rename", "path conversion failed"),
-    };
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "pread");
+    }
 
-    // Validate unused args
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
+    let buf = sc_convert_buf(buf_arg, buf_cageid, cageid);
+    let count = sc_convert_sysarg_to_usize(count_arg, count_cageid, cageid);
+    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
+
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "rename_syscall"
+            "pread_syscall"
         );
     }
 
-    let ret = unsafe { libc::rename(oldpath.as_ptr(), newpath.as_ptr()) };
-
+    let ret = unsafe { libc::pread(kernel_fd, buf as *mut c_void, count, offset) as i32 };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "rename");
+        return handle_errno(errno, "pread");
     }
     ret
 }
 
-//------------------------------------UNLINK & UNLINAT SYSCALL------------------------------------
-/// `unlink` removes a file from the filesystem.
-/// Reference: https://man7.org/linux/man-pages/man2/unlink.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/pwrite.2.html
 ///
-/// ## Arguments:
-///  - `pathname`: Path to the file to be removed.
+/// Linux `pwrite()` syscall writes up to `count` bytes from the buffer pointed to by `buf` to the file
+/// associated with the open file descriptor, `fd`, starting at the given `offset` without changing the
+/// file offset. Since we implement a file descriptor management subsystem (called `fdtables`), we first
+/// translate the virtual file descriptor to the corresponding kernel file descriptor, then convert the
+/// buffer, count, and offset from cage memory before invoking the kernel's `libc::pwrite()` function.
 ///
-/// ## Implementation Details:
-///  - The path is converted from the RawPOSIX perspective to the host kernel perspective
-///    using `sc_convert_path_to_host`, which handles path normalization relative to the cage's CWD.
-///  - The underlying libc::unlink() is called with the converted path.
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
+///     - buf_arg: pointer to a buffer that stores the data to be written (user's perspective)
+///     - count_arg: the maximum number of bytes to write to the file descriptor
+///     - offset_arg: file offset at which the input/output operation takes place
+///     - arg5, arg6: additional arguments which are expected to be unused
 ///
-/// ## Return Value:
-///  - `0` on success.
-///  - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn unlink_syscall(
+/// ## Returns:
+///     - On success, the number of bytes written is returned.
+///     - On error, -1 is returned and errno is set to indicate the error.
+pub extern "C" fn pwrite_syscall(
     cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    arg2: u64,
-    arg2_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    buf_arg: u64,
+    buf_cageid: u64,
+    count_arg: u64,
+    count_cageid: u64,
+    offset_arg: u64,
+    offset_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "unlink", "path conversion failed"),
-    };
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "pwrite");
+    }
 
-    // would sometimes check, sometimes be a no-op depending on the compiler settings
-    if !(sc_unusedarg(arg2, arg2_cageid)
-        && sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
+    let buf = sc_convert_buf(buf_arg, buf_cageid, cageid);
+    let count = sc_convert_sysarg_to_usize(count_arg, count_cageid, cageid);
+    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
+
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "unlink_syscall"
+            "pwrite_syscall"
         );
     }
 
-    let ret = unsafe { libc::unlink(path.as_ptr()) };
-
+    let ret = unsafe { libc::pwrite(kernel_fd, buf as *const c_void, count, offset) as i32 };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "unlink");
+        return handle_errno(errno, "pwrite");
     }
-
     ret
 }
 
-/// Reference: https://man7.org/linux/man-pages/man2/unlink.2.html
-///
-/// `unlinkat` removes a file or directory relative to a directory file descriptor.
-///
-/// ## Arguments:
-/// - `dirfd`: Directory file descriptor. If `AT_FDCWD`, it uses the current working directory.
-/// - `pathname`: Path of the file/directory to be removed.
-/// - `flags`: Can include `AT_REMOVEDIR` to indicate directory removal.
-///
-/// There are two cases:
-/// Case 1: When `dirfd` is AT_FDCWD:
-/// - RawPOSIX maintains its own notion of the current working directory.
-/// - We convert the provided relative `pathname` (using `convpath` and `normpath`) into an absolute
-///   path relative to the cage's CWD within the chroot jail.
-/// - After this conversion, the path is already absolute from the host’s perspective, so `AT_FDCWD`
-///   doesn't actually rely on the host’s working directory. This avoids mismatches between RawPOSIX
-///   and the host environment.
-///
-/// Case 2: When `dirfd` is not AT_FDCWD:
-/// - We translate the RawPOSIX virtual file descriptor to the corresponding kernel file descriptor.
-/// - In this case, we simply create a C string from the provided `pathname` (without further conversion)
-///   and let the underlying kernel call resolve the path relative to the directory represented by that fd.
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/copy_file_range.2.html
 ///
-/// ## Return Value:
-/// - `0` on success.
-/// - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn unlinkat_syscall(
+/// Linux `copy_file_range()` copies up to `len` bytes from one file descriptor
+/// to another. If `off_in` or `off_out` is NULL, the corresponding file offset
+/// is used and updated by the kernel. If they are non-NULL, they point to file
+/// offsets and the file offsets of the descriptors are not changed.
+pub extern "C" fn copy_file_range_syscall(
     cageid: u64,
-    dirfd_arg: u64,
-    dirfd_cageid: u64,
-    pathname_arg: u64,
-    pathname_cageid: u64,
+    fd_in_arg: u64,
+    fd_in_cageid: u64,
+    off_in_arg: u64,
+    off_in_cageid: u64,
+    fd_out_arg: u64,
+    fd_out_cageid: u64,
+    off_out_arg: u64,
+    off_out_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
     flags_arg: u64,
     flags_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
-    arg5: u64,
-    arg5_cageid: u64,
-    arg6: u64,
-    arg6_cageid: u64,
 ) -> i32 {
-    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
-    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+    let fd_in = convert_fd_to_host(fd_in_arg, fd_in_cageid, cageid);
+    if fd_in < 0 {
+        return handle_errno(-fd_in, "copy_file_range");
+    }
 
-    // Validate unused args - this should never fail in correct implementation
-    if !(sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
-        panic!(
-            "{}: unused arguments contain unexpected values -- security violation",
-            "unlinkat_syscall"
-        );
+    let fd_out = convert_fd_to_host(fd_out_arg, fd_out_cageid, cageid);
+    if fd_out < 0 {
+        return handle_errno(-fd_out, "copy_file_range");
     }
 
-    let c_path;
-    // Determine the appropriate kernel file descriptor and pathname conversion based on dirfd.
-    let kernel_fd = if dirfd == AT_FDCWD {
-        // Case 1: When AT_FDCWD is used.
-        // Convert the provided pathname from the RawPOSIX working directory (which is different from the host's)
-        // into an absolute path within the chroot jail.
-        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
-            Ok(path) => path,
-            Err(e) => return syscall_error(e, "unlinkat", "path conversion failed"),
-        };
-        AT_FDCWD
+    let off_in = if off_in_arg == 0 {
+        std::ptr::null_mut()
     } else {
-        // Case 2: When a specific directory fd is provided.
-        // Translate the virtual file descriptor to the corresponding kernel file descriptor.
-        let wrappedvfd = fdtables::translate_virtual_fd(cageid, dirfd as u64);
-        if wrappedvfd.is_err() {
-            return syscall_error(Errno::EBADF, "unlinkat", "Bad File Descriptor");
-        }
-        let vfd = wrappedvfd.unwrap();
-        // For this case, we pass the provided pathname directly.
-        let pathname = pathname_arg;
-        let tmp_cstr = get_cstr(pathname).unwrap();
-        c_path = CString::new(tmp_cstr).unwrap();
-        vfd.underfd as i32
+        sc_convert_buf(off_in_arg, off_in_cageid, cageid) as *mut libc::loff_t
     };
 
-    // Call the underlying libc::unlinkat() function with the fd and pathname.
-    let ret = unsafe { libc::unlinkat(kernel_fd, c_path.as_ptr(), flags) };
+    let off_out = if off_out_arg == 0 {
+        std::ptr::null_mut()
+    } else {
+        sc_convert_buf(off_out_arg, off_out_cageid, cageid) as *mut libc::loff_t
+    };
+
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_cageid, cageid);
+
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_copy_file_range as libc::c_long,
+            fd_in,
+            off_in,
+            fd_out,
+            off_out,
+            len,
+            flags,
+        ) as i32
+    };
 
-    // If the call failed, retrieve and handle the errno
     if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "unlinkat");
+        return handle_errno(get_errno(), "copy_file_range");
     }
+
     ret
 }
 
-//------------------------------------FCHMODAT SYSCALL------------------------------------
-/// Reference: https://man7.org/linux/man-pages/man2/fchmodat.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/sendfile.2.html
 ///
-/// `fchmodat` changes the permissions of a file relative to a directory fd.
-/// Modeled on `unlinkat_syscall` for the dirfd handling.
-pub extern "C" fn fchmodat_syscall(
-    cageid: u64,
-    dirfd_arg: u64,
-    dirfd_cageid: u64,
-    pathname_arg: u64,
-    pathname_cageid: u64,
-    mode_arg: u64,
-    mode_cageid: u64,
-    flags_arg: u64,
-    flags_cageid: u64,
+/// Linux `sendfile()` copies data between two file descriptors inside the kernel. Both virtual
+/// fds are translated to kernel fds through `fdtables`, so any combination the host kernel
+/// supports (regular file to socket, file to pipe, file to file) works unchanged. If `offset`
+/// is non-NULL it points to a 64-bit offset in the cage's memory that is read from and updated
+/// by the kernel, and the file offset of `in_fd` is left untouched.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - out_fd_arg: virtual fd to write to
+///     - in_fd_arg: virtual fd to read from
+///     - offset_arg: pointer to a `loff_t` offset (may be NULL)
+///     - count_arg: number of bytes to copy
+///
+/// ## Returns:
+///     - On success, the number of bytes written to `out_fd`.
+///     - On error, a negative errno is returned.
+pub extern "C" fn sendfile_syscall(
+    cageid: u64,
+    out_fd_arg: u64,
+    out_fd_cageid: u64,
+    in_fd_arg: u64,
+    in_fd_cageid: u64,
+    offset_arg: u64,
+    offset_cageid: u64,
+    count_arg: u64,
+    count_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
-    let mode = sc_convert_sysarg_to_u32(mode_arg, mode_cageid, cageid);
-    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+    let out_fd = convert_fd_to_host(out_fd_arg, out_fd_cageid, cageid);
+    if out_fd < 0 {
+        return handle_errno(-out_fd, "sendfile");
+    }
+    let in_fd = convert_fd_to_host(in_fd_arg, in_fd_cageid, cageid);
+    if in_fd < 0 {
+        return handle_errno(-in_fd, "sendfile");
+    }
+
+    let offset = if offset_arg == 0 {
+        std::ptr::null_mut()
+    } else {
+        sc_convert_buf(offset_arg, offset_cageid, cageid) as *mut libc::off_t
+    };
+    let count = sc_convert_sysarg_to_usize(count_arg, count_cageid, cageid);
 
     if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "fchmodat_syscall"
+            "sendfile_syscall"
         );
     }
 
-    let c_path;
-    let kernel_fd = if dirfd == AT_FDCWD {
-        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
-            Ok(path) => path,
-            Err(e) => return syscall_error(e, "fchmodat", "path conversion failed"),
-        };
-        AT_FDCWD
-    } else {
-        let wrappedvfd = fdtables::translate_virtual_fd(cageid, dirfd as u64);
-        if wrappedvfd.is_err() {
-            return syscall_error(Errno::EBADF, "fchmodat", "Bad File Descriptor");
-        }
-        let vfd = wrappedvfd.unwrap();
-        let tmp_cstr = get_cstr(pathname_arg).unwrap();
-        c_path = CString::new(tmp_cstr).unwrap();
-        vfd.underfd as i32
-    };
-
-    let ret = unsafe { libc::fchmodat(kernel_fd, c_path.as_ptr(), mode, flags) };
+    let ret = unsafe { libc::sendfile(out_fd, in_fd, offset, count) as i32 };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "fchmodat");
+        // Same as write(): a broken pipe/socket on the output side raises SIGPIPE first
+        if errno == Errno::EPIPE as i32 {
+            lind_send_signal(cageid, SIGPIPE);
+        }
+        return handle_errno(errno, "sendfile");
     }
     ret
 }
 
-//------------------------------------FACCESSAT SYSCALL------------------------------------
-/// Reference: https://man7.org/linux/man-pages/man2/faccessat.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/splice.2.html
 ///
-/// `faccessat` checks accessibility of a file relative to a directory fd.
-pub extern "C" fn faccessat_syscall(
+/// Linux `splice()` moves data between two file descriptors without copying through user
+/// space, where at least one of the descriptors must refer to a pipe. The virtual fds are
+/// translated to kernel fds, and the optional offsets are `loff_t` pointers in the cage's
+/// memory, following the same NULL/non-NULL rules as `copy_file_range_syscall`.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - fd_in_arg / off_in_arg: input virtual fd and optional offset pointer
+///     - fd_out_arg / off_out_arg: output virtual fd and optional offset pointer
+///     - len_arg: number of bytes to move
+///     - flags_arg: `SPLICE_F_*` flags, passed to the kernel unchanged
+///
+/// ## Returns:
+///     - On success, the number of bytes spliced (0 means end of input).
+///     - On error, a negative errno is returned.
+pub extern "C" fn splice_syscall(
     cageid: u64,
-    dirfd_arg: u64,
-    dirfd_cageid: u64,
-    pathname_arg: u64,
-    pathname_cageid: u64,
-    mode_arg: u64,
-    mode_cageid: u64,
+    fd_in_arg: u64,
+    fd_in_cageid: u64,
+    off_in_arg: u64,
+    off_in_cageid: u64,
+    fd_out_arg: u64,
+    fd_out_cageid: u64,
+    off_out_arg: u64,
+    off_out_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
     flags_arg: u64,
     flags_cageid: u64,
-    arg5: u64,
-    arg5_cageid: u64,
-    arg6: u64,
-    arg6_cageid: u64,
 ) -> i32 {
-    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
-    let mode = sc_convert_sysarg_to_i32(mode_arg, mode_cageid, cageid);
-    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
-
-    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
-        panic!(
-            "{}: unused arguments contain unexpected values -- security violation",
-            "faccessat_syscall"
-        );
+    let fd_in = convert_fd_to_host(fd_in_arg, fd_in_cageid, cageid);
+    if fd_in < 0 {
+        return handle_errno(-fd_in, "splice");
+    }
+    let fd_out = convert_fd_to_host(fd_out_arg, fd_out_cageid, cageid);
+    if fd_out < 0 {
+        return handle_errno(-fd_out, "splice");
     }
 
-    let c_path;
-    let kernel_fd = if dirfd == AT_FDCWD {
-        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
-            Ok(path) => path,
-            Err(e) => return syscall_error(e, "faccessat", "path conversion failed"),
-        };
-        AT_FDCWD
+    let off_in = if off_in_arg == 0 {
+        std::ptr::null_mut()
     } else {
-        let wrappedvfd = fdtables::translate_virtual_fd(cageid, dirfd as u64);
-        if wrappedvfd.is_err() {
-            return syscall_error(Errno::EBADF, "faccessat", "Bad File Descriptor");
-        }
-        let vfd = wrappedvfd.unwrap();
-        let tmp_cstr = get_cstr(pathname_arg).unwrap();
-        c_path = CString::new(tmp_cstr).unwrap();
-        vfd.underfd as i32
+        sc_convert_buf(off_in_arg, off_in_cageid, cageid) as *mut libc::loff_t
+    };
+    let off_out = if off_out_arg == 0 {
+        std::ptr::null_mut()
+    } else {
+        sc_convert_buf(off_out_arg, off_out_cageid, cageid) as *mut libc::loff_t
     };
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_cageid, cageid);
 
-    let ret = unsafe { libc::faccessat(kernel_fd, c_path.as_ptr(), mode, flags) };
+    let ret = unsafe { libc::splice(fd_in, off_in, fd_out, off_out, len, flags) as i32 };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "faccessat");
+        if errno == Errno::EPIPE as i32 {
+            lind_send_signal(cageid, SIGPIPE);
+        }
+        return handle_errno(errno, "splice");
     }
     ret
 }
 
-//------------------------------------FSTATAT SYSCALL (newfstatat)------------------------------------
-/// Reference: https://man7.org/linux/man-pages/man2/fstatat.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/tee.2.html
 ///
-/// `fstatat` retrieves stat info for a path relative to a directory fd.
-/// Linux's `newfstatat` (262) is what `fts_open` uses for traversal.
-pub extern "C" fn fstatat_syscall(
+/// Linux `tee()` duplicates up to `len` bytes from the pipe `fd_in` to the pipe `fd_out`
+/// without consuming the input. Both virtual fds are translated to kernel fds.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - fd_in_arg: virtual fd of the source pipe
+///     - fd_out_arg: virtual fd of the destination pipe
+///     - len_arg: number of bytes to duplicate
+///     - flags_arg: `SPLICE_F_*` flags, passed to the kernel unchanged
+///
+/// ## Returns:
+///     - On success, the number of bytes duplicated.
+///     - On error, a negative errno is returned.
+pub extern "C" fn tee_syscall(
     cageid: u64,
-    dirfd_arg: u64,
-    dirfd_cageid: u64,
-    pathname_arg: u64,
-    pathname_cageid: u64,
-    statbuf_arg: u64,
-    statbuf_cageid: u64,
+    fd_in_arg: u64,
+    fd_in_cageid: u64,
+    fd_out_arg: u64,
+    fd_out_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
     flags_arg: u64,
     flags_cageid: u64,
     arg5: u64,
@@ -2521,270 +5409,224 @@ pub extern "C" fn fstatat_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
-    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+    let fd_in = convert_fd_to_host(fd_in_arg, fd_in_cageid, cageid);
+    if fd_in < 0 {
+        return handle_errno(-fd_in, "tee");
+    }
+    let fd_out = convert_fd_to_host(fd_out_arg, fd_out_cageid, cageid);
+    if fd_out < 0 {
+        return handle_errno(-fd_out, "tee");
+    }
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_cageid, cageid);
 
     if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "fstatat_syscall"
+            "tee_syscall"
         );
     }
 
-    let c_path;
-    let kernel_fd = if dirfd == AT_FDCWD {
-        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
-            Ok(path) => path,
-            Err(e) => return syscall_error(e, "fstatat", "path conversion failed"),
-        };
-        AT_FDCWD
-    } else {
-        let wrappedvfd = fdtables::translate_virtual_fd(cageid, dirfd as u64);
-        if wrappedvfd.is_err() {
-            return syscall_error(Errno::EBADF, "fstatat", "Bad File Descriptor");
-        }
-        let vfd = wrappedvfd.unwrap();
-        let tmp_cstr = get_cstr(pathname_arg).unwrap();
-        c_path = CString::new(tmp_cstr).unwrap();
-        vfd.underfd as i32
-    };
-
-    let mut libc_statbuf: stat = unsafe { std::mem::zeroed() };
-    let ret = unsafe { libc::fstatat(kernel_fd, c_path.as_ptr(), &mut libc_statbuf, flags) };
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "fstatat");
-    }
-
-    match sc_convert_addr_to_statdata(statbuf_arg, statbuf_cageid, cageid) {
-        Ok(statbuf_addr) => convert_statdata_to_user(statbuf_addr, libc_statbuf),
-        Err(e) => return syscall_error(e, "fstatat", "Bad address"),
-    }
-
-    ret
-}
-
-//------------------------------------RENAMEAT / RENAMEAT2 SYSCALLS------------------------------------
-/// Internal helper used by both renameat and renameat2.
-fn renameat_inner(
-    cageid: u64,
-    olddirfd: i32,
-    oldpath_arg: u64,
-    oldpath_cageid: u64,
-    newdirfd: i32,
-    newpath_arg: u64,
-    newpath_cageid: u64,
-    flags: u32,
-    use_renameat2: bool,
-    label: &'static str,
-) -> i32 {
-    let c_oldpath;
-    let old_kernel_fd = if olddirfd == AT_FDCWD {
-        c_oldpath = match sc_convert_path_to_host(oldpath_arg, oldpath_cageid, cageid) {
-            Ok(path) => path,
-            Err(e) => return syscall_error(e, label, "old path conversion failed"),
-        };
-        AT_FDCWD
-    } else {
-        let wrappedvfd = fdtables::translate_virtual_fd(cageid, olddirfd as u64);
-        if wrappedvfd.is_err() {
-            return syscall_error(Errno::EBADF, label, "Bad olddirfd");
-        }
-        let vfd = wrappedvfd.unwrap();
-        let tmp_cstr = get_cstr(oldpath_arg).unwrap();
-        c_oldpath = CString::new(tmp_cstr).unwrap();
-        vfd.underfd as i32
-    };
-
-    let c_newpath;
-    let new_kernel_fd = if newdirfd == AT_FDCWD {
-        c_newpath = match sc_convert_path_to_host(newpath_arg, newpath_cageid, cageid) {
-            Ok(path) => path,
-            Err(e) => return syscall_error(e, label, "new path conversion failed"),
-        };
-        AT_FDCWD
-    } else {
-        let wrappedvfd = fdtables::translate_virtual_fd(cageid, newdirfd as u64);
-        if wrappedvfd.is_err() {
-            return syscall_error(Errno::EBADF, label, "Bad newdirfd");
-        }
-        let vfd = wrappedvfd.unwrap();
-        let tmp_cstr = get_cstr(newpath_arg).unwrap();
-        c_newpath = CString::new(tmp_cstr).unwrap();
-        vfd.underfd as i32
-    };
-
-    let ret = unsafe {
-        if use_renameat2 {
-            libc::syscall(
-                libc::SYS_renameat2,
-                old_kernel_fd,
-                c_oldpath.as_ptr(),
-                new_kernel_fd,
-                c_newpath.as_ptr(),
-                flags,
-            ) as libc::c_int
-        } else {
-            libc::renameat(
-                old_kernel_fd,
-                c_oldpath.as_ptr(),
-                new_kernel_fd,
-                c_newpath.as_ptr(),
-            )
-        }
-    };
+    let ret = unsafe { libc::tee(fd_in, fd_out, len, flags) as i32 };
     if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, label);
+        return handle_errno(get_errno(), "tee");
     }
     ret
 }
 
-pub extern "C" fn renameat_syscall(
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/vmsplice.2.html
+///
+/// Linux `vmsplice()` splices the user memory described by an iovec array into a pipe (or
+/// from a pipe into user memory). As with `writev_syscall`, glibc has already rewritten the
+/// iovec array into host layout, so the array can be handed to the kernel directly.
+///
+/// `SPLICE_F_GIFT` is masked out: gifting pages would let the kernel take ownership of pages
+/// inside the cage's linear memory, which lind must keep control of.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - vfd_arg: virtual fd of the pipe
+///     - iov_arg: pointer to the host-layout iovec array
+///     - nr_segs_arg: number of iovec entries
+///     - flags_arg: `SPLICE_F_*` flags
+///
+/// ## Returns:
+///     - On success, the number of bytes transferred.
+///     - On error, a negative errno is returned.
+pub extern "C" fn vmsplice_syscall(
     cageid: u64,
-    olddirfd_arg: u64,
-    olddirfd_cageid: u64,
-    oldpath_arg: u64,
-    oldpath_cageid: u64,
-    newdirfd_arg: u64,
-    newdirfd_cageid: u64,
-    newpath_arg: u64,
-    newpath_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    iov_arg: u64,
+    iov_cageid: u64,
+    nr_segs_arg: u64,
+    nr_segs_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let olddirfd = sc_convert_sysarg_to_i32(olddirfd_arg, olddirfd_cageid, cageid);
-    let newdirfd = sc_convert_sysarg_to_i32(newdirfd_arg, newdirfd_cageid, cageid);
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "vmsplice");
+    }
+    let iov_ptr = sc_convert_buf(iov_arg, iov_cageid, cageid);
+    let nr_segs = sc_convert_sysarg_to_usize(nr_segs_arg, nr_segs_cageid, cageid);
+    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_cageid, cageid) & !libc::SPLICE_F_GIFT;
 
     if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "renameat_syscall"
+            "vmsplice_syscall"
         );
     }
 
-    renameat_inner(
-        cageid,
-        olddirfd,
-        oldpath_arg,
-        oldpath_cageid,
-        newdirfd,
-        newpath_arg,
-        newpath_cageid,
-        0,
-        false,
-        "renameat",
-    )
+    let ret =
+        unsafe { libc::vmsplice(kernel_fd, iov_ptr as *const libc::iovec, nr_segs, flags) as i32 };
+    if ret < 0 {
+        let errno = get_errno();
+        if errno == Errno::EPIPE as i32 {
+            lind_send_signal(cageid, SIGPIPE);
+        }
+        return handle_errno(errno, "vmsplice");
+    }
+    ret
 }
 
-pub extern "C" fn renameat2_syscall(
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/fallocate.2.html
+///
+/// Linux `fallocate()` manipulates the allocated disk space of the file referred to by `fd`
+/// in the byte range `[offset, offset + len)`. The virtual fd is translated to the kernel fd
+/// and the mode (`FALLOC_FL_*`) is passed to the kernel unchanged.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - vfd_arg: virtual file descriptor
+///     - mode_arg: `FALLOC_FL_*` mode bits (0 allocates and extends the file size)
+///     - offset_arg: start of the range
+///     - len_arg: length of the range
+///
+/// ## Returns:
+///     - 0 on success.
+///     - On error, a negative errno is returned.
+pub extern "C" fn fallocate_syscall(
     cageid: u64,
-    olddirfd_arg: u64,
-    olddirfd_cageid: u64,
-    oldpath_arg: u64,
-    oldpath_cageid: u64,
-    newdirfd_arg: u64,
-    newdirfd_cageid: u64,
-    newpath_arg: u64,
-    newpath_cageid: u64,
-    flags_arg: u64,
-    flags_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    mode_arg: u64,
+    mode_cageid: u64,
+    offset_arg: u64,
+    offset_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let olddirfd = sc_convert_sysarg_to_i32(olddirfd_arg, olddirfd_cageid, cageid);
-    let newdirfd = sc_convert_sysarg_to_i32(newdirfd_arg, newdirfd_cageid, cageid);
-    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_cageid, cageid);
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "fallocate");
+    }
+    let mode = sc_convert_sysarg_to_i32(mode_arg, mode_cageid, cageid);
+    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
+    let len = sc_convert_sysarg_to_i64(len_arg, len_cageid, cageid);
 
-    if !sc_unusedarg(arg6, arg6_cageid) {
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "renameat2_syscall"
+            "fallocate_syscall"
         );
     }
 
-    renameat_inner(
-        cageid,
-        olddirfd,
-        oldpath_arg,
-        oldpath_cageid,
-        newdirfd,
-        newpath_arg,
-        newpath_cageid,
-        flags,
-        true,
-        "renameat2",
-    )
+    let ret = unsafe { libc::fallocate(kernel_fd, mode, offset, len) };
+    if ret < 0 {
+        return handle_errno(get_errno(), "fallocate");
+    }
+    ret
 }
 
-//------------------------------------CHOWN / LCHOWN / FCHOWNAT SYSCALLS------------------------------------
-/// `fchownat` changes the ownership of a file relative to a directory fd.
-/// Linux's `chown(path, uid, gid)` is implemented as `fchownat(AT_FDCWD, path, uid, gid, 0)`,
-/// and `lchown` as `fchownat(AT_FDCWD, path, uid, gid, AT_SYMLINK_NOFOLLOW)` — but glibc may
-/// route them via dedicated syscalls, so we expose all three.
-pub extern "C" fn fchownat_syscall(
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/posix_fadvise.2.html
+///
+/// Linux `fadvise64()` (`posix_fadvise()` in glibc) announces an intention to access file data
+/// in a specific pattern. The virtual fd is translated to the kernel fd and the advice is passed
+/// to the kernel unchanged.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - vfd_arg: virtual file descriptor
+///     - offset_arg: start of the range the advice applies to
+///     - len_arg: length of the range (0 means until the end of the file)
+///     - advice_arg: one of the `POSIX_FADV_*` values
+///
+/// ## Returns:
+///     - 0 on success.
+///     - On error, a negative errno is returned.
+pub extern "C" fn fadvise64_syscall(
     cageid: u64,
-    dirfd_arg: u64,
-    dirfd_cageid: u64,
-    pathname_arg: u64,
-    pathname_cageid: u64,
-    owner_arg: u64,
-    owner_cageid: u64,
-    group_arg: u64,
-    group_cageid: u64,
-    flags_arg: u64,
-    flags_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    offset_arg: u64,
+    offset_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    advice_arg: u64,
+    advice_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let dirfd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
-    let owner = sc_convert_sysarg_to_u32(owner_arg, owner_cageid, cageid);
-    let group = sc_convert_sysarg_to_u32(group_arg, group_cageid, cageid);
-    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "fadvise64");
+    }
+    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
+    let len = sc_convert_sysarg_to_i64(len_arg, len_cageid, cageid);
+    let advice = sc_convert_sysarg_to_i32(advice_arg, advice_cageid, cageid);
 
-    if !sc_unusedarg(arg6, arg6_cageid) {
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "fchownat_syscall"
+            "fadvise64_syscall"
         );
     }
 
-    let c_path;
-    let kernel_fd = if dirfd == AT_FDCWD {
-        c_path = match sc_convert_path_to_host(pathname_arg, pathname_cageid, cageid) {
-            Ok(path) => path,
-            Err(e) => return syscall_error(e, "fchownat", "path conversion failed"),
-        };
-        AT_FDCWD
-    } else {
-        let wrappedvfd = fdtables::translate_virtual_fd(cageid, dirfd as u64);
-        if wrappedvfd.is_err() {
-            return syscall_error(Errno::EBADF, "fchownat", "Bad File Descriptor");
-        }
-        let vfd = wrappedvfd.unwrap();
-        let tmp_cstr = get_cstr(pathname_arg).unwrap();
-        c_path = CString::new(tmp_cstr).unwrap();
-        vfd.underfd as i32
-    };
-
-    let ret = unsafe { libc::fchownat(kernel_fd, c_path.as_ptr(), owner, group, flags) };
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "fchownat");
+    // posix_fadvise returns the error number directly instead of setting errno
+    let ret = unsafe { libc::posix_fadvise(kernel_fd, offset, len, advice) };
+    if ret != 0 {
+        return handle_errno(ret, "fadvise64");
     }
-    ret
+    0
 }
 
-pub extern "C" fn chown_syscall(
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/chdir.2.html
+///
+/// Linux `chdir()` syscall changes the current working directory of the calling process to the directory
+/// specified by path. Since path seen by user is different from actual path on host, we need to convert
+/// the path first. RawPOSIX also updates the cage's current working directory in the cage structure after
+/// successfully changing the directory, ensuring cage isolation is maintained.
+///
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - path_arg: pointer to a pathname naming the directory (user's perspective)
+///     - path_cageid: cage identifier for the path argument
+///     - arg2, arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+///
+/// ## Returns:
+///     - 0 on success.
+///     - -1 on error, with errno set to indicate the error.
+pub extern "C" fn chdir_syscall(
     cageid: u64,
     path_arg: u64,
     path_cageid: u64,
-    owner_arg: u64,
-    owner_cageid: u64,
-    group_arg: u64,
-    group_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -2792,39 +5634,69 @@ pub extern "C" fn chown_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
+    // Type conversion
     let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
         Ok(path) => path,
-        Err(e) => return syscall_error(e, "chown", "path conversion failed"),
+        Err(e) => return syscall_error(e, "chdir", "path conversion failed"),
     };
-    let owner = sc_convert_sysarg_to_u32(owner_arg, owner_cageid, cageid);
-    let group = sc_convert_sysarg_to_u32(group_arg, group_cageid, cageid);
 
-    if !(sc_unusedarg(arg4, arg4_cageid)
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "chown_syscall"
+            "chdir_syscall"
         );
     }
 
-    let ret = unsafe { libc::chown(path.as_ptr(), owner, group) };
+    // Call the kernel chdir function
+    let ret = unsafe { libc::chdir(path.as_ptr()) };
+
+    // Error handling
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "chown");
+        return handle_errno(errno, "chdir");
+    }
+
+    // Update the cage's current working directory
+    if let Some(cage) = get_cage(cageid) {
+        let path_string = path.to_string_lossy();
+        let user_path = PathBuf::from(path_without_trailing_slashes(path_string.as_ref()));
+        let mut cwd = cage.cwd.write();
+        *cwd = Arc::new(user_path);
     }
+
     ret
 }
 
-pub extern "C" fn lchown_syscall(
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/rmdir.2.html
+///
+/// Linux `rmdir()` syscall removes a directory, which must be empty. Since path seen by user is different
+/// from actual path on host, we need to convert the path first. RawPOSIX doesn't have any other operations,
+/// so all operations will be handled by host. RawPOSIX does error handling for this syscall.
+///
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - path_arg: pointer to a pathname naming the directory to be removed (user's perspective)
+///     - path_cageid: cage identifier for the path argument
+///     - arg2, arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+///
+/// ## Returns:
+///     - 0 on success.
+///     - -1 on error, with errno set to indicate the error.
+pub extern "C" fn rmdir_syscall(
     cageid: u64,
     path_arg: u64,
     path_cageid: u64,
-    owner_arg: u64,
-    owner_cageid: u64,
-    group_arg: u64,
-    group_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -2832,50 +5704,61 @@ pub extern "C" fn lchown_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
+    // Type conversion
     let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
         Ok(path) => path,
-        Err(e) => return syscall_error(e, "lchown", "path conversion failed"),
+        Err(e) => return syscall_error(e, "rmdir", "path conversion failed"),
     };
-    let owner = sc_convert_sysarg_to_u32(owner_arg, owner_cageid, cageid);
-    let group = sc_convert_sysarg_to_u32(group_arg, group_cageid, cageid);
 
-    if !(sc_unusedarg(arg4, arg4_cageid)
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "lchown_syscall"
+            "rmdir_syscall"
         );
     }
 
-    let ret = unsafe { libc::lchown(path.as_ptr(), owner, group) };
+    // Call the kernel rmdir function
+    let ret = unsafe { libc::rmdir(path.as_ptr()) };
+
+    // Error handling
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "lchown");
+        return handle_errno(errno, "rmdir");
     }
+
     ret
 }
 
-//------------------------------------ACCESS SYSCALL------------------------------------
-/// `access` checks whether the calling process can access the file pathname.
-/// Reference: https://man7.org/linux/man-pages/man2/access.2.html
-/// ## Arguments:
-///  - `pathname`: Path to the file to check accessibility.
-///  - `mode`: Accessibility check mode (F_OK, R_OK, W_OK, X_OK or combinations).
-/// ## Implementation Details:
-///  - The path is converted from the RawPOSIX perspective to the host kernel perspective
-///    using `sc_convert_path_to_host`, which handles path normalization relative to the cage's CWD.
-///  - The mode parameter is passed directly to the underlying libc::access() call.
-/// ## Return Value:
-///  - `0` on success (file is accessible in the requested mode).
-///  - `-1` on failure, with `errno` set appropriately.
-pub extern "C" fn access_syscall(
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/chmod.2.html
+///
+/// Linux `chmod()` syscall changes the permissions of a file. Since path seen by user is different from
+/// actual path on host, we need to convert the path first. The mode argument specifies the permissions
+/// to be assigned to the file and is passed directly to the kernel after type conversion.
+///
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - path_arg: pointer to a pathname naming the file (user's perspective)
+///     - path_cageid: cage identifier for the path argument
+///     - mode_arg: the new file permissions (user's perspective)
+///     - mode_cageid: cage identifier for the mode argument
+///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+///
+/// ## Returns:
+///     - 0 on success.
+///     - -1 on error, with errno set to indicate the error.
+pub extern "C" fn chmod_syscall(
     cageid: u64,
     path_arg: u64,
     path_cageid: u64,
-    amode_arg: u64,
-    amode_cageid: u64,
+    mode_arg: u64,
+    mode_cageid: u64,
     arg3: u64,
     arg3_cageid: u64,
     arg4: u64,
@@ -2888,11 +5771,11 @@ pub extern "C" fn access_syscall(
     // Type conversion
     let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
         Ok(path) => path,
-        Err(e) => return syscall_error(e, "access", "path conversion failed"),
+        Err(e) => return syscall_error(e, "chmod", "path conversion failed"),
     };
-    let amode = sc_convert_sysarg_to_i32(amode_arg, amode_cageid, cageid);
+    let mode = sc_convert_sysarg_to_u32(mode_arg, mode_cageid, cageid);
 
-    // Validate unused args
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
     if !(sc_unusedarg(arg3, arg3_cageid)
         && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
@@ -2900,49 +5783,46 @@ pub extern "C" fn access_syscall(
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "access_syscall"
+            "chmod_syscall"
         );
     }
 
-    let ret = unsafe { libc::access(path.as_ptr(), amode) };
+    // Call the kernel chmod function
+    let ret = unsafe { libc::chmod(path.as_ptr(), mode) };
+
+    // Error handling
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "access");
+        return handle_errno(errno, "chmod");
     }
+
     ret
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/clock_gettime.2.html
-///
-/// `clock_gettime_syscall` retrieves the time of the specified clock and
-/// stores it in a user-provided `timespec` structure.  
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/fchmod.2.html
 ///
-/// ## Implementation Details:
-/// - The `clockid` argument is converted from the Cage's virtual argument
-///   into a host `u32`.
-/// - The `tp` pointer (destination for the `timespec` result) is translated
-///   from Wasm linear memory into a host address via `sc_convert_addr_to_host`.
-/// - Unused arguments `arg3`–`arg6` are validated with `sc_unusedarg`.
-/// - The underlying `SYS_clock_gettime` syscall is invoked directly with the
-///   converted arguments.
-/// - On error, `errno` is retrieved with `get_errno()` and normalized through
-///   `handle_errno()`.
+/// Linux `fchmod()` syscall changes the permissions of an open file referred to by file descriptor `fd`.
+/// Since we implement a file descriptor management subsystem (called `fdtables`), we first translate the
+/// virtual file descriptor to the corresponding kernel file descriptor, then convert the mode from cage
+/// memory before invoking the kernel's `libc::fchmod()` function.
 ///
-/// ## Arguments:
-/// - `cageid`: Identifier of the calling Cage
-/// - `clockid_arg`: The clock to be queried (e.g., `CLOCK_REALTIME`)
-/// - `tp_arg`: Address of the user buffer for the result `timespec`
-/// - `arg3`–`arg6`: Reserved, must be unused
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
+///     - mode_arg: the new file permissions to be applied to the file
+///     - mode_cageid: cage identifier for the mode argument
+///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
 ///
 /// ## Returns:
 ///     - 0 on success.
-///     - -1 on failure, with errno set appropriately.
-pub extern "C" fn clock_gettime_syscall(
+///     - -1 on error, with errno set to indicate the error.
+pub extern "C" fn fchmod_syscall(
     cageid: u64,
-    clockid_arg: u64,
-    clockid_cageid: u64,
-    tp_arg: u64,
-    _tp_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    mode_arg: u64,
+    mode_cageid: u64,
     arg3: u64,
     arg3_cageid: u64,
     arg4: u64,
@@ -2952,9 +5832,14 @@ pub extern "C" fn clock_gettime_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let clockid = sc_convert_sysarg_to_u32(clockid_arg, clockid_cageid, cageid);
-    let tp = tp_arg as *mut u8;
-    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "fchmod");
+    }
+
+    let mode = sc_convert_sysarg_to_u32(mode_arg, mode_cageid, cageid);
+
     if !(sc_unusedarg(arg3, arg3_cageid)
         && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
@@ -2962,36 +5847,36 @@ pub extern "C" fn clock_gettime_syscall(
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "clock_gettime_syscall"
+            "fchmod_syscall"
         );
     }
 
-    let ret = unsafe { syscall(SYS_clock_gettime, clockid, tp) as i32 };
-
+    let ret = unsafe { libc::fchmod(kernel_fd, mode) };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "clock_gettime");
+        return handle_errno(errno, "fchmod");
     }
-
     ret
 }
 
-/// Linux Reference: https://man7.org/linux/man-pages/man2/dup.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/umask.2.html
 ///
-/// Since the two file descriptors refer to the same open file description, they share file offset
-/// and file status flags. Then, in RawPOSIX, we mapped duplicated file descriptor to same underlying
-/// kernel fd.
+/// Linux `umask()` sets the calling process's file mode creation mask and returns the previous
+/// mask. The mask is kept per cage in `Cage::umask` rather than in the host process (whose umask
+/// is fixed at 0), and is applied by `open`, `openat`, `mkdir`, `mknod` and `bind` through
+/// `apply_cage_umask`. Only the permission bits (`0o777`) of the new mask are kept.
 ///
-/// ## Arguments:
-/// - `virtual_fd`: virtual file descriptor
+/// ## Input:
+///     - cageid: current cage identifier
+///     - mask_arg: the new file creation mask
+///     - arg2, arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
 ///
 /// ## Returns:
-///     - On success, the new file descriptor is returned.
-///     - On error, -1 is returned and errno is set to indicate the error.
-pub extern "C" fn dup_syscall(
+///     - The previous value of the mask. This call always succeeds.
+pub extern "C" fn umask_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    _vfd_cageid: u64,
+    mask_arg: u64,
+    mask_cageid: u64,
     arg2: u64,
     arg2_cageid: u64,
     arg3: u64,
@@ -3003,6 +5888,8 @@ pub extern "C" fn dup_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
+    let mask = sc_convert_sysarg_to_u32(mask_arg, mask_cageid, cageid);
+
     if !(sc_unusedarg(arg2, arg2_cageid)
         && sc_unusedarg(arg3, arg3_cageid)
         && sc_unusedarg(arg4, arg4_cageid)
@@ -3011,37 +5898,133 @@ pub extern "C" fn dup_syscall(
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "dup_syscall"
+            "umask_syscall"
         );
     }
 
-    let wrappedvfd = fdtables::translate_virtual_fd(cageid, vfd_arg as u64);
-    if wrappedvfd.is_err() {
-        return syscall_error(Errno::EBADF, "dup", "Bad File Descriptor");
-    }
-    let vfd = wrappedvfd.unwrap();
-    let ret_kernelfd = unsafe { libc::dup(vfd.underfd as i32) };
-    let ret_vfd =
-        fdtables::get_unused_virtual_fd(cageid, vfd.fdkind, ret_kernelfd as u64, false, 0).unwrap();
-    return ret_vfd as i32;
+    let cage = get_cage(cageid).unwrap();
+    cage.umask.swap(mask & S_IRWXA, Ordering::Relaxed) as i32
 }
 
-/// dup2() performs the same task as dup(), so we utilize dup() here and mapping underlying kernel
-/// fd with specific `new_virutalfd`
+//------------------------------------UTIMENSAT SYSCALL------------------------------------
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/utimensat.2.html
+///
+/// `utimensat` updates the access and modification times of a file with
+/// nanosecond precision.  Used by `touch`, `cp -p`, and any test that
+/// asserts on file timestamps.  `futimens(fd, ts)` is implemented in
+/// glibc by calling `utimensat(fd, NULL, ts, 0)`, so a single handler
+/// here covers both.
 ///
 /// ## Arguments:
-/// - `old_virtualfd`: original virtual file descriptor
-/// - `new_virtualfd`: specified new virtual file descriptor
+/// - `dirfd`: directory fd; `AT_FDCWD` means "relative to the cage cwd";
+///   when `pathname` is NULL, `dirfd` itself is the target file (futimens).
+/// - `pathname`: path relative to `dirfd`, or NULL for the futimens form.
+/// - `times`: pointer to two `timespec` values [atime, mtime], or NULL
+///   meaning "set both to now".  `tv_nsec` may be the special values
+///   `UTIME_NOW` or `UTIME_OMIT`.
+/// - `flags`: 0 or `AT_SYMLINK_NOFOLLOW`.
+///
+/// ## Returns:
+/// - 0 on success.
+/// - negated errno on failure.
+pub extern "C" fn utimensat_syscall(
+    cageid: u64,
+    dirfd_arg: u64,
+    dirfd_cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    times_arg: u64,
+    _times_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let virtual_fd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "utimensat_syscall"
+        );
+    }
+
+    // Glibc's __utimensat64_helper has already copied the cage's
+    // __timespec64 into a kernel-ABI layout { i64 tv_sec; i64 tv_nsec; }
+    // and passed that translated pointer through, so casting directly
+    // matches the host kernel's struct timespec.  May be NULL ("now").
+    let times = times_arg as *const libc::timespec;
+
+    // Two cases for the path/dirfd combination:
+    //   (a) pathname == NULL:  futimens form — operate on the file
+    //       referred to by dirfd.  dirfd must be a real fd, not AT_FDCWD.
+    //   (b) pathname != NULL:  utimensat form — resolve via cage's path
+    //       conversion (handles cwd + chroot), then optionally relative
+    //       to dirfd if not AT_FDCWD.
+    let ret = if path_arg == 0 {
+        if virtual_fd == AT_FDCWD {
+            return syscall_error(Errno::EBADF, "utimensat", "AT_FDCWD with NULL path");
+        }
+        let kernel_fd = convert_fd_to_host(virtual_fd as u64, dirfd_cageid, cageid);
+        if kernel_fd < 0 {
+            return handle_errno(-kernel_fd, "utimensat");
+        }
+        // futimens(): glibc routes futimens(fd, ts) as utimensat(fd, NULL, ts, 0).
+        // The kernel utimensat syscall accepts NULL path with a real fd, so we
+        // forward to libc::futimens (its userspace wrapper around the same).
+        unsafe { libc::futimens(kernel_fd, times) }
+    } else {
+        let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+            Ok(p) => p,
+            Err(e) => return syscall_error(e, "utimensat", "path conversion failed"),
+        };
+        if virtual_fd == AT_FDCWD {
+            unsafe { libc::utimensat(AT_FDCWD, path.as_ptr(), times, flags) }
+        } else {
+            let kernel_fd = convert_fd_to_host(virtual_fd as u64, dirfd_cageid, cageid);
+            if kernel_fd < 0 {
+                return handle_errno(-kernel_fd, "utimensat");
+            }
+            unsafe { libc::utimensat(kernel_fd, path.as_ptr(), times, flags) }
+        }
+    };
+
+    if ret < 0 {
+        return handle_errno(get_errno(), "utimensat");
+    }
+    ret
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/getcwd.2.html
+///
+/// `getcwd_syscall` retrieves the current working directory for the calling Cage.
+///
+/// Unlike directly calling `libc::getcwd`, this implementation uses the Cage's
+/// own `cwd` field maintained inside the Cage struct. Each Cage has its own
+/// logical working directory, which may differ from the host kernel's notion
+/// of the filesystem root. Because of this mismatch between the Cage's root
+/// and the kernel root, we cannot delegate to `libc::getcwd` and must instead
+/// return the Cage-specific `cwd` value.
+///
+/// ## Input:
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - buf_arg: pointer to a buffer where the current working directory path will be stored (user's perspective)
+///     - size_arg: the size of the buffer in bytes
+///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
 ///
 /// ## Returns:
-///     - On success, returns the new file descriptor.
-///     - On error, -1 is returned and errno is set to indicate the error.
-pub extern "C" fn dup2_syscall(
+///     - On success, returns 0 after copying the current working directory path to the buffer.
+///     - On error, returns -1 and errno is set to indicate the error.
+pub extern "C" fn getcwd_syscall(
     cageid: u64,
-    old_vfd_arg: u64,
-    _old_vfd_cageid: u64,
-    new_vfd_arg: u64,
-    _new_vfd_cageid: u64,
+    buf_arg: u64,
+    _buf_cageid: u64,
+    size_arg: u64,
+    size_cageid: u64,
     arg3: u64,
     arg3_cageid: u64,
     arg4: u64,
@@ -3051,7 +6034,13 @@ pub extern "C" fn dup2_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    let buf = buf_arg as *mut u8;
+
+    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
+    if size == 0 {
+        return syscall_error(Errno::EINVAL, "getcwd", "Size cannot be zero");
+    }
+
     if !(sc_unusedarg(arg3, arg3_cageid)
         && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
@@ -3059,66 +6048,50 @@ pub extern "C" fn dup2_syscall(
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "dup2_syscall"
+            "getcwd_syscall"
         );
     }
 
-    // Validate both virtual fds
-    if old_vfd_arg > MAXFD as u64 || new_vfd_arg > MAXFD as u64 {
-        return syscall_error(Errno::EBADF, "dup2", "Bad File Descriptor");
-    } else if old_vfd_arg == new_vfd_arg {
-        // Does nothing
-        return new_vfd_arg as i32;
+    let cage = get_cage(cageid).unwrap();
+    let cwd_container = cage.cwd.read();
+    let path = cwd_container.to_str().unwrap();
+    // The required size includes the null terminator
+    let required_size = path.len() + 1;
+    if required_size > size as usize {
+        return syscall_error(Errno::ERANGE, "getcwd_syscall", "Invalid buffer size");
+    }
+    unsafe {
+        ptr::copy(path.as_ptr(), buf, path.len());
+        *buf.add(path.len()) = 0;
     }
 
-    // If the file descriptor newfd was previously open, it is closed before being reused; the
-    // close is performed silently (i.e., any errors during the close are not reported by dup2()).
-    // This step is handled inside `fdtables`
-    match fdtables::translate_virtual_fd(cageid, old_vfd_arg) {
-        Ok(old_vfd) => {
-            // Request another virtual fd to refer to same underlying kernel fd as `virtual_fd`
-            // from input.
-            // The two file descriptors do not share file descriptor flags (the
-            // close-on-exec flag).  The close-on-exec flag (FD_CLOEXEC; see fcntl_syscall())
-            // for the duplicate descriptor is off
-            let _ = fdtables::get_specific_virtual_fd(
-                cageid,
-                new_vfd_arg,
-                old_vfd.fdkind,
-                old_vfd.underfd,
-                false,
-                old_vfd.perfdinfo,
-            )
-            .unwrap();
+    // std::copy guarantees it copies exactly path.len() bytes.
+    let bytes_written: i32 = path.len().try_into().unwrap();
 
-            return new_vfd_arg as i32;
-        }
-        Err(_e) => {
-            return syscall_error(Errno::EBADF, "dup2", "Bad File Descriptor");
-        }
-    }
+    bytes_written
 }
 
-/// dup3() duplicates `old_virtualfd` to `new_virtualfd`, similar to dup2(),
-/// but requires the two descriptors to differ and allows setting FD_CLOEXEC via `flags`.
-/// It first calls `dup2_syscall` to copy the file descriptor, then sets the close-on-exec flag if requested.
-
+/// Truncate a file to a specified length
+///
 /// ## Arguments:
-/// - `old_virtualfd`: source virtual file descriptor
-/// - `new_virtualfd`: target virtual file descriptor
-/// - `flags`: must be 0 or O_CLOEXEC
+///     - cageid: current cage identifier
+///     - path_arg: pointer to the pathname of the file to truncate
+///     - path_cageid: cage identifier for the path argument
+///     - length_arg: the new length to truncate the file to
+///     - length_cageid: cage identifier for the length argument
+///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
 ///
 /// ## Returns:
-///     - On success, returns the new file descriptor.
-///     - On error, -1 is returned and errno is set to indicate the error (EBADF or EINVAL).
-pub extern "C" fn dup3_syscall(
+///     - 0 on success.
+///     - -1 on error, with errno set to indicate the error.
+pub extern "C" fn truncate_syscall(
     cageid: u64,
-    old_vfd_arg: u64,
-    old_vfd_cageid: u64,
-    new_vfd_arg: u64,
-    new_vfd_cageid: u64,
-    flags_arg: u64,
-    flags_cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    length_arg: u64,
+    length_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -3126,153 +6099,124 @@ pub extern "C" fn dup3_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
-
-    if !(sc_unusedarg(arg4, arg4_cageid)
+    // Validate unused arguments
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "dup3_syscall"
+            "truncate_syscall"
         );
     }
 
-    if old_vfd_arg > MAXFD as u64 || new_vfd_arg > MAXFD as u64 {
-        return syscall_error(Errno::EBADF, "dup3", "Bad File Descriptor");
-    }
-
-    if old_vfd_arg == new_vfd_arg {
-        return syscall_error(Errno::EINVAL, "dup3", "oldfd and newfd must be different");
-    }
-
-    if flags != 0 && flags != O_CLOEXEC {
-        return syscall_error(Errno::EINVAL, "dup3", "Invalid flags");
-    }
+    // Type conversion
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "truncate", "path conversion failed"),
+    };
+    let length = sc_convert_sysarg_to_i64(length_arg, length_cageid, cageid);
 
-    let ret = dup2_syscall(
-        cageid,
-        old_vfd_arg,
-        old_vfd_cageid,
-        new_vfd_arg,
-        new_vfd_cageid,
-        UNUSED_ARG,
-        UNUSED_ID,
-        UNUSED_ARG,
-        UNUSED_ID,
-        UNUSED_ARG,
-        UNUSED_ID,
-        UNUSED_ARG,
-        UNUSED_ID,
-    );
-    if ret < 0 {
-        return ret;
-    }
+    // Call libc truncate
+    let ret = unsafe { libc::truncate(path.as_ptr() as *const i8, length) };
 
-    if flags == O_CLOEXEC {
-        let _ = fdtables::set_cloexec(cageid, new_vfd_arg, true);
+    if ret == -1 {
+        let errno = get_errno();
+        return handle_errno(errno, "truncate");
     }
 
-    return new_vfd_arg as i32;
+    0
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/fchdir.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/clock_nanosleep.2.html
 ///
-/// Linux `fchdir()` syscall changes the current working directory of the calling process to the
-/// directory referred to by the open file descriptor `fd`. Since we implement a file descriptor
-/// management subsystem (called `fdtables`), we first translate the virtual file descriptor to the
-/// corresponding kernel file descriptor before invoking the kernel's `libc::fchdir()` function.
+/// `nanosleep_time64_syscall` suspends execution of the calling Cage for
+/// the time interval specified in the requested `timespec` structure.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment referring to a directory
-///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+/// ## Implementation Details:
+/// - The `req` (requested time) and `rem` (remaining time) pointers are
+///   converted from Wasm linear memory to host addresses using
+///   `sc_convert_buf`.
+/// - Unused arguments `arg5` and `arg6` are validated with `sc_unusedarg`.
+/// - The underlying `SYS_clock_nanosleep` syscall is invoked directly.  
+/// - On error, `errno` is retrieved and normalized through `handle_errno()`.
+///
+/// ## Arguments:
+/// - `cageid`: Identifier of the calling Cage
+/// - `clockid_arg`: The clock against which the sleep interval is measured
+/// - `flags_arg`: Flags controlling sleep behavior
+/// - `req_arg`: Address of the requested sleep interval (`timespec`)
+/// - `rem_arg`: Address of the remaining interval (`timespec`) if interrupted
 ///
 /// ## Returns:
 ///     - 0 on success.
-///     - -1 on error, with errno set to indicate the error.
-pub extern "C" fn fchdir_syscall(
+///     - -1 on failure, with errno set appropriately.
+pub extern "C" fn nanosleep_time64_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    arg2: u64,
-    arg2_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
+    clockid_arg: u64,
+    clockid_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    req_arg: u64,
+    req_cageid: u64,
+    rem_arg: u64,
+    rem_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "fchdir");
-    }
-
-    if !(sc_unusedarg(arg2, arg2_cageid)
-        && sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
+    // Type conversion
+    let clockid = sc_convert_sysarg_to_u32(clockid_arg, clockid_cageid, cageid);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+    let req = sc_convert_buf(req_arg, req_cageid, cageid);
+    let rem = sc_convert_buf(rem_arg, rem_cageid, cageid);
+    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "fchdir_syscall"
+            "nanosleep_time64_syscall"
         );
     }
-
-    let ret = unsafe { libc::fchdir(kernel_fd) };
+    let ret = unsafe { syscall(SYS_clock_nanosleep, clockid, flags, req, rem) as i32 };
     if ret < 0 {
-        return handle_errno(get_errno(), "fchdir");
-    }
-
-    // Update the cage's current working directory
-    // We need to get the current working directory from the kernel to update the cage
-    let mut cwd_buf = [0u8; PATH_MAX as usize];
-    let cwd_ptr = unsafe { libc::getcwd(cwd_buf.as_mut_ptr() as *mut i8, cwd_buf.len()) };
-    if !cwd_ptr.is_null() {
-        if let Some(cage) = get_cage(cageid) {
-            let host_path = unsafe { std::ffi::CStr::from_ptr(cwd_ptr) }.to_string_lossy();
-            let user_path = PathBuf::from(host_path.as_ref());
-            let mut cwd = cage.cwd.write();
-            *cwd = Arc::new(user_path);
-        }
+        let errno = get_errno();
+        return handle_errno(errno, "nanosleep");
     }
-
     ret
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/writev.2.html
-///
-/// Linux `writev()` syscall performs scatter-gather output by writing data from multiple buffers
-/// to a file descriptor in a single operation. Since we implement a file descriptor management
-/// subsystem (called `fdtables`), we first translate the virtual file descriptor to the corresponding
-/// kernel file descriptor, then translate the iovec array pointer from cage virtual memory to host
-/// memory before invoking the kernel's `libc::writev()` function.
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/mprotect.2.html
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
-///     - iov_arg: pointer to an array of iovec structures describing the buffers (user's perspective)
-///     - iovcnt_arg: number of iovec structures in the array
-///     - arg4, arg5, arg6: additional arguments which are expected to be unused
+/// Linux `mprotect()` syscall changes the protection of memory pages. It sets the protection
+/// bits for memory pages in the range [addr, addr+len-1] to prot. Private pages that become
+/// writable start counting against the cage's memory limits (see `cage::memory::quota`), so
+/// making them writable fails with `ENOMEM` if that would exceed one.
+///
+/// ## Arguments:
+///     - cageid: current cage identifier
+///     - addr_arg: pointer to the start of the memory region to change protection for
+///     - addr_cageid: cage ID for addr argument validation
+///     - len_arg: length of the memory region in bytes
+///     - len_cageid: cage ID for len argument validation  
+///     - prot_arg: new protection flags (PROT_READ, PROT_WRITE, PROT_EXEC, PROT_NONE)
+///     - prot_cageid: cage ID for prot argument validation
+///     - arg4, arg4_cageid: unused argument and its cage ID
+///     - arg5, arg5_cageid: unused argument and its cage ID
+///     - arg6, arg6_cageid: unused argument and its cage ID
 ///
 /// ## Returns:
-///     - On success, the number of bytes written is returned.
-///     - On error, -1 is returned and errno is set to indicate the error.
-pub extern "C" fn writev_syscall(
+///     - 0 on success
+///     - -1 on error with appropriate errno set
+pub extern "C" fn mprotect_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    iov_arg: u64,
-    iov_cageid: u64,
-    iovcnt_arg: u64,
-    iovcnt_cageid: u64,
+    addr_arg: u64,
+    _addr_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    prot_arg: u64,
+    prot_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -3280,58 +6224,112 @@ pub extern "C" fn writev_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "writev");
-    }
-
-    let iovcnt = sc_convert_sysarg_to_i32(iovcnt_arg, iovcnt_cageid, cageid);
-    let iov_ptr = sc_convert_buf(iov_arg, iov_cageid, cageid);
+    // Type conversion
+    let addr = addr_arg as *mut u8;
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    let prot = sc_convert_sysarg_to_i32(prot_arg, prot_cageid, cageid);
 
+    // Validate unused args
     if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "writev"
+            "mprotect_syscall"
         );
     }
 
-    let ret = unsafe { libc::writev(kernel_fd, iov_ptr as *const libc::iovec, iovcnt) as i32 };
+    // Validate protection flags
+    let valid_prot = PROT_READ | PROT_WRITE | PROT_EXEC | PROT_NONE;
+    if prot & !valid_prot != 0 {
+        return syscall_error(Errno::EINVAL, "mprotect", "Invalid protection flags");
+    }
+
+    // For security, we don't allow PROT_EXEC in lind-wasm
+    if prot & PROT_EXEC != 0 {
+        return syscall_error(Errno::EINVAL, "mprotect", "PROT_EXEC is not allowed");
+    }
+
+    // Round length up to page boundary (mprotect operates on whole pages)
+    let rounded_length = round_up_page(len as u64) as usize;
+
+    // Skip the vmmap if length is zero (no pages to update) — Linux treats len=0 as a no-op
+    if rounded_length == 0 {
+        let ret = unsafe { libc::mprotect(addr as *mut c_void, rounded_length, prot) };
+        if ret < 0 {
+            let errno = get_errno();
+            return handle_errno(errno, "mprotect");
+        }
+        return ret;
+    }
+
+    if addr as u64 != round_up_page(addr as u64) {
+        return syscall_error(Errno::EINVAL, "mprotect", "address is not aligned");
+    }
+    let cage = get_cage(cageid).unwrap();
+    let mut vmmap = cage.vmmap.write();
+    let user_addr = match (addr as usize).checked_sub(vmmap.base_address.unwrap()) {
+        Some(user_addr)
+            if user_addr as u64 + rounded_length as u64 <= vmmap.address_space_size() =>
+        {
+            user_addr
+        }
+        _ => return syscall_error(Errno::ENOMEM, "mprotect", "range is outside the cage"),
+    };
+    let page_num = (user_addr >> PAGESHIFT) as u32;
+    let npages = (rounded_length >> PAGESHIFT) as u32;
+
+    // Private pages made writable start counting against the memory limits
+    if let Err(e) = quota::check_protect(&cage, &vmmap, page_num, npages, prot) {
+        return syscall_error(e, "mprotect", "memory limit exceeded");
+    }
+
+    // Call the kernel mprotect
+    let ret = unsafe { libc::mprotect(addr as *mut c_void, rounded_length, prot) };
     if ret < 0 {
-        return handle_errno(get_errno(), "writev");
+        let errno = get_errno();
+        quota::recount(&cage, &vmmap);
+        return handle_errno(errno, "mprotect");
     }
+
+    // Update vmmap to reflect the new protection flags
+    vmmap.change_prot(page_num, npages, prot);
+    quota::recount(&cage, &vmmap);
+
     ret
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/readv.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/ioctl.2.html
 ///
-/// Linux `readv()` syscall performs scatter input by reading data from a file descriptor
-/// into multiple buffers in a single operation. Since we implement a file descriptor management
-/// subsystem (called `fdtables`), we first translate the virtual file descriptor to the corresponding
-/// kernel file descriptor, then translate the iovec array pointer from cage virtual memory to host
-/// memory before invoking the kernel's `libc::readv()` function.
+/// Linux `ioctl()` syscall manipulates the underlying device parameters of special files.
+/// In particular, many operating characteristics of character special files (e.g., terminals)
+/// may be controlled with ioctl() operations. The argument fd must be an open file descriptor.
 ///
-/// ## Input:
+/// ## Arguments:
 ///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
-///     - iov_arg: pointer to an array of iovec structures describing the buffers
-///     - iovcnt_arg: number of iovec structures in the array
-///     - arg4, arg5, arg6: additional arguments which are expected to be unused
+///     - vfd_arg: virtual file descriptor (must be an open file descriptor)
+///     - vfd_cageid: cage ID for vfd argument validation
+///     - req_arg: device-dependent operation code
+///     - req_cageid: cage ID for req argument validation
+///     - ptrunion_arg: pointer to memory (untyped pointer, traditionally char *argp)
+///     - ptrunion_cageid: cage ID for ptrunion argument validation
+///     - arg4, arg4_cageid: unused argument and its cage ID
+///     - arg5, arg5_cageid: unused argument and its cage ID
+///     - arg6, arg6_cageid: unused argument and its cage ID
 ///
 /// ## Returns:
-///     - On success, the number of bytes read is returned.
-///     - On error, -1 is returned and errno is set to indicate the error.
-pub extern "C" fn readv_syscall(
+///     - Usually, on success zero is returned. A few ioctl() operations use the return value
+///       as an output parameter and return a nonnegative value on success.
+///     - On error, -1 is returned, and errno is set to indicate the error.
+pub extern "C" fn ioctl_syscall(
     cageid: u64,
     vfd_arg: u64,
-    vfd_cageid: u64,
-    iov_arg: u64,
-    iov_cageid: u64,
-    iovcnt_arg: u64,
-    iovcnt_cageid: u64,
+    _vfd_cageid: u64,
+    req_arg: u64,
+    req_cageid: u64,
+    ptrunion_arg: u64,
+    _ptrunion_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -3339,162 +6337,317 @@ pub extern "C" fn readv_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "readv");
-    }
-
-    let iovcnt = sc_convert_sysarg_to_i32(iovcnt_arg, iovcnt_cageid, cageid);
-    let iov_ptr = sc_convert_buf(iov_arg, iov_cageid, cageid);
+    // Type conversion
+    let ptrunion = ptrunion_arg as *mut u8;
+    let req = sc_convert_sysarg_to_u32(req_arg, req_cageid, cageid);
 
+    // Validate unused args
     if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "readv"
+            "ioctl_syscall"
         );
     }
 
-    let ret = unsafe { libc::readv(kernel_fd, iov_ptr as *const libc::iovec, iovcnt) as i32 };
+    // handle FIOCLEX, set close_on_exec flag for the file descriptor
+    if req as u64 == FIOCLEX as u64 {
+        let ret = match fdtables::set_cloexec(cageid, vfd_arg, true) {
+            Ok(_) => 0,
+            Err(_) => syscall_error(Errno::EBADF, "ioctl", "Bad File Descriptor"),
+        };
+
+        return ret;
+    }
+
+    // Besides FIOCLEX, we only support FIONBIO, FIOASYNC, FIONREAD, and TIOCGWINSZ right now.
+    // Return error for unsupported requests.
+    if req != FIONBIO && req != FIOASYNC && req != FIONREAD && req != TIOCGWINSZ {
+        lind_debug_panic!("Lind unsupported ioctl request");
+    }
+
+    let wrappedvfd = fdtables::translate_virtual_fd(cageid, vfd_arg);
+    if wrappedvfd.is_err() {
+        return syscall_error(Errno::EBADF, "ioctl", "Bad File Descriptor");
+    }
+
+    let vfd = wrappedvfd.unwrap();
+
+    // FIONBIO and FIOASYNC apply to the eventfd behind an in-process socket, which holds its
+    // file status flags; only FIONREAD needs the socket itself
+    if req == FIONREAD {
+        if let Some(sock) = inproc_socket::lookup(cageid, vfd_arg) {
+            unsafe { *(ptrunion as *mut i32) = sock.pending() as i32 };
+            return 0;
+        }
+    }
+
+    let ret = unsafe {
+        libc::ioctl(
+            vfd.underfd as i32,
+            req as libc::Ioctl,
+            ptrunion as *mut c_void,
+        )
+    };
+
     if ret < 0 {
-        return handle_errno(get_errno(), "readv");
+        let errno = get_errno();
+        return handle_errno(errno, "ioctl");
     }
-    ret
+    return ret;
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/preadv.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/flock.2.html
 ///
-/// `preadv()` combines the functionality of `readv()` and `pread()`: it performs
-/// scatter input from the file at the given offset without changing the file pointer.
+/// `Flock()` syscall applies or removes an advisory lock on an open file. We first translate the virtual file descriptor to the
+/// corresponding kernel file descriptor, then convert the operation flags from cage memory before invoking
+/// the kernel's `libc::flock()` function.
 ///
 /// ## Arguments:
-///     - vfd_arg: virtual file descriptor
-///     - iov_arg: pointer to an array of iovec structures
-///     - iovcnt_arg: number of iovec structures
-///     - offset_arg: file offset to read from
+///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
+///     - cageid: current cage identifier
+///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
+///     - vfd_cageid: cage ID for vfd argument validation
+///     - op_arg: operation flags (LOCK_SH, LOCK_EX, LOCK_UN, optionally ORed with LOCK_NB)
+///     - op_cageid: cage ID for op argument validation
+///     - arg3, arg3_cageid: unused argument and its cage ID
+///     - arg4, arg4_cageid: unused argument and its cage ID
+///     - arg5, arg5_cageid: unused argument and its cage ID
+///     - arg6, arg6_cageid: unused argument and its cage ID
 ///
 /// ## Returns:
-///     - On success, the number of bytes read.
-///     - On error, -1 with errno set.
-pub extern "C" fn preadv_syscall(
+///     - 0 on success
+///     - -1 on error, with errno set to indicate the error (EBADF, EINTR, EINVAL, ENOLCK, EWOULDBLOCK)
+pub extern "C" fn flock_syscall(
     cageid: u64,
     vfd_arg: u64,
     vfd_cageid: u64,
-    iov_arg: u64,
-    iov_cageid: u64,
-    iovcnt_arg: u64,
-    iovcnt_cageid: u64,
-    offset_arg: u64,
-    offset_cageid: u64,
+    op_arg: u64,
+    op_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
     let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    // Return error
     if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "preadv");
+        return handle_errno(-kernel_fd, "flock");
     }
 
-    let iovcnt = sc_convert_sysarg_to_i32(iovcnt_arg, iovcnt_cageid, cageid);
-    let iov_ptr = sc_convert_buf(iov_arg, iov_cageid, cageid);
-    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
+    let op = sc_convert_sysarg_to_i32(op_arg, op_cageid, cageid);
 
-    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+    // Validate unused arguments
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "preadv_syscall"
+            "flock_syscall"
         );
     }
 
-    let ret =
-        unsafe { libc::preadv(kernel_fd, iov_ptr as *const libc::iovec, iovcnt, offset) as i32 };
+    // Validate operation flags
+    let valid_ops = libc::LOCK_SH | libc::LOCK_EX | libc::LOCK_UN | libc::LOCK_NB;
+    if op & !valid_ops != 0 {
+        return syscall_error(Errno::EINVAL, "flock", "Invalid operation flags");
+    }
+
+    // Ensure at least one primary operation is specified
+    let primary_ops = libc::LOCK_SH | libc::LOCK_EX | libc::LOCK_UN;
+    if op & primary_ops == 0 {
+        return syscall_error(Errno::EINVAL, "flock", "No primary operation specified");
+    }
+
+    let ret = unsafe { libc::flock(kernel_fd, op) };
     if ret < 0 {
-        return handle_errno(get_errno(), "preadv");
+        let errno = get_errno();
+        return handle_errno(errno, "flock");
     }
+
     ret
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/pwritev.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/shmget.2.html
+///
+/// shmget checks whether a shared memory key already exists, and either returns the existing
+/// segment ID or creates a new segment (after validating flags and size) before registering
+/// it in the metadata tables.
 ///
-/// `pwritev()` combines the functionality of `writev()` and `pwrite()`: it performs
-/// gather output to the file at the given offset without changing the file pointer.
+/// 1. **Convert arguments**  
+///    - Convert `key_arg`, `size_arg`, and `shmflg_arg` into native types,
+///      while validating `arg4`–`arg6` are unused.
 ///
-/// ## Arguments:
-///     - vfd_arg: virtual file descriptor
-///     - iov_arg: pointer to an array of iovec structures
-///     - iovcnt_arg: number of iovec structures
-///     - offset_arg: file offset to write at
+/// 2. **Special case: IPC_PRIVATE**  
+///    - Return `ENOENT` since `IPC_PRIVATE` segments are not supported yet.
+///
+/// 3. **Check if key exists**  
+///    - If the key already exists in `shmkeyidtable`:  
+///       - If both `IPC_CREAT` and `IPC_EXCL` are set → error `EEXIST`.  
+///       - Else return the existing `shmid`.
+///
+/// 4. **Key does not exist**  
+///    - If `IPC_CREAT` not set → error `ENOENT`.  
+///    - Validate `size` against `SHMMIN` and `SHMMAX`.  
+///    - Allocate new `shmid` via `new_keyid()`.  
+///    - Insert into `shmkeyidtable`.  
+///    - Create a new shared memory segment with owner `cageid`, default `uid/gid`,
+///      and mode = lowest 9 bits of `shmflg`.  
+///    - Insert segment into `shmtable`.
+///
+/// ## Arguments
+/// * `cageid`       – The ID of the calling cage, used for ownership and validation.
+/// * `key_arg`      – The key used to identify the shared memory segment (raw u64).
+/// * `key_cageid`   – The cage ID associated with `key_arg` for validation.
+/// * `size_arg`     – The size (in bytes) of the requested segment (raw u64).
+/// * `size_cageid`  – The cage ID associated with `size_arg`.
+/// * `shmflg_arg`   – Flags controlling creation, permissions, and behavior (raw u64).
+/// * `shmflg_cageid`– The cage ID associated with `shmflg_arg`.
 ///
 /// ## Returns:
-///     - On success, the number of bytes written.
-///     - On error, -1 with errno set.
-pub extern "C" fn pwritev_syscall(
+/// On success, it returns the segment ID; on failure, it returns an appropriate error code.
+pub extern "C" fn shmget_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    iov_arg: u64,
-    iov_cageid: u64,
-    iovcnt_arg: u64,
-    iovcnt_cageid: u64,
-    offset_arg: u64,
-    offset_cageid: u64,
+    key_arg: u64,
+    key_cageid: u64,
+    size_arg: u64,
+    size_cageid: u64,
+    shmflg_arg: u64,
+    shmflg_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "pwritev");
-    }
-
-    let iovcnt = sc_convert_sysarg_to_i32(iovcnt_arg, iovcnt_cageid, cageid);
-    let iov_ptr = sc_convert_buf(iov_arg, iov_cageid, cageid);
-    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
-
-    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+    let key = sc_convert_sysarg_to_i32(key_arg, key_cageid, cageid);
+    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
+    let shmflg = sc_convert_sysarg_to_i32(shmflg_arg, shmflg_cageid, cageid);
+    // Validate unused args
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "pwritev_syscall"
+            "shmget_syscall"
         );
     }
 
-    let ret =
-        unsafe { libc::pwritev(kernel_fd, iov_ptr as *const libc::iovec, iovcnt, offset) as i32 };
-    if ret < 0 {
-        return handle_errno(get_errno(), "pwritev");
+    if key == IPC_PRIVATE {
+        lind_debug_panic!("shmget key IPC_PRIVATE is not allowed in Lind");
     }
-    ret
+    let shmid: i32;
+    let metadata = &SHM_METADATA;
+
+    // The size of the segment should be rounded up to a multiple of pages
+    let rounded_size = round_up_page(size as u64) as usize;
+
+    match metadata.shmkeyidtable.entry(key) {
+        Occupied(occupied) => {
+            if (IPC_CREAT | IPC_EXCL) == (shmflg & (IPC_CREAT | IPC_EXCL)) {
+                return syscall_error(
+                    Errno::EEXIST,
+                    "shmget",
+                    "key already exists and IPC_CREAT and IPC_EXCL were used",
+                );
+            }
+            shmid = *occupied.get();
+        }
+        Vacant(vacant) => {
+            if 0 == (shmflg & IPC_CREAT) {
+                return syscall_error(
+                    Errno::ENOENT,
+                    "shmget",
+                    "tried to use a key that did not exist, and IPC_CREAT was not specified",
+                );
+            }
+
+            if (rounded_size as u32) < SHMMIN || (rounded_size as u32) > SHMMAX {
+                return syscall_error(
+                    Errno::EINVAL,
+                    "shmget",
+                    "Size is less than SHMMIN or more than SHMMAX",
+                );
+            }
+
+            shmid = metadata.new_keyid();
+            vacant.insert(shmid);
+            let mode = (shmflg & 0x1FF) as u16; // mode is 9 least signficant bits of shmflag, even if we dont really do anything with them
+
+            let segment = new_shm_segment(
+                key,
+                rounded_size,
+                cageid as u32,
+                DEFAULT_UID,
+                DEFAULT_GID,
+                mode,
+            );
+            metadata.shmtable.insert(shmid, segment);
+        }
+    };
+    shmid // return the shmid
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/fstat.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man3/shmat.3p.html
 ///
-/// Linux `fstat()` syscall retrieves information about the file referred to by the open file descriptor `fd`.
-/// Since we implement a file descriptor management subsystem (called `fdtables`), we first translate the virtual
-/// file descriptor to the corresponding kernel file descriptor, then call the kernel's `libc::fstat()` function.
-/// The returned stat structure is converted to our ABI-stable StatData format and copied to the user's buffer.
+/// Handles the shmat syscall by mapping shared memory segments into the cage's address space.
+/// This function manages the attachment of shared memory segments by updating the cage's vmmap
+/// and handling the raw shmat helpers.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
-///     - stat_arg: pointer to a stat structure where the file information will be stored (user's perspective)
-///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+/// 1) **Parse & validate args**
+///    - Decode `shmid`, `shmaddr`, `shmflg`; ensure `arg4..arg6` are truly unused (panic if not).
+/// 2) **Resolve access mode**
+///    - If `SHM_RDONLY` set → `prot = PROT_READ`; otherwise `prot = PROT_READ|PROT_WRITE`.
+/// 3) **Lookup segment length**
+///    - `get_shm_length(shmid)` → error `EINVAL` if unknown segment.
+/// 4) **Validate alignment & sizes**
+///    - `shmaddr` must be page-aligned (error `EINVAL` if not).
+///    - Round segment length up to page size.
+/// 5) **Choose placement in vmmap**
+///    - If `shmaddr == 0` → `find_map_space(pages, align=1)`.
+///    - Else           → `find_map_space_with_hint(pages, align=1, hint=shmaddr)`.
+///    - No fit → error `ENOMEM`.
+/// 6) **Translate to system address**
+///    - Convert chosen user address to system address via `vmmap.user_to_sys`.
+/// 7) **Perform attach in the backend**
+///    - Call `shmat_helper(cageid, sysaddr, shmflg, shmid)`.
+///    - Must return the same user address; mismatch to panic.
+/// 8) **Record mapping**
+///    - Add a `vmmap` entry with `backing = SharedMemory(shmid)`,
+///      `prot` and `maxprot = prot`, length in pages, offset 0, `len` as filelen.
 ///
-/// ## Returns:
-///     - 0 on success.
-///     - -1 on error, with errno set to indicate the error.
-pub extern "C" fn fstat_syscall(
+/// # Arguments
+/// * `cageid` - The cage ID that is performing the shmat operation
+/// * `addr` - The requested address to attach the shared memory segment (can be null)
+/// * `prot` - The memory protection flags for the mapping
+/// * `shmflag` - Flags controlling the shared memory attachment behavior
+/// * `shmid` - The ID of the shared memory segment to attach
+///
+/// # Returns
+/// * `i32` - The address where the shared memory segment was attached (its page number in a
+///   64-bit cage), or an error code
+///
+/// # Errors
+/// * `EINVAL` - If the provided address is not page-aligned
+/// * `ENOMEM` - If there is insufficient memory to complete the attachment
+pub extern "C" fn shmat_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    statbuf_arg: u64,
-    statbuf_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
+    shmid_arg: u64,
+    shmid_cageid: u64,
+    shmaddr_arg: u64,
+    shmaddr_cageid: u64,
+    shmflg_arg: u64,
+    shmflg_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -3502,122 +6655,174 @@ pub extern "C" fn fstat_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "fstat");
-    }
-
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
+    let shmid = sc_convert_sysarg_to_i32(shmid_arg, shmid_cageid, cageid);
+    let mut useraddr = {
+        if shmaddr_arg == 0 {
+            0
+        } else {
+            sc_convert_sysarg_to_uaddr(shmaddr_arg, shmaddr_cageid, cageid)
+        }
+    };
+    let shmflag = sc_convert_sysarg_to_i32(shmflg_arg, shmflg_cageid, cageid);
+    let mut prot = 0;
+    // Validate unused args
+    if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "fstat_syscall"
+            "shmat_syscall"
+        );
+    }
+
+    // Get the cage reference.
+    let cage = get_cage(cageid).unwrap();
+
+    // If SHM_RDONLY is set in shmflag, then use read-only protection,
+    // otherwise default to read–write.
+    prot = if shmflag & SHM_RDONLY != 0 {
+        PROT_READ
+    } else {
+        PROT_READ | PROT_WRITE
+    };
+    let len = match get_shm_length(shmid) {
+        Some(l) => l,
+        None => return syscall_error(Errno::EINVAL, "shmat", "invalid shmid"),
+    };
+
+    // Check that the provided address is page aligned.
+    let rounded_addr = round_up_page(useraddr);
+    if rounded_addr != useraddr {
+        return syscall_error(Errno::EINVAL, "shmat", "address is not aligned");
+    }
+
+    // Round up the length to a multiple of the page size.
+    let rounded_length = round_up_page(len as u64);
+
+    // Initialize the user address from the provided address pointer.
+    // If addr is null (0), we need to allocate memory space from the virtual memory map (vmmap).
+    let vmmap = cage.vmmap.write();
+    let result;
+    if useraddr == 0 {
+        // Allocate a suitable space in the virtual memory map for the shared memory segment
+        // based on the rounded length of the segment.
+        result = vmmap.find_map_space((rounded_length >> PAGESHIFT) as u32, 1);
+    } else {
+        // Use the user-specified address as a hint to find an appropriate memory address
+        // for the shared memory segment.
+        result = vmmap.find_map_space_with_hint(
+            (rounded_length >> PAGESHIFT) as u32,
+            1,
+            (useraddr >> PAGESHIFT) as u32,
         );
     }
+    // drop the write lock of vmmap to avoid deadlock
+    drop(vmmap);
 
-    // Cast directly to libc::stat and write kernel data into buffer.
-    let mut host_stat: libc::stat = unsafe { std::mem::zeroed() };
-    let ret = unsafe { libc::fstat(kernel_fd, &mut host_stat as *mut libc::stat) };
-    if ret < 0 {
-        return handle_errno(get_errno(), "fstat");
+    if result.is_none() {
+        // If no suitable memory space is found, return an error indicating insufficient memory.
+        return syscall_error(Errno::ENOMEM, "shmat", "no memory") as i32;
     }
+    let space = result.unwrap();
+    // Update the user address to the start of the allocated memory space.
+    useraddr = (space.start() as u64) << PAGESHIFT;
 
-    // Validate guest buffer range and writability
-    match sc_convert_addr_to_statdata(statbuf_arg, statbuf_cageid, cageid) {
-        // 3) Populate StatData directly
-        Ok(statbuf_addr) => convert_statdata_to_user(statbuf_addr, host_stat),
-        Err(e) => return syscall_error(e, "fstat", "Bad address"),
+    if useraddr + rounded_length > cage.vmmap.read().address_space_size() {
+        return syscall_error(
+            Errno::EINVAL,
+            "shmat",
+            "address range overflows the cage address space",
+        );
     }
 
-    ret
-}
-
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/ftruncate.2.html
-///
-/// Linux `ftruncate()` syscall truncates the file referred to by the file descriptor `fd` to be at most
-/// `length` bytes in size. Since we implement a file descriptor management subsystem (called `fdtables`),
-/// we first translate the virtual file descriptor to the corresponding kernel file descriptor, then convert
-/// the length argument from u64 to i64 type before invoking the kernel's `libc::ftruncate()` function.
-///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
-///     - length_arg: the desired length in bytes for the file truncation
-///     - arg4, arg5, arg6: additional arguments which are expected to be unused
-///
-/// ## Returns:
-///     - 0 on success.
-///     - -1 on error, with errno set to indicate the error.
-pub extern "C" fn ftruncate_syscall(
-    cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    length_arg: u64,
-    length_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
-    arg5: u64,
-    arg5_cageid: u64,
-    arg6: u64,
-    arg6_cageid: u64,
-) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "ftruncate");
+    // Convert the user address into a system address.
+    // Read the virtual memory map to access the user address space.
+    let vmmap = cage.vmmap.read();
+    // The segment counts toward the address space limit, but it is not committed by this cage.
+    if let Err(e) = quota::check_mapping(
+        &cage,
+        &vmmap,
+        (useraddr >> PAGESHIFT) as u32,
+        (rounded_length >> PAGESHIFT) as u32,
+        false,
+    ) {
+        return syscall_error(e, "shmat", "memory limit exceeded");
     }
+    // Convert the user address to the corresponding system address for the shared memory segment.
+    let sysaddr = vmmap.user_to_sys(useraddr);
+    // Release the lock on the virtual memory map as we no longer need it.
+    drop(vmmap);
 
-    let length = sc_convert_sysarg_to_i64(length_arg, length_cageid, cageid);
+    // Call the raw shmat helper to attach the shared memory segment.
+    let result = shmat_helper(cageid, sysaddr as *mut u8, shmflag, shmid);
 
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
-        panic!(
-            "{}: unused arguments contain unexpected values -- security violation",
-            "ftruncate_syscall"
-        );
+    // Check for error BEFORE sys_to_user conversion
+    if is_mmap_error(result) {
+        let errno = get_errno();
+        quota::recount(&cage, &cage.vmmap.read());
+        return handle_errno(errno, "shmat");
     }
 
-    let ret = unsafe { libc::ftruncate(kernel_fd, length) };
-    if ret < 0 {
-        return handle_errno(get_errno(), "ftruncate");
+    let vmmap = cage.vmmap.read();
+    let result = vmmap.sys_to_user(result);
+    drop(vmmap);
+
+    if result != useraddr {
+        panic!("shmat did not attach at the expected address");
     }
-    ret
+    let mut vmmap = cage.vmmap.write();
+    let backing = MemoryBackingType::SharedMemory(shmid as u64);
+    let maxprot = prot;
+    vmmap
+        .add_entry_with_overwrite(
+            (useraddr >> PAGESHIFT) as u32,
+            (rounded_length >> PAGESHIFT) as u32,
+            prot,
+            maxprot,
+            0,
+            backing,
+            0,
+            len as i64,
+            cageid,
+        )
+        .expect("shmat: failed to add vmmap entry");
+    quota::recount(&cage, &vmmap);
+
+    vmmap.user_addr_to_retval(useraddr)
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/fstatfs.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man3/shmdt.3p.html
 ///
-/// Linux `fstatfs()` syscall returns information about a mounted filesystem referred to by the open file
-/// descriptor `fd`. Since we implement a file descriptor management subsystem (called `fdtables`), we first
-/// translate the virtual file descriptor to the corresponding kernel file descriptor, then call the kernel's
-/// `libc::fstatfs()` function. The returned statfs structure is converted to our ABI-stable FSData format
-/// and copied to the user's buffer.
+/// `shmdt_syscall`, interacting with the `vmmap` structure.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
-///     - statfs_arg: pointer to a statfs structure where the filesystem information will be stored (user's perspective)
-///     - arg4, arg5, arg6: additional arguments which are expected to be unused
+/// This function processes the `shmdt_syscall` by updating the `vmmap` entries and managing
+/// the shared memory detachment operation. It performs address validation, converts user
+/// addresses to system addresses, and updates the virtual memory mappings accordingly.
 ///
-/// ## Returns:
-///     - 0 on success.
-///     - -1 on error, with errno set to indicate the error.
-pub extern "C" fn fstatfs_syscall(
+/// 1) **Parse & validate args**
+///    - Decode `shmaddr`; ensure `arg2..arg6` are unused (panic if not).
+/// 2) **Validate alignment**
+///    - `shmaddr` must be page-aligned (error `EINVAL` if not).
+/// 3) **Translate to system address**
+///    - `vmmap.user_to_sys(shmaddr)` to get the underlying system pointer.
+/// 4) **Perform detach in the backend**
+///    - `shmdt_helper(cageid, sysaddr)` → returns detached length (bytes) or negative errno.
+/// 5) **Remove mapping from vmmap**
+///    - Remove `length >> PAGESHIFT` pages starting at `shmaddr >> PAGESHIFT`.
+///
+/// # Arguments
+/// * `cageid` - Identifier of the cage that calls the `shmdt`
+/// * `addr` - Starting address of the shared memory region to detach
+///
+/// # Returns
+/// * `i32` - 0 for success and negative errno for failure
+pub extern "C" fn shmdt_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    statfs_arg: u64,
-    statfs_cageid: u64,
+    shmaddr_arg: u64,
+    shmaddr_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
     arg3: u64,
     arg3_cageid: u64,
     arg4: u64,
@@ -3627,67 +6832,91 @@ pub extern "C" fn fstatfs_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "fstatfs");
-    }
+    // NOTE: glibc's shmdt wrapper already calls TRANSLATE_GUEST_POINTER_TO_HOST,
+    // so shmaddr_arg is already a host/system pointer. Do NOT translate again.
+    // This avoids dependency on vmmap.base_address which can change after fork/exec.
+    let sysaddr = shmaddr_arg as usize;
 
-    if !(sc_unusedarg(arg3, arg3_cageid)
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
         && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "fstatfs_syscall"
+            "shmdt_syscall"
         );
     }
 
-    // 1) Call host fstatfs into a local host variable
-    let mut host_statfs: libc::statfs = unsafe { std::mem::zeroed() };
-    let ret = unsafe { libc::fstatfs(kernel_fd, &mut host_statfs) };
-    if ret < 0 {
-        return handle_errno(get_errno(), "fstatfs");
+    // Retrieve the cage reference.
+    let cage = get_cage(cageid).unwrap();
+
+    // Check that the provided address is aligned on a page boundary.
+    if sysaddr & (PAGESIZE as usize - 1) != 0 {
+        return syscall_error(Errno::EINVAL, "shmdt", "address is not aligned");
     }
 
-    // 2) Validate guest buffer range and writability
-    match sc_convert_addr_to_fstatdata(statfs_arg, statfs_cageid, cageid) {
-        // 3) Populate StatData directly
-        Ok(statbuf_addr) => convert_fstatdata_to_user(statbuf_addr, host_statfs),
-        Err(e) => return syscall_error(e, "fstatfs", "Bad address"),
+    // Call shmdt_helper which returns length of the detached segment.
+    // Pass the host address directly - rev_shm stores host addresses from shmat.
+    let length = shmdt_helper(cageid, sysaddr as *mut u8);
+    if length < 0 {
+        return length;
     }
 
-    ret
+    // Remove the mapping from the vmmap.
+    // Convert sys address back to user address for vmmap bookkeeping.
+    let mut vmmap = cage.vmmap.write();
+    let useraddr = vmmap.sys_to_user(sysaddr);
+    vmmap
+        .remove_entry((useraddr >> PAGESHIFT) as u32, (length as u32) >> PAGESHIFT)
+        .expect("shmdt: remove_entry failed");
+    quota::recount(&cage, &vmmap);
+
+    0
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/getdents64.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man3/shmctl.3p.html
 ///
-/// Linux `getdents()` syscall reads several directory entries from the directory referred to by the open file
-/// descriptor `fd` into the buffer pointed to by `dirp`. Since we implement a file descriptor management
-/// subsystem (called `fdtables`), we first translate the virtual file descriptor to the corresponding kernel
-/// file descriptor, then call the kernel's `getdents64` syscall directly to retrieve directory entries.
+/// It converts and validates the `shmid`, `cmd`, and optional `buf` arguments, enforces that unused
+/// arguments are truly unused (panicking on unexpected values), and then applies
+/// the requested control operation: `IPC_STAT` copies the segment’s metadata
+/// (`shminfo`) into the caller-provided buffer, and `IPC_RMID` marks the segment
+/// for removal (setting `SHM_DEST`) and deletes it immediately if there are no
+/// attachments, also clearing the key to id mapping.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment referring to a directory
-///     - dirp_arg: pointer to a buffer where the directory entries will be stored (user's perspective)
-///     - count_arg: size of the buffer pointed to by dirp
-///     - arg4, arg5, arg6: additional arguments which are expected to be unused
+/// 1) **Parse & validate args**
+///    - Decode `shmid`, `cmd`, and optional `buf` pointer; ensure `arg4..arg6` unused (panic if not).
+/// 2) **Locate segment**
+///    - Lookup `shmid` in `shmtable`; if not found → error `EINVAL`.
+/// 3) **Dispatch by `cmd`**
+///    - `IPC_STAT`: copy `segment.shminfo` into caller’s `*buf`.
+///    - `IPC_RMID`: mark for removal (`segment.rmid = true`, set `SHM_DEST` in mode).
+///        * If `shm_nattch == 0`, remove the segment immediately and clear the key→id mapping.
+///    - Otherwise: error `EINVAL` (unsupported command).
+///
+/// ## Arguments
+/// * `cageid`        – The ID of the calling cage, used for ownership and validation.
+/// * `shmid_arg`     – Shared memory segment ID (raw u64).
+/// * `shmid_cageid`  – Cage ID associated with `shmid_arg`, used for validation.
+/// * `cmd_arg`       – Control command (e.g., `IPC_STAT`, `IPC_RMID`) (raw u64).
+/// * `cmd_cageid`    – Cage ID associated with `cmd_arg`.
+/// * `buf_arg`       – Pointer to a buffer (struct shmid_ds) used for returning or
+///                     updating segment info, depending on the command.
+/// * `buf_cageid`    – Cage ID associated with `buf_arg`.
 ///
 /// ## Returns:
-///     - On success, the number of bytes read is returned.
-///     - On end of directory, 0 is returned.
-///     - On error, -1 is returned and errno is set to indicate the error.
-pub extern "C" fn getdents_syscall(
+/// On invalid identifiers or
+/// unsupported commands, it returns `-EINVAL` via `syscall_error`; on success,
+/// returns `0`.
+pub extern "C" fn shmctl_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    dirp_arg: u64,
-    dirp_cageid: u64,
-    count_arg: u64,
-    count_cageid: u64,
+    shmid_arg: u64,
+    shmid_cageid: u64,
+    cmd_arg: u64,
+    cmd_cageid: u64,
+    buf_arg: u64,
+    buf_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -3695,58 +6924,74 @@ pub extern "C" fn getdents_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "getdents");
-    }
-
-    let dirp = sc_convert_buf(dirp_arg, dirp_cageid, cageid);
-    let count = sc_convert_sysarg_to_usize(count_arg, count_cageid, cageid);
+    let shmid = sc_convert_sysarg_to_i32(shmid_arg, shmid_cageid, cageid);
+    let cmd = sc_convert_sysarg_to_i32(cmd_arg, cmd_cageid, cageid);
+    let buf = sc_convert_addr_to_shmidstruct(buf_arg, buf_cageid, cageid).unwrap();
 
+    // Validate unused args
     if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "getdents_syscall"
+            "shmctl_syscall"
         );
     }
 
-    let ret =
-        unsafe { libc::syscall(libc::SYS_getdents64 as libc::c_long, kernel_fd, dirp, count) };
+    let metadata = &SHM_METADATA;
 
-    ret as i32
+    if let Some(mut segment) = metadata.shmtable.get_mut(&shmid) {
+        match cmd {
+            IPC_STAT => {
+                *buf = segment.shminfo;
+            }
+            IPC_RMID => {
+                segment.rmid = true;
+                segment.shminfo.shm_perm.mode |= SHM_DEST as u16;
+                if segment.shminfo.shm_nattch == 0 {
+                    let key = segment.key;
+                    drop(segment);
+                    metadata.shmtable.remove(&shmid);
+                    metadata.shmkeyidtable.remove(&key);
+                }
+            }
+            _ => {
+                return syscall_error(
+                    Errno::EINVAL,
+                    "shmctl",
+                    "Arguments provided do not match implemented parameters",
+                );
+            }
+        }
+    } else {
+        return syscall_error(Errno::EINVAL, "shmctl", "Invalid identifier");
+    }
+
+    0 //shmctl has succeeded!
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/lseek.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/semget.2.html
 ///
-/// Linux `lseek()` syscall repositions the file offset of the open file description associated with the file
-/// descriptor `fd` to the argument `offset` according to the directive `whence`. Since we implement a file
-/// descriptor management subsystem (called `fdtables`), we first translate the virtual file descriptor to the
-/// corresponding kernel file descriptor, then convert the offset and whence parameters from cage memory before
-/// invoking the kernel's `libc::lseek()` function.
+/// Returns the id of the semaphore set for `key`, creating one with `nsems` semaphores if
+/// `semflg` has `IPC_CREAT`. Semaphore sets are kept by `cage::ipc` and shared by all cages.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
-///     - offset_arg: the new offset according to the directive whence
-///     - whence_arg: how to interpret the offset (SEEK_SET, SEEK_CUR, or SEEK_END)
-///     - arg4, arg5, arg6: additional arguments which are expected to be unused
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `key_arg` - The key of the set, or `IPC_PRIVATE` for a new one.
+/// * `nsems_arg` - The number of semaphores in a new set.
+/// * `semflg_arg` - `IPC_CREAT`, `IPC_EXCL` and the permission bits.
 ///
 /// ## Returns:
-///     - On success, the resulting offset location as measured in bytes from the beginning of the file is returned.
-///     - On error, -1 is returned and errno is set to indicate the error.
-pub extern "C" fn lseek_syscall(
+/// The id of the set on success, or a negative errno on failure.
+pub extern "C" fn semget_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    offset_arg: u64,
-    offset_cageid: u64,
-    whence_arg: u64,
-    whence_cageid: u64,
+    key_arg: u64,
+    key_cageid: u64,
+    nsems_arg: u64,
+    nsems_cageid: u64,
+    semflg_arg: u64,
+    semflg_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -3754,241 +6999,173 @@ pub extern "C" fn lseek_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "lseek");
-    }
-
-    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
-    let whence = sc_convert_sysarg_to_i32(whence_arg, whence_cageid, cageid);
-
+    let key = sc_convert_sysarg_to_i32(key_arg, key_cageid, cageid);
+    let nsems = sc_convert_sysarg_to_i32(nsems_arg, nsems_cageid, cageid);
+    let semflg = sc_convert_sysarg_to_i32(semflg_arg, semflg_cageid, cageid);
     if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "lseek_syscall"
+            "semget_syscall"
         );
     }
 
-    let ret = unsafe { libc::lseek(kernel_fd, offset, whence) };
-    if ret < 0 {
-        return handle_errno(get_errno(), "lseek");
+    match cage::semget(key, nsems, semflg) {
+        Ok(semid) => semid,
+        Err(e) => syscall_error(e, "semget", "could not get semaphore set"),
     }
-
-    ret as i32
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/pread.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/semop.2.html
 ///
-/// Linux `pread()` syscall reads up to `count` bytes from the file descriptor `fd` at the
-/// given `offset` without changing the file offset. Since we implement a file descriptor management
-/// subsystem (called `fdtables`), we first translate the virtual file descriptor to the corresponding
-/// kernel file descriptor, then convert the buffer and offset from cage memory before invoking the
-/// kernel's `libc::pread()` function.
+/// Performs `nsops` operations on the semaphore set `semid` atomically. glibc's `semop` calls
+/// this with a null timeout, which waits as long as it takes.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
-///     - buf_arg: pointer to a buffer where the read data will be stored (user's perspective)
-///     - count_arg: the maximum number of bytes to read from the file descriptor
-///     - offset_arg: file offset at which the input/output operation takes place
-///     - arg5, arg6: additional arguments which are expected to be unused
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `semid_arg` - The id of the set.
+/// * `sops_arg` - Host pointer to the array of `struct sembuf`.
+/// * `nsops_arg` - The number of operations.
+/// * `timeout_arg` - Host pointer to a relative `struct __timespec64`, or 0 to wait forever.
 ///
 /// ## Returns:
-///     - On success, the number of bytes read is returned (zero indicates end of file).
-///     - On error, -1 is returned and errno is set to indicate the error.
-pub extern "C" fn pread_syscall(
+/// 0 on success, or a negative errno on failure.
+pub extern "C" fn semtimedop_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    buf_arg: u64,
-    buf_cageid: u64,
-    count_arg: u64,
-    count_cageid: u64,
-    offset_arg: u64,
-    offset_cageid: u64,
+    semid_arg: u64,
+    semid_cageid: u64,
+    sops_arg: u64,
+    _sops_cageid: u64,
+    nsops_arg: u64,
+    nsops_cageid: u64,
+    timeout_arg: u64,
+    _timeout_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "pread");
-    }
-
-    let buf = sc_convert_buf(buf_arg, buf_cageid, cageid);
-    let count = sc_convert_sysarg_to_usize(count_arg, count_cageid, cageid);
-    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
-
+    let semid = sc_convert_sysarg_to_i32(semid_arg, semid_cageid, cageid);
+    let nsops = sc_convert_sysarg_to_usize(nsops_arg, nsops_cageid, cageid);
     if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "pread_syscall"
+            "semtimedop_syscall"
         );
     }
 
-    let ret = unsafe { libc::pread(kernel_fd, buf as *mut c_void, count, offset) as i32 };
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "pread");
+    if nsops > SEMOPM {
+        return syscall_error(Errno::EBIG, "semop", "too many operations");
+    }
+    if nsops > 0 && sops_arg == 0 {
+        return syscall_error(Errno::EFAULT, "semop", "operations are null");
+    }
+    let timeout = match futex::guest_timespec(timeout_arg) {
+        Some(ts) if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) => {
+            return syscall_error(Errno::EINVAL, "semtimedop", "invalid timeout");
+        }
+        Some(ts) => Some(std::time::Duration::new(
+            ts.tv_sec as u64,
+            ts.tv_nsec as u32,
+        )),
+        None => None,
+    };
+    let sops: Vec<SembufStruct> = (0..nsops)
+        .map(|i| unsafe { std::ptr::read_unaligned((sops_arg as *const SembufStruct).add(i)) })
+        .collect();
+
+    match cage::semtimedop(cageid, semid, &sops, timeout) {
+        Ok(()) => 0,
+        Err(e) => syscall_error(e, "semop", "semaphore operation failed"),
     }
-    ret
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/pwrite.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/semctl.2.html
 ///
-/// Linux `pwrite()` syscall writes up to `count` bytes from the buffer pointed to by `buf` to the file
-/// associated with the open file descriptor, `fd`, starting at the given `offset` without changing the
-/// file offset. Since we implement a file descriptor management subsystem (called `fdtables`), we first
-/// translate the virtual file descriptor to the corresponding kernel file descriptor, then convert the
-/// buffer, count, and offset from cage memory before invoking the kernel's `libc::pwrite()` function.
+/// Performs the control operation `cmd` on the semaphore set `semid`. glibc adds `IPC_64` to
+/// `cmd`, which is ignored. The fourth argument is the value for `SETVAL`, and a host pointer
+/// for the commands that take an array or a `struct semid_ds`.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
-///     - buf_arg: pointer to a buffer that stores the data to be written (user's perspective)
-///     - count_arg: the maximum number of bytes to write to the file descriptor
-///     - offset_arg: file offset at which the input/output operation takes place
-///     - arg5, arg6: additional arguments which are expected to be unused
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `semid_arg` - The id of the set.
+/// * `semnum_arg` - The semaphore the command applies to, for single-semaphore commands.
+/// * `cmd_arg` - The command.
+/// * `arg_arg` - The value or host pointer the command takes.
 ///
 /// ## Returns:
-///     - On success, the number of bytes written is returned.
-///     - On error, -1 is returned and errno is set to indicate the error.
-pub extern "C" fn pwrite_syscall(
+/// The requested value for the `GET*` commands, 0 for the others, or a negative errno on
+/// failure.
+pub extern "C" fn semctl_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    buf_arg: u64,
-    buf_cageid: u64,
-    count_arg: u64,
-    count_cageid: u64,
-    offset_arg: u64,
-    offset_cageid: u64,
+    semid_arg: u64,
+    semid_cageid: u64,
+    semnum_arg: u64,
+    semnum_cageid: u64,
+    cmd_arg: u64,
+    cmd_cageid: u64,
+    arg_arg: u64,
+    _arg_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
-    arg6_cageid: u64,
-) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "pwrite");
-    }
-
-    let buf = sc_convert_buf(buf_arg, buf_cageid, cageid);
-    let count = sc_convert_sysarg_to_usize(count_arg, count_cageid, cageid);
-    let offset = sc_convert_sysarg_to_i64(offset_arg, offset_cageid, cageid);
-
-    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
-        panic!(
-            "{}: unused arguments contain unexpected values -- security violation",
-            "pwrite_syscall"
-        );
-    }
-
-    let ret = unsafe { libc::pwrite(kernel_fd, buf as *const c_void, count, offset) as i32 };
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "pwrite");
-    }
-    ret
-}
-
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/copy_file_range.2.html
-///
-/// Linux `copy_file_range()` copies up to `len` bytes from one file descriptor
-/// to another. If `off_in` or `off_out` is NULL, the corresponding file offset
-/// is used and updated by the kernel. If they are non-NULL, they point to file
-/// offsets and the file offsets of the descriptors are not changed.
-pub extern "C" fn copy_file_range_syscall(
-    cageid: u64,
-    fd_in_arg: u64,
-    fd_in_cageid: u64,
-    off_in_arg: u64,
-    off_in_cageid: u64,
-    fd_out_arg: u64,
-    fd_out_cageid: u64,
-    off_out_arg: u64,
-    off_out_cageid: u64,
-    len_arg: u64,
-    len_cageid: u64,
-    flags_arg: u64,
-    flags_cageid: u64,
-) -> i32 {
-    let fd_in = convert_fd_to_host(fd_in_arg, fd_in_cageid, cageid);
-    if fd_in < 0 {
-        return handle_errno(-fd_in, "copy_file_range");
-    }
-
-    let fd_out = convert_fd_to_host(fd_out_arg, fd_out_cageid, cageid);
-    if fd_out < 0 {
-        return handle_errno(-fd_out, "copy_file_range");
-    }
-
-    let off_in = if off_in_arg == 0 {
-        std::ptr::null_mut()
-    } else {
-        sc_convert_buf(off_in_arg, off_in_cageid, cageid) as *mut libc::loff_t
-    };
-
-    let off_out = if off_out_arg == 0 {
-        std::ptr::null_mut()
-    } else {
-        sc_convert_buf(off_out_arg, off_out_cageid, cageid) as *mut libc::loff_t
-    };
-
-    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
-    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_cageid, cageid);
-
-    let ret = unsafe {
-        libc::syscall(
-            libc::SYS_copy_file_range as libc::c_long,
-            fd_in,
-            off_in,
-            fd_out,
-            off_out,
-            len,
-            flags,
-        ) as i32
-    };
-
-    if ret < 0 {
-        return handle_errno(get_errno(), "copy_file_range");
+    arg6_cageid: u64,
+) -> i32 {
+    let semid = sc_convert_sysarg_to_i32(semid_arg, semid_cageid, cageid);
+    let semnum = sc_convert_sysarg_to_i32(semnum_arg, semnum_cageid, cageid);
+    let cmd = sc_convert_sysarg_to_i32(cmd_arg, cmd_cageid, cageid) & !IPC_64;
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "semctl_syscall"
+        );
     }
 
-    ret
+    let needs_pointer = matches!(cmd, GETALL | SETALL | IPC_STAT | IPC_SET);
+    if needs_pointer && arg_arg == 0 {
+        return syscall_error(Errno::EFAULT, "semctl", "argument is null");
+    }
+    let ret = match cmd {
+        SETVAL => cage::semctl(cageid, semid, semnum, cmd, SemctlArg::Val(arg_arg as i32)),
+        GETALL | SETALL => match cage::sem_nsems(semid) {
+            Ok(nsems) => {
+                let array = unsafe { std::slice::from_raw_parts_mut(arg_arg as *mut u16, nsems) };
+                cage::semctl(cageid, semid, semnum, cmd, SemctlArg::Array(array))
+            }
+            Err(e) => Err(e),
+        },
+        IPC_STAT | IPC_SET => {
+            let buf = unsafe { &mut *(arg_arg as *mut SemidStruct) };
+            cage::semctl(cageid, semid, semnum, cmd, SemctlArg::Buf(buf))
+        }
+        _ => cage::semctl(cageid, semid, semnum, cmd, SemctlArg::None),
+    };
+    match ret {
+        Ok(val) => val,
+        Err(e) => syscall_error(e, "semctl", "semaphore control operation failed"),
+    }
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/chdir.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/msgget.2.html
 ///
-/// Linux `chdir()` syscall changes the current working directory of the calling process to the directory
-/// specified by path. Since path seen by user is different from actual path on host, we need to convert
-/// the path first. RawPOSIX also updates the cage's current working directory in the cage structure after
-/// successfully changing the directory, ensuring cage isolation is maintained.
+/// Returns the id of the message queue for `key`, creating one if `msgflg` has `IPC_CREAT`.
+/// Message queues are kept by `cage::ipc` and shared by all cages.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - path_arg: pointer to a pathname naming the directory (user's perspective)
-///     - path_cageid: cage identifier for the path argument
-///     - arg2, arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `key_arg` - The key of the queue, or `IPC_PRIVATE` for a new one.
+/// * `msgflg_arg` - `IPC_CREAT`, `IPC_EXCL` and the permission bits.
 ///
 /// ## Returns:
-///     - 0 on success.
-///     - -1 on error, with errno set to indicate the error.
-pub extern "C" fn chdir_syscall(
+/// The id of the queue on success, or a negative errno on failure.
+pub extern "C" fn msgget_syscall(
     cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    arg2: u64,
-    arg2_cageid: u64,
+    key_arg: u64,
+    key_cageid: u64,
+    msgflg_arg: u64,
+    msgflg_cageid: u64,
     arg3: u64,
     arg3_cageid: u64,
     arg4: u64,
@@ -3998,69 +7175,157 @@ pub extern "C" fn chdir_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "chdir", "path conversion failed"),
-    };
-
-    // would sometimes check, sometimes be a no-op depending on the compiler settings
-    if !(sc_unusedarg(arg2, arg2_cageid)
-        && sc_unusedarg(arg3, arg3_cageid)
+    let key = sc_convert_sysarg_to_i32(key_arg, key_cageid, cageid);
+    let msgflg = sc_convert_sysarg_to_i32(msgflg_arg, msgflg_cageid, cageid);
+    if !(sc_unusedarg(arg3, arg3_cageid)
         && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "chdir_syscall"
+            "msgget_syscall"
         );
     }
 
-    // Call the kernel chdir function
-    let ret = unsafe { libc::chdir(path.as_ptr()) };
+    match cage::msgget(key, msgflg) {
+        Ok(msqid) => msqid,
+        Err(e) => syscall_error(e, "msgget", "could not get message queue"),
+    }
+}
 
-    // Error handling
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "chdir");
+/// Linux reference: https://man7.org/linux/man-pages/man2/msgsnd.2.html
+///
+/// Sends the message at `msgp` to the queue `msqid`. The message starts with its type, a
+/// 32-bit `long` in the cage, followed by `msgsz` bytes of text.
+///
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `msqid_arg` - The id of the queue.
+/// * `msgp_arg` - Host pointer to the message.
+/// * `msgsz_arg` - The length of the text.
+/// * `msgflg_arg` - `IPC_NOWAIT` or 0.
+///
+/// ## Returns:
+/// 0 on success, or a negative errno on failure.
+pub extern "C" fn msgsnd_syscall(
+    cageid: u64,
+    msqid_arg: u64,
+    msqid_cageid: u64,
+    msgp_arg: u64,
+    _msgp_cageid: u64,
+    msgsz_arg: u64,
+    msgsz_cageid: u64,
+    msgflg_arg: u64,
+    msgflg_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let msqid = sc_convert_sysarg_to_i32(msqid_arg, msqid_cageid, cageid);
+    let msgsz = sc_convert_sysarg_to_usize(msgsz_arg, msgsz_cageid, cageid);
+    let msgflg = sc_convert_sysarg_to_i32(msgflg_arg, msgflg_cageid, cageid);
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "msgsnd_syscall"
+        );
     }
 
-    // Update the cage's current working directory
-    if let Some(cage) = get_cage(cageid) {
-        let path_string = path.to_string_lossy();
-        let user_path = PathBuf::from(path_without_trailing_slashes(path_string.as_ref()));
-        let mut cwd = cage.cwd.write();
-        *cwd = Arc::new(user_path);
+    if msgsz > MSGMAX {
+        return syscall_error(Errno::EINVAL, "msgsnd", "message is too long");
+    }
+    if msgp_arg == 0 {
+        return syscall_error(Errno::EFAULT, "msgsnd", "message is null");
+    }
+    let mtype = unsafe { std::ptr::read_unaligned(msgp_arg as *const i32) };
+    let text = unsafe { std::slice::from_raw_parts((msgp_arg + 4) as *const u8, msgsz) };
+    match cage::msgsnd(cageid, msqid, mtype, text, msgflg) {
+        Ok(()) => 0,
+        Err(e) => syscall_error(e, "msgsnd", "could not send message"),
     }
+}
 
-    ret
+/// Linux reference: https://man7.org/linux/man-pages/man2/msgrcv.2.html
+///
+/// Receives a message selected by `msgtyp` from the queue `msqid` into the buffer at `msgp`,
+/// which has room for the 32-bit type and `msgsz` bytes of text.
+///
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `msqid_arg` - The id of the queue.
+/// * `msgp_arg` - Host pointer to the buffer.
+/// * `msgsz_arg` - The room for text in the buffer.
+/// * `msgtyp_arg` - Which message to take: the first, the first of a type, or the first of the
+///   lowest type up to a bound.
+/// * `msgflg_arg` - `IPC_NOWAIT`, `MSG_EXCEPT` and `MSG_NOERROR`.
+///
+/// ## Returns:
+/// The number of bytes of text received, or a negative errno on failure.
+pub extern "C" fn msgrcv_syscall(
+    cageid: u64,
+    msqid_arg: u64,
+    msqid_cageid: u64,
+    msgp_arg: u64,
+    _msgp_cageid: u64,
+    msgsz_arg: u64,
+    msgsz_cageid: u64,
+    msgtyp_arg: u64,
+    msgtyp_cageid: u64,
+    msgflg_arg: u64,
+    msgflg_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let msqid = sc_convert_sysarg_to_i32(msqid_arg, msqid_cageid, cageid);
+    let msgsz = sc_convert_sysarg_to_i32(msgsz_arg, msgsz_cageid, cageid);
+    let msgtyp = sc_convert_sysarg_to_i32(msgtyp_arg, msgtyp_cageid, cageid);
+    let msgflg = sc_convert_sysarg_to_i32(msgflg_arg, msgflg_cageid, cageid);
+    if !sc_unusedarg(arg6, arg6_cageid) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "msgrcv_syscall"
+        );
+    }
+
+    if msgsz < 0 {
+        return syscall_error(Errno::EINVAL, "msgrcv", "negative message size");
+    }
+    if msgp_arg == 0 {
+        return syscall_error(Errno::EFAULT, "msgrcv", "buffer is null");
+    }
+    let text = unsafe { std::slice::from_raw_parts_mut((msgp_arg + 4) as *mut u8, msgsz as usize) };
+    match cage::msgrcv(cageid, msqid, text, msgtyp, msgflg) {
+        Ok((mtype, len)) => {
+            unsafe { std::ptr::write_unaligned(msgp_arg as *mut i32, mtype) };
+            len as i32
+        }
+        Err(e) => syscall_error(e, "msgrcv", "could not receive message"),
+    }
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/rmdir.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/msgctl.2.html
 ///
-/// Linux `rmdir()` syscall removes a directory, which must be empty. Since path seen by user is different
-/// from actual path on host, we need to convert the path first. RawPOSIX doesn't have any other operations,
-/// so all operations will be handled by host. RawPOSIX does error handling for this syscall.
+/// Performs the control operation `cmd` on the message queue `msqid`. glibc adds `IPC_64` to
+/// `cmd`, which is ignored.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - path_arg: pointer to a pathname naming the directory to be removed (user's perspective)
-///     - path_cageid: cage identifier for the path argument
-///     - arg2, arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `msqid_arg` - The id of the queue.
+/// * `cmd_arg` - `IPC_STAT`, `IPC_SET` or `IPC_RMID`.
+/// * `buf_arg` - Host pointer to the `struct msqid_ds` for `IPC_STAT` and `IPC_SET`.
 ///
 /// ## Returns:
-///     - 0 on success.
-///     - -1 on error, with errno set to indicate the error.
-pub extern "C" fn rmdir_syscall(
+/// 0 on success, or a negative errno on failure.
+pub extern "C" fn msgctl_syscall(
     cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    arg2: u64,
-    arg2_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
+    msqid_arg: u64,
+    msqid_cageid: u64,
+    cmd_arg: u64,
+    cmd_cageid: u64,
+    buf_arg: u64,
+    _buf_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -4068,61 +7333,54 @@ pub extern "C" fn rmdir_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "rmdir", "path conversion failed"),
-    };
-
-    // would sometimes check, sometimes be a no-op depending on the compiler settings
-    if !(sc_unusedarg(arg2, arg2_cageid)
-        && sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
+    let msqid = sc_convert_sysarg_to_i32(msqid_arg, msqid_cageid, cageid);
+    let cmd = sc_convert_sysarg_to_i32(cmd_arg, cmd_cageid, cageid) & !IPC_64;
+    if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "rmdir_syscall"
+            "msgctl_syscall"
         );
     }
 
-    // Call the kernel rmdir function
-    let ret = unsafe { libc::rmdir(path.as_ptr()) };
-
-    // Error handling
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "rmdir");
+    let buf = (buf_arg != 0).then(|| unsafe { &mut *(buf_arg as *mut MsqidStruct) });
+    match cage::msgctl(msqid, cmd, buf) {
+        Ok(()) => 0,
+        Err(e) => syscall_error(e, "msgctl", "message queue control operation failed"),
     }
+}
 
-    ret
+/// Reads the NUL-terminated string at host address `addr`, or returns `None` if `addr` is null.
+fn host_cstr<'a>(addr: u64) -> Option<&'a [u8]> {
+    if addr == 0 {
+        return None;
+    }
+    Some(unsafe { std::ffi::CStr::from_ptr(addr as *const libc::c_char) }.to_bytes())
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/chmod.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/memfd_create.2.html
 ///
-/// Linux `chmod()` syscall changes the permissions of a file. Since path seen by user is different from
-/// actual path on host, we need to convert the path first. The mode argument specifies the permissions
-/// to be assigned to the file and is passed directly to the kernel after type conversion.
+/// Creates an anonymous file in host memory and returns a descriptor for it. `MFD_CLOEXEC` sets
+/// close-on-exec on the descriptor, and `MFD_ALLOW_SEALING` lets `fcntl(F_ADD_SEALS)` seal the
+/// file; the seal commands go to the host like other `fcntl` commands. A `MAP_SHARED` mapping of
+/// the file is shared with forked cages, like any file mapping (see `fork_vmmap`).
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - path_arg: pointer to a pathname naming the file (user's perspective)
-///     - path_cageid: cage identifier for the path argument
-///     - mode_arg: the new file permissions (user's perspective)
-///     - mode_cageid: cage identifier for the mode argument
-///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `name_arg` - Host pointer to the name of the file, which only shows in `/proc/self/maps`.
+/// * `flags_arg` - `MFD_CLOEXEC` and `MFD_ALLOW_SEALING`. Huge pages and the exec seals are not
+///   supported and fail with `EINVAL`.
 ///
 /// ## Returns:
-///     - 0 on success.
-///     - -1 on error, with errno set to indicate the error.
-pub extern "C" fn chmod_syscall(
+/// The new file descriptor on success, or a negative errno on failure.
+pub extern "C" fn memfd_create_syscall(
     cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    mode_arg: u64,
-    mode_cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
     arg3: u64,
     arg3_cageid: u64,
     arg4: u64,
@@ -4132,14 +7390,7 @@ pub extern "C" fn chmod_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "chmod", "path conversion failed"),
-    };
-    let mode = sc_convert_sysarg_to_u32(mode_arg, mode_cageid, cageid);
-
-    // would sometimes check, sometimes be a no-op depending on the compiler settings
+    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_cageid, cageid);
     if !(sc_unusedarg(arg3, arg3_cageid)
         && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
@@ -4147,268 +7398,291 @@ pub extern "C" fn chmod_syscall(
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "chmod_syscall"
+            "memfd_create_syscall"
         );
     }
 
-    // Call the kernel chmod function
-    let ret = unsafe { libc::chmod(path.as_ptr(), mode) };
-
-    // Error handling
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "chmod");
+    let name = match host_cstr(name_arg) {
+        Some(name) => name,
+        None => return syscall_error(Errno::EFAULT, "memfd_create", "name is null"),
+    };
+    if name.len() > MFD_NAME_MAX {
+        return syscall_error(Errno::EINVAL, "memfd_create", "name is too long");
+    }
+    if flags & !(MFD_CLOEXEC | MFD_ALLOW_SEALING) != 0 {
+        return syscall_error(Errno::EINVAL, "memfd_create", "unsupported flags");
     }
 
-    ret
+    // The host fd is always close-on-exec; the cage's flag is kept by fdtables
+    let cname = std::ffi::CString::new(name).unwrap();
+    let kernel_fd = unsafe {
+        libc::memfd_create(
+            cname.as_ptr(),
+            libc::MFD_CLOEXEC | (flags & MFD_ALLOW_SEALING),
+        )
+    };
+    if kernel_fd < 0 {
+        return handle_errno(get_errno(), "memfd_create");
+    }
+    let should_cloexec = flags & MFD_CLOEXEC != 0;
+    match fdtables::get_unused_virtual_fd(
+        cageid,
+        FDKIND_KERNEL,
+        kernel_fd as u64,
+        should_cloexec,
+        0,
+    ) {
+        Ok(vfd) => vfd as i32,
+        Err(e) => {
+            // EMFILE or ENFILE from fdtables; don't leak the kernel fd
+            unsafe { libc::close(kernel_fd) };
+            handle_errno(e as i32, "memfd_create")
+        }
+    }
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/fchmod.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/mq_open.2.html
 ///
-/// Linux `fchmod()` syscall changes the permissions of an open file referred to by file descriptor `fd`.
-/// Since we implement a file descriptor management subsystem (called `fdtables`), we first translate the
-/// virtual file descriptor to the corresponding kernel file descriptor, then convert the mode from cage
-/// memory before invoking the kernel's `libc::fchmod()` function.
+/// Opens the POSIX message queue `name`, creating it if `oflag` has `O_CREAT`. glibc strips the
+/// leading slash from the name. Queues are kept by `crate::mqueue` and shared by all cages; the
+/// new descriptor is pollable and always close-on-exec.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
-///     - mode_arg: the new file permissions to be applied to the file
-///     - mode_cageid: cage identifier for the mode argument
-///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `name_arg` - Host pointer to the queue name.
+/// * `oflag_arg` - The access mode, with `O_CREAT`, `O_EXCL` and `O_NONBLOCK`.
+/// * `mode_arg` - The permission bits of a new queue, masked by the cage's umask.
+/// * `attr_arg` - Host pointer to the `struct mq_attr` of a new queue, or 0 for defaults.
 ///
 /// ## Returns:
-///     - 0 on success.
-///     - -1 on error, with errno set to indicate the error.
-pub extern "C" fn fchmod_syscall(
+/// The new file descriptor on success, or a negative errno on failure.
+pub extern "C" fn mq_open_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    oflag_arg: u64,
+    oflag_cageid: u64,
     mode_arg: u64,
     mode_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
+    attr_arg: u64,
+    _attr_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "fchmod");
+    let oflag = sc_convert_sysarg_to_i32(oflag_arg, oflag_cageid, cageid);
+    let mode = sc_convert_sysarg_to_u32(mode_arg, mode_cageid, cageid);
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "mq_open_syscall"
+        );
     }
 
-    let mode = sc_convert_sysarg_to_u32(mode_arg, mode_cageid, cageid);
+    let name = match host_cstr(name_arg) {
+        Some(name) => name,
+        None => return syscall_error(Errno::EFAULT, "mq_open", "name is null"),
+    };
+    let attr = if oflag & O_CREAT != 0 {
+        mqueue::guest_attr(cageid, attr_arg)
+    } else {
+        None
+    };
+    let mode = apply_cage_umask(cageid, mode);
+    match mqueue::open(cageid, name, oflag, mode, attr.as_ref()) {
+        Ok(fd) => fd as i32,
+        Err(e) => syscall_error(e, "mq_open", "could not open message queue"),
+    }
+}
 
-    if !(sc_unusedarg(arg3, arg3_cageid)
+/// Linux reference: https://man7.org/linux/man-pages/man2/mq_unlink.2.html
+///
+/// Removes the POSIX message queue `name`. Descriptors already open keep working until closed.
+///
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `name_arg` - Host pointer to the queue name, without its leading slash.
+///
+/// ## Returns:
+/// 0 on success, or a negative errno on failure.
+pub extern "C" fn mq_unlink_syscall(
+    _cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
         && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "fchmod_syscall"
+            "mq_unlink_syscall"
         );
     }
 
-    let ret = unsafe { libc::fchmod(kernel_fd, mode) };
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "fchmod");
+    let name = match host_cstr(name_arg) {
+        Some(name) => name,
+        None => return syscall_error(Errno::EFAULT, "mq_unlink", "name is null"),
+    };
+    match mqueue::unlink(name) {
+        Ok(()) => 0,
+        Err(e) => syscall_error(e, "mq_unlink", "could not remove message queue"),
     }
-    ret
 }
 
-//------------------------------------UTIMENSAT SYSCALL------------------------------------
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/utimensat.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/mq_timedsend.2.html
 ///
-/// `utimensat` updates the access and modification times of a file with
-/// nanosecond precision.  Used by `touch`, `cp -p`, and any test that
-/// asserts on file timestamps.  `futimens(fd, ts)` is implemented in
-/// glibc by calling `utimensat(fd, NULL, ts, 0)`, so a single handler
-/// here covers both.
+/// Sends the `msg_len` bytes at `msg_ptr` with priority `msg_prio` to the queue behind
+/// `mqdes`, waiting for room until the absolute CLOCK_REALTIME `abs_timeout` unless the
+/// descriptor is nonblocking. glibc's `mq_send` passes a null timeout.
 ///
-/// ## Arguments:
-/// - `dirfd`: directory fd; `AT_FDCWD` means "relative to the cage cwd";
-///   when `pathname` is NULL, `dirfd` itself is the target file (futimens).
-/// - `pathname`: path relative to `dirfd`, or NULL for the futimens form.
-/// - `times`: pointer to two `timespec` values [atime, mtime], or NULL
-///   meaning "set both to now".  `tv_nsec` may be the special values
-///   `UTIME_NOW` or `UTIME_OMIT`.
-/// - `flags`: 0 or `AT_SYMLINK_NOFOLLOW`.
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `mqdes_arg` - The message queue descriptor.
+/// * `msg_arg` - Host pointer to the message.
+/// * `len_arg` - The length of the message.
+/// * `prio_arg` - The priority of the message.
+/// * `timeout_arg` - Host pointer to a `struct __timespec64`, or 0 to wait forever.
 ///
 /// ## Returns:
-/// - 0 on success.
-/// - negated errno on failure.
-pub extern "C" fn utimensat_syscall(
+/// 0 on success, or a negative errno on failure.
+pub extern "C" fn mq_timedsend_syscall(
     cageid: u64,
-    dirfd_arg: u64,
-    dirfd_cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    times_arg: u64,
-    _times_cageid: u64,
-    flags_arg: u64,
-    flags_cageid: u64,
-    arg5: u64,
-    arg5_cageid: u64,
+    mqdes_arg: u64,
+    mqdes_cageid: u64,
+    msg_arg: u64,
+    _msg_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    prio_arg: u64,
+    prio_cageid: u64,
+    timeout_arg: u64,
+    _timeout_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let virtual_fd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
-    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
-
-    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    let prio = sc_convert_sysarg_to_u32(prio_arg, prio_cageid, cageid);
+    if !sc_unusedarg(arg6, arg6_cageid) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "utimensat_syscall"
+            "mq_timedsend_syscall"
         );
     }
 
-    // Glibc's __utimensat64_helper has already copied the cage's
-    // __timespec64 into a kernel-ABI layout { i64 tv_sec; i64 tv_nsec; }
-    // and passed that translated pointer through, so casting directly
-    // matches the host kernel's struct timespec.  May be NULL ("now").
-    let times = times_arg as *const libc::timespec;
-
-    // Two cases for the path/dirfd combination:
-    //   (a) pathname == NULL:  futimens form — operate on the file
-    //       referred to by dirfd.  dirfd must be a real fd, not AT_FDCWD.
-    //   (b) pathname != NULL:  utimensat form — resolve via cage's path
-    //       conversion (handles cwd + chroot), then optionally relative
-    //       to dirfd if not AT_FDCWD.
-    let ret = if path_arg == 0 {
-        if virtual_fd == AT_FDCWD {
-            return syscall_error(Errno::EBADF, "utimensat", "AT_FDCWD with NULL path");
-        }
-        let kernel_fd = convert_fd_to_host(virtual_fd as u64, dirfd_cageid, cageid);
-        if kernel_fd < 0 {
-            return handle_errno(-kernel_fd, "utimensat");
-        }
-        // futimens(): glibc routes futimens(fd, ts) as utimensat(fd, NULL, ts, 0).
-        // The kernel utimensat syscall accepts NULL path with a real fd, so we
-        // forward to libc::futimens (its userspace wrapper around the same).
-        unsafe { libc::futimens(kernel_fd, times) }
+    let desc = match mqueue::lookup(mqdes_cageid, mqdes_arg) {
+        Some(desc) => desc,
+        None => return syscall_error(Errno::EBADF, "mq_timedsend", "not a message queue"),
+    };
+    if len > 0 && msg_arg == 0 {
+        return syscall_error(Errno::EFAULT, "mq_timedsend", "message is null");
+    }
+    let msg = if len == 0 {
+        &[][..]
     } else {
-        let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-            Ok(p) => p,
-            Err(e) => return syscall_error(e, "utimensat", "path conversion failed"),
-        };
-        if virtual_fd == AT_FDCWD {
-            unsafe { libc::utimensat(AT_FDCWD, path.as_ptr(), times, flags) }
-        } else {
-            let kernel_fd = convert_fd_to_host(virtual_fd as u64, dirfd_cageid, cageid);
-            if kernel_fd < 0 {
-                return handle_errno(-kernel_fd, "utimensat");
-            }
-            unsafe { libc::utimensat(kernel_fd, path.as_ptr(), times, flags) }
-        }
+        unsafe { std::slice::from_raw_parts(msg_arg as *const u8, len) }
     };
-
-    if ret < 0 {
-        return handle_errno(get_errno(), "utimensat");
+    match desc.send(cageid, msg, prio, futex::guest_timespec(timeout_arg)) {
+        Ok(()) => 0,
+        Err(e) => syscall_error(e, "mq_timedsend", "could not send message"),
     }
-    ret
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/getcwd.2.html
-///
-/// `getcwd_syscall` retrieves the current working directory for the calling Cage.
+/// Linux reference: https://man7.org/linux/man-pages/man2/mq_timedreceive.2.html
 ///
-/// Unlike directly calling `libc::getcwd`, this implementation uses the Cage's
-/// own `cwd` field maintained inside the Cage struct. Each Cage has its own
-/// logical working directory, which may differ from the host kernel's notion
-/// of the filesystem root. Because of this mismatch between the Cage's root
-/// and the kernel root, we cannot delegate to `libc::getcwd` and must instead
-/// return the Cage-specific `cwd` value.
+/// Receives the oldest message of the highest priority from the queue behind `mqdes` into the
+/// `msg_len` bytes at `msg_ptr`, waiting for one until the absolute CLOCK_REALTIME
+/// `abs_timeout` unless the descriptor is nonblocking. glibc's `mq_receive` passes a null
+/// timeout.
 ///
-/// ## Input:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - buf_arg: pointer to a buffer where the current working directory path will be stored (user's perspective)
-///     - size_arg: the size of the buffer in bytes
-///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `mqdes_arg` - The message queue descriptor.
+/// * `msg_arg` - Host pointer to the buffer.
+/// * `len_arg` - The size of the buffer.
+/// * `prio_arg` - Host pointer to where the priority goes, or 0.
+/// * `timeout_arg` - Host pointer to a `struct __timespec64`, or 0 to wait forever.
 ///
 /// ## Returns:
-///     - On success, returns 0 after copying the current working directory path to the buffer.
-///     - On error, returns -1 and errno is set to indicate the error.
-pub extern "C" fn getcwd_syscall(
+/// The length of the message on success, or a negative errno on failure.
+pub extern "C" fn mq_timedreceive_syscall(
     cageid: u64,
-    buf_arg: u64,
-    _buf_cageid: u64,
-    size_arg: u64,
-    size_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
-    arg5: u64,
-    arg5_cageid: u64,
+    mqdes_arg: u64,
+    mqdes_cageid: u64,
+    msg_arg: u64,
+    _msg_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    prio_arg: u64,
+    _prio_cageid: u64,
+    timeout_arg: u64,
+    _timeout_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let buf = buf_arg as *mut u8;
-
-    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
-    if size == 0 {
-        return syscall_error(Errno::EINVAL, "getcwd", "Size cannot be zero");
-    }
-
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    if !sc_unusedarg(arg6, arg6_cageid) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "getcwd_syscall"
+            "mq_timedreceive_syscall"
         );
     }
 
-    let cage = get_cage(cageid).unwrap();
-    let cwd_container = cage.cwd.read();
-    let path = cwd_container.to_str().unwrap();
-    // The required size includes the null terminator
-    let required_size = path.len() + 1;
-    if required_size > size as usize {
-        return syscall_error(Errno::ERANGE, "getcwd_syscall", "Invalid buffer size");
-    }
-    unsafe {
-        ptr::copy(path.as_ptr(), buf, path.len());
-        *buf.add(path.len()) = 0;
+    let desc = match mqueue::lookup(mqdes_cageid, mqdes_arg) {
+        Some(desc) => desc,
+        None => return syscall_error(Errno::EBADF, "mq_timedreceive", "not a message queue"),
+    };
+    if msg_arg == 0 {
+        return syscall_error(Errno::EFAULT, "mq_timedreceive", "buffer is null");
+    }
+    let buf = unsafe { std::slice::from_raw_parts_mut(msg_arg as *mut u8, len) };
+    match desc.receive(cageid, buf, futex::guest_timespec(timeout_arg)) {
+        Ok((n, prio)) => {
+            if prio_arg != 0 {
+                unsafe { std::ptr::write_unaligned(prio_arg as *mut u32, prio) };
+            }
+            n as i32
+        }
+        Err(e) => syscall_error(e, "mq_timedreceive", "could not receive message"),
     }
-
-    // std::copy guarantees it copies exactly path.len() bytes.
-    let bytes_written: i32 = path.len().try_into().unwrap();
-
-    bytes_written
 }
 
-/// Truncate a file to a specified length
+/// Linux reference: https://man7.org/linux/man-pages/man2/mq_notify.2.html
 ///
-/// ## Arguments:
-///     - cageid: current cage identifier
-///     - path_arg: pointer to the pathname of the file to truncate
-///     - path_cageid: cage identifier for the path argument
-///     - length_arg: the new length to truncate the file to
-///     - length_cageid: cage identifier for the length argument
-///     - arg3, arg4, arg5, arg6: additional arguments which are expected to be unused
+/// Registers the caller to be notified when a message arrives on the empty queue behind
+/// `mqdes`, or unregisters it if `sevp` is null. For `SIGEV_THREAD`, glibc passes its netlink
+/// socket in `sigev_signo` and the host address of the cookie to send on it as the third
+/// argument, which the kernel would read through `sigev_value` itself.
+///
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `mqdes_arg` - The message queue descriptor.
+/// * `sevp_arg` - Host pointer to the `struct sigevent`, or 0.
+/// * `cookie_arg` - Host pointer to the `SIGEV_THREAD` cookie, or 0.
 ///
 /// ## Returns:
-///     - 0 on success.
-///     - -1 on error, with errno set to indicate the error.
-pub extern "C" fn truncate_syscall(
+/// 0 on success, or a negative errno on failure.
+pub extern "C" fn mq_notify_syscall(
     cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    length_arg: u64,
-    length_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
+    mqdes_arg: u64,
+    _mqdes_cageid: u64,
+    sevp_arg: u64,
+    _sevp_cageid: u64,
+    cookie_arg: u64,
+    _cookie_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -4416,122 +7690,122 @@ pub extern "C" fn truncate_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Validate unused arguments
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
+    if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "truncate_syscall"
+            "mq_notify_syscall"
         );
     }
 
-    // Type conversion
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "truncate", "path conversion failed"),
-    };
-    let length = sc_convert_sysarg_to_i64(length_arg, length_cageid, cageid);
-
-    // Call libc truncate
-    let ret = unsafe { libc::truncate(path.as_ptr() as *const i8, length) };
-
-    if ret == -1 {
-        let errno = get_errno();
-        return handle_errno(errno, "truncate");
+    match mqueue::notify(cageid, mqdes_arg, sevp_arg, cookie_arg) {
+        Ok(()) => 0,
+        Err(e) => syscall_error(e, "mq_notify", "could not change notification"),
     }
-
-    0
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/clock_nanosleep.2.html
-///
-/// `nanosleep_time64_syscall` suspends execution of the calling Cage for
-/// the time interval specified in the requested `timespec` structure.
+/// Linux reference: https://man7.org/linux/man-pages/man3/mq_getattr.3.html
 ///
-/// ## Implementation Details:
-/// - The `req` (requested time) and `rem` (remaining time) pointers are
-///   converted from Wasm linear memory to host addresses using
-///   `sc_convert_buf`.
-/// - Unused arguments `arg5` and `arg6` are validated with `sc_unusedarg`.
-/// - The underlying `SYS_clock_nanosleep` syscall is invoked directly.  
-/// - On error, `errno` is retrieved and normalized through `handle_errno()`.
+/// Backs `mq_getattr` and `mq_setattr`: stores the attributes of the queue behind `mqdes` in
+/// `omqstat`, then applies the `O_NONBLOCK` flag of `mqstat`. Either pointer may be null.
 ///
-/// ## Arguments:
-/// - `cageid`: Identifier of the calling Cage
-/// - `clockid_arg`: The clock against which the sleep interval is measured
-/// - `flags_arg`: Flags controlling sleep behavior
-/// - `req_arg`: Address of the requested sleep interval (`timespec`)
-/// - `rem_arg`: Address of the remaining interval (`timespec`) if interrupted
+/// ## Arguments
+/// * `cageid` - The ID of the calling cage.
+/// * `mqdes_arg` - The message queue descriptor.
+/// * `new_arg` - Host pointer to the new `struct mq_attr`, or 0.
+/// * `old_arg` - Host pointer to where the old attributes go, or 0.
 ///
 /// ## Returns:
-///     - 0 on success.
-///     - -1 on failure, with errno set appropriately.
-pub extern "C" fn nanosleep_time64_syscall(
+/// 0 on success, or a negative errno on failure.
+pub extern "C" fn mq_getsetattr_syscall(
     cageid: u64,
-    clockid_arg: u64,
-    clockid_cageid: u64,
-    flags_arg: u64,
-    flags_cageid: u64,
-    req_arg: u64,
-    req_cageid: u64,
-    rem_arg: u64,
-    rem_cageid: u64,
+    mqdes_arg: u64,
+    _mqdes_cageid: u64,
+    new_arg: u64,
+    _new_cageid: u64,
+    old_arg: u64,
+    _old_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let clockid = sc_convert_sysarg_to_u32(clockid_arg, clockid_cageid, cageid);
-    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
-    let req = sc_convert_buf(req_arg, req_cageid, cageid);
-    let rem = sc_convert_buf(rem_arg, rem_cageid, cageid);
-    // would sometimes check, sometimes be a no-op depending on the compiler settings
-    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "nanosleep_time64_syscall"
+            "mq_getsetattr_syscall"
         );
     }
-    let ret = unsafe { syscall(SYS_clock_nanosleep, clockid, flags, req, rem) as i32 };
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "nanosleep");
+
+    let desc = match mqueue::lookup(cageid, mqdes_arg) {
+        Some(desc) => desc,
+        None => return syscall_error(Errno::EBADF, "mq_getsetattr", "not a message queue"),
+    };
+    match desc.getsetattr(mqueue::guest_attr(cageid, new_arg).as_ref()) {
+        Ok(old) => {
+            mqueue::put_guest_attr(cageid, old_arg, &old);
+            0
+        }
+        Err(e) => syscall_error(e, "mq_getsetattr", "invalid attributes"),
     }
-    ret
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/mprotect.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/getrandom.2.html
 ///
-/// Linux `mprotect()` syscall changes the protection of memory pages. It sets the protection
-/// bits for memory pages in the range [addr, addr+len-1] to prot.
+/// Implements the `getrandom(2)` syscall for a cage. This wrapper converts and
+/// validates all caller-provided arguments, resolves the user buffer pointer into
+/// a host pointer, enforces cage-ownership consistency, and then directly invokes
+/// the host kernel’s `SYS_getrandom` via `syscall()`. Any error from the host is
+/// converted into a cage-appropriate errno using `handle_errno`.
 ///
-/// ## Arguments:
-///     - cageid: current cage identifier
-///     - addr_arg: pointer to the start of the memory region to change protection for
-///     - addr_cageid: cage ID for addr argument validation
-///     - len_arg: length of the memory region in bytes
-///     - len_cageid: cage ID for len argument validation  
-///     - prot_arg: new protection flags (PROT_READ, PROT_WRITE, PROT_EXEC, PROT_NONE)
-///     - prot_cageid: cage ID for prot argument validation
-///     - arg4, arg4_cageid: unused argument and its cage ID
-///     - arg5, arg5_cageid: unused argument and its cage ID
-///     - arg6, arg6_cageid: unused argument and its cage ID
+/// 1) **Parse & validate args**
+///    - `buf_arg` is interpreted as a user pointer and converted to a host pointer
+///      using `sc_convert_uaddr_to_host`, ensuring it belongs to `cageid`.
+///    - `buflen_arg` and `flags_arg` are converted to 32-bit values via
+///      `sc_convert_sysarg_to_u32`, validating cage ownership and rejecting
+///      malformed arguments.
+///    - Unused arguments `arg4..arg6` must be zero; if not, they cause a panic
+///      (enforcing a strict syscall ABI).
 ///
-/// ## Returns:
-///     - 0 on success
-///     - -1 on error with appropriate errno set
-pub extern "C" fn mprotect_syscall(
+/// 2) **Invoke host syscall**
+///    - Calls `syscall(SYS_getrandom, buf, buflen, flags)` unsafely to request
+///      random bytes from the host kernel.
+///    - On negative return, retrieves `errno` using `get_errno()` and converts it
+///      to a standardized cage-side error with `handle_errno`.
+///
+/// 3) **Return value**
+///    - On success, returns the number of random bytes written (0 ≤ n ≤ buflen).
+///    - On failure, returns a negative errno (`-EINTR`, `-EAGAIN`, `-EINVAL`, etc.).
+///
+/// ## Arguments
+/// * `cageid`            – Cage issuing the syscall; used to validate all arguments.
+/// * `buf_arg`           – Raw user pointer to the destination buffer.
+/// * `buf_arg_cageid`    – Cage ID associated with `buf_arg`.
+/// * `buflen_arg`        – Number of random bytes requested (raw u64).
+/// * `buflen_arg_cageid` – Cage ID associated with `buflen_arg`.
+/// * `flags_arg`         – `getrandom` flags (e.g., `GRND_NONBLOCK`) as raw u64.
+/// * `flags_arg_cageid`  – Cage ID associated with `flags_arg`.
+/// * `arg4..arg6`        – Unused placeholder arguments for syscall ABI; must be zero.
+/// * `arg4_cageid..arg6_cageid` – Cage IDs for unused arguments; checked but unused.
+///
+/// ## Returns
+/// On success: number of bytes written (positive `i32`).  
+/// On failure: a negative errno from `handle_errno`.
+pub extern "C" fn getrandom_syscall(
     cageid: u64,
-    addr_arg: u64,
-    _addr_cageid: u64,
-    len_arg: u64,
-    len_cageid: u64,
-    prot_arg: u64,
-    prot_cageid: u64,
+    buf_arg: u64,
+    _buf_arg_cageid: u64,
+    buflen_arg: u64,
+    buflen_arg_cageid: u64,
+    flags_arg: u64,
+    flags_arg_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -4539,10 +7813,9 @@ pub extern "C" fn mprotect_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let addr = addr_arg as *mut u8;
-    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
-    let prot = sc_convert_sysarg_to_i32(prot_arg, prot_cageid, cageid);
+    let buf = buf_arg;
+    let buflen = sc_convert_sysarg_to_u32(buflen_arg, buflen_arg_cageid, cageid);
+    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_arg_cageid, cageid);
 
     // Validate unused args
     if !(sc_unusedarg(arg4, arg4_cageid)
@@ -4551,77 +7824,42 @@ pub extern "C" fn mprotect_syscall(
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "mprotect_syscall"
+            "getrandom_syscall"
         );
     }
 
-    // Validate protection flags
-    let valid_prot = PROT_READ | PROT_WRITE | PROT_EXEC | PROT_NONE;
-    if prot & !valid_prot != 0 {
-        return syscall_error(Errno::EINVAL, "mprotect", "Invalid protection flags");
-    }
-
-    // For security, we don't allow PROT_EXEC in lind-wasm
-    if prot & PROT_EXEC != 0 {
-        return syscall_error(Errno::EINVAL, "mprotect", "PROT_EXEC is not allowed");
-    }
-
-    // Round length up to page boundary (mprotect operates on whole pages)
-    let rounded_length = round_up_page(len as u64) as usize;
-
-    // Call the kernel mprotect
-    let ret = unsafe { libc::mprotect(addr as *mut c_void, rounded_length, prot) };
+    let ret = unsafe { getrandom(buf as *mut c_void, buflen.try_into().unwrap(), flags) };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "mprotect");
-    }
-
-    // Update vmmap to reflect the new protection flags
-    // Skip if length is zero (no pages to update) — Linux treats len=0 as a no-op
-    if rounded_length > 0 {
-        let cage = get_cage(cageid).unwrap();
-        let mut vmmap = cage.vmmap.write();
-        let user_addr = vmmap.sys_to_user(addr as usize) as u32;
-        vmmap.change_prot(
-            user_addr >> PAGESHIFT,
-            (rounded_length >> PAGESHIFT) as u32,
-            prot,
-        );
+        return handle_errno(errno, "getrandom");
     }
 
-    ret
+    // convert isize to i32 safely, as ret shouldn't be larger than 32-bit
+    // due to buflen being u32
+    ret.try_into().unwrap()
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/ioctl.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/symlink.2.html
 ///
-/// Linux `ioctl()` syscall manipulates the underlying device parameters of special files.
-/// In particular, many operating characteristics of character special files (e.g., terminals)
-/// may be controlled with ioctl() operations. The argument fd must be an open file descriptor.
+/// Creates a symbolic link at `linkpath` that points to `target`.
 ///
-/// ## Arguments:
-///     - cageid: current cage identifier
-///     - vfd_arg: virtual file descriptor (must be an open file descriptor)
-///     - vfd_cageid: cage ID for vfd argument validation
-///     - req_arg: device-dependent operation code
-///     - req_cageid: cage ID for req argument validation
-///     - ptrunion_arg: pointer to memory (untyped pointer, traditionally char *argp)
-///     - ptrunion_cageid: cage ID for ptrunion argument validation
-///     - arg4, arg4_cageid: unused argument and its cage ID
-///     - arg5, arg5_cageid: unused argument and its cage ID
-///     - arg6, arg6_cageid: unused argument and its cage ID
+/// ## Arguments
+/// * `cageid` – The ID of the calling cage.
+/// * `target_arg` / `target_arg_cageid` – Pointer to the target path string.
+/// * `linkpath_arg` / `linkpath_arg_cageid` – Pointer to the path where the
+///   symbolic link should be created.
 ///
-/// ## Returns:
-///     - Usually, on success zero is returned. A few ioctl() operations use the return value
-///       as an output parameter and return a nonnegative value on success.
-///     - On error, -1 is returned, and errno is set to indicate the error.
-pub extern "C" fn ioctl_syscall(
+/// ## Returns
+/// * `0` on success.
+/// * Negative errno (`EEXIST`, `ENOENT`, `EFAULT`, etc.) on failure.
+pub extern "C" fn symlink_syscall(
     cageid: u64,
-    vfd_arg: u64,
-    _vfd_cageid: u64,
-    req_arg: u64,
-    req_cageid: u64,
-    ptrunion_arg: u64,
-    _ptrunion_cageid: u64,
+    target_arg: u64,
+    target_cageid: u64,
+    linkpath_arg: u64,
+    linkpath_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -4629,88 +7867,71 @@ pub extern "C" fn ioctl_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion
-    let ptrunion = ptrunion_arg as *mut u8;
-    let req = sc_convert_sysarg_to_u32(req_arg, req_cageid, cageid);
+    // The symlink target is stored as-is and should NOT be path-normalized.
+    // Normalizing would resolve relative paths against cwd, but relative symlink
+    // targets are meant to resolve relative to the symlink's location at dereference
+    // time, not at creation time. So we use get_cstr directly instead of
+    // sc_convert_path_to_host.
+    let target = match get_cstr(target_arg) {
+        Ok(path) => path,
+        Err(_) => return syscall_error(Errno::EFAULT, "symlink", "target path conversion failed"),
+    };
 
-    // Validate unused args
-    if !(sc_unusedarg(arg4, arg4_cageid)
+    // The linkpath is where the symlink is created, so it does need full path resolution.
+    let linkpath = match sc_convert_path_to_host(linkpath_arg, linkpath_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "symlink", "linkpath conversion failed"),
+    };
+
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "ioctl_syscall"
+            "symlink_syscall"
         );
     }
 
-    // handle FIOCLEX, set close_on_exec flag for the file descriptor
-    if req as u64 == FIOCLEX as u64 {
-        let ret = match fdtables::set_cloexec(cageid, vfd_arg, true) {
-            Ok(_) => 0,
-            Err(_) => syscall_error(Errno::EBADF, "ioctl", "Bad File Descriptor"),
-        };
-
-        return ret;
-    }
-
-    // Besides FIOCLEX, we only support FIONBIO, FIOASYNC, FIONREAD, and TIOCGWINSZ right now.
-    // Return error for unsupported requests.
-    if req != FIONBIO && req != FIOASYNC && req != FIONREAD && req != TIOCGWINSZ {
-        lind_debug_panic!("Lind unsupported ioctl request");
-    }
-
-    let wrappedvfd = fdtables::translate_virtual_fd(cageid, vfd_arg);
-    if wrappedvfd.is_err() {
-        return syscall_error(Errno::EBADF, "ioctl", "Bad File Descriptor");
-    }
-
-    let vfd = wrappedvfd.unwrap();
-
-    let ret = unsafe {
-        libc::ioctl(
-            vfd.underfd as i32,
-            req as libc::Ioctl,
-            ptrunion as *mut c_void,
-        )
-    };
+    let ret = unsafe { libc::symlink(target.as_ptr() as *const libc::c_char, linkpath.as_ptr()) };
 
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "ioctl");
+        return handle_errno(errno, "symlink");
     }
-    return ret;
+
+    ret
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/flock.2.html
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/symlink.2.html
 ///
-/// `Flock()` syscall applies or removes an advisory lock on an open file. We first translate the virtual file descriptor to the
-/// corresponding kernel file descriptor, then convert the operation flags from cage memory before invoking
-/// the kernel's `libc::flock()` function.
+/// Creates a symbolic link at `linkpath` relative to `dirfd` that points to `target`.
+/// This is the `dirfd`-relative variant of `symlink`.
 ///
-/// ## Arguments:
-///     This call will have one cageid indicating the current cage, and several regular arguments similar to Linux:
-///     - cageid: current cage identifier
-///     - vfd_arg: the virtual file descriptor from the RawPOSIX environment
-///     - vfd_cageid: cage ID for vfd argument validation
-///     - op_arg: operation flags (LOCK_SH, LOCK_EX, LOCK_UN, optionally ORed with LOCK_NB)
-///     - op_cageid: cage ID for op argument validation
-///     - arg3, arg3_cageid: unused argument and its cage ID
-///     - arg4, arg4_cageid: unused argument and its cage ID
-///     - arg5, arg5_cageid: unused argument and its cage ID
-///     - arg6, arg6_cageid: unused argument and its cage ID
+/// If `dirfd` is `AT_FDCWD`, the call behaves identically to `symlink_syscall`,
+/// resolving `linkpath` relative to the current working directory.
+/// Otherwise, `linkpath` is resolved relative to the directory referred to by `dirfd`.
 ///
-/// ## Returns:
-///     - 0 on success
-///     - -1 on error, with errno set to indicate the error (EBADF, EINTR, EINVAL, ENOLCK, EWOULDBLOCK)
-pub extern "C" fn flock_syscall(
-    cageid: u64,
-    vfd_arg: u64,
-    vfd_cageid: u64,
-    op_arg: u64,
-    op_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
+/// ## Arguments
+/// * `cageid` – The ID of the calling cage.
+/// * `target_arg` / `target_cageid` – Pointer to the target path string.
+/// * `dirfd_arg` / `dirfd_cageid` – File descriptor of the directory to resolve
+///   `linkpath` relative to, or `AT_FDCWD`.
+/// * `linkpath_arg` / `linkpath_cageid` – Pointer to the path where the
+///   symbolic link should be created, relative to `dirfd`.
+///
+/// ## Returns
+/// * `0` on success.
+/// * Negative errno (`EEXIST`, `ENOENT`, `EBADF`, `EFAULT`, etc.) on failure.
+pub extern "C" fn symlinkat_syscall(
+    cageid: u64,
+    target_arg: u64,
+    target_cageid: u64,
+    dirfd_arg: u64,
+    dirfd_cageid: u64,
+    linkpath_arg: u64,
+    linkpath_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -4718,218 +7939,168 @@ pub extern "C" fn flock_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
-    // Return error
-    if kernel_fd < 0 {
-        return handle_errno(-kernel_fd, "flock");
-    }
+    // The symlink target is stored as-is and should NOT be path-normalized.
+    // Normalizing would resolve relative paths against cwd, but relative symlink
+    // targets are meant to resolve relative to the symlink's location at dereference
+    // time, not at creation time. So we use get_cstr directly instead of
+    // sc_convert_path_to_host.
+    let target = match get_cstr(target_arg) {
+        Ok(path) => path,
+        Err(_) => {
+            return syscall_error(Errno::EFAULT, "symlinkat", "target path conversion failed")
+        }
+    };
+    let virtual_fd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
 
-    let op = sc_convert_sysarg_to_i32(op_arg, op_cageid, cageid);
+    // The linkpath is where the symlink is created, so it does need full path resolution.
+    let linkpath = match sc_convert_path_to_host(linkpath_arg, linkpath_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "symlinkat", "linkpath conversion failed"),
+    };
 
-    // Validate unused arguments
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
+    if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "flock_syscall"
+            "symlinkat_syscall"
         );
     }
 
-    // Validate operation flags
-    let valid_ops = libc::LOCK_SH | libc::LOCK_EX | libc::LOCK_UN | libc::LOCK_NB;
-    if op & !valid_ops != 0 {
-        return syscall_error(Errno::EINVAL, "flock", "Invalid operation flags");
-    }
+    let ret = if virtual_fd == libc::AT_FDCWD {
+        unsafe { libc::symlink(target.as_ptr() as *const libc::c_char, linkpath.as_ptr()) }
+    } else {
+        let kernel_fd = convert_fd_to_host(virtual_fd as u64, dirfd_cageid, cageid);
+        if kernel_fd < 0 {
+            return handle_errno(-kernel_fd, "symlinkat");
+        }
 
-    // Ensure at least one primary operation is specified
-    let primary_ops = libc::LOCK_SH | libc::LOCK_EX | libc::LOCK_UN;
-    if op & primary_ops == 0 {
-        return syscall_error(Errno::EINVAL, "flock", "No primary operation specified");
-    }
+        // Use the raw (untranslated) linkpath here intentionally — sc_convert_path_to_host
+        // resolves relative to CWD, but symlinkat interprets linkpath relative to dirfd.
+        // The kernel handles that resolution, so we pass the original guest pointer.
+        let raw_linkpath = match get_cstr(linkpath_arg) {
+            Ok(p) => p,
+            Err(_) => return syscall_error(Errno::EINVAL, "symlinkat", "invalid linkpath"),
+        };
+
+        unsafe {
+            libc::symlinkat(
+                target.as_ptr() as *const libc::c_char,
+                kernel_fd,
+                raw_linkpath.as_ptr() as *const c_char,
+            )
+        }
+    };
 
-    let ret = unsafe { libc::flock(kernel_fd, op) };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "flock");
+        return handle_errno(errno, "symlinkat");
     }
 
     ret
 }
 
-/// Linux reference: https://man7.org/linux/man-pages/man2/shmget.2.html
-///
-/// shmget checks whether a shared memory key already exists, and either returns the existing
-/// segment ID or creates a new segment (after validating flags and size) before registering
-/// it in the metadata tables.
-///
-/// 1. **Convert arguments**  
-///    - Convert `key_arg`, `size_arg`, and `shmflg_arg` into native types,
-///      while validating `arg4`–`arg6` are unused.
-///
-/// 2. **Special case: IPC_PRIVATE**  
-///    - Return `ENOENT` since `IPC_PRIVATE` segments are not supported yet.
-///
-/// 3. **Check if key exists**  
-///    - If the key already exists in `shmkeyidtable`:  
-///       - If both `IPC_CREAT` and `IPC_EXCL` are set → error `EEXIST`.  
-///       - Else return the existing `shmid`.
+/// Linux reference: https://man7.org/linux/man-pages/man2/setxattr.2.html
 ///
-/// 4. **Key does not exist**  
-///    - If `IPC_CREAT` not set → error `ENOENT`.  
-///    - Validate `size` against `SHMMIN` and `SHMMAX`.  
-///    - Allocate new `shmid` via `new_keyid()`.  
-///    - Insert into `shmkeyidtable`.  
-///    - Create a new shared memory segment with owner `cageid`, default `uid/gid`,
-///      and mode = lowest 9 bits of `shmflg`.  
-///    - Insert segment into `shmtable`.
+/// Sets the value of an extended attribute identified by `name`
+/// and associated with the given `path` in the filesystem.
 ///
-/// ## Arguments
-/// * `cageid`       – The ID of the calling cage, used for ownership and validation.
-/// * `key_arg`      – The key used to identify the shared memory segment (raw u64).
-/// * `key_cageid`   – The cage ID associated with `key_arg` for validation.
-/// * `size_arg`     – The size (in bytes) of the requested segment (raw u64).
-/// * `size_cageid`  – The cage ID associated with `size_arg`.
-/// * `shmflg_arg`   – Flags controlling creation, permissions, and behavior (raw u64).
-/// * `shmflg_cageid`– The cage ID associated with `shmflg_arg`.
+/// ## Arguments:
+/// * `cageid` - Cage identifier
+/// * `path_arg` - Path to the file
+/// * `path_cageid` - Cage ID for path
+/// * `name_arg` - Name of the extended attribute
+/// * `name_cageid` - Cage ID for name
+/// * `value_arg` - Pointer to the value to set
+/// * `value_cageid` - Cage ID for value
+/// * `size_arg` - Size of the value
+/// * `size_cageid` - Cage ID for size
+/// * `flags_arg` - Flags for setxattr (e.g., XATTR_CREATE, XATTR_REPLACE)
+/// * `flags_cageid` - Cage ID for flags
 ///
 /// ## Returns:
-/// On success, it returns the segment ID; on failure, it returns an appropriate error code.
-pub extern "C" fn shmget_syscall(
+/// On success, 0 is returned. On error, -1 is returned and errno is set appropriately.
+pub extern "C" fn setxattr_syscall(
     cageid: u64,
-    key_arg: u64,
-    key_cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    name_arg: u64,
+    name_cageid: u64,
+    value_arg: u64,
+    value_cageid: u64,
     size_arg: u64,
     size_cageid: u64,
-    shmflg_arg: u64,
-    shmflg_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
-    arg5: u64,
-    arg5_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let key = sc_convert_sysarg_to_i32(key_arg, key_cageid, cageid);
+    // Type conversion for path
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "setxattr", "path conversion failed"),
+    };
+
+    // Type conversion for name (attribute name, not a path - no path normalization needed)
+    let name_str = match get_cstr(name_arg) {
+        Ok(s) => s,
+        Err(_) => return syscall_error(Errno::EFAULT, "setxattr", "name conversion failed"),
+    };
+    let name = match std::ffi::CString::new(name_str) {
+        Ok(s) => s,
+        Err(_) => return syscall_error(Errno::EINVAL, "setxattr", "name contains null byte"),
+    };
+
+    // Type conversion for value buffer
+    let value = sc_convert_buf(value_arg, value_cageid, cageid) as *const libc::c_void;
     let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
-    let shmflg = sc_convert_sysarg_to_i32(shmflg_arg, shmflg_cageid, cageid);
-    // Validate unused args
-    if !(sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
+
+    // Validate unused arg
+    if !sc_unusedarg(arg6, arg6_cageid) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "shmget_syscall"
+            "setxattr_syscall"
         );
     }
 
-    if key == IPC_PRIVATE {
-        lind_debug_panic!("shmget key IPC_PRIVATE is not allowed in Lind");
-    }
-    let shmid: i32;
-    let metadata = &SHM_METADATA;
-
-    // The size of the segment should be rounded up to a multiple of pages
-    let rounded_size = round_up_page(size as u64) as usize;
-
-    match metadata.shmkeyidtable.entry(key) {
-        Occupied(occupied) => {
-            if (IPC_CREAT | IPC_EXCL) == (shmflg & (IPC_CREAT | IPC_EXCL)) {
-                return syscall_error(
-                    Errno::EEXIST,
-                    "shmget",
-                    "key already exists and IPC_CREAT and IPC_EXCL were used",
-                );
-            }
-            shmid = *occupied.get();
-        }
-        Vacant(vacant) => {
-            if 0 == (shmflg & IPC_CREAT) {
-                return syscall_error(
-                    Errno::ENOENT,
-                    "shmget",
-                    "tried to use a key that did not exist, and IPC_CREAT was not specified",
-                );
-            }
-
-            if (rounded_size as u32) < SHMMIN || (rounded_size as u32) > SHMMAX {
-                return syscall_error(
-                    Errno::EINVAL,
-                    "shmget",
-                    "Size is less than SHMMIN or more than SHMMAX",
-                );
-            }
-
-            shmid = metadata.new_keyid();
-            vacant.insert(shmid);
-            let mode = (shmflg & 0x1FF) as u16; // mode is 9 least signficant bits of shmflag, even if we dont really do anything with them
-
-            let segment = new_shm_segment(
-                key,
-                rounded_size,
-                cageid as u32,
-                DEFAULT_UID,
-                DEFAULT_GID,
-                mode,
-            );
-            metadata.shmtable.insert(shmid, segment);
-        }
-    };
-    shmid // return the shmid
-}
-
-/// Linux reference: https://man7.org/linux/man-pages/man3/shmat.3p.html
-///
-/// Handles the shmat syscall by mapping shared memory segments into the cage's address space.
-/// This function manages the attachment of shared memory segments by updating the cage's vmmap
-/// and handling the raw shmat helpers.
-///
-/// 1) **Parse & validate args**
-///    - Decode `shmid`, `shmaddr`, `shmflg`; ensure `arg4..arg6` are truly unused (panic if not).
-/// 2) **Resolve access mode**
-///    - If `SHM_RDONLY` set → `prot = PROT_READ`; otherwise `prot = PROT_READ|PROT_WRITE`.
-/// 3) **Lookup segment length**
-///    - `get_shm_length(shmid)` → error `EINVAL` if unknown segment.
-/// 4) **Validate alignment & sizes**
-///    - `shmaddr` must be page-aligned (error `EINVAL` if not).
-///    - Round segment length up to page size.
-/// 5) **Choose placement in vmmap**
-///    - If `shmaddr == 0` → `find_map_space(pages, align=1)`.
-///    - Else           → `find_map_space_with_hint(pages, align=1, hint=shmaddr)`.
-///    - No fit → error `ENOMEM`.
-/// 6) **Translate to system address**
-///    - Convert chosen user address to system address via `vmmap.user_to_sys`.
-/// 7) **Perform attach in the backend**
-///    - Call `shmat_helper(cageid, sysaddr, shmflg, shmid)`.
-///    - Must return the same user address; mismatch to panic.
-/// 8) **Record mapping**
-///    - Add a `vmmap` entry with `backing = SharedMemory(shmid)`,
-///      `prot` and `maxprot = prot`, length in pages, offset 0, `len` as filelen.
+    // Call to kernel setxattr
+    let ret = unsafe { libc::setxattr(path.as_ptr(), name.as_ptr(), value, size, flags) };
+
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "setxattr");
+    }
+
+    ret
+}
+
+/// Linux reference: https://man7.org/linux/man-pages/man2/listxattr.2.html
 ///
-/// # Arguments
-/// * `cageid` - The cage ID that is performing the shmat operation
-/// * `addr` - The requested address to attach the shared memory segment (can be null)
-/// * `prot` - The memory protection flags for the mapping
-/// * `shmflag` - Flags controlling the shared memory attachment behavior
-/// * `shmid` - The ID of the shared memory segment to attach
+/// Retrieves the list of extended attribute names associated with the given `path`.
 ///
-/// # Returns
-/// * `u32` - The address where the shared memory segment was attached, or an error code
+/// ## Arguments:
+/// * `cageid` - Cage identifier
+/// * `path_arg` - Path to the file
+/// * `path_cageid` - Cage ID for path
+/// * `list_arg` - Pointer to buffer to store the list of attribute names
+/// * `list_cageid` - Cage ID for list
+/// * `size_arg` - Size of the buffer
+/// * `size_cageid` - Cage ID for size
 ///
-/// # Errors
-/// * `EINVAL` - If the provided address is not page-aligned
-/// * `ENOMEM` - If there is insufficient memory to complete the attachment
-pub extern "C" fn shmat_syscall(
+/// ## Returns:
+/// On success, returns the size of the list of attribute names. If `list` is NULL and `size` is zero,
+/// returns the size of the buffer needed to store the list.
+/// On error, -1 is returned and errno is set appropriately.
+pub extern "C" fn listxattr_syscall(
     cageid: u64,
-    shmid_arg: u64,
-    shmid_cageid: u64,
-    shmaddr_arg: u64,
-    shmaddr_cageid: u64,
-    shmflg_arg: u64,
-    shmflg_cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    list_arg: u64,
+    list_cageid: u64,
+    size_arg: u64,
+    size_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -4937,16 +8108,20 @@ pub extern "C" fn shmat_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let shmid = sc_convert_sysarg_to_i32(shmid_arg, shmid_cageid, cageid);
-    let mut useraddr = {
-        if shmaddr_arg == 0 {
-            0 as u32
-        } else {
-            sc_convert_sysarg_to_u32(shmaddr_arg, shmaddr_cageid, cageid)
-        }
+    // Type conversion for path
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "listxattr", "path conversion failed"),
     };
-    let shmflag = sc_convert_sysarg_to_i32(shmflg_arg, shmflg_cageid, cageid);
-    let mut prot = 0;
+
+    // Type conversion for list buffer (may be NULL)
+    let list = if list_arg == 0 {
+        std::ptr::null_mut()
+    } else {
+        sc_convert_to_u8_mut(list_arg, list_cageid, cageid) as *mut libc::c_char
+    };
+    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
+
     // Validate unused args
     if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
@@ -4954,341 +8129,367 @@ pub extern "C" fn shmat_syscall(
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "shmat_syscall"
+            "listxattr_syscall"
         );
     }
 
-    // Get the cage reference.
-    let cage = get_cage(cageid).unwrap();
+    // Call to kernel listxattr
+    let ret = unsafe { libc::listxattr(path.as_ptr(), list, size) };
 
-    // If SHM_RDONLY is set in shmflag, then use read-only protection,
-    // otherwise default to read–write.
-    prot = if shmflag & SHM_RDONLY != 0 {
-        PROT_READ
-    } else {
-        PROT_READ | PROT_WRITE
-    };
-    let len = match get_shm_length(shmid) {
-        Some(l) => l,
-        None => return syscall_error(Errno::EINVAL, "shmat", "invalid shmid"),
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "listxattr");
+    }
+
+    // Convert ssize_t to i32 safely
+    ret.try_into().unwrap_or(i32::MAX)
+}
+
+/// Helper for the xattr family.
+///
+/// Attribute names (e.g. `user.comment`) are not paths, so they are copied out of the
+/// cage's memory verbatim instead of going through `sc_convert_path_to_host`.
+fn xattr_name_to_host(name_arg: u64, syscall: &str) -> Result<CString, i32> {
+    let name_str = match get_cstr(name_arg) {
+        Ok(s) => s,
+        Err(_) => {
+            return Err(syscall_error(
+                Errno::EFAULT,
+                syscall,
+                "name conversion failed",
+            ))
+        }
     };
+    match CString::new(name_str) {
+        Ok(s) => Ok(s),
+        Err(_) => Err(syscall_error(
+            Errno::EINVAL,
+            syscall,
+            "name contains null byte",
+        )),
+    }
+}
 
-    // Check that the provided address is page aligned.
-    let rounded_addr = round_up_page(useraddr as u64);
-    if rounded_addr != useraddr as u64 {
-        return syscall_error(Errno::EINVAL, "shmat", "address is not aligned");
+/// Helper for the xattr family.
+///
+/// A `size` of zero is a size probe: the kernel must see a NULL buffer so that it
+/// returns the length of the value (or list) instead of failing with `ERANGE`.
+fn xattr_buf_to_host(buf_arg: u64, buf_cageid: u64, cageid: u64, size: usize) -> *mut c_void {
+    if buf_arg == 0 || size == 0 {
+        std::ptr::null_mut()
+    } else {
+        sc_convert_to_u8_mut(buf_arg, buf_cageid, cageid) as *mut c_void
     }
+}
 
-    // Round up the length to a multiple of the page size.
-    let rounded_length = round_up_page(len as u64);
+/// Linux reference: https://man7.org/linux/man-pages/man2/setxattr.2.html
+///
+/// Same as `setxattr_syscall`, except that when `path` refers to a symbolic link the
+/// extended attribute is set on the link itself rather than on the file it refers to.
+///
+/// ## Arguments:
+/// * `cageid` - Cage identifier
+/// * `path_arg` - Path to the file (not followed if it is a symlink)
+/// * `name_arg` - Name of the extended attribute
+/// * `value_arg` - Pointer to the value to set
+/// * `size_arg` - Size of the value
+/// * `flags_arg` - Flags for setxattr (e.g., XATTR_CREATE, XATTR_REPLACE)
+///
+/// ## Returns:
+/// On success, 0 is returned. On error, a negative errno is returned.
+pub extern "C" fn lsetxattr_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    value_arg: u64,
+    value_cageid: u64,
+    size_arg: u64,
+    size_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "lsetxattr", "path conversion failed"),
+    };
+    let name = match xattr_name_to_host(name_arg, "lsetxattr") {
+        Ok(name) => name,
+        Err(e) => return e,
+    };
+    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
+    let value = xattr_buf_to_host(value_arg, value_cageid, cageid, size);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
 
-    // Initialize the user address from the provided address pointer.
-    // If addr is null (0), we need to allocate memory space from the virtual memory map (vmmap).
-    let vmmap = cage.vmmap.write();
-    let result;
-    if useraddr == 0 {
-        // Allocate a suitable space in the virtual memory map for the shared memory segment
-        // based on the rounded length of the segment.
-        result = vmmap.find_map_space((rounded_length >> PAGESHIFT) as u32, 1);
-    } else {
-        // Use the user-specified address as a hint to find an appropriate memory address
-        // for the shared memory segment.
-        result = vmmap.find_map_space_with_hint(
-            rounded_length as u32 >> PAGESHIFT,
-            1,
-            useraddr as u32 >> PAGESHIFT,
+    if !sc_unusedarg(arg6, arg6_cageid) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "lsetxattr_syscall"
         );
     }
-    // drop the write lock of vmmap to avoid deadlock
-    drop(vmmap);
 
-    if result.is_none() {
-        // If no suitable memory space is found, return an error indicating insufficient memory.
-        return syscall_error(Errno::ENOMEM, "shmat", "no memory") as i32;
+    let ret = unsafe { libc::lsetxattr(path.as_ptr(), name.as_ptr(), value, size, flags) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "lsetxattr");
     }
-    let space = result.unwrap();
-    // Update the user address to the start of the allocated memory space.
-    useraddr = (space.start() << PAGESHIFT) as u32;
 
-    if (useraddr as u64) + rounded_length > 0x100000000u64 {
-        return syscall_error(
-            Errno::EINVAL,
-            "shmat",
-            "address range overflows 32-bit space",
-        );
-    }
+    ret
+}
 
-    // Convert the user address into a system address.
-    // Read the virtual memory map to access the user address space.
-    let vmmap = cage.vmmap.read();
-    // Convert the user address to the corresponding system address for the shared memory segment.
-    let sysaddr = vmmap.user_to_sys(useraddr);
-    // Release the lock on the virtual memory map as we no longer need it.
-    drop(vmmap);
+/// Linux reference: https://man7.org/linux/man-pages/man2/fsetxattr.2.html
+///
+/// Sets the value of an extended attribute on the open file referred to by the virtual
+/// file descriptor `fd`. The virtual fd is translated to its kernel fd through `fdtables`.
+///
+/// ## Arguments:
+/// * `cageid` - Cage identifier
+/// * `vfd_arg` - Virtual file descriptor of the open file
+/// * `name_arg` - Name of the extended attribute
+/// * `value_arg` - Pointer to the value to set
+/// * `size_arg` - Size of the value
+/// * `flags_arg` - Flags for setxattr (e.g., XATTR_CREATE, XATTR_REPLACE)
+///
+/// ## Returns:
+/// On success, 0 is returned. On error, a negative errno is returned.
+pub extern "C" fn fsetxattr_syscall(
+    cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    value_arg: u64,
+    value_cageid: u64,
+    size_arg: u64,
+    size_cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "fsetxattr");
+    }
+    let name = match xattr_name_to_host(name_arg, "fsetxattr") {
+        Ok(name) => name,
+        Err(e) => return e,
+    };
+    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
+    let value = xattr_buf_to_host(value_arg, value_cageid, cageid, size);
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
 
-    // Call the raw shmat helper to attach the shared memory segment.
-    let result = shmat_helper(cageid, sysaddr as *mut u8, shmflag, shmid);
+    if !sc_unusedarg(arg6, arg6_cageid) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "fsetxattr_syscall"
+        );
+    }
 
-    // Check for error BEFORE sys_to_user conversion
-    if is_mmap_error(result) {
+    let ret = unsafe { libc::fsetxattr(kernel_fd, name.as_ptr(), value, size, flags) };
+    if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "shmat");
+        return handle_errno(errno, "fsetxattr");
     }
 
-    let vmmap = cage.vmmap.read();
-    let result = vmmap.sys_to_user(result);
-    drop(vmmap);
+    ret
+}
+
+/// Linux reference: https://man7.org/linux/man-pages/man2/getxattr.2.html
+///
+/// Retrieves the value of the extended attribute identified by `name` and associated with
+/// the given `path`. If `size` is zero the value is not copied and the current size of the
+/// attribute value is returned instead, so callers can size their buffer.
+///
+/// ## Arguments:
+/// * `cageid` - Cage identifier
+/// * `path_arg` - Path to the file
+/// * `name_arg` - Name of the extended attribute
+/// * `value_arg` - Pointer to the buffer that receives the value (may be NULL when `size` is 0)
+/// * `size_arg` - Size of the buffer
+///
+/// ## Returns:
+/// On success, the size of the attribute value. On error, a negative errno is returned
+/// (`ENODATA` if the attribute doesn't exist, `ERANGE` if the buffer is too small).
+pub extern "C" fn getxattr_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    value_arg: u64,
+    value_cageid: u64,
+    size_arg: u64,
+    size_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "getxattr", "path conversion failed"),
+    };
+    let name = match xattr_name_to_host(name_arg, "getxattr") {
+        Ok(name) => name,
+        Err(e) => return e,
+    };
+    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
+    let value = xattr_buf_to_host(value_arg, value_cageid, cageid, size);
+
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "getxattr_syscall"
+        );
+    }
 
-    if result as u32 != useraddr {
-        panic!("shmat did not attach at the expected address");
+    let ret = unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), value, size) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "getxattr");
     }
-    let mut vmmap = cage.vmmap.write();
-    let backing = MemoryBackingType::SharedMemory(shmid as u64);
-    let maxprot = prot;
-    vmmap
-        .add_entry_with_overwrite(
-            useraddr >> PAGESHIFT,
-            (rounded_length >> PAGESHIFT) as u32,
-            prot,
-            maxprot,
-            0,
-            backing,
-            0,
-            len as i64,
-            cageid,
-        )
-        .expect("shmat: failed to add vmmap entry");
 
-    useraddr as i32
+    ret.try_into().unwrap_or(i32::MAX)
 }
 
-/// Linux reference: https://man7.org/linux/man-pages/man3/shmdt.3p.html
-///
-/// `shmdt_syscall`, interacting with the `vmmap` structure.
-///
-/// This function processes the `shmdt_syscall` by updating the `vmmap` entries and managing
-/// the shared memory detachment operation. It performs address validation, converts user
-/// addresses to system addresses, and updates the virtual memory mappings accordingly.
+/// Linux reference: https://man7.org/linux/man-pages/man2/getxattr.2.html
 ///
-/// 1) **Parse & validate args**
-///    - Decode `shmaddr`; ensure `arg2..arg6` are unused (panic if not).
-/// 2) **Validate alignment**
-///    - `shmaddr` must be page-aligned (error `EINVAL` if not).
-/// 3) **Translate to system address**
-///    - `vmmap.user_to_sys(shmaddr)` to get the underlying system pointer.
-/// 4) **Perform detach in the backend**
-///    - `shmdt_helper(cageid, sysaddr)` → returns detached length (bytes) or negative errno.
-/// 5) **Remove mapping from vmmap**
-///    - Remove `length >> PAGESHIFT` pages starting at `shmaddr >> PAGESHIFT`.
+/// Same as `getxattr_syscall`, except that a symbolic link at `path` is interrogated
+/// itself rather than the file it refers to.
 ///
-/// # Arguments
-/// * `cageid` - Identifier of the cage that calls the `shmdt`
-/// * `addr` - Starting address of the shared memory region to detach
+/// ## Arguments:
+/// * `cageid` - Cage identifier
+/// * `path_arg` - Path to the file (not followed if it is a symlink)
+/// * `name_arg` - Name of the extended attribute
+/// * `value_arg` - Pointer to the buffer that receives the value (may be NULL when `size` is 0)
+/// * `size_arg` - Size of the buffer
 ///
-/// # Returns
-/// * `i32` - 0 for success and negative errno for failure
-pub extern "C" fn shmdt_syscall(
+/// ## Returns:
+/// On success, the size of the attribute value. On error, a negative errno is returned.
+pub extern "C" fn lgetxattr_syscall(
     cageid: u64,
-    shmaddr_arg: u64,
-    shmaddr_cageid: u64,
-    arg2: u64,
-    arg2_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    value_arg: u64,
+    value_cageid: u64,
+    size_arg: u64,
+    size_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // NOTE: glibc's shmdt wrapper already calls TRANSLATE_GUEST_POINTER_TO_HOST,
-    // so shmaddr_arg is already a host/system pointer. Do NOT translate again.
-    // This avoids dependency on vmmap.base_address which can change after fork/exec.
-    let sysaddr = shmaddr_arg as usize;
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "lgetxattr", "path conversion failed"),
+    };
+    let name = match xattr_name_to_host(name_arg, "lgetxattr") {
+        Ok(name) => name,
+        Err(e) => return e,
+    };
+    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
+    let value = xattr_buf_to_host(value_arg, value_cageid, cageid, size);
 
-    if !(sc_unusedarg(arg2, arg2_cageid)
-        && sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "shmdt_syscall"
+            "lgetxattr_syscall"
         );
     }
 
-    // Retrieve the cage reference.
-    let cage = get_cage(cageid).unwrap();
-
-    // Check that the provided address is aligned on a page boundary.
-    if sysaddr & (PAGESIZE as usize - 1) != 0 {
-        return syscall_error(Errno::EINVAL, "shmdt", "address is not aligned");
-    }
-
-    // Call shmdt_helper which returns length of the detached segment.
-    // Pass the host address directly - rev_shm stores host addresses from shmat.
-    let length = shmdt_helper(cageid, sysaddr as *mut u8);
-    if length < 0 {
-        return length;
+    let ret = unsafe { libc::lgetxattr(path.as_ptr(), name.as_ptr(), value, size) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "lgetxattr");
     }
 
-    // Remove the mapping from the vmmap.
-    // Convert sys address back to user address for vmmap bookkeeping.
-    let mut vmmap = cage.vmmap.write();
-    let useraddr = vmmap.sys_to_user(sysaddr);
-    vmmap
-        .remove_entry(useraddr >> PAGESHIFT, (length as u32) >> PAGESHIFT)
-        .expect("shmdt: remove_entry failed");
-
-    0
+    ret.try_into().unwrap_or(i32::MAX)
 }
 
-/// Linux reference: https://man7.org/linux/man-pages/man3/shmctl.3p.html
-///
-/// It converts and validates the `shmid`, `cmd`, and optional `buf` arguments, enforces that unused
-/// arguments are truly unused (panicking on unexpected values), and then applies
-/// the requested control operation: `IPC_STAT` copies the segment’s metadata
-/// (`shminfo`) into the caller-provided buffer, and `IPC_RMID` marks the segment
-/// for removal (setting `SHM_DEST`) and deletes it immediately if there are no
-/// attachments, also clearing the key to id mapping.
+/// Linux reference: https://man7.org/linux/man-pages/man2/fgetxattr.2.html
 ///
-/// 1) **Parse & validate args**
-///    - Decode `shmid`, `cmd`, and optional `buf` pointer; ensure `arg4..arg6` unused (panic if not).
-/// 2) **Locate segment**
-///    - Lookup `shmid` in `shmtable`; if not found → error `EINVAL`.
-/// 3) **Dispatch by `cmd`**
-///    - `IPC_STAT`: copy `segment.shminfo` into caller’s `*buf`.
-///    - `IPC_RMID`: mark for removal (`segment.rmid = true`, set `SHM_DEST` in mode).
-///        * If `shm_nattch == 0`, remove the segment immediately and clear the key→id mapping.
-///    - Otherwise: error `EINVAL` (unsupported command).
+/// Retrieves the value of an extended attribute of the open file referred to by the
+/// virtual file descriptor `fd`. Size-probe semantics are the same as `getxattr_syscall`.
 ///
-/// ## Arguments
-/// * `cageid`        – The ID of the calling cage, used for ownership and validation.
-/// * `shmid_arg`     – Shared memory segment ID (raw u64).
-/// * `shmid_cageid`  – Cage ID associated with `shmid_arg`, used for validation.
-/// * `cmd_arg`       – Control command (e.g., `IPC_STAT`, `IPC_RMID`) (raw u64).
-/// * `cmd_cageid`    – Cage ID associated with `cmd_arg`.
-/// * `buf_arg`       – Pointer to a buffer (struct shmid_ds) used for returning or
-///                     updating segment info, depending on the command.
-/// * `buf_cageid`    – Cage ID associated with `buf_arg`.
+/// ## Arguments:
+/// * `cageid` - Cage identifier
+/// * `vfd_arg` - Virtual file descriptor of the open file
+/// * `name_arg` - Name of the extended attribute
+/// * `value_arg` - Pointer to the buffer that receives the value (may be NULL when `size` is 0)
+/// * `size_arg` - Size of the buffer
 ///
 /// ## Returns:
-/// On invalid identifiers or
-/// unsupported commands, it returns `-EINVAL` via `syscall_error`; on success,
-/// returns `0`.
-pub extern "C" fn shmctl_syscall(
+/// On success, the size of the attribute value. On error, a negative errno is returned.
+pub extern "C" fn fgetxattr_syscall(
     cageid: u64,
-    shmid_arg: u64,
-    shmid_cageid: u64,
-    cmd_arg: u64,
-    cmd_cageid: u64,
-    buf_arg: u64,
-    buf_cageid: u64,
-    arg4: u64,
-    arg4_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    value_arg: u64,
+    value_cageid: u64,
+    size_arg: u64,
+    size_cageid: u64,
     arg5: u64,
     arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let shmid = sc_convert_sysarg_to_i32(shmid_arg, shmid_cageid, cageid);
-    let cmd = sc_convert_sysarg_to_i32(cmd_arg, cmd_cageid, cageid);
-    let buf = sc_convert_addr_to_shmidstruct(buf_arg, buf_cageid, cageid).unwrap();
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "fgetxattr");
+    }
+    let name = match xattr_name_to_host(name_arg, "fgetxattr") {
+        Ok(name) => name,
+        Err(e) => return e,
+    };
+    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
+    let value = xattr_buf_to_host(value_arg, value_cageid, cageid, size);
 
-    // Validate unused args
-    if !(sc_unusedarg(arg4, arg4_cageid)
-        && sc_unusedarg(arg5, arg5_cageid)
-        && sc_unusedarg(arg6, arg6_cageid))
-    {
+    if !(sc_unusedarg(arg5, arg5_cageid) && sc_unusedarg(arg6, arg6_cageid)) {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "shmctl_syscall"
+            "fgetxattr_syscall"
         );
     }
 
-    let metadata = &SHM_METADATA;
-
-    if let Some(mut segment) = metadata.shmtable.get_mut(&shmid) {
-        match cmd {
-            IPC_STAT => {
-                *buf = segment.shminfo;
-            }
-            IPC_RMID => {
-                segment.rmid = true;
-                segment.shminfo.shm_perm.mode |= SHM_DEST as u16;
-                if segment.shminfo.shm_nattch == 0 {
-                    let key = segment.key;
-                    drop(segment);
-                    metadata.shmtable.remove(&shmid);
-                    metadata.shmkeyidtable.remove(&key);
-                }
-            }
-            _ => {
-                return syscall_error(
-                    Errno::EINVAL,
-                    "shmctl",
-                    "Arguments provided do not match implemented parameters",
-                );
-            }
-        }
-    } else {
-        return syscall_error(Errno::EINVAL, "shmctl", "Invalid identifier");
+    let ret = unsafe { libc::fgetxattr(kernel_fd, name.as_ptr(), value, size) };
+    if ret < 0 {
+        let errno = get_errno();
+        return handle_errno(errno, "fgetxattr");
     }
 
-    0 //shmctl has succeeded!
+    ret.try_into().unwrap_or(i32::MAX)
 }
 
-/// Linux reference: https://man7.org/linux/man-pages/man2/getrandom.2.html
-///
-/// Implements the `getrandom(2)` syscall for a cage. This wrapper converts and
-/// validates all caller-provided arguments, resolves the user buffer pointer into
-/// a host pointer, enforces cage-ownership consistency, and then directly invokes
-/// the host kernel’s `SYS_getrandom` via `syscall()`. Any error from the host is
-/// converted into a cage-appropriate errno using `handle_errno`.
-///
-/// 1) **Parse & validate args**
-///    - `buf_arg` is interpreted as a user pointer and converted to a host pointer
-///      using `sc_convert_uaddr_to_host`, ensuring it belongs to `cageid`.
-///    - `buflen_arg` and `flags_arg` are converted to 32-bit values via
-///      `sc_convert_sysarg_to_u32`, validating cage ownership and rejecting
-///      malformed arguments.
-///    - Unused arguments `arg4..arg6` must be zero; if not, they cause a panic
-///      (enforcing a strict syscall ABI).
-///
-/// 2) **Invoke host syscall**
-///    - Calls `syscall(SYS_getrandom, buf, buflen, flags)` unsafely to request
-///      random bytes from the host kernel.
-///    - On negative return, retrieves `errno` using `get_errno()` and converts it
-///      to a standardized cage-side error with `handle_errno`.
+/// Linux reference: https://man7.org/linux/man-pages/man2/listxattr.2.html
 ///
-/// 3) **Return value**
-///    - On success, returns the number of random bytes written (0 ≤ n ≤ buflen).
-///    - On failure, returns a negative errno (`-EINTR`, `-EAGAIN`, `-EINVAL`, etc.).
+/// Same as `listxattr_syscall`, except that a symbolic link at `path` is listed itself
+/// rather than the file it refers to.
 ///
-/// ## Arguments
-/// * `cageid`            – Cage issuing the syscall; used to validate all arguments.
-/// * `buf_arg`           – Raw user pointer to the destination buffer.
-/// * `buf_arg_cageid`    – Cage ID associated with `buf_arg`.
-/// * `buflen_arg`        – Number of random bytes requested (raw u64).
-/// * `buflen_arg_cageid` – Cage ID associated with `buflen_arg`.
-/// * `flags_arg`         – `getrandom` flags (e.g., `GRND_NONBLOCK`) as raw u64.
-/// * `flags_arg_cageid`  – Cage ID associated with `flags_arg`.
-/// * `arg4..arg6`        – Unused placeholder arguments for syscall ABI; must be zero.
-/// * `arg4_cageid..arg6_cageid` – Cage IDs for unused arguments; checked but unused.
+/// ## Arguments:
+/// * `cageid` - Cage identifier
+/// * `path_arg` - Path to the file (not followed if it is a symlink)
+/// * `list_arg` - Pointer to buffer to store the list of attribute names
+/// * `size_arg` - Size of the buffer
 ///
-/// ## Returns
-/// On success: number of bytes written (positive `i32`).  
-/// On failure: a negative errno from `handle_errno`.
-pub extern "C" fn getrandom_syscall(
+/// ## Returns:
+/// On success, returns the size of the list of attribute names (the needed size when
+/// `size` is zero). On error, a negative errno is returned.
+pub extern "C" fn llistxattr_syscall(
     cageid: u64,
-    buf_arg: u64,
-    _buf_arg_cageid: u64,
-    buflen_arg: u64,
-    buflen_arg_cageid: u64,
-    flags_arg: u64,
-    flags_arg_cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    list_arg: u64,
+    list_cageid: u64,
+    size_arg: u64,
+    size_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -5296,53 +8497,54 @@ pub extern "C" fn getrandom_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let buf = buf_arg;
-    let buflen = sc_convert_sysarg_to_u32(buflen_arg, buflen_arg_cageid, cageid);
-    let flags = sc_convert_sysarg_to_u32(flags_arg, flags_arg_cageid, cageid);
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "llistxattr", "path conversion failed"),
+    };
+    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
+    let list = xattr_buf_to_host(list_arg, list_cageid, cageid, size) as *mut libc::c_char;
 
-    // Validate unused args
     if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "getrandom_syscall"
+            "llistxattr_syscall"
         );
     }
 
-    let ret = unsafe { getrandom(buf as *mut c_void, buflen.try_into().unwrap(), flags) };
+    let ret = unsafe { libc::llistxattr(path.as_ptr(), list, size) };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "getrandom");
+        return handle_errno(errno, "llistxattr");
     }
 
-    // convert isize to i32 safely, as ret shouldn't be larger than 32-bit
-    // due to buflen being u32
-    ret.try_into().unwrap()
+    ret.try_into().unwrap_or(i32::MAX)
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/symlink.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/flistxattr.2.html
 ///
-/// Creates a symbolic link at `linkpath` that points to `target`.
+/// Retrieves the list of extended attribute names of the open file referred to by the
+/// virtual file descriptor `fd`.
 ///
-/// ## Arguments
-/// * `cageid` – The ID of the calling cage.
-/// * `target_arg` / `target_arg_cageid` – Pointer to the target path string.
-/// * `linkpath_arg` / `linkpath_arg_cageid` – Pointer to the path where the
-///   symbolic link should be created.
+/// ## Arguments:
+/// * `cageid` - Cage identifier
+/// * `vfd_arg` - Virtual file descriptor of the open file
+/// * `list_arg` - Pointer to buffer to store the list of attribute names
+/// * `size_arg` - Size of the buffer
 ///
-/// ## Returns
-/// * `0` on success.
-/// * Negative errno (`EEXIST`, `ENOENT`, `EFAULT`, etc.) on failure.
-pub extern "C" fn symlink_syscall(
+/// ## Returns:
+/// On success, returns the size of the list of attribute names (the needed size when
+/// `size` is zero). On error, a negative errno is returned.
+pub extern "C" fn flistxattr_syscall(
     cageid: u64,
-    target_arg: u64,
-    target_cageid: u64,
-    linkpath_arg: u64,
-    linkpath_cageid: u64,
-    arg3: u64,
-    arg3_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    list_arg: u64,
+    list_cageid: u64,
+    size_arg: u64,
+    size_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -5350,71 +8552,52 @@ pub extern "C" fn symlink_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // The symlink target is stored as-is and should NOT be path-normalized.
-    // Normalizing would resolve relative paths against cwd, but relative symlink
-    // targets are meant to resolve relative to the symlink's location at dereference
-    // time, not at creation time. So we use get_cstr directly instead of
-    // sc_convert_path_to_host.
-    let target = match get_cstr(target_arg) {
-        Ok(path) => path,
-        Err(_) => return syscall_error(Errno::EFAULT, "symlink", "target path conversion failed"),
-    };
-
-    // The linkpath is where the symlink is created, so it does need full path resolution.
-    let linkpath = match sc_convert_path_to_host(linkpath_arg, linkpath_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "symlink", "linkpath conversion failed"),
-    };
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "flistxattr");
+    }
+    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
+    let list = xattr_buf_to_host(list_arg, list_cageid, cageid, size) as *mut libc::c_char;
 
-    if !(sc_unusedarg(arg3, arg3_cageid)
-        && sc_unusedarg(arg4, arg4_cageid)
+    if !(sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "symlink_syscall"
+            "flistxattr_syscall"
         );
     }
 
-    let ret = unsafe { libc::symlink(target.as_ptr() as *const libc::c_char, linkpath.as_ptr()) };
-
+    let ret = unsafe { libc::flistxattr(kernel_fd, list, size) };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "symlink");
+        return handle_errno(errno, "flistxattr");
     }
 
-    ret
+    ret.try_into().unwrap_or(i32::MAX)
 }
 
-/// Reference to Linux: https://man7.org/linux/man-pages/man2/symlink.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/removexattr.2.html
 ///
-/// Creates a symbolic link at `linkpath` relative to `dirfd` that points to `target`.
-/// This is the `dirfd`-relative variant of `symlink`.
-///
-/// If `dirfd` is `AT_FDCWD`, the call behaves identically to `symlink_syscall`,
-/// resolving `linkpath` relative to the current working directory.
-/// Otherwise, `linkpath` is resolved relative to the directory referred to by `dirfd`.
+/// Removes the extended attribute identified by `name` from the file at `path`.
 ///
-/// ## Arguments
-/// * `cageid` – The ID of the calling cage.
-/// * `target_arg` / `target_cageid` – Pointer to the target path string.
-/// * `dirfd_arg` / `dirfd_cageid` – File descriptor of the directory to resolve
-///   `linkpath` relative to, or `AT_FDCWD`.
-/// * `linkpath_arg` / `linkpath_cageid` – Pointer to the path where the
-///   symbolic link should be created, relative to `dirfd`.
+/// ## Arguments:
+/// * `cageid` - Cage identifier
+/// * `path_arg` - Path to the file
+/// * `name_arg` - Name of the extended attribute to remove
 ///
-/// ## Returns
-/// * `0` on success.
-/// * Negative errno (`EEXIST`, `ENOENT`, `EBADF`, `EFAULT`, etc.) on failure.
-pub extern "C" fn symlinkat_syscall(
+/// ## Returns:
+/// On success, 0 is returned. On error, a negative errno is returned (`ENODATA` if the
+/// attribute doesn't exist).
+pub extern "C" fn removexattr_syscall(
     cageid: u64,
-    target_arg: u64,
-    target_cageid: u64,
-    dirfd_arg: u64,
-    dirfd_cageid: u64,
-    linkpath_arg: u64,
-    linkpath_cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -5422,168 +8605,111 @@ pub extern "C" fn symlinkat_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // The symlink target is stored as-is and should NOT be path-normalized.
-    // Normalizing would resolve relative paths against cwd, but relative symlink
-    // targets are meant to resolve relative to the symlink's location at dereference
-    // time, not at creation time. So we use get_cstr directly instead of
-    // sc_convert_path_to_host.
-    let target = match get_cstr(target_arg) {
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
         Ok(path) => path,
-        Err(_) => {
-            return syscall_error(Errno::EFAULT, "symlinkat", "target path conversion failed")
-        }
+        Err(e) => return syscall_error(e, "removexattr", "path conversion failed"),
     };
-    let virtual_fd = sc_convert_sysarg_to_i32(dirfd_arg, dirfd_cageid, cageid);
-
-    // The linkpath is where the symlink is created, so it does need full path resolution.
-    let linkpath = match sc_convert_path_to_host(linkpath_arg, linkpath_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "symlinkat", "linkpath conversion failed"),
+    let name = match xattr_name_to_host(name_arg, "removexattr") {
+        Ok(name) => name,
+        Err(e) => return e,
     };
 
-    if !(sc_unusedarg(arg4, arg4_cageid)
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "symlinkat_syscall"
+            "removexattr_syscall"
         );
     }
 
-    let ret = if virtual_fd == libc::AT_FDCWD {
-        unsafe { libc::symlink(target.as_ptr() as *const libc::c_char, linkpath.as_ptr()) }
-    } else {
-        let kernel_fd = convert_fd_to_host(virtual_fd as u64, dirfd_cageid, cageid);
-        if kernel_fd < 0 {
-            return handle_errno(-kernel_fd, "symlinkat");
-        }
-
-        // Use the raw (untranslated) linkpath here intentionally — sc_convert_path_to_host
-        // resolves relative to CWD, but symlinkat interprets linkpath relative to dirfd.
-        // The kernel handles that resolution, so we pass the original guest pointer.
-        let raw_linkpath = match get_cstr(linkpath_arg) {
-            Ok(p) => p,
-            Err(_) => return syscall_error(Errno::EINVAL, "symlinkat", "invalid linkpath"),
-        };
-
-        unsafe {
-            libc::symlinkat(
-                target.as_ptr() as *const libc::c_char,
-                kernel_fd,
-                raw_linkpath.as_ptr() as *const c_char,
-            )
-        }
-    };
-
+    let ret = unsafe { libc::removexattr(path.as_ptr(), name.as_ptr()) };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "symlinkat");
+        return handle_errno(errno, "removexattr");
     }
 
     ret
 }
 
-/// Linux reference: https://man7.org/linux/man-pages/man2/setxattr.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/removexattr.2.html
 ///
-/// Sets the value of an extended attribute identified by `name`
-/// and associated with the given `path` in the filesystem.
+/// Same as `removexattr_syscall`, except that a symbolic link at `path` is modified itself
+/// rather than the file it refers to.
 ///
 /// ## Arguments:
 /// * `cageid` - Cage identifier
-/// * `path_arg` - Path to the file
-/// * `path_cageid` - Cage ID for path
-/// * `name_arg` - Name of the extended attribute
-/// * `name_cageid` - Cage ID for name
-/// * `value_arg` - Pointer to the value to set
-/// * `value_cageid` - Cage ID for value
-/// * `size_arg` - Size of the value
-/// * `size_cageid` - Cage ID for size
-/// * `flags_arg` - Flags for setxattr (e.g., XATTR_CREATE, XATTR_REPLACE)
-/// * `flags_cageid` - Cage ID for flags
+/// * `path_arg` - Path to the file (not followed if it is a symlink)
+/// * `name_arg` - Name of the extended attribute to remove
 ///
 /// ## Returns:
-/// On success, 0 is returned. On error, -1 is returned and errno is set appropriately.
-pub extern "C" fn setxattr_syscall(
+/// On success, 0 is returned. On error, a negative errno is returned.
+pub extern "C" fn lremovexattr_syscall(
     cageid: u64,
     path_arg: u64,
     path_cageid: u64,
     name_arg: u64,
-    name_cageid: u64,
-    value_arg: u64,
-    value_cageid: u64,
-    size_arg: u64,
-    size_cageid: u64,
-    flags_arg: u64,
-    flags_cageid: u64,
+    _name_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion for path
     let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
         Ok(path) => path,
-        Err(e) => return syscall_error(e, "setxattr", "path conversion failed"),
-    };
-
-    // Type conversion for name (attribute name, not a path - no path normalization needed)
-    let name_str = match get_cstr(name_arg) {
-        Ok(s) => s,
-        Err(_) => return syscall_error(Errno::EFAULT, "setxattr", "name conversion failed"),
+        Err(e) => return syscall_error(e, "lremovexattr", "path conversion failed"),
     };
-    let name = match std::ffi::CString::new(name_str) {
-        Ok(s) => s,
-        Err(_) => return syscall_error(Errno::EINVAL, "setxattr", "name contains null byte"),
+    let name = match xattr_name_to_host(name_arg, "lremovexattr") {
+        Ok(name) => name,
+        Err(e) => return e,
     };
 
-    // Type conversion for value buffer
-    let value = sc_convert_buf(value_arg, value_cageid, cageid) as *const libc::c_void;
-    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
-    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
-
-    // Validate unused arg
-    if !sc_unusedarg(arg6, arg6_cageid) {
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "setxattr_syscall"
+            "lremovexattr_syscall"
         );
     }
 
-    // Call to kernel setxattr
-    let ret = unsafe { libc::setxattr(path.as_ptr(), name.as_ptr(), value, size, flags) };
-
+    let ret = unsafe { libc::lremovexattr(path.as_ptr(), name.as_ptr()) };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "setxattr");
+        return handle_errno(errno, "lremovexattr");
     }
 
     ret
 }
 
-/// Linux reference: https://man7.org/linux/man-pages/man2/listxattr.2.html
+/// Linux reference: https://man7.org/linux/man-pages/man2/fremovexattr.2.html
 ///
-/// Retrieves the list of extended attribute names associated with the given `path`.
+/// Removes an extended attribute from the open file referred to by the virtual file
+/// descriptor `fd`.
 ///
 /// ## Arguments:
 /// * `cageid` - Cage identifier
-/// * `path_arg` - Path to the file
-/// * `path_cageid` - Cage ID for path
-/// * `list_arg` - Pointer to buffer to store the list of attribute names
-/// * `list_cageid` - Cage ID for list
-/// * `size_arg` - Size of the buffer
-/// * `size_cageid` - Cage ID for size
+/// * `vfd_arg` - Virtual file descriptor of the open file
+/// * `name_arg` - Name of the extended attribute to remove
 ///
 /// ## Returns:
-/// On success, returns the size of the list of attribute names. If `list` is NULL and `size` is zero,
-/// returns the size of the buffer needed to store the list.
-/// On error, -1 is returned and errno is set appropriately.
-pub extern "C" fn listxattr_syscall(
+/// On success, 0 is returned. On error, a negative errno is returned.
+pub extern "C" fn fremovexattr_syscall(
     cageid: u64,
-    path_arg: u64,
-    path_cageid: u64,
-    list_arg: u64,
-    list_cageid: u64,
-    size_arg: u64,
-    size_cageid: u64,
+    vfd_arg: u64,
+    vfd_cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
     arg5: u64,
@@ -5591,39 +8717,31 @@ pub extern "C" fn listxattr_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    // Type conversion for path
-    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
-        Ok(path) => path,
-        Err(e) => return syscall_error(e, "listxattr", "path conversion failed"),
-    };
-
-    // Type conversion for list buffer (may be NULL)
-    let list = if list_arg == 0 {
-        std::ptr::null_mut()
-    } else {
-        sc_convert_to_u8_mut(list_arg, list_cageid, cageid) as *mut libc::c_char
+    let kernel_fd = convert_fd_to_host(vfd_arg, vfd_cageid, cageid);
+    if kernel_fd < 0 {
+        return handle_errno(-kernel_fd, "fremovexattr");
+    }
+    let name = match xattr_name_to_host(name_arg, "fremovexattr") {
+        Ok(name) => name,
+        Err(e) => return e,
     };
-    let size = sc_convert_sysarg_to_usize(size_arg, size_cageid, cageid);
 
-    // Validate unused args
-    if !(sc_unusedarg(arg4, arg4_cageid)
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
         && sc_unusedarg(arg5, arg5_cageid)
         && sc_unusedarg(arg6, arg6_cageid))
     {
         panic!(
             "{}: unused arguments contain unexpected values -- security violation",
-            "listxattr_syscall"
+            "fremovexattr_syscall"
         );
     }
 
-    // Call to kernel listxattr
-    let ret = unsafe { libc::listxattr(path.as_ptr(), list, size) };
-
+    let ret = unsafe { libc::fremovexattr(kernel_fd, name.as_ptr()) };
     if ret < 0 {
         let errno = get_errno();
-        return handle_errno(errno, "listxattr");
+        return handle_errno(errno, "fremovexattr");
     }
 
-    // Convert ssize_t to i32 safely
-    ret.try_into().unwrap_or(i32::MAX)
+    ret
 }
@@ -0,0 +1,164 @@
+//! Futex operations cages cannot pass straight to the host
+//!
+//! Lind threads share the cage's linear memory, so most futex operations run directly on the host
+//! futex at the translated address. `futex_syscall` handles two differences here:
+//!
+//! - Timeouts: glibc always passes a `struct __timespec64` (64-bit seconds, then 32-bit
+//!   nanoseconds and 32 bits of padding), which `guest_timespec` copies into a host `timespec`.
+//! - PI futexes: the host would read the owner in the futex word as a host TID, but cages store
+//!   lind TIDs there. `lock_pi` and `unlock_pi` keep the PI futex protocol on the word themselves
+//!   and sleep on host FUTEX_WAIT_BITSET. Ownership, FUTEX_WAITERS and FUTEX_OWNER_DIED behave as
+//!   on Linux, but there is no priority inheritance, since cage priorities are bookkeeping only
+//!   (see `crate::sched`).
+//!
+//! Sleeping and waking on PI futexes uses shared host futex operations, like the robust list walk
+//! in `cage::futex`, so that a waiter sleeping in `lock_pi` is woken by either, and so that PI
+//! futexes in memory shared between cages are keyed by the backing object. Lind TIDs are only
+//! unique within a cage, though, so a PI mutex shared between cages cannot tell apart owners
+//! with the same TID.
+use cage::futex_wake_one;
+use cage::signal::thread_check_killed;
+use std::ptr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use sysdefs::constants::err_const::{get_errno, handle_errno, syscall_error, Errno};
+use sysdefs::constants::sys_const::{
+    FUTEX_BITSET_MATCH_ANY, FUTEX_CLOCK_REALTIME, FUTEX_OWNER_DIED, FUTEX_TID_MASK, FUTEX_WAITERS,
+    FUTEX_WAIT_BITSET,
+};
+
+/// Reads the `struct __timespec64` at host address `addr`, or returns `None` if `addr` is null.
+pub fn guest_timespec(addr: u64) -> Option<libc::timespec> {
+    if addr == 0 {
+        return None;
+    }
+    // SAFETY: glibc translated `addr` to the host address of a timespec in the cage's memory. The
+    // padding after tv_nsec is not read, as glibc leaves it uninitialized.
+    unsafe {
+        Some(libc::timespec {
+            tv_sec: ptr::read_unaligned(addr as *const i64),
+            tv_nsec: ptr::read_unaligned((addr + 8) as *const i32) as libc::c_long,
+        })
+    }
+}
+
+/// Sleeps on the host futex at `addr` while it holds `val`, until woken or until `abstime` on
+/// CLOCK_REALTIME (if `realtime`) or CLOCK_MONOTONIC. Returns 0 once woken, or the host errno.
+fn wait_bitset(addr: usize, val: u32, abstime: Option<&libc::timespec>, realtime: bool) -> i32 {
+    let op = if realtime {
+        FUTEX_WAIT_BITSET | FUTEX_CLOCK_REALTIME
+    } else {
+        FUTEX_WAIT_BITSET
+    };
+    let timeout = abstime.map_or(ptr::null(), |ts| ts as *const libc::timespec);
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            addr,
+            op,
+            val,
+            timeout,
+            0usize,
+            FUTEX_BITSET_MATCH_ANY,
+        )
+    };
+    if ret < 0 {
+        get_errno()
+    } else {
+        0
+    }
+}
+
+/// Takes the PI futex at host address `addr` for thread `tid` of cage `cageid`, for
+/// FUTEX_LOCK_PI, FUTEX_LOCK_PI2 and FUTEX_TRYLOCK_PI.
+///
+/// A word whose TID bits are 0 is free, and is taken by storing `tid` in them. FUTEX_OWNER_DIED
+/// is kept so that glibc reports EOWNERDEAD, and FUTEX_WAITERS so that other waiters are still
+/// woken. Otherwise the caller sets FUTEX_WAITERS, so that the owner unlocks through
+/// FUTEX_UNLOCK_PI, and sleeps until `abstime`. A thread that slept takes the lock with
+/// FUTEX_WAITERS set, as others may still be waiting.
+///
+/// # Returns
+/// * 0 once the lock is taken
+/// * EDEADLK if `tid` already owns it
+/// * EAGAIN, with `trylock`, if another thread owns it
+/// * ETIMEDOUT if `abstime` passed
+/// * EINTR if the thread is being killed
+pub fn lock_pi(
+    cageid: u64,
+    addr: usize,
+    tid: u32,
+    abstime: Option<libc::timespec>,
+    realtime: bool,
+    trylock: bool,
+) -> i32 {
+    // SAFETY: `futex_syscall` checked that `addr` is an aligned, writable word of the cage
+    let word = unsafe { &*(addr as *const AtomicU32) };
+    let mut waited = false;
+    loop {
+        let uval = word.load(Ordering::SeqCst);
+        let owner = uval & FUTEX_TID_MASK;
+        if owner == 0 {
+            let mut locked = tid | (uval & (FUTEX_OWNER_DIED | FUTEX_WAITERS));
+            if waited {
+                locked |= FUTEX_WAITERS;
+            }
+            if word
+                .compare_exchange(uval, locked, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+            {
+                return 0;
+            }
+            continue;
+        }
+        if owner == tid {
+            return syscall_error(Errno::EDEADLK, "futex", "caller already owns the PI futex");
+        }
+        if trylock {
+            return syscall_error(
+                Errno::EAGAIN,
+                "futex",
+                "PI futex is owned by another thread",
+            );
+        }
+
+        let waiting = uval | FUTEX_WAITERS;
+        if uval != waiting
+            && word
+                .compare_exchange(uval, waiting, Ordering::SeqCst, Ordering::SeqCst)
+                .is_err()
+        {
+            continue;
+        }
+        match wait_bitset(addr, waiting, abstime.as_ref(), realtime) {
+            0 | libc::EAGAIN => {}
+            // Interrupted to let the thread see it is being killed; otherwise keep waiting, as
+            // Linux restarts FUTEX_LOCK_PI after a signal
+            libc::EINTR => {
+                if thread_check_killed(cageid, tid as u64) {
+                    return syscall_error(Errno::EINTR, "futex", "thread is being killed");
+                }
+            }
+            errno => return handle_errno(errno, "futex"),
+        }
+        waited = true;
+    }
+}
+
+/// Releases the PI futex at host address `addr` held by thread `tid`, for FUTEX_UNLOCK_PI, and
+/// wakes one waiter if FUTEX_WAITERS was set.
+///
+/// # Returns
+/// * 0 on success
+/// * EPERM if `tid` does not own the futex
+pub fn unlock_pi(addr: usize, tid: u32) -> i32 {
+    // SAFETY: `futex_syscall` checked that `addr` is an aligned, writable word of the cage
+    let word = unsafe { &*(addr as *const AtomicU32) };
+    if word.load(Ordering::SeqCst) & FUTEX_TID_MASK != tid {
+        return syscall_error(Errno::EPERM, "futex", "caller does not own the PI futex");
+    }
+    // Only waiters change the word while we own it, and only to set FUTEX_WAITERS
+    if word.swap(0, Ordering::SeqCst) & FUTEX_WAITERS != 0 {
+        futex_wake_one(addr);
+    }
+    0
+}
@@ -1,17 +1,24 @@
 use crate::fs_calls::kernel_close;
+use crate::inproc_socket::inproc_socket_close;
+use crate::mqueue::mqueue_close;
+use crate::netns;
+use crate::sched;
 use crate::sys_calls::exit_group_syscall;
 use crate::syscall_table::*;
+use crate::uts;
+use cage::memory::quota::MemQuota;
 use cage::{add_cage, cagetable_clear, cagetable_init, timer::IntervalTimer, Cage, Vmmap};
 use dashmap::DashMap;
 use fdtables;
 use parking_lot::{Mutex, RwLock};
 use std::ffi::CString;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering::*};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64, Ordering::*};
 use std::sync::Arc;
 use sysdefs::constants::{
-    EXIT_SUCCESS, FDKIND_KERNEL, INIT_CAGEID, MAIN_THREADID, RAWPOSIX_CAGEID, STDERR_FILENO,
-    STDIN_FILENO, STDOUT_FILENO, THREEI_CAGEID, UNUSED_ARG, UNUSED_ID, VERBOSE,
+    DEFAULT_UMASK, EXIT_SUCCESS, FDKIND_INPROC_SOCKET, FDKIND_KERNEL, FDKIND_MQUEUE, INIT_CAGEID,
+    MAIN_THREADID, RAWPOSIX_CAGEID, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, THREEI_CAGEID,
+    UNUSED_ARG, UNUSED_ID, VERBOSE,
 };
 use threei::{
     copy_data_between_cages, copy_handler_table_to_cage, register_handler,
@@ -180,8 +187,10 @@ pub fn register_threei_syscall(self_cageid: u64) -> i32 {
     0
 }
 
-/// Raise the host process soft fd limit to the hard maximum. Lind supports up to 1024 cages
-/// each with up to 1024 fds, so the default soft limit (typically 1024) is too low.
+/// Raise the host process fd limit so it can back every cage's fds. Cages share a global pool
+/// of up to `fdtables::TOTAL_FD_MAX` fds, so the default soft limit (typically 1024) is far too
+/// low. If the host won't go that high, the global cap in fdtables is lowered to match, so cages
+/// get ENFILE rather than a failure from the host.
 fn init_fd_limit() {
     unsafe {
         let mut lim = libc::rlimit {
@@ -193,11 +202,26 @@ fn init_fd_limit() {
             panic!("getrlimit failed: {}", std::io::Error::last_os_error());
         }
 
-        lim.rlim_cur = lim.rlim_max; // raise soft to hard
+        // Raising the hard limit needs privilege, so this may fail.  If it does, we make do with
+        // the hard limit we have.
+        let wanted = fdtables::TOTAL_FD_MAX as libc::rlim_t;
+        if lim.rlim_max < wanted {
+            let raised = libc::rlimit {
+                rlim_cur: wanted,
+                rlim_max: wanted,
+            };
+            if libc::setrlimit(libc::RLIMIT_NOFILE, &raised) == 0 {
+                lim = raised;
+            }
+        }
+
+        lim.rlim_cur = std::cmp::min(lim.rlim_max, wanted); // raise soft to hard
 
         if libc::setrlimit(libc::RLIMIT_NOFILE, &lim) != 0 {
             panic!("setrlimit failed: {}", std::io::Error::last_os_error());
         }
+
+        fdtables::set_total_fd_max(std::cmp::min(fdtables::TOTAL_FD_MAX, lim.rlim_cur as u64));
     }
 }
 
@@ -243,11 +267,23 @@ pub fn rawposix_start(verbosity: isize) {
 
     init_fd_limit();
 
+    // File creation masks are tracked per cage (see `Cage::umask`) and applied by rawposix
+    // before calling into the kernel, so the host process itself must not mask anything.
+    unsafe {
+        libc::umask(0);
+    }
+
     // init cage table
     cagetable_init();
 
     // register kernel close to fdtables
     fdtables::register_close_handlers(FDKIND_KERNEL, fdtables::NULL_FUNC, kernel_close);
+    fdtables::register_close_handlers(
+        FDKIND_INPROC_SOCKET,
+        fdtables::NULL_FUNC,
+        inproc_socket_close,
+    );
+    fdtables::register_close_handlers(FDKIND_MQUEUE, fdtables::NULL_FUNC, mqueue_close);
 
     // register syscalls for init cage
     register_rawposix_syscall(INIT_CAGEID);
@@ -271,18 +307,26 @@ pub fn rawposix_start(verbosity: isize) {
     let initcage = Cage {
         cageid: INIT_CAGEID,
         cwd: RwLock::new(Arc::new(PathBuf::from("/"))),
+        umask: AtomicU32::new(DEFAULT_UMASK),
+        netns: AtomicU64::new(netns::initial_netns()),
+        utsns: AtomicU64::new(uts::INITIAL_UTSNS),
+        cpu_affinity: RwLock::new(sched::initial_affinity()),
+        nice: AtomicI32::new(0),
         parent: INIT_CAGEID,
         rev_shm: Mutex::new(Vec::new()),
         main_threadid: RwLock::new(0),
         interval_timer: IntervalTimer::new(INIT_CAGEID),
         epoch_handler: DashMap::new(),
         os_tid_map: DashMap::new(),
+        robust_list: DashMap::new(),
         signalhandler: DashMap::new(),
         pending_signals: RwLock::new(vec![]),
         sigset: AtomicU64::new(0),
         zombies: RwLock::new(vec![]),
         child_num: AtomicU64::new(0),
         vmmap: RwLock::new(Vmmap::new()),
+        memory_snapshot: Mutex::new(None),
+        mem_quota: MemQuota::new(INIT_CAGEID),
         final_exit_status: RwLock::new(None),
         exit_group_initiated: AtomicBool::new(false),
         is_dead: AtomicBool::new(false),
@@ -0,0 +1,1286 @@
+//! In-process AF_UNIX stream sockets
+//!
+//! All cages run inside one host process, so a UNIX domain connection between two cages does not
+//! need the host kernel: each direction of the connection is a ring buffer in host memory that
+//! both ends copy into and out of directly. This module implements such sockets as their own
+//! fdtables fd kind (`FDKIND_INPROC_SOCKET`). It is always compiled, but sockets are only created
+//! in-process when rawposix is built with the `inproc-sockets` feature.
+//!
+//! Scope and interaction with the host:
+//! - Only `AF_UNIX`/`SOCK_STREAM` sockets are created in-process. Datagram and seqpacket sockets,
+//!   and every other family (including loopback TCP), remain host kernel sockets.
+//! - Binding to a pathname also binds a host "anchor" socket to that path, so the socket node
+//!   exists in the filesystem with the usual permissions and `EADDRINUSE` behaviour. Processes
+//!   outside Lind cannot connect to an in-process listener.
+//! - Connecting to an address no cage is listening on swaps the in-process socket for a host
+//!   kernel socket in the same fd slot and connects that instead, so host daemons stay reachable.
+//!   Other fds that share the socket (through `dup` or `fork`) keep the in-process socket.
+//! - `SCM_RIGHTS` can pass fds backed by host fds; the receiver gets duplicates of them, attached
+//!   to the bytes of the send that carried them as on Linux. Passing an in-process socket or a
+//!   message queue, and every other control message, fails with `EOPNOTSUPP`.
+//!
+//! Readiness: every socket owns two eventfds that mirror whether it is readable and writable.
+//! The read-side eventfd doubles as the fdtables `underfd`, so `fcntl(F_SETFL)` and `FIONBIO`
+//! land on it, and its `O_NONBLOCK` flag is the socket's nonblocking flag. `poll`, `select` and
+//! `epoll` wait on the eventfds alongside ordinary host fds and then report events computed from
+//! the socket state. The mirrors are only kept up to date once a socket has been watched.
+use cage::{signal::signal::lind_send_signal, signal_check_trigger};
+use dashmap::DashMap;
+use fdtables;
+use lazy_static::lazy_static;
+use libc::{
+    c_void, iovec, sockaddr, sockaddr_un, socklen_t, ucred, AF_UNIX, EFD_CLOEXEC, EFD_NONBLOCK,
+    EPOLLERR, EPOLLET, EPOLLHUP, EPOLLIN, EPOLLONESHOT, EPOLLOUT, EPOLLPRI, EPOLLRDHUP,
+    EPOLL_CTL_ADD, EPOLL_CTL_DEL, MSG_DONTWAIT, MSG_NOSIGNAL, MSG_PEEK, MSG_WAITALL, O_NONBLOCK,
+    POLLERR, POLLHUP, POLLIN, POLLOUT, POLLRDHUP, POLLRDNORM, POLLWRNORM, SHUT_RD, SHUT_RDWR,
+    SHUT_WR, SOCK_CLOEXEC, SOCK_NONBLOCK, SOCK_STREAM, SOL_SOCKET, SO_ACCEPTCONN, SO_DOMAIN,
+    SO_ERROR, SO_KEEPALIVE, SO_LINGER, SO_PASSCRED, SO_PEERCRED, SO_PROTOCOL, SO_RCVBUF,
+    SO_RCVTIMEO, SO_REUSEADDR, SO_SNDBUF, SO_SNDTIMEO, SO_TYPE,
+};
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use std::{mem, ptr};
+use sysdefs::constants::err_const::{get_errno, Errno};
+use sysdefs::constants::fs_const::S_IRWXA;
+use sysdefs::constants::lind_platform_const::{FDKIND_INPROC_SOCKET, FDKIND_KERNEL};
+use sysdefs::constants::net_const::{SO_RCVTIMEO_NEW, SO_SNDTIMEO_NEW};
+use sysdefs::constants::sys_const::SIGPIPE;
+
+/// Whether new AF_UNIX stream sockets are created in-process.
+pub const ENABLED: bool = cfg!(feature = "inproc-sockets");
+
+/// Bytes buffered per direction of a connection, matching the default Linux `SO_SNDBUF` for
+/// UNIX sockets.
+pub const RING_CAPACITY: usize = 212992;
+
+/// Largest accepted `listen()` backlog.
+const MAX_BACKLOG: usize = 4096;
+
+/// Length of `sun_family` at the start of a `sockaddr_un`.
+const FAMILY_LEN: usize = mem::size_of::<libc::sa_family_t>();
+
+/// Blocking operations wake up this often to check for pending signals.
+const WAIT_CHUNK: Duration = Duration::from_millis(100);
+
+/// Identity of a bound name: the inode of a pathname socket node, or the bytes of an abstract name.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum NameKey {
+    Path(u64, u64),
+    Abstract(Vec<u8>),
+}
+
+lazy_static! {
+    // Every open in-process socket, keyed by its fdtables underfd.
+    static ref SOCKETS: DashMap<u64, Arc<InprocSocket>> = DashMap::new();
+    // Bound names of in-process sockets, for connect() to find listeners.
+    static ref NAMES: Mutex<HashMap<NameKey, Weak<InprocSocket>>> = Mutex::new(HashMap::new());
+    // Event masks of in-process sockets added to an epoll instance, keyed by (host epfd, underfd).
+    static ref EPOLL_INTEREST: Mutex<HashMap<(u64, u64), u32>> = Mutex::new(HashMap::new());
+}
+
+static AUTOBIND_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn host_errno() -> Errno {
+    Errno::from_discriminant(get_errno()).unwrap_or(Errno::EIO)
+}
+
+/// Fixed-capacity byte queue for one direction of a connection. The storage is allocated on the
+/// first write, so idle connections stay cheap.
+struct RingBuffer {
+    buf: Vec<u8>,
+    head: usize,
+    len: usize,
+    /// Stream offset of the byte at `head`, i.e. how many bytes were ever consumed.
+    start: u64,
+}
+
+impl RingBuffer {
+    fn new() -> Self {
+        RingBuffer {
+            buf: Vec::new(),
+            head: 0,
+            len: 0,
+            start: 0,
+        }
+    }
+
+    fn space(&self) -> usize {
+        RING_CAPACITY - self.len
+    }
+
+    /// Appends as much of `src` as fits and returns the number of bytes taken.
+    fn write(&mut self, src: &[u8]) -> usize {
+        if self.buf.is_empty() {
+            self.buf = vec![0; RING_CAPACITY];
+        }
+        let n = src.len().min(self.space());
+        let tail = (self.head + self.len) % RING_CAPACITY;
+        let first = n.min(RING_CAPACITY - tail);
+        self.buf[tail..tail + first].copy_from_slice(&src[..first]);
+        self.buf[..n - first].copy_from_slice(&src[first..n]);
+        self.len += n;
+        n
+    }
+
+    /// Copies bytes starting `offset` bytes into the queue without removing them.
+    fn peek(&self, offset: usize, dst: &mut [u8]) -> usize {
+        let n = dst.len().min(self.len.saturating_sub(offset));
+        let start = (self.head + offset) % RING_CAPACITY;
+        let first = n.min(RING_CAPACITY - start);
+        dst[..first].copy_from_slice(&self.buf[start..start + first]);
+        dst[first..n].copy_from_slice(&self.buf[..n - first]);
+        n
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.len -= n;
+        self.start += n as u64;
+        self.head = if self.len == 0 {
+            0
+        } else {
+            (self.head + n) % RING_CAPACITY
+        };
+    }
+
+    fn clear(&mut self) {
+        self.start += self.len as u64;
+        self.buf = Vec::new();
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+/// Walks a host iovec array, tracking how much of it has been filled or drained.
+struct IoCursor<'a> {
+    iov: &'a [iovec],
+    index: usize,
+    offset: usize,
+}
+
+impl<'a> IoCursor<'a> {
+    fn new(iov: &'a [iovec]) -> Self {
+        IoCursor {
+            iov,
+            index: 0,
+            offset: 0,
+        }
+    }
+
+    /// The unprocessed part of the current buffer, or `None` once every buffer is done.
+    fn chunk(&mut self) -> Option<&'a mut [u8]> {
+        while self.index < self.iov.len() {
+            let v = &self.iov[self.index];
+            if self.offset < v.iov_len {
+                return Some(unsafe {
+                    std::slice::from_raw_parts_mut(
+                        (v.iov_base as *mut u8).add(self.offset),
+                        v.iov_len - self.offset,
+                    )
+                });
+            }
+            self.index += 1;
+            self.offset = 0;
+        }
+        None
+    }
+
+    fn advance(&mut self, n: usize) {
+        self.offset += n;
+    }
+}
+
+fn iov_total(iov: &[iovec]) -> usize {
+    iov.iter().map(|v| v.iov_len).sum()
+}
+
+/// The pair of eventfds mirroring a socket's readiness for `poll`/`select`/`epoll`.
+struct Readiness {
+    readable: i32,
+    writable: i32,
+    watched: AtomicBool,
+    mirror: Mutex<(bool, bool)>,
+}
+
+impl Readiness {
+    fn new() -> Result<Self, Errno> {
+        let readable = unsafe { libc::eventfd(0, EFD_CLOEXEC) };
+        if readable < 0 {
+            return Err(host_errno());
+        }
+        let writable = unsafe { libc::eventfd(0, EFD_CLOEXEC | EFD_NONBLOCK) };
+        if writable < 0 {
+            let err = host_errno();
+            unsafe { libc::close(readable) };
+            return Err(err);
+        }
+        Ok(Readiness {
+            readable,
+            writable,
+            watched: AtomicBool::new(false),
+            mirror: Mutex::new((false, false)),
+        })
+    }
+
+    /// Brings the eventfds in line with the socket state. `None` leaves that side unchanged.
+    fn update(&self, readable: Option<bool>, writable: Option<bool>) {
+        if !self.watched.load(Ordering::Acquire) {
+            return;
+        }
+        let mut mirror = self.mirror.lock();
+        if let Some(r) = readable {
+            if r != mirror.0 {
+                Self::set(self.readable, r);
+                mirror.0 = r;
+            }
+        }
+        if let Some(w) = writable {
+            if w != mirror.1 {
+                Self::set(self.writable, w);
+                mirror.1 = w;
+            }
+        }
+    }
+
+    /// Signals or drains one eventfd. Draining only happens after a signal, so the read never
+    /// blocks even when the socket (and with it the read-side eventfd) is in blocking mode.
+    fn set(fd: i32, on: bool) {
+        let mut value: u64 = 1;
+        unsafe {
+            if on {
+                libc::write(fd, &value as *const u64 as *const c_void, 8);
+            } else {
+                libc::read(fd, &mut value as *mut u64 as *mut c_void, 8);
+            }
+        }
+    }
+
+    fn unwatch(&self) {
+        self.watched.store(false, Ordering::Release);
+        let mut mirror = self.mirror.lock();
+        if mirror.0 {
+            Self::set(self.readable, false);
+        }
+        if mirror.1 {
+            Self::set(self.writable, false);
+        }
+        *mirror = (false, false);
+    }
+}
+
+impl Drop for Readiness {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.readable);
+            libc::close(self.writable);
+        }
+    }
+}
+
+/// Host fds in flight through `SCM_RIGHTS`. They are duplicates owned by this value and closed
+/// when it is dropped without being handed to a receiver.
+#[derive(Default)]
+pub struct PassedFds(Vec<i32>);
+
+impl PassedFds {
+    /// Takes ownership of a duplicate of `hostfd`.
+    pub fn push_dup(&mut self, hostfd: i32) -> Result<(), Errno> {
+        let fd = unsafe { libc::fcntl(hostfd, libc::F_DUPFD_CLOEXEC, 0) };
+        if fd < 0 {
+            return Err(host_errno());
+        }
+        self.0.push(fd);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Hands the fds over to the caller, who becomes responsible for closing them.
+    pub fn into_fds(mut self) -> Vec<i32> {
+        mem::take(&mut self.0)
+    }
+}
+
+impl Drop for PassedFds {
+    fn drop(&mut self) {
+        for fd in self.0.drain(..) {
+            unsafe { libc::close(fd) };
+        }
+    }
+}
+
+/// Fds sent along with the stream bytes `start..end`, the bytes of the send that carried them.
+struct Rights {
+    start: u64,
+    end: u64,
+    fds: PassedFds,
+}
+
+struct ChannelState {
+    ring: RingBuffer,
+    /// Fds not yet received, in stream order.
+    rights: VecDeque<Rights>,
+    /// The sending end shut down or closed: readers see EOF once the ring is drained.
+    writer_closed: bool,
+    /// The receiving end shut down or closed: writers get `EPIPE`.
+    reader_closed: bool,
+    reader: Weak<Readiness>,
+    writer: Weak<Readiness>,
+}
+
+impl ChannelState {
+    fn readable(&self) -> bool {
+        self.ring.len > 0 || self.writer_closed || self.reader_closed
+    }
+
+    fn writable(&self) -> bool {
+        self.ring.space() > 0 || self.writer_closed || self.reader_closed
+    }
+
+    fn closed(&self) -> bool {
+        self.writer_closed || self.reader_closed
+    }
+
+    fn sync(&self) {
+        if let Some(reader) = self.reader.upgrade() {
+            reader.update(Some(self.readable()), None);
+        }
+        if let Some(writer) = self.writer.upgrade() {
+            writer.update(None, Some(self.writable()));
+        }
+    }
+}
+
+/// One direction of a connection.
+struct Channel {
+    state: Mutex<ChannelState>,
+    cond: Condvar,
+}
+
+impl Channel {
+    fn new(reader: &Arc<Readiness>, writer: &Arc<Readiness>) -> Arc<Self> {
+        Arc::new(Channel {
+            state: Mutex::new(ChannelState {
+                ring: RingBuffer::new(),
+                rights: VecDeque::new(),
+                writer_closed: false,
+                reader_closed: false,
+                reader: Arc::downgrade(reader),
+                writer: Arc::downgrade(writer),
+            }),
+            cond: Condvar::new(),
+        })
+    }
+
+    /// Applies `f` to the state, then refreshes both ends' readiness and wakes any waiters.
+    fn modify(&self, f: impl FnOnce(&mut ChannelState)) {
+        let mut st = self.state.lock();
+        f(&mut st);
+        st.sync();
+        drop(st);
+        self.cond.notify_all();
+    }
+}
+
+enum Conn {
+    Unconnected,
+    Listening {
+        backlog: usize,
+        queue: VecDeque<Arc<InprocSocket>>,
+    },
+    Connected {
+        rx: Arc<Channel>,
+        tx: Arc<Channel>,
+    },
+}
+
+struct SocketState {
+    conn: Conn,
+    /// Bound address as `sockaddr_un` bytes, family included.
+    local: Option<Vec<u8>>,
+    peer: Option<Vec<u8>>,
+    name: Option<NameKey>,
+    /// Host socket holding the filesystem node of a pathname bind.
+    anchor: Option<i32>,
+    /// Cage that connected or listened, reported to the peer through `SO_PEERCRED`.
+    cred_cage: u64,
+    peer_cage: u64,
+}
+
+/// An in-process AF_UNIX stream socket.
+pub struct InprocSocket {
+    readiness: Arc<Readiness>,
+    state: Mutex<SocketState>,
+    cond: Condvar,
+}
+
+impl InprocSocket {
+    fn new() -> Result<Arc<Self>, Errno> {
+        Ok(Arc::new(InprocSocket {
+            readiness: Arc::new(Readiness::new()?),
+            state: Mutex::new(SocketState {
+                conn: Conn::Unconnected,
+                local: None,
+                peer: None,
+                name: None,
+                anchor: None,
+                cred_cage: 0,
+                peer_cage: 0,
+            }),
+            cond: Condvar::new(),
+        }))
+    }
+
+    /// The fdtables underfd of this socket.
+    pub fn underfd(&self) -> u64 {
+        self.readiness.readable as u64
+    }
+
+    /// The eventfds to wait on for read and write readiness.
+    pub fn eventfds(&self) -> (i32, i32) {
+        (self.readiness.readable, self.readiness.writable)
+    }
+
+    pub fn nonblocking(&self) -> bool {
+        unsafe { libc::fcntl(self.readiness.readable, libc::F_GETFL) & O_NONBLOCK != 0 }
+    }
+
+    pub fn set_nonblocking(&self) {
+        unsafe {
+            let flags = libc::fcntl(self.readiness.readable, libc::F_GETFL);
+            libc::fcntl(self.readiness.readable, libc::F_SETFL, flags | O_NONBLOCK);
+        }
+    }
+
+    pub fn connected(&self) -> bool {
+        matches!(self.state.lock().conn, Conn::Connected { .. })
+    }
+
+    fn channels(&self) -> Option<(Arc<Channel>, Arc<Channel>)> {
+        match &self.state.lock().conn {
+            Conn::Connected { rx, tx } => Some((rx.clone(), tx.clone())),
+            _ => None,
+        }
+    }
+
+    /// Recomputes both mirrors from the socket state.
+    fn sync(&self) {
+        let st = self.state.lock();
+        let (readable, writable) = match &st.conn {
+            Conn::Unconnected => (true, true),
+            Conn::Listening { queue, .. } => (!queue.is_empty(), false),
+            Conn::Connected { rx, tx } => (rx.state.lock().readable(), tx.state.lock().writable()),
+        };
+        self.readiness.update(Some(readable), Some(writable));
+    }
+
+    /// Starts maintaining the readiness eventfds. Called before the socket is waited on.
+    pub fn watch(&self) {
+        if !self.readiness.watched.swap(true, Ordering::AcqRel) {
+            self.sync();
+        }
+    }
+
+    /// The `poll` events currently true for this socket, limited to `events` plus the
+    /// always-reported `POLLHUP`/`POLLERR`.
+    pub fn poll_events(&self, events: i16) -> i16 {
+        let st = self.state.lock();
+        let mut revents = 0;
+        match &st.conn {
+            // Linux reports an unconnected stream socket as hung up and writable
+            Conn::Unconnected => revents |= POLLOUT | POLLWRNORM | POLLHUP,
+            Conn::Listening { queue, .. } => {
+                if !queue.is_empty() {
+                    revents |= POLLIN | POLLRDNORM;
+                }
+            }
+            Conn::Connected { rx, tx } => {
+                let r = rx.state.lock();
+                if r.readable() {
+                    revents |= POLLIN | POLLRDNORM;
+                }
+                if r.closed() {
+                    revents |= POLLRDHUP;
+                }
+                let rx_closed = r.closed();
+                drop(r);
+                let t = tx.state.lock();
+                if t.writable() {
+                    revents |= POLLOUT | POLLWRNORM;
+                }
+                if rx_closed && t.closed() {
+                    revents |= POLLHUP;
+                }
+            }
+        }
+        revents & (events | POLLHUP | POLLERR)
+    }
+
+    /// Sleeps on `cond` for one chunk, or fails with `EINTR` if a signal is pending.
+    fn wait<T>(cageid: u64, cond: &Condvar, guard: &mut MutexGuard<T>) -> Result<(), Errno> {
+        if signal_check_trigger(cageid) {
+            return Err(Errno::EINTR);
+        }
+        cond.wait_for(guard, WAIT_CHUNK);
+        Ok(())
+    }
+
+    /// Copies the iovecs into the send channel, blocking until all of it is queued unless the
+    /// socket is nonblocking or `MSG_DONTWAIT` is given.
+    pub fn send(&self, cageid: u64, iov: &[iovec], flags: i32) -> Result<usize, Errno> {
+        self.sendmsg(cageid, iov, flags, PassedFds::default())
+    }
+
+    /// `send()` that also passes `fds` to the peer, attached to the bytes it queues. The fds are
+    /// dropped if nothing is queued.
+    pub fn sendmsg(
+        &self,
+        cageid: u64,
+        iov: &[iovec],
+        flags: i32,
+        fds: PassedFds,
+    ) -> Result<usize, Errno> {
+        let (_, tx) = self.channels().ok_or(Errno::ENOTCONN)?;
+        let total = iov_total(iov);
+        let mut cursor = IoCursor::new(iov);
+        let mut sent = 0;
+        let mut fds = (!fds.is_empty()).then_some(fds);
+        let mut st = tx.state.lock();
+        let mut origin = st.ring.start + st.ring.len as u64;
+        loop {
+            if st.closed() {
+                drop(st);
+                if sent > 0 {
+                    return Ok(sent);
+                }
+                if flags & MSG_NOSIGNAL == 0 {
+                    lind_send_signal(cageid, SIGPIPE);
+                }
+                return Err(Errno::EPIPE);
+            }
+
+            if sent == 0 {
+                origin = st.ring.start + st.ring.len as u64;
+            }
+            let before = sent;
+            while let Some(chunk) = cursor.chunk() {
+                let n = st.ring.write(chunk);
+                if n == 0 {
+                    break;
+                }
+                cursor.advance(n);
+                sent += n;
+            }
+            if sent > before {
+                let end = origin + sent as u64;
+                if let Some(fds) = fds.take() {
+                    st.rights.push_back(Rights {
+                        start: origin,
+                        end,
+                        fds,
+                    });
+                } else if let Some(rights) = st.rights.back_mut().filter(|r| r.start == origin) {
+                    rights.end = end;
+                }
+                st.sync();
+                tx.cond.notify_all();
+            }
+            if sent == total {
+                return Ok(sent);
+            }
+
+            let result = if flags & MSG_DONTWAIT != 0 || self.nonblocking() {
+                Err(Errno::EAGAIN)
+            } else {
+                Self::wait(cageid, &tx.cond, &mut st)
+            };
+            if let Err(e) = result {
+                return if sent > 0 { Ok(sent) } else { Err(e) };
+            }
+        }
+    }
+
+    /// Copies queued bytes into the iovecs. Returns 0 at end of stream. Fds passed with the
+    /// bytes are closed, as `recv()` on Linux does.
+    pub fn recv(&self, cageid: u64, iov: &[iovec], flags: i32) -> Result<usize, Errno> {
+        self.recvmsg(cageid, iov, flags).map(|(n, _)| n)
+    }
+
+    /// `recv()` that also returns the fds passed with the bytes read. Like Linux, a read that
+    /// reaches bytes carrying fds takes them and stops at the end of the send they came with, so
+    /// fds of two sends are never returned together. `MSG_PEEK` returns duplicates.
+    pub fn recvmsg(
+        &self,
+        cageid: u64,
+        iov: &[iovec],
+        flags: i32,
+    ) -> Result<(usize, PassedFds), Errno> {
+        let (rx, _) = self.channels().ok_or(Errno::ENOTCONN)?;
+        let total = iov_total(iov);
+        if total == 0 {
+            return Ok((0, PassedFds::default()));
+        }
+        let mut cursor = IoCursor::new(iov);
+        let mut got = 0;
+        let mut fds = PassedFds::default();
+        // Stream offset the read has to stop at once it took fds
+        let mut stop: Option<u64> = None;
+        let mut st = rx.state.lock();
+        loop {
+            if st.ring.len > 0 {
+                let before = got;
+                while let Some(chunk) = cursor.chunk() {
+                    let offset = if flags & MSG_PEEK != 0 { got } else { 0 };
+                    let pos = st.ring.start + offset as u64;
+                    let limit = match stop {
+                        Some(end) => end.saturating_sub(pos),
+                        None => match st.rights.iter().position(|r| r.end > pos) {
+                            Some(i) if st.rights[i].start <= pos => {
+                                let end = st.rights[i].end;
+                                if flags & MSG_PEEK != 0 {
+                                    for &fd in &st.rights[i].fds.0 {
+                                        fds.push_dup(fd)?;
+                                    }
+                                } else {
+                                    fds = st.rights.remove(i).unwrap().fds;
+                                }
+                                stop = Some(end);
+                                end - pos
+                            }
+                            Some(i) => st.rights[i].start - pos,
+                            None => u64::MAX,
+                        },
+                    };
+                    if limit == 0 {
+                        break;
+                    }
+                    let len = chunk.len().min(limit.try_into().unwrap_or(usize::MAX));
+                    let n = st.ring.peek(offset, &mut chunk[..len]);
+                    if n == 0 {
+                        break;
+                    }
+                    if flags & MSG_PEEK == 0 {
+                        st.ring.consume(n);
+                    }
+                    cursor.advance(n);
+                    got += n;
+                }
+                if flags & MSG_PEEK != 0 {
+                    return Ok((got, fds));
+                }
+                if got > before {
+                    st.sync();
+                    rx.cond.notify_all();
+                }
+                if got == total || flags & MSG_WAITALL == 0 || stop.is_some() {
+                    return Ok((got, fds));
+                }
+            }
+            if st.closed() {
+                return Ok((got, fds));
+            }
+
+            let result = if flags & MSG_DONTWAIT != 0 || self.nonblocking() {
+                Err(Errno::EAGAIN)
+            } else {
+                Self::wait(cageid, &rx.cond, &mut st)
+            };
+            if let Err(e) = result {
+                return if got > 0 { Ok((got, fds)) } else { Err(e) };
+            }
+        }
+    }
+
+    /// Bytes waiting to be read, for `FIONREAD`.
+    pub fn pending(&self) -> usize {
+        match self.channels() {
+            Some((rx, _)) => rx.state.lock().ring.len,
+            None => 0,
+        }
+    }
+
+    /// `bind()`. `addr`/`addrlen` are the host-visible `sockaddr_un` and its length.
+    pub(crate) fn bind(
+        self: &Arc<Self>,
+        cageid: u64,
+        addr: *const sockaddr,
+        addrlen: socklen_t,
+    ) -> Result<(), Errno> {
+        let addr = Self::addr_bytes(addr, addrlen)?;
+        let mut st = self.state.lock();
+        if st.local.is_some() {
+            return Err(Errno::EINVAL);
+        }
+
+        let mut names = NAMES.lock();
+        let (addr, key, anchor) = if addr.len() == FAMILY_LEN {
+            // Autobind: Linux picks an unused five hex digit abstract name
+            loop {
+                let n = AUTOBIND_COUNTER.fetch_add(1, Ordering::Relaxed) & 0xfffff;
+                let mut candidate = addr.clone();
+                candidate.push(0);
+                candidate.extend_from_slice(format!("{:05x}", n).as_bytes());
+                let key = NameKey::Abstract(candidate[FAMILY_LEN..].to_vec());
+                if !Self::name_in_use(&names, &key) {
+                    break (candidate, key, None);
+                }
+            }
+        } else if addr[FAMILY_LEN] == 0 {
+            let key = NameKey::Abstract(addr[FAMILY_LEN..].to_vec());
+            if Self::name_in_use(&names, &key) {
+                return Err(Errno::EADDRINUSE);
+            }
+            (addr, key, None)
+        } else {
+            let (key, anchor) = Self::bind_anchor(cageid, &addr)?;
+            (addr, key, Some(anchor))
+        };
+        names.insert(key.clone(), Arc::downgrade(self));
+
+        st.local = Some(addr);
+        st.name = Some(key);
+        st.anchor = anchor;
+        Ok(())
+    }
+
+    fn name_in_use(names: &HashMap<NameKey, Weak<InprocSocket>>, key: &NameKey) -> bool {
+        names.get(key).is_some_and(|w| w.strong_count() > 0)
+    }
+
+    /// Creates the filesystem node of a pathname bind with a host socket and returns its
+    /// identity along with the host socket.
+    fn bind_anchor(cageid: u64, addr: &[u8]) -> Result<(NameKey, i32), Errno> {
+        let fd = unsafe { libc::socket(AF_UNIX, SOCK_STREAM | SOCK_CLOEXEC, 0) };
+        if fd < 0 {
+            return Err(host_errno());
+        }
+        let mut sun: sockaddr_un = unsafe { mem::zeroed() };
+        unsafe {
+            ptr::copy_nonoverlapping(addr.as_ptr(), &mut sun as *mut _ as *mut u8, addr.len());
+        }
+        let ret = unsafe {
+            libc::bind(
+                fd,
+                &sun as *const _ as *const sockaddr,
+                addr.len() as socklen_t,
+            )
+        };
+        let mut st: libc::stat = unsafe { mem::zeroed() };
+        if ret < 0 || unsafe { libc::stat(sun.sun_path.as_ptr(), &mut st) } < 0 {
+            let err = host_errno();
+            unsafe { libc::close(fd) };
+            return Err(err);
+        }
+        // Same umask handling as a host bind_syscall
+        let mode = crate::fs_calls::apply_cage_umask(cageid, S_IRWXA);
+        unsafe { libc::chmod(sun.sun_path.as_ptr(), mode) };
+        Ok((NameKey::Path(st.st_dev, st.st_ino), fd))
+    }
+
+    /// Copies a `sockaddr_un` out of the caller's buffer, checking its family.
+    fn addr_bytes(addr: *const sockaddr, addrlen: socklen_t) -> Result<Vec<u8>, Errno> {
+        let len = addrlen as usize;
+        if addr.is_null() || len < FAMILY_LEN || len > mem::size_of::<sockaddr_un>() {
+            return Err(Errno::EINVAL);
+        }
+        if unsafe { (*addr).sa_family } as i32 != AF_UNIX {
+            return Err(Errno::EINVAL);
+        }
+        let mut bytes = vec![0u8; len];
+        unsafe { ptr::copy_nonoverlapping(addr as *const u8, bytes.as_mut_ptr(), len) };
+        Ok(bytes)
+    }
+
+    /// Finds the key a `connect()` target is bound under, if it could be an in-process socket.
+    fn lookup_key(addr: &[u8]) -> Option<NameKey> {
+        if addr.len() == FAMILY_LEN {
+            return None;
+        }
+        if addr[FAMILY_LEN] == 0 {
+            return Some(NameKey::Abstract(addr[FAMILY_LEN..].to_vec()));
+        }
+        let mut sun: sockaddr_un = unsafe { mem::zeroed() };
+        unsafe {
+            ptr::copy_nonoverlapping(addr.as_ptr(), &mut sun as *mut _ as *mut u8, addr.len());
+        }
+        let mut st: libc::stat = unsafe { mem::zeroed() };
+        if unsafe { libc::stat(sun.sun_path.as_ptr(), &mut st) } < 0 {
+            return None;
+        }
+        Some(NameKey::Path(st.st_dev, st.st_ino))
+    }
+
+    /// `listen()`.
+    pub fn listen(&self, cageid: u64, backlog: i32) -> Result<(), Errno> {
+        let backlog = (backlog.max(0) as usize).min(MAX_BACKLOG);
+        let mut st = self.state.lock();
+        if st.local.is_none() {
+            return Err(Errno::EINVAL);
+        }
+        match &mut st.conn {
+            Conn::Connected { .. } => return Err(Errno::EINVAL),
+            Conn::Listening { backlog: b, .. } => *b = backlog,
+            Conn::Unconnected => {
+                st.conn = Conn::Listening {
+                    backlog,
+                    queue: VecDeque::new(),
+                };
+                st.cred_cage = cageid;
+            }
+        }
+        drop(st);
+        self.sync();
+        Ok(())
+    }
+
+    /// `connect()`. Returns `Ok(false)` if no in-process socket listens on the address, in which
+    /// case the caller should go through the host kernel.
+    pub(crate) fn connect(
+        &self,
+        cageid: u64,
+        addr: *const sockaddr,
+        addrlen: socklen_t,
+    ) -> Result<bool, Errno> {
+        let addr = Self::addr_bytes(addr, addrlen)?;
+        let local = {
+            let st = self.state.lock();
+            match st.conn {
+                Conn::Connected { .. } => return Err(Errno::EISCONN),
+                Conn::Listening { .. } => return Err(Errno::EINVAL),
+                Conn::Unconnected => st.local.clone(),
+            }
+        };
+
+        let listener = match Self::lookup_key(&addr)
+            .and_then(|key| NAMES.lock().get(&key).and_then(Weak::upgrade))
+        {
+            Some(listener) => listener,
+            None => return Ok(false),
+        };
+
+        // The accepting end of the connection
+        let server = InprocSocket::new()?;
+        let c2s = Channel::new(&server.readiness, &self.readiness);
+        let s2c = Channel::new(&self.readiness, &server.readiness);
+
+        let mut lst = listener.state.lock();
+        loop {
+            let listener_local = lst.local.clone();
+            let listener_cage = lst.cred_cage;
+            let (backlog, queue) = match &mut lst.conn {
+                Conn::Listening { backlog, queue } => (*backlog, queue),
+                _ => return Err(Errno::ECONNREFUSED),
+            };
+            if queue.len() <= backlog {
+                {
+                    let mut sst = server.state.lock();
+                    sst.conn = Conn::Connected {
+                        rx: c2s.clone(),
+                        tx: s2c.clone(),
+                    };
+                    sst.local = listener_local.clone();
+                    sst.peer = local.clone();
+                    sst.cred_cage = listener_cage;
+                    sst.peer_cage = cageid;
+                }
+                queue.push_back(server);
+                break;
+            }
+            if self.nonblocking() {
+                return Err(Errno::EAGAIN);
+            }
+            Self::wait(cageid, &listener.cond, &mut lst)?;
+        }
+        let peer = lst.local.clone();
+        let peer_cage = lst.cred_cage;
+        drop(lst);
+        listener.sync();
+        listener.cond.notify_all();
+
+        let mut st = self.state.lock();
+        st.conn = Conn::Connected { rx: s2c, tx: c2s };
+        st.peer = peer;
+        st.cred_cage = cageid;
+        st.peer_cage = peer_cage;
+        drop(st);
+        self.sync();
+        Ok(true)
+    }
+
+    /// `accept()`: takes the next queued connection.
+    pub fn accept(&self, cageid: u64) -> Result<Arc<InprocSocket>, Errno> {
+        let mut st = self.state.lock();
+        loop {
+            let queue = match &mut st.conn {
+                Conn::Listening { queue, .. } => queue,
+                _ => return Err(Errno::EINVAL),
+            };
+            if let Some(server) = queue.pop_front() {
+                drop(st);
+                self.sync();
+                self.cond.notify_all();
+                return Ok(server);
+            }
+            if self.nonblocking() {
+                return Err(Errno::EAGAIN);
+            }
+            Self::wait(cageid, &self.cond, &mut st)?;
+        }
+    }
+
+    /// `shutdown()`.
+    pub fn shutdown(&self, how: i32) -> Result<(), Errno> {
+        if how != SHUT_RD && how != SHUT_WR && how != SHUT_RDWR {
+            return Err(Errno::EINVAL);
+        }
+        let (rx, tx) = self.channels().ok_or(Errno::ENOTCONN)?;
+        if how != SHUT_WR {
+            rx.modify(|st| st.reader_closed = true);
+        }
+        if how != SHUT_RD {
+            tx.modify(|st| st.writer_closed = true);
+        }
+        Ok(())
+    }
+
+    /// Writes the local (`peer == false`) or peer address in `getsockname` format.
+    pub(crate) fn copy_out_name(
+        &self,
+        peer: bool,
+        addr: *mut u8,
+        addrlen: *mut socklen_t,
+    ) -> Result<(), Errno> {
+        let st = self.state.lock();
+        if peer && !matches!(st.conn, Conn::Connected { .. }) {
+            return Err(Errno::ENOTCONN);
+        }
+        let name = if peer { &st.peer } else { &st.local };
+        let family = (AF_UNIX as libc::sa_family_t).to_ne_bytes();
+        let bytes = name.as_deref().unwrap_or(&family);
+        Self::copy_out(bytes, addr, addrlen)
+    }
+
+    /// Copies `bytes` to a user buffer with a `socklen_t` value-result length, truncating like
+    /// Linux does.
+    fn copy_out(bytes: &[u8], buf: *mut u8, len: *mut socklen_t) -> Result<(), Errno> {
+        if len.is_null() {
+            return Ok(());
+        }
+        let room = unsafe { *len } as usize;
+        if !buf.is_null() {
+            let n = room.min(bytes.len());
+            unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), buf, n) };
+        }
+        unsafe { *len = bytes.len() as socklen_t };
+        Ok(())
+    }
+
+    /// `getsockopt()` for the options that make sense on an in-process socket.
+    pub(crate) fn getsockopt(
+        &self,
+        level: i32,
+        optname: i32,
+        optval: *mut u8,
+        optlen: *mut socklen_t,
+    ) -> Result<(), Errno> {
+        if level != SOL_SOCKET {
+            return Err(Errno::EOPNOTSUPP);
+        }
+        if optval.is_null() || optlen.is_null() {
+            return Err(Errno::EFAULT);
+        }
+        let st = self.state.lock();
+        let value: i32 = match optname {
+            SO_TYPE => SOCK_STREAM,
+            SO_DOMAIN => AF_UNIX,
+            SO_PROTOCOL | SO_ERROR => 0,
+            SO_ACCEPTCONN => matches!(st.conn, Conn::Listening { .. }) as i32,
+            SO_SNDBUF | SO_RCVBUF => RING_CAPACITY as i32,
+            SO_PEERCRED => {
+                let cred = ucred {
+                    pid: st.peer_cage as i32,
+                    uid: unsafe { libc::getuid() },
+                    gid: unsafe { libc::getgid() },
+                };
+                let bytes = unsafe {
+                    std::slice::from_raw_parts(
+                        &cred as *const ucred as *const u8,
+                        mem::size_of::<ucred>(),
+                    )
+                };
+                let room = unsafe { *optlen } as usize;
+                let n = room.min(bytes.len());
+                unsafe {
+                    ptr::copy_nonoverlapping(bytes.as_ptr(), optval, n);
+                    *optlen = n as socklen_t;
+                }
+                return Ok(());
+            }
+            _ => return Err(Errno::ENOPROTOOPT),
+        };
+        let bytes = value.to_ne_bytes();
+        let n = (unsafe { *optlen } as usize).min(bytes.len());
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr(), optval, n);
+            *optlen = n as socklen_t;
+        }
+        Ok(())
+    }
+
+    /// `setsockopt()`: common socket-level options are accepted and have no effect.
+    pub fn setsockopt(&self, level: i32, optname: i32) -> Result<(), Errno> {
+        if level != SOL_SOCKET {
+            return Err(Errno::EOPNOTSUPP);
+        }
+        match optname {
+            SO_REUSEADDR | SO_KEEPALIVE | SO_SNDBUF | SO_RCVBUF | SO_PASSCRED | SO_LINGER
+            | SO_RCVTIMEO | SO_SNDTIMEO | SO_RCVTIMEO_NEW | SO_SNDTIMEO_NEW => Ok(()),
+            _ => Err(Errno::ENOPROTOOPT),
+        }
+    }
+
+    /// Closes both directions and drops everything the socket holds. Runs when the last fd
+    /// referring to the socket is closed.
+    fn release(&self) {
+        self.readiness.unwatch();
+        let mut st = self.state.lock();
+        match mem::replace(&mut st.conn, Conn::Unconnected) {
+            Conn::Connected { rx, tx } => {
+                rx.modify(|c| {
+                    c.reader_closed = true;
+                    c.ring.clear();
+                    c.rights.clear();
+                });
+                tx.modify(|c| c.writer_closed = true);
+            }
+            Conn::Listening { queue, .. } => {
+                for server in queue {
+                    server.release();
+                }
+            }
+            Conn::Unconnected => {}
+        }
+        if let Some(key) = st.name.take() {
+            let mut names = NAMES.lock();
+            let ours = names
+                .get(&key)
+                .is_some_and(|w| ptr::eq(w.as_ptr(), self) || w.strong_count() == 0);
+            if ours {
+                names.remove(&key);
+            }
+        }
+        if let Some(anchor) = st.anchor.take() {
+            unsafe { libc::close(anchor) };
+        }
+        drop(st);
+        self.cond.notify_all();
+    }
+}
+
+/// Returns the in-process socket behind a virtual fd, if it is one.
+pub fn lookup(cageid: u64, virtualfd: u64) -> Option<Arc<InprocSocket>> {
+    if !ENABLED {
+        return None;
+    }
+    let entry = fdtables::translate_virtual_fd(cageid, virtualfd).ok()?;
+    if entry.fdkind != FDKIND_INPROC_SOCKET {
+        return None;
+    }
+    SOCKETS.get(&entry.underfd).map(|s| s.clone())
+}
+
+/// Whether `socket()`/`socketpair()` with these arguments should create in-process sockets.
+pub fn wants(domain: i32, socktype: i32, protocol: i32) -> bool {
+    ENABLED
+        && domain == AF_UNIX
+        && socktype & !(SOCK_NONBLOCK | SOCK_CLOEXEC) == SOCK_STREAM
+        && protocol == 0
+}
+
+/// Registers a socket in the cage's fd table and returns its virtual fd.
+fn install(cageid: u64, sock: Arc<InprocSocket>, flags: i32) -> Result<u64, Errno> {
+    if flags & SOCK_NONBLOCK != 0 {
+        sock.set_nonblocking();
+    }
+    let underfd = sock.underfd();
+    SOCKETS.insert(underfd, sock.clone());
+    match fdtables::get_unused_virtual_fd(
+        cageid,
+        FDKIND_INPROC_SOCKET,
+        underfd,
+        flags & SOCK_CLOEXEC != 0,
+        0,
+    ) {
+        Ok(virtualfd) => Ok(virtualfd),
+        Err(e) => {
+            SOCKETS.remove(&underfd);
+            sock.release();
+            Err(Errno::from_discriminant(e as i32).unwrap_or(Errno::EMFILE))
+        }
+    }
+}
+
+/// `socket()` for an in-process socket.
+pub fn socket(cageid: u64, socktype: i32) -> Result<u64, Errno> {
+    install(cageid, InprocSocket::new()?, socktype)
+}
+
+/// `socketpair()` for in-process sockets.
+pub fn socketpair(cageid: u64, socktype: i32) -> Result<(u64, u64), Errno> {
+    let a = InprocSocket::new()?;
+    let b = InprocSocket::new()?;
+    let a2b = Channel::new(&b.readiness, &a.readiness);
+    let b2a = Channel::new(&a.readiness, &b.readiness);
+    for (sock, rx, tx) in [(&a, &b2a, &a2b), (&b, &a2b, &b2a)] {
+        let mut st = sock.state.lock();
+        st.conn = Conn::Connected {
+            rx: rx.clone(),
+            tx: tx.clone(),
+        };
+        st.cred_cage = cageid;
+        st.peer_cage = cageid;
+    }
+    let first = install(cageid, a, socktype)?;
+    match install(cageid, b, socktype) {
+        Ok(second) => Ok((first, second)),
+        Err(e) => {
+            let _ = fdtables::close_virtualfd(cageid, first);
+            Err(e)
+        }
+    }
+}
+
+/// `accept()`/`accept4()` on an in-process listener. Writes the peer address through the host
+/// pointers `addr`/`addrlen` when given and returns the new virtual fd.
+pub(crate) fn accept(
+    cageid: u64,
+    listener: &InprocSocket,
+    addr: *mut u8,
+    addrlen: *mut socklen_t,
+    flags: i32,
+) -> Result<u64, Errno> {
+    let server = listener.accept(cageid)?;
+    if !addr.is_null() {
+        server.copy_out_name(true, addr, addrlen)?;
+    }
+    install(cageid, server, flags)
+}
+
+/// Puts a host kernel socket in place of an unconnected in-process socket, for `connect()` to
+/// an address no cage listens on. Returns the new host fd.
+pub fn replace_with_kernel_socket(
+    cageid: u64,
+    virtualfd: u64,
+    sock: &InprocSocket,
+) -> Result<i32, Errno> {
+    let entry = fdtables::translate_virtual_fd(cageid, virtualfd).map_err(|_| Errno::EBADF)?;
+    let mut flags = SOCK_STREAM | SOCK_CLOEXEC;
+    if sock.nonblocking() {
+        flags |= SOCK_NONBLOCK;
+    }
+    let kernel_fd = unsafe { libc::socket(AF_UNIX, flags, 0) };
+    if kernel_fd < 0 {
+        return Err(host_errno());
+    }
+    if let Err(e) = fdtables::get_specific_virtual_fd(
+        cageid,
+        virtualfd,
+        FDKIND_KERNEL,
+        kernel_fd as u64,
+        entry.should_cloexec,
+        0,
+    ) {
+        unsafe { libc::close(kernel_fd) };
+        return Err(Errno::from_discriminant(e as i32).unwrap_or(Errno::EBADF));
+    }
+    Ok(kernel_fd)
+}
+
+/// Close handler registered with fdtables for `FDKIND_INPROC_SOCKET`; runs when the last
+/// virtual fd for a socket goes away.
+pub fn inproc_socket_close(fdentry: fdtables::FDTableEntry, _count: u64) -> Result<(), i32> {
+    if let Some((underfd, sock)) = SOCKETS.remove(&fdentry.underfd) {
+        let (readable, writable) = sock.eventfds();
+        let mut interest = EPOLL_INTEREST.lock();
+        interest.retain(|&(epfd, fd), _| {
+            if fd == underfd {
+                unsafe {
+                    libc::epoll_ctl(epfd as i32, EPOLL_CTL_DEL, readable, ptr::null_mut());
+                    libc::epoll_ctl(epfd as i32, EPOLL_CTL_DEL, writable, ptr::null_mut());
+                }
+            }
+            fd != underfd
+        });
+        drop(interest);
+        sock.release();
+    }
+    Ok(())
+}
+
+/// `epoll_ctl()` for an in-process socket on the host epoll instance `epfd`. Registers the
+/// socket's eventfds, which carry their own fd number as event data, and returns them so the
+/// caller can map them back to the virtual fd.
+pub fn epoll_ctl(
+    epfd: u64,
+    op: i32,
+    sock: &InprocSocket,
+    events: Option<u32>,
+) -> Result<Vec<i32>, Errno> {
+    let key = (epfd, sock.underfd());
+    let (readable, writable) = sock.eventfds();
+    let mut interest = EPOLL_INTEREST.lock();
+    let registered = interest.contains_key(&key);
+    match op {
+        EPOLL_CTL_ADD if registered => return Err(Errno::EEXIST),
+        EPOLL_CTL_ADD => {}
+        _ if !registered => return Err(Errno::ENOENT),
+        _ => unsafe {
+            // MOD re-registers from scratch; DEL stops here
+            libc::epoll_ctl(epfd as i32, EPOLL_CTL_DEL, readable, ptr::null_mut());
+            libc::epoll_ctl(epfd as i32, EPOLL_CTL_DEL, writable, ptr::null_mut());
+            interest.remove(&key);
+        },
+    }
+    if op == EPOLL_CTL_DEL {
+        return Ok(Vec::new());
+    }
+
+    let events = events.ok_or(Errno::EFAULT)?;
+    sock.watch();
+    let modifiers = events & (EPOLLET | EPOLLONESHOT) as u32;
+    let mut fds = Vec::new();
+    if events & (EPOLLIN | EPOLLPRI | EPOLLRDHUP) as u32 != 0 || events & EPOLLOUT as u32 == 0 {
+        fds.push(readable);
+    }
+    if events & EPOLLOUT as u32 != 0 {
+        fds.push(writable);
+    }
+    for &fd in &fds {
+        let mut event = libc::epoll_event {
+            events: EPOLLIN as u32 | modifiers,
+            u64: fd as u64,
+        };
+        if unsafe { libc::epoll_ctl(epfd as i32, EPOLL_CTL_ADD, fd, &mut event) } < 0 {
+            let err = host_errno();
+            unsafe {
+                libc::epoll_ctl(epfd as i32, EPOLL_CTL_DEL, readable, ptr::null_mut());
+                libc::epoll_ctl(epfd as i32, EPOLL_CTL_DEL, writable, ptr::null_mut());
+            }
+            return Err(err);
+        }
+    }
+    interest.insert(key, events);
+    Ok(fds)
+}
+
+/// The epoll events to report for an in-process socket registered on `epfd`, or `None` if it
+/// is not registered there.
+pub fn epoll_events(epfd: u64, sock: &InprocSocket) -> Option<u32> {
+    let mask = *EPOLL_INTEREST.lock().get(&(epfd, sock.underfd()))?;
+    let wanted = (mask & (EPOLLIN | EPOLLOUT | EPOLLPRI | EPOLLRDHUP) as u32) as i16;
+    Some(sock.poll_events(wanted) as u16 as u32 & (mask | (EPOLLHUP | EPOLLERR) as u32))
+}
@@ -3,10 +3,20 @@
 // This library provides POSIX-compliant system call implementations that operate
 // within the Lind-WASM sandbox environment using the 3i (Three Interposition) system.
 
+pub mod checkpoint;
 pub mod fs_calls;
+pub mod futex;
 pub mod init;
+pub mod inproc_socket;
+pub mod mqueue;
 pub mod net_calls;
+pub mod netns;
+pub mod rtnetlink;
+pub mod sched;
+pub mod shm;
+pub mod sockopt;
 pub mod sys_calls;
 pub mod syscall_table;
+pub mod uts;
 
 pub use syscall_table::*;
@@ -0,0 +1,696 @@
+//! POSIX message queues
+//!
+//! Queues live in host memory, in one namespace shared by every cage, as `/dev/mqueue` is on a
+//! Linux host without IPC namespaces. A queue is created by `mq_open` with `O_CREAT` and removed
+//! from the namespace by `mq_unlink`; its messages stay readable through the descriptors that are
+//! still open. Descriptors are their own fdtables fd kind (`FDKIND_MQUEUE`) and, like other open
+//! file descriptions, are shared by `dup` and `fork`. `mq_open` always sets `FD_CLOEXEC`, as Linux
+//! does.
+//!
+//! Readiness: every descriptor owns an eventfd, which is its fdtables `underfd`. Its counter
+//! mirrors the queue: 0 while empty, 1 while it holds messages, and the largest value an eventfd
+//! can hold while full, so the eventfd polls readable exactly when the queue has messages and
+//! writable exactly when it has room. `poll`, `select` and `epoll` wait on it like on any host fd.
+//! Its `O_NONBLOCK` flag is the descriptor's nonblocking flag, so `fcntl(F_SETFL)` works too.
+//!
+//! `mq_notify` supports `SIGEV_NONE`, `SIGEV_SIGNAL` and glibc's `SIGEV_THREAD` protocol, where
+//! the 32-byte cookie is delivered on the netlink socket glibc passes in `sigev_signo` (an
+//! emulated route socket, see `crate::rtnetlink`). Signals carry no `siginfo`.
+use crate::rtnetlink;
+use cage::{get_cage, lind_send_signal, signal_check_trigger};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use libc::{c_void, EFD_CLOEXEC, O_NONBLOCK};
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{mem, ptr};
+use sysdefs::constants::err_const::{get_errno, Errno};
+use sysdefs::constants::fs_const::{
+    MQ_MAXMSG_DEFAULT, MQ_MAXMSG_HARD, MQ_MSGSIZE_DEFAULT, MQ_MSGSIZE_HARD, MQ_NAME_MAX,
+    MQ_PRIO_MAX, MQ_QUEUES_MAX, NOTIFY_COOKIE_LEN, NOTIFY_REMOVED, NOTIFY_WOKENUP, O_ACCMODE,
+    O_CREAT, O_EXCL, O_RDONLY, O_RDWR, O_WRONLY, SIGEV_NONE, SIGEV_SIGNAL, SIGEV_THREAD,
+};
+use sysdefs::constants::lind_platform_const::FDKIND_MQUEUE;
+use sysdefs::constants::sys_const::SIGNAL_MAX;
+use sysdefs::data::fs_struct::{MqAttrStruct, MqAttrStruct64};
+
+/// Blocking operations wake up this often to check for pending signals.
+const WAIT_CHUNK: Duration = Duration::from_millis(100);
+
+/// Eventfd counter values mirroring an empty queue, a queue with messages and a full queue.
+const LEVEL_EMPTY: u64 = 0;
+const LEVEL_READY: u64 = 1;
+const LEVEL_FULL: u64 = u64::MAX - 1;
+
+lazy_static! {
+    // Queues by name, without the leading slash.
+    static ref NAMES: Mutex<HashMap<Vec<u8>, Arc<MessageQueue>>> = Mutex::new(HashMap::new());
+    // Every open descriptor, keyed by its fdtables underfd.
+    static ref DESCRIPTIONS: DashMap<u64, Arc<Description>> = DashMap::new();
+}
+
+fn host_errno() -> Errno {
+    Errno::from_discriminant(get_errno()).unwrap_or(Errno::EIO)
+}
+
+fn effective_ids() -> (u32, u32) {
+    unsafe { (libc::geteuid(), libc::getegid()) }
+}
+
+/// How a registered `mq_notify` caller is told about a message.
+enum Notify {
+    None,
+    Signal(i32),
+    // Emulated netlink socket, and the cookie to send on it
+    Thread(u64, [u8; NOTIFY_COOKIE_LEN]),
+}
+
+struct Registration {
+    owner: u64,
+    // The descriptor it was made through
+    underfd: u64,
+    how: Notify,
+}
+
+impl Registration {
+    /// Tells the owner a message arrived.
+    fn fire(self) {
+        match self.how {
+            Notify::None => {}
+            Notify::Signal(signo) => {
+                lind_send_signal(self.owner, signo);
+            }
+            Notify::Thread(socket, mut cookie) => {
+                cookie[NOTIFY_COOKIE_LEN - 1] = NOTIFY_WOKENUP;
+                rtnetlink::deliver(socket, &cookie);
+            }
+        }
+    }
+
+    /// Tells the owner the registration is gone, so glibc can free what the cookie refers to.
+    fn cancel(self) {
+        if let Notify::Thread(socket, mut cookie) = self.how {
+            cookie[NOTIFY_COOKIE_LEN - 1] = NOTIFY_REMOVED;
+            rtnetlink::deliver(socket, &cookie);
+        }
+    }
+}
+
+struct QueueState {
+    // Messages by priority, highest first, each priority in the order sent
+    messages: BTreeMap<u32, VecDeque<Vec<u8>>>,
+    curmsgs: usize,
+    // Bytes of all queued messages
+    qsize: usize,
+    // Callers blocked in mq_timedreceive
+    receivers: usize,
+    notify: Option<Registration>,
+    // Readiness eventfds of the open descriptors, with the level each one mirrors
+    eventfds: HashMap<i32, u64>,
+}
+
+/// A message queue, alive while it has a name or an open descriptor.
+pub struct MessageQueue {
+    maxmsg: usize,
+    msgsize: usize,
+    uid: u32,
+    gid: u32,
+    mode: u32,
+    state: Mutex<QueueState>,
+    cond: Condvar,
+}
+
+impl QueueState {
+    fn level(&self, maxmsg: usize) -> u64 {
+        match self.curmsgs {
+            0 => LEVEL_EMPTY,
+            n if n >= maxmsg => LEVEL_FULL,
+            _ => LEVEL_READY,
+        }
+    }
+
+    /// Brings every descriptor's eventfd in line with the queue. The read only happens when the
+    /// counter is not 0, so it never blocks even on a blocking descriptor.
+    fn update_eventfds(&mut self, maxmsg: usize) {
+        let level = self.level(maxmsg);
+        for (&fd, mirror) in self.eventfds.iter_mut() {
+            if *mirror == level {
+                continue;
+            }
+            let mut value: u64 = 0;
+            unsafe {
+                if *mirror != LEVEL_EMPTY {
+                    libc::read(fd, &mut value as *mut u64 as *mut c_void, 8);
+                }
+                if level != LEVEL_EMPTY {
+                    libc::write(fd, &level as *const u64 as *const c_void, 8);
+                }
+            }
+            *mirror = level;
+        }
+    }
+
+    /// The current registration, dropping it if its owner has exited.
+    fn registration(&mut self) -> Option<&Registration> {
+        if let Some(reg) = &self.notify {
+            if get_cage(reg.owner).is_none() {
+                self.notify.take().unwrap().cancel();
+            }
+        }
+        self.notify.as_ref()
+    }
+}
+
+impl MessageQueue {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock()
+    }
+
+    /// Whether the caller may open the queue with access mode `accmode` (Linux `inode_permission`
+    /// on the queue's inode).
+    fn permits(&self, accmode: i32) -> bool {
+        let (euid, egid) = effective_ids();
+        if euid == 0 {
+            return true;
+        }
+        let granted = if euid == self.uid {
+            self.mode >> 6
+        } else if egid == self.gid {
+            self.mode >> 3
+        } else {
+            self.mode
+        };
+        let wanted = match accmode {
+            O_RDONLY => 0o4,
+            O_WRONLY => 0o2,
+            _ => 0o6,
+        };
+        wanted & !granted & 0o7 == 0
+    }
+}
+
+/// An open message queue description.
+pub struct Description {
+    queue: Arc<MessageQueue>,
+    accmode: i32,
+    eventfd: i32,
+    // Read offset into the status text `read()` returns
+    status_pos: Mutex<usize>,
+}
+
+impl Description {
+    pub fn nonblocking(&self) -> bool {
+        unsafe { libc::fcntl(self.eventfd, libc::F_GETFL) & O_NONBLOCK != 0 }
+    }
+
+    fn set_nonblocking(&self, on: bool) {
+        unsafe {
+            let flags = libc::fcntl(self.eventfd, libc::F_GETFL);
+            let flags = if on {
+                flags | O_NONBLOCK
+            } else {
+                flags & !O_NONBLOCK
+            };
+            libc::fcntl(self.eventfd, libc::F_SETFL, flags);
+        }
+    }
+
+    /// The mode bits of the queue, for `fstat`.
+    pub fn mode(&self) -> u32 {
+        self.queue.mode
+    }
+
+    /// `mq_timedsend()`: queues `msg` with priority `prio`, waiting for room until `abstime` on
+    /// CLOCK_REALTIME, or forever without one, unless the descriptor is nonblocking.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Once the message is queued
+    /// * `Err(Errno::EBADF)` - If the descriptor is not open for writing
+    /// * `Err(Errno::EMSGSIZE)` - If `msg` is longer than the queue's message size
+    /// * `Err(Errno::EINVAL)` - If `prio` is `MQ_PRIO_MAX` or more, or `abstime` is invalid
+    /// * `Err(Errno::EAGAIN)` - If the queue is full and the descriptor is nonblocking
+    /// * `Err(Errno::ETIMEDOUT)` - If `abstime` passed while the queue was full
+    /// * `Err(Errno::EINTR)` - If a signal arrived while waiting
+    pub fn send(
+        &self,
+        cageid: u64,
+        msg: &[u8],
+        prio: u32,
+        abstime: Option<libc::timespec>,
+    ) -> Result<(), Errno> {
+        if self.accmode == O_RDONLY {
+            return Err(Errno::EBADF);
+        }
+        if msg.len() > self.queue.msgsize {
+            return Err(Errno::EMSGSIZE);
+        }
+        if prio >= MQ_PRIO_MAX {
+            return Err(Errno::EINVAL);
+        }
+        let deadline = deadline(abstime)?;
+        let queue = &self.queue;
+        let mut st = queue.lock();
+        while st.curmsgs >= queue.maxmsg {
+            if self.nonblocking() {
+                return Err(Errno::EAGAIN);
+            }
+            wait(cageid, &queue.cond, &mut st, deadline)?;
+        }
+
+        st.messages.entry(prio).or_default().push_back(msg.to_vec());
+        st.curmsgs += 1;
+        st.qsize += msg.len();
+        // A message arriving on an empty queue notifies, unless a receiver takes it right away
+        if st.curmsgs == 1 && st.receivers == 0 && st.registration().is_some() {
+            st.notify.take().unwrap().fire();
+        }
+        st.update_eventfds(queue.maxmsg);
+        drop(st);
+        queue.cond.notify_all();
+        Ok(())
+    }
+
+    /// `mq_timedreceive()`: takes the oldest message of the highest priority into `buf`,
+    /// waiting for one until `abstime` on CLOCK_REALTIME, or forever without one, unless the
+    /// descriptor is nonblocking.
+    ///
+    /// # Returns
+    /// * `Ok((usize, u32))` - The length and priority of the message
+    /// * `Err(Errno::EBADF)` - If the descriptor is not open for reading
+    /// * `Err(Errno::EMSGSIZE)` - If `buf` is shorter than the queue's message size
+    /// * `Err(Errno::EINVAL)` - If `abstime` is invalid
+    /// * `Err(Errno::EAGAIN)` - If the queue is empty and the descriptor is nonblocking
+    /// * `Err(Errno::ETIMEDOUT)` - If `abstime` passed while the queue was empty
+    /// * `Err(Errno::EINTR)` - If a signal arrived while waiting
+    pub fn receive(
+        &self,
+        cageid: u64,
+        buf: &mut [u8],
+        abstime: Option<libc::timespec>,
+    ) -> Result<(usize, u32), Errno> {
+        if self.accmode == O_WRONLY {
+            return Err(Errno::EBADF);
+        }
+        if buf.len() < self.queue.msgsize {
+            return Err(Errno::EMSGSIZE);
+        }
+        let deadline = deadline(abstime)?;
+        let queue = &self.queue;
+        let mut st = queue.lock();
+        while st.curmsgs == 0 {
+            if self.nonblocking() {
+                return Err(Errno::EAGAIN);
+            }
+            st.receivers += 1;
+            let waited = wait(cageid, &queue.cond, &mut st, deadline);
+            st.receivers -= 1;
+            waited?;
+        }
+
+        let mut highest = st.messages.last_entry().unwrap();
+        let prio = *highest.key();
+        let msg = highest.get_mut().pop_front().unwrap();
+        if highest.get().is_empty() {
+            highest.remove();
+        }
+        st.curmsgs -= 1;
+        st.qsize -= msg.len();
+        st.update_eventfds(queue.maxmsg);
+        drop(st);
+        queue.cond.notify_all();
+        buf[..msg.len()].copy_from_slice(&msg);
+        Ok((msg.len(), prio))
+    }
+
+    /// `mq_notify()` from `cageid`. `how` is `None` to unregister.
+    ///
+    /// # Returns
+    /// * `Ok(())` - On success
+    /// * `Err(Errno::EBUSY)` - If a caller is already registered for the queue
+    fn notify(&self, cageid: u64, underfd: u64, how: Option<Notify>) -> Result<(), Errno> {
+        let mut st = self.queue.lock();
+        let registered = st.registration().map(|reg| reg.owner);
+        match how {
+            None => {
+                if registered == Some(cageid) {
+                    st.notify.take().unwrap().cancel();
+                }
+                Ok(())
+            }
+            Some(_) if registered.is_some() => Err(Errno::EBUSY),
+            Some(how) => {
+                st.notify = Some(Registration {
+                    owner: cageid,
+                    underfd,
+                    how,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// `mq_getsetattr()`: returns the attributes before the call, then applies the `O_NONBLOCK`
+    /// flag of `new` if given.
+    ///
+    /// # Returns
+    /// * `Ok(MqAttrStruct)` - The old attributes
+    /// * `Err(Errno::EINVAL)` - If `new` has flags other than `O_NONBLOCK`
+    pub fn getsetattr(&self, new: Option<&MqAttrStruct>) -> Result<MqAttrStruct, Errno> {
+        if let Some(new) = new {
+            if new.mq_flags & !O_NONBLOCK != 0 {
+                return Err(Errno::EINVAL);
+            }
+        }
+        let old = MqAttrStruct {
+            mq_flags: if self.nonblocking() { O_NONBLOCK } else { 0 },
+            mq_maxmsg: self.queue.maxmsg as i32,
+            mq_msgsize: self.queue.msgsize as i32,
+            mq_curmsgs: self.queue.lock().curmsgs as i32,
+            ..Default::default()
+        };
+        if let Some(new) = new {
+            self.set_nonblocking(new.mq_flags & O_NONBLOCK != 0);
+        }
+        Ok(old)
+    }
+
+    /// `read()` on the descriptor: the status line Linux shows for a file in `/dev/mqueue`,
+    /// continuing where the last read stopped. Returns the number of bytes copied.
+    pub fn read_status(&self, buf: &mut [u8]) -> usize {
+        let mut st = self.queue.lock();
+        let qsize = st.qsize;
+        let (notify, signo, pid) = match st.registration() {
+            Some(reg) => match reg.how {
+                Notify::None => (SIGEV_NONE, 0, reg.owner),
+                Notify::Signal(signo) => (SIGEV_SIGNAL, signo, reg.owner),
+                Notify::Thread(..) => (SIGEV_THREAD, 0, reg.owner),
+            },
+            None => (0, 0, 0),
+        };
+        drop(st);
+        let status = format!(
+            "QSIZE:{:<10} NOTIFY:{:<5} SIGNO:{:<5} NOTIFY_PID:{:<6}\n",
+            qsize, notify, signo, pid
+        );
+        let mut pos = self.status_pos.lock();
+        let start = (*pos).min(status.len());
+        let n = buf.len().min(status.len() - start);
+        buf[..n].copy_from_slice(&status.as_bytes()[start..start + n]);
+        *pos += n;
+        n
+    }
+}
+
+/// Converts an absolute CLOCK_REALTIME timeout into a time to wait until.
+fn deadline(abstime: Option<libc::timespec>) -> Result<Option<SystemTime>, Errno> {
+    match abstime {
+        None => Ok(None),
+        Some(ts) => {
+            if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+                return Err(Errno::EINVAL);
+            }
+            Ok(Some(
+                UNIX_EPOCH + Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32),
+            ))
+        }
+    }
+}
+
+/// Sleeps on `cond` for one chunk, or less if `deadline` comes first.
+fn wait(
+    cageid: u64,
+    cond: &Condvar,
+    guard: &mut MutexGuard<QueueState>,
+    deadline: Option<SystemTime>,
+) -> Result<(), Errno> {
+    if signal_check_trigger(cageid) {
+        return Err(Errno::EINTR);
+    }
+    let chunk = match deadline {
+        Some(deadline) => match deadline.duration_since(SystemTime::now()) {
+            Ok(left) if !left.is_zero() => left.min(WAIT_CHUNK),
+            _ => return Err(Errno::ETIMEDOUT),
+        },
+        None => WAIT_CHUNK,
+    };
+    cond.wait_for(guard, chunk);
+    Ok(())
+}
+
+/// Checks a queue name as passed to the syscalls, without glibc's leading slash.
+fn check_name(name: &[u8]) -> Result<(), Errno> {
+    if name.is_empty() {
+        return Err(Errno::ENOENT);
+    }
+    if name.len() > MQ_NAME_MAX {
+        return Err(Errno::ENAMETOOLONG);
+    }
+    if name.contains(&b'/') {
+        return Err(Errno::EACCES);
+    }
+    Ok(())
+}
+
+/// Returns the message queue descriptor behind a virtual fd, if it is one.
+pub fn lookup(cageid: u64, virtualfd: u64) -> Option<Arc<Description>> {
+    let entry = fdtables::translate_virtual_fd(cageid, virtualfd).ok()?;
+    if entry.fdkind != FDKIND_MQUEUE {
+        return None;
+    }
+    DESCRIPTIONS.get(&entry.underfd).map(|d| d.clone())
+}
+
+/// `mq_open()`: opens the queue `name`, creating it with `mode` (already masked by the cage's
+/// umask) and `attr` if `oflag` has `O_CREAT`. Returns the new virtual fd.
+///
+/// # Returns
+/// * `Ok(u64)` - The virtual fd of the new descriptor
+/// * `Err(Errno::ENOENT)` - If the queue does not exist and `O_CREAT` was not given, or `name`
+///   is empty
+/// * `Err(Errno::EEXIST)` - If the queue exists and `O_CREAT | O_EXCL` was given
+/// * `Err(Errno::EACCES)` - If `name` contains a slash, or the mode denies the access asked for
+/// * `Err(Errno::ENAMETOOLONG)` - If `name` is too long
+/// * `Err(Errno::EINVAL)` - If the access mode or `attr` is invalid
+/// * `Err(Errno::ENOSPC)` - If there are `MQ_QUEUES_MAX` queues already
+/// * `Err(Errno::EMFILE)` - If the cage has no free fd
+pub fn open(
+    cageid: u64,
+    name: &[u8],
+    oflag: i32,
+    mode: u32,
+    attr: Option<&MqAttrStruct>,
+) -> Result<u64, Errno> {
+    check_name(name)?;
+    let accmode = oflag & O_ACCMODE;
+    if accmode != O_RDONLY && accmode != O_WRONLY && accmode != O_RDWR {
+        return Err(Errno::EINVAL);
+    }
+
+    let mut names = NAMES.lock();
+    let queue = match names.get(name) {
+        Some(_) if oflag & (O_CREAT | O_EXCL) == O_CREAT | O_EXCL => return Err(Errno::EEXIST),
+        Some(queue) => {
+            if !queue.permits(accmode) {
+                return Err(Errno::EACCES);
+            }
+            queue.clone()
+        }
+        None if oflag & O_CREAT == 0 => return Err(Errno::ENOENT),
+        None => {
+            if names.len() >= MQ_QUEUES_MAX {
+                return Err(Errno::ENOSPC);
+            }
+            let (uid, gid) = effective_ids();
+            let (maxmsg, msgsize) = match attr {
+                Some(attr) => {
+                    let (maxmsg_max, msgsize_max) = if uid == 0 {
+                        (MQ_MAXMSG_HARD, MQ_MSGSIZE_HARD)
+                    } else {
+                        (MQ_MAXMSG_DEFAULT, MQ_MSGSIZE_DEFAULT)
+                    };
+                    if attr.mq_maxmsg <= 0
+                        || attr.mq_msgsize <= 0
+                        || attr.mq_maxmsg > maxmsg_max
+                        || attr.mq_msgsize > msgsize_max
+                    {
+                        return Err(Errno::EINVAL);
+                    }
+                    (attr.mq_maxmsg, attr.mq_msgsize)
+                }
+                None => (MQ_MAXMSG_DEFAULT, MQ_MSGSIZE_DEFAULT),
+            };
+            let queue = Arc::new(MessageQueue {
+                maxmsg: maxmsg as usize,
+                msgsize: msgsize as usize,
+                uid,
+                gid,
+                mode: mode & 0o777,
+                state: Mutex::new(QueueState {
+                    messages: BTreeMap::new(),
+                    curmsgs: 0,
+                    qsize: 0,
+                    receivers: 0,
+                    notify: None,
+                    eventfds: HashMap::new(),
+                }),
+                cond: Condvar::new(),
+            });
+            // Checking permissions on a queue we just created is pointless, as Linux skips it
+            names.insert(name.to_vec(), queue.clone());
+            queue
+        }
+    };
+    drop(names);
+
+    let eventfd = unsafe { libc::eventfd(0, EFD_CLOEXEC) };
+    if eventfd < 0 {
+        return Err(host_errno());
+    }
+    let desc = Arc::new(Description {
+        queue: queue.clone(),
+        accmode,
+        eventfd,
+        status_pos: Mutex::new(0),
+    });
+    if oflag & O_NONBLOCK != 0 {
+        desc.set_nonblocking(true);
+    }
+    {
+        let mut st = queue.lock();
+        st.eventfds.insert(eventfd, LEVEL_EMPTY);
+        st.update_eventfds(queue.maxmsg);
+    }
+    let underfd = eventfd as u64;
+    DESCRIPTIONS.insert(underfd, desc);
+    fdtables::get_unused_virtual_fd(cageid, FDKIND_MQUEUE, underfd, true, 0).map_err(|e| {
+        release(underfd);
+        Errno::from_discriminant(e as i32).unwrap_or(Errno::EMFILE)
+    })
+}
+
+/// `mq_unlink()`: removes the queue `name` from the namespace. Descriptors already open keep
+/// working.
+///
+/// # Returns
+/// * `Ok(())` - On success
+/// * `Err(Errno::ENOENT)` - If there is no such queue, or `name` is empty
+/// * `Err(Errno::EACCES)` - If the caller neither owns the queue nor is root, or `name` contains
+///   a slash
+/// * `Err(Errno::ENAMETOOLONG)` - If `name` is too long
+pub fn unlink(name: &[u8]) -> Result<(), Errno> {
+    check_name(name)?;
+    let mut names = NAMES.lock();
+    let queue = names.get(name).ok_or(Errno::ENOENT)?;
+    // /dev/mqueue is a sticky directory
+    let (euid, _) = effective_ids();
+    if euid != 0 && euid != queue.uid {
+        return Err(Errno::EACCES);
+    }
+    names.remove(name);
+    Ok(())
+}
+
+/// `mq_notify()` on the descriptor behind `virtualfd`. `sigevent` is the host address of the
+/// caller's `struct sigevent`, or 0 to unregister. For `SIGEV_THREAD`, `sigev_signo` is glibc's
+/// netlink socket and `cookie` the host address of the cookie to send on it.
+///
+/// # Returns
+/// * `Ok(())` - On success
+/// * `Err(Errno::EBADF)` - If `virtualfd` is not a message queue descriptor, or the
+///   `SIGEV_THREAD` socket is not a netlink socket
+/// * `Err(Errno::EINVAL)` - If `sigev_notify` or `sigev_signo` is invalid
+/// * `Err(Errno::EFAULT)` - If a `SIGEV_THREAD` caller gave no cookie
+/// * `Err(Errno::EBUSY)` - If a caller is already registered for the queue
+pub fn notify(cageid: u64, virtualfd: u64, sigevent: u64, cookie: u64) -> Result<(), Errno> {
+    let desc = lookup(cageid, virtualfd).ok_or(Errno::EBADF)?;
+    let underfd = desc.eventfd as u64;
+    if sigevent == 0 {
+        return desc.notify(cageid, underfd, None);
+    }
+    // sival_ptr, then sigev_signo and sigev_notify, in the cage's 32-bit layout
+    let (signo, kind) = unsafe {
+        (
+            ptr::read_unaligned((sigevent + 4) as *const i32),
+            ptr::read_unaligned((sigevent + 8) as *const i32),
+        )
+    };
+    let how = match kind {
+        SIGEV_NONE => Notify::None,
+        SIGEV_SIGNAL => {
+            if !(1..=SIGNAL_MAX).contains(&signo) {
+                return Err(Errno::EINVAL);
+            }
+            Notify::Signal(signo)
+        }
+        SIGEV_THREAD => {
+            let entry =
+                fdtables::translate_virtual_fd(cageid, signo as u64).map_err(|_| Errno::EBADF)?;
+            let socket = rtnetlink::route_socket_id(entry.underfd as i32).ok_or(Errno::EBADF)?;
+            if cookie == 0 {
+                return Err(Errno::EFAULT);
+            }
+            let mut bytes = [0u8; NOTIFY_COOKIE_LEN];
+            unsafe {
+                ptr::copy_nonoverlapping(cookie as *const u8, bytes.as_mut_ptr(), bytes.len())
+            };
+            Notify::Thread(socket, bytes)
+        }
+        _ => return Err(Errno::EINVAL),
+    };
+    desc.notify(cageid, underfd, Some(how))
+}
+
+/// Forgets the descriptor with `underfd` and closes its eventfd.
+fn release(underfd: u64) {
+    if let Some((_, desc)) = DESCRIPTIONS.remove(&underfd) {
+        let mut st = desc.queue.lock();
+        st.eventfds.remove(&desc.eventfd);
+        // Closing the descriptor a registration was made through removes it
+        if st.notify.as_ref().map(|reg| reg.underfd) == Some(underfd) {
+            st.notify.take().unwrap().cancel();
+        }
+        drop(st);
+        unsafe { libc::close(desc.eventfd) };
+    }
+}
+
+/// Close handler registered with fdtables for `FDKIND_MQUEUE`; runs when the last virtual fd for
+/// a descriptor goes away.
+pub fn mqueue_close(fdentry: fdtables::FDTableEntry, _count: u64) -> Result<(), i32> {
+    release(fdentry.underfd);
+    Ok(())
+}
+
+/// Reads the `struct mq_attr` at host address `addr`, or returns `None` if `addr` is null. Its
+/// fields are `long`, which is 64-bit in a 64-bit cage.
+pub fn guest_attr(cageid: u64, addr: u64) -> Option<MqAttrStruct> {
+    if addr == 0 {
+        return None;
+    }
+    if get_cage(cageid).unwrap().vmmap.read().memory64 {
+        let attr = unsafe { ptr::read_unaligned(addr as *const MqAttrStruct64) };
+        return Some(attr.into());
+    }
+    Some(unsafe { ptr::read_unaligned(addr as *const MqAttrStruct) })
+}
+
+/// Writes `attr` to the `struct mq_attr` at host address `addr`, if it is not null.
+pub fn put_guest_attr(cageid: u64, addr: u64, attr: &MqAttrStruct) {
+    if addr == 0 {
+        return;
+    }
+    if get_cage(cageid).unwrap().vmmap.read().memory64 {
+        let attr = MqAttrStruct64::from(*attr);
+        unsafe { ptr::write_unaligned(addr as *mut MqAttrStruct64, attr) };
+        return;
+    }
+    unsafe {
+        ptr::copy_nonoverlapping(
+            attr as *const MqAttrStruct as *const u8,
+            addr as *mut u8,
+            mem::size_of::<MqAttrStruct>(),
+        )
+    };
+}
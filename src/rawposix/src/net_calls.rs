@@ -1,4 +1,11 @@
+use crate::fs_calls::apply_cage_umask;
+use crate::inproc_socket::{self, InprocSocket, PassedFds};
+use crate::netns;
+use crate::rtnetlink;
+use crate::sockopt;
+use crate::uts;
 use cage::{get_cage, readtimer, signal_check_trigger, starttimer, timeout_setup_ms, Duration};
+use dashmap::DashMap;
 use fdtables;
 use lazy_static::lazy_static;
 use libc::*;
@@ -6,10 +13,14 @@ use parking_lot::Mutex;
 use std::collections::{HashMap, HashSet};
 use std::{mem, ptr};
 use sysdefs::constants::err_const::{get_errno, handle_errno, syscall_error, Errno};
+use sysdefs::constants::fs_const::S_IRWXA;
 use sysdefs::constants::lind_platform_const::{UNUSED_ARG, UNUSED_ID};
-use sysdefs::constants::net_const::{EPOLL_CLOEXEC, EPOLL_CTL_ADD, EPOLL_CTL_DEL, EPOLL_CTL_MOD};
-use sysdefs::constants::FDKIND_KERNEL;
-use sysdefs::data::net_struct::SockAddr;
+use sysdefs::constants::net_const::{
+    EPOLL_CLOEXEC, EPOLL_CTL_ADD, EPOLL_CTL_DEL, EPOLL_CTL_MOD, SCM_CREDENTIALS, SCM_RIGHTS,
+    SOCK_DIAG_BY_FAMILY, UDIAG_SHOW_PEER, UNIX_DIAG_PEER,
+};
+use sysdefs::constants::{FDKIND_INPROC_SOCKET, FDKIND_KERNEL, FDKIND_MQUEUE};
+use sysdefs::data::net_struct::{GuestCmsgHdr, SockAddr, UnixDiagMsg, UnixDiagReq};
 use typemap::cage_helpers::convert_fd_to_host;
 use typemap::datatype_conversion::*;
 use typemap::network_helpers::{convert_host_sockaddr, convert_sockpair, copy_out_sockaddr};
@@ -27,6 +38,137 @@ lazy_static! {
     static ref REAL_EPOLL_MAP: Mutex<HashMap<u64, HashMap<i32, u64>>> = Mutex::new(HashMap::new());
 }
 
+lazy_static! {
+    // All cages share one host process, so the kernel stamps every SCM_CREDENTIALS message sent
+    // between cages with the same host pid. To report the sending cage instead, we remember which
+    // cage created or accepted (or last called sendmsg on) each host AF_UNIX socket. On receipt,
+    // and for SO_PEERCRED, the peer of the socket is found through NETLINK_SOCK_DIAG and looked
+    // up here by inode.
+    // <kernel_fd, (socket inode, cageid)>
+    static ref UNIX_SOCKET_OWNERS: DashMap<i32, (u64, u64)> = DashMap::new();
+}
+
+/// Records `cageid` as the owner of `kernel_fd` if it is a host `AF_UNIX` socket.
+fn track_unix_socket(cageid: u64, kernel_fd: i32) {
+    let mut domain: i32 = 0;
+    let mut len = mem::size_of::<i32>() as socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            kernel_fd,
+            SOL_SOCKET,
+            SO_DOMAIN,
+            &mut domain as *mut i32 as *mut c_void,
+            &mut len,
+        )
+    };
+    if ret < 0 || domain != AF_UNIX {
+        return;
+    }
+    let mut st: libc::stat = unsafe { mem::zeroed() };
+    if unsafe { libc::fstat(kernel_fd, &mut st) } == 0 {
+        UNIX_SOCKET_OWNERS.insert(kernel_fd, (st.st_ino, cageid));
+    }
+}
+
+/// Drops the owner record of a host fd that is being closed. Called from `kernel_close`.
+pub fn untrack_unix_socket(kernel_fd: i32) {
+    UNIX_SOCKET_OWNERS.remove(&kernel_fd);
+}
+
+/// Returns the inode of the socket connected to the host `AF_UNIX` socket `kernel_fd`, using a
+/// `SOCK_DIAG_BY_FAMILY` request with `UDIAG_SHOW_PEER`.
+fn unix_peer_inode(kernel_fd: i32) -> Option<u64> {
+    let mut st: libc::stat = unsafe { mem::zeroed() };
+    if unsafe { libc::fstat(kernel_fd, &mut st) } < 0 {
+        return None;
+    }
+
+    #[repr(C)]
+    struct Request {
+        hdr: nlmsghdr,
+        req: UnixDiagReq,
+    }
+    let request = Request {
+        hdr: nlmsghdr {
+            nlmsg_len: mem::size_of::<Request>() as u32,
+            nlmsg_type: SOCK_DIAG_BY_FAMILY,
+            nlmsg_flags: NLM_F_REQUEST as u16,
+            nlmsg_seq: 0,
+            nlmsg_pid: 0,
+        },
+        req: UnixDiagReq {
+            sdiag_family: AF_UNIX as u8,
+            udiag_states: u32::MAX,
+            udiag_ino: st.st_ino as u32,
+            udiag_show: UDIAG_SHOW_PEER,
+            udiag_cookie: [u32::MAX, u32::MAX],
+            ..Default::default()
+        },
+    };
+
+    let nl = unsafe { libc::socket(AF_NETLINK, SOCK_DGRAM | SOCK_CLOEXEC, NETLINK_SOCK_DIAG) };
+    if nl < 0 {
+        return None;
+    }
+    let mut reply = [0u64; 128];
+    let n = unsafe {
+        if libc::send(
+            nl,
+            &request as *const Request as *const c_void,
+            mem::size_of::<Request>(),
+            0,
+        ) < 0
+        {
+            -1
+        } else {
+            libc::recv(
+                nl,
+                reply.as_mut_ptr() as *mut c_void,
+                mem::size_of_val(&reply),
+                0,
+            )
+        }
+    };
+    unsafe { libc::close(nl) };
+
+    let hdrlen = mem::size_of::<nlmsghdr>();
+    let msglen = mem::size_of::<UnixDiagMsg>();
+    if n < (hdrlen + msglen) as isize {
+        return None;
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(reply.as_ptr() as *const u8, n as usize) };
+    let hdr = unsafe { ptr::read_unaligned(bytes.as_ptr() as *const nlmsghdr) };
+    if hdr.nlmsg_type != SOCK_DIAG_BY_FAMILY || hdr.nlmsg_len as usize > bytes.len() {
+        return None;
+    }
+
+    // Walk the rtattrs following the unix_diag_msg: { u16 rta_len; u16 rta_type; payload }
+    let end = hdr.nlmsg_len as usize;
+    let mut off = hdrlen + msglen;
+    while off + 4 <= end {
+        let rta_len = u16::from_ne_bytes([bytes[off], bytes[off + 1]]) as usize;
+        let rta_type = u16::from_ne_bytes([bytes[off + 2], bytes[off + 3]]);
+        if rta_len < 4 || off + rta_len > end {
+            break;
+        }
+        if rta_type == UNIX_DIAG_PEER && rta_len >= 8 {
+            let peer = u32::from_ne_bytes(bytes[off + 4..off + 8].try_into().unwrap());
+            return Some(peer as u64);
+        }
+        off += (rta_len + 3) & !3;
+    }
+    None
+}
+
+/// Returns the cage owning the socket at the other end of `kernel_fd`, if known.
+pub(crate) fn unix_peer_cage(kernel_fd: i32) -> Option<u64> {
+    let peer = unix_peer_inode(kernel_fd)?;
+    UNIX_SOCKET_OWNERS
+        .iter()
+        .find(|entry| entry.value().0 == peer)
+        .map(|entry| entry.value().1)
+}
+
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/poll.2.html
 ///
 /// Linux `poll()` syscall waits for one of a set of file descriptors to become ready to perform I/O.
@@ -80,8 +222,9 @@ pub extern "C" fn poll_syscall(
         return syscall_error(Errno::EFAULT, "poll_syscall", "Invalid Cage ID");
     }
 
-    // Basic bounds checking - validate arguments before conversion - FD_PER_PROCESS_MAX is defined in fdtables constants
-    if nfds_arg > fdtables::FD_PER_PROCESS_MAX {
+    // Basic bounds checking - validate arguments before conversion - like Linux, nfds may not
+    // exceed the cage's RLIMIT_NOFILE soft limit (kept in fdtables)
+    if nfds_arg > fdtables::get_fd_limits(cageid).0 {
         return syscall_error(Errno::EINVAL, "poll_syscall", "Too many file descriptors");
     }
 
@@ -132,19 +275,22 @@ pub extern "C" fn poll_syscall(
 
     // Process kernel-backed FDs and handle invalid FDs
     let mut all_kernel_pollfds: Vec<libc::pollfd> = Vec::new();
-    let mut kernel_to_vfd_mapping: HashMap<usize, u64> = HashMap::new();
+    let mut kernel_to_vfd_mapping: HashMap<usize, (u32, u64)> = HashMap::new();
+    // In-process sockets: (index in the user array, socket, requested events)
+    let mut inproc_fds: Vec<(usize, std::sync::Arc<InprocSocket>, i16)> = Vec::new();
     let mut total_ready = 0i32;
 
     for (fdkind, fd_set) in poll_data_by_fdkind {
         match fdkind {
-            FDKIND_KERNEL => {
+            // A message queue descriptor's eventfd polls as the queue does
+            FDKIND_KERNEL | FDKIND_MQUEUE => {
                 // Collect all kernel FDs for polling
                 for (vfd, fdentry) in fd_set {
                     // Use O(1) lookup to find original events for this virtual fd
                     let events = *vfd_to_events.get(&(vfd as i32)).unwrap_or(&0);
 
                     let kernel_index = all_kernel_pollfds.len();
-                    kernel_to_vfd_mapping.insert(kernel_index, vfd);
+                    kernel_to_vfd_mapping.insert(kernel_index, (fdkind, vfd));
 
                     all_kernel_pollfds.push(libc::pollfd {
                         fd: fdentry.underfd as i32,
@@ -153,6 +299,36 @@ pub extern "C" fn poll_syscall(
                     });
                 }
             }
+            FDKIND_INPROC_SOCKET => {
+                // Poll the socket's readiness eventfds so that a state change wakes us up; the
+                // events themselves are read from the socket state after every wakeup
+                for (vfd, _fdentry) in fd_set {
+                    let sock = match inproc_socket::lookup(cageid, vfd) {
+                        Some(sock) => sock,
+                        None => continue,
+                    };
+                    let events = *vfd_to_events.get(&(vfd as i32)).unwrap_or(&0);
+                    sock.watch();
+                    let (readable, writable) = sock.eventfds();
+                    if events & (POLLIN | POLLRDNORM) != 0 {
+                        all_kernel_pollfds.push(libc::pollfd {
+                            fd: readable,
+                            events: POLLIN,
+                            revents: 0,
+                        });
+                    }
+                    if events & (POLLOUT | POLLWRNORM) != 0 {
+                        all_kernel_pollfds.push(libc::pollfd {
+                            fd: writable,
+                            events: POLLIN,
+                            revents: 0,
+                        });
+                    }
+                    if let Some(&array_index) = vfd_to_index.get(&(vfd as i32)) {
+                        inproc_fds.push((array_index, sock, events));
+                    }
+                }
+            }
             fdtables::FDT_INVALID_FD => {
                 // Handle invalid FDs immediately - fdtables has already identified them
                 for (vfd, _fdentry) in fd_set {
@@ -170,12 +346,12 @@ pub extern "C" fn poll_syscall(
     }
 
     // Poll all kernel-backed fds with timeout/signal checking loop
-    if !all_kernel_pollfds.is_empty() {
+    if !all_kernel_pollfds.is_empty() || !inproc_fds.is_empty() {
         let start_time = starttimer();
         // Keep track of total duration for our exit check in the poll loop
         let (duration, chunk_timeout) = timeout_setup_ms(original_timeout);
 
-        let ret;
+        let mut inproc_ready = 0;
         loop {
             let current_chunk_timeout = if duration == Duration::MAX {
                 chunk_timeout
@@ -199,9 +375,30 @@ pub extern "C" fn poll_syscall(
                 return handle_errno(errno, "poll_syscall");
             }
 
+            // A woken eventfd alone does not make an in-process socket ready, so with any of
+            // those in the set count the kernel fds and sockets separately
+            let mut ready = poll_ret;
+            if !inproc_fds.is_empty() {
+                ready = 0;
+                for (kernel_index, kernel_pollfd) in all_kernel_pollfds.iter().enumerate() {
+                    if kernel_pollfd.revents != 0
+                        && kernel_to_vfd_mapping.contains_key(&kernel_index)
+                    {
+                        ready += 1;
+                    }
+                }
+                inproc_ready = 0;
+                for (array_index, sock, events) in &inproc_fds {
+                    let revents = sock.poll_events(*events);
+                    fds_slice[*array_index].revents = revents;
+                    if revents != 0 {
+                        inproc_ready += 1;
+                    }
+                }
+            }
+
             // Check for ready FDs or time elapsed is greater than the total duration of the timeout
-            if poll_ret > 0 || readtimer(start_time) >= duration {
-                ret = poll_ret;
+            if ready + inproc_ready > 0 || readtimer(start_time) >= duration {
                 break;
             }
 
@@ -217,10 +414,10 @@ pub extern "C" fn poll_syscall(
         // Convert kernel results back to virtual fds using fdtables helper
         for (kernel_index, kernel_pollfd) in all_kernel_pollfds.iter().enumerate() {
             if kernel_pollfd.revents != 0 {
-                if let Some(&_virtual_fd) = kernel_to_vfd_mapping.get(&kernel_index) {
+                if let Some(&(fdkind, _virtual_fd)) = kernel_to_vfd_mapping.get(&kernel_index) {
                     // Use fdtables helper to convert kernel fd back to virtual fd
                     if let Some(converted_vfd) = fdtables::convert_poll_result_back_to_virtual(
-                        FDKIND_KERNEL,
+                        fdkind,
                         kernel_pollfd.fd as u64,
                         &fdtables_mapping_table,
                     ) {
@@ -233,6 +430,7 @@ pub extern "C" fn poll_syscall(
                 }
             }
         }
+        total_ready += inproc_ready;
     }
 
     total_ready
@@ -337,9 +535,13 @@ pub extern "C" fn ppoll_syscall(
 /// Specifically, kernel fds are passed to the underlying libc select, while impipe and imsock fds would be processed by the
 /// in-memory system. Afterward, the results are combined and consolidated accordingly.
 ///
-/// TODO: Implement in-memory FD support for select syscall
-/// (Note: Currently, only kernel fds are supported. The implementation for in-memory pipes is commented out and will require
-/// further integration and testing once in-memory pipe support is added.)
+/// In-process sockets (`FDKIND_INPROC_SOCKET`) are not kernel fds, so their bits are dropped from the
+/// sets passed to libc. Instead their readiness eventfds are added to the read set so that libc select
+/// wakes up when one of them changes state, and their bits are then reported from the socket's own
+/// state after each pass.
+///
+/// Message queue descriptors (`FDKIND_MQUEUE`) are waited on through their eventfds, which are
+/// ready exactly when the queue is, and reported under their virtual fds.
 ///
 /// select() will return:
 ///     - the total number of bits that are set in readfds, writefds, errorfds
@@ -419,7 +621,7 @@ pub extern "C" fn select_syscall(
     fdkindset.insert(FDKIND_KERNEL);
 
     // Prepare bitmasks for select using fdtables
-    let (selectbittables, _unparsedtables, mappingtable) =
+    let (selectbittables, unparsedtables, mappingtable) =
         match fdtables::prepare_bitmasks_for_select(
             cageid,
             nfds as u64,
@@ -459,6 +661,45 @@ pub extern "C" fn select_syscall(
 
     let realnewnfds = readnfd.max(writenfd).max(errornfd);
 
+    // In-process sockets in the read and write sets, as (virtual fd, socket). Their readiness
+    // eventfds go into the kernel read set so that a state change wakes select up. They never
+    // have exceptional conditions.
+    let mut inproc_sets: [Vec<(u64, std::sync::Arc<InprocSocket>)>; 2] = [Vec::new(), Vec::new()];
+    let mut wake_readfds = fdtables::_init_fd_set();
+    let mut wake_nfds = 0;
+    for (i, set) in inproc_sets.iter_mut().enumerate() {
+        let entries = match unparsedtables[i].get(&FDKIND_INPROC_SOCKET) {
+            Some(entries) => entries,
+            None => continue,
+        };
+        for entry in entries {
+            let vfd = mappingtable[&(FDKIND_INPROC_SOCKET, entry.underfd)];
+            if let Some(sock) = inproc_socket::lookup(cageid, vfd) {
+                sock.watch();
+                let (readable, writable) = sock.eventfds();
+                let wake_fd = if i == 0 { readable } else { writable };
+                unsafe { FD_SET(wake_fd, &mut wake_readfds) };
+                wake_nfds = wake_nfds.max(wake_fd as u64 + 1);
+                set.push((vfd, sock));
+            }
+        }
+    }
+    let has_inproc = wake_nfds > 0;
+
+    // Message queue descriptors in the read and write sets, as (virtual fd, eventfd). The
+    // eventfd polls as the queue does, so it goes into the matching kernel set.
+    let mut mq_sets: [Vec<(u64, i32)>; 2] = [Vec::new(), Vec::new()];
+    let mut mq_nfds = 0;
+    for (i, set) in mq_sets.iter_mut().enumerate() {
+        if let Some(entries) = unparsedtables[i].get(&FDKIND_MQUEUE) {
+            for entry in entries {
+                let vfd = mappingtable[&(FDKIND_MQUEUE, entry.underfd)];
+                set.push((vfd, entry.underfd as i32));
+                mq_nfds = mq_nfds.max(entry.underfd + 1);
+            }
+        }
+    }
+
     // Convert timeval pointer to milliseconds for consistency with poll/epoll_wait timeout handling
     // select takes a timeval* (tv_sec + tv_usec), but poll/epoll_wait use integer milliseconds
     let timeout_ms = if let Some(timeout_ptr) = timeout_ptr {
@@ -480,10 +721,23 @@ pub extern "C" fn select_syscall(
     // Convert chunk_timeout (ms) to timeval for select
 
     let mut ret;
+    let mut unreal_read = HashSet::new();
+    let mut unreal_write = HashSet::new();
     loop {
         let mut tmp_readfds = real_readfds.clone();
         let mut tmp_writefds = real_writefds.clone();
         let mut tmp_errorfds = real_errorfds.clone();
+        for fd in 0..wake_nfds as i32 {
+            if unsafe { FD_ISSET(fd, &wake_readfds) } {
+                unsafe { FD_SET(fd, &mut tmp_readfds) };
+            }
+        }
+        for &(_, fd) in &mq_sets[0] {
+            unsafe { FD_SET(fd, &mut tmp_readfds) };
+        }
+        for &(_, fd) in &mq_sets[1] {
+            unsafe { FD_SET(fd, &mut tmp_writefds) };
+        }
 
         let current_chunk_ms = if duration == Duration::MAX {
             chunk_timeout
@@ -503,8 +757,8 @@ pub extern "C" fn select_syscall(
         // nfds should be the highest-numbered file descriptor + 1
         ret = unsafe {
             libc::select(
-                (realnewnfds + 1) as i32,
-                if readfds_ptr.is_some() {
+                (realnewnfds.max(wake_nfds).max(mq_nfds) + 1) as i32,
+                if readfds_ptr.is_some() || has_inproc {
                     &mut tmp_readfds as *mut _
                 } else {
                     std::ptr::null_mut()
@@ -532,6 +786,41 @@ pub extern "C" fn select_syscall(
             return handle_errno(errno, "select_syscall");
         }
 
+        // Woken eventfds are not results: drop them from the kernel read set and take the
+        // in-process sockets' readiness from their state instead
+        if has_inproc {
+            for fd in 0..wake_nfds as i32 {
+                if unsafe { FD_ISSET(fd, &wake_readfds) && FD_ISSET(fd, &tmp_readfds) } {
+                    unsafe { FD_CLR(fd, &mut tmp_readfds) };
+                    ret -= 1;
+                }
+            }
+            unreal_read = inproc_sets[0]
+                .iter()
+                .filter(|(_, sock)| sock.poll_events(POLLIN) != 0)
+                .map(|(vfd, _)| *vfd)
+                .collect();
+            unreal_write = inproc_sets[1]
+                .iter()
+                .filter(|(_, sock)| sock.poll_events(POLLOUT) & (POLLOUT | POLLERR) != 0)
+                .map(|(vfd, _)| *vfd)
+                .collect();
+            ret += (unreal_read.len() + unreal_write.len()) as i32;
+        }
+
+        // Ready message queue eventfds are reported under their virtual fds
+        for (set, (tmp, unreal)) in mq_sets.iter().zip([
+            (&mut tmp_readfds, &mut unreal_read),
+            (&mut tmp_writefds, &mut unreal_write),
+        ]) {
+            for &(vfd, fd) in set {
+                if unsafe { FD_ISSET(fd, tmp) } {
+                    unsafe { FD_CLR(fd, tmp) };
+                    unreal.insert(vfd);
+                }
+            }
+        }
+
         // Check for valid return or time elapsed is greater than the total duration of the timeout
         // Since we have this check here for total time elapsed, we can call libc select with a 0 timeout as we do above
         if ret > 0 || readtimer(start_time) >= duration {
@@ -550,13 +839,6 @@ pub extern "C" fn select_syscall(
         }
     }
 
-    let unreal_read = HashSet::new();
-    let unreal_write = HashSet::new();
-
-    // TODO: Implement in-memory FD checking for select syscall
-    // Currently only kernel FDs are supported. In-memory pipes and sockets
-    // will require custom polling logic when in-memory system is integrated.
-
     // Convert kernel FD results back to virtual FDs and subsequently write to user memory
     // This step translates the kernel select() results (which contain kernel FDs) back into
     // virtual FDs that using the mapping table created earlier    let (read_flags, read_result) = fdtables::get_one_virtual_bitmask_from_select_result(
@@ -830,6 +1112,30 @@ pub extern "C" fn epoll_ctl_syscall(
         }
     };
 
+    // An in-process socket is watched through its readiness eventfds, which map back to the
+    // socket's virtual fd like any kernel fd; epoll_wait then reports the socket's own events
+    if vfd.fdkind == FDKIND_INPROC_SOCKET {
+        let sock = match inproc_socket::lookup(cageid, fd_arg) {
+            Some(sock) => sock,
+            None => return syscall_error(Errno::EBADF, "epoll_ctl_syscall", "Bad File Descriptor"),
+        };
+        let events = user_event_opt.map(|ue| ue.events);
+        return match inproc_socket::epoll_ctl(epfd, op, &sock, events) {
+            Ok(wake_fds) => {
+                let mut epollmapping = REAL_EPOLL_MAP.lock();
+                let fdmap = epollmapping.entry(epfd).or_default();
+                let (readable, writable) = sock.eventfds();
+                fdmap.remove(&readable);
+                fdmap.remove(&writable);
+                for fd in wake_fds {
+                    fdmap.insert(fd, fd_arg);
+                }
+                0
+            }
+            Err(e) => syscall_error(e, "epoll_ctl_syscall", "in-process socket registration"),
+        };
+    }
+
     // We intentionally DO NOT overwrite the user's epoll_event inside the guest's
     // linear memory. At this layer we translate the user-visible (virtual) FD into a
     // kernel FD (underfd), which is not visible to user space. Mutating
@@ -904,11 +1210,10 @@ pub extern "C" fn epoll_ctl_syscall(
 /// virtual FDs before writing to user-space events array. This ensures kernel identifiers never leak
 /// into user memory while maintaining proper syscall semantics.
 ///
-/// TODO: Implement in-memory FD support for epoll_wait
-/// For in-memory FDs, custom polling logic needs to be implemented when in-memory system
-/// is integrated. This will involve checking in-memory pipe/socket readiness and converting
-/// results back to virtual FDs. Results are converted back to virtual FDs and written to
-/// the user-space events array.
+/// In-process sockets are registered with the kernel epoll through their readiness eventfds (see
+/// `epoll_ctl_syscall`). When one of those fires, the socket's current events are computed from its
+/// own state and reported once for the socket. Spurious wakeups that yield no events are dropped and
+/// the wait continues.
 ///
 /// ## Arguments:
 ///     - cageid: current cage identifier.
@@ -1010,9 +1315,8 @@ pub extern "C" fn epoll_wait_syscall(
         // - We map (cage, underfd) to vfd and store the vfd in the user's event data.
         // - We also copy the event mask verbatim.
         // This ensures guest memory only ever contains guest-visible virtual FDs.
-        let mut ret;
         loop {
-            ret = unsafe {
+            let ret = unsafe {
                 libc::epoll_wait(
                     epfd as i32,
                     kernel_events.as_mut_ptr(),
@@ -1026,9 +1330,52 @@ pub extern "C" fn epoll_wait_syscall(
                 return handle_errno(errno, "epoll");
             }
 
+            // Convert back to user's data structure
+            // Loop over virtual epollfd to find corresponding mapping relationship between kernel fd and virtual fd
+            let mut count = 0;
+            for kernel_event in &kernel_events[..ret as usize] {
+                let ret_kernelfd = kernel_event.u64;
+
+                let epollmapping = REAL_EPOLL_MAP.lock();
+
+                let ret_virtualfd = match epollmapping
+                    .get(&epfd)
+                    .and_then(|kernel_map| kernel_map.get(&(ret_kernelfd as i32)).copied())
+                {
+                    Some(vfd) => vfd,
+                    None => {
+                        return syscall_error(
+                            Errno::EBADF,
+                            "epoll",
+                            "could not translate kernel fd to virtual fd",
+                        );
+                    }
+                };
+                drop(epollmapping);
+
+                // For an in-process socket the kernel only saw one of its eventfds fire; report
+                // the socket's current events, once per socket
+                if let Some(sock) = inproc_socket::lookup(cageid, ret_virtualfd) {
+                    let revents = inproc_socket::epoll_events(epfd, &sock).unwrap_or(0);
+                    if let Some(prev) = events[..count].iter_mut().find(|e| e.u64 == ret_virtualfd)
+                    {
+                        prev.events |= revents;
+                        continue;
+                    }
+                    if revents == 0 {
+                        continue;
+                    }
+                    events[count].events = revents;
+                } else {
+                    events[count].events = kernel_event.events;
+                }
+                events[count].u64 = ret_virtualfd as u64;
+                count += 1;
+            }
+
             // check for timeout against total duration or if epoll_wait returned successfully.
-            if ret > 0 || readtimer(start_time) >= duration {
-                break;
+            if count > 0 || readtimer(start_time) >= duration {
+                return count as i32;
             }
 
             // Check for signals that may have interrupted the select operation
@@ -1039,31 +1386,6 @@ pub extern "C" fn epoll_wait_syscall(
                 return syscall_error(Errno::EINTR, "epoll", "interrupted");
             }
         }
-        // Convert back to user's data structure
-        // Loop over virtual epollfd to find corresponding mapping relationship between kernel fd and virtual fd
-        for i in 0..ret as usize {
-            let ret_kernelfd = kernel_events[i].u64;
-
-            let epollmapping = REAL_EPOLL_MAP.lock();
-
-            let ret_virtualfd = match epollmapping
-                .get(&epfd)
-                .and_then(|kernel_map| kernel_map.get(&(ret_kernelfd as i32)).copied())
-            {
-                Some(vfd) => vfd,
-                None => {
-                    return syscall_error(
-                        Errno::EBADF,
-                        "epoll",
-                        "could not translate kernel fd to virtual fd",
-                    );
-                }
-            };
-
-            events[i].u64 = ret_virtualfd as u64;
-            events[i].events = kernel_events[i].events;
-        }
-        return ret;
     }
 
     return 0; // Should never reach
@@ -1117,7 +1439,22 @@ pub extern "C" fn socket_syscall(
         );
     }
 
-    let kernel_fd = unsafe { libc::socket(domain, socktype, protocol) };
+    if inproc_socket::wants(domain, socktype, protocol) {
+        return match inproc_socket::socket(cageid, socktype) {
+            Ok(virtualfd) => virtualfd as i32,
+            Err(e) => syscall_error(e, "socket", "could not create in-process socket"),
+        };
+    }
+
+    // Route netlink sockets answer for the cage's network view rather than the host's
+    let kernel_fd = if rtnetlink::wants(domain, protocol) {
+        match rtnetlink::socket(cageid, socktype) {
+            Ok(kernel_fd) => kernel_fd,
+            Err(e) => return syscall_error(e, "socket", "could not create route netlink socket"),
+        }
+    } else {
+        unsafe { libc::socket(domain, socktype, protocol) }
+    };
 
     if kernel_fd < 0 {
         let errno = get_errno();
@@ -1134,8 +1471,20 @@ pub extern "C" fn socket_syscall(
     // (equivalent to `O_NONBLOCK`). Since our virtual FD maps directly to a
     // host kernel FD (`FDKIND_KERNEL`), we simply defer to the kernel as the
     // source of truth and do not duplicate this flag in `fdtables::optionalinfo`.
-    fdtables::get_unused_virtual_fd(cageid, FDKIND_KERNEL, kernel_fd as u64, cloexec, 0).unwrap()
-        as i32
+    match fdtables::get_unused_virtual_fd(cageid, FDKIND_KERNEL, kernel_fd as u64, cloexec, 0) {
+        Ok(virtualfd) => {
+            if domain == AF_UNIX {
+                track_unix_socket(cageid, kernel_fd);
+            }
+            virtualfd as i32
+        }
+        Err(e) => {
+            // EMFILE or ENFILE from fdtables; don't leak the kernel fd
+            unsafe { libc::close(kernel_fd) };
+            rtnetlink::reap_closed();
+            handle_errno(e as i32, "socket")
+        }
+    }
 }
 
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/connect.2.html
@@ -1175,7 +1524,7 @@ pub extern "C" fn connect_syscall(
     arg6: u64,
     arg6_cageid: u64,
 ) -> i32 {
-    let fd = convert_fd_to_host(fd_arg, fd_cageid, cageid);
+    let mut fd = convert_fd_to_host(fd_arg, fd_cageid, cageid);
     let addr = addr_arg as *mut u8;
 
     // would check when `secure` flag has been set during compilation,
@@ -1193,11 +1542,50 @@ pub extern "C" fn connect_syscall(
 
     let (finalsockaddr, addrlen) = convert_host_sockaddr(addr, addr_cageid, cageid);
 
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        match sock.connect(cageid, finalsockaddr, addrlen) {
+            Ok(true) => return 0,
+            // No cage listens on the address, so connect through the host kernel instead
+            Ok(false) => {
+                match inproc_socket::replace_with_kernel_socket(fd_cageid, fd_arg, &sock) {
+                    Ok(kernel_fd) => {
+                        track_unix_socket(cageid, kernel_fd);
+                        fd = kernel_fd;
+                    }
+                    Err(e) => return syscall_error(e, "connect", "could not create host socket"),
+                }
+            }
+            Err(e) => return syscall_error(e, "connect", "in-process connect failed"),
+        }
+    }
+
+    if rtnetlink::is_route_socket(fd) {
+        return match rtnetlink::connect(finalsockaddr, addrlen) {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "connect", "route netlink sockets only reach the kernel"),
+        };
+    }
+
+    // A loopback address of a private network namespace lives on another host port
+    let mut routed = match netns::route(cageid, fd, finalsockaddr, addrlen) {
+        Ok(routed) => routed,
+        Err(e) => return syscall_error(e, "connect", "destination refused for this cage"),
+    };
+    let (finalsockaddr, addrlen) = match &mut routed {
+        Some((storage, len)) => (storage as *mut sockaddr_storage as *mut sockaddr, *len),
+        None => (finalsockaddr, addrlen),
+    };
+
     let ret = unsafe { libc::connect(fd, finalsockaddr, addrlen) };
     if ret < 0 {
         let errno = get_errno();
+        // A nonblocking connect has its local port already
+        if errno == EINPROGRESS {
+            netns::note_local_port(cageid, fd);
+        }
         return handle_errno(errno, "connect");
     }
+    netns::note_local_port(cageid, fd);
     ret
 }
 
@@ -1250,11 +1638,43 @@ pub extern "C" fn bind_syscall(
 
     let (finalsockaddr, addrlen) = convert_host_sockaddr(addr, addr_cageid, cageid);
 
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        return match sock.bind(cageid, finalsockaddr, addrlen) {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "bind", "in-process bind failed"),
+        };
+    }
+
+    if rtnetlink::is_route_socket(fd) {
+        return match rtnetlink::bind(fd, finalsockaddr, addrlen) {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "bind", "invalid netlink address"),
+        };
+    }
+
+    match netns::bind(cageid, fd, finalsockaddr, addrlen) {
+        Ok(true) => return 0,
+        Ok(false) => {}
+        Err(e) => return syscall_error(e, "bind", "bind in the cage's network namespace failed"),
+    }
+
     let ret = unsafe { libc::bind(fd, finalsockaddr, addrlen) };
     if ret < 0 {
         let errno = get_errno();
         return handle_errno(errno, "bind");
     }
+
+    // A pathname AF_UNIX bind creates a socket node with mode 0777 minus the umask. The host
+    // umask is 0, so narrow the node to the cage's own umask here. Abstract sockets (leading
+    // NUL byte) have no filesystem node.
+    if !finalsockaddr.is_null() && unsafe { (*finalsockaddr).sa_family } as i32 == AF_UNIX {
+        let sun = finalsockaddr as *const sockaddr_un;
+        let sun_path = unsafe { (*sun).sun_path.as_ptr() };
+        if unsafe { *sun_path } != 0 {
+            let mode = apply_cage_umask(cageid, S_IRWXA);
+            unsafe { libc::chmod(sun_path, mode) };
+        }
+    }
     ret
 }
 
@@ -1303,12 +1723,21 @@ pub extern "C" fn listen_syscall(
         );
     }
 
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        return match sock.listen(cageid, backlog) {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "listen", "in-process listen failed"),
+        };
+    }
+
     let ret = unsafe { libc::listen(fd, backlog) };
 
     if ret < 0 {
         let errno = get_errno();
         return handle_errno(errno, "listen");
     }
+    // Listening without a bind picks a port
+    netns::note_local_port(cageid, fd);
     ret
 }
 
@@ -1324,7 +1753,7 @@ pub extern "C" fn listen_syscall(
 ///     - cageid: current cageid
 ///     - fd_arg: virtual file descriptor referring to the listening socket
 ///     - addr_arg: optional pointer to a buffer that will receive the address of the connecting entity
-///     - len_arg: not used in this implementation
+///     - len_arg: only used for in-process sockets, where it receives the peer address length
 ///
 /// ## Return:
 ///     - On success: new virtual file descriptor associated with the accepted socket
@@ -1335,7 +1764,7 @@ pub extern "C" fn accept_syscall(
     fd_cageid: u64,
     addr_arg: u64,
     addr_cageid: u64,
-    _len_arg: u64,
+    len_arg: u64,
     _len_cageid: u64,
     arg4: u64,
     arg4_cageid: u64,
@@ -1359,7 +1788,16 @@ pub extern "C" fn accept_syscall(
         );
     }
 
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        let lenp = len_arg as *mut socklen_t;
+        return match inproc_socket::accept(cageid, &sock, addr, lenp, 0) {
+            Ok(virtualfd) => virtualfd as i32,
+            Err(e) => syscall_error(e, "accept", "in-process accept failed"),
+        };
+    }
+
     let (finalsockaddr, mut addrlen) = convert_host_sockaddr(addr, addr_cageid, cageid);
+    let addrcap = addrlen;
 
     let ret_kernelfd = unsafe { libc::accept(fd, finalsockaddr, &mut addrlen as *mut u32) };
 
@@ -1367,13 +1805,19 @@ pub extern "C" fn accept_syscall(
         let errno = get_errno();
         return handle_errno(errno, "accept");
     }
+    netns::host_to_guest(cageid, fd, finalsockaddr, addrlen.min(addrcap));
 
     // We need to register this new kernel fd in fdtables
-    let ret_virtualfd =
-        fdtables::get_unused_virtual_fd(cageid, FDKIND_KERNEL, ret_kernelfd as u64, false, 0)
-            .unwrap();
-
-    ret_virtualfd as i32
+    match fdtables::get_unused_virtual_fd(cageid, FDKIND_KERNEL, ret_kernelfd as u64, false, 0) {
+        Ok(ret_virtualfd) => {
+            track_unix_socket(cageid, ret_kernelfd);
+            ret_virtualfd as i32
+        }
+        Err(e) => {
+            unsafe { libc::close(ret_kernelfd) };
+            handle_errno(e as i32, "accept")
+        }
+    }
 }
 
 /// The Linux `accept4()` syscall is similar to `accept()` but allows setting flags
@@ -1408,7 +1852,16 @@ pub extern "C" fn accept4_syscall(
     }
     let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid);
 
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        let lenp = len_arg as *mut socklen_t;
+        return match inproc_socket::accept(cageid, &sock, addr, lenp, flags) {
+            Ok(virtualfd) => virtualfd as i32,
+            Err(e) => syscall_error(e, "accept4", "in-process accept failed"),
+        };
+    }
+
     let (finalsockaddr, mut addrlen) = convert_host_sockaddr(addr, addr_cageid, cageid);
+    let addrcap = addrlen;
 
     let ret_kernelfd = unsafe {
         if flags == 0 {
@@ -1422,26 +1875,34 @@ pub extern "C" fn accept4_syscall(
         let errno = get_errno();
         return handle_errno(errno, "accept4");
     }
+    netns::host_to_guest(cageid, fd, finalsockaddr, addrlen.min(addrcap));
 
     let should_cloexec = (flags & libc::SOCK_CLOEXEC) != 0;
 
-    let ret_virtualfd = fdtables::get_unused_virtual_fd(
+    match fdtables::get_unused_virtual_fd(
         cageid,
         FDKIND_KERNEL,
         ret_kernelfd as u64,
         should_cloexec,
         0,
-    )
-    .unwrap();
-
-    ret_virtualfd as i32
+    ) {
+        Ok(ret_virtualfd) => {
+            track_unix_socket(cageid, ret_kernelfd);
+            ret_virtualfd as i32
+        }
+        Err(e) => {
+            unsafe { libc::close(ret_kernelfd) };
+            handle_errno(e as i32, "accept4")
+        }
+    }
 }
 
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/setsockopt.2.html
 ///
 /// The Linux `setsockopt()` syscall sets options for a socket. Options may exist at multiple protocol levels.
 /// This implementation translates the virtual file descriptor and user-provided option values into host-space values
-/// before applying the `setsockopt` syscall on the host kernel.
+/// before applying the `setsockopt` syscall on the host kernel. Only the options listed in `sockopt` are supported;
+/// others fail with `ENOPROTOOPT`.
 ///
 /// ## Input:
 ///     - cageid: current cageid
@@ -1484,14 +1945,22 @@ pub extern "C" fn setsockopt_syscall(
         );
     }
 
-    let ret = unsafe { libc::setsockopt(fd, level, optname, optval as *mut c_void, optlen) };
-
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "setsockopt");
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        return match sock.setsockopt(level, optname) {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "setsockopt", "unsupported in-process socket option"),
+        };
     }
 
-    ret
+    let ret = if rtnetlink::is_route_socket(fd) {
+        rtnetlink::setsockopt(fd, level, optname, optval as *const c_void, optlen)
+    } else {
+        sockopt::set(fd, level, optname, optval as *const c_void, optlen)
+    };
+    match ret {
+        Ok(()) => 0,
+        Err(e) => syscall_error(e, "setsockopt", "setting the socket option failed"),
+    }
 }
 
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/shutdown.2.html
@@ -1539,6 +2008,13 @@ pub extern "C" fn shutdown_syscall(
         );
     }
 
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        return match sock.shutdown(how) {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "shutdown", "in-process shutdown failed"),
+        };
+    }
+
     let ret = unsafe { libc::shutdown(fd, how) };
 
     if ret < 0 {
@@ -1604,6 +2080,19 @@ pub extern "C" fn getsockname_syscall(
         return syscall_error(Errno::EFAULT, "getsockname_syscall", "len is null");
     }
 
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        return match sock.copy_out_name(false, user_addr as *mut u8, lenp) {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "getsockname", "in-process getsockname failed"),
+        };
+    }
+
+    // Report the address the cage bound in its network namespace, not the host's
+    if let Some(local) = netns::local_name(fd).or_else(|| rtnetlink::local_name(fd)) {
+        unsafe { copy_out_sockaddr(user_addr, lenp, &local) };
+        return 0;
+    }
+
     // Read initial length and clamp to storage size
     let mut len: socklen_t = unsafe { *lenp };
     let max_len = mem::size_of::<sockaddr_storage>() as socklen_t;
@@ -1625,6 +2114,7 @@ pub extern "C" fn getsockname_syscall(
         let errno = get_errno();
         return handle_errno(errno, "getsockname");
     }
+    netns::host_to_guest(cageid, fd, &mut storage as *mut _ as *mut sockaddr, len);
 
     // Copy into guest-visible SockAddr wrapper + write back len
     unsafe {
@@ -1681,6 +2171,47 @@ pub extern "C" fn sendto_syscall(
     // to be mutable.
     let (finalsockaddr, addrlen) = convert_host_sockaddr(sockaddr, sockaddr_cageid, cageid);
 
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        // Like Linux, a stream socket refuses a destination address
+        if !finalsockaddr.is_null() {
+            let e = if sock.connected() {
+                Errno::EISCONN
+            } else {
+                Errno::EOPNOTSUPP
+            };
+            return syscall_error(e, "sendto", "address given for a stream socket");
+        }
+        let iov = [iovec {
+            iov_base: buf as *mut c_void,
+            iov_len: buflen,
+        }];
+        return match sock.send(cageid, &iov, flag) {
+            Ok(n) => n as i32,
+            Err(e) => syscall_error(e, "sendto", "in-process send failed"),
+        };
+    }
+
+    if rtnetlink::is_route_socket(fd) {
+        let iov = [iovec {
+            iov_base: buf as *mut c_void,
+            iov_len: buflen,
+        }];
+        return match rtnetlink::send(cageid, fd, &iov, finalsockaddr, addrlen) {
+            Ok(n) => n as i32,
+            Err(e) => syscall_error(e, "sendto", "route netlink request failed"),
+        };
+    }
+
+    // A loopback address of a private network namespace lives on another host port
+    let mut routed = match netns::route(cageid, fd, finalsockaddr, addrlen) {
+        Ok(routed) => routed,
+        Err(e) => return syscall_error(e, "sendto", "destination refused for this cage"),
+    };
+    let (finalsockaddr, addrlen) = match &mut routed {
+        Some((storage, len)) => (storage as *mut sockaddr_storage as *mut sockaddr, *len),
+        None => (finalsockaddr, addrlen),
+    };
+
     let ret = unsafe {
         libc::sendto(
             fd,
@@ -1696,6 +2227,7 @@ pub extern "C" fn sendto_syscall(
         let errno = get_errno();
         return handle_errno(errno, "sendto");
     }
+    netns::note_local_port(cageid, fd);
 
     ret
 }
@@ -1743,6 +2275,23 @@ pub extern "C" fn recvfrom_syscall(
     let addr_nullity = sc_convert_arg_nullity(addr_arg, addr_cageid, cageid);
     let addrlen_nullity = sc_convert_arg_nullity(addrlen_arg, addrlen_cageid, cageid);
 
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        let iov = [iovec {
+            iov_base: buf as *mut c_void,
+            iov_len: buflen,
+        }];
+        return match sock.recv(cageid, &iov, flag) {
+            Ok(n) => {
+                // A connected stream socket reports no source address
+                if !addrlen_nullity {
+                    unsafe { *(addrlen_arg as *mut socklen_t) = 0 };
+                }
+                n as i32
+            }
+            Err(e) => syscall_error(e, "recvfrom", "in-process receive failed"),
+        };
+    }
+
     // Case 1: both NULL → caller doesn’t want peer address
     // In this case recvfrom() won’t write to addr/addrlen,
     // so we can pass null pointers directly to libc.
@@ -1787,6 +2336,16 @@ pub extern "C" fn recvfrom_syscall(
             return handle_errno(errno, "recvfrom");
         }
 
+        netns::host_to_guest(
+            cageid,
+            fd,
+            &mut src_storage as *mut _ as *mut sockaddr,
+            src_len,
+        );
+        if let Some(kernel) = rtnetlink::peer_name(fd) {
+            src_storage = kernel;
+        }
+
         // Copy peer address back to user’s src_addr / addrlen
         if ret >= 0 {
             unsafe {
@@ -1800,6 +2359,300 @@ pub extern "C" fn recvfrom_syscall(
     0
 }
 
+/// Rebuilds a cage's control buffer in the host `cmsghdr` layout for `sendmsg`.
+///
+/// `SCM_RIGHTS` payloads carry virtual fds, which are translated to the host fds backing them;
+/// fds without a kernel object of their own (in-process sockets, message queues) cannot be
+/// passed and fail with `EOPNOTSUPP`.
+/// An `SCM_CREDENTIALS` payload must name the sending cage as its pid; it is rewritten with the
+/// host pid so the kernel accepts it (uid and gid are already host values). Other control
+/// messages are copied as is.
+///
+/// Returns the 8-byte aligned host buffer and its length in bytes.
+fn cmsgs_guest_to_host(
+    cageid: u64,
+    control: *const u8,
+    controllen: usize,
+) -> Result<(Vec<u64>, usize), Errno> {
+    let guest = unsafe { std::slice::from_raw_parts(control, controllen) };
+
+    // Split the cage's buffer into (level, type, payload) triples first, so the host buffer can
+    // be sized exactly.
+    let mut msgs: Vec<(i32, i32, &[u8])> = Vec::new();
+    let mut offset = 0;
+    while offset + GuestCmsgHdr::LEN <= controllen {
+        let hdr = unsafe { ptr::read_unaligned(control.add(offset) as *const GuestCmsgHdr) };
+        let len = hdr.cmsg_len as usize;
+        if len < GuestCmsgHdr::LEN || len > controllen - offset {
+            return Err(Errno::EINVAL);
+        }
+        msgs.push((
+            hdr.cmsg_level,
+            hdr.cmsg_type,
+            &guest[offset + GuestCmsgHdr::LEN..offset + len],
+        ));
+        offset += GuestCmsgHdr::align(len);
+    }
+
+    let total: usize = msgs
+        .iter()
+        .map(|(_, _, data)| unsafe { CMSG_SPACE(data.len() as u32) } as usize)
+        .sum();
+    let mut buf = vec![0u64; total.div_ceil(8)];
+    let base = buf.as_mut_ptr() as *mut u8;
+
+    let mut offset = 0;
+    for (level, typ, data) in msgs {
+        unsafe {
+            let cmsg = base.add(offset) as *mut cmsghdr;
+            (*cmsg).cmsg_len = CMSG_LEN(data.len() as u32) as usize;
+            (*cmsg).cmsg_level = level;
+            (*cmsg).cmsg_type = typ;
+            let out = CMSG_DATA(cmsg);
+
+            match (level, typ) {
+                (SOL_SOCKET, SCM_RIGHTS) => {
+                    if data.len() % mem::size_of::<i32>() != 0 {
+                        return Err(Errno::EINVAL);
+                    }
+                    for (i, raw) in data.chunks_exact(4).enumerate() {
+                        let virtualfd = i32::from_ne_bytes(raw.try_into().unwrap());
+                        if virtualfd < 0 {
+                            return Err(Errno::EBADF);
+                        }
+                        let entry = fdtables::translate_virtual_fd(cageid, virtualfd as u64)
+                            .map_err(|_| Errno::EBADF)?;
+                        // In-process sockets and message queues are backed by an eventfd
+                        // that means nothing to the receiver
+                        if entry.fdkind != FDKIND_KERNEL {
+                            return Err(Errno::EOPNOTSUPP);
+                        }
+                        ptr::write_unaligned((out as *mut i32).add(i), entry.underfd as i32);
+                    }
+                }
+                (SOL_SOCKET, SCM_CREDENTIALS) => {
+                    if data.len() != mem::size_of::<ucred>() {
+                        return Err(Errno::EINVAL);
+                    }
+                    let mut cred = ptr::read_unaligned(data.as_ptr() as *const ucred);
+                    if cred.pid as u64 != cageid {
+                        return Err(Errno::EPERM);
+                    }
+                    cred.pid = libc::getpid();
+                    ptr::write_unaligned(out as *mut ucred, cred);
+                }
+                _ => ptr::copy_nonoverlapping(data.as_ptr(), out, data.len()),
+            }
+
+            offset += CMSG_SPACE(data.len() as u32) as usize;
+        }
+    }
+
+    Ok((buf, total))
+}
+
+/// Copies the control messages returned by the host `recvmsg` back into the cage's buffer in the
+/// wasm32 `cmsghdr` layout.
+///
+/// Host fds received through `SCM_RIGHTS` are installed as new virtual fds of the cage,
+/// close-on-exec when `cloexec` (`MSG_CMSG_CLOEXEC`) is set. The pid in `SCM_CREDENTIALS` becomes
+/// the cage id of the sender, or 0 when the sender isn't a known cage of this lind instance.
+/// As on Linux, control data that doesn't fit sets `MSG_CTRUNC` and fds that don't fit are closed.
+///
+/// Returns the number of bytes written to the cage's buffer and the `msg_flags` to add.
+fn cmsgs_host_to_guest(
+    cageid: u64,
+    kernel_fd: i32,
+    host_msg: &msghdr,
+    control: *mut u8,
+    controllen: usize,
+    cloexec: bool,
+) -> (usize, i32) {
+    let mut written = 0;
+    let mut flags = 0;
+
+    let mut cmsg = unsafe { CMSG_FIRSTHDR(host_msg) };
+    while !cmsg.is_null() {
+        let (level, typ, len) =
+            unsafe { ((*cmsg).cmsg_level, (*cmsg).cmsg_type, (*cmsg).cmsg_len) };
+        let data = unsafe { CMSG_DATA(cmsg) } as *const u8;
+        let datalen = len.saturating_sub(unsafe { CMSG_LEN(0) } as usize);
+
+        let fits_header = written + GuestCmsgHdr::LEN <= controllen;
+        let room = if fits_header {
+            controllen - written - GuestCmsgHdr::LEN
+        } else {
+            0
+        };
+        let out = unsafe { control.add(written + GuestCmsgHdr::LEN) };
+
+        // Number of payload bytes copied to the cage, or None if nothing should be written
+        let copied = match (level, typ) {
+            (SOL_SOCKET, SCM_RIGHTS) => {
+                let nfds = datalen / mem::size_of::<i32>();
+                let nfit = nfds.min(room / mem::size_of::<i32>());
+                let mut installed = 0;
+                for i in 0..nfds {
+                    let hostfd = unsafe { ptr::read_unaligned((data as *const i32).add(i)) };
+                    if i >= nfit {
+                        unsafe { libc::close(hostfd) };
+                        flags |= MSG_CTRUNC;
+                        continue;
+                    }
+                    match fdtables::get_unused_virtual_fd(
+                        cageid,
+                        FDKIND_KERNEL,
+                        hostfd as u64,
+                        cloexec,
+                        0,
+                    ) {
+                        Ok(virtualfd) => {
+                            unsafe {
+                                ptr::write_unaligned(
+                                    (out as *mut i32).add(installed),
+                                    virtualfd as i32,
+                                )
+                            };
+                            installed += 1;
+                        }
+                        Err(_) => {
+                            unsafe { libc::close(hostfd) };
+                            flags |= MSG_CTRUNC;
+                        }
+                    }
+                }
+                if installed > 0 {
+                    Some(installed * mem::size_of::<i32>())
+                } else {
+                    None
+                }
+            }
+            (SOL_SOCKET, SCM_CREDENTIALS) if datalen == mem::size_of::<ucred>() && fits_header => {
+                let mut cred = unsafe { ptr::read_unaligned(data as *const ucred) };
+                cred.pid = if cred.pid == unsafe { libc::getpid() } {
+                    unix_peer_cage(kernel_fd).unwrap_or(0) as pid_t
+                } else {
+                    0
+                };
+                let n = datalen.min(room);
+                unsafe { ptr::copy_nonoverlapping(&cred as *const ucred as *const u8, out, n) };
+                Some(n)
+            }
+            _ if fits_header => {
+                let n = datalen.min(room);
+                unsafe { ptr::copy_nonoverlapping(data, out, n) };
+                Some(n)
+            }
+            _ => None,
+        };
+
+        match copied {
+            Some(n) => {
+                // SCM_RIGHTS already flagged any fds it had to drop
+                if (level != SOL_SOCKET || typ != SCM_RIGHTS) && n < datalen {
+                    flags |= MSG_CTRUNC;
+                }
+                let hdr = GuestCmsgHdr {
+                    cmsg_len: (GuestCmsgHdr::LEN + n) as u32,
+                    cmsg_level: level,
+                    cmsg_type: typ,
+                };
+                unsafe { ptr::write_unaligned(control.add(written) as *mut GuestCmsgHdr, hdr) };
+                written += GuestCmsgHdr::align(GuestCmsgHdr::LEN + n).min(controllen - written);
+            }
+            None => {
+                if !(level == SOL_SOCKET && typ == SCM_RIGHTS) {
+                    flags |= MSG_CTRUNC;
+                }
+            }
+        }
+
+        cmsg = unsafe { CMSG_NXTHDR(host_msg, cmsg) };
+    }
+
+    (written, flags)
+}
+
+/// Collects the fds of the `SCM_RIGHTS` messages in a cage's control buffer for `sendmsg` on an
+/// in-process socket, as duplicates of their host fds.
+///
+/// Only fds backed by a host fd can be passed, and `SCM_RIGHTS` is the only control message
+/// supported; anything else fails with `EOPNOTSUPP`.
+fn inproc_rights(cageid: u64, control: *const u8, controllen: usize) -> Result<PassedFds, Errno> {
+    let mut fds = PassedFds::default();
+    let mut offset = 0;
+    while offset + GuestCmsgHdr::LEN <= controllen {
+        let hdr = unsafe { ptr::read_unaligned(control.add(offset) as *const GuestCmsgHdr) };
+        let len = hdr.cmsg_len as usize;
+        if len < GuestCmsgHdr::LEN || len > controllen - offset {
+            return Err(Errno::EINVAL);
+        }
+        if (hdr.cmsg_level, hdr.cmsg_type) != (SOL_SOCKET, SCM_RIGHTS) {
+            return Err(Errno::EOPNOTSUPP);
+        }
+        let datalen = len - GuestCmsgHdr::LEN;
+        if !datalen.is_multiple_of(mem::size_of::<i32>()) {
+            return Err(Errno::EINVAL);
+        }
+        let data = unsafe { control.add(offset + GuestCmsgHdr::LEN) } as *const i32;
+        for i in 0..datalen / mem::size_of::<i32>() {
+            let virtualfd = unsafe { ptr::read_unaligned(data.add(i)) };
+            if virtualfd < 0 {
+                return Err(Errno::EBADF);
+            }
+            let entry = fdtables::translate_virtual_fd(cageid, virtualfd as u64)
+                .map_err(|_| Errno::EBADF)?;
+            if entry.fdkind != FDKIND_KERNEL {
+                return Err(Errno::EOPNOTSUPP);
+            }
+            fds.push_dup(entry.underfd as i32)?;
+        }
+        offset += GuestCmsgHdr::align(len);
+    }
+    Ok(fds)
+}
+
+/// Delivers fds received on an in-process socket to the cage's control buffer as one
+/// `SCM_RIGHTS` message, the same way `cmsgs_host_to_guest` does for a host `recvmsg`.
+///
+/// Returns the number of bytes written to the cage's buffer and the `msg_flags` to set.
+fn inproc_rights_to_guest(
+    cageid: u64,
+    fds: PassedFds,
+    control: *mut u8,
+    controllen: usize,
+    cloexec: bool,
+) -> (usize, i32) {
+    if fds.is_empty() {
+        return (0, 0);
+    }
+    let fds = fds.into_fds();
+    let datalen = fds.len() * mem::size_of::<i32>();
+    let space = unsafe { CMSG_SPACE(datalen as u32) } as usize;
+    let mut buf = vec![0u64; space.div_ceil(8)];
+    let mut host_msg: msghdr = unsafe { mem::zeroed() };
+    host_msg.msg_control = buf.as_mut_ptr() as *mut c_void;
+    host_msg.msg_controllen = space;
+    unsafe {
+        let cmsg = CMSG_FIRSTHDR(&host_msg);
+        (*cmsg).cmsg_len = CMSG_LEN(datalen as u32) as usize;
+        (*cmsg).cmsg_level = SOL_SOCKET;
+        (*cmsg).cmsg_type = SCM_RIGHTS;
+        ptr::copy_nonoverlapping(fds.as_ptr() as *const u8, CMSG_DATA(cmsg), datalen);
+    }
+    let controllen = if control.is_null() { 0 } else { controllen };
+    cmsgs_host_to_guest(cageid, -1, &host_msg, control, controllen, cloexec)
+}
+
+/// The iovec array of a host-layout `msghdr`.
+unsafe fn msg_iov_slice<'a>(msg: &libc::msghdr) -> &'a [iovec] {
+    if msg.msg_iov.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(msg.msg_iov, msg.msg_iovlen)
+    }
+}
+
 /// recvmsg syscall: receive message from socket (wasm32 guest to host pointer translation).
 /// Reads guest msghdr/iovec (ILP32 32-bit layout), translates pointers to host,
 /// calls libc::recvmsg, copies back output fields.
@@ -1837,14 +2690,84 @@ pub extern "C" fn recvmsg_syscall(
     // to host layout using the split-pointer trick, so msg_arg is a host pointer
     // to a host-layout msghdr ready for libc::recvmsg.
     let msg_ptr = sc_convert_buf(msg_arg, msg_cageid, cageid) as *mut libc::msghdr;
+    let msg = unsafe { &mut *msg_ptr };
+
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        let iov = unsafe { msg_iov_slice(msg) };
+        return match sock.recvmsg(cageid, iov, flags & !MSG_CMSG_CLOEXEC) {
+            Ok((n, fds)) => {
+                let (written, extra_flags) = inproc_rights_to_guest(
+                    cageid,
+                    fds,
+                    msg.msg_control as *mut u8,
+                    msg.msg_controllen,
+                    flags & MSG_CMSG_CLOEXEC != 0,
+                );
+                msg.msg_namelen = 0;
+                msg.msg_controllen = written;
+                msg.msg_flags = extra_flags;
+                n as i32
+            }
+            Err(e) => syscall_error(e, "recvmsg", "in-process receive failed"),
+        };
+    }
+
+    // The kernel reports the full length of a source address it truncated
+    let namecap = msg.msg_namelen;
+
+    if msg.msg_control.is_null() || msg.msg_controllen == 0 {
+        let ret = unsafe { libc::recvmsg(fd, msg_ptr, flags) as i32 };
+        if ret < 0 {
+            return handle_errno(get_errno(), "recvmsg");
+        }
+        let namelen = msg.msg_namelen.min(namecap);
+        netns::host_to_guest(cageid, fd, msg.msg_name as *mut sockaddr, namelen);
+        route_source(fd, msg, namecap);
+        return ret;
+    }
+
+    // msg_control still points at the cage's buffer in the wasm32 cmsghdr layout. Receive into a
+    // host-layout buffer large enough for as many messages as could fit in the cage's buffer
+    // (each host header needs at most 8 more bytes), then convert back.
+    let guest_control = msg.msg_control as *mut u8;
+    let guest_controllen = msg.msg_controllen;
+    let host_controllen = guest_controllen + (guest_controllen / GuestCmsgHdr::LEN + 1) * 8;
+    let mut host_control = vec![0u64; host_controllen.div_ceil(8)];
+    let mut host_msg = *msg;
+    host_msg.msg_control = host_control.as_mut_ptr() as *mut c_void;
+    host_msg.msg_controllen = host_control.len() * 8;
 
-    let ret = unsafe { libc::recvmsg(fd, msg_ptr, flags) as i32 };
+    let ret = unsafe { libc::recvmsg(fd, &mut host_msg, flags) as i32 };
     if ret < 0 {
         return handle_errno(get_errno(), "recvmsg");
     }
+
+    let (written, extra_flags) = cmsgs_host_to_guest(
+        cageid,
+        fd,
+        &host_msg,
+        guest_control,
+        guest_controllen,
+        flags & MSG_CMSG_CLOEXEC != 0,
+    );
+    msg.msg_namelen = host_msg.msg_namelen;
+    msg.msg_controllen = written;
+    msg.msg_flags = host_msg.msg_flags | extra_flags;
+    let namelen = msg.msg_namelen.min(namecap);
+    netns::host_to_guest(cageid, fd, msg.msg_name as *mut sockaddr, namelen);
+    route_source(fd, msg, namecap);
     ret
 }
 
+/// Reports the kernel as the source of a message received on a route netlink socket, whose host
+/// peer is unnamed. `namecap` is the size of the cage's `msg_name` buffer.
+fn route_source(fd: i32, msg: &mut libc::msghdr, namecap: socklen_t) {
+    if let Some(kernel) = rtnetlink::peer_name(fd) {
+        msg.msg_namelen = namecap;
+        unsafe { copy_out_sockaddr(msg.msg_name as *mut SockAddr, &mut msg.msg_namelen, &kernel) };
+    }
+}
+
 /// sendmsg syscall: send message on socket (wasm32 guest to host pointer translation).
 /// Reads guest msghdr/iovec (ILP32 32-bit layout), translates pointers to host,
 /// calls libc::sendmsg.
@@ -1882,19 +2805,92 @@ pub extern "C" fn sendmsg_syscall(
     // to host layout using the split-pointer trick, so msg_arg is a host pointer
     // to a host-layout msghdr ready for libc::sendmsg.
     let msg_ptr = sc_convert_buf(msg_arg, msg_cageid, cageid) as *const libc::msghdr;
+    let msg = unsafe { &*msg_ptr };
+
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        let fds = if msg.msg_control.is_null() || msg.msg_controllen == 0 {
+            PassedFds::default()
+        } else {
+            match inproc_rights(cageid, msg.msg_control as *const u8, msg.msg_controllen) {
+                Ok(fds) => fds,
+                Err(e) => {
+                    return syscall_error(e, "sendmsg", "unsupported control message");
+                }
+            }
+        };
+        let iov = unsafe { msg_iov_slice(msg) };
+        return match sock.sendmsg(cageid, iov, flags, fds) {
+            Ok(n) => n as i32,
+            Err(e) => syscall_error(e, "sendmsg", "in-process send failed"),
+        };
+    }
+
+    if rtnetlink::is_route_socket(fd) {
+        let iov = unsafe { msg_iov_slice(msg) };
+        let name = msg.msg_name as *const sockaddr;
+        return match rtnetlink::send(cageid, fd, iov, name, msg.msg_namelen) {
+            Ok(n) => n as i32,
+            Err(e) => syscall_error(e, "sendmsg", "route netlink request failed"),
+        };
+    }
+
+    // A loopback address of a private network namespace lives on another host port
+    let mut routed =
+        match netns::route(cageid, fd, msg.msg_name as *const sockaddr, msg.msg_namelen) {
+            Ok(routed) => routed,
+            Err(e) => return syscall_error(e, "sendmsg", "destination refused for this cage"),
+        };
+    let mut host_msg = *msg;
+    if let Some((storage, len)) = &mut routed {
+        host_msg.msg_name = storage as *mut sockaddr_storage as *mut c_void;
+        host_msg.msg_namelen = *len;
+    }
 
-    let ret = unsafe { libc::sendmsg(fd, msg_ptr, flags) as i32 };
+    if msg.msg_control.is_null() || msg.msg_controllen == 0 {
+        let ret = unsafe { libc::sendmsg(fd, &host_msg, flags) as i32 };
+        if ret < 0 {
+            return handle_errno(get_errno(), "sendmsg");
+        }
+        netns::note_local_port(cageid, fd);
+        return ret;
+    }
+
+    // msg_control still points at the cage's buffer in the wasm32 cmsghdr layout, with virtual
+    // fds in any SCM_RIGHTS payload. Send a translated host copy instead.
+    let (mut host_control, host_controllen) =
+        match cmsgs_guest_to_host(cageid, msg.msg_control as *const u8, msg.msg_controllen) {
+            Ok(control) => control,
+            Err(Errno::EBADF) => {
+                return syscall_error(Errno::EBADF, "sendmsg", "SCM_RIGHTS contains an invalid fd")
+            }
+            Err(Errno::EPERM) => {
+                return syscall_error(
+                    Errno::EPERM,
+                    "sendmsg",
+                    "SCM_CREDENTIALS does not match the sending cage",
+                )
+            }
+            Err(e) => return syscall_error(e, "sendmsg", "malformed control message"),
+        };
+    host_msg.msg_control = host_control.as_mut_ptr() as *mut c_void;
+    host_msg.msg_controllen = host_controllen;
+
+    let ret = unsafe { libc::sendmsg(fd, &host_msg, flags) as i32 };
     if ret < 0 {
         return handle_errno(get_errno(), "sendmsg");
     }
+    netns::note_local_port(cageid, fd);
+    // This cage is now the one the peer hears from on this socket
+    track_unix_socket(cageid, fd);
     ret
 }
 
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/gethostname.2.html
 ///
 /// The Linux `gethostname()` syscall returns the current host name of the system.
-/// This implementation retrieves the destination buffer and length from the current cage,
-/// and stores the host name into user space.
+/// This implementation returns the hostname of the cage's UTS namespace (see `uts`), never the
+/// host's own. As in glibc, a buffer too small for the name and its NUL gets as much of it as
+/// fits, and the call fails with ENAMETOOLONG.
 ///
 /// ## Input:
 ///     - cageid: identifier of the current cage
@@ -1935,20 +2931,81 @@ pub extern "C" fn gethostname_syscall(
         );
     }
 
-    let ret = unsafe { libc::gethostname(name as *mut i8, len) };
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "gethostname");
+    let mut hostname = uts::hostname(cageid);
+    hostname.push(0);
+    let copied = hostname.len().min(len);
+    unsafe { std::ptr::copy_nonoverlapping(hostname.as_ptr(), name, copied) };
+    if copied < hostname.len() {
+        return syscall_error(Errno::ENAMETOOLONG, "gethostname", "buffer too small");
     }
 
-    ret
+    0
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/sethostname.2.html
+///
+/// The Linux `sethostname()` syscall sets the hostname of the caller's UTS namespace. This
+/// implementation renames the cage's UTS namespace (see `uts`), so every cage sharing it sees
+/// the new name; the host's hostname is left alone.
+///
+/// ## Input:
+///     - cageid: identifier of the current cage
+///     - name_arg: pointer to the new name, which need not be NUL-terminated
+///     - len_arg: length of the new name
+///
+/// ## Return:
+///     - On success: 0
+///     - On failure: EINVAL if the name is longer than `HOST_NAME_MAX`
+pub extern "C" fn sethostname_syscall(
+    cageid: u64,
+    name_arg: u64,
+    _name_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let name = name_arg as *const u8;
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "sethostname_syscall"
+        );
+    }
+
+    if len > uts::HOST_NAME_MAX {
+        return syscall_error(Errno::EINVAL, "sethostname", "name too long");
+    }
+    if name.is_null() && len > 0 {
+        return syscall_error(Errno::EFAULT, "sethostname", "name is null");
+    }
+    let hostname = if len == 0 {
+        &[][..]
+    } else {
+        unsafe { std::slice::from_raw_parts(name, len) }
+    };
+    uts::set_hostname(cageid, hostname);
+    0
 }
 
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/getsockopt.2.html
 ///
 /// The Linux `getsockopt()` syscall retrieves the value of a socket option.
 /// This implementation retrieves the virtual file descriptor, option level, and option name
-/// from the current cage, and writes the result to the provided user-space buffer.
+/// from the current cage, and writes the result to the provided user-space buffer in the cage's
+/// layout. Only the options listed in `sockopt` are supported; `SO_PEERCRED` reports the peer cage.
 ///
 /// ## Input:
 ///     - cageid: identifier of the current cage
@@ -1994,13 +3051,22 @@ pub extern "C" fn getsockopt_syscall(
         );
     }
 
-    let ret = unsafe { libc::getsockopt(fd, level, optname, optval, optlen) };
-    if ret < 0 {
-        let errno = get_errno();
-        return handle_errno(errno, "getsockopt");
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        return match sock.getsockopt(level, optname, optval as *mut u8, optlen) {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "getsockopt", "unsupported in-process socket option"),
+        };
     }
 
-    ret
+    let ret = if rtnetlink::is_route_socket(fd) {
+        rtnetlink::getsockopt(fd, level, optname, optval, optlen)
+    } else {
+        sockopt::get(fd, level, optname, optval, optlen)
+    };
+    match ret {
+        Ok(()) => 0,
+        Err(e) => syscall_error(e, "getsockopt", "reading the socket option failed"),
+    }
 }
 
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/getpeername.2.html
@@ -2052,6 +3118,18 @@ pub extern "C" fn getpeername_syscall(
         return syscall_error(Errno::EFAULT, "getpeername_syscall", "len is null");
     }
 
+    if let Some(sock) = inproc_socket::lookup(fd_cageid, fd_arg) {
+        return match sock.copy_out_name(true, user_addr as *mut u8, lenp) {
+            Ok(()) => 0,
+            Err(e) => syscall_error(e, "getpeername", "in-process getpeername failed"),
+        };
+    }
+
+    if let Some(peer) = rtnetlink::peer_name(fd) {
+        unsafe { copy_out_sockaddr(user_addr, lenp, &peer) };
+        return 0;
+    }
+
     let mut len: socklen_t = unsafe { *lenp };
     let max_len = mem::size_of::<sockaddr_storage>() as socklen_t;
     if len > max_len {
@@ -2071,6 +3149,7 @@ pub extern "C" fn getpeername_syscall(
         let errno = get_errno();
         return handle_errno(errno, "getpeername");
     }
+    netns::host_to_guest(cageid, fd, &mut storage as *mut _ as *mut sockaddr, len);
 
     unsafe {
         copy_out_sockaddr(user_addr, lenp, &storage);
@@ -2129,6 +3208,17 @@ pub extern "C" fn socketpair_syscall(
         );
     }
 
+    if inproc_socket::wants(domain, typ, protocol) {
+        return match inproc_socket::socketpair(cageid, typ) {
+            Ok((vsv_1, vsv_2)) => {
+                virtual_socket_vector.sock1 = vsv_1 as i32;
+                virtual_socket_vector.sock2 = vsv_2 as i32;
+                0
+            }
+            Err(e) => syscall_error(e, "socketpair", "could not create in-process sockets"),
+        };
+    }
+
     let mut kernel_socket_vector: [i32; 2] = [0, 0];
 
     let ret = unsafe { libc::socketpair(domain, typ, protocol, kernel_socket_vector.as_mut_ptr()) };
@@ -2151,9 +3241,31 @@ pub extern "C" fn socketpair_syscall(
     // host kernel FD (`FDKIND_KERNEL`), we simply defer to the kernel as the
     // source of truth and do not duplicate this flag in `fdtables::optionalinfo`.
     let vsv_1 =
-        fdtables::get_unused_virtual_fd(cageid, FDKIND_KERNEL, ksv_1 as u64, cloexec, 0).unwrap();
+        match fdtables::get_unused_virtual_fd(cageid, FDKIND_KERNEL, ksv_1 as u64, cloexec, 0) {
+            Ok(virtualfd) => virtualfd,
+            Err(e) => {
+                unsafe {
+                    libc::close(ksv_1);
+                    libc::close(ksv_2);
+                }
+                return handle_errno(e as i32, "socketpair");
+            }
+        };
     let vsv_2 =
-        fdtables::get_unused_virtual_fd(cageid, FDKIND_KERNEL, ksv_2 as u64, cloexec, 0).unwrap();
+        match fdtables::get_unused_virtual_fd(cageid, FDKIND_KERNEL, ksv_2 as u64, cloexec, 0) {
+            Ok(virtualfd) => virtualfd,
+            Err(e) => {
+                // Closing the first virtual fd also closes its kernel fd.
+                let _ = fdtables::close_virtualfd(cageid, vsv_1);
+                unsafe { libc::close(ksv_2) };
+                return handle_errno(e as i32, "socketpair");
+            }
+        };
+
+    if domain == AF_UNIX {
+        track_unix_socket(cageid, ksv_1);
+        track_unix_socket(cageid, ksv_2);
+    }
 
     // Update virtual socketpair struct
     virtual_socket_vector.sock1 = vsv_1 as i32;
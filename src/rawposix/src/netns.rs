@@ -0,0 +1,585 @@
+//! Virtual network namespaces for cages
+//!
+//! All cages share the host process, so by default (`HOST_NETNS`) they share its network stack:
+//! a cage binding :8080 takes the host's port 8080, and two cages doing so collide. A cage started
+//! with `--private-net` (or `--publish`), or one that calls `unshare(CLONE_NEWNET)`, gets a
+//! namespace of its own instead. Children inherit their parent's namespace on fork. Inside a
+//! private namespace:
+//!
+//! - Ports are private. An AF_INET/AF_INET6 bind to the wildcard or a loopback address is backed by
+//!   a host socket on the loopback address, with a port chosen by the host. The cage keeps seeing
+//!   the port it asked for, both in `getsockname` and in the peer addresses reported to other
+//!   sockets of the namespace. Binding any other address fails with EADDRNOTAVAIL.
+//! - Loopback is private. A connection or datagram to 127.0.0.0/8 or ::1 reaches only sockets of
+//!   the same namespace and is refused with ECONNREFUSED otherwise, also when a host service
+//!   listens on that port.
+//! - The only interface is `lo`, with 127.0.0.1/8 and ::1. `NETLINK_ROUTE` sockets (and so
+//!   `getifaddrs` and `if_nameindex`) report that, see `rtnetlink`.
+//! - Published ports (`--publish HOST:GUEST[/udp]`) are the way in. In the namespace the first
+//!   cage starts in, a bind to port GUEST is made on the host's port HOST, at the address the cage
+//!   asked for.
+//! - Other destinations are reached through the host, much like behind a NAT.
+//!
+//! Independently of namespaces, the `--net-allow` / `--net-deny` rules restrict the destinations
+//! any cage may connect or send to. A refused destination fails with EPERM, as it would behind a
+//! host firewall rule.
+//!
+//! Known limitations: a socket follows the namespace of the cage using it rather than the one it
+//! was created in, and a wildcard IPv6 bind is backed by `::1` only, so it can't be reached
+//! through 127.0.0.1.
+use cage::get_cage;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use libc::{c_void, sockaddr, sockaddr_in, sockaddr_in6, sockaddr_storage, socklen_t};
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ptr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use sysdefs::constants::err_const::{get_errno, Errno};
+
+/// The host's own network stack. Cages in it bind and connect on the host directly.
+pub const HOST_NETNS: u64 = 0;
+
+/// Transport protocol of a port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Proto {
+    Tcp,
+    Udp,
+}
+
+/// A `--publish HOST:GUEST[/tcp|udp]` entry: a bind to port `guest_port` in the first cage's
+/// namespace takes port `host_port` on the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortMapping {
+    pub host_port: u16,
+    pub guest_port: u16,
+    pub proto: Proto,
+}
+
+impl FromStr for PortMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (ports, proto) = match s.split_once('/') {
+            Some((ports, "tcp")) => (ports, Proto::Tcp),
+            Some((ports, "udp")) => (ports, Proto::Udp),
+            Some((_, other)) => {
+                return Err(format!("unknown protocol '{}', expected tcp or udp", other))
+            }
+            None => (s, Proto::Tcp),
+        };
+        let (host, guest) = ports
+            .split_once(':')
+            .ok_or_else(|| "expected HOST_PORT:GUEST_PORT".to_string())?;
+        let port = |p: &str| match p.parse::<u16>() {
+            Ok(0) | Err(_) => Err(format!("invalid port '{}'", p)),
+            Ok(port) => Ok(port),
+        };
+        Ok(PortMapping {
+            host_port: port(host)?,
+            guest_port: port(guest)?,
+            proto,
+        })
+    }
+}
+
+/// A `--net-allow` / `--net-deny` destination, written `ADDR[/PREFIX][:PORT[-PORT]]`. An IPv6
+/// address followed by a prefix or port is put in brackets, as in `[2001:db8::]/32:443`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetRule {
+    addr: IpAddr,
+    prefix: u8,
+    ports: Option<(u16, u16)>,
+}
+
+impl NetRule {
+    fn matches(&self, ip: IpAddr, port: u16) -> bool {
+        if let Some((lo, hi)) = self.ports {
+            if port < lo || port > hi {
+                return false;
+            }
+        }
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                prefix_eq(&net.octets(), &ip.octets(), self.prefix)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                prefix_eq(&net.octets(), &ip.octets(), self.prefix)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Whether the first `prefix` bits of `a` and `b` are equal.
+fn prefix_eq(a: &[u8], b: &[u8], prefix: u8) -> bool {
+    let whole = prefix as usize / 8;
+    let rest = prefix % 8;
+    a[..whole] == b[..whole] && (rest == 0 || (a[whole] ^ b[whole]) >> (8 - rest) == 0)
+}
+
+impl FromStr for NetRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        // Split the address from the "/PREFIX:PORTS" tail. Without brackets, a port can only
+        // follow an IPv4 address or a prefix length, so a lone ':' separates it.
+        let (addr, tail) = if let Some(rest) = s.strip_prefix('[') {
+            rest.split_once(']')
+                .ok_or_else(|| format!("missing ']' in '{}'", s))?
+        } else if let Some(slash) = s.find('/') {
+            s.split_at(slash)
+        } else if s.matches(':').count() == 1 {
+            s.split_at(s.find(':').unwrap())
+        } else {
+            (s, "")
+        };
+
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|_| format!("invalid address '{}'", addr))?;
+        let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+
+        let (prefix, ports) = match tail.split_once(':') {
+            Some((prefix, ports)) => (prefix, Some(ports)),
+            None => (tail, None),
+        };
+        let prefix = match prefix {
+            "" => max_prefix,
+            p => p
+                .strip_prefix('/')
+                .and_then(|p| p.parse::<u8>().ok())
+                .filter(|&p| p <= max_prefix)
+                .ok_or_else(|| format!("invalid prefix length '{}'", p))?,
+        };
+
+        let ports = match ports {
+            None => None,
+            Some(p) => {
+                let (lo, hi) = p.split_once('-').unwrap_or((p, p));
+                let port = |x: &str| {
+                    x.parse::<u16>()
+                        .map_err(|_| format!("invalid port '{}'", x))
+                };
+                let (lo, hi) = (port(lo)?, port(hi)?);
+                if lo > hi {
+                    return Err(format!("invalid port range '{}'", p));
+                }
+                Some((lo, hi))
+            }
+        };
+
+        Ok(NetRule {
+            addr,
+            prefix,
+            ports,
+        })
+    }
+}
+
+/// Network settings chosen when lind-boot starts (see `configure`).
+#[derive(Debug, Clone, Default)]
+pub struct NetConfig {
+    /// Start the first cage in a private namespace rather than the host's.
+    pub private: bool,
+    /// Ports of the first cage's namespace that are published on the host.
+    pub publish: Vec<PortMapping>,
+    /// If not empty, cages may only reach destinations matching one of these.
+    pub allow: Vec<NetRule>,
+    /// Destinations cages may not reach, even if allowed above.
+    pub deny: Vec<NetRule>,
+}
+
+static CONFIG: OnceLock<NetConfig> = OnceLock::new();
+
+// Namespace ids are never reused, so a stale id can't alias a new namespace
+static NEXT_NETNS: AtomicU64 = AtomicU64::new(HOST_NETNS + 1);
+
+// The namespace published ports belong to
+static PUBLISH_NETNS: AtomicU64 = AtomicU64::new(HOST_NETNS);
+
+lazy_static! {
+    // Ports bound in private namespaces, and the host address backing each.
+    // <(netns, proto, guest port), (host address, host port)>
+    static ref PORTS: DashMap<(u64, Proto, u16), (IpAddr, u16)> = DashMap::new();
+    // Host sockets holding one of those ports, so that the port is freed when the socket closes
+    // and getsockname can report the address the cage asked for. <kernel_fd, binding>
+    static ref BINDINGS: DashMap<i32, Binding> = DashMap::new();
+}
+
+struct Binding {
+    key: (u64, Proto, u16),
+    host_port: u16,
+    local: sockaddr_storage,
+}
+
+/// Sets the network configuration. Must be called before `rawposix_start`; later calls have no
+/// effect.
+pub fn configure(config: NetConfig) {
+    let _ = CONFIG.set(config);
+}
+
+fn config() -> &'static NetConfig {
+    CONFIG.get_or_init(NetConfig::default)
+}
+
+/// Returns the namespace of the first cage: a new private one if configured, the host's
+/// otherwise. Published ports belong to this namespace.
+pub fn initial_netns() -> u64 {
+    if !config().private {
+        return HOST_NETNS;
+    }
+    let netns = new_netns();
+    PUBLISH_NETNS.store(netns, Ordering::Relaxed);
+    netns
+}
+
+/// Allocates a new private namespace, with no ports bound.
+pub fn new_netns() -> u64 {
+    NEXT_NETNS.fetch_add(1, Ordering::Relaxed)
+}
+
+fn cage_netns(cageid: u64) -> u64 {
+    get_cage(cageid).map_or(HOST_NETNS, |cage| cage.netns.load(Ordering::Relaxed))
+}
+
+/// Whether the cage is in a private namespace, which has only a loopback interface.
+pub(crate) fn is_private(cageid: u64) -> bool {
+    cage_netns(cageid) != HOST_NETNS
+}
+
+fn host_errno() -> Errno {
+    Errno::from_discriminant(get_errno()).unwrap_or(Errno::EIO)
+}
+
+/// Treats an IPv4-mapped IPv6 address as the IPv4 address it maps.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        ip => ip,
+    }
+}
+
+/// The loopback address of the same kind as `ip`.
+fn loopback(ip: IpAddr) -> IpAddr {
+    match canonical(ip) {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+    }
+}
+
+/// Reads the family, address and port of an AF_INET or AF_INET6 socket address of `len` bytes.
+fn inet_addr(addr: *const sockaddr, len: socklen_t) -> Option<(i32, IpAddr, u16)> {
+    if addr.is_null() || (len as usize) < mem::size_of::<libc::sa_family_t>() {
+        return None;
+    }
+    let family = unsafe { ptr::read_unaligned(ptr::addr_of!((*addr).sa_family)) } as i32;
+    match family {
+        libc::AF_INET if len as usize >= mem::size_of::<sockaddr_in>() => {
+            let sin = unsafe { ptr::read_unaligned(addr as *const sockaddr_in) };
+            let ip = Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr));
+            Some((family, IpAddr::V4(ip), u16::from_be(sin.sin_port)))
+        }
+        libc::AF_INET6 if len as usize >= mem::size_of::<sockaddr_in6>() => {
+            let sin6 = unsafe { ptr::read_unaligned(addr as *const sockaddr_in6) };
+            let ip = Ipv6Addr::from(sin6.sin6_addr.s6_addr);
+            Some((family, IpAddr::V6(ip), u16::from_be(sin6.sin6_port)))
+        }
+        _ => None,
+    }
+}
+
+/// Builds a socket address of `family` for `ip` and `port`. An IPv4 address is given in its
+/// IPv4-mapped form to AF_INET6; an IPv6 address can't be given to AF_INET.
+fn make_addr(family: i32, ip: IpAddr, port: u16) -> Option<(sockaddr_storage, socklen_t)> {
+    let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+    match family {
+        libc::AF_INET => {
+            let IpAddr::V4(v4) = canonical(ip) else {
+                return None;
+            };
+            let sin = &mut storage as *mut sockaddr_storage as *mut sockaddr_in;
+            unsafe {
+                (*sin).sin_family = libc::AF_INET as libc::sa_family_t;
+                (*sin).sin_port = port.to_be();
+                (*sin).sin_addr.s_addr = u32::from(v4).to_be();
+            }
+            Some((storage, mem::size_of::<sockaddr_in>() as socklen_t))
+        }
+        libc::AF_INET6 => {
+            let v6 = match ip {
+                IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+                IpAddr::V6(v6) => v6,
+            };
+            let sin6 = &mut storage as *mut sockaddr_storage as *mut sockaddr_in6;
+            unsafe {
+                (*sin6).sin6_family = libc::AF_INET6 as libc::sa_family_t;
+                (*sin6).sin6_port = port.to_be();
+                (*sin6).sin6_addr.s6_addr = v6.octets();
+            }
+            Some((storage, mem::size_of::<sockaddr_in6>() as socklen_t))
+        }
+        _ => None,
+    }
+}
+
+/// The protocol of a host socket, if it has ports.
+fn socket_proto(fd: i32) -> Option<Proto> {
+    let mut socktype: i32 = 0;
+    let mut len = mem::size_of::<i32>() as socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_TYPE,
+            &mut socktype as *mut i32 as *mut c_void,
+            &mut len,
+        )
+    };
+    match socktype {
+        _ if ret < 0 => None,
+        libc::SOCK_STREAM => Some(Proto::Tcp),
+        libc::SOCK_DGRAM => Some(Proto::Udp),
+        _ => None,
+    }
+}
+
+/// The local address of a host socket.
+fn local_addr(fd: i32) -> Option<(sockaddr_storage, socklen_t)> {
+    let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
+    let ret = unsafe {
+        libc::getsockname(
+            fd,
+            &mut storage as *mut sockaddr_storage as *mut sockaddr,
+            &mut len,
+        )
+    };
+    (ret == 0).then_some((storage, len))
+}
+
+/// Whether the `--net-allow` / `--net-deny` rules let a cage reach `port` on `ip`.
+fn permitted(ip: IpAddr, port: u16) -> bool {
+    let config = config();
+    !config.deny.iter().any(|rule| rule.matches(ip, port))
+        && (config.allow.is_empty() || config.allow.iter().any(|rule| rule.matches(ip, port)))
+}
+
+/// Binds the host socket `fd` to `addr` for a cage in a private namespace, as described in the
+/// module documentation. Returns `Ok(false)` if the cage is in the host's namespace or `addr`
+/// is not an internet address, and the caller should bind as usual.
+pub(crate) fn bind(
+    cageid: u64,
+    fd: i32,
+    addr: *const sockaddr,
+    addrlen: socklen_t,
+) -> Result<bool, Errno> {
+    let netns = cage_netns(cageid);
+    if netns == HOST_NETNS {
+        return Ok(false);
+    }
+    let (Some((family, ip, port)), Some(proto)) = (inet_addr(addr, addrlen), socket_proto(fd))
+    else {
+        return Ok(false);
+    };
+    if BINDINGS.contains_key(&fd) {
+        return Err(Errno::EINVAL);
+    }
+    let wildcard = canonical(ip).is_unspecified();
+    if !wildcard && !canonical(ip).is_loopback() {
+        return Err(Errno::EADDRNOTAVAIL);
+    }
+
+    // A published port is bound where the cage asked, on the host's port. Anything else stays
+    // on the host's loopback, on a port of the host's choosing.
+    let published = config()
+        .publish
+        .iter()
+        .find(|m| m.proto == proto && m.guest_port == port && port != 0)
+        .filter(|_| netns == PUBLISH_NETNS.load(Ordering::Relaxed));
+    let (host_ip, host_port) = match published {
+        Some(mapping) => (ip, mapping.host_port),
+        None if wildcard => (loopback(ip), 0),
+        None => (ip, 0),
+    };
+    let (host_addr, host_len) = make_addr(family, host_ip, host_port).ok_or(Errno::EINVAL)?;
+
+    // Hold the port's entry while binding, so that two sockets can't both take it
+    let reserved = match port {
+        0 => None,
+        _ => match PORTS.entry((netns, proto, port)) {
+            Entry::Occupied(_) => return Err(Errno::EADDRINUSE),
+            Entry::Vacant(entry) => Some(entry),
+        },
+    };
+
+    let ret = unsafe {
+        libc::bind(
+            fd,
+            &host_addr as *const sockaddr_storage as *const sockaddr,
+            host_len,
+        )
+    };
+    if ret < 0 {
+        return Err(host_errno());
+    }
+    let host_port = local_addr(fd)
+        .and_then(|(storage, len)| inet_addr(&storage as *const _ as *const sockaddr, len))
+        .map(|(_, _, port)| port)
+        .ok_or(Errno::EIO)?;
+
+    // A cage asking for any port gets the host's port number
+    let guest_port = if port == 0 { host_port } else { port };
+    match reserved {
+        Some(entry) => {
+            entry.insert((host_ip, host_port));
+        }
+        None => match PORTS.entry((netns, proto, guest_port)) {
+            Entry::Occupied(_) => return Err(Errno::EADDRINUSE),
+            Entry::Vacant(entry) => {
+                entry.insert((host_ip, host_port));
+            }
+        },
+    }
+
+    let (local, _) = make_addr(family, ip, guest_port).ok_or(Errno::EINVAL)?;
+    BINDINGS.insert(
+        fd,
+        Binding {
+            key: (netns, proto, guest_port),
+            host_port,
+            local,
+        },
+    );
+    Ok(true)
+}
+
+/// Checks the destination `addr` of a connect, sendto or sendmsg on the host socket `fd`.
+///
+/// Returns the host address to use instead when a cage in a private namespace addresses its
+/// loopback, `None` to use `addr` unchanged, ECONNREFUSED if no socket of the namespace holds
+/// the loopback port, and EPERM if the policy refuses the destination.
+pub(crate) fn route(
+    cageid: u64,
+    fd: i32,
+    addr: *const sockaddr,
+    addrlen: socklen_t,
+) -> Result<Option<(sockaddr_storage, socklen_t)>, Errno> {
+    let Some((family, ip, port)) = inet_addr(addr, addrlen) else {
+        return Ok(None);
+    };
+    let netns = cage_netns(cageid);
+    let dest = canonical(ip);
+
+    if netns != HOST_NETNS && dest.is_loopback() {
+        let Some(proto) = socket_proto(fd) else {
+            return Ok(None);
+        };
+        let (host_ip, host_port) = PORTS
+            .get(&(netns, proto, port))
+            .map(|entry| *entry.value())
+            .ok_or(Errno::ECONNREFUSED)?;
+        let target = if canonical(host_ip).is_unspecified() {
+            loopback(host_ip)
+        } else {
+            host_ip
+        };
+        return make_addr(family, target, host_port)
+            .map(Some)
+            .ok_or(Errno::ECONNREFUSED);
+    }
+
+    if !permitted(dest, port) {
+        return Err(Errno::EPERM);
+    }
+    Ok(None)
+}
+
+/// Registers the port a socket of a private namespace was given implicitly, by a connect,
+/// sendto or listen without a bind, so that other sockets of the namespace can reach it (to
+/// answer a datagram, for instance). Only loopback ports are registered; they keep their host
+/// number.
+pub(crate) fn note_local_port(cageid: u64, fd: i32) {
+    let netns = cage_netns(cageid);
+    if netns == HOST_NETNS || BINDINGS.contains_key(&fd) {
+        return;
+    }
+    let Some((local, len)) = local_addr(fd) else {
+        return;
+    };
+    let Some((_, ip, port)) = inet_addr(&local as *const _ as *const sockaddr, len) else {
+        return;
+    };
+    if port == 0 || !canonical(ip).is_loopback() {
+        return;
+    }
+    let Some(proto) = socket_proto(fd) else {
+        return;
+    };
+
+    let key = (netns, proto, port);
+    if let Entry::Vacant(entry) = PORTS.entry(key) {
+        entry.insert((ip, port));
+        BINDINGS.insert(
+            fd,
+            Binding {
+                key,
+                host_port: port,
+                local,
+            },
+        );
+    }
+}
+
+/// The address the cage bound the host socket `fd` to, if it is bound in a private namespace.
+pub(crate) fn local_name(fd: i32) -> Option<sockaddr_storage> {
+    BINDINGS.get(&fd).map(|binding| binding.local)
+}
+
+/// Rewrites an address the host reported for the socket `fd` (its peer, or its own local
+/// address if it was accepted) so that a host port backing a port of the cage's namespace shows
+/// as that port. `addr` holds `len` bytes and is changed in place.
+pub(crate) fn host_to_guest(cageid: u64, fd: i32, addr: *mut sockaddr, len: socklen_t) {
+    let netns = cage_netns(cageid);
+    if netns == HOST_NETNS {
+        return;
+    }
+    let Some((_, ip, host_port)) = inet_addr(addr, len) else {
+        return;
+    };
+    if !canonical(ip).is_loopback() {
+        return;
+    }
+    let Some(proto) = socket_proto(fd) else {
+        return;
+    };
+
+    let guest_port = PORTS
+        .iter()
+        .find(|entry| {
+            let (ns, p, _) = *entry.key();
+            ns == netns && p == proto && entry.value().1 == host_port
+        })
+        .map(|entry| entry.key().2);
+    if let Some(port) = guest_port {
+        // sin_port and sin6_port are at the same offset
+        unsafe {
+            ptr::write_unaligned(
+                ptr::addr_of_mut!((*(addr as *mut sockaddr_in)).sin_port),
+                port.to_be(),
+            )
+        };
+    }
+}
+
+/// Frees the port held by a host socket that is being closed. Called from `kernel_close`.
+pub fn release_socket(kernel_fd: i32) {
+    if let Some((_, binding)) = BINDINGS.remove(&kernel_fd) {
+        PORTS.remove_if(&binding.key, |_, &(_, host_port)| {
+            host_port == binding.host_port
+        });
+    }
+}
@@ -0,0 +1,734 @@
+//! Emulated `NETLINK_ROUTE` sockets
+//!
+//! A cage's `socket(AF_NETLINK, _, NETLINK_ROUTE)` doesn't get a host netlink socket, which would
+//! describe the host's interfaces whatever namespace the cage is in. It gets one end of a host
+//! `AF_UNIX` `SOCK_SEQPACKET` pair instead. Requests sent on it are answered here by writing the
+//! replies into the other end, so reads, `poll`, `epoll` and nonblocking mode work through the
+//! kernel as for any other socket, and message boundaries are kept.
+//!
+//! The queries libraries use to enumerate interfaces are answered: `RTM_GETLINK` (a dump, or one
+//! link by index or name) and `RTM_GETADDR` dumps. That is what glibc's `getifaddrs`,
+//! `if_nameindex` and `getaddrinfo(AI_ADDRCONFIG)` send. Replies describe the cage's network view:
+//! the host's interfaces in the host namespace, or a lone `lo` with 127.0.0.1/8 and ::1 in a
+//! private one (see `netns`). Other queries fail with EOPNOTSUPP and changes with EPERM, in an
+//! `NLMSG_ERROR` reply. `SOL_NETLINK` options are accepted and have no effect, so multicast groups
+//! can be joined but never deliver notifications.
+//!
+//! Emulated sockets are keyed by the inode of the cage's end, so `dup`ed fds refer to the same
+//! socket. The reply end is closed once every fd of the cage's end is.
+
+use crate::netns;
+use crate::sockopt;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use libc::{
+    c_void, iovec, nlmsghdr, pollfd, sockaddr, sockaddr_ll, sockaddr_nl, sockaddr_storage,
+    socklen_t, AF_INET, AF_INET6, AF_PACKET, AF_UNIX, AF_UNSPEC, MSG_DONTWAIT, MSG_NOSIGNAL,
+    POLLHUP, SOCK_CLOEXEC, SOCK_DGRAM, SOCK_NONBLOCK, SOCK_RAW, SOCK_SEQPACKET,
+};
+use std::ffi::CStr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::{mem, ptr, slice};
+use sysdefs::constants::err_const::{get_errno, Errno};
+use sysdefs::constants::net_const::{
+    AF_NETLINK, ARPHRD_LOOPBACK, IFA_ADDRESS, IFA_BROADCAST, IFA_F_PERMANENT, IFA_LABEL, IFA_LOCAL,
+    IFF_BROADCAST, IFF_LOOPBACK, IFF_LOWER_UP, IFF_POINTOPOINT, IFF_RUNNING, IFF_UP, IFLA_ADDRESS,
+    IFLA_BROADCAST, IFLA_IFNAME, IFLA_MTU, IFLA_OPERSTATE, IF_OPER_DOWN, IF_OPER_UNKNOWN,
+    IF_OPER_UP, NETLINK_ROUTE, NLMSG_DONE, NLMSG_ERROR, NLM_F_ACK, NLM_F_CAPPED, NLM_F_DUMP,
+    NLM_F_MULTI, NLM_F_REQUEST, RTM_BASE, RTM_GETADDR, RTM_GETLINK, RTM_NEWADDR, RTM_NEWLINK,
+    RT_SCOPE_HOST, RT_SCOPE_LINK, RT_SCOPE_UNIVERSE, SOL_NETLINK, SOL_SOCKET, SO_DOMAIN,
+    SO_PROTOCOL, SO_TYPE,
+};
+use sysdefs::data::net_struct::{IfAddrMsg, IfInfoMsg};
+
+/// Replies to one request are packed into datagrams of at most this size, about what the kernel
+/// puts in one read of a dump.
+const DATAGRAM_SIZE: usize = 4096;
+
+const HDRLEN: usize = mem::size_of::<nlmsghdr>();
+
+lazy_static! {
+    // Emulated sockets by the inode of the cage's end. <inode, route socket>
+    static ref SOCKETS: DashMap<u64, RouteSocket> = DashMap::new();
+}
+
+struct RouteSocket {
+    // The other end of the pair, where replies are written
+    reply_fd: i32,
+    // Port id reported by getsockname and stamped on replies
+    portid: u32,
+    // SOCK_RAW or SOCK_DGRAM, as the cage asked for
+    socktype: i32,
+}
+
+fn host_errno() -> Errno {
+    Errno::from_discriminant(get_errno()).unwrap_or(Errno::EIO)
+}
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn bytes_of<T>(value: &T) -> &[u8] {
+    unsafe { slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>()) }
+}
+
+fn inode(fd: i32) -> Option<u64> {
+    let mut st: libc::stat = unsafe { mem::zeroed() };
+    (unsafe { libc::fstat(fd, &mut st) } == 0).then_some(st.st_ino)
+}
+
+/// Returns the inode of `fd` if it is the cage's end of an emulated socket.
+fn lookup(fd: i32) -> Option<u64> {
+    if SOCKETS.is_empty() {
+        return None;
+    }
+    inode(fd).filter(|ino| SOCKETS.contains_key(ino))
+}
+
+/// Whether `socket(domain, _, protocol)` creates an emulated socket.
+pub(crate) fn wants(domain: i32, protocol: i32) -> bool {
+    domain == AF_NETLINK && protocol == NETLINK_ROUTE
+}
+
+/// Whether the host fd `fd` is the cage's end of an emulated socket.
+pub(crate) fn is_route_socket(fd: i32) -> bool {
+    lookup(fd).is_some()
+}
+
+/// Creates an emulated socket for `cageid`. Returns the host fd the cage uses.
+pub(crate) fn socket(cageid: u64, socktype: i32) -> Result<i32, Errno> {
+    let kind = socktype & !(SOCK_NONBLOCK | SOCK_CLOEXEC);
+    if kind != SOCK_RAW && kind != SOCK_DGRAM {
+        return Err(Errno::ESOCKTNOSUPPORT);
+    }
+    let mut fds = [0i32; 2];
+    let flags = SOCK_SEQPACKET | SOCK_CLOEXEC | (socktype & SOCK_NONBLOCK);
+    if unsafe { libc::socketpair(AF_UNIX, flags, 0, fds.as_mut_ptr()) } < 0 {
+        return Err(host_errno());
+    }
+    let Some(ino) = inode(fds[0]) else {
+        let e = host_errno();
+        unsafe {
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+        return Err(e);
+    };
+    SOCKETS.insert(
+        ino,
+        RouteSocket {
+            reply_fd: fds[1],
+            portid: cageid as u32,
+            socktype: kind,
+        },
+    );
+    Ok(fds[0])
+}
+
+/// The id of the emulated socket whose cage end is the host fd `fd`, for `deliver`.
+pub(crate) fn route_socket_id(fd: i32) -> Option<u64> {
+    lookup(fd)
+}
+
+/// Queues `msg` as one datagram for the cage to read from the emulated socket `id`, as the
+/// kernel does for `mq_notify` cookies. Does nothing if the socket is gone or its buffer is full.
+pub(crate) fn deliver(id: u64, msg: &[u8]) {
+    if let Some(sock) = SOCKETS.get(&id) {
+        unsafe {
+            libc::send(
+                sock.reply_fd,
+                msg.as_ptr() as *const c_void,
+                msg.len(),
+                MSG_DONTWAIT | MSG_NOSIGNAL,
+            )
+        };
+    }
+}
+
+/// Closes the reply end of every emulated socket the cage no longer has an fd for. Called from
+/// `kernel_close` once a host fd is closed.
+pub fn reap_closed() {
+    if SOCKETS.is_empty() {
+        return;
+    }
+    SOCKETS.retain(|_, sock| {
+        let mut pfd = pollfd {
+            fd: sock.reply_fd,
+            events: 0,
+            revents: 0,
+        };
+        let hung_up = unsafe { libc::poll(&mut pfd, 1, 0) } > 0 && pfd.revents & POLLHUP != 0;
+        if hung_up {
+            unsafe { libc::close(sock.reply_fd) };
+        }
+        !hung_up
+    });
+}
+
+fn netlink_addr(portid: u32) -> sockaddr_storage {
+    let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+    let nl = &mut storage as *mut sockaddr_storage as *mut sockaddr_nl;
+    unsafe {
+        (*nl).nl_family = AF_NETLINK as u16;
+        (*nl).nl_pid = portid;
+    }
+    storage
+}
+
+/// Reads the `sockaddr_nl` a cage passed to `bind`, `connect` or `sendto`.
+fn read_addr(addr: *const sockaddr, len: socklen_t) -> Result<sockaddr_nl, Errno> {
+    if (len as usize) < mem::size_of::<sockaddr_nl>() {
+        return Err(Errno::EINVAL);
+    }
+    if addr.is_null() {
+        return Err(Errno::EFAULT);
+    }
+    let nl = unsafe { ptr::read_unaligned(addr as *const sockaddr_nl) };
+    if nl.nl_family as i32 != AF_NETLINK {
+        return Err(Errno::EINVAL);
+    }
+    Ok(nl)
+}
+
+/// `bind()`: a nonzero `nl_pid` becomes the socket's port id.
+pub(crate) fn bind(fd: i32, addr: *const sockaddr, len: socklen_t) -> Result<(), Errno> {
+    let nl = read_addr(addr, len)?;
+    if nl.nl_pid != 0 {
+        if let Some(mut sock) = lookup(fd).and_then(|ino| SOCKETS.get_mut(&ino)) {
+            sock.portid = nl.nl_pid;
+        }
+    }
+    Ok(())
+}
+
+/// `connect()`: only the kernel (port id 0) can be connected to. `AF_UNSPEC` disconnects.
+pub(crate) fn connect(addr: *const sockaddr, len: socklen_t) -> Result<(), Errno> {
+    if !addr.is_null() && len >= 2 && unsafe { (*addr).sa_family } as i32 == AF_UNSPEC {
+        return Ok(());
+    }
+    match read_addr(addr, len)?.nl_pid {
+        0 => Ok(()),
+        _ => Err(Errno::ECONNREFUSED),
+    }
+}
+
+/// The address `getsockname` reports for an emulated socket.
+pub(crate) fn local_name(fd: i32) -> Option<sockaddr_storage> {
+    let ino = lookup(fd)?;
+    SOCKETS.get(&ino).map(|sock| netlink_addr(sock.portid))
+}
+
+/// The address replies come from, reported by `getpeername`, `recvfrom` and `recvmsg`: the
+/// kernel's.
+pub(crate) fn peer_name(fd: i32) -> Option<sockaddr_storage> {
+    lookup(fd).map(|_| netlink_addr(0))
+}
+
+/// `getsockopt()`: the socket reports itself as a netlink socket; other socket-level options
+/// apply to the cage's end of the pair.
+pub(crate) fn getsockopt(
+    fd: i32,
+    level: i32,
+    optname: i32,
+    optval: *mut c_void,
+    optlen: *mut socklen_t,
+) -> Result<(), Errno> {
+    let socktype = lookup(fd)
+        .and_then(|ino| SOCKETS.get(&ino).map(|sock| sock.socktype))
+        .ok_or(Errno::EBADF)?;
+    let value = match (level, optname) {
+        (SOL_SOCKET, SO_DOMAIN) => AF_NETLINK,
+        (SOL_SOCKET, SO_PROTOCOL) => NETLINK_ROUTE,
+        (SOL_SOCKET, SO_TYPE) => socktype,
+        (SOL_NETLINK, _) => 0,
+        _ => return sockopt::get(fd, level, optname, optval, optlen),
+    };
+    if optlen.is_null() {
+        return Err(Errno::EFAULT);
+    }
+    let n = (unsafe { *optlen } as usize).min(mem::size_of::<i32>());
+    if n > 0 && optval.is_null() {
+        return Err(Errno::EFAULT);
+    }
+    unsafe {
+        ptr::copy_nonoverlapping(value.to_ne_bytes().as_ptr(), optval as *mut u8, n);
+        *optlen = n as socklen_t;
+    }
+    Ok(())
+}
+
+/// `setsockopt()`: `SOL_NETLINK` options have no effect; socket-level options apply to the
+/// cage's end of the pair.
+pub(crate) fn setsockopt(
+    fd: i32,
+    level: i32,
+    optname: i32,
+    optval: *const c_void,
+    optlen: socklen_t,
+) -> Result<(), Errno> {
+    if level == SOL_NETLINK {
+        return Ok(());
+    }
+    sockopt::set(fd, level, optname, optval, optlen)
+}
+
+/// Answers the requests a cage sends on an emulated socket. Returns the number of bytes sent.
+///
+/// Replies the cage hasn't read yet count against the socket's buffer; when it is full, the send
+/// fails with ENOBUFS.
+pub(crate) fn send(
+    cageid: u64,
+    fd: i32,
+    iov: &[iovec],
+    dest: *const sockaddr,
+    destlen: socklen_t,
+) -> Result<usize, Errno> {
+    if !dest.is_null() {
+        // The kernel is the only netlink peer there is
+        if read_addr(dest, destlen)?.nl_pid != 0 {
+            return Err(Errno::ECONNREFUSED);
+        }
+    }
+    let (reply_fd, portid) = lookup(fd)
+        .and_then(|ino| SOCKETS.get(&ino).map(|sock| (sock.reply_fd, sock.portid)))
+        .ok_or(Errno::EBADF)?;
+
+    let mut request = Vec::new();
+    for v in iov {
+        if v.iov_len == 0 {
+            continue;
+        }
+        if v.iov_base.is_null() {
+            return Err(Errno::EFAULT);
+        }
+        request.extend_from_slice(unsafe {
+            slice::from_raw_parts(v.iov_base as *const u8, v.iov_len)
+        });
+    }
+
+    let view = View::of(cageid);
+    let mut out = Replies {
+        portid,
+        datagrams: Vec::new(),
+        current: Vec::new(),
+    };
+    let mut off = 0;
+    while off + HDRLEN <= request.len() {
+        let hdr = unsafe { ptr::read_unaligned(request[off..].as_ptr() as *const nlmsghdr) };
+        let len = hdr.nlmsg_len as usize;
+        if len < HDRLEN || off + len > request.len() {
+            break;
+        }
+        answer(&view, &hdr, &request[off + HDRLEN..off + len], &mut out);
+        off += align(len);
+    }
+
+    for datagram in &out.datagrams {
+        let ret = unsafe {
+            libc::send(
+                reply_fd,
+                datagram.as_ptr() as *const c_void,
+                datagram.len(),
+                MSG_DONTWAIT | MSG_NOSIGNAL,
+            )
+        };
+        if ret < 0 {
+            return Err(Errno::ENOBUFS);
+        }
+    }
+    Ok(request.len())
+}
+
+/// Reply messages, packed into datagrams.
+struct Replies {
+    portid: u32,
+    datagrams: Vec<Vec<u8>>,
+    current: Vec<u8>,
+}
+
+impl Replies {
+    fn push(&mut self, msg_type: u16, flags: u16, seq: u32, payload: &[u8]) {
+        let len = HDRLEN + payload.len();
+        if !self.current.is_empty() && self.current.len() + align(len) > DATAGRAM_SIZE {
+            self.datagrams.push(mem::take(&mut self.current));
+        }
+        let hdr = nlmsghdr {
+            nlmsg_len: len as u32,
+            nlmsg_type: msg_type,
+            nlmsg_flags: flags,
+            nlmsg_seq: seq,
+            nlmsg_pid: self.portid,
+        };
+        self.current.extend_from_slice(bytes_of(&hdr));
+        self.current.extend_from_slice(payload);
+        self.current.resize(align(self.current.len()), 0);
+    }
+
+    /// Ends a dump.
+    fn done(&mut self, seq: u32) {
+        self.push(NLMSG_DONE, NLM_F_MULTI, seq, &0i32.to_ne_bytes());
+    }
+
+    /// Replies with `struct nlmsgerr`: an error, or an acknowledgement when `errno` is 0. Only
+    /// the request's header is echoed.
+    fn error(&mut self, request: &nlmsghdr, errno: i32) {
+        let mut payload = (-errno).to_ne_bytes().to_vec();
+        payload.extend_from_slice(bytes_of(request));
+        self.push(NLMSG_ERROR, NLM_F_CAPPED, request.nlmsg_seq, &payload);
+    }
+
+    /// Ends the replies to one request, which the kernel never shares a datagram with the next.
+    fn flush(&mut self) {
+        if !self.current.is_empty() {
+            self.datagrams.push(mem::take(&mut self.current));
+        }
+    }
+}
+
+/// Answers one request.
+fn answer(view: &View, hdr: &nlmsghdr, payload: &[u8], out: &mut Replies) {
+    // Control messages (NOOP, DONE, ...) and replies are ignored, as by the kernel
+    if hdr.nlmsg_flags & NLM_F_REQUEST == 0 || hdr.nlmsg_type < RTM_BASE {
+        return;
+    }
+    let seq = hdr.nlmsg_seq;
+    let dump = hdr.nlmsg_flags & NLM_F_DUMP == NLM_F_DUMP;
+
+    let result = match hdr.nlmsg_type {
+        RTM_GETLINK if dump => {
+            for link in &view.links {
+                out.push(RTM_NEWLINK, NLM_F_MULTI, seq, &link.encode());
+            }
+            out.done(seq);
+            Ok(())
+        }
+        RTM_GETLINK => match view.find_link(payload) {
+            Some(link) => {
+                out.push(RTM_NEWLINK, 0, seq, &link.encode());
+                Ok(())
+            }
+            None => Err(Errno::ENODEV),
+        },
+        RTM_GETADDR if dump => {
+            // The request starts with a struct rtgenmsg or ifaddrmsg; both begin with the family
+            let family = payload.first().map_or(AF_UNSPEC, |&f| f as i32);
+            for addr in &view.addrs {
+                if family == AF_UNSPEC || family == addr.family() {
+                    out.push(RTM_NEWADDR, NLM_F_MULTI, seq, &addr.encode());
+                }
+            }
+            out.done(seq);
+            Ok(())
+        }
+        // Other queries (routes, neighbours, rules, ...): RTM_GET* types are 4n + 2
+        t if t % 4 == 2 => Err(Errno::EOPNOTSUPP),
+        // Changes need CAP_NET_ADMIN, which cages don't have
+        _ => Err(Errno::EPERM),
+    };
+
+    match result {
+        Err(e) => out.error(hdr, e as i32),
+        Ok(()) if !dump && hdr.nlmsg_flags & NLM_F_ACK != 0 => out.error(hdr, 0),
+        Ok(()) => {}
+    }
+    out.flush();
+}
+
+/// Iterates over the route attributes in `buf` as (type, payload).
+fn attrs(buf: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut off = 0;
+    std::iter::from_fn(move || {
+        if off + 4 > buf.len() {
+            return None;
+        }
+        let len = u16::from_ne_bytes([buf[off], buf[off + 1]]) as usize;
+        let kind = u16::from_ne_bytes([buf[off + 2], buf[off + 3]]);
+        if len < 4 || off + len > buf.len() {
+            return None;
+        }
+        let data = &buf[off + 4..off + len];
+        off += align(len);
+        Some((kind, data))
+    })
+}
+
+/// Appends a route attribute (`struct rtattr` and payload, padded to 4 bytes).
+fn put_attr(buf: &mut Vec<u8>, kind: u16, data: &[u8]) {
+    buf.extend_from_slice(&((4 + data.len()) as u16).to_ne_bytes());
+    buf.extend_from_slice(&kind.to_ne_bytes());
+    buf.extend_from_slice(data);
+    buf.resize(align(buf.len()), 0);
+}
+
+fn put_str(buf: &mut Vec<u8>, kind: u16, s: &str) {
+    let mut data = s.as_bytes().to_vec();
+    data.push(0);
+    put_attr(buf, kind, &data);
+}
+
+fn ip_bytes(ip: IpAddr) -> Vec<u8> {
+    match ip {
+        IpAddr::V4(ip) => ip.octets().to_vec(),
+        IpAddr::V6(ip) => ip.octets().to_vec(),
+    }
+}
+
+/// An interface as reported by `RTM_NEWLINK`.
+struct Link {
+    index: i32,
+    name: String,
+    flags: u32,
+    // ARPHRD_* device type
+    kind: u16,
+    mtu: Option<u32>,
+    addr: Vec<u8>,
+    broadcast: Vec<u8>,
+}
+
+impl Link {
+    fn encode(&self) -> Vec<u8> {
+        let info = IfInfoMsg {
+            ifi_family: AF_UNSPEC as u8,
+            ifi_type: self.kind,
+            ifi_index: self.index,
+            ifi_flags: self.flags,
+            ..Default::default()
+        };
+        let mut buf = bytes_of(&info).to_vec();
+        put_str(&mut buf, IFLA_IFNAME, &self.name);
+        if let Some(mtu) = self.mtu {
+            put_attr(&mut buf, IFLA_MTU, &mtu.to_ne_bytes());
+        }
+        if !self.addr.is_empty() {
+            put_attr(&mut buf, IFLA_ADDRESS, &self.addr);
+        }
+        if !self.broadcast.is_empty() {
+            put_attr(&mut buf, IFLA_BROADCAST, &self.broadcast);
+        }
+        let operstate = if self.flags & IFF_LOOPBACK != 0 {
+            IF_OPER_UNKNOWN
+        } else if self.flags & IFF_RUNNING != 0 {
+            IF_OPER_UP
+        } else {
+            IF_OPER_DOWN
+        };
+        put_attr(&mut buf, IFLA_OPERSTATE, &[operstate]);
+        buf
+    }
+}
+
+/// An address as reported by `RTM_NEWADDR`.
+struct Address {
+    index: u32,
+    prefix: u8,
+    local: IpAddr,
+    // Other end of a point-to-point link
+    peer: Option<IpAddr>,
+    broadcast: Option<Ipv4Addr>,
+    label: String,
+}
+
+impl Address {
+    fn family(&self) -> i32 {
+        match self.local {
+            IpAddr::V4(_) => AF_INET,
+            IpAddr::V6(_) => AF_INET6,
+        }
+    }
+
+    fn scope(&self) -> u8 {
+        match self.local {
+            IpAddr::V4(ip) if ip.is_loopback() => RT_SCOPE_HOST,
+            IpAddr::V6(ip) if ip.is_loopback() => RT_SCOPE_HOST,
+            IpAddr::V6(ip) if ip.segments()[0] & 0xffc0 == 0xfe80 => RT_SCOPE_LINK,
+            _ => RT_SCOPE_UNIVERSE,
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let msg = IfAddrMsg {
+            ifa_family: self.family() as u8,
+            ifa_prefixlen: self.prefix,
+            ifa_flags: IFA_F_PERMANENT,
+            ifa_scope: self.scope(),
+            ifa_index: self.index,
+        };
+        let mut buf = bytes_of(&msg).to_vec();
+        let local = ip_bytes(self.local);
+        put_attr(
+            &mut buf,
+            IFA_ADDRESS,
+            &ip_bytes(self.peer.unwrap_or(self.local)),
+        );
+        if self.local.is_ipv4() || self.peer.is_some() {
+            put_attr(&mut buf, IFA_LOCAL, &local);
+        }
+        if let Some(broadcast) = self.broadcast {
+            put_attr(&mut buf, IFA_BROADCAST, &broadcast.octets());
+        }
+        if self.local.is_ipv4() {
+            put_str(&mut buf, IFA_LABEL, &self.label);
+        }
+        buf
+    }
+}
+
+/// The interfaces and addresses a cage sees.
+struct View {
+    links: Vec<Link>,
+    addrs: Vec<Address>,
+}
+
+impl View {
+    fn of(cageid: u64) -> View {
+        if netns::is_private(cageid) {
+            View::loopback()
+        } else {
+            View::host()
+        }
+    }
+
+    /// A private namespace: only `lo`.
+    fn loopback() -> View {
+        let lo = |local: IpAddr, prefix: u8| Address {
+            index: 1,
+            prefix,
+            local,
+            peer: None,
+            broadcast: None,
+            label: "lo".to_string(),
+        };
+        View {
+            links: vec![Link {
+                index: 1,
+                name: "lo".to_string(),
+                flags: IFF_UP | IFF_LOOPBACK | IFF_RUNNING | IFF_LOWER_UP,
+                kind: ARPHRD_LOOPBACK,
+                mtu: Some(65536),
+                addr: vec![0; 6],
+                broadcast: vec![0; 6],
+            }],
+            addrs: vec![
+                lo(IpAddr::V4(Ipv4Addr::LOCALHOST), 8),
+                lo(IpAddr::V6(Ipv6Addr::LOCALHOST), 128),
+            ],
+        }
+    }
+
+    /// The host namespace: the host's interfaces, as `getifaddrs` reports them.
+    fn host() -> View {
+        let mut view = View {
+            links: Vec::new(),
+            addrs: Vec::new(),
+        };
+        let mut list: *mut libc::ifaddrs = ptr::null_mut();
+        if unsafe { libc::getifaddrs(&mut list) } < 0 {
+            return view;
+        }
+
+        let mut cur = list;
+        while !cur.is_null() {
+            let ifa = unsafe { &*cur };
+            cur = ifa.ifa_next;
+            if ifa.ifa_addr.is_null() {
+                continue;
+            }
+            let name = unsafe { CStr::from_ptr(ifa.ifa_name) }
+                .to_string_lossy()
+                .into_owned();
+            match unsafe { (*ifa.ifa_addr).sa_family } as i32 {
+                AF_PACKET => {
+                    let ll = unsafe { &*(ifa.ifa_addr as *const sockaddr_ll) };
+                    let halen = (ll.sll_halen as usize).min(ll.sll_addr.len());
+                    let broadcast = if ifa.ifa_ifu.is_null() {
+                        Vec::new()
+                    } else {
+                        let ll = unsafe { &*(ifa.ifa_ifu as *const sockaddr_ll) };
+                        ll.sll_addr[..halen].to_vec()
+                    };
+                    let mtu = std::fs::read_to_string(format!("/sys/class/net/{}/mtu", name))
+                        .ok()
+                        .and_then(|mtu| mtu.trim().parse().ok());
+                    view.links.push(Link {
+                        index: ll.sll_ifindex,
+                        name,
+                        flags: ifa.ifa_flags,
+                        kind: ll.sll_hatype,
+                        mtu,
+                        addr: ll.sll_addr[..halen].to_vec(),
+                        broadcast,
+                    });
+                }
+                AF_INET | AF_INET6 => {
+                    let Some(local) = (unsafe { ip_of(ifa.ifa_addr) }) else {
+                        continue;
+                    };
+                    let prefix = unsafe { ip_of(ifa.ifa_netmask) }.map_or(0, |mask| {
+                        ip_bytes(mask).iter().map(|b| b.count_ones()).sum()
+                    }) as u8;
+                    let other = unsafe { ip_of(ifa.ifa_ifu) };
+                    let peer = other.filter(|_| ifa.ifa_flags & IFF_POINTOPOINT != 0);
+                    let broadcast = match other {
+                        Some(IpAddr::V4(ip)) if ifa.ifa_flags & IFF_BROADCAST != 0 => Some(ip),
+                        _ => None,
+                    };
+                    // An alias label like "eth0:1" names an address of eth0
+                    let base = name.split(':').next().unwrap_or(&name);
+                    let index = match std::ffi::CString::new(base) {
+                        Ok(base) => unsafe { libc::if_nametoindex(base.as_ptr()) },
+                        Err(_) => 0,
+                    };
+                    view.addrs.push(Address {
+                        index,
+                        prefix,
+                        local,
+                        peer,
+                        broadcast,
+                        label: name,
+                    });
+                }
+                _ => {}
+            }
+        }
+        unsafe { libc::freeifaddrs(list) };
+
+        view.links.sort_by_key(|link| link.index);
+        view
+    }
+
+    /// Finds the link a non-dump `RTM_GETLINK` asks for: by index, or by `IFLA_IFNAME`.
+    fn find_link(&self, payload: &[u8]) -> Option<&Link> {
+        let len = mem::size_of::<IfInfoMsg>();
+        if payload.len() < len {
+            return None;
+        }
+        let info = unsafe { ptr::read_unaligned(payload.as_ptr() as *const IfInfoMsg) };
+        if info.ifi_index != 0 {
+            return self.links.iter().find(|link| link.index == info.ifi_index);
+        }
+        let (_, name) = attrs(&payload[len..]).find(|&(kind, _)| kind == IFLA_IFNAME)?;
+        let name = name.split(|&b| b == 0).next().unwrap_or(name);
+        self.links.iter().find(|link| link.name.as_bytes() == name)
+    }
+}
+
+/// Reads the address in a `sockaddr_in`/`sockaddr_in6`.
+unsafe fn ip_of(addr: *const sockaddr) -> Option<IpAddr> {
+    if addr.is_null() {
+        return None;
+    }
+    match (*addr).sa_family as i32 {
+        AF_INET => {
+            let sin = &*(addr as *const libc::sockaddr_in);
+            Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(
+                sin.sin_addr.s_addr,
+            ))))
+        }
+        AF_INET6 => {
+            let sin6 = &*(addr as *const libc::sockaddr_in6);
+            Some(IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr)))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,111 @@
+//! CPUs and scheduling as cages see them
+//!
+//! Cages see `--cpus` virtual CPUs, numbered from 0, or as many as the host lets lind run on if
+//! that option isn't given. Each cage has an affinity mask over these CPUs: the first cage starts
+//! with all of them, children inherit their parent's mask on fork, and `sched_setaffinity`
+//! narrows it. `sched_getaffinity` (and so `nproc` and `sysconf(_SC_NPROCESSORS_ONLN)`) reports
+//! the mask, and `getcpu` names a CPU in it. Each cage also has a nice value, read and set with
+//! `getpriority`/`setpriority`.
+//!
+//! Both are bookkeeping only: the host scheduler still runs cage threads on any host CPU, at the
+//! lind process's own priority. Linux keeps both per thread; lind keeps them per cage, and
+//! applies a call naming a thread of the cage to the whole cage.
+use cage::{get_cage, Cage, CAGE_MAP};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, OnceLock};
+
+/// Width of a word of the cage's `cpu_set_t` (its `unsigned long`).
+pub const GUEST_MASK_WORD: usize = 4;
+
+/// Scheduling settings chosen when lind-boot starts (see `configure`).
+#[derive(Debug, Clone, Default)]
+pub struct SchedConfig {
+    /// Number of virtual CPUs, instead of the host CPUs lind may run on.
+    pub cpus: Option<usize>,
+}
+
+static CONFIG: OnceLock<SchedConfig> = OnceLock::new();
+
+/// Sets the scheduling configuration. Must be called before `rawposix_start`; later calls have
+/// no effect.
+pub fn configure(config: SchedConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// Number of virtual CPUs cages see.
+pub fn ncpus() -> usize {
+    static NCPUS: OnceLock<usize> = OnceLock::new();
+    *NCPUS.get_or_init(|| {
+        let configured = CONFIG.get().and_then(|config| config.cpus);
+        configured
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+            .max(1)
+    })
+}
+
+/// Affinity mask of the first cage: every virtual CPU.
+pub fn initial_affinity() -> Vec<u64> {
+    let n = ncpus();
+    let mut mask = vec![u64::MAX; n.div_ceil(64)];
+    if !n.is_multiple_of(64) {
+        *mask.last_mut().unwrap() = (1u64 << (n % 64)) - 1;
+    }
+    mask
+}
+
+/// Size in bytes of the cage's CPU masks: what `sched_getaffinity` copies out, and the least
+/// buffer it accepts.
+pub fn mask_size() -> usize {
+    ncpus().div_ceil(GUEST_MASK_WORD * 8) * GUEST_MASK_WORD
+}
+
+/// Reads a mask from the cage's `cpu_set_t` bytes, dropping CPUs that don't exist.
+pub fn mask_from_bytes(bytes: &[u8]) -> Vec<u64> {
+    let mut mask = vec![0u64; ncpus().div_ceil(64)];
+    for cpu in 0..ncpus().min(bytes.len() * 8) {
+        if bytes[cpu / 8] & (1 << (cpu % 8)) != 0 {
+            mask[cpu / 64] |= 1 << (cpu % 64);
+        }
+    }
+    mask
+}
+
+/// Writes a mask as the cage's `cpu_set_t` bytes, `mask_size()` of them.
+pub fn mask_to_bytes(mask: &[u64]) -> Vec<u8> {
+    let mut bytes = vec![0u8; mask_size()];
+    for cpu in cpus_in(mask) {
+        bytes[cpu / 8] |= 1 << (cpu % 8);
+    }
+    bytes
+}
+
+/// The CPUs in a mask, in increasing order.
+pub fn cpus_in(mask: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    (0..ncpus()).filter(move |&cpu| mask[cpu / 64] & (1 << (cpu % 64)) != 0)
+}
+
+/// The CPU `getcpu` reports for a cage with this mask: the host CPU the caller runs on, folded
+/// onto the CPUs of the mask.
+pub fn current_cpu(mask: &[u64]) -> usize {
+    let allowed: Vec<usize> = cpus_in(mask).collect();
+    let host_cpu = unsafe { libc::sched_getcpu() }.max(0) as usize;
+    allowed
+        .get(host_cpu % allowed.len().max(1))
+        .copied()
+        .unwrap_or(0)
+}
+
+/// The cage a `pid` argument names: the caller for 0, otherwise the live cage with that id.
+pub fn target_cage(cageid: u64, pid: i32) -> Option<Arc<Cage>> {
+    let target = if pid == 0 { cageid } else { pid as u64 };
+    get_cage(target).filter(|cage| !cage.is_dead.load(Ordering::Acquire))
+}
+
+/// Every live cage, for `PRIO_USER`: all cages run as the same user.
+pub fn live_cages() -> Vec<Arc<Cage>> {
+    CAGE_MAP
+        .iter()
+        .filter_map(|slot| slot.load_full())
+        .filter(|cage| !cage.is_dead.load(Ordering::Acquire))
+        .collect()
+}
@@ -0,0 +1,233 @@
+//! POSIX shared memory objects
+//!
+//! glibc's `shm_open` and `shm_unlink` open and unlink `/dev/shm/NAME`. Lind keeps these objects
+//! itself instead of in lindfs: each one is a host memfd, in one namespace shared by every cage,
+//! as `/dev/shm` is on a Linux host. `open` and `openat` on a path under `/dev/shm` come here,
+//! and so do `unlink` and `link`; other calls on these paths (`stat`, listing `/dev/shm`...) see
+//! lindfs. An object lives while it has a name, an open descriptor or a mapping.
+//!
+//! glibc's `sem_open` keeps named semaphores here too, as `/dev/shm/sem.NAME`: it creates the
+//! object under a temporary name, maps it, and then `link`s it to its real name.
+//!
+//! Descriptors are ordinary kernel fds. Each open reopens the object's memfd through
+//! `/proc/self/fd`, so it gets its own file offset and access mode. Where lindfs has no `/proc`,
+//! the memfd is duplicated instead, and the descriptor shares the memfd's offset and read-write
+//! access mode. A `MAP_SHARED` mapping of any of them maps the same host pages, and `fork_vmmap`
+//! remaps it shared into a forked child, so cages see each other's writes as processes do on
+//! Linux. The same holds for the files `memfd_create` makes.
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use sysdefs::constants::err_const::{get_errno, Errno};
+use sysdefs::constants::fs_const::{
+    O_ACCMODE, O_CLOEXEC, O_CREAT, O_EXCL, O_NONBLOCK, O_RDONLY, O_RDWR, O_TRUNC, O_WRONLY,
+    SHM_NAME_MAX,
+};
+
+/// Directory of the namespace, with its trailing slash
+const SHM_DIR: &[u8] = b"/dev/shm/";
+
+lazy_static! {
+    // The memfd of every object, by name.
+    static ref NAMES: Mutex<HashMap<Vec<u8>, OwnedFd>> = Mutex::new(HashMap::new());
+}
+
+fn host_errno() -> Errno {
+    Errno::from_discriminant(get_errno()).unwrap_or(Errno::EIO)
+}
+
+fn effective_ids() -> (u32, u32) {
+    unsafe { (libc::geteuid(), libc::getegid()) }
+}
+
+/// Owner and mode of the object behind `fd`, as `fchown` and `fchmod` on any of its
+/// descriptors left them.
+fn owner_and_mode(fd: i32) -> Result<(u32, u32, u32), Errno> {
+    let mut st: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstat(fd, &mut st) } < 0 {
+        return Err(host_errno());
+    }
+    Ok((st.st_uid, st.st_gid, st.st_mode & 0o777))
+}
+
+/// Whether the caller may open the object behind `fd` with access mode `accmode`.
+fn permits(fd: i32, accmode: i32) -> Result<bool, Errno> {
+    let (euid, egid) = effective_ids();
+    if euid == 0 {
+        return Ok(true);
+    }
+    let (uid, gid, mode) = owner_and_mode(fd)?;
+    let granted = if euid == uid {
+        mode >> 6
+    } else if egid == gid {
+        mode >> 3
+    } else {
+        mode
+    };
+    let wanted = match accmode {
+        O_RDONLY => 0o4,
+        O_WRONLY => 0o2,
+        _ => 0o6,
+    };
+    Ok(wanted & !granted & 0o7 == 0)
+}
+
+/// Checks an object name, the part of the path after `/dev/shm/`.
+fn check_name(name: &[u8]) -> Result<(), Errno> {
+    if name.len() > SHM_NAME_MAX {
+        return Err(Errno::ENAMETOOLONG);
+    }
+    // The namespace has no subdirectories
+    if name.contains(&b'/') {
+        return Err(Errno::ENOENT);
+    }
+    Ok(())
+}
+
+/// A new memfd for the object `name`, with permission bits `mode`.
+fn create_object(name: &[u8], mode: u32) -> Result<OwnedFd, Errno> {
+    let cname = CString::new(name).map_err(|_| Errno::EINVAL)?;
+    // Files in /dev/shm cannot be sealed, so neither can the memfd
+    let fd = unsafe { libc::memfd_create(cname.as_ptr(), libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        return Err(host_errno());
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+    if unsafe { libc::fchmod(fd.as_raw_fd(), mode & 0o777) } < 0 {
+        return Err(host_errno());
+    }
+    Ok(fd)
+}
+
+/// A new descriptor of the object behind `fd`, with the access mode and `O_NONBLOCK` of `oflag`.
+fn reopen(fd: i32, oflag: i32) -> Result<i32, Errno> {
+    let path = CString::new(format!("/proc/self/fd/{}", fd)).unwrap();
+    let flags = (oflag & (O_ACCMODE | O_NONBLOCK)) | O_CLOEXEC;
+    let newfd = unsafe { libc::open(path.as_ptr(), flags) };
+    if newfd >= 0 {
+        return Ok(newfd);
+    }
+    if get_errno() != libc::ENOENT {
+        return Err(host_errno());
+    }
+    // No /proc in lindfs
+    let newfd = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+    if newfd < 0 {
+        return Err(host_errno());
+    }
+    Ok(newfd)
+}
+
+fn open_object(name: &[u8], oflag: i32, mode: u32) -> Result<i32, Errno> {
+    check_name(name)?;
+    let accmode = oflag & O_ACCMODE;
+    if accmode != O_RDONLY && accmode != O_WRONLY && accmode != O_RDWR {
+        return Err(Errno::EINVAL);
+    }
+    if oflag & libc::O_DIRECTORY != 0 {
+        return Err(Errno::ENOTDIR);
+    }
+
+    let mut names = NAMES.lock();
+    let object = match names.entry(name.to_vec()) {
+        Entry::Occupied(_) if oflag & (O_CREAT | O_EXCL) == O_CREAT | O_EXCL => {
+            return Err(Errno::EEXIST)
+        }
+        Entry::Occupied(entry) => {
+            let object = entry.into_mut();
+            if !permits(object.as_raw_fd(), accmode)? {
+                return Err(Errno::EACCES);
+            }
+            if oflag & O_TRUNC != 0
+                && accmode != O_RDONLY
+                && unsafe { libc::ftruncate(object.as_raw_fd(), 0) } < 0
+            {
+                return Err(host_errno());
+            }
+            object
+        }
+        Entry::Vacant(_) if oflag & O_CREAT == 0 => return Err(Errno::ENOENT),
+        // Checking permissions on an object we just created is pointless, as Linux skips it
+        Entry::Vacant(entry) => entry.insert(create_object(name, mode)?),
+    };
+    reopen(object.as_raw_fd(), oflag)
+}
+
+/// Opens the shared memory object `path` names, if it is under `/dev/shm`, creating it with
+/// `mode` (already masked by the cage's umask) if `oflag` has `O_CREAT`. Returns the new kernel
+/// fd, or `None` for a path outside the namespace.
+///
+/// # Errors
+/// * `Errno::ENOENT` - If the object does not exist and `O_CREAT` was not given, or the name
+///   contains a slash
+/// * `Errno::EEXIST` - If the object exists and `O_CREAT | O_EXCL` was given
+/// * `Errno::EACCES` - If the object's mode denies the access asked for
+/// * `Errno::ENAMETOOLONG` - If the name is too long
+/// * `Errno::EINVAL` - If the access mode is invalid
+/// * `Errno::ENOTDIR` - If `oflag` has `O_DIRECTORY`
+pub(crate) fn open(path: &CStr, oflag: i32, mode: u32) -> Option<Result<i32, Errno>> {
+    let name = path.to_bytes().strip_prefix(SHM_DIR)?;
+    Some(open_object(name, oflag, mode))
+}
+
+fn unlink_object(name: &[u8]) -> Result<(), Errno> {
+    check_name(name)?;
+    let mut names = NAMES.lock();
+    let object = names.get(name).ok_or(Errno::ENOENT)?;
+    // /dev/shm is a sticky directory
+    let (euid, _) = effective_ids();
+    if euid != 0 && euid != owner_and_mode(object.as_raw_fd())?.0 {
+        return Err(Errno::EPERM);
+    }
+    // Closing the memfd frees the object once no descriptor or mapping is left
+    names.remove(name);
+    Ok(())
+}
+
+/// Removes the shared memory object `path` names from the namespace, if it is under
+/// `/dev/shm`. Descriptors and mappings of the object keep working. Returns `None` for a path
+/// outside the namespace.
+///
+/// # Errors
+/// * `Errno::ENOENT` - If there is no such object
+/// * `Errno::EPERM` - If the caller neither owns the object nor is root
+/// * `Errno::ENAMETOOLONG` - If the name is too long
+pub(crate) fn unlink(path: &CStr) -> Option<Result<(), Errno>> {
+    let name = path.to_bytes().strip_prefix(SHM_DIR)?;
+    Some(unlink_object(name))
+}
+
+fn link_object(old: &[u8], new: &[u8]) -> Result<(), Errno> {
+    check_name(old)?;
+    check_name(new)?;
+    let mut names = NAMES.lock();
+    let object = names.get(old).ok_or(Errno::ENOENT)?;
+    if names.contains_key(new) {
+        return Err(Errno::EEXIST);
+    }
+    let fd = object.try_clone().map_err(|_| host_errno())?;
+    names.insert(new.to_vec(), fd);
+    Ok(())
+}
+
+/// Gives the shared memory object `old` names the additional name `new`, if both are under
+/// `/dev/shm`. Returns `None` if neither is.
+///
+/// # Errors
+/// * `Errno::EXDEV` - If only one of the paths is under `/dev/shm`
+/// * `Errno::ENOENT` - If there is no object named `old`
+/// * `Errno::EEXIST` - If an object named `new` exists
+/// * `Errno::ENAMETOOLONG` - If a name is too long
+pub(crate) fn link(old: &CStr, new: &CStr) -> Option<Result<(), Errno>> {
+    let old = old.to_bytes().strip_prefix(SHM_DIR);
+    let new = new.to_bytes().strip_prefix(SHM_DIR);
+    match (old, new) {
+        (Some(old), Some(new)) => Some(link_object(old, new)),
+        (None, None) => None,
+        // /dev/shm is a file system of its own
+        _ => Some(Err(Errno::EXDEV)),
+    }
+}
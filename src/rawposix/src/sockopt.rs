@@ -0,0 +1,274 @@
+//! Socket options of host sockets
+//!
+//! `getsockopt`/`setsockopt` only accept the options listed in [`OPTIONS`]; anything else fails
+//! with ENOPROTOOPT (or EOPNOTSUPP for an unknown level) instead of reaching the host kernel.
+//! Each entry records whether the option can be read, written or both, and how its value is laid
+//! out in the cage:
+//!
+//! - Most values are `int`s, or structs whose wasm32 layout is the host's (`struct linger`,
+//!   `ip_mreq`/`ip_mreqn`, `ip_mreq_source`, `ipv6_mreq`, the time64 `struct timeval` of
+//!   `SO_RCVTIMEO_NEW`, `struct tcp_info`) and are handed to the kernel as they are. The kernel
+//!   checks their lengths.
+//! - `SO_RCVTIMEO_OLD`/`SO_SNDTIMEO_OLD` carry the cage's 32-bit `struct timeval`, which is
+//!   converted to and from the host's.
+//! - `SO_PEERCRED` reports the cage at the other end of an AF_UNIX socket as the pid, with the
+//!   uid/gid cages see from `getuid`/`getgid`, the same way `recvmsg` rewrites `SCM_CREDENTIALS`.
+//!   A peer outside lind is reported as pid 0.
+
+use crate::net_calls::unix_peer_cage;
+use libc::{c_void, socklen_t, timeval, ucred};
+use std::{mem, ptr};
+use sysdefs::constants::err_const::{get_errno, Errno};
+use sysdefs::constants::net_const::*;
+use sysdefs::data::net_struct::GuestOldTimeval;
+
+// Layouts that are passed through unchanged, at their wasm32 sizes
+const _: () = assert!(mem::size_of::<libc::linger>() == 8);
+const _: () = assert!(mem::size_of::<libc::ip_mreqn>() == 12);
+const _: () = assert!(mem::size_of::<libc::ipv6_mreq>() == 20);
+const _: () = assert!(mem::size_of::<timeval>() == 16);
+
+/// How an option's value is laid out in the cage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// An `int`, or a struct or byte string laid out as on the host.
+    Native,
+    /// The cage's 32-bit `struct timeval`.
+    OldTimeval,
+    /// `struct ucred` describing the peer cage.
+    PeerCred,
+}
+
+/// Whether an option can be read, written or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Get,
+    Set,
+    Both,
+}
+
+use Access::{Both, Get, Set};
+use Layout::{Native, OldTimeval, PeerCred};
+
+/// The supported options: (level, optname, layout, access).
+static OPTIONS: &[(i32, i32, Layout, Access)] = &[
+    (SOL_SOCKET, SO_DEBUG, Native, Both),
+    (SOL_SOCKET, SO_REUSEADDR, Native, Both),
+    (SOL_SOCKET, SO_TYPE, Native, Get),
+    (SOL_SOCKET, SO_ERROR, Native, Get),
+    (SOL_SOCKET, SO_DONTROUTE, Native, Both),
+    (SOL_SOCKET, SO_BROADCAST, Native, Both),
+    (SOL_SOCKET, SO_SNDBUF, Native, Both),
+    (SOL_SOCKET, SO_RCVBUF, Native, Both),
+    (SOL_SOCKET, SO_SNDBUFFORCE, Native, Set),
+    (SOL_SOCKET, SO_RCVBUFFORCE, Native, Set),
+    (SOL_SOCKET, SO_KEEPALIVE, Native, Both),
+    (SOL_SOCKET, SO_OOBINLINE, Native, Both),
+    (SOL_SOCKET, SO_NO_CHECK, Native, Both),
+    (SOL_SOCKET, SO_PRIORITY, Native, Both),
+    (SOL_SOCKET, SO_LINGER, Native, Both),
+    (SOL_SOCKET, SO_REUSEPORT, Native, Both),
+    (SOL_SOCKET, SO_PASSCRED, Native, Both),
+    (SOL_SOCKET, SO_PEERCRED, PeerCred, Get),
+    (SOL_SOCKET, SO_RCVLOWAT, Native, Both),
+    (SOL_SOCKET, SO_SNDLOWAT, Native, Both),
+    (SOL_SOCKET, SO_RCVTIMEO_OLD, OldTimeval, Both),
+    (SOL_SOCKET, SO_SNDTIMEO_OLD, OldTimeval, Both),
+    (SOL_SOCKET, SO_BINDTODEVICE, Native, Both),
+    (SOL_SOCKET, SO_ACCEPTCONN, Native, Get),
+    (SOL_SOCKET, SO_PROTOCOL, Native, Get),
+    (SOL_SOCKET, SO_DOMAIN, Native, Get),
+    (SOL_SOCKET, SO_RCVTIMEO_NEW, Native, Both),
+    (SOL_SOCKET, SO_SNDTIMEO_NEW, Native, Both),
+    (SOL_TCP, TCP_NODELAY, Native, Both),
+    (SOL_TCP, TCP_MAXSEG, Native, Both),
+    (SOL_TCP, TCP_CORK, Native, Both),
+    (SOL_TCP, TCP_KEEPIDLE, Native, Both),
+    (SOL_TCP, TCP_KEEPINTVL, Native, Both),
+    (SOL_TCP, TCP_KEEPCNT, Native, Both),
+    (SOL_TCP, TCP_SYNCNT, Native, Both),
+    (SOL_TCP, TCP_LINGER2, Native, Both),
+    (SOL_TCP, TCP_DEFER_ACCEPT, Native, Both),
+    (SOL_TCP, TCP_WINDOW_CLAMP, Native, Both),
+    (SOL_TCP, TCP_INFO, Native, Get),
+    (SOL_TCP, TCP_QUICKACK, Native, Both),
+    (SOL_TCP, TCP_CONGESTION, Native, Both),
+    (SOL_TCP, TCP_USER_TIMEOUT, Native, Both),
+    (SOL_TCP, TCP_FASTOPEN, Native, Both),
+    (SOL_TCP, TCP_NOTSENT_LOWAT, Native, Both),
+    (IPPROTO_IP, IP_TOS, Native, Both),
+    (IPPROTO_IP, IP_TTL, Native, Both),
+    (IPPROTO_IP, IP_HDRINCL, Native, Both),
+    (IPPROTO_IP, IP_OPTIONS, Native, Both),
+    (IPPROTO_IP, IP_PKTINFO, Native, Both),
+    (IPPROTO_IP, IP_MTU_DISCOVER, Native, Both),
+    (IPPROTO_IP, IP_RECVERR, Native, Both),
+    (IPPROTO_IP, IP_RECVTTL, Native, Both),
+    (IPPROTO_IP, IP_RECVTOS, Native, Both),
+    (IPPROTO_IP, IP_MTU, Native, Get),
+    (IPPROTO_IP, IP_FREEBIND, Native, Both),
+    (IPPROTO_IP, IP_MULTICAST_IF, Native, Both),
+    (IPPROTO_IP, IP_MULTICAST_TTL, Native, Both),
+    (IPPROTO_IP, IP_MULTICAST_LOOP, Native, Both),
+    (IPPROTO_IP, IP_ADD_MEMBERSHIP, Native, Set),
+    (IPPROTO_IP, IP_DROP_MEMBERSHIP, Native, Set),
+    (IPPROTO_IP, IP_ADD_SOURCE_MEMBERSHIP, Native, Set),
+    (IPPROTO_IP, IP_DROP_SOURCE_MEMBERSHIP, Native, Set),
+    (IPPROTO_IPV6, IPV6_UNICAST_HOPS, Native, Both),
+    (IPPROTO_IPV6, IPV6_MULTICAST_IF, Native, Both),
+    (IPPROTO_IPV6, IPV6_MULTICAST_HOPS, Native, Both),
+    (IPPROTO_IPV6, IPV6_MULTICAST_LOOP, Native, Both),
+    (IPPROTO_IPV6, IPV6_ADD_MEMBERSHIP, Native, Set),
+    (IPPROTO_IPV6, IPV6_DROP_MEMBERSHIP, Native, Set),
+    (IPPROTO_IPV6, IPV6_MTU_DISCOVER, Native, Both),
+    (IPPROTO_IPV6, IPV6_MTU, Native, Get),
+    (IPPROTO_IPV6, IPV6_RECVERR, Native, Both),
+    (IPPROTO_IPV6, IPV6_V6ONLY, Native, Both),
+    (IPPROTO_IPV6, IPV6_RECVPKTINFO, Native, Both),
+    (IPPROTO_IPV6, IPV6_RECVTCLASS, Native, Both),
+    (IPPROTO_IPV6, IPV6_TCLASS, Native, Both),
+];
+
+/// Returns the layout of option `optname` at `level` if it supports `access`. Like the kernel,
+/// an unknown option fails with ENOPROTOOPT and an unknown level with EOPNOTSUPP.
+fn lookup(level: i32, optname: i32, access: Access) -> Result<Layout, Errno> {
+    if !OPTIONS.iter().any(|&(l, _, _, _)| l == level) {
+        return Err(Errno::EOPNOTSUPP);
+    }
+    OPTIONS
+        .iter()
+        .find(|&&(l, n, _, a)| l == level && n == optname && (a == Both || a == access))
+        .map(|&(_, _, layout, _)| layout)
+        .ok_or(Errno::ENOPROTOOPT)
+}
+
+fn host_errno() -> Errno {
+    Errno::from_discriminant(get_errno()).unwrap_or(Errno::EIO)
+}
+
+/// The host option behind `SO_RCVTIMEO_OLD`/`SO_SNDTIMEO_OLD`.
+fn host_timeo(optname: i32) -> i32 {
+    if optname == SO_RCVTIMEO_OLD {
+        libc::SO_RCVTIMEO
+    } else {
+        libc::SO_SNDTIMEO
+    }
+}
+
+/// Reads a fixed-size option from the host socket.
+fn host_get<T>(fd: i32, level: i32, optname: i32) -> Result<T, Errno> {
+    let mut value: T = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<T>() as socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            fd,
+            level,
+            optname,
+            &mut value as *mut T as *mut c_void,
+            &mut len,
+        )
+    };
+    if ret < 0 {
+        return Err(host_errno());
+    }
+    Ok(value)
+}
+
+/// Copies `value` to the cage, truncated to `*optlen` bytes, and stores the copied length.
+fn copy_out<T>(value: &T, optval: *mut c_void, optlen: *mut socklen_t) -> Result<(), Errno> {
+    let n = (unsafe { *optlen } as usize).min(mem::size_of::<T>());
+    if n > 0 && optval.is_null() {
+        return Err(Errno::EFAULT);
+    }
+    unsafe {
+        ptr::copy_nonoverlapping(value as *const T as *const u8, optval as *mut u8, n);
+        *optlen = n as socklen_t;
+    }
+    Ok(())
+}
+
+/// `getsockopt()` on the host socket `fd`, with `optval`/`optlen` in the cage's layout.
+pub(crate) fn get(
+    fd: i32,
+    level: i32,
+    optname: i32,
+    optval: *mut c_void,
+    optlen: *mut socklen_t,
+) -> Result<(), Errno> {
+    let layout = lookup(level, optname, Get)?;
+    if optlen.is_null() {
+        return Err(Errno::EFAULT);
+    }
+    if (unsafe { *optlen } as i32) < 0 {
+        return Err(Errno::EINVAL);
+    }
+
+    match layout {
+        Native => {
+            if unsafe { libc::getsockopt(fd, level, optname, optval, optlen) } < 0 {
+                return Err(host_errno());
+            }
+            Ok(())
+        }
+        OldTimeval => {
+            let tv: timeval = host_get(fd, SOL_SOCKET, host_timeo(optname))?;
+            let guest = GuestOldTimeval {
+                tv_sec: tv.tv_sec.min(i32::MAX as i64) as i32,
+                tv_usec: tv.tv_usec as i32,
+            };
+            copy_out(&guest, optval, optlen)
+        }
+        PeerCred => {
+            let mut cred: ucred = host_get(fd, SOL_SOCKET, SO_PEERCRED)?;
+            // Every cage runs in this process, so a peer inside lind carries our pid
+            cred.pid = if cred.pid == unsafe { libc::getpid() } {
+                unix_peer_cage(fd).unwrap_or(0) as i32
+            } else {
+                0
+            };
+            copy_out(&cred, optval, optlen)
+        }
+    }
+}
+
+/// `setsockopt()` on the host socket `fd`, with `optval` in the cage's layout.
+pub(crate) fn set(
+    fd: i32,
+    level: i32,
+    optname: i32,
+    optval: *const c_void,
+    optlen: socklen_t,
+) -> Result<(), Errno> {
+    let layout = lookup(level, optname, Set)?;
+
+    let ret = match layout {
+        Native => unsafe { libc::setsockopt(fd, level, optname, optval, optlen) },
+        OldTimeval => {
+            if (optlen as usize) < mem::size_of::<GuestOldTimeval>() {
+                return Err(Errno::EINVAL);
+            }
+            if optval.is_null() {
+                return Err(Errno::EFAULT);
+            }
+            let guest = unsafe { ptr::read_unaligned(optval as *const GuestOldTimeval) };
+            let tv = timeval {
+                tv_sec: guest.tv_sec as i64,
+                tv_usec: guest.tv_usec as i64,
+            };
+            unsafe {
+                libc::setsockopt(
+                    fd,
+                    SOL_SOCKET,
+                    host_timeo(optname),
+                    &tv as *const timeval as *const c_void,
+                    mem::size_of::<timeval>() as socklen_t,
+                )
+            }
+        }
+        PeerCred => return Err(Errno::ENOPROTOOPT),
+    };
+    if ret < 0 {
+        return Err(host_errno());
+    }
+    Ok(())
+}
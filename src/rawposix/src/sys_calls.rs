@@ -1,37 +1,47 @@
 //! System syscalls implementation
 //!
 //! This module contains all system calls that are being emulated/faked in Lind.
-use crate::fs_calls::kernel_close;
+use crate::fs_calls::{chdir_syscall, close_syscall, dup2_syscall, fchdir_syscall, open_syscall};
+use crate::netns;
+use crate::sched;
+use crate::uts;
+use cage::memory::quota;
 use cage::memory::vmmap::{VmmapOps, *};
-use cage::signal::signal::{convert_signal_mask, lind_send_signal, signal_check_trigger};
+use cage::signal::signal::{
+    convert_signal_mask, current_thread_id, lind_send_signal, signal_check_trigger,
+};
 use cage::timer::IntervalTimer;
 use cage::{add_cage, encode_wait_status, get_cage, remove_cage, Cage, ExitStatus, Zombie};
 use dashmap::DashMap;
 use fdtables;
 use libc::sched_yield;
 use parking_lot::{Mutex, RwLock};
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::path::PathBuf;
 use std::sync::atomic::Ordering::*;
-use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64};
 use std::sync::Arc;
 use std::time::Duration;
-use sysdefs::constants::err_const::{syscall_error, Errno, VERBOSE};
-use sysdefs::constants::fs_const::{STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO};
+use sysdefs::constants::err_const::{get_errno, handle_errno, syscall_error, Errno, VERBOSE};
+use sysdefs::constants::fs_const::{
+    MAP_SHARED, PAGESHIFT, PAGESIZE, PROT_NONE, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO,
+};
 use sysdefs::constants::lind_platform_const::{
-    MAX_CAGEID, MAX_LINEAR_MEMORY_SIZE, RAWPOSIX_CAGEID, UNUSED_ARG, UNUSED_ID, UNUSED_NAME,
-    WASMTIME_CAGEID,
+    MAX_CAGEID, RAWPOSIX_CAGEID, UNUSED_ARG, UNUSED_ID, UNUSED_NAME, WASMTIME_CAGEID,
 };
 use sysdefs::constants::sys_const::{
-    DEFAULT_GID, DEFAULT_UID, EXIT_SUCCESS, ITIMER_REAL, RLIMIT_AS, RLIMIT_CORE, RLIMIT_DATA,
-    RLIMIT_NOFILE, RLIMIT_NPROC, RLIMIT_RSS, RLIMIT_STACK, SIGCHLD, SIGKILL, SIGSTOP, SIG_BLOCK,
-    SIG_SETMASK, SIG_UNBLOCK, WNOHANG,
+    ITIMER_REAL, NICE_MAX, NICE_MIN, PATH_BSHELL, POSIX_SPAWN_SETSIGMASK, PRIO_PGRP, PRIO_PROCESS,
+    PRIO_USER, RLIMIT_AS, RLIMIT_CORE, RLIMIT_DATA, RLIMIT_NOFILE, RLIMIT_NPROC, RLIMIT_RSS,
+    RLIMIT_STACK, SCHED_OTHER, SIGKILL, SIGSTOP, SIG_BLOCK, SIG_SETMASK, SIG_UNBLOCK,
+    SPAWN_DO_CHDIR, SPAWN_DO_CLOSE, SPAWN_DO_CLOSEFROM, SPAWN_DO_DUP2, SPAWN_DO_FCHDIR,
+    SPAWN_DO_OPEN, SPAWN_DO_TCSETPGRP, SPAWN_XFLAGS_TRY_SHELL, WNOHANG,
 };
 use sysdefs::constants::syscall_const;
 use sysdefs::data::fs_struct::{ITimerVal, Rlimit, SigactionStruct};
 use sysdefs::lind_debug_panic;
 use sysdefs::{constants::sys_const, data::sys_struct};
 use typemap::datatype_conversion::*;
+use typemap::path_conversion::sc_convert_path_to_host;
 
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/clone.2.html
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/fork.2.html
@@ -115,15 +125,33 @@ pub extern "C" fn fork_syscall(
 
     // Fork path: create a new cage
     if isthread == 0 {
-        // Allocate a fresh cage ID for the child.
-        child_cageid = cage::alloc_cage_id().unwrap();
-
-        // Duplicate the parent's file descriptor table.
-        fdtables::copy_fdtable_for_cage(parent_cageid, child_cageid).unwrap();
+        // Refuse the fork before anything is set up if the child's copy of the parent's memory
+        // would exceed the memory budgets
+        let reservation = match quota::check_fork(&get_cage(parent_cageid).unwrap()) {
+            Ok(reservation) => reservation,
+            Err(e) => return syscall_error(e, "fork", "memory budget exceeded"),
+        };
 
         // Get the self cage
         let selfcage = get_cage(parent_cageid).unwrap();
 
+        // Allocate a fresh cage ID for the child.
+        child_cageid = match cage::alloc_cage_id() {
+            Some(id) => id,
+            None => {
+                quota::cancel_fork(&selfcage, reservation);
+                return syscall_error(Errno::EAGAIN, "fork", "no cage IDs left");
+            }
+        };
+
+        // Duplicate the parent's file descriptor table. The child's fds count against the global
+        // fd limit, so this fails with ENFILE once it is reached.
+        if fdtables::copy_fdtable_for_cage(parent_cageid, child_cageid).is_err() {
+            cage::release_cage_id(child_cageid);
+            quota::cancel_fork(&selfcage, reservation);
+            return syscall_error(Errno::ENFILE, "fork", "too many open files in the system");
+        }
+
         // Clone the parent's virtual memory map
         let parent_vmmap = selfcage.vmmap.read();
         let new_vmmap = parent_vmmap.clone();
@@ -132,18 +160,26 @@ pub extern "C" fn fork_syscall(
         let cageobj = Cage {
             cageid: child_cageid,
             cwd: RwLock::new(selfcage.cwd.read().clone()),
+            umask: AtomicU32::new(selfcage.umask.load(Relaxed)),
+            netns: AtomicU64::new(selfcage.netns.load(Relaxed)),
+            utsns: AtomicU64::new(selfcage.utsns.load(Relaxed)),
+            cpu_affinity: RwLock::new(selfcage.cpu_affinity.read().clone()),
+            nice: AtomicI32::new(selfcage.nice.load(Relaxed)),
             parent: parent_cageid,
             rev_shm: Mutex::new(Vec::new()),
             main_threadid: RwLock::new(0),
             interval_timer: IntervalTimer::new(child_cageid),
             epoch_handler: DashMap::new(),
             os_tid_map: DashMap::new(),
+            robust_list: DashMap::new(),
             pending_signals: RwLock::new(vec![]),
             signalhandler: selfcage.signalhandler.clone(),
             sigset: AtomicU64::new(0),
             zombies: RwLock::new(vec![]),
             child_num: AtomicU64::new(0),
             vmmap: RwLock::new(new_vmmap),
+            memory_snapshot: Mutex::new(None),
+            mem_quota: selfcage.mem_quota.for_child(parent_cageid, child_cageid),
             final_exit_status: RwLock::new(None),
             exit_group_initiated: AtomicBool::new(false),
             is_dead: AtomicBool::new(false),
@@ -156,6 +192,9 @@ pub extern "C" fn fork_syscall(
         // Register the new cage to global cage table
         add_cage(child_cageid, cageobj);
 
+        // Account the memory the child inherited, which check_fork reserved
+        quota::claim_fork(&get_cage(child_cageid).unwrap(), reservation);
+
         // Copy the 3i handler table from parent to child.
         //
         // This ensures that the child process inherits all syscall
@@ -220,7 +259,7 @@ pub extern "C" fn fork_syscall(
 /// (closing or inheriting them based on the `should_cloexec` flag in fdtable), resetting semaphores, and
 /// managing process attributes and threads (terminating unnecessary threads). This allows us to fully implement
 /// the exec functionality while aligning with POSIX standards. Cage fields remained in exec():
-/// cageid, cwd, parent, interval_timer
+/// cageid, cwd, umask, parent, interval_timer
 pub extern "C" fn exec_syscall(
     cageid: u64,
     path: u64,
@@ -282,11 +321,9 @@ pub extern "C" fn exec_syscall(
 
         selfcage.rev_shm.lock().clear();
 
-        // ensures that all old mappings and states are discarded, allowing the new cage to
-        // run in a clean virtual address space, while reusing the existing `Vmmap` container
-        // to avoid extra allocations.
-        let mut vmmap = selfcage.vmmap.write();
-        vmmap.clear(); //todo: this just clean the vmmap in the cage, still need some modify for wasmtime and call to kernal
+        // The old mappings are still in use while the old program unwinds. Once it has, wasmtime
+        // releases them and empties the vmmap (see `cage::memory::reset_vmmap_for_exec`), and
+        // the new program reuses the cage's linear memory.
 
         // perform signal related clean up
         // all the signal handler becomes default after exec
@@ -294,6 +331,8 @@ pub extern "C" fn exec_syscall(
         selfcage.signalhandler.clear();
         // the sigset will be reset after exec
         selfcage.sigset.store(0, Relaxed);
+        // robust lists point into the old memory, and the new program registers its own
+        selfcage.robust_list.clear();
         // Do NOT clear epoch_handler or main_threadid here.
         // If exec-ed module crashes, the thread is still running and needs its
         // epoch_handler entry for proper exit tracking.  On success,
@@ -305,6 +344,471 @@ pub extern "C" fn exec_syscall(
     ret
 }
 
+/// Reference to Linux: https://man7.org/linux/man-pages/man3/posix_spawn.3.html
+///
+/// Lind-specific fast path of `posix_spawn`. glibc's `posix_spawn` would otherwise fork the caller,
+/// which copies its whole linear memory through an Asyncify unwind and rewind, only for the child
+/// to exec right away. Here the child cage is created directly: like `fork_syscall` it inherits
+/// the fd table, cwd, umask, namespaces and scheduling state of the parent, but it starts with an
+/// empty vmmap and default signal dispositions, as after exec. The file actions are then applied
+/// to the child by running the corresponding syscalls on its behalf, its close-on-exec descriptors
+/// are closed, and Wasmtime is called via 3i to load the program and start it in the child cage on
+/// a new thread. The parent keeps running and its memory is never copied. If the program cannot be
+/// loaded (`ENOEXEC`) and the attributes ask for `SPAWN_XFLAGS_TRY_SHELL`, it is run as a script by
+/// `/bin/sh` instead, as older glibc versions do.
+///
+/// If a file action fails or the program cannot be loaded, the child cage is discarded and the
+/// error is returned, so the parent never has to reap a child that failed before exec. Lind does
+/// not implement process groups, sessions, scheduling policies or user ids, so only the signal
+/// mask attribute is applied.
+///
+/// ## Arguments
+/// - path_arg: the program to run. Relative paths resolve against the child's cwd, after the
+///   file actions have run
+/// - argv_arg / envs_arg: NULL-terminated arrays of host string pointers
+/// - actions_arg: `nactions_arg` `SpawnAction` entries, applied in order
+/// - attr_arg: a `SpawnAttr`, or NULL for the default attributes
+///
+/// ## Returns:
+/// The child's cage id on success, or a negative errno
+pub extern "C" fn spawn_syscall(
+    cageid: u64,
+    path_arg: u64,
+    // Not used to convert the path, which resolves against the child's cwd rather than the
+    // caller's (see below)
+    _path_cageid: u64,
+    argv_arg: u64,
+    argv_cageid: u64,
+    envs_arg: u64,
+    envs_cageid: u64,
+    actions_arg: u64,
+    actions_cageid: u64,
+    nactions_arg: u64,
+    nactions_cageid: u64,
+    attr_arg: u64,
+    attr_cageid: u64,
+) -> i32 {
+    let nactions = sc_convert_sysarg_to_usize(nactions_arg, nactions_cageid, cageid);
+    let actions_ptr =
+        sc_convert_buf(actions_arg, actions_cageid, cageid) as *const sys_struct::SpawnAction;
+    let actions: &[sys_struct::SpawnAction] = if nactions == 0 {
+        &[]
+    } else if actions_ptr.is_null() {
+        return syscall_error(Errno::EFAULT, "spawn", "file actions are NULL");
+    } else {
+        unsafe { std::slice::from_raw_parts(actions_ptr, nactions) }
+    };
+    let attr = unsafe {
+        (sc_convert_buf(attr_arg, attr_cageid, cageid) as *const sys_struct::SpawnAttr).as_ref()
+    };
+    if path_arg == 0 {
+        return syscall_error(Errno::EFAULT, "spawn", "path is NULL");
+    }
+
+    let child_cageid = match cage::alloc_cage_id() {
+        Some(id) => id,
+        None => return syscall_error(Errno::EAGAIN, "spawn", "no cage ids left"),
+    };
+
+    if fdtables::copy_fdtable_for_cage(cageid, child_cageid).is_err() {
+        cage::release_cage_id(child_cageid);
+        return syscall_error(Errno::ENFILE, "spawn", "too many open files in the system");
+    }
+
+    let selfcage = get_cage(cageid).unwrap();
+
+    let sigset = match attr {
+        Some(attr) if attr.flags & POSIX_SPAWN_SETSIGMASK != 0 => attr.sigmask,
+        _ => selfcage.sigset.load(Relaxed),
+    };
+
+    let cageobj = Cage {
+        cageid: child_cageid,
+        cwd: RwLock::new(selfcage.cwd.read().clone()),
+        umask: AtomicU32::new(selfcage.umask.load(Relaxed)),
+        netns: AtomicU64::new(selfcage.netns.load(Relaxed)),
+        utsns: AtomicU64::new(selfcage.utsns.load(Relaxed)),
+        cpu_affinity: RwLock::new(selfcage.cpu_affinity.read().clone()),
+        nice: AtomicI32::new(selfcage.nice.load(Relaxed)),
+        parent: cageid,
+        rev_shm: Mutex::new(Vec::new()),
+        main_threadid: RwLock::new(0),
+        interval_timer: IntervalTimer::new(child_cageid),
+        epoch_handler: DashMap::new(),
+        os_tid_map: DashMap::new(),
+        robust_list: DashMap::new(),
+        pending_signals: RwLock::new(vec![]),
+        signalhandler: DashMap::new(),
+        sigset: AtomicU64::new(sigset),
+        zombies: RwLock::new(vec![]),
+        child_num: AtomicU64::new(0),
+        vmmap: RwLock::new(Vmmap::new()),
+        memory_snapshot: Mutex::new(None),
+        mem_quota: selfcage.mem_quota.for_child(cageid, child_cageid),
+        final_exit_status: RwLock::new(None),
+        exit_group_initiated: AtomicBool::new(false),
+        is_dead: AtomicBool::new(false),
+        grate_inflight: AtomicU64::new(0),
+    };
+
+    selfcage.child_num.fetch_add(1, SeqCst);
+    add_cage(child_cageid, cageobj);
+
+    // The child keeps the parent's syscall routing, as after fork
+    threei::copy_handler_table_to_cage(
+        UNUSED_ARG,
+        UNUSED_ARG,
+        cageid,
+        child_cageid,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+    );
+
+    if let Err(ret) = apply_spawn_actions(child_cageid, actions) {
+        discard_spawned_cage(&selfcage, child_cageid);
+        return ret;
+    }
+
+    // Resolve the path now, so that a chdir action applies to it
+    let path = match sc_convert_path_to_host(path_arg, child_cageid, child_cageid) {
+        Ok(path) => path,
+        Err(e) => {
+            discard_spawned_cage(&selfcage, child_cageid);
+            return syscall_error(e, "spawn", "path conversion failed");
+        }
+    };
+
+    // Descriptors marked close-on-exec are closed once the actions have run, as by the execve
+    // in the child of a forking posix_spawn
+    fdtables::empty_fds_for_exec(child_cageid);
+
+    let mut ret = start_spawned_program(
+        cageid,
+        &path,
+        argv_arg,
+        argv_cageid,
+        envs_arg,
+        envs_cageid,
+        child_cageid,
+    );
+
+    // Older glibc versions run a program that fails to load as a shell script, with the file
+    // name as the script's argument
+    let try_shell = attr.is_some_and(|attr| attr.xflags & SPAWN_XFLAGS_TRY_SHELL != 0);
+    if ret == -(Errno::ENOEXEC as i32) && try_shell {
+        let shell = CString::new(PATH_BSHELL).unwrap();
+        let mut shell_argv = vec![shell.as_ptr() as u64, path_arg];
+        let argv = argv_arg as *const u64;
+        if !argv.is_null() && unsafe { *argv } != 0 {
+            let mut i = 1;
+            while unsafe { *argv.add(i) } != 0 {
+                shell_argv.push(unsafe { *argv.add(i) });
+                i += 1;
+            }
+        }
+        shell_argv.push(0);
+        ret = match sc_convert_path_to_host(shell.as_ptr() as u64, child_cageid, child_cageid) {
+            Ok(shell_path) => start_spawned_program(
+                cageid,
+                &shell_path,
+                shell_argv.as_ptr() as u64,
+                argv_cageid,
+                envs_arg,
+                envs_cageid,
+                child_cageid,
+            ),
+            Err(e) => syscall_error(e, "spawn", "path conversion failed"),
+        };
+    }
+
+    if ret < 0 {
+        discard_spawned_cage(&selfcage, child_cageid);
+    }
+
+    ret
+}
+
+/// Has Wasmtime load the program at the host path `path` and start it in the child cage. The
+/// parent's cage id and the id of its calling thread are passed to find that thread's Wasmtime
+/// context, as for clone.
+///
+/// # Returns
+/// * The child's cage id, or a negative errno if the program could not be loaded
+fn start_spawned_program(
+    cageid: u64,
+    path: &CStr,
+    argv_arg: u64,
+    argv_cageid: u64,
+    envs_arg: u64,
+    envs_cageid: u64,
+    child_cageid: u64,
+) -> i32 {
+    // Wasmtime is entered through the calling thread's context, as the parent's other threads
+    // keep running
+    let parent_tid = match current_thread_id(cageid) {
+        Some(tid) => tid as u64,
+        None => return syscall_error(Errno::ESRCH, "spawn", "unknown calling thread"),
+    };
+    threei::make_syscall(
+        RAWPOSIX_CAGEID,
+        syscall_const::SPAWN_SYSCALL as u64,
+        UNUSED_NAME,
+        WASMTIME_CAGEID,
+        path.as_ptr() as u64,
+        cageid,
+        argv_arg,
+        argv_cageid,
+        envs_arg,
+        envs_cageid,
+        child_cageid,
+        UNUSED_ID,
+        parent_tid,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+    )
+}
+
+/// Applies posix_spawn file actions to a child cage that has not started yet, in order.
+///
+/// Failures of close actions are ignored, as in glibc.
+///
+/// # Returns
+/// * `Err` with the negative errno of the first action that failed
+fn apply_spawn_actions(child_cageid: u64, actions: &[sys_struct::SpawnAction]) -> Result<(), i32> {
+    let close = |fd: u64| {
+        close_syscall(
+            child_cageid,
+            fd,
+            child_cageid,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+        )
+    };
+    let dup2 = |oldfd: u64, newfd: u64| {
+        dup2_syscall(
+            child_cageid,
+            oldfd,
+            child_cageid,
+            newfd,
+            child_cageid,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+            UNUSED_ARG,
+            UNUSED_ID,
+        )
+    };
+
+    for action in actions {
+        let fd = action.fd as u64;
+        let ret = match action.tag {
+            SPAWN_DO_CLOSE => {
+                close(fd);
+                0
+            }
+            SPAWN_DO_DUP2 if action.fd == action.newfd => {
+                // Duplicating a descriptor onto itself clears its close-on-exec flag
+                match fdtables::set_cloexec(child_cageid, fd, false) {
+                    Ok(()) => 0,
+                    Err(_) => syscall_error(Errno::EBADF, "spawn", "bad dup2 file descriptor"),
+                }
+            }
+            SPAWN_DO_DUP2 => dup2(fd, action.newfd as u64),
+            SPAWN_DO_OPEN => {
+                // The target descriptor is closed first, so that opening cannot fail for want of
+                // a free descriptor
+                close(fd);
+                let newfd = open_syscall(
+                    child_cageid,
+                    action.path,
+                    child_cageid,
+                    action.oflag as u64,
+                    child_cageid,
+                    action.mode as u64,
+                    child_cageid,
+                    UNUSED_ARG,
+                    UNUSED_ID,
+                    UNUSED_ARG,
+                    UNUSED_ID,
+                    UNUSED_ARG,
+                    UNUSED_ID,
+                );
+                if newfd >= 0 && newfd != action.fd {
+                    let ret = dup2(newfd as u64, fd);
+                    close(newfd as u64);
+                    ret.min(0)
+                } else {
+                    newfd.min(0)
+                }
+            }
+            SPAWN_DO_CHDIR => chdir_syscall(
+                child_cageid,
+                action.path,
+                child_cageid,
+                UNUSED_ARG,
+                UNUSED_ID,
+                UNUSED_ARG,
+                UNUSED_ID,
+                UNUSED_ARG,
+                UNUSED_ID,
+                UNUSED_ARG,
+                UNUSED_ID,
+                UNUSED_ARG,
+                UNUSED_ID,
+            ),
+            SPAWN_DO_FCHDIR => fchdir_syscall(
+                child_cageid,
+                fd,
+                child_cageid,
+                UNUSED_ARG,
+                UNUSED_ID,
+                UNUSED_ARG,
+                UNUSED_ID,
+                UNUSED_ARG,
+                UNUSED_ID,
+                UNUSED_ARG,
+                UNUSED_ID,
+                UNUSED_ARG,
+                UNUSED_ID,
+            ),
+            SPAWN_DO_CLOSEFROM => {
+                for openfd in fdtables::return_fdtable_copy(child_cageid).into_keys() {
+                    if openfd >= fd {
+                        close(openfd);
+                    }
+                }
+                0
+            }
+            // Lind has no process groups, so there is no foreground group to set
+            SPAWN_DO_TCSETPGRP => 0,
+            _ => syscall_error(Errno::EINVAL, "spawn", "unknown file action"),
+        };
+        if ret < 0 {
+            return Err(ret);
+        }
+    }
+    Ok(())
+}
+
+/// Removes a child cage created by `spawn_syscall` that never started running.
+fn discard_spawned_cage(parent: &Cage, child_cageid: u64) {
+    parent.child_num.fetch_sub(1, SeqCst);
+    threei::handler_table::_rm_cage_from_handler(child_cageid);
+    fdtables::remove_cage_from_fdtable(child_cageid);
+    remove_cage(child_cageid);
+}
+
+/// Lind-specific, with no Linux counterpart: saves the calling cage, with the cages below it, to
+/// a snapshot file that `lind-boot --restore` resumes them from.
+///
+/// RawPOSIX only checks that the cages can be saved and resolves the path against the caller's
+/// cwd. Wasmtime is then called via 3i to unwind the caller as for fork, and to stop each
+/// descendant at its next epoch check, which leaves the whole Wasm state in linear memory. The
+/// file is written once every cage is stopped (see `checkpoint::CageImage` for what it holds), and
+/// the cages then rewind and carry on. The caller sees 0 after saving, and 1 when it resumes from
+/// the file.
+///
+/// ## Arguments
+/// - path_arg: the snapshot file, created or truncated with mode 0600
+///
+/// ## Returns:
+/// 0 once saved, or a negative errno. `EOPNOTSUPP` if a cage has threads, shared memory or
+/// descriptors that cannot be reopened, `EAGAIN` if the cage tree changed while it was being
+/// stopped, and `EBUSY` if a descendant did not stop in time or the tree is already being saved.
+pub extern "C" fn checkpoint_syscall(
+    cageid: u64,
+    path_arg: u64,
+    path_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "checkpoint_syscall"
+        );
+    }
+    if path_arg == 0 {
+        return syscall_error(Errno::EFAULT, "checkpoint", "path is NULL");
+    }
+
+    let tree = std::iter::once(cageid).chain(cage::cage_descendants(cageid));
+    for cage in tree.filter_map(get_cage) {
+        if cage.epoch_handler.len() > 1 {
+            return syscall_error(
+                Errno::EOPNOTSUPP,
+                "checkpoint",
+                "cages with several threads cannot be saved",
+            );
+        }
+        if !cage.rev_shm.lock().is_empty() {
+            return syscall_error(
+                Errno::EOPNOTSUPP,
+                "checkpoint",
+                "cages with shared memory attached cannot be saved",
+            );
+        }
+    }
+
+    let path = match sc_convert_path_to_host(path_arg, path_cageid, cageid) {
+        Ok(path) => path,
+        Err(e) => return syscall_error(e, "checkpoint", "path conversion failed"),
+    };
+
+    // Wasmtime unwinds the cage and writes the file. The path is copied before the unwind starts.
+    threei::make_syscall(
+        RAWPOSIX_CAGEID,
+        syscall_const::CHECKPOINT_SYSCALL as u64,
+        UNUSED_NAME,
+        WASMTIME_CAGEID,
+        path.as_ptr() as u64,
+        cageid,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+        UNUSED_ARG,
+        UNUSED_ID,
+    )
+}
+
 /// Reference to Linux: https://man7.org/linux/man-pages/man3/exit.3.html
 /// Syscall 60 — exit the calling thread only.
 ///
@@ -1212,6 +1716,13 @@ pub extern "C" fn sigprocmask_syscall(
 /// soft limit is the value that kernel enforces for the reponse. Hard limit the ceiling for how high the soft limit can be set.
 /// An unprevileged process may set only the soft limit and irreversibly lower hard limit.
 /// A previleged process may make arbitrary changes to either hard/soft values.
+///
+/// In Lind every cage is unprivileged, and only RLIMIT_NOFILE, RLIMIT_AS and
+/// RLIMIT_DATA may be set.  The fd limits are kept per cage in fdtables, which
+/// returns EMFILE once a cage hits its soft limit.  The memory limits are kept in
+/// the cage's `MemQuota`, and mappings that would exceed them fail with ENOMEM.
+/// Limits above the 4 GiB of linear memory are clamped to it.  A forked cage
+/// inherits the limits and exec keeps them.
 /// ## Returns
 /// On success, returns 0. On error, -1 is returned, and errno is set.
 
@@ -1246,48 +1757,81 @@ pub extern "C" fn prlimit64_syscall(
     // get resource numeber from arg2
     let resource = sc_convert_sysarg_to_u32(arg2, arg2_cageid, cageid);
 
-    // setrlimit unsupported
-    if !sc_convert_arg_nullity(arg3, arg3_cageid, cageid) {
-        lind_debug_panic!("prlimit64: setrlimit not supported");
-        return syscall_error(Errno::EPERM, "prlimit64", "setrlimit not supported");
-    }
+    // The old limit is read before any new limit is applied, so a single call
+    // can both set and return the previous value.
+    let (old_cur, old_max) = match resource {
+        RLIMIT_STACK => (8 * 1024 * 1024, 8 * 1024 * 1024),
+        // fd limits are per-cage and enforced by fdtables
+        RLIMIT_NOFILE => fdtables::get_fd_limits(cageid),
+        // memory limits are per-cage and enforced by the memory quota
+        RLIMIT_AS => {
+            let rlimits = get_cage(cageid).unwrap().mem_quota.rlimits();
+            (rlimits.as_cur, rlimits.as_max)
+        }
+        RLIMIT_DATA => {
+            let rlimits = get_cage(cageid).unwrap().mem_quota.rlimits();
+            (rlimits.data_cur, rlimits.data_max)
+        }
+        RLIMIT_RSS => {
+            let size = get_cage(cageid).unwrap().vmmap.read().address_space_size() - 1;
+            (size, size)
+        }
+        RLIMIT_NPROC => (MAX_CAGEID as u64, MAX_CAGEID as u64),
+        RLIMIT_CORE => (0, 0),
+        _ => {
+            lind_debug_panic!("prlimit64: unsupported resource {}", resource);
+            (0, 0)
+        }
+    };
 
-    // handle getrlimit calls
-    // default to 1024.
-    if !sc_convert_arg_nullity(arg4, arg4_cageid, cageid) {
-        let old_limit = match sc_convert_addr_to_rlimit(arg4, arg4_cageid, cageid) {
+    // handle setrlimit calls.  Only RLIMIT_NOFILE, RLIMIT_AS and RLIMIT_DATA can be changed.
+    if !sc_convert_arg_nullity(arg3, arg3_cageid, cageid) {
+        let new_limit = match sc_convert_addr_to_rlimit(arg3, arg3_cageid, cageid) {
             Ok(rlim) => rlim,
             Err(e) => return syscall_error(e, "prlimit64", "bad address"),
         };
         match resource {
-            RLIMIT_STACK => {
-                old_limit.rlim_cur = 8 * 1024 * 1024;
-                old_limit.rlim_max = 8 * 1024 * 1024;
-            }
             RLIMIT_NOFILE => {
-                old_limit.rlim_cur = 1024;
-                old_limit.rlim_max = 1024;
-            }
-            RLIMIT_DATA | RLIMIT_RSS | RLIMIT_AS => {
-                old_limit.rlim_cur = MAX_LINEAR_MEMORY_SIZE as u32;
-                old_limit.rlim_max = MAX_LINEAR_MEMORY_SIZE as u32;
-            }
-            RLIMIT_NPROC => {
-                old_limit.rlim_cur = MAX_CAGEID as u32;
-                old_limit.rlim_max = MAX_CAGEID as u32;
+                // EINVAL if soft > hard, EPERM if the hard limit is raised
+                if let Err(e) =
+                    fdtables::set_fd_limits(cageid, new_limit.rlim_cur, new_limit.rlim_max)
+                {
+                    return handle_errno(e as i32, "prlimit64");
+                }
             }
-            RLIMIT_CORE => {
-                old_limit.rlim_cur = 0;
-                old_limit.rlim_max = 0;
+            RLIMIT_AS | RLIMIT_DATA => {
+                let cage = get_cage(cageid).unwrap();
+                let size = cage.vmmap.read().address_space_size() - 1;
+                let cur = new_limit.rlim_cur.min(size);
+                let max = new_limit.rlim_max.min(size);
+                let mut rlimits = cage.mem_quota.rlimits();
+                if resource == RLIMIT_AS {
+                    (rlimits.as_cur, rlimits.as_max) = (cur, max);
+                } else {
+                    (rlimits.data_cur, rlimits.data_max) = (cur, max);
+                }
+                // EINVAL if soft > hard, EPERM if the hard limit is raised
+                if let Err(e) = cage.mem_quota.set_rlimits(rlimits) {
+                    return syscall_error(e, "prlimit64", "invalid memory limit");
+                }
             }
             _ => {
-                lind_debug_panic!("prlimit64: unsupported resource {}", resource);
-                old_limit.rlim_cur = 0;
-                old_limit.rlim_max = 0;
+                lind_debug_panic!("prlimit64: setrlimit not supported for {}", resource);
+                return syscall_error(Errno::EPERM, "prlimit64", "setrlimit not supported");
             }
         }
     }
 
+    // handle getrlimit calls
+    if !sc_convert_arg_nullity(arg4, arg4_cageid, cageid) {
+        let old_limit = match sc_convert_addr_to_rlimit(arg4, arg4_cageid, cageid) {
+            Ok(rlim) => rlim,
+            Err(e) => return syscall_error(e, "prlimit64", "bad address"),
+        };
+        old_limit.rlim_cur = old_cur;
+        old_limit.rlim_max = old_max;
+    }
+
     0 //success
 }
 
@@ -1330,6 +1874,577 @@ pub extern "C" fn sched_yield_syscall(
     (unsafe { sched_yield() }) as i32
 }
 
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/unshare.2.html
+///
+/// Linux `unshare()` moves the calling process into new namespaces. The only namespaces cages
+/// have are the network and UTS namespaces kept by rawposix, so only these two flags are
+/// supported. `CLONE_NEWNET` moves the cage into a new private network namespace, with its own
+/// ports and loopback (see `crate::netns`); sockets the cage already holds are not moved.
+/// `CLONE_NEWUTS` gives the cage a hostname of its own, starting as a copy of its current one
+/// (see `crate::uts`). Its children inherit the new namespaces.
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - flags_arg: `CLONE_NEWNET` and/or `CLONE_NEWUTS`, or 0
+///
+/// ## Returns:
+///     - 0 on success
+///     - EINVAL if any other flag is set
+pub extern "C" fn unshare_syscall(
+    cageid: u64,
+    flags_arg: u64,
+    flags_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let flags = sc_convert_sysarg_to_i32(flags_arg, flags_cageid, cageid) as u64;
+
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "unshare_syscall"
+        );
+    }
+
+    if flags & !(sys_const::CLONE_NEWNET | sys_const::CLONE_NEWUTS) != 0 {
+        return syscall_error(Errno::EINVAL, "unshare", "unsupported namespace flags");
+    }
+
+    let cage = get_cage(cageid).unwrap();
+    if flags & sys_const::CLONE_NEWNET != 0 {
+        cage.netns.store(netns::new_netns(), Relaxed);
+    }
+    if flags & sys_const::CLONE_NEWUTS != 0 {
+        cage.utsns.store(uts::new_utsns(cageid), Relaxed);
+    }
+    0
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/uname.2.html
+///
+/// Linux `uname()` describes the running kernel. Cages get the host's description, except that
+/// the nodename is the hostname of the cage's UTS namespace and the release is the one chosen
+/// with lind-boot's `--kernel-release`, if any (see `crate::uts`).
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - buf_arg: pointer to the cage's `struct utsname`
+///
+/// ## Returns:
+///     - 0 on success
+///     - EFAULT if `buf_arg` is null
+pub extern "C" fn uname_syscall(
+    cageid: u64,
+    buf_arg: u64,
+    _buf_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let buf = buf_arg as *mut libc::utsname;
+
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "uname_syscall"
+        );
+    }
+
+    if buf.is_null() {
+        return syscall_error(Errno::EFAULT, "uname", "buffer is null");
+    }
+    // Every field is a char array, so the cage's struct has the host's layout
+    unsafe { std::ptr::write_unaligned(buf, uts::utsname(cageid)) };
+    0
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/sysinfo.2.html
+///
+/// Linux `sysinfo()` reports system-wide statistics. Memory is reported for the caller's linear
+/// memory rather than the host's: the total is the cage's address space, the free amount is
+/// what the cage's `Vmmap` has not mapped, and the shared amount is its shared mappings. The
+/// process count is the number of live cages. Uptime and load averages are the host's; there is
+/// no swap.
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - info_arg: pointer to the cage's `struct sysinfo`
+///
+/// ## Returns:
+///     - 0 on success
+///     - EFAULT if `info_arg` is null
+pub extern "C" fn sysinfo_syscall(
+    cageid: u64,
+    info_arg: u64,
+    _info_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "sysinfo_syscall"
+        );
+    }
+
+    if info_arg == 0 {
+        return syscall_error(Errno::EFAULT, "sysinfo", "buffer is null");
+    }
+
+    let mut host: libc::sysinfo = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sysinfo(&mut host) } < 0 {
+        return handle_errno(get_errno(), "sysinfo");
+    }
+
+    // Count the cage's mapped and shared pages. 4 GiB doesn't fit the 32-bit fields of a 32-bit
+    // cage in bytes, so sizes are given in pages there, and in bytes in a 64-bit cage.
+    let cage = get_cage(cageid).unwrap();
+    let vmmap = cage.vmmap.read();
+    let (mut mapped, mut shared) = (0u64, 0u64);
+    for (_, entry) in vmmap.entries.iter() {
+        if entry.prot == PROT_NONE {
+            continue;
+        }
+        mapped += entry.npages as u64;
+        if entry.flags as u32 & MAP_SHARED != 0 {
+            shared += entry.npages as u64;
+        }
+    }
+    let total = vmmap.address_space_size() >> PAGESHIFT;
+    let procs = sched::live_cages().len() as u16;
+
+    if vmmap.memory64 {
+        let out = sys_struct::GuestSysinfo64 {
+            uptime: host.uptime as i64,
+            loads: host.loads.map(|load| load as u64),
+            totalram: total << PAGESHIFT,
+            freeram: total.saturating_sub(mapped) << PAGESHIFT,
+            sharedram: shared << PAGESHIFT,
+            procs,
+            mem_unit: 1,
+            ..Default::default()
+        };
+        unsafe { std::ptr::write_unaligned(info_arg as *mut sys_struct::GuestSysinfo64, out) };
+    } else {
+        let out = sys_struct::GuestSysinfo {
+            uptime: host.uptime as i32,
+            loads: host.loads.map(|load| load as u32),
+            totalram: total as u32,
+            freeram: total.saturating_sub(mapped) as u32,
+            sharedram: shared as u32,
+            procs,
+            mem_unit: PAGESIZE,
+            ..Default::default()
+        };
+        unsafe { std::ptr::write_unaligned(info_arg as *mut sys_struct::GuestSysinfo, out) };
+    }
+    0
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/sched_getaffinity.2.html
+///
+/// Linux `sched_getaffinity()` returns the CPU affinity mask of a thread. This implementation
+/// returns the mask of the cage named by `pid` (0 for the caller) over lind's virtual CPUs (see
+/// `crate::sched`). As the raw Linux syscall does, it returns the size of the mask it copied,
+/// and glibc clears the rest of the caller's buffer.
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - pid_arg: cage to query, or 0 for the caller
+///     - len_arg: size of the caller's mask in bytes
+///     - mask_arg: pointer to the caller's `cpu_set_t`
+///
+/// ## Returns:
+///     - the number of bytes copied on success
+///     - EINVAL if the buffer is too small for the mask or not a whole number of words
+///     - ESRCH if there is no such cage
+pub extern "C" fn sched_getaffinity_syscall(
+    cageid: u64,
+    pid_arg: u64,
+    pid_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    mask_arg: u64,
+    _mask_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let pid = sc_convert_sysarg_to_i32(pid_arg, pid_cageid, cageid);
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    let mask = mask_arg as *mut u8;
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "sched_getaffinity_syscall"
+        );
+    }
+
+    if len < sched::mask_size() || !len.is_multiple_of(sched::GUEST_MASK_WORD) {
+        return syscall_error(Errno::EINVAL, "sched_getaffinity", "mask buffer too small");
+    }
+    if mask.is_null() {
+        return syscall_error(Errno::EFAULT, "sched_getaffinity", "mask is null");
+    }
+    let Some(target) = sched::target_cage(cageid, pid) else {
+        return syscall_error(Errno::ESRCH, "sched_getaffinity", "no such cage");
+    };
+
+    let bytes = sched::mask_to_bytes(&target.cpu_affinity.read());
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), mask, bytes.len()) };
+    bytes.len() as i32
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/sched_setaffinity.2.html
+///
+/// Linux `sched_setaffinity()` sets the CPU affinity mask of a thread. This implementation sets
+/// the mask of the cage named by `pid` (0 for the caller) over lind's virtual CPUs (see
+/// `crate::sched`). CPUs beyond the last virtual one are ignored. The mask is only reported back
+/// to the cage; host threads keep running on any host CPU.
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - pid_arg: cage to change, or 0 for the caller
+///     - len_arg: size of the new mask in bytes
+///     - mask_arg: pointer to the new `cpu_set_t`
+///
+/// ## Returns:
+///     - 0 on success
+///     - EINVAL if the mask has none of the virtual CPUs
+///     - ESRCH if there is no such cage
+pub extern "C" fn sched_setaffinity_syscall(
+    cageid: u64,
+    pid_arg: u64,
+    pid_cageid: u64,
+    len_arg: u64,
+    len_cageid: u64,
+    mask_arg: u64,
+    _mask_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let pid = sc_convert_sysarg_to_i32(pid_arg, pid_cageid, cageid);
+    let len = sc_convert_sysarg_to_usize(len_arg, len_cageid, cageid);
+    let mask = mask_arg as *const u8;
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "sched_setaffinity_syscall"
+        );
+    }
+
+    if mask.is_null() && len > 0 {
+        return syscall_error(Errno::EFAULT, "sched_setaffinity", "mask is null");
+    }
+    let Some(target) = sched::target_cage(cageid, pid) else {
+        return syscall_error(Errno::ESRCH, "sched_setaffinity", "no such cage");
+    };
+
+    let bytes = if len == 0 {
+        &[][..]
+    } else {
+        unsafe { std::slice::from_raw_parts(mask, len) }
+    };
+    let new_mask = sched::mask_from_bytes(bytes);
+    if sched::cpus_in(&new_mask).next().is_none() {
+        return syscall_error(Errno::EINVAL, "sched_setaffinity", "no usable CPU in mask");
+    }
+    *target.cpu_affinity.write() = new_mask;
+    0
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/getcpu.2.html
+///
+/// Linux `getcpu()` reports the CPU and NUMA node the caller runs on. This implementation
+/// reports one of the virtual CPUs in the cage's affinity mask, picked from the host CPU the
+/// caller runs on (see `crate::sched`), and node 0. Either pointer may be null.
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - cpu_arg: pointer to receive the CPU number, or null
+///     - node_arg: pointer to receive the node number, or null
+///     - cache_arg: unused, as on Linux since 2.6.24
+///
+/// ## Returns:
+///     - 0
+pub extern "C" fn getcpu_syscall(
+    cageid: u64,
+    cpu_arg: u64,
+    _cpu_cageid: u64,
+    node_arg: u64,
+    _node_cageid: u64,
+    _cache_arg: u64,
+    _cache_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let cpu = cpu_arg as *mut u32;
+    let node = node_arg as *mut u32;
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "getcpu_syscall"
+        );
+    }
+
+    let cage = get_cage(cageid).unwrap();
+    if !cpu.is_null() {
+        let current = sched::current_cpu(&cage.cpu_affinity.read());
+        unsafe { std::ptr::write_unaligned(cpu, current as u32) };
+    }
+    if !node.is_null() {
+        unsafe { std::ptr::write_unaligned(node, 0) };
+    }
+    0
+}
+
+/// The cages a `getpriority`/`setpriority` call applies to. Lind has no process groups, so each
+/// cage is a group of its own, and all cages run as the same user.
+fn priority_targets(cageid: u64, which: i32, who: i32) -> Result<Vec<Arc<Cage>>, Errno> {
+    let targets = match which {
+        PRIO_PROCESS | PRIO_PGRP => sched::target_cage(cageid, who).into_iter().collect(),
+        PRIO_USER if who == 0 || who as u32 == unsafe { libc::getuid() } => sched::live_cages(),
+        PRIO_USER => Vec::new(),
+        _ => return Err(Errno::EINVAL),
+    };
+    if targets.is_empty() {
+        return Err(Errno::ESRCH);
+    }
+    Ok(targets)
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/getpriority.2.html
+///
+/// Linux `getpriority()` returns the highest priority (lowest nice value) of the processes
+/// selected by `which` and `who`. This implementation reads the nice values kept per cage (see
+/// `crate::sched`). As the raw Linux syscall does, it returns `20 - nice` so that the result is
+/// never negative; glibc converts it back.
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - which_arg: `PRIO_PROCESS`, `PRIO_PGRP` or `PRIO_USER`
+///     - who_arg: cage, process group or user id, or 0 for the caller's
+///
+/// ## Returns:
+///     - `20 - nice` on success
+///     - EINVAL if `which` is invalid
+///     - ESRCH if no cage matches
+pub extern "C" fn getpriority_syscall(
+    cageid: u64,
+    which_arg: u64,
+    which_cageid: u64,
+    who_arg: u64,
+    who_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let which = sc_convert_sysarg_to_i32(which_arg, which_cageid, cageid);
+    let who = sc_convert_sysarg_to_i32(who_arg, who_cageid, cageid);
+
+    if !(sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "getpriority_syscall"
+        );
+    }
+
+    match priority_targets(cageid, which, who) {
+        Ok(targets) => {
+            let nice = targets
+                .iter()
+                .map(|cage| cage.nice.load(Relaxed))
+                .min()
+                .unwrap();
+            20 - nice
+        }
+        Err(e) => syscall_error(e, "getpriority", "no matching cage"),
+    }
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/setpriority.2.html
+///
+/// Linux `setpriority()` sets the nice value of the processes selected by `which` and `who`.
+/// This implementation sets the nice values kept per cage (see `crate::sched`), clamped to
+/// -20..19 as on Linux. Host threads keep the lind process's priority. Unless lind runs as root,
+/// lowering a cage's nice value fails with EACCES, as it does for an unprivileged Linux process.
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - which_arg: `PRIO_PROCESS`, `PRIO_PGRP` or `PRIO_USER`
+///     - who_arg: cage, process group or user id, or 0 for the caller's
+///     - prio_arg: the new nice value
+///
+/// ## Returns:
+///     - 0 on success
+///     - EINVAL if `which` is invalid
+///     - ESRCH if no cage matches
+///     - EACCES if an unprivileged caller asks for a lower nice value
+pub extern "C" fn setpriority_syscall(
+    cageid: u64,
+    which_arg: u64,
+    which_cageid: u64,
+    who_arg: u64,
+    who_cageid: u64,
+    prio_arg: u64,
+    prio_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let which = sc_convert_sysarg_to_i32(which_arg, which_cageid, cageid);
+    let who = sc_convert_sysarg_to_i32(who_arg, who_cageid, cageid);
+    let prio = sc_convert_sysarg_to_i32(prio_arg, prio_cageid, cageid);
+
+    if !(sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "setpriority_syscall"
+        );
+    }
+
+    let targets = match priority_targets(cageid, which, who) {
+        Ok(targets) => targets,
+        Err(e) => return syscall_error(e, "setpriority", "no matching cage"),
+    };
+    let nice = prio.clamp(NICE_MIN, NICE_MAX);
+    let privileged = unsafe { libc::geteuid() } == 0;
+    if !privileged && targets.iter().any(|cage| nice < cage.nice.load(Relaxed)) {
+        return syscall_error(Errno::EACCES, "setpriority", "cannot raise priority");
+    }
+    for cage in targets {
+        cage.nice.store(nice, Relaxed);
+    }
+    0
+}
+
+/// Reference to Linux: https://man7.org/linux/man-pages/man2/sched_getscheduler.2.html
+///
+/// Linux `sched_getscheduler()` returns the scheduling policy of a thread. Cages only ever run
+/// under the default time-sharing policy, so this returns `SCHED_OTHER` for any live cage.
+///
+/// ## Input:
+///     - cageid: current cage identifier
+///     - pid_arg: cage to query, or 0 for the caller
+///
+/// ## Returns:
+///     - `SCHED_OTHER` on success
+///     - EINVAL if `pid` is negative
+///     - ESRCH if there is no such cage
+pub extern "C" fn sched_getscheduler_syscall(
+    cageid: u64,
+    pid_arg: u64,
+    pid_cageid: u64,
+    arg2: u64,
+    arg2_cageid: u64,
+    arg3: u64,
+    arg3_cageid: u64,
+    arg4: u64,
+    arg4_cageid: u64,
+    arg5: u64,
+    arg5_cageid: u64,
+    arg6: u64,
+    arg6_cageid: u64,
+) -> i32 {
+    let pid = sc_convert_sysarg_to_i32(pid_arg, pid_cageid, cageid);
+
+    if !(sc_unusedarg(arg2, arg2_cageid)
+        && sc_unusedarg(arg3, arg3_cageid)
+        && sc_unusedarg(arg4, arg4_cageid)
+        && sc_unusedarg(arg5, arg5_cageid)
+        && sc_unusedarg(arg6, arg6_cageid))
+    {
+        panic!(
+            "{}: unused arguments contain unexpected values -- security violation",
+            "sched_getscheduler_syscall"
+        );
+    }
+
+    if pid < 0 {
+        return syscall_error(Errno::EINVAL, "sched_getscheduler", "negative pid");
+    }
+    match sched::target_cage(cageid, pid) {
+        Some(_) => SCHED_OTHER,
+        None => syscall_error(Errno::ESRCH, "sched_getscheduler", "no such cage"),
+    }
+}
+
 /// Reference to Linux: https://man7.org/linux/man-pages/man2/rt_sigsuspend.2.html
 ///
 /// Atomically saves the current signal mask into `oldset`, replaces it with
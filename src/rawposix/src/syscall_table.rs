@@ -6,18 +6,27 @@
 use super::fs_calls::{
     access_syscall, brk_syscall, chdir_syscall, chmod_syscall, chown_syscall,
     clock_gettime_syscall, close_syscall, copy_file_range_syscall, dup2_syscall, dup3_syscall,
-    dup_syscall, faccessat_syscall, fchdir_syscall, fchmod_syscall, fchmodat_syscall,
-    fchownat_syscall, fcntl_syscall, fdatasync_syscall, flock_syscall, fstat_syscall,
-    fstatat_syscall, fstatfs_syscall, fsync_syscall, ftruncate_syscall, futex_syscall,
-    getcwd_syscall, getdents_syscall, getrandom_syscall, ioctl_syscall, lchown_syscall,
-    link_syscall, listxattr_syscall, lseek_syscall, lstat_syscall, mkdir_syscall, mknod_syscall,
-    mmap_syscall, mprotect_syscall, munmap_syscall, nanosleep_time64_syscall, open_syscall,
-    openat_syscall, pipe2_syscall, pipe_syscall, pread_syscall, preadv_syscall, pwrite_syscall,
-    pwritev_syscall, read_syscall, readlink_syscall, readlinkat_syscall, readv_syscall,
-    rename_syscall, renameat2_syscall, renameat_syscall, rmdir_syscall, setxattr_syscall,
-    shmat_syscall, shmctl_syscall, shmdt_syscall, shmget_syscall, stat_syscall, statfs_syscall,
-    symlink_syscall, symlinkat_syscall, sync_file_range_syscall, truncate_syscall, unlink_syscall,
-    unlinkat_syscall, utimensat_syscall, write_syscall, writev_syscall,
+    dup_syscall, faccessat_syscall, fadvise64_syscall, fallocate_syscall, fchdir_syscall,
+    fchmod_syscall, fchmodat_syscall, fchownat_syscall, fcntl_syscall, fdatasync_syscall,
+    fgetxattr_syscall, flistxattr_syscall, flock_syscall, fremovexattr_syscall, fsetxattr_syscall,
+    fstat_syscall, fstatat_syscall, fstatfs_syscall, fsync_syscall, ftruncate_syscall,
+    futex_syscall, get_robust_list_syscall, getcwd_syscall, getdents_syscall, getrandom_syscall,
+    getxattr_syscall, ioctl_syscall, lchown_syscall, lgetxattr_syscall, link_syscall,
+    listxattr_syscall, llistxattr_syscall, lremovexattr_syscall, lseek_syscall, lsetxattr_syscall,
+    lstat_syscall, madvise_syscall, memfd_create_syscall, mincore_syscall, mkdir_syscall,
+    mknod_syscall, mlock_syscall, mmap_syscall, mprotect_syscall, mq_getsetattr_syscall,
+    mq_notify_syscall, mq_open_syscall, mq_timedreceive_syscall, mq_timedsend_syscall,
+    mq_unlink_syscall, mremap_syscall, msgctl_syscall, msgget_syscall, msgrcv_syscall,
+    msgsnd_syscall, msync_syscall, munlock_syscall, munmap_syscall, nanosleep_time64_syscall,
+    open_syscall, openat_syscall, pipe2_syscall, pipe_syscall, pread_syscall, preadv_syscall,
+    pwrite_syscall, pwritev_syscall, read_syscall, readlink_syscall, readlinkat_syscall,
+    readv_syscall, removexattr_syscall, rename_syscall, renameat2_syscall, renameat_syscall,
+    rmdir_syscall, semctl_syscall, semget_syscall, semtimedop_syscall, sendfile_syscall,
+    set_robust_list_syscall, set_tid_address_syscall, setxattr_syscall, shmat_syscall,
+    shmctl_syscall, shmdt_syscall, shmget_syscall, splice_syscall, stat_syscall, statfs_syscall,
+    statx_syscall, symlink_syscall, symlinkat_syscall, sync_file_range_syscall, tee_syscall,
+    truncate_syscall, umask_syscall, unlink_syscall, unlinkat_syscall, utimensat_syscall,
+    vmsplice_syscall, write_syscall, writev_syscall,
 };
 use super::init::RawCallFunc;
 use super::net_calls::{
@@ -25,13 +34,17 @@ use super::net_calls::{
     epoll_create_syscall, epoll_ctl_syscall, epoll_wait_syscall, gethostname_syscall,
     getpeername_syscall, getsockname_syscall, getsockopt_syscall, listen_syscall, poll_syscall,
     ppoll_syscall, recvfrom_syscall, recvmsg_syscall, select_syscall, sendmsg_syscall,
-    sendto_syscall, setsockopt_syscall, shutdown_syscall, socket_syscall, socketpair_syscall,
+    sendto_syscall, sethostname_syscall, setsockopt_syscall, shutdown_syscall, socket_syscall,
+    socketpair_syscall,
 };
 use super::sys_calls::{
-    exec_syscall, exit_group_syscall, exit_syscall, fork_syscall, getegid_syscall, geteuid_syscall,
-    getgid_syscall, getpgid_syscall, getpid_syscall, getppid_syscall, getuid_syscall, kill_syscall,
-    prlimit64_syscall, sched_yield_syscall, setitimer_syscall, sigaction_syscall,
-    sigprocmask_syscall, sigsuspend_syscall, waitpid_syscall,
+    checkpoint_syscall, exec_syscall, exit_group_syscall, exit_syscall, fork_syscall,
+    getcpu_syscall, getegid_syscall, geteuid_syscall, getgid_syscall, getpgid_syscall,
+    getpid_syscall, getppid_syscall, getpriority_syscall, getuid_syscall, kill_syscall,
+    prlimit64_syscall, sched_getaffinity_syscall, sched_getscheduler_syscall,
+    sched_setaffinity_syscall, sched_yield_syscall, setitimer_syscall, setpriority_syscall,
+    sigaction_syscall, sigprocmask_syscall, sigsuspend_syscall, spawn_syscall, sysinfo_syscall,
+    uname_syscall, unshare_syscall, waitpid_syscall,
 };
 use sysdefs::constants::syscall_const;
 
@@ -70,6 +83,10 @@ pub const SYSCALL_TABLE: &[(u64, RawCallFunc)] = &[
         syscall_const::RT_SIGSUSPEND_SYSCALL as u64,
         sigsuspend_syscall,
     ),
+    (syscall_const::MREMAP_SYSCALL as u64, mremap_syscall),
+    (syscall_const::MSYNC_SYSCALL as u64, msync_syscall),
+    (syscall_const::MINCORE_SYSCALL as u64, mincore_syscall),
+    (syscall_const::MADVISE_SYSCALL as u64, madvise_syscall),
     (syscall_const::SHMGET_SYSCALL as u64, shmget_syscall),
     (syscall_const::SHMAT_SYSCALL as u64, shmat_syscall),
     (syscall_const::SHMCTL_SYSCALL as u64, shmctl_syscall),
@@ -81,6 +98,7 @@ pub const SYSCALL_TABLE: &[(u64, RawCallFunc)] = &[
     ),
     (syscall_const::SETITIMER_SYSCALL as u64, setitimer_syscall),
     (syscall_const::GETPID_SYSCALL as u64, getpid_syscall),
+    (syscall_const::SENDFILE_SYSCALL as u64, sendfile_syscall),
     (syscall_const::SOCKET_SYSCALL as u64, socket_syscall),
     (syscall_const::CONNECT_SYSCALL as u64, connect_syscall),
     (syscall_const::ACCEPT_SYSCALL as u64, accept_syscall),
@@ -104,10 +122,19 @@ pub const SYSCALL_TABLE: &[(u64, RawCallFunc)] = &[
     (syscall_const::GETSOCKOPT_SYSCALL as u64, getsockopt_syscall),
     (syscall_const::CLONE_SYSCALL as u64, fork_syscall),
     (syscall_const::EXEC_SYSCALL as u64, exec_syscall),
+    (syscall_const::SPAWN_SYSCALL as u64, spawn_syscall),
+    (syscall_const::CHECKPOINT_SYSCALL as u64, checkpoint_syscall),
     (syscall_const::EXIT_SYSCALL as u64, exit_syscall),
     (syscall_const::WAITPID_SYSCALL as u64, waitpid_syscall),
     (syscall_const::KILL_SYSCALL as u64, kill_syscall),
+    (syscall_const::UNAME_SYSCALL as u64, uname_syscall),
+    (syscall_const::SEMGET_SYSCALL as u64, semget_syscall),
+    (syscall_const::SEMCTL_SYSCALL as u64, semctl_syscall),
     (syscall_const::SHMDT_SYSCALL as u64, shmdt_syscall),
+    (syscall_const::MSGGET_SYSCALL as u64, msgget_syscall),
+    (syscall_const::MSGSND_SYSCALL as u64, msgsnd_syscall),
+    (syscall_const::MSGRCV_SYSCALL as u64, msgrcv_syscall),
+    (syscall_const::MSGCTL_SYSCALL as u64, msgctl_syscall),
     (syscall_const::FCNTL_SYSCALL as u64, fcntl_syscall),
     (syscall_const::FLOCK_SYSCALL as u64, flock_syscall),
     (syscall_const::FSYNC_SYSCALL as u64, fsync_syscall),
@@ -129,6 +156,8 @@ pub const SYSCALL_TABLE: &[(u64, RawCallFunc)] = &[
     (syscall_const::FCHMOD_SYSCALL as u64, fchmod_syscall),
     (syscall_const::CHOWN_SYSCALL as u64, chown_syscall),
     (syscall_const::LCHOWN_SYSCALL as u64, lchown_syscall),
+    (syscall_const::UMASK_SYSCALL as u64, umask_syscall),
+    (syscall_const::SYSINFO_SYSCALL as u64, sysinfo_syscall),
     (syscall_const::GETUID_SYSCALL as u64, getuid_syscall),
     (syscall_const::GETGID_SYSCALL as u64, getgid_syscall),
     (syscall_const::GETEUID_SYSCALL as u64, geteuid_syscall),
@@ -138,17 +167,68 @@ pub const SYSCALL_TABLE: &[(u64, RawCallFunc)] = &[
     (syscall_const::MKNOD_SYSCALL as u64, mknod_syscall),
     (syscall_const::STATFS_SYSCALL as u64, statfs_syscall),
     (syscall_const::FSTATFS_SYSCALL as u64, fstatfs_syscall),
+    (
+        syscall_const::GETPRIORITY_SYSCALL as u64,
+        getpriority_syscall,
+    ),
+    (
+        syscall_const::SETPRIORITY_SYSCALL as u64,
+        setpriority_syscall,
+    ),
+    (
+        syscall_const::SCHED_GETSCHEDULER_SYSCALL as u64,
+        sched_getscheduler_syscall,
+    ),
+    (syscall_const::MLOCK_SYSCALL as u64, mlock_syscall),
+    (syscall_const::MUNLOCK_SYSCALL as u64, munlock_syscall),
     (
         syscall_const::GETHOSTNAME_SYSCALL as u64,
         gethostname_syscall,
     ),
-    (188 as u64, setxattr_syscall),
-    (194 as u64, listxattr_syscall),
+    (
+        syscall_const::SETHOSTNAME_SYSCALL as u64,
+        sethostname_syscall,
+    ),
+    (syscall_const::SETXATTR_SYSCALL as u64, setxattr_syscall),
+    (syscall_const::LSETXATTR_SYSCALL as u64, lsetxattr_syscall),
+    (syscall_const::FSETXATTR_SYSCALL as u64, fsetxattr_syscall),
+    (syscall_const::GETXATTR_SYSCALL as u64, getxattr_syscall),
+    (syscall_const::LGETXATTR_SYSCALL as u64, lgetxattr_syscall),
+    (syscall_const::FGETXATTR_SYSCALL as u64, fgetxattr_syscall),
+    (syscall_const::LISTXATTR_SYSCALL as u64, listxattr_syscall),
+    (syscall_const::LLISTXATTR_SYSCALL as u64, llistxattr_syscall),
+    (syscall_const::FLISTXATTR_SYSCALL as u64, flistxattr_syscall),
+    (
+        syscall_const::REMOVEXATTR_SYSCALL as u64,
+        removexattr_syscall,
+    ),
+    (
+        syscall_const::LREMOVEXATTR_SYSCALL as u64,
+        lremovexattr_syscall,
+    ),
+    (
+        syscall_const::FREMOVEXATTR_SYSCALL as u64,
+        fremovexattr_syscall,
+    ),
     (syscall_const::FUTEX_SYSCALL as u64, futex_syscall),
+    (
+        syscall_const::SCHED_SETAFFINITY_SYSCALL as u64,
+        sched_setaffinity_syscall,
+    ),
+    (
+        syscall_const::SCHED_GETAFFINITY_SYSCALL as u64,
+        sched_getaffinity_syscall,
+    ),
     (
         syscall_const::EPOLL_CREATE_SYSCALL as u64,
         epoll_create_syscall,
     ),
+    (
+        syscall_const::SET_TID_ADDRESS_SYSCALL as u64,
+        set_tid_address_syscall,
+    ),
+    (syscall_const::SEMTIMEDOP_SYSCALL as u64, semtimedop_syscall),
+    (syscall_const::FADVISE64_SYSCALL as u64, fadvise64_syscall),
     (
         syscall_const::CLOCK_GETTIME_SYSCALL as u64,
         clock_gettime_syscall,
@@ -156,6 +236,21 @@ pub const SYSCALL_TABLE: &[(u64, RawCallFunc)] = &[
     (syscall_const::EXIT_GROUP_SYSCALL as u64, exit_group_syscall),
     (syscall_const::EPOLL_WAIT_SYSCALL as u64, epoll_wait_syscall),
     (syscall_const::EPOLL_CTL_SYSCALL as u64, epoll_ctl_syscall),
+    (syscall_const::MQ_OPEN_SYSCALL as u64, mq_open_syscall),
+    (syscall_const::MQ_UNLINK_SYSCALL as u64, mq_unlink_syscall),
+    (
+        syscall_const::MQ_TIMEDSEND_SYSCALL as u64,
+        mq_timedsend_syscall,
+    ),
+    (
+        syscall_const::MQ_TIMEDRECEIVE_SYSCALL as u64,
+        mq_timedreceive_syscall,
+    ),
+    (syscall_const::MQ_NOTIFY_SYSCALL as u64, mq_notify_syscall),
+    (
+        syscall_const::MQ_GETSETATTR_SYSCALL as u64,
+        mq_getsetattr_syscall,
+    ),
     (syscall_const::OPENAT_SYSCALL as u64, openat_syscall),
     (syscall_const::FCHOWNAT_SYSCALL as u64, fchownat_syscall),
     (syscall_const::NEWFSTATAT_SYSCALL as u64, fstatat_syscall),
@@ -166,11 +261,24 @@ pub const SYSCALL_TABLE: &[(u64, RawCallFunc)] = &[
     (syscall_const::FCHMODAT_SYSCALL as u64, fchmodat_syscall),
     (syscall_const::FACCESSAT_SYSCALL as u64, faccessat_syscall),
     (syscall_const::PPOLL_SYSCALL as u64, ppoll_syscall),
+    (syscall_const::UNSHARE_SYSCALL as u64, unshare_syscall),
+    (
+        syscall_const::SET_ROBUST_LIST_SYSCALL as u64,
+        set_robust_list_syscall,
+    ),
+    (
+        syscall_const::GET_ROBUST_LIST_SYSCALL as u64,
+        get_robust_list_syscall,
+    ),
+    (syscall_const::SPLICE_SYSCALL as u64, splice_syscall),
+    (syscall_const::TEE_SYSCALL as u64, tee_syscall),
     (
         syscall_const::SYNC_FILE_RANGE_SYSCALL as u64,
         sync_file_range_syscall,
     ),
+    (syscall_const::VMSPLICE_SYSCALL as u64, vmsplice_syscall),
     (syscall_const::UTIMENSAT_SYSCALL as u64, utimensat_syscall),
+    (syscall_const::FALLOCATE_SYSCALL as u64, fallocate_syscall),
     (syscall_const::ACCEPT4_SYSCALL as u64, accept4_syscall),
     (syscall_const::PREADV_SYSCALL as u64, preadv_syscall),
     (syscall_const::PWRITEV_SYSCALL as u64, pwritev_syscall),
@@ -181,10 +289,16 @@ pub const SYSCALL_TABLE: &[(u64, RawCallFunc)] = &[
     (syscall_const::DUP3_SYSCALL as u64, dup3_syscall),
     (syscall_const::PIPE2_SYSCALL as u64, pipe2_syscall),
     (syscall_const::PRLIMIT64_SYSCALL as u64, prlimit64_syscall),
+    (syscall_const::GETCPU_SYSCALL as u64, getcpu_syscall),
     (syscall_const::RENAMEAT2_SYSCALL as u64, renameat2_syscall),
     (syscall_const::GETRANDOM_SYSCALL as u64, getrandom_syscall),
+    (
+        syscall_const::MEMFD_CREATE_SYSCALL as u64,
+        memfd_create_syscall,
+    ),
     (
         syscall_const::COPY_FILE_RANGE_SYSCALL as u64,
         copy_file_range_syscall,
     ),
+    (syscall_const::STATX_SYSCALL as u64, statx_syscall),
 ];
@@ -0,0 +1,293 @@
+//! Host names and resolver configuration as cages see them
+//!
+//! Cages never see or change the host's hostname. Each cage is in a UTS namespace holding its
+//! hostname: the first cage starts in one named by `--hostname` (the host's name by default),
+//! children inherit their parent's namespace on fork, and `unshare(CLONE_NEWUTS)` gives a cage a
+//! copy of its own. `sethostname` renames every cage of the namespace. `uname` reports that name
+//! as the nodename, along with the kernel release given by `--kernel-release` (the host's by
+//! default) and `wasm32` as the machine.
+//!
+//! Name resolution can be set up from lind-boot without editing lindfs:
+//!
+//! - `--add-host NAME:IP` entries are appended to `/etc/hosts`, as is `127.0.1.1 NAME` for the
+//!   `--hostname` NAME, so that cages can resolve their own name.
+//! - `--dns IP` replaces the `nameserver` lines of `/etc/resolv.conf`; `search` and `options`
+//!   lines are kept.
+//!
+//! A cage opening one of these files read-only gets a sealed memfd holding the lindfs file (if
+//! any) with those changes, made when the file is opened. Writes, `stat` and `access` still go to
+//! the lindfs file.
+use cage::get_cage;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use std::ffi::CStr;
+use std::fs;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use sysdefs::constants::err_const::{get_errno, Errno};
+
+/// The namespace the first cage starts in.
+pub const INITIAL_UTSNS: u64 = 0;
+
+/// Longest hostname `sethostname` accepts, as on Linux.
+pub const HOST_NAME_MAX: usize = 64;
+
+/// Length of each field of `struct utsname`, including the terminating NUL.
+const UTSNAME_LENGTH: usize = 65;
+
+/// Machine `uname` reports: cages run wasm32 code whatever the host is.
+const MACHINE: &[u8] = b"wasm32";
+
+const HOSTS_PATH: &[u8] = b"/etc/hosts";
+const RESOLV_CONF_PATH: &[u8] = b"/etc/resolv.conf";
+
+/// A `--add-host NAME:IP` entry. The name is split off at the first ':', so an IPv6 address
+/// needs no brackets, as in `db:fd00::2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostEntry {
+    pub name: String,
+    pub ip: IpAddr,
+}
+
+impl FromStr for HostEntry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (name, ip) = s
+            .split_once(':')
+            .ok_or_else(|| "expected NAME:IP".to_string())?;
+        if name.is_empty() || name.contains(char::is_whitespace) || name.contains('#') {
+            return Err(format!("invalid host name '{}'", name));
+        }
+        let ip = ip
+            .parse()
+            .map_err(|_| format!("invalid address '{}'", ip))?;
+        Ok(HostEntry {
+            name: name.to_string(),
+            ip,
+        })
+    }
+}
+
+/// Checks a `--hostname` value the way `sethostname` would.
+pub fn check_hostname(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > HOST_NAME_MAX {
+        return Err(format!(
+            "hostname must be 1 to {} bytes long",
+            HOST_NAME_MAX
+        ));
+    }
+    Ok(())
+}
+
+/// Host name settings chosen when lind-boot starts (see `configure`).
+#[derive(Debug, Clone, Default)]
+pub struct UtsConfig {
+    /// Hostname of the first cage's namespace, instead of the host's.
+    pub hostname: Option<String>,
+    /// Kernel release reported by `uname`, instead of the host's.
+    pub release: Option<String>,
+    /// Entries added to `/etc/hosts`.
+    pub hosts: Vec<HostEntry>,
+    /// Name servers that replace those of `/etc/resolv.conf`.
+    pub dns: Vec<IpAddr>,
+}
+
+static CONFIG: OnceLock<UtsConfig> = OnceLock::new();
+
+// Namespace ids are never reused, so a stale id can't alias a new namespace
+static NEXT_UTSNS: AtomicU64 = AtomicU64::new(INITIAL_UTSNS + 1);
+
+lazy_static! {
+    // Hostname of each namespace that has one set. <utsns, hostname>
+    static ref HOSTNAMES: DashMap<u64, Vec<u8>> = DashMap::new();
+}
+
+/// Sets the host name configuration. Must be called before `rawposix_start`; later calls have
+/// no effect.
+pub fn configure(config: UtsConfig) {
+    let _ = CONFIG.set(config);
+}
+
+fn config() -> &'static UtsConfig {
+    CONFIG.get_or_init(UtsConfig::default)
+}
+
+fn host_errno() -> Errno {
+    Errno::from_discriminant(get_errno()).unwrap_or(Errno::EIO)
+}
+
+/// The host's `uname`, read once.
+fn host_uname() -> &'static libc::utsname {
+    static HOST: OnceLock<libc::utsname> = OnceLock::new();
+    HOST.get_or_init(|| {
+        let mut buf: libc::utsname = unsafe { std::mem::zeroed() };
+        unsafe { libc::uname(&mut buf) };
+        buf
+    })
+}
+
+/// The bytes of a NUL-terminated `utsname` field.
+fn field_bytes(field: &[libc::c_char]) -> &[u8] {
+    let bytes = unsafe { &*(field as *const [libc::c_char] as *const [u8]) };
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    &bytes[..end]
+}
+
+/// Hostname of the first cage's namespace until it calls `sethostname`.
+fn initial_hostname() -> Vec<u8> {
+    match &config().hostname {
+        Some(name) => name.as_bytes().to_vec(),
+        None => field_bytes(&host_uname().nodename).to_vec(),
+    }
+}
+
+fn cage_utsns(cageid: u64) -> u64 {
+    get_cage(cageid).map_or(INITIAL_UTSNS, |cage| cage.utsns.load(Ordering::Relaxed))
+}
+
+/// The hostname of the cage's namespace, without a terminating NUL.
+pub fn hostname(cageid: u64) -> Vec<u8> {
+    let utsns = cage_utsns(cageid);
+    match HOSTNAMES.get(&utsns) {
+        Some(name) => name.clone(),
+        None => initial_hostname(),
+    }
+}
+
+/// Renames the cage's namespace. The caller checks the length against `HOST_NAME_MAX`.
+pub fn set_hostname(cageid: u64, name: &[u8]) {
+    HOSTNAMES.insert(cage_utsns(cageid), name.to_vec());
+}
+
+/// Allocates a new namespace, named like the cage's current one.
+pub fn new_utsns(cageid: u64) -> u64 {
+    let utsns = NEXT_UTSNS.fetch_add(1, Ordering::Relaxed);
+    HOSTNAMES.insert(utsns, hostname(cageid));
+    utsns
+}
+
+/// Copies `value` into a `utsname` field, truncating it to leave room for the NUL.
+fn set_field(field: &mut [libc::c_char; UTSNAME_LENGTH], value: &[u8]) {
+    let len = value.len().min(UTSNAME_LENGTH - 1);
+    for (dst, &src) in field.iter_mut().zip(&value[..len]) {
+        *dst = src as libc::c_char;
+    }
+    field[len..].fill(0);
+}
+
+/// The `struct utsname` the cage sees: the host's, with the cage's hostname as the nodename,
+/// the configured release, the lind machine and no NIS domain.
+pub fn utsname(cageid: u64) -> libc::utsname {
+    let mut buf = *host_uname();
+    set_field(&mut buf.nodename, &hostname(cageid));
+    if let Some(release) = &config().release {
+        set_field(&mut buf.release, release.as_bytes());
+    }
+    set_field(&mut buf.machine, MACHINE);
+    set_field(&mut buf.domainname, b"(none)");
+    buf
+}
+
+/// Adds the configured entries to the lindfs `/etc/hosts`.
+fn hosts_overlay(base: Vec<u8>) -> Vec<u8> {
+    let config = config();
+    let mut out = base;
+    if !out.is_empty() && !out.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+    out.extend_from_slice(b"# Added by lind-boot\n");
+    if let Some(name) = &config.hostname {
+        out.extend_from_slice(format!("127.0.1.1\t{}\n", name).as_bytes());
+    }
+    for entry in &config.hosts {
+        out.extend_from_slice(format!("{}\t{}\n", entry.ip, entry.name).as_bytes());
+    }
+    out
+}
+
+/// Replaces the name servers of the lindfs `/etc/resolv.conf` with the configured ones.
+fn resolv_conf_overlay(base: Vec<u8>) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::new();
+    for line in base.split_inclusive(|&b| b == b'\n') {
+        let is_nameserver = line
+            .trim_ascii_start()
+            .strip_prefix(b"nameserver")
+            .is_some_and(|rest| rest.first().is_some_and(u8::is_ascii_whitespace));
+        if !is_nameserver {
+            out.extend_from_slice(line);
+        }
+    }
+    if !out.is_empty() && !out.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+    out.extend_from_slice(b"# Added by lind-boot\n");
+    for ip in &config().dns {
+        out.extend_from_slice(format!("nameserver {}\n", ip).as_bytes());
+    }
+    out
+}
+
+/// A read-only, sealed memfd holding `contents`, positioned at the start.
+fn sealed_memfd(contents: &[u8]) -> Result<i32, Errno> {
+    let fd = unsafe {
+        libc::memfd_create(
+            c"lind-etc".as_ptr(),
+            libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING,
+        )
+    };
+    if fd < 0 {
+        return Err(host_errno());
+    }
+    let mut written = 0;
+    while written < contents.len() {
+        let ret = unsafe {
+            libc::write(
+                fd,
+                contents[written..].as_ptr() as *const libc::c_void,
+                contents.len() - written,
+            )
+        };
+        if ret < 0 {
+            let errno = host_errno();
+            unsafe { libc::close(fd) };
+            return Err(errno);
+        }
+        written += ret as usize;
+    }
+    let seals = libc::F_SEAL_SEAL | libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE;
+    unsafe {
+        libc::fchmod(fd, 0o644);
+        libc::fcntl(fd, libc::F_ADD_SEALS, seals);
+        libc::lseek(fd, 0, libc::SEEK_SET);
+    }
+    Ok(fd)
+}
+
+/// Opens the overlay for `path` (an absolute, normalized path) if it has one and `oflag` only
+/// asks to read it. Returns the new kernel fd, or `None` to open the lindfs file as usual.
+pub(crate) fn open_overlay(path: &CStr, oflag: i32) -> Option<Result<i32, Errno>> {
+    if oflag & libc::O_ACCMODE != libc::O_RDONLY || oflag & libc::O_DIRECTORY != 0 {
+        return None;
+    }
+    let config = config();
+    let overlay: fn(Vec<u8>) -> Vec<u8> = match path.to_bytes() {
+        HOSTS_PATH if config.hostname.is_some() || !config.hosts.is_empty() => hosts_overlay,
+        RESOLV_CONF_PATH if !config.dns.is_empty() => resolv_conf_overlay,
+        _ => return None,
+    };
+    let path = std::str::from_utf8(path.to_bytes()).ok()?;
+    let base = match fs::read(path) {
+        Ok(base) => base,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => {
+            let errno = e
+                .raw_os_error()
+                .and_then(|e| Errno::from_discriminant(e).ok());
+            return Some(Err(errno.unwrap_or(Errno::EIO)));
+        }
+    };
+    Some(sealed_memfd(&overlay(base)))
+}
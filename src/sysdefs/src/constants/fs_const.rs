@@ -55,6 +55,7 @@ pub const S_IRWXO: u32 = 0o007; // Others read, write, execute
 pub const S_IROTH: u32 = 0o004; // Others read
 pub const S_IWOTH: u32 = 0o002; // Others write
 pub const S_IXOTH: u32 = 0o001; // Others execute
+pub const DEFAULT_UMASK: u32 = 0o022; // Creation mask of the init cage, same as a Linux login shell
 
 //Commands for FCNTL
 // Source: include/linux/fcntl.h
@@ -139,6 +140,58 @@ pub const SHMSEG: u32 = SHMMNI; // Maximum segments per process
 
 pub const SEM_VALUE_MAX: u32 = 2147483647; // Maximum value for a semaphore
 
+// Source: include/uapi/linux/ipc.h
+pub const IPC_NOWAIT: i32 = 0o4000; // Return an error instead of blocking
+pub const IPC_64: i32 = 0x100; // New-style ipc structures, set by glibc in every *ctl command
+
+// Source: include/uapi/linux/sem.h
+pub const SEM_UNDO: i16 = 0x1000; // Undo the operation when the process exits
+pub const GETPID: i32 = 11; // semctl commands
+pub const GETVAL: i32 = 12;
+pub const GETALL: i32 = 13;
+pub const GETNCNT: i32 = 14;
+pub const GETZCNT: i32 = 15;
+pub const SETVAL: i32 = 16;
+pub const SETALL: i32 = 17;
+
+pub const SEMMNI: usize = 32000; // Maximum number of semaphore sets system wide
+pub const SEMMSL: i32 = 32000; // Maximum semaphores per set
+pub const SEMOPM: usize = 500; // Maximum operations per semop call
+pub const SEMVMX: i32 = 32767; // Maximum semaphore value
+
+// Source: include/uapi/linux/msg.h
+pub const MSG_NOERROR: i32 = 0o10000; // Truncate messages that are too long
+pub const MSG_EXCEPT: i32 = 0o20000; // Receive the first message not of the given type
+
+pub const MSGMNI: usize = 32000; // Maximum number of message queues system wide
+pub const MSGMAX: usize = 8192; // Maximum size of one message
+pub const MSGMNB: usize = 16384; // Default maximum size of a queue in bytes
+
+// Source: include/uapi/linux/mqueue.h and ipc/mqueue.c
+pub const MQ_PRIO_MAX: u32 = 32768; // Message priorities are below this
+pub const MQ_MAXMSG_DEFAULT: i32 = 10; // Default and unprivileged limit of mq_maxmsg
+pub const MQ_MSGSIZE_DEFAULT: i32 = 8192; // Default and unprivileged limit of mq_msgsize
+pub const MQ_MAXMSG_HARD: i32 = 65536; // Limit of mq_maxmsg for root
+pub const MQ_MSGSIZE_HARD: i32 = 16 * 1024 * 1024; // Limit of mq_msgsize for root
+pub const MQ_QUEUES_MAX: usize = 256; // Maximum number of message queues system wide
+pub const MQ_NAME_MAX: usize = 255; // Longest queue name, without the leading slash
+
+pub const NOTIFY_COOKIE_LEN: usize = 32; // Length of a SIGEV_THREAD notification cookie
+pub const NOTIFY_WOKENUP: u8 = 1; // Cookie status: a message arrived
+pub const NOTIFY_REMOVED: u8 = 2; // Cookie status: the registration went away
+
+// Source: include/uapi/linux/memfd.h and mm/memfd.c
+pub const MFD_CLOEXEC: u32 = 0x0001; // Set close-on-exec on the new descriptor
+pub const MFD_ALLOW_SEALING: u32 = 0x0002; // Allow F_ADD_SEALS on the file
+pub const MFD_NAME_MAX: usize = 249; // Longest memfd name
+
+pub const SHM_NAME_MAX: usize = 255; // Longest name of a POSIX shared memory object
+
+// Source: include/uapi/asm-generic/siginfo.h
+pub const SIGEV_SIGNAL: i32 = 0; // Notify by sending a signal
+pub const SIGEV_NONE: i32 = 1; // No notification
+pub const SIGEV_THREAD: i32 = 2; // glibc runs a function in a new thread
+
 // ===== Memory Protection Flags =====
 // Source: include/uapi/asm-generic/mman-common.h
 pub const PROT_NONE: i32 = 0x0; // Page cannot be accessed
@@ -157,6 +210,9 @@ pub const MAP_PRIVATE: u32 = 0x02; // Changes are private to this process
 pub const MAP_SHARING_MASK: u32 = 0x03; // Mask to isolate sharing bits
 pub const MAP_POPULATE: u32 = 0x8000; // Override lazy loading of pages
 pub const MAP_ANON: u32 = 0x20; // Don't use a file descriptor
+pub const MAP_GROWSDOWN: u32 = 0x0100; // Stack-like segment
+pub const MAP_NORESERVE: u32 = 0x4000; // Don't reserve swap space
+pub const MAP_STACK: u32 = 0x20000; // Mapping is used for a thread stack
 
 // ===== Page Size Constants =====
 // Note: These values are architecture-dependent
@@ -172,6 +228,24 @@ pub const MAP_FAILED: *mut std::ffi::c_void = (-1isize) as *mut std::ffi::c_void
 // Source: include/uapi/asm-generic/mman-common.h
 pub const MREMAP_MAYMOVE: u32 = 0x01; // Can relocate mapping
 pub const MREMAP_FIXED: u32 = 0x02; // New address is specified exactly
+pub const MREMAP_DONTUNMAP: u32 = 0x04; // Leave the old range mapped after a move
+
+// ===== msync Flags =====
+// Source: include/uapi/asm-generic/mman-common.h
+pub const MS_ASYNC: i32 = 1; // Schedule writeback and return
+pub const MS_INVALIDATE: i32 = 2; // Invalidate other mappings of the same file
+pub const MS_SYNC: i32 = 4; // Write back and wait for completion
+
+// ===== madvise Advice Values =====
+// Source: include/uapi/asm-generic/mman-common.h
+pub const MADV_NORMAL: i32 = 0; // No special treatment
+pub const MADV_RANDOM: i32 = 1; // Expect random page references
+pub const MADV_SEQUENTIAL: i32 = 2; // Expect sequential page references
+pub const MADV_WILLNEED: i32 = 3; // Will need these pages
+pub const MADV_DONTNEED: i32 = 4; // Don't need these pages
+pub const MADV_FREE: i32 = 8; // Free pages only if memory pressure
+pub const MADV_HUGEPAGE: i32 = 14; // Worth backing with hugepages
+pub const MADV_NOHUGEPAGE: i32 = 15; // Not worth backing with hugepages
 
 // ===== File Access Modes =====
 // Source: include/uapi/asm-generic/fcntl.h
@@ -15,21 +15,32 @@ pub const LINDFS_ROOT: &str = "/home/lind/lind-wasm/lindfs";
 /// in `fdtables`. Used to distinguish kernel-backed FDs from fully virtual ones
 /// (e.g., in-memory pipes).
 pub const FDKIND_KERNEL: u32 = 0;
+/// An AF_UNIX stream socket whose connection lives in host memory (see
+/// `rawposix::inproc_socket`). Its `underfd` is an eventfd used for readiness.
+pub const FDKIND_INPROC_SOCKET: u32 = 1;
+/// A POSIX message queue descriptor (see `rawposix::mqueue`). Its `underfd` is an eventfd used
+/// for readiness.
+pub const FDKIND_MQUEUE: u32 = 2;
 /// Maximum allowed Cage ID.  
 /// This limit is inherited from earlier implementations and may be
 /// adjusted in the future.
 pub const MAX_CAGEID: i32 = 2048;
 pub const MAXFD: usize = 1024; // Maximum file descriptors per cage
 /// Maximum linear memory size for a single Wasm module in the current lind-wasm runtime.
-/// Since lind-wasm uses 32-bit memories, the linear memory address space is limited to 4 GiB.
+/// With 32-bit memories, the linear memory address space is limited to 4 GiB.
 /// This constant represents that theoretical upper bound (0xFFFF_FFFF bytes).
 ///
 /// The implementation assumes that the allocated linear memory
 /// region is contiguous.  
 ///
-/// **This limit may be adjusted in the future if lind-wasm adopts 64-bit memories
-/// or other memory models.**
+/// 64-bit cages use `MAX_LINEAR_MEMORY64_SIZE` instead.
 pub const MAX_LINEAR_MEMORY_SIZE: u64 = 0xFFFF_FFFF;
+/// Maximum linear memory size of a 64-bit (memory64) cage.
+///
+/// A cage's linear memory is reserved whole when it is created, so that its base address never
+/// moves. 64 GiB per cage keeps `MAX_CAGEID` such reservations within the 128 TiB user address
+/// space of a 64-bit host.
+pub const MAX_LINEAR_MEMORY64_SIZE: u64 = (1 << 36) - 1;
 /// Placeholder for unused syscall argument
 pub const UNUSED_ARG: u64 = 0xDEADBEEF_DEADBEEF;
 /// Placeholder for unused cage/grate ID
@@ -100,6 +111,10 @@ pub const MAIN_THREADID: u64 = 1;
 /// Number of instances to pre-allocate for the initial cage
 pub const INSTANCE_NUMBER: usize = 5000;
 
+/// Custom section of a module pre-initialized by `lind-boot --preinit`, holding the memory its
+/// init function left, which is restored in place of the module's own when a cage loads it.
+pub const LIND_PREINIT_SECTION: &str = "lind.preinit";
+
 /// Maximum execve recursion depth for shebang execution, 4 is the typical value used in Linux.
 pub const MAX_SHEBANG_DEPTH: i32 = 4;
 
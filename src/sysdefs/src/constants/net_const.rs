@@ -256,6 +256,68 @@ pub const MSG_BATCH: i32 = 0x40000; // sendmmsg(): more messages coming
 pub const MSG_EOF: i32 = MSG_FIN; // Alias for MSG_FIN
 pub const MSG_NO_SHARED_FRAGS: i32 = 0x80000; // sendpage() internal: no shared frags
 pub const MSG_SENDPAGE_DECRYPTED: i32 = 0x100000; // sendpage() internal: page needs encryption
+pub const MSG_CMSG_CLOEXEC: i32 = 0x40000000; // Set close-on-exec on fds received via SCM_RIGHTS
+
+// ===== Ancillary Data Types =====
+// Source: include/linux/socket.h
+pub const SCM_RIGHTS: i32 = 0x01; // Transfer file descriptors (array of int)
+pub const SCM_CREDENTIALS: i32 = 0x02; // Transfer process credentials (struct ucred)
+
+// ===== Socket Diagnostics =====
+// Source: include/uapi/linux/sock_diag.h, include/uapi/linux/unix_diag.h
+pub const NETLINK_SOCK_DIAG: i32 = 4; // Netlink protocol for socket monitoring
+pub const SOCK_DIAG_BY_FAMILY: u16 = 20; // Request type: query sockets of one family
+pub const UDIAG_SHOW_PEER: u32 = 0x00000004; // Report the inode of the peer socket
+pub const UNIX_DIAG_PEER: u16 = 2; // Attribute carrying the peer inode (u32)
+
+// ===== Route Netlink =====
+// Source: include/uapi/linux/netlink.h, include/uapi/linux/rtnetlink.h,
+// include/uapi/linux/if_link.h, include/uapi/linux/if_addr.h, include/uapi/linux/if.h
+pub const NETLINK_ROUTE: i32 = 0; // Netlink protocol for routing and link configuration
+pub const SOL_NETLINK: i32 = 270; // Netlink socket option level
+
+pub const NLMSG_NOOP: u16 = 0x1; // Nothing to do
+pub const NLMSG_ERROR: u16 = 0x2; // Error or acknowledgement (struct nlmsgerr)
+pub const NLMSG_DONE: u16 = 0x3; // End of a multipart message
+pub const NLM_F_REQUEST: u16 = 0x01; // Message is a request
+pub const NLM_F_MULTI: u16 = 0x02; // Part of a multipart message
+pub const NLM_F_ACK: u16 = 0x04; // Reply with an acknowledgement
+pub const NLM_F_CAPPED: u16 = 0x100; // Error reply echoes only the request header
+pub const NLM_F_DUMP: u16 = 0x300; // Dump every object (NLM_F_ROOT | NLM_F_MATCH)
+
+pub const RTM_BASE: u16 = 16; // First rtnetlink message type
+pub const RTM_NEWLINK: u16 = 16; // Link description
+pub const RTM_GETLINK: u16 = 18; // Query links
+pub const RTM_NEWADDR: u16 = 20; // Address description
+pub const RTM_GETADDR: u16 = 22; // Query addresses
+
+pub const IFLA_ADDRESS: u16 = 1; // Hardware address
+pub const IFLA_BROADCAST: u16 = 2; // Hardware broadcast address
+pub const IFLA_IFNAME: u16 = 3; // Interface name (NUL-terminated)
+pub const IFLA_MTU: u16 = 4; // MTU (u32)
+pub const IFLA_OPERSTATE: u16 = 16; // RFC 2863 operational state (u8)
+
+pub const IFA_ADDRESS: u16 = 1; // Prefix address (peer address on point-to-point links)
+pub const IFA_LOCAL: u16 = 2; // Local address
+pub const IFA_LABEL: u16 = 3; // Interface label (NUL-terminated)
+pub const IFA_BROADCAST: u16 = 4; // Broadcast address
+pub const IFA_F_PERMANENT: u8 = 0x80; // Address configured statically
+
+pub const RT_SCOPE_UNIVERSE: u8 = 0; // Global address
+pub const RT_SCOPE_LINK: u8 = 253; // Valid on the link only
+pub const RT_SCOPE_HOST: u8 = 254; // Valid on this host only
+
+pub const IF_OPER_UNKNOWN: u8 = 0; // Operational state unknown (loopback)
+pub const IF_OPER_DOWN: u8 = 2; // Interface down
+pub const IF_OPER_UP: u8 = 6; // Interface up
+pub const ARPHRD_LOOPBACK: u16 = 772; // Loopback device type
+
+pub const IFF_UP: u32 = 0x1; // Interface is up
+pub const IFF_BROADCAST: u32 = 0x2; // Broadcast address valid
+pub const IFF_LOOPBACK: u32 = 0x8; // Loopback interface
+pub const IFF_POINTOPOINT: u32 = 0x10; // Point-to-point link
+pub const IFF_RUNNING: u32 = 0x40; // Resources allocated
+pub const IFF_LOWER_UP: u32 = 0x10000; // Carrier present
 
 // ===== Shutdown Constants =====
 // Source: include/linux/socket.h
@@ -289,23 +351,72 @@ pub const SO_RCVLOWAT: i32 = 18; // Receive low-water mark
 pub const SO_SNDLOWAT: i32 = 19; // Send low-water mark
 pub const SO_RCVTIMEO_OLD: i32 = 20; // Receive timeout (old)
 pub const SO_SNDTIMEO_OLD: i32 = 21; // Send timeout (old)
+pub const SO_BINDTODEVICE: i32 = 25; // Bind to a network interface
 pub const SO_PEERNAME: i32 = 28; // Name of connected peer
 pub const SO_ACCEPTCONN: i32 = 30; // Socket has had listen()
+pub const SO_PROTOCOL: i32 = 38; // Get socket protocol
+pub const SO_DOMAIN: i32 = 39; // Get socket domain
+pub const SO_RCVTIMEO_NEW: i32 = 66; // Receive timeout (64-bit time)
+pub const SO_SNDTIMEO_NEW: i32 = 67; // Send timeout (64-bit time)
 
 // ===== TCP Options =====
 // Source: include/uapi/linux/tcp.h
 pub const SOL_TCP: i32 = IPPROTO_TCP; // TCP protocol level
 pub const SOL_UDP: i32 = IPPROTO_UDP; // UDP protocol level
 
-pub const TCP_NODELAY: i32 = 0x01; // Don't delay send to coalesce packets
-pub const TCP_MAXSEG: i32 = 0x02; // Set maximum segment size
-pub const TCP_NOPUSH: i32 = 0x04; // Don't push last block of write
-pub const TCP_NOOPT: i32 = 0x08; // Don't use TCP options
-pub const TCP_KEEPALIVE: i32 = 0x10; // Idle time for keepalive
-pub const TCP_CONNECTIONTIMEOUT: i32 = 0x20; // Connection timeout
-pub const PERSIST_TIMEOUT: i32 = 0x40; // Persist timeout
-pub const TCP_RXT_CONNDROPTIME: i32 = 0x80; // Retransmission timeout before drop
-pub const TCP_RXT_FINDROP: i32 = 0x100; // Drop after 3 FIN retransmissions
+pub const TCP_NODELAY: i32 = 1; // Don't delay send to coalesce packets
+pub const TCP_MAXSEG: i32 = 2; // Set maximum segment size
+pub const TCP_CORK: i32 = 3; // Don't send partial frames
+pub const TCP_KEEPIDLE: i32 = 4; // Idle time before keepalive probes
+pub const TCP_KEEPINTVL: i32 = 5; // Interval between keepalive probes
+pub const TCP_KEEPCNT: i32 = 6; // Number of keepalive probes
+pub const TCP_SYNCNT: i32 = 7; // Number of SYN retransmits
+pub const TCP_LINGER2: i32 = 8; // Lifetime of orphaned FIN_WAIT2 sockets
+pub const TCP_DEFER_ACCEPT: i32 = 9; // Wake up listener only when data arrives
+pub const TCP_WINDOW_CLAMP: i32 = 10; // Bound advertised window
+pub const TCP_INFO: i32 = 11; // Connection information (struct tcp_info)
+pub const TCP_QUICKACK: i32 = 12; // Disable delayed ACKs
+pub const TCP_CONGESTION: i32 = 13; // Congestion control algorithm name
+pub const TCP_USER_TIMEOUT: i32 = 18; // How long sent data may stay unacknowledged
+pub const TCP_FASTOPEN: i32 = 23; // TCP Fast Open queue length
+pub const TCP_NOTSENT_LOWAT: i32 = 25; // Unsent-data low-water mark
+
+// ===== IP Options =====
+// Source: include/uapi/linux/in.h
+pub const IP_TOS: i32 = 1; // Type of service
+pub const IP_TTL: i32 = 2; // Time to live
+pub const IP_HDRINCL: i32 = 3; // Header is included with data
+pub const IP_OPTIONS: i32 = 4; // IP header options
+pub const IP_PKTINFO: i32 = 8; // Receive IP_PKTINFO control messages
+pub const IP_MTU_DISCOVER: i32 = 10; // Path MTU discovery mode
+pub const IP_RECVERR: i32 = 11; // Queue extended errors
+pub const IP_RECVTTL: i32 = 12; // Receive IP_TTL control messages
+pub const IP_RECVTOS: i32 = 13; // Receive IP_TOS control messages
+pub const IP_MTU: i32 = 14; // Current path MTU
+pub const IP_FREEBIND: i32 = 15; // Allow binding to non-local addresses
+pub const IP_MULTICAST_IF: i32 = 32; // Outgoing multicast interface
+pub const IP_MULTICAST_TTL: i32 = 33; // Multicast time to live
+pub const IP_MULTICAST_LOOP: i32 = 34; // Loop multicast back to local sockets
+pub const IP_ADD_MEMBERSHIP: i32 = 35; // Join a multicast group
+pub const IP_DROP_MEMBERSHIP: i32 = 36; // Leave a multicast group
+pub const IP_ADD_SOURCE_MEMBERSHIP: i32 = 39; // Join a source-specific group
+pub const IP_DROP_SOURCE_MEMBERSHIP: i32 = 40; // Leave a source-specific group
+
+// ===== IPv6 Options =====
+// Source: include/uapi/linux/in6.h
+pub const IPV6_UNICAST_HOPS: i32 = 16; // Unicast hop limit
+pub const IPV6_MULTICAST_IF: i32 = 17; // Outgoing multicast interface
+pub const IPV6_MULTICAST_HOPS: i32 = 18; // Multicast hop limit
+pub const IPV6_MULTICAST_LOOP: i32 = 19; // Loop multicast back to local sockets
+pub const IPV6_ADD_MEMBERSHIP: i32 = 20; // Join a multicast group
+pub const IPV6_DROP_MEMBERSHIP: i32 = 21; // Leave a multicast group
+pub const IPV6_MTU_DISCOVER: i32 = 23; // Path MTU discovery mode
+pub const IPV6_MTU: i32 = 24; // Current path MTU
+pub const IPV6_RECVERR: i32 = 25; // Queue extended errors
+pub const IPV6_V6ONLY: i32 = 26; // Restrict to IPv6 communication only
+pub const IPV6_RECVPKTINFO: i32 = 49; // Receive IPV6_PKTINFO control messages
+pub const IPV6_RECVTCLASS: i32 = 66; // Receive IPV6_TCLASS control messages
+pub const IPV6_TCLASS: i32 = 67; // Traffic class
 
 // ===== Poll Constants =====
 // Source: include/uapi/asm-generic/poll.h
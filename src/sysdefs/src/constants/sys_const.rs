@@ -15,9 +15,10 @@ pub const DEFAULT_GID: u32 = 1000; // Default group ID
 // Source: include/uapi/asm-generic/resource.h
 pub const SIGNAL_MAX: i32 = 64; // Maximum number of signals
 
-// File descriptor limits
+// File descriptor limits (defaults; each cage's limits are kept and enforced by fdtables,
+// which mirrors these as FD_PER_PROCESS_MAX and FD_PER_PROCESS_HARD_MAX)
 pub const NOFILE_CUR: u64 = 1024; // Soft limit for number of open files
-pub const NOFILE_MAX: u64 = 4 * 1024; // Hard limit for number of open files
+pub const NOFILE_MAX: u64 = 64 * 1024; // Hard limit for number of open files
 
 // Stack size limits
 pub const STACK_CUR: u64 = 8192 * 1024; // Soft limit for stack size (8MB)
@@ -34,6 +35,16 @@ pub const RLIMIT_NPROC: u32 = 6;
 pub const RLIMIT_NOFILE: u32 = 7;
 pub const RLIMIT_MEMLOCK: u32 = 8;
 pub const RLIMIT_AS: u32 = 9;
+pub const RLIM_INFINITY: u64 = u64::MAX; // No limit (struct rlimit64)
+
+// ===== Scheduling =====
+// Source: include/uapi/linux/resource.h and include/uapi/linux/sched.h
+pub const PRIO_PROCESS: i32 = 0; // getpriority/setpriority: who is a process
+pub const PRIO_PGRP: i32 = 1; // who is a process group
+pub const PRIO_USER: i32 = 2; // who is a user
+pub const NICE_MIN: i32 = -20; // Highest priority
+pub const NICE_MAX: i32 = 19; // Lowest priority
+pub const SCHED_OTHER: i32 = 0; // Default time-sharing policy
 
 // ===== Process Exit Status =====
 // Source: <stdlib.h> and POSIX standard
@@ -192,6 +203,23 @@ pub const FUTEX_WAKE_BITSET: i32 = 10;
 pub const FUTEX_WAIT_REQUEUE_PI: i32 = 11;
 pub const FUTEX_CMP_REQUEUE_PI: i32 = 12;
 pub const FUTEX_LOCK_PI2: i32 = 13;
+pub const FUTEX_PRIVATE_FLAG: i32 = 128;
+pub const FUTEX_CLOCK_REALTIME: i32 = 256;
+pub const FUTEX_CMD_MASK: i32 = !(FUTEX_PRIVATE_FLAG | FUTEX_CLOCK_REALTIME);
+pub const FUTEX_BITSET_MATCH_ANY: u32 = 0xffffffff;
+
+// Futex word bits of robust and PI mutexes
+pub const FUTEX_WAITERS: u32 = 0x80000000;
+pub const FUTEX_OWNER_DIED: u32 = 0x40000000;
+pub const FUTEX_TID_MASK: u32 = 0x3fffffff;
+// Size of struct robust_list_head in a cage: list.next, futex_offset and list_op_pending, all
+// 32-bit
+pub const ROBUST_LIST_HEAD_SIZE: usize = 12;
+// Size of struct robust_list_head in a 64-bit cage, where all three are 64-bit
+pub const ROBUST_LIST_HEAD_SIZE64: usize = 24;
+// Most robust list entries walked when a thread dies, so a corrupt or circular list cannot
+// hang its exit
+pub const ROBUST_LIST_LIMIT: usize = 2048;
 
 /* Cloning flags.  */
 pub const CSIGNAL: u64 = 0x000000ff; /* Signal mask to be sent at exit.  */
@@ -223,6 +251,20 @@ pub const CLONE_IO: u64 = 0x80000000; /* Clone I/O context.  */
 clone3 syscalls.  */
 pub const CLONE_NEWTIME: u64 = 0x00000080; /* New time namespace */
 
+/* posix_spawn file action tags, in the order of glibc's `struct __spawn_action`.  */
+pub const SPAWN_DO_CLOSE: i32 = 0;
+pub const SPAWN_DO_DUP2: i32 = 1;
+pub const SPAWN_DO_OPEN: i32 = 2;
+pub const SPAWN_DO_CHDIR: i32 = 3;
+pub const SPAWN_DO_FCHDIR: i32 = 4;
+pub const SPAWN_DO_CLOSEFROM: i32 = 5;
+pub const SPAWN_DO_TCSETPGRP: i32 = 6;
+/* posix_spawnattr flags.  */
+pub const POSIX_SPAWN_SETSIGMASK: i32 = 0x08; /* Use the mask given in the attributes.  */
+/* glibc-internal spawn flags.  */
+pub const SPAWN_XFLAGS_TRY_SHELL: i32 = 0x2; /* Run a program that fails with ENOEXEC with the shell.  */
+pub const PATH_BSHELL: &str = "/bin/sh";
+
 // dlopen mode constants
 pub const RTLD_LAZY: i32 = 0x00001; /* Lazy function call binding.  */
 pub const RTLD_NOW: i32 = 0x00002; /* Immediate function call binding.  */
@@ -30,6 +30,10 @@ pub const ACCESS_SYSCALL: i32 = 21;
 pub const PIPE_SYSCALL: i32 = 22;
 pub const SELECT_SYSCALL: i32 = 23;
 pub const SCHED_YIELD_SYSCALL: i32 = 24;
+pub const MREMAP_SYSCALL: i32 = 25;
+pub const MSYNC_SYSCALL: i32 = 26;
+pub const MINCORE_SYSCALL: i32 = 27;
+pub const MADVISE_SYSCALL: i32 = 28;
 pub const SHMGET_SYSCALL: i32 = 29;
 pub const SHMAT_SYSCALL: i32 = 30;
 pub const SHMCTL_SYSCALL: i32 = 31;
@@ -38,6 +42,7 @@ pub const DUP2_SYSCALL: i32 = 33;
 pub const NANOSLEEP_SYSCALL: i32 = 35;
 pub const SETITIMER_SYSCALL: i32 = 38;
 pub const GETPID_SYSCALL: i32 = 39;
+pub const SENDFILE_SYSCALL: i32 = 40;
 pub const SOCKET_SYSCALL: i32 = 41;
 pub const CONNECT_SYSCALL: i32 = 42;
 pub const ACCEPT_SYSCALL: i32 = 43;
@@ -59,7 +64,14 @@ pub const EXEC_SYSCALL: i32 = 59;
 pub const EXIT_SYSCALL: i32 = 60;
 pub const WAITPID_SYSCALL: i32 = 61;
 pub const KILL_SYSCALL: i32 = 62;
+pub const UNAME_SYSCALL: i32 = 63;
+pub const SEMGET_SYSCALL: i32 = 64;
+pub const SEMCTL_SYSCALL: i32 = 66;
 pub const SHMDT_SYSCALL: i32 = 67;
+pub const MSGGET_SYSCALL: i32 = 68;
+pub const MSGSND_SYSCALL: i32 = 69;
+pub const MSGRCV_SYSCALL: i32 = 70;
+pub const MSGCTL_SYSCALL: i32 = 71;
 pub const FCNTL_SYSCALL: i32 = 72;
 pub const FLOCK_SYSCALL: i32 = 73;
 pub const FSYNC_SYSCALL: i32 = 74;
@@ -81,6 +93,8 @@ pub const CHMOD_SYSCALL: i32 = 90;
 pub const FCHMOD_SYSCALL: i32 = 91;
 pub const CHOWN_SYSCALL: i32 = 92;
 pub const LCHOWN_SYSCALL: i32 = 94;
+pub const UMASK_SYSCALL: i32 = 95;
+pub const SYSINFO_SYSCALL: i32 = 99;
 pub const GETUID_SYSCALL: i32 = 102;
 pub const GETGID_SYSCALL: i32 = 104;
 pub const GETEUID_SYSCALL: i32 = 107;
@@ -90,13 +104,48 @@ pub const GETPGID_SYSCALL: i32 = 121;
 pub const MKNOD_SYSCALL: i32 = 133;
 pub const STATFS_SYSCALL: i32 = 137;
 pub const FSTATFS_SYSCALL: i32 = 138;
+pub const GETPRIORITY_SYSCALL: i32 = 140;
+pub const SETPRIORITY_SYSCALL: i32 = 141;
+pub const SCHED_GETSCHEDULER_SYSCALL: i32 = 145;
+pub const MLOCK_SYSCALL: i32 = 149;
+pub const MUNLOCK_SYSCALL: i32 = 150;
 pub const GETHOSTNAME_SYSCALL: i32 = 170;
+// Linux has no gethostname syscall, and lind gave it sethostname's number, so sethostname is
+// numbered after the Lind-specific calls instead.
+pub const SETHOSTNAME_SYSCALL: i32 = 1004;
+// Lind-specific: creates a cage running a program directly, the fast path of posix_spawn
+pub const SPAWN_SYSCALL: i32 = 1005;
+// Lind-specific: saves the calling cage to a snapshot file that lind-boot --restore resumes from
+pub const CHECKPOINT_SYSCALL: i32 = 1006;
+pub const SETXATTR_SYSCALL: i32 = 188;
+pub const LSETXATTR_SYSCALL: i32 = 189;
+pub const FSETXATTR_SYSCALL: i32 = 190;
+pub const GETXATTR_SYSCALL: i32 = 191;
+pub const LGETXATTR_SYSCALL: i32 = 192;
+pub const FGETXATTR_SYSCALL: i32 = 193;
+pub const LISTXATTR_SYSCALL: i32 = 194;
+pub const LLISTXATTR_SYSCALL: i32 = 195;
+pub const FLISTXATTR_SYSCALL: i32 = 196;
+pub const REMOVEXATTR_SYSCALL: i32 = 197;
+pub const LREMOVEXATTR_SYSCALL: i32 = 198;
+pub const FREMOVEXATTR_SYSCALL: i32 = 199;
 pub const FUTEX_SYSCALL: i32 = 202;
+pub const SCHED_SETAFFINITY_SYSCALL: i32 = 203;
+pub const SCHED_GETAFFINITY_SYSCALL: i32 = 204;
 pub const EPOLL_CREATE_SYSCALL: i32 = 213;
+pub const SET_TID_ADDRESS_SYSCALL: i32 = 218;
+pub const SEMTIMEDOP_SYSCALL: i32 = 220;
+pub const FADVISE64_SYSCALL: i32 = 221;
 pub const EXIT_GROUP_SYSCALL: i32 = 231;
 pub const CLOCK_GETTIME_SYSCALL: i32 = 228;
 pub const EPOLL_WAIT_SYSCALL: i32 = 232;
 pub const EPOLL_CTL_SYSCALL: i32 = 233;
+pub const MQ_OPEN_SYSCALL: i32 = 240;
+pub const MQ_UNLINK_SYSCALL: i32 = 241;
+pub const MQ_TIMEDSEND_SYSCALL: i32 = 242;
+pub const MQ_TIMEDRECEIVE_SYSCALL: i32 = 243;
+pub const MQ_NOTIFY_SYSCALL: i32 = 244;
+pub const MQ_GETSETATTR_SYSCALL: i32 = 245;
 pub const OPENAT_SYSCALL: i32 = 257;
 pub const FCHOWNAT_SYSCALL: i32 = 260;
 pub const NEWFSTATAT_SYSCALL: i32 = 262;
@@ -107,17 +156,26 @@ pub const READLINKAT_SYSCALL: i32 = 267;
 pub const FCHMODAT_SYSCALL: i32 = 268;
 pub const FACCESSAT_SYSCALL: i32 = 269;
 pub const PPOLL_SYSCALL: i32 = 271;
+pub const UNSHARE_SYSCALL: i32 = 272;
+pub const SET_ROBUST_LIST_SYSCALL: i32 = 273;
+pub const GET_ROBUST_LIST_SYSCALL: i32 = 274;
+pub const SPLICE_SYSCALL: i32 = 275;
+pub const TEE_SYSCALL: i32 = 276;
 pub const SYNC_FILE_RANGE_SYSCALL: i32 = 277;
+pub const VMSPLICE_SYSCALL: i32 = 278;
 pub const UTIMENSAT_SYSCALL: i32 = 280;
+pub const FALLOCATE_SYSCALL: i32 = 285;
 pub const ACCEPT4_SYSCALL: i32 = 288;
 pub const PREADV_SYSCALL: i32 = 295;
 pub const PWRITEV_SYSCALL: i32 = 296;
 pub const PRLIMIT64_SYSCALL: i32 = 302;
+pub const GETCPU_SYSCALL: i32 = 309;
 pub const EPOLL_CREATE1_SYSCALL: i32 = 291;
 pub const DUP3_SYSCALL: i32 = 292;
 pub const PIPE2_SYSCALL: i32 = 293;
 pub const RENAMEAT2_SYSCALL: i32 = 316;
 pub const GETRANDOM_SYSCALL: i32 = 318;
+pub const MEMFD_CREATE_SYSCALL: i32 = 319;
 pub const COPY_FILE_RANGE_SYSCALL: i32 = 326;
 pub const STATX_SYSCALL: i32 = 332;
 pub const RT_SIGSUSPEND_SYSCALL: i32 = 130;
@@ -49,11 +49,11 @@ pub struct StatData {
     pub st_ctim: (u64, u64),
 }
 
-//R Limit for getrlimit system call
+//R Limit for prlimit64 system call (matches struct rlimit64)
 #[repr(C)]
 pub struct Rlimit {
-    pub rlim_cur: u32,
-    pub rlim_max: u32,
+    pub rlim_cur: u64,
+    pub rlim_max: u64,
 }
 
 #[derive(Eq, PartialEq, Default, Copy, Clone, Debug)]
@@ -133,6 +133,94 @@ pub struct ShmidsStruct {
     pub shm_nattch: u32,
 }
 
+/// `struct semid64_ds` as cages pass it to `semctl` (every `unsigned long` is 32 bits).
+#[derive(Copy, Clone, Default)]
+#[repr(C)]
+pub struct SemidStruct {
+    pub sem_perm: IpcPermStruct,
+    pub sem_otime: u32,
+    pub sem_otime_high: u32,
+    pub sem_ctime: u32,
+    pub sem_ctime_high: u32,
+    pub sem_nsems: u32,
+    pub __unused1: u32,
+    pub __unused2: u32,
+}
+
+/// `struct msqid64_ds` as cages pass it to `msgctl`.
+#[derive(Copy, Clone, Default)]
+#[repr(C)]
+pub struct MsqidStruct {
+    pub msg_perm: IpcPermStruct,
+    pub msg_stime: u32,
+    pub msg_stime_high: u32,
+    pub msg_rtime: u32,
+    pub msg_rtime_high: u32,
+    pub msg_ctime: u32,
+    pub msg_ctime_high: u32,
+    pub msg_cbytes: u32,
+    pub msg_qnum: u32,
+    pub msg_qbytes: u32,
+    pub msg_lspid: i32,
+    pub msg_lrpid: i32,
+    pub __unused4: u32,
+    pub __unused5: u32,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)]
+pub struct SembufStruct {
+    pub sem_num: u16,
+    pub sem_op: i16,
+    pub sem_flg: i16,
+}
+
+/// `struct mq_attr` as cages see it (`long` is 32 bits).
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)]
+pub struct MqAttrStruct {
+    pub mq_flags: i32,
+    pub mq_maxmsg: i32,
+    pub mq_msgsize: i32,
+    pub mq_curmsgs: i32,
+    pub __pad: [i32; 4],
+}
+
+/// `struct mq_attr` as 64-bit cages see it (`long` is 64 bits).
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)]
+pub struct MqAttrStruct64 {
+    pub mq_flags: i64,
+    pub mq_maxmsg: i64,
+    pub mq_msgsize: i64,
+    pub mq_curmsgs: i64,
+    pub __pad: [i64; 4],
+}
+
+impl From<MqAttrStruct> for MqAttrStruct64 {
+    fn from(attr: MqAttrStruct) -> Self {
+        MqAttrStruct64 {
+            mq_flags: attr.mq_flags as i64,
+            mq_maxmsg: attr.mq_maxmsg as i64,
+            mq_msgsize: attr.mq_msgsize as i64,
+            mq_curmsgs: attr.mq_curmsgs as i64,
+            __pad: [0; 4],
+        }
+    }
+}
+
+impl From<MqAttrStruct64> for MqAttrStruct {
+    fn from(attr: MqAttrStruct64) -> Self {
+        MqAttrStruct {
+            mq_flags: attr.mq_flags as i32,
+            mq_maxmsg: attr.mq_maxmsg.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+            mq_msgsize: attr.mq_msgsize.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+            mq_curmsgs: attr.mq_curmsgs as i32,
+            __pad: [0; 4],
+        }
+    }
+}
+
 pub type SigsetType = u64;
 
 pub type IovecStruct = libc::iovec;
@@ -141,6 +229,8 @@ pub type IovecStruct = libc::iovec;
 #[repr(C)]
 pub struct SigactionStruct {
     pub sa_handler: u32,
+    // Upper half of `sa_handler` in 64-bit cages, whose table indices still fit 32 bits
+    pub __pad: u32,
     pub sa_mask: SigsetType,
     pub sa_flags: i32,
 }
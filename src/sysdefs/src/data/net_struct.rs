@@ -114,3 +114,85 @@ pub struct SockPair {
     pub sock1: i32,
     pub sock2: i32,
 }
+
+/// Header of one control message in a cage's `msg_control` buffer.
+///
+/// The wasm32 `struct cmsghdr` has a 4-byte `size_t cmsg_len`, so the header is 12 bytes and
+/// `CMSG_ALIGN` rounds to 4 bytes, while the host header is 16 bytes with 8-byte alignment.
+/// `sendmsg`/`recvmsg` rebuild control buffers between the two layouts.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct GuestCmsgHdr {
+    pub cmsg_len: u32,
+    pub cmsg_level: i32,
+    pub cmsg_type: i32,
+}
+
+impl GuestCmsgHdr {
+    /// Size of the header itself (`CMSG_LEN(0)` in the cage).
+    pub const LEN: usize = mem::size_of::<GuestCmsgHdr>();
+
+    /// Rounds a length up the way the cage's `CMSG_ALIGN` does.
+    pub fn align(len: usize) -> usize {
+        (len + 3) & !3
+    }
+}
+
+/// The cage's 32-bit `struct timeval`, used by `SO_RCVTIMEO_OLD`/`SO_SNDTIMEO_OLD`.
+///
+/// The host's `struct timeval` has 64-bit fields, as does the cage's default (time64) one that
+/// goes with `SO_RCVTIMEO_NEW`/`SO_SNDTIMEO_NEW`.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct GuestOldTimeval {
+    pub tv_sec: i32,
+    pub tv_usec: i32,
+}
+
+/// `struct unix_diag_req`: a `SOCK_DIAG_BY_FAMILY` request for `AF_UNIX` sockets.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct UnixDiagReq {
+    pub sdiag_family: u8,
+    pub sdiag_protocol: u8,
+    pub pad: u16,
+    pub udiag_states: u32,
+    pub udiag_ino: u32,
+    pub udiag_show: u32,
+    pub udiag_cookie: [u32; 2],
+}
+
+/// `struct unix_diag_msg`: the fixed part of a `SOCK_DIAG_BY_FAMILY` reply for `AF_UNIX`.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct UnixDiagMsg {
+    pub udiag_family: u8,
+    pub udiag_type: u8,
+    pub udiag_state: u8,
+    pub pad: u8,
+    pub udiag_ino: u32,
+    pub udiag_cookie: [u32; 2],
+}
+
+/// `struct ifinfomsg`: the fixed part of an `RTM_NEWLINK`/`RTM_GETLINK` message.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IfInfoMsg {
+    pub ifi_family: u8,
+    pub pad: u8,
+    pub ifi_type: u16,
+    pub ifi_index: i32,
+    pub ifi_flags: u32,
+    pub ifi_change: u32,
+}
+
+/// `struct ifaddrmsg`: the fixed part of an `RTM_NEWADDR`/`RTM_GETADDR` message.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IfAddrMsg {
+    pub ifa_family: u8,
+    pub ifa_prefixlen: u8,
+    pub ifa_flags: u8,
+    pub ifa_scope: u8,
+    pub ifa_index: u32,
+}
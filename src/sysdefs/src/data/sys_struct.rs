@@ -13,3 +13,71 @@ pub struct CloneArgStruct {
     pub set_tid_size: u64, // Number of TIDs in the `set_tid` array
     pub cgroup: u64, // File descriptor for the cgroup to which the child process should be attached
 }
+
+/// One posix_spawn file action, as passed by glibc to the spawn syscall.
+///
+/// `tag` is one of the `SPAWN_DO_*` constants and selects which other fields are used. `path` is
+/// a host address.
+#[derive(Copy, Clone, Default, Debug)]
+#[repr(C)]
+pub struct SpawnAction {
+    pub tag: i32,
+    pub fd: i32,    // Descriptor closed, opened or duplicated from, or the fchdir target
+    pub newfd: i32, // Target descriptor of dup2
+    pub oflag: i32, // open flags
+    pub mode: u32,  // open mode
+    pub path: u64,  // open and chdir path
+}
+
+/// The posix_spawn attributes the spawn syscall applies to the child cage.
+#[derive(Copy, Clone, Default, Debug)]
+#[repr(C)]
+pub struct SpawnAttr {
+    pub flags: i32,   // posix_spawnattr flags
+    pub xflags: i32,  // glibc-internal SPAWN_XFLAGS_* flags
+    pub sigmask: u64, // Child's signal mask, when POSIX_SPAWN_SETSIGMASK is set
+}
+
+/// The cage's `struct sysinfo`.
+///
+/// `long` and `unsigned long` are 4 bytes in the cage, so this is 64 bytes, while the host's is
+/// 112. Memory sizes are counted in units of `mem_unit` bytes.
+#[derive(Copy, Clone, Default, Debug)]
+#[repr(C)]
+pub struct GuestSysinfo {
+    pub uptime: i32,     // Seconds since boot
+    pub loads: [u32; 3], // 1, 5 and 15 minute load averages, scaled by 65536
+    pub totalram: u32,   // Total usable memory
+    pub freeram: u32,    // Available memory
+    pub sharedram: u32,  // Amount of shared memory
+    pub bufferram: u32,  // Memory used by buffers
+    pub totalswap: u32,  // Total swap space
+    pub freeswap: u32,   // Swap space still available
+    pub procs: u16,      // Number of current processes
+    pub pad: u16,        // Explicit padding
+    pub totalhigh: u32,  // Total high memory
+    pub freehigh: u32,   // Available high memory
+    pub mem_unit: u32,   // Memory unit size in bytes
+    pub _f: [u8; 8],     // Padding to 64 bytes
+}
+
+/// The `struct sysinfo` of 64-bit cages, where `long` is 8 bytes as on the host.
+#[derive(Copy, Clone, Default, Debug)]
+#[repr(C)]
+pub struct GuestSysinfo64 {
+    pub uptime: i64,     // Seconds since boot
+    pub loads: [u64; 3], // 1, 5 and 15 minute load averages, scaled by 65536
+    pub totalram: u64,   // Total usable memory
+    pub freeram: u64,    // Available memory
+    pub sharedram: u64,  // Amount of shared memory
+    pub bufferram: u64,  // Memory used by buffers
+    pub totalswap: u64,  // Total swap space
+    pub freeswap: u64,   // Swap space still available
+    pub procs: u16,      // Number of current processes
+    pub pad: u16,        // Explicit padding
+    pub _pad2: u32,      // Alignment of totalhigh
+    pub totalhigh: u64,  // Total high memory
+    pub freehigh: u64,   // Available high memory
+    pub mem_unit: u32,   // Memory unit size in bytes
+    pub _f: [u8; 4],     // Padding to 112 bytes
+}
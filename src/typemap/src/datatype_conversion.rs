@@ -260,6 +260,36 @@ pub fn sc_convert_to_u8_mut(arg: u64, arg_cageid: u64, cageid: u64) -> *mut u8 {
     arg as *mut u8
 }
 
+/// `sc_convert_sysarg_to_uaddr` is the type conversion function used to convert a user
+/// (cage-relative) address argument, such as the address given to `mremap`. User addresses are
+/// 32-bit in 32-bit cages and 64-bit in 64-bit (memory64) cages, so they are kept as u64. When
+/// in `secure` mode, the address must also lie in the address space of the cage, and the call
+/// panics otherwise.
+///
+/// ## Arguments:
+/// arg: argument value
+/// arg_cageid: argument's cageid
+/// cageid: source cageid (the cage executes the call)
+///
+/// ## Returns:
+/// Success: The user address
+/// Fail: panic
+#[cfg_attr(not(feature = "secure"), allow(unused_variables))]
+pub fn sc_convert_sysarg_to_uaddr(arg: u64, arg_cageid: u64, cageid: u64) -> u64 {
+    #[cfg(feature = "secure")]
+    {
+        if !validate_cageid(arg_cageid, cageid) {
+            panic!("Invalid Cage ID");
+        }
+        let cage = get_cage(cageid).unwrap();
+        if arg >= cage.vmmap.read().address_space_size() {
+            panic!("Invalid argument");
+        }
+    }
+
+    arg
+}
+
 /// This function translates the buffer pointer from user buffer address to system address, because we are
 /// transferring between 32-bit WASM environment to 64-bit kernel
 ///
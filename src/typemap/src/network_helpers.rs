@@ -6,12 +6,12 @@
 use crate::cage_helpers::validate_cageid;
 use cage::{get_cage, translate_vmmap_addr};
 use libc::{
-    sa_family_t, sockaddr, sockaddr_in, sockaddr_in6, sockaddr_storage, sockaddr_un, socklen_t,
-    strlen,
+    sa_family_t, sockaddr, sockaddr_in, sockaddr_in6, sockaddr_nl, sockaddr_storage, sockaddr_un,
+    socklen_t,
 };
 use std::os::raw::{c_char, c_void};
 use std::ptr;
-use sysdefs::constants::net_const::AF_UNIX;
+use sysdefs::constants::net_const::{AF_INET, AF_INET6, AF_NETLINK, AF_UNIX};
 use sysdefs::constants::{syscall_error, Errno};
 use sysdefs::data::net_struct::{SockAddr, SockPair};
 
@@ -125,6 +125,7 @@ pub unsafe fn copy_out_sockaddr(
         AF_INET => size_of::<sockaddr_in>() as socklen_t,
         AF_INET6 => size_of::<sockaddr_in6>() as socklen_t,
         AF_UNIX => size_of::<sockaddr_un>() as socklen_t,
+        AF_NETLINK => size_of::<sockaddr_nl>() as socklen_t,
         _ => 0,
     };
 
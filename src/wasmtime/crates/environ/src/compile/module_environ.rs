@@ -20,6 +20,7 @@ use std::collections::HashMap;
 use std::mem;
 use std::path::PathBuf;
 use std::sync::Arc;
+use sysdefs::constants::lind_platform_const::LIND_PREINIT_SECTION;
 use sysdefs::lind_log;
 use wasmparser::{
     CustomSectionReader, DataKind, ElementItems, ElementKind, Encoding, ExternalKind,
@@ -896,6 +897,9 @@ and for re-adding support for interface types you can see this issue:
                     }
                 }
             }
+            _ if section.name() == LIND_PREINIT_SECTION => {
+                self.result.module.lind_preinit = Some(section.data().to_vec());
+            }
             _ => {
                 let name = section.name().trim_end_matches(".dwo");
                 if name.starts_with(".debug_") {
@@ -510,6 +510,9 @@ pub struct Module {
 
     /// dylink import information
     pub dylink_import_info: Option<DylinkImportInfo>,
+
+    /// lind-wasm: cage memory saved by `lind-boot --preinit`
+    pub lind_preinit: Option<Vec<u8>>,
 }
 
 /// lind-wasm addition: dylink memory information
@@ -607,6 +610,7 @@ impl Module {
             tags: Default::default(),
             dylink_mem_info: None,
             dylink_import_info: None,
+            lind_preinit: None,
         }
     }
 
@@ -902,6 +906,7 @@ impl TypeTrace for Module {
             tags,
             dylink_mem_info: _,
             dylink_import_info: _,
+            lind_preinit: _,
         } = self;
 
         for t in types.values().copied() {
@@ -958,6 +963,7 @@ impl TypeTrace for Module {
             tags,
             dylink_mem_info: _,
             dylink_import_info: _,
+            lind_preinit: _,
         } = self;
 
         for t in types.values_mut() {
@@ -28,7 +28,7 @@ use wasmtime_lind_multi_process::{LindHost, get_memory_base, get_memory_base_and
 // for functions that require a fixed number of parameters but do not utilize
 // all of them.
 use sysdefs::constants::syscall_const::{
-    CLONE_SYSCALL, EXEC_SYSCALL, EXIT_GROUP_SYSCALL, EXIT_SYSCALL,
+    CHECKPOINT_SYSCALL, CLONE_SYSCALL, EXIT_GROUP_SYSCALL, EXIT_SYSCALL,
 };
 
 /// Stores argv and environment variables for the guest program. During glibc's
@@ -147,12 +147,30 @@ fn add_syscall_to_linker<
                 }
             }
 
+            // `checkpoint` unwinds the cage the same way, and its rewind result is 0 once the
+            // snapshot is written, or 1 when the cage resumes from it under `lind-boot --restore`.
+            if call_number as i32 == CHECKPOINT_SYSCALL {
+                if let Some(rewind_res) = wasmtime_lind_multi_process::catch_rewind(&mut caller) {
+                    return Ok(rewind_res);
+                }
+            }
+
+            // A cage that froze in this syscall for the checkpoint of an ancestor cage stops
+            // rewinding here. It runs the syscall again, unless it was restored from the
+            // snapshot file, in which case the syscall fails with EINTR.
+            let resumed = wasmtime_lind_multi_process::resume_frozen(&mut caller);
+            if resumed == Some(false) {
+                return Ok(-(sysdefs::constants::Errno::EINTR as i32));
+            }
+
             // If we are reaching here at rewind state, that means fork was called within
             // a syscall-interrupted signal handler. We should restore the saved return value
             // of the syscall that was interrupted, rather than re-executing it.
             // If there's no syscall rewind data, we're rewinding from an exit_call —
             // let the rewind continue without re-executing the syscall.
-            if let AsyncifyState::Rewind(_) = caller.as_context().get_asyncify_state() {
+            if let AsyncifyState::Rewind(_) = caller.as_context().get_asyncify_state()
+                && resumed.is_none()
+            {
                 let retval = match caller.as_context_mut().get_current_syscall_rewind_data() {
                     Some(v) => v,
                     None => {
@@ -0,0 +1,743 @@
+//! Wasmtime side of cage checkpoints
+//!
+//! `checkpoint_call` unwinds the calling cage the same way `fork_call` does, so that its whole
+//! call stack ends up in linear memory. The cages below it are stopped as well: each one is asked
+//! to freeze, interrupted out of any blocking syscall, and unwinds from its next epoch check
+//! (`signal::signal_handler` calls `freeze_call`). Once every cage is unwound, the snapshot file
+//! is written: for each cage, the Wasm state that does not live in linear memory (globals, the
+//! Asyncify unwind location and the stack bounds), followed by RawPOSIX's `CageImage`. The cages
+//! then rewind and carry on, and a frozen cage that was blocked in a syscall runs it again.
+//!
+//! `lind-boot --restore` reads the file back with `CheckpointImage::read`, loads the same program
+//! into the first cage, and calls `restore_checkpoint` instead of starting it: the cage's memory
+//! and RawPOSIX state are replaced, the globals are put back, and the cage is set to rewind from
+//! the saved location when `_start` runs, so that `lind_checkpoint()` returns 1. The saved
+//! descendants are then created again with the IDs they had, and each resumes where it froze.
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::ptr::NonNull;
+use std::sync::{Arc, Condvar, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow, bail};
+use rawposix::checkpoint::{CageImage, ImageReader, ImageWriter};
+use sysdefs::constants::Errno;
+use wasmtime::{
+    AsContext, AsContextMut, AsyncifyState, Caller, Instance, Module, OnCalledAction, Store,
+    VMContext, VMOpaqueContext,
+};
+use wasmtime_environ::GlobalIndex;
+use wasmtime_lind_3i::{VmCtxWrapper, get_vmctx_thread};
+
+use crate::utils::parse_path;
+use crate::{
+    ASYNCIFY_START_REWIND, CAGE_START_ID, LindCtx, LindHost, THREAD_START_ID, UNWIND_METADATA_SIZE,
+    catch_rewind, current_cageid, get_memory_base,
+};
+
+// how long a checkpoint waits for the cages below the caller to stop
+const FREEZE_TIMEOUT: Duration = Duration::from_secs(10);
+// how often it checks whether one of them exited meanwhile
+const FREEZE_POLL: Duration = Duration::from_millis(10);
+
+// checkpoints of cage trees in progress, by the descendant cages that are asked to freeze
+static FREEZE_REQUESTS: LazyLock<Mutex<HashMap<u64, Arc<TreeCheckpoint>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// frozen cages that were set to rewind, which stop the rewind where they unwound, and whether
+// they run the syscall they froze in again
+static RESUMING: LazyLock<Mutex<HashMap<u64, bool>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The Wasm state of a saved cage, together with its RawPOSIX state.
+pub struct CageCheckpoint {
+    // path of the program the cage runs, which restores it
+    program: String,
+    // name of the main module, checked against the restoring program
+    module_name: String,
+    // where Asyncify saved the unwound call stack, in the cage's address space
+    unwind_data_start: u64,
+    stack_top: u64,
+    stack_base: u64,
+    globals: Vec<(GlobalIndex, i64)>,
+    stack_snapshots: HashMap<u64, Vec<u8>>,
+    cage: CageImage,
+}
+
+impl CageCheckpoint {
+    /// The ID the cage had when it was saved.
+    pub fn cageid(&self) -> u64 {
+        self.cage.cageid()
+    }
+
+    /// The parent the cage had when it was saved.
+    pub fn parent(&self) -> u64 {
+        self.cage.parent()
+    }
+
+    /// The program to load into the cage before it is restored.
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    /// Creates the cage of a saved descendant under `parent`, with the ID it had.
+    pub fn create_cage(&self, parent: u64) -> Result<()> {
+        self.cage
+            .create_cage(parent)
+            .map_err(|e| anyhow!("failed to create cage {} ({:?})", self.cageid(), e))
+    }
+
+    fn read_from(reader: &mut ImageReader) -> Result<Self, Errno> {
+        let program = String::from_utf8(reader.get_bytes()?.to_vec()).map_err(|_| Errno::EINVAL)?;
+        let module_name =
+            String::from_utf8(reader.get_bytes()?.to_vec()).map_err(|_| Errno::EINVAL)?;
+        let unwind_data_start = reader.get_u64()?;
+        let stack_top = reader.get_u64()?;
+        let stack_base = reader.get_u64()?;
+
+        let mut globals = Vec::new();
+        for _ in 0..reader.get_u32()? {
+            let index = GlobalIndex::from_u32(reader.get_u32()?);
+            globals.push((index, reader.get_i64()?));
+        }
+        let mut stack_snapshots = HashMap::new();
+        for _ in 0..reader.get_u32()? {
+            let hash = reader.get_u64()?;
+            stack_snapshots.insert(hash, reader.get_bytes()?.to_vec());
+        }
+
+        let cage = CageImage::read_from(reader)?;
+        Ok(CageCheckpoint {
+            program,
+            module_name,
+            unwind_data_start,
+            stack_top,
+            stack_base,
+            globals,
+            stack_snapshots,
+            cage,
+        })
+    }
+
+    fn write_to(&self, writer: &mut ImageWriter) {
+        writer.put_bytes(self.program.as_bytes());
+        writer.put_bytes(self.module_name.as_bytes());
+        writer.put_u64(self.unwind_data_start);
+        writer.put_u64(self.stack_top);
+        writer.put_u64(self.stack_base);
+
+        writer.put_u32(self.globals.len() as u32);
+        for (index, value) in &self.globals {
+            writer.put_u32(index.as_u32());
+            writer.put_i64(*value);
+        }
+        // sorted so that saving the same state twice gives the same file
+        let mut stack_snapshots: Vec<_> = self.stack_snapshots.iter().collect();
+        stack_snapshots.sort_by_key(|(hash, _)| **hash);
+        writer.put_u32(stack_snapshots.len() as u32);
+        for (hash, data) in stack_snapshots {
+            writer.put_u64(*hash);
+            writer.put_bytes(data);
+        }
+
+        self.cage.write_to(writer);
+    }
+}
+
+/// A snapshot file: the cage that called `lind_checkpoint()`, then the cages below it, each one
+/// after its parent.
+pub struct CheckpointImage {
+    cages: Vec<CageCheckpoint>,
+}
+
+impl CheckpointImage {
+    /// Reads a snapshot file written by `lind_checkpoint()`.
+    pub fn read(path: &Path) -> Result<Self> {
+        let buf = fs::read(path)
+            .map_err(|e| anyhow!("failed to read checkpoint {}: {}", path.display(), e))?;
+        Self::decode(&buf)
+            .map_err(|e| anyhow!("{} is not a valid checkpoint ({:?})", path.display(), e))
+    }
+
+    /// The saved cages, the one that called `lind_checkpoint()` first.
+    pub fn cages(&self) -> &[CageCheckpoint] {
+        &self.cages
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self, Errno> {
+        let mut reader = ImageReader::new(buf)?;
+        let mut cages = Vec::new();
+        for _ in 0..reader.get_u32()? {
+            cages.push(CageCheckpoint::read_from(&mut reader)?);
+        }
+        if cages.is_empty() || !reader.is_empty() {
+            return Err(Errno::EINVAL);
+        }
+        Ok(CheckpointImage { cages })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut writer = ImageWriter::new();
+        writer.put_u32(self.cages.len() as u32);
+        for cage in &self.cages {
+            cage.write_to(&mut writer);
+        }
+        writer.into_bytes()
+    }
+
+    /// Writes the image to `path`, created or truncated with mode 0600.
+    fn write(&self, path: &str) -> Result<(), Errno> {
+        let io_errno = |e: std::io::Error| {
+            Errno::from_discriminant(e.raw_os_error().unwrap_or(0)).unwrap_or(Errno::EIO)
+        };
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(io_errno)?;
+        file.write_all(&self.encode()).map_err(io_errno)?;
+        file.sync_all().map_err(io_errno)
+    }
+}
+
+/// The checkpoint of a cage tree, shared by the cage that saves it and the cages below it.
+pub(crate) struct TreeCheckpoint {
+    state: Mutex<TreeState>,
+    // signaled when a cage below is saved, and when the snapshot file is written
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct TreeState {
+    // the cages below that are frozen, or why they cannot be saved
+    saved: HashMap<u64, Result<CageCheckpoint, Errno>>,
+    // set once the file is written or the checkpoint failed, which resumes the frozen cages
+    done: bool,
+}
+
+impl TreeCheckpoint {
+    // Asks the cages below the caller to freeze, and interrupts them so that they do. Returns
+    // None if one of them is already being saved.
+    fn request(cageid: u64, descendants: &[u64]) -> Option<Arc<TreeCheckpoint>> {
+        let tree = Arc::new(TreeCheckpoint {
+            state: Mutex::new(TreeState::default()),
+            changed: Condvar::new(),
+        });
+        {
+            let mut requests = FREEZE_REQUESTS.lock().unwrap();
+            if std::iter::once(&cageid)
+                .chain(descendants)
+                .any(|id| requests.contains_key(id))
+            {
+                return None;
+            }
+            for id in descendants {
+                requests.insert(*id, tree.clone());
+            }
+        }
+        for id in descendants {
+            cage::signal::signal_interrupt(*id);
+        }
+        Some(tree)
+    }
+
+    // Records a frozen cage below, or why it cannot be saved.
+    fn deposit(&self, cageid: u64, cage: Result<CageCheckpoint, Errno>) {
+        let mut state = self.state.lock().unwrap();
+        state.saved.insert(cageid, cage);
+        self.changed.notify_all();
+    }
+
+    fn is_done(&self) -> bool {
+        self.state.lock().unwrap().done
+    }
+
+    // Waits until the file is written, or the checkpoint failed.
+    fn wait_done(&self) {
+        let mut state = self.state.lock().unwrap();
+        while !state.done {
+            state = self.changed.wait(state).unwrap();
+        }
+    }
+
+    // Waits until every cage below is frozen, and takes what they saved, in the same order.
+    //
+    // Returns EAGAIN if one of them exits first, EBUSY if one of them does not stop in time
+    // (such as one blocked in a syscall signals do not interrupt), and the error of a cage
+    // that cannot be saved.
+    fn wait_frozen(&self, descendants: &[u64]) -> Result<Vec<CageCheckpoint>, Errno> {
+        let deadline = Instant::now() + FREEZE_TIMEOUT;
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(id) = descendants
+                .iter()
+                .find(|id| matches!(state.saved.get(id), Some(Err(_))))
+            {
+                return state.saved.remove(id).unwrap().map(|_| vec![]);
+            }
+            let mut running = descendants
+                .iter()
+                .filter(|id| !state.saved.contains_key(id))
+                .peekable();
+            if running.peek().is_none() {
+                break;
+            }
+            if running.any(|id| {
+                cage::get_cage(*id).map_or(true, |cage| {
+                    cage.is_dead.load(std::sync::atomic::Ordering::Acquire)
+                })
+            }) {
+                return Err(Errno::EAGAIN);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Errno::EBUSY);
+            }
+            // interrupted again until they see the request, which a cage that was not running
+            // yet, or was about to block in a syscall, misses
+            {
+                let requests = FREEZE_REQUESTS.lock().unwrap();
+                for id in descendants.iter().filter(|id| requests.contains_key(id)) {
+                    cage::signal::signal_interrupt(*id);
+                }
+            }
+            state = self
+                .changed
+                .wait_timeout(state, FREEZE_POLL.min(deadline - now))
+                .unwrap()
+                .0;
+        }
+        Ok(descendants
+            .iter()
+            .filter_map(|id| state.saved.remove(id))
+            .filter_map(Result::ok)
+            .collect())
+    }
+
+    // Resumes the frozen cages. The cages below that did not see the request yet no longer do,
+    // and their epoch is put back unless a signal is pending for them.
+    fn finish(&self, descendants: &[u64]) {
+        let mut requests = FREEZE_REQUESTS.lock().unwrap();
+        let mut state = self.state.lock().unwrap();
+        for id in descendants {
+            if requests.remove(id).is_some() && cage::signal::lind_check_no_pending_signal(*id) {
+                cage::signal::signal_epoch_reset(*id);
+            }
+        }
+        state.done = true;
+        self.changed.notify_all();
+    }
+}
+
+// Takes the request to freeze cage `cageid` for the checkpoint of an ancestor cage, if any.
+pub(crate) fn take_freeze_request(cageid: u64) -> Option<Arc<TreeCheckpoint>> {
+    FREEZE_REQUESTS.lock().unwrap().remove(&cageid)
+}
+
+/// Stops the rewind of a cage that froze for the checkpoint of an ancestor cage, once it is back
+/// where it unwound. Returns None if the caller is not such a cage, whose rewind goes on.
+///
+/// The cage froze either at an epoch check, or in a syscall it was interrupted in. The syscall
+/// runs again if this returns `Some(true)`, and fails with EINTR otherwise: a cage restored from
+/// a snapshot file passes it arguments that point into the memory of the saved process.
+pub fn resume_frozen<
+    T: LindHost<T, U> + Clone + Send + 'static + std::marker::Sync,
+    U: Clone + Send + 'static + std::marker::Sync,
+>(
+    caller: &mut Caller<'_, T>,
+) -> Option<bool> {
+    if !matches!(
+        caller.as_context().get_asyncify_state(),
+        AsyncifyState::Rewind(_)
+    ) {
+        return None;
+    }
+    let cageid = current_cageid(caller) as u64;
+    let restart = RESUMING.lock().unwrap().remove(&cageid)?;
+    catch_rewind(caller);
+
+    // the epoch was switched to make the cage freeze
+    if cage::signal::lind_check_no_pending_signal(cageid) {
+        cage::signal::signal_epoch_reset(cageid);
+    }
+    Some(restart)
+}
+
+impl<T: Clone + Send + 'static + std::marker::Sync, U: Clone + Send + 'static + std::marker::Sync>
+    LindCtx<T, U>
+{
+    // Starts unwinding the calling cage to save it, as fork_call does. Returns where the unwind
+    // data is, and a function that saves the cage once the unwind is done.
+    //
+    // Cages using dynamic loading or threads cannot be saved, nor can cages in a signal handler.
+    // Up to `interrupted` syscalls may be on the stack: the one the cage is frozen in, which
+    // runs again once it resumes.
+    fn start_checkpoint_unwind(
+        &self,
+        mut caller: &mut Caller<'_, T>,
+        interrupted: usize,
+    ) -> Result<
+        (
+            u64,
+            impl FnOnce() -> Result<CageCheckpoint, Errno> + Send + 'static,
+        ),
+        Errno,
+    > {
+        // main module is the first module in the module list
+        let (_, program, main_module) = self.modules.get(0).unwrap();
+        let module_name = match main_module.name() {
+            Some(name) if main_module.dylink_meminfo().is_none() => name.to_string(),
+            _ => return Err(Errno::EOPNOTSUPP),
+        };
+        let program = program.clone();
+
+        {
+            let store = caller.as_context_mut().0;
+            if store.is_thread()
+                || !store.get_signal_asyncify_data().is_empty()
+                || store.get_syscall_asyncify_data().len() > interrupted
+            {
+                return Err(Errno::EOPNOTSUPP);
+            }
+        }
+
+        // get the base address of the memory
+        let address = get_memory_base(&mut caller) as *mut u8;
+
+        // get the stack pointer global
+        let stack_pointer = caller.get_stack_pointer().unwrap();
+
+        // we store the unwind data at the top of the user stack, see fork_call for the layout
+        let unwind_data_start_usr = caller.as_context().get_stack_top();
+        let unwind_data_start_sys = address as u64 + unwind_data_start_usr;
+        unsafe {
+            *(unwind_data_start_sys as *mut u32) =
+                (unwind_data_start_usr + UNWIND_METADATA_SIZE) as u32;
+            *((unwind_data_start_sys + 4) as *mut u32) = stack_pointer;
+        }
+
+        let globals = caller
+            .as_context_mut()
+            .get_global_snapshot()
+            .remove(&module_name)
+            .unwrap_or_default();
+        let stack_snapshots = caller.as_context_mut().get_stack_snapshots();
+        let stack_top = caller.as_context().get_stack_top();
+        let stack_base = caller.as_context().get_stack_base();
+
+        // mark the start of unwind
+        let asyncify_start_unwind_func = caller.get_asyncify_start_unwind().unwrap();
+        let _res = asyncify_start_unwind_func.call(&mut caller, unwind_data_start_usr as i32);
+
+        let cageid = self.cageid as u64;
+        let save = move || {
+            CageImage::capture(cageid).map(|cage| CageCheckpoint {
+                program,
+                module_name,
+                unwind_data_start: unwind_data_start_usr,
+                stack_top,
+                stack_base,
+                globals,
+                stack_snapshots,
+                cage,
+            })
+        };
+        Ok((unwind_data_start_usr, save))
+    }
+
+    // checkpoint syscall. Saves the calling cage and the cages below it to `path`. It works as
+    // follows:
+    // 1. ask the cages below to freeze, which they do from their next epoch check
+    // 2. unwind the callstack and save the function context (unwind context), as fork does
+    // 3. once the unwind is done, wait for the cages below to be unwound too, then save every
+    //    cage (memory, fds, signals...) and its wasm globals to the file
+    // 4. resume the cages below, and rewind the cage, which returns 0 (or -errno if the cages
+    //    could not be saved)
+    //
+    // Cages using dynamic loading, threads, or calling from a signal handler cannot be saved.
+    pub fn checkpoint_call(&self, caller: &mut Caller<'_, T>, path: String) -> Result<i32> {
+        let cageid = self.cageid as u64;
+        let descendants = cage::cage_descendants(cageid);
+
+        // checked before the cages below are interrupted
+        {
+            let main_module = &self.modules.get(0).unwrap().2;
+            let store = caller.as_context_mut().0;
+            if main_module.dylink_meminfo().is_some()
+                || store.is_thread()
+                || !store.get_signal_asyncify_data().is_empty()
+                || !store.get_syscall_asyncify_data().is_empty()
+            {
+                return Ok(-(Errno::EOPNOTSUPP as i32));
+            }
+        }
+        let tree = match TreeCheckpoint::request(cageid, &descendants) {
+            Some(tree) => tree,
+            None => return Ok(-(Errno::EBUSY as i32)),
+        };
+
+        let (unwind_data_start_usr, save) = match self.start_checkpoint_unwind(caller, 0) {
+            Ok(unwind) => unwind,
+            Err(errno) => {
+                tree.finish(&descendants);
+                return Ok(-(errno as i32));
+            }
+        };
+
+        // get the asyncify_stop_unwind and asyncify_start_rewind, which will later
+        // be used when the unwind process finished
+        let asyncify_stop_unwind_func = caller.get_asyncify_stop_unwind().unwrap();
+        let asyncify_start_rewind_func = caller.get_asyncify_start_rewind().unwrap();
+
+        // set up unwind callback function
+        let store = caller.as_context_mut().0;
+        store.set_on_called(Box::new(move |mut store| {
+            // unwind finished and we need to stop the unwind
+            let _res = asyncify_stop_unwind_func.call(&mut store, ());
+
+            // the whole callstack is in linear memory now, so the cage can be saved once the
+            // cages below are frozen. A cage that forked before it froze is not in the list.
+            let ret = tree
+                .wait_frozen(&descendants)
+                .and_then(|below| {
+                    if cage::cage_descendants(cageid) != descendants {
+                        return Err(Errno::EAGAIN);
+                    }
+                    let mut cages = vec![save()?];
+                    cages.extend(below);
+                    CheckpointImage { cages }.write(&path)
+                })
+                .map_or_else(|e| -(e as i32), |()| 0);
+            tree.finish(&descendants);
+
+            // mark the cage to rewind state, with the checkpoint result as return value
+            let _ = asyncify_start_rewind_func.call(&mut store, unwind_data_start_usr as i32);
+            store.set_asyncify_state(AsyncifyState::Rewind(ret));
+
+            // return InvokeAgain here would make the cage re-invoke main
+            return Ok(OnCalledAction::InvokeAgain);
+        }));
+
+        // set asyncify state to unwind
+        store.set_asyncify_state(AsyncifyState::Unwind);
+
+        // the return value seen by the cage is set on rewind
+        Ok(0)
+    }
+
+    // Freezes the calling cage for the checkpoint of an ancestor cage, from its epoch check.
+    // It works as follows:
+    // 1. unwind the callstack, as checkpoint_call does
+    // 2. once the unwind is done, save the cage and hand it to the ancestor
+    // 3. wait for the snapshot file to be written
+    // 4. rewind the cage, which stops where it unwound (see `resume_frozen`)
+    //
+    // Returns true if the cage is unwinding. Otherwise the cage could not be saved, or the
+    // checkpoint is already over, and it carries on.
+    pub(crate) fn freeze_call(
+        &self,
+        caller: &mut Caller<'_, T>,
+        tree: Arc<TreeCheckpoint>,
+    ) -> bool {
+        let cageid = self.cageid as u64;
+        if tree.is_done() {
+            if cage::signal::lind_check_no_pending_signal(cageid) {
+                cage::signal::signal_epoch_reset(cageid);
+            }
+            return false;
+        }
+
+        // the syscall the cage is blocked in, if any, returned EINTR to get here
+        let (unwind_data_start_usr, save) = match self.start_checkpoint_unwind(caller, 1) {
+            Ok(unwind) => unwind,
+            Err(errno) => {
+                tree.deposit(cageid, Err(errno));
+                if cage::signal::lind_check_no_pending_signal(cageid) {
+                    cage::signal::signal_epoch_reset(cageid);
+                }
+                return false;
+            }
+        };
+
+        let asyncify_stop_unwind_func = caller.get_asyncify_stop_unwind().unwrap();
+        let asyncify_start_rewind_func = caller.get_asyncify_start_rewind().unwrap();
+
+        let store = caller.as_context_mut().0;
+        store.set_on_called(Box::new(move |mut store| {
+            // unwind finished and we need to stop the unwind
+            let _res = asyncify_stop_unwind_func.call(&mut store, ());
+
+            // the interrupted syscall is run again rather than returning EINTR, so its return
+            // value is not needed on rewind
+            store.pop_syscall_asyncify_data();
+
+            tree.deposit(cageid, save());
+            tree.wait_done();
+
+            // mark the cage to rewind state, back to where it froze
+            RESUMING.lock().unwrap().insert(cageid, true);
+            let _ = asyncify_start_rewind_func.call(&mut store, unwind_data_start_usr as i32);
+            store.set_asyncify_state(AsyncifyState::Rewind(0));
+
+            // return InvokeAgain here would make the cage re-invoke main
+            return Ok(OnCalledAction::InvokeAgain);
+        }));
+
+        // set asyncify state to unwind
+        store.set_asyncify_state(AsyncifyState::Unwind);
+        true
+    }
+}
+
+/// Resumes a saved cage in the freshly instantiated main module of cage `cageid`.
+///
+/// Must be called before the module's `_start` runs, in place of `_initialize`. The cage's
+/// memory and RawPOSIX state are replaced by the saved ones, and the instance is left in rewind
+/// state so that `_start` goes straight back to where the cage was saved: the first cage to its
+/// `lind_checkpoint()` call, which returns 1, and the others to where they froze.
+pub fn restore_checkpoint<T>(
+    store: &mut Store<T>,
+    instance: &Instance,
+    module: &Module,
+    cageid: u64,
+    image: &CageCheckpoint,
+) -> Result<()> {
+    // the globals are checked before anything is changed, as applying them panics on a mismatch
+    if module.name() != Some(image.module_name.as_str()) {
+        bail!(
+            "checkpoint was saved from module `{}`, not this program",
+            image.module_name
+        );
+    }
+    let globals = store
+        .as_context_mut()
+        .get_global_snapshot()
+        .remove(&image.module_name)
+        .unwrap_or_default();
+    if globals.len() != image.globals.len()
+        || globals
+            .iter()
+            .zip(&image.globals)
+            .any(|((index, _), (saved, _))| index != saved)
+    {
+        bail!("checkpoint globals do not match this program");
+    }
+
+    image
+        .cage
+        .restore(cageid)
+        .map_err(|e| anyhow!("failed to restore cage {} ({:?})", cageid, e))?;
+
+    // The C library caches the memory base and the cage ID, which differ in this process. The
+    // first cage looks them up again once lind_checkpoint() returns 1, but the others resume
+    // wherever they froze, so the cached values are rewritten here.
+    let cage = cage::get_cage(cageid).ok_or_else(|| anyhow!("cage {} is gone", cageid))?;
+    let memory_base = cage.vmmap.read().user_to_sys(0) as u64;
+    for (name, value) in [("__lind_base", memory_base), ("__lind_cageid", cageid)] {
+        match instance
+            .get_global(&mut *store, name)
+            .and_then(|global| global.get(&mut *store).i32())
+        {
+            Some(addr) => unsafe {
+                *((memory_base + addr as u32 as u64) as *mut u64) = value;
+            },
+            None if cageid == CAGE_START_ID as u64 => {}
+            None => bail!(
+                "cage {} cannot be restored: its program does not export {}",
+                cageid,
+                name
+            ),
+        }
+    }
+
+    instance.apply_global_snapshots(&mut *store, &image.globals);
+    store.as_context_mut().set_stack_top(image.stack_top);
+    store.as_context_mut().set_stack_base(image.stack_base);
+    store.set_stack_snapshots(image.stack_snapshots.clone());
+
+    // mark the cage to rewind state, and make lind_checkpoint() return 1
+    let rewind_start = instance.get_typed_func::<i32, ()>(&mut *store, ASYNCIFY_START_REWIND)?;
+    rewind_start.call(&mut *store, image.unwind_data_start as i32)?;
+    let retval = if cageid == CAGE_START_ID as u64 {
+        1
+    } else {
+        RESUMING.lock().unwrap().insert(cageid, false);
+        0
+    };
+    store
+        .as_context_mut()
+        .set_asyncify_state(AsyncifyState::Rewind(retval));
+    Ok(())
+}
+
+/// Re-entering Wasmtime trampoline for the Lind-specific `checkpoint` syscall.
+///
+/// Conceptually, the execution flow is:
+///   Wasm
+///     -> Wasmtime lind-common trampoline
+///     -> 3i dispatch with grateid=RAWPOSIX
+///     -> RawPOSIX syscall handling (checks the cage can be saved, converts the path)
+///     -> 3i dispatch with grateid=WASMTIME
+///     -> **back to Wasmtime (this function)**
+///
+/// `path` is a host pointer to the path RawPOSIX converted, and `path_cageid` the cage that is
+/// saved, whose main thread VMContext is used to re-enter Wasmtime. The function pointer of
+/// `checkpoint_syscall` is registered into the 3i handler table during lind-boot initialization,
+/// next to clone/exec/exit/spawn.
+pub fn checkpoint_syscall<T, U>(
+    _cageid: u64,
+    path: u64,
+    path_cageid: u64,
+    _arg2: u64,
+    _arg2_cageid: u64,
+    _arg3: u64,
+    _arg3_cageid: u64,
+    _arg4: u64,
+    _arg4_cageid: u64,
+    _arg5: u64,
+    _arg5_cageid: u64,
+    _arg6: u64,
+    _arg6_cageid: u64,
+) -> i32
+where
+    T: LindHost<T, U> + Clone + Send + Sync + 'static,
+    U: Clone + Send + Sync + 'static,
+{
+    unsafe {
+        let vmctx_wrapper: VmCtxWrapper =
+            match get_vmctx_thread(path_cageid, THREAD_START_ID as u64) {
+                Some(v) => v,
+                None => {
+                    panic!("no VMContext found for cage_id {}", path_cageid);
+                }
+            };
+        // Convert back to VMContext
+        let opaque: *mut VMOpaqueContext = vmctx_wrapper.as_ptr() as *mut VMOpaqueContext;
+
+        let vmctx_raw: *mut VMContext =
+            VMContext::from_opaque(NonNull::new_unchecked(opaque)).as_ptr();
+
+        Caller::with(vmctx_raw, |mut caller: Caller<'_, T>| {
+            let host = caller.data().clone();
+            let ctx = host.get_ctx();
+
+            // the path is copied now, as the unwind runs after this function returns
+            let path = match parse_path(path) {
+                Ok(path) => path,
+                Err(_) => return -(Errno::EFAULT as i32),
+            };
+
+            match ctx.checkpoint_call(&mut caller, path) {
+                Ok(ret) => ret,
+                Err(e) => {
+                    log::error!("failed to checkpoint: {}", e);
+                    -1
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,127 @@
+//! State carried from one program of a cage to the next across `exec`.
+//!
+//! - The linear memory of the old program is handed to the new one instead of reserving and
+//!   mapping a fresh 4 GiB memory: `execve_call` stashes it once the old program has unwound, and
+//!   `attach_shared_memory` takes it back, releases the old program's pages and empties the
+//!   vmmap in place (see `cage::memory::reset_vmmap_for_exec`). Instantiation then only writes
+//!   the new module's data segments.
+//! - Precompiled (`.cwasm`) modules are cached by path, so that a shell running the same tools
+//!   over and over deserializes each of them once. A cached module is used as long as the file
+//!   keeps the same inode, size and modification time.
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use wasmtime::{Engine, MemoryType, Module, SharedMemory};
+
+/// Most precompiled modules kept in the cache
+const MODULE_CACHE_MAX: usize = 64;
+
+/// Linear memories of cages that are exec-ing, by cage id
+static EXEC_MEMORIES: LazyLock<Mutex<HashMap<u64, SharedMemory>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Keeps the linear memory of `cageid` for the program it is exec-ing.
+pub(crate) fn stash_exec_memory(cageid: u64, memory: SharedMemory) {
+    EXEC_MEMORIES.lock().unwrap().insert(cageid, memory);
+}
+
+/// Drops the linear memory kept for `cageid`, if the new program failed before taking it.
+pub(crate) fn forget_exec_memory(cageid: u64) {
+    EXEC_MEMORIES.lock().unwrap().remove(&cageid);
+}
+
+/// Takes back the linear memory `stash_exec_memory` kept for `cageid`, if it can serve as a
+/// memory of type `ty`. A memory that cannot is dropped.
+pub(crate) fn take_exec_memory(cageid: u64, ty: &MemoryType) -> Option<SharedMemory> {
+    let memory = EXEC_MEMORIES.lock().unwrap().remove(&cageid)?;
+    let old = memory.ty();
+    let compatible = old.is_64() == ty.is_64()
+        && old.page_size() == ty.page_size()
+        && old.maximum() == ty.maximum()
+        && memory.size() >= ty.minimum();
+    compatible.then_some(memory)
+}
+
+/// What identifies the contents of a module file
+#[derive(PartialEq, Eq, Clone, Copy)]
+struct FileStamp {
+    dev: u64,
+    ino: u64,
+    size: u64,
+    mtime: i64,
+    mtime_nsec: i64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<FileStamp> {
+        let meta = std::fs::metadata(path).ok()?;
+        Some(FileStamp {
+            dev: meta.dev(),
+            ino: meta.ino(),
+            size: meta.size(),
+            mtime: meta.mtime(),
+            mtime_nsec: meta.mtime_nsec(),
+        })
+    }
+}
+
+struct CachedModule {
+    stamp: FileStamp,
+    module: Module,
+    // Value of `ModuleCache::clock` when the module was last used
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct ModuleCache {
+    modules: HashMap<PathBuf, CachedModule>,
+    clock: u64,
+}
+
+static MODULE_CACHE: LazyLock<Mutex<ModuleCache>> =
+    LazyLock::new(|| Mutex::new(ModuleCache::default()));
+
+/// Deserializes the precompiled module at `path` for `engine`, or returns the cached one if the
+/// file has not changed since.
+pub(crate) fn load_precompiled(engine: &Engine, path: &Path) -> wasmtime::Result<Module> {
+    let stamp = FileStamp::of(path);
+    {
+        let mut cache = MODULE_CACHE.lock().unwrap();
+        cache.clock += 1;
+        let clock = cache.clock;
+        if let Some(cached) = cache.modules.get_mut(path)
+            && Some(cached.stamp) == stamp
+            && Engine::same(cached.module.engine(), engine)
+        {
+            cached.last_used = clock;
+            return Ok(cached.module.clone());
+        }
+    }
+
+    let module = unsafe { Module::deserialize_file(engine, path) }?;
+    // A file whose metadata cannot be read is not cached, as changes to it would go unnoticed
+    if let Some(stamp) = stamp {
+        let mut cache = MODULE_CACHE.lock().unwrap();
+        if cache.modules.len() >= MODULE_CACHE_MAX && !cache.modules.contains_key(path) {
+            let oldest = cache
+                .modules
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                cache.modules.remove(&oldest);
+            }
+        }
+        let last_used = cache.clock;
+        cache.modules.insert(
+            path.to_path_buf(),
+            CachedModule {
+                stamp,
+                module: module.clone(),
+                last_used,
+            },
+        );
+    }
+    Ok(module)
+}
@@ -5,9 +5,7 @@ use cfg_if::cfg_if;
 use anyhow::{Context, Result, anyhow};
 use std::ffi::c_void;
 use std::ptr::NonNull;
-use sysdefs::constants::lind_platform_const::{
-    UNUSED_ARG, UNUSED_ID, UNUSED_NAME, unset_stack_arena_base,
-};
+use sysdefs::constants::lind_platform_const::{MAX_LINEAR_MEMORY64_SIZE, unset_stack_arena_base};
 use sysdefs::constants::syscall_const::{EXEC_SYSCALL, EXIT_SYSCALL, FORK_SYSCALL};
 use sysdefs::constants::{Errno, MAX_SHEBANG_DEPTH, MMAP_SYSCALL};
 use sysdefs::lind_debug_panic;
@@ -33,11 +31,16 @@ use cage::alloc_cage_id;
 use cage::signal::{lind_signal_init, lind_thread_exit};
 use wasmtime_environ::MemoryIndex;
 
+pub use crate::checkpoint::{
+    CageCheckpoint, CheckpointImage, checkpoint_syscall, restore_checkpoint, resume_frozen,
+};
 use crate::shebang::{build_shebang_argv, parse_shebang};
 use crate::utils::{parse_argv, parse_env, parse_path};
 
 pub mod signal;
 
+mod checkpoint;
+mod exec;
 mod shebang;
 mod utils;
 
@@ -1407,59 +1410,25 @@ impl<T: Clone + Send + 'static + std::marker::Sync, U: Clone + Send + 'static +
         environs: Option<Vec<(String, Option<String>)>>,
         recursion_depth: i32,
     ) -> Result<i32> {
-        // linux limits the maximum recursion depth of shebang
-        // it's typical value is 4, so let's use the same value
-        if recursion_depth > MAX_SHEBANG_DEPTH {
-            return Ok(-(Errno::ELOOP as i32));
-        }
-
-        // if the file to exec does not exist
-        if !std::path::Path::new(&path).exists() {
-            // return ENOENT
-            return Ok(-(Errno::ENOENT as i32));
-        }
+        // load the new module as soon as possible to catch the error before unwinding, which is hard to unwind back if exec file has some problems
+        let (exec_module, path, argv) = match self.load_exec_module(path, argv, recursion_depth) {
+            Ok(loaded) => loaded,
+            Err(errno) => return Ok(-errno),
+        };
 
-        // parse the wasm module as soon as possible to catch the error before unwinding, which is hard to unwind back if exec file has some problems
-        let mut main_module = &self.modules.get(0).unwrap().2;
+        let main_module = &self.modules.get(0).unwrap().2;
 
         // detect if dynamic loading is enabled
         let dylink_enabled = main_module.dylink_meminfo().is_some();
 
         let engine = main_module.engine().clone();
-        let exec_file_path = Path::new(&path);
-        let exec_module = match Engine::detect_precompiled_file(exec_file_path) {
-            Ok(_) => unsafe { Module::deserialize_file(&engine, exec_file_path) },
-            Err(_) => Module::from_file(&engine, exec_file_path),
-        };
-        if exec_module.is_err() {
-            let shebang_res = parse_shebang(exec_file_path);
-
-            if shebang_res.is_err() {
-                return Ok(-(Errno::ENOEXEC as i32));
-            }
-
-            let shebang_opt = shebang_res.unwrap();
-            if shebang_opt.is_none() {
-                return Ok(-(Errno::ENOEXEC as i32));
-            }
-
-            // if shebang is present, we reconstruct the argv and path and call execve again with the interpreter specified by shebang
-            let shebang = shebang_opt.unwrap();
-
-            let new_argv = match build_shebang_argv(&shebang, &argv) {
-                Ok(args) => args,
-                Err(_) => return Ok(-(Errno::ENOEXEC as i32)),
-            };
-            // it's safe to unwrap here since above build_shebang_argv already checks if the interpreter path is valid
-            let new_path = shebang.interpreter.to_str().unwrap().to_string();
-
-            return self.execve_call(caller, new_path, new_argv, environs, recursion_depth + 1);
-        }
-        let exec_module = exec_module.unwrap();
 
         // get the base address of the memory
         let address = get_memory_base(&mut caller);
 
+        // the new program takes over the linear memory once the old one has unwound
+        let exec_memory = get_shared_memory(&mut caller);
+
         // get the wasm stack top address
         let parent_stack_low_usr = caller.as_context().get_stack_top();
 
@@ -1524,6 +1493,10 @@ impl<T: Clone + Send + 'static + std::marker::Sync, U: Clone + Send + 'static +
                 });
             }
 
+            if let Some(memory) = exec_memory {
+                exec::stash_exec_memory(cloned_cageid as u64, memory);
+            }
+
             let ret = exec_call(
                 &cloned_lindboot_cli,
                 &path,
@@ -1542,6 +1515,8 @@ impl<T: Clone + Send + 'static + std::marker::Sync, U: Clone + Send + 'static +
             // called.  Mirror the fork-crash cleanup path (see fork_call error
             // handling) exactly.
             if let Err(ref _e) = ret {
+                // the memory is still stashed if the new program failed before taking it
+                exec::forget_exec_memory(cloned_cageid as u64);
                 cage::cage_record_exit_status(cloned_cageid as u64, cage::ExitStatus::Exited(1));
                 if let Some(c) = cage::get_cage(cloned_cageid as u64) {
                     c.is_dead.store(true, std::sync::atomic::Ordering::Release);
@@ -1566,6 +1541,146 @@ impl<T: Clone + Send + 'static + std::marker::Sync, U: Clone + Send + 'static +
         return Ok(0);
     }
 
+    // load the module that an exec-like call (execve or spawn) should run
+    // if the file is a script, it is followed to the interpreter named by its shebang, and the
+    // interpreter's path and argv are returned along with its module
+    // Function Argument:
+    // * path: the path of the file to run
+    // * argv: the argument list
+    // * recursion_depth: how many shebangs have been followed so far, starting from 1
+    // on failure, the errno to report is returned
+    fn load_exec_module(
+        &self,
+        path: String,
+        argv: Vec<String>,
+        recursion_depth: i32,
+    ) -> std::result::Result<(Module, String, Vec<String>), i32> {
+        // linux limits the maximum recursion depth of shebang
+        // it's typical value is 4, so let's use the same value
+        if recursion_depth > MAX_SHEBANG_DEPTH {
+            return Err(Errno::ELOOP as i32);
+        }
+
+        // if the file to exec does not exist
+        if !std::path::Path::new(&path).exists() {
+            // return ENOENT
+            return Err(Errno::ENOENT as i32);
+        }
+
+        // main module is the first module in the module list
+        let main_module = &self.modules.get(0).unwrap().2;
+
+        let engine = main_module.engine().clone();
+        let exec_file_path = Path::new(&path);
+        let exec_module = match Engine::detect_precompiled_file(exec_file_path) {
+            Ok(_) => exec::load_precompiled(&engine, exec_file_path),
+            Err(_) => Module::from_file(&engine, exec_file_path),
+        };
+        if exec_module.is_err() {
+            let shebang_res = parse_shebang(exec_file_path);
+
+            if shebang_res.is_err() {
+                return Err(Errno::ENOEXEC as i32);
+            }
+
+            let shebang_opt = shebang_res.unwrap();
+            if shebang_opt.is_none() {
+                return Err(Errno::ENOEXEC as i32);
+            }
+
+            // if shebang is present, we reconstruct the argv and path and load the interpreter specified by shebang instead
+            let shebang = shebang_opt.unwrap();
+
+            let new_argv = match build_shebang_argv(&shebang, &argv) {
+                Ok(args) => args,
+                Err(_) => return Err(Errno::ENOEXEC as i32),
+            };
+            // it's safe to unwrap here since above build_shebang_argv already checks if the interpreter path is valid
+            let new_path = shebang.interpreter.to_str().unwrap().to_string();
+
+            return self.load_exec_module(new_path, new_argv, recursion_depth + 1);
+        }
+
+        Ok((exec_module.unwrap(), path, argv))
+    }
+
+    // spawn syscall, the fast path of posix_spawn. It starts a program in a cage that RawPOSIX
+    // has just created, without forking the calling cage: the caller is not unwound and its memory
+    // is never copied. It works as follows:
+    // 1. load the module to run, so that a bad file is reported to the caller
+    // 2. on a new host thread, run the module in the child cage the same way execve_call does
+    //    after unwinding
+    // Function Argument:
+    // * path: the path of the file to run, already resolved against the child's cwd
+    // * argv: the argument list
+    // * environs: the environment variables
+    // * child_cageid: the cage to run the program in
+    pub fn spawn_call(
+        &self,
+        path: String,
+        argv: Vec<String>,
+        environs: Option<Vec<(String, Option<String>)>>,
+        child_cageid: u64,
+    ) -> Result<i32> {
+        let (exec_module, path, argv) = match self.load_exec_module(path, argv, 1) {
+            Ok(loaded) => loaded,
+            Err(errno) => return Ok(-errno),
+        };
+
+        let engine = exec_module.engine().clone();
+        let cloned_lindboot_cli = self.lindboot_cli.clone();
+        let cloned_lind_manager = self.lind_manager.clone();
+        let exec_call = self.exec_host.clone();
+
+        // new cage created, increment the cage counter before it can exit
+        self.lind_manager.increment();
+
+        let builder = thread::Builder::new()
+            .name(format!("lind-spawn-{}", child_cageid))
+            .stack_size(self.thread_stack_size);
+        let spawned = builder.spawn(move || {
+            let ret = exec_call(
+                &cloned_lindboot_cli,
+                &path,
+                &argv,
+                engine,
+                exec_module,
+                child_cageid as i32,
+                &cloned_lind_manager,
+                &environs,
+            );
+
+            // Wasm instance crashed — perform the same cleanup as the fork-crash path
+            // so the parent sees a proper zombie and resources are freed.
+            if let Err(err) = ret {
+                lind_log!(Default, "Spawned Cage Error: {:?}", err);
+                cage::cage_record_exit_status(child_cageid, cage::ExitStatus::Exited(1));
+                if let Some(c) = cage::get_cage(child_cageid) {
+                    c.is_dead.store(true, std::sync::atomic::Ordering::Release);
+                }
+                threei::EXITING_TABLE.insert(child_cageid);
+                threei::handler_table::_rm_grate_from_handler(child_cageid);
+                cage::signal::lind_thread_exit(child_cageid, THREAD_START_ID as u64);
+                cage::cage_finalize(child_cageid);
+                if !rm_vmctx_thread(child_cageid, 0) {
+                    lind_log!(
+                        Default,
+                        "[wasmtime|spawn-crash] Failed to remove VMContext for cage {}",
+                        child_cageid
+                    );
+                }
+                cloned_lind_manager.decrement();
+            }
+        });
+
+        if spawned.is_err() {
+            self.lind_manager.decrement();
+            return Ok(-(Errno::EAGAIN as i32));
+        }
+
+        Ok(child_cageid as i32)
+    }
+
     // exit syscall
     // actual exit syscall that would kill other threads is not supported yet
     // TODO: exit_call should be switched to epoch interrupt method later
@@ -1923,6 +2038,20 @@ pub fn get_memory_base<T: Clone + Send + 'static + std::marker::Sync>(
     }
 }
 
+/// Returns the shared linear memory of the caller as a handle another store can import, or
+/// `None` if the memory is not shared.
+pub fn get_shared_memory<T: Clone + Send + 'static + std::marker::Sync>(
+    caller: &mut Caller<'_, T>,
+) -> Option<SharedMemory> {
+    let engine = caller.engine().clone();
+    caller
+        .as_context_mut()
+        .0
+        .all_memories()
+        .next()?
+        .into_shared_memory(&engine)
+}
+
 /// Returns `(base_ptr_as_u64, data_size_in_bytes)` for the first guest linear
 /// memory. Use this instead of `get_memory_base` whenever bounds checks are
 /// needed before accessing guest memory.
@@ -2209,6 +2338,91 @@ where
     }
 }
 
+/// Entry point for the Lind-specific `spawn` syscall (the `posix_spawn` fast path)
+/// after RawPOSIX-side processing.
+///
+/// RawPOSIX has already created the child cage and applied the file actions.
+/// This function re-enters Wasmtime through the `VMContext` of the parent's
+/// calling thread `parent_tid`, only to reach its `LindCtx` (engine, preloads,
+/// exec closure), and then starts the program in the child cage on a new thread
+/// via `spawn_call`. Unlike `clone_syscall` and `exec_syscall`, the parent's
+/// execution state is left untouched: no Asyncify unwind takes place. Since the
+/// parent's other threads keep running, the context of the calling thread is
+/// used, not the main thread's, whose Store may be in use at the same time.
+///
+/// The function pointer of `spawn_syscall` is registered into the 3i handler
+/// table during lind-boot initialization, next to clone/exec/exit.
+///
+/// Returns the child cage id, or a negative errno if the program could not be
+/// loaded.
+pub fn spawn_syscall<T, U>(
+    _cageid: u64,
+    path: u64,
+    parent_cageid: u64,
+    argv: u64,
+    _argv_cageid: u64,
+    envs: u64,
+    _envs_cageid: u64,
+    child_cageid: u64,
+    _arg4_cageid: u64,
+    parent_tid: u64,
+    _arg5_cageid: u64,
+    _arg6: u64,
+    _arg6_cageid: u64,
+) -> i32
+where
+    T: LindHost<T, U> + Clone + Send + Sync + 'static,
+    U: Clone + Send + Sync + 'static,
+{
+    unsafe {
+        let vmctx_wrapper: VmCtxWrapper = match get_vmctx_thread(parent_cageid, parent_tid) {
+            Some(v) => v,
+            None => {
+                log::error!(
+                    "no VMContext found for cage_id {} thread {}",
+                    parent_cageid,
+                    parent_tid
+                );
+                return -(Errno::ESRCH as i32);
+            }
+        };
+        // Convert back to VMContext
+        let opaque: *mut VMOpaqueContext = vmctx_wrapper.as_ptr() as *mut VMOpaqueContext;
+
+        let vmctx_raw: *mut VMContext =
+            VMContext::from_opaque(NonNull::new_unchecked(opaque)).as_ptr();
+
+        Caller::with(vmctx_raw, |caller: Caller<'_, T>| {
+            let host = caller.data().clone();
+            let ctx = host.get_ctx();
+
+            // parse the arguments from the caller's memory space
+            let path = match parse_path(path) {
+                Ok(path) => path,
+                Err(_) => return -(Errno::EFAULT as i32),
+            };
+
+            let argv = match parse_argv(argv) {
+                Ok(argv) => argv,
+                Err(_) => return -(Errno::EFAULT as i32),
+            };
+
+            let envs = match parse_env(envs) {
+                Ok(envs) => envs,
+                Err(_) => return -(Errno::EFAULT as i32),
+            };
+
+            match ctx.spawn_call(path, argv, envs, child_cageid) {
+                Ok(ret) => ret,
+                Err(e) => {
+                    log::error!("failed to spawn: {}", e);
+                    -1
+                }
+            }
+        })
+    }
+}
+
 /// Re-entering Wasmtime trampoline for the `exit` syscall.
 ///
 /// This function serves as the **Wasmtime re-entry trampoline** for `exit`,
@@ -2357,7 +2571,8 @@ where
 }
 
 // attach a new SharedMemory to the Linker for multi-threading usage
-// Warning: only set need_init to true for first cage initialization
+// Warning: only set need_init to true for first cage initialization, or for the new program of
+// a cage that exec-ed, which gets the cage's old memory back instead of a new one
 //
 // `all_modules` should include the main module plus all preload library modules.
 // The shared memory is created with limits that satisfy every module's import
@@ -2381,11 +2596,13 @@ pub fn attach_shared_memory<
     let mut import_module_name = "";
     let mut import_name = "";
     let mut found = false;
+    let mut memory64 = false;
     for import in main_module.imports() {
         if let Some(m) = import.ty().memory() {
             if m.is_shared() {
                 import_module_name = import.module();
                 import_name = import.name();
+                memory64 = m.is_64();
                 found = true;
                 break;
             }
@@ -2404,6 +2621,9 @@ pub fn attach_shared_memory<
     // true accessible extent.  The max-limit type check in matching.rs is
     // relaxed for shared memories to allow this.
     //
+    // A memory64 module gets a 64-bit cage, whose memory is MAX_LINEAR_MEMORY64_SIZE
+    // (64 GiB) instead, handled the same way.
+    //
     // We still compute combined_min (= max of all declared mins) to satisfy
     // every module's minimum requirement.
     let mut combined_min: u64 = 0;
@@ -2421,28 +2641,51 @@ pub fn attach_shared_memory<
     // Always use 65536 pages (4 GiB) as the declared maximum so that
     // current_length can be grown to the full physical reservation.
     const LIND_MAX_PAGES: u64 = 65536;
+    const LIND_MAX_PAGES64: u64 = (MAX_LINEAR_MEMORY64_SIZE + 1) >> 16;
+    let max_pages = if memory64 {
+        LIND_MAX_PAGES64
+    } else {
+        LIND_MAX_PAGES
+    };
 
     let mem_type = wasmtime::MemoryTypeBuilder::new()
         .shared(true)
+        .memory64(memory64)
         .min(combined_min)
-        .max(Some(LIND_MAX_PAGES))
+        .max(Some(max_pages))
         .build()
         .map_err(anyhow::Error::from)?;
 
-    let mem = SharedMemory::new(main_module.engine(), mem_type)?;
-
-    // Grow to the full 4 GiB so that every wasm address in [0, 4GiB) passes
-    // the current_length bounds check.  The physical pages are already reserved
-    // by MmapMemory; this just makes them PROT_READ|PROT_WRITE and updates
-    // current_length atomically.
-    let delta = LIND_MAX_PAGES.saturating_sub(combined_min);
-    if delta > 0 {
-        mem.grow(delta)
-            .map_err(anyhow::Error::from)
-            .context("failed to grow shared memory to 4 GiB")?;
-    }
+    // A cage that exec-ed reuses the memory of its old program, which is already grown (see
+    // `exec`). The old program's pages are released below, when its vmmap is reset.
+    let reused = if need_init {
+        exec::take_exec_memory(cageid as u64, &mem_type)
+    } else {
+        None
+    };
+    let mem = match reused {
+        Some(mem) => mem,
+        None => {
+            let mem = SharedMemory::new(main_module.engine(), mem_type)?;
+
+            // Grow to the full 4 GiB so that every wasm address in [0, 4GiB) passes
+            // the current_length bounds check.  The physical pages are already reserved
+            // by MmapMemory; this just makes them PROT_READ|PROT_WRITE and updates
+            // current_length atomically.
+            let delta = max_pages.saturating_sub(combined_min);
+            if delta > 0 {
+                mem.grow(delta)
+                    .map_err(anyhow::Error::from)
+                    .context("failed to grow shared memory to 4 GiB")?;
+            }
+            mem
+        }
+    };
 
     if need_init {
+        // empty the vmmap of the cage's previous program, if it exec-ed, and release its pages
+        cage::reset_vmmap_for_exec(cageid as u64);
+
         let memory_base = mem.get_memory_base();
 
         // lind-wasm: reset the entire 4 GiB wasm linear memory to PROT_NONE
@@ -2454,11 +2697,15 @@ pub fn attach_shared_memory<
         unsafe {
             libc::mprotect(
                 memory_base as *mut libc::c_void,
-                1usize << 32,
+                (max_pages as usize) << 16,
                 libc::PROT_NONE,
             );
         }
 
+        // the address space of the vmmap follows the memory, and is set before it is laid out
+        if memory64 {
+            cage::set_memory64(cageid as u64);
+        }
         cage::init_vmmap(cageid as u64, memory_base as usize, None);
     }
     linker.define(&store, import_module_name, import_name, mem.clone())?;
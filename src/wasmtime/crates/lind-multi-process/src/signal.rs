@@ -66,6 +66,10 @@ pub fn signal_handler<
 
     // if we are reaching here under Asyncify rewinding process, we need to resume its callstack instead of doing the normal execution
     if let AsyncifyState::Rewind(_) = caller.as_context().get_asyncify_state() {
+        // A cage that froze here for the checkpoint of an ancestor cage resumes here
+        if crate::resume_frozen(caller).is_some() {
+            return Ok(0);
+        }
         // Retrieve the signal function entered last time with its parameters.
         // None is expected here: exit_call and syscall-level asyncify also
         // trigger epoch rewind, but they don't push signal rewind data.
@@ -103,6 +107,13 @@ pub fn signal_handler<
     // as only main thread is responsible for handling the signals, and the only situation for
     // other non-main thread entered the epoch callback is that they are killed
 
+    // the checkpoint of an ancestor cage stops the cage here until the snapshot file is written
+    if let Some(tree) = crate::checkpoint::take_freeze_request(cageid) {
+        if ctx.freeze_call(caller, tree) {
+            return Ok(0);
+        }
+    }
+
     // we loop to retrieve pending signals one by one untill there isn't any unblocked pending signals
     loop {
         let signal = cage::signal::lind_get_first_signal(cageid);
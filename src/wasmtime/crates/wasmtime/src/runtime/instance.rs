@@ -19,9 +19,9 @@ use core::ptr::NonNull;
 use std::sync::LazyLock;
 use std::sync::atomic::{AtomicI32, Ordering};
 use sysdefs::constants::fs_const::{
-    MAP_ANONYMOUS, MAP_FIXED, MAP_PRIVATE, PAGESHIFT, PROT_READ, PROT_WRITE,
+    MAP_ANONYMOUS, MAP_FIXED, MAP_PRIVATE, PAGESHIFT, PROT_NONE, PROT_READ, PROT_WRITE,
 };
-use sysdefs::constants::syscall_const::MMAP_SYSCALL;
+use sysdefs::constants::syscall_const::{MMAP_SYSCALL, MPROTECT_SYSCALL};
 use sysdefs::constants::{
     DEFAULT_STACKSIZE, FPCAST_FUNC_SIGNATURE, GUARD_SIZE, PAGESIZE, lind_platform_const,
 };
@@ -592,6 +592,39 @@ impl Instance {
                     0,
                     cageid,
                 );
+
+                // The guard before each grate-worker stack slot is made inaccessible, so that a
+                // worker overflowing its stack traps instead of writing into the slot below.
+                // Like the mapping above, this goes through RawPOSIX so the vmmap records the
+                // guards, which then keeps other mappings out of them.
+                if stack_arena_base != 0 {
+                    for worker in 0..lind_platform_const::MAX_GRATE_WORKERS as u64 {
+                        let guard = stack_arena_base as u64
+                            + worker
+                                * (lind_platform_const::GRATE_STACK_GUARD_SIZE
+                                    + lind_platform_const::GRATE_STACK_SLOT_SIZE)
+                                    as u64;
+                        // mprotect takes a host address
+                        make_syscall(
+                            cageid,
+                            (MPROTECT_SYSCALL) as u64,
+                            0,
+                            cageid,
+                            memory_base as u64 + guard,
+                            cageid,
+                            lind_platform_const::GRATE_STACK_GUARD_SIZE as u64,
+                            cageid,
+                            PROT_NONE as u64,
+                            cageid,
+                            lind_platform_const::UNUSED_ARG,
+                            lind_platform_const::UNUSED_ID,
+                            lind_platform_const::UNUSED_ARG,
+                            lind_platform_const::UNUSED_ID,
+                            lind_platform_const::UNUSED_ARG,
+                            lind_platform_const::UNUSED_ID,
+                        );
+                    }
+                }
             }
             // InstantiateChild: this is the child wasm instance forked by parent
             InstantiateType::InstantiateChild {
@@ -105,6 +105,11 @@ impl CompiledModule {
         self.module.dylink_import_info.as_ref()
     }
 
+    /// lind-wasm: retrieve the cage memory saved by `lind-boot --preinit`
+    pub fn lind_preinit(&self) -> Option<&[u8]> {
+        self.module.lind_preinit.as_deref()
+    }
+
     /// Looks up the `name` section name for the function index `idx`, if one
     /// was specified in the original wasm module.
     pub fn func_name(&self, idx: FuncIndex) -> Option<&str> {
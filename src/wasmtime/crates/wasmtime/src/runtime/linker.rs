@@ -597,10 +597,12 @@ impl<T> Linker<T> {
                     }
                     let base = mem.get_memory_base();
                     memory_base = Some(base);
-                    // lind-wasm: reset the child's fresh 4 GiB to PROT_NONE before
-                    // rawposix takes ownership via init_vmmap + fork_vmmap.
+                    // lind-wasm: reset the child's fresh 4 GiB (or the whole memory of a
+                    // 64-bit cage) to PROT_NONE before rawposix takes ownership via
+                    // init_vmmap + fork_vmmap.
+                    let len = (max_pages as usize) << memory_type.page_size_log2();
                     unsafe {
-                        libc::mprotect(base as *mut libc::c_void, 1usize << 32, libc::PROT_NONE);
+                        libc::mprotect(base as *mut libc::c_void, len, libc::PROT_NONE);
                     }
                     new_linker.define(&mut store, &module, &name, mem)?;
                 }
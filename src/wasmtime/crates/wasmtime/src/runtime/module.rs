@@ -1113,6 +1113,12 @@ impl Module {
         self.compiled_module().dylink_import_info()
     }
 
+    // lind-wasm: retrieve the cage memory saved by `lind-boot --preinit`
+    #[allow(missing_docs)]
+    pub fn lind_preinit(&self) -> Option<&[u8]> {
+        self.compiled_module().lind_preinit()
+    }
+
     pub(crate) fn id(&self) -> CompiledModuleId {
         self.inner.module.unique_id()
     }
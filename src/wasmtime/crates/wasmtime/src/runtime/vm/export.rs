@@ -40,6 +40,13 @@ impl ExportMemory {
         }
     }
 
+    /// lind-wasm: the shared memory as a [`crate::SharedMemory`] of `engine`, so that another
+    /// store can import it.
+    pub fn into_shared_memory(self, engine: &crate::Engine) -> Option<crate::SharedMemory> {
+        self.shared()
+            .map(|vm| crate::SharedMemory::from_raw(vm, engine.clone()))
+    }
+
     /// Returns the base pointer of the memory without consuming self.
     /// For shared memory the pointer is read directly from VMMemoryDefinition;
     /// for unshared memory None is returned (caller must use data_ptr with a store).
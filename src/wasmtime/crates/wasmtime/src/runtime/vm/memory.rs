@@ -113,6 +113,17 @@ pub use shared_memory_disabled::SharedMemory;
 
 pub const MAX_MEMORY_SIZE: usize = 1 << 32;
 
+/// lind-wasm: size of the linear memory of a cage, reserved whole when it is created: 4 GiB, or
+/// `MAX_LINEAR_MEMORY64_SIZE` for a 64-bit (memory64) cage.
+pub fn lind_memory_size(ty: &wasmtime_environ::Memory) -> usize {
+    match ty.idx_type {
+        wasmtime_environ::IndexType::I32 => MAX_MEMORY_SIZE,
+        wasmtime_environ::IndexType::I64 => {
+            sysdefs::constants::lind_platform_const::MAX_LINEAR_MEMORY64_SIZE as usize + 1
+        }
+    }
+}
+
 /// A memory allocator
 pub trait RuntimeMemoryCreator: Send + Sync {
     /// Create new RuntimeLinearMemory
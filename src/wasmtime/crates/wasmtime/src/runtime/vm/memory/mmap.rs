@@ -59,7 +59,7 @@ impl MmapMemory {
         let is_lind_guest_memory = ty.shared;
         // lind-wasm: we enable maximum use of memory at start
         if is_lind_guest_memory {
-            maximum = Some(super::MAX_MEMORY_SIZE);
+            maximum = Some(super::lind_memory_size(ty));
         }
 
         // It's a programmer error for these two configuration values to exceed
@@ -87,7 +87,10 @@ impl MmapMemory {
         //
         // If the minimum size doesn't fit within this linear memory.
         let mut alloc_bytes = if is_lind_guest_memory {
-            tunables.memory_reservation
+            // the memory of a 64-bit cage is larger than the default reservation
+            tunables
+                .memory_reservation
+                .max(super::lind_memory_size(ty) as u64)
         } else {
             tunables.lind_internal_memory_reservation
         };
@@ -125,9 +125,9 @@ impl<'a> ModuleContext<'a> {
             wasmparser::TypeRef::Table(ty) => {
                 self.push_table(ty);
             }
-            wasmparser::TypeRef::Tag(_) => {
-                unreachable!("exceptions are unsupported; checked in validation")
-            }
+            // lind-wasm: tags, like the `__c_longjmp` tag of Lind modules,
+            // hold no state to snapshot.
+            wasmparser::TypeRef::Tag(_) => {}
             wasmparser::TypeRef::FuncExact(_) => {
                 unreachable!("custom-descriptors are unsupported; checked in validation")
             }
@@ -44,7 +44,8 @@ const DEFAULT_KEEP_INIT_FUNC: bool = false;
 /// * The initialization function may not call any imported functions. Doing so
 ///   will trigger a trap and `wizer` will exit.
 ///
-/// * The Wasm module may not import globals, tables, or memories.
+/// * The Wasm module may not import globals, tables, or memories, unless
+///   imported memories are allowed with [`Wizer::allow_imported_memory`].
 ///
 /// * Reference types are not supported yet. This is tricky because it would
 ///   allow the Wasm module to mutate tables, and we would need to be able to
@@ -98,6 +99,11 @@ pub struct Wizer {
         arg(long, require_equals = true, value_name = "true|false")
     )]
     keep_init_func: Option<Option<bool>>,
+
+    /// lind-wasm: accept a module that imports its memory, whose contents the
+    /// embedder saves on its own.
+    #[cfg_attr(feature = "clap", arg(skip))]
+    allow_imported_memory: bool,
 }
 
 #[cfg(feature = "clap")]
@@ -151,6 +157,7 @@ impl Wizer {
             init_func: "wizer-initialize".to_string(),
             func_renames: vec![],
             keep_init_func: None,
+            allow_imported_memory: false,
         }
     }
 
@@ -184,6 +191,20 @@ impl Wizer {
         self
     }
 
+    /// lind-wasm: accept a module that imports its memory, such as a Lind
+    /// cage's shared memory.
+    ///
+    /// Imported memories are not snapshotted, so the embedder must save their
+    /// contents itself. `data.drop` is accepted as well: these modules drop
+    /// their passive segments in a start function, which has already run when
+    /// the snapshot is taken and is removed from the pre-initialized module.
+    ///
+    /// This is `false` by default.
+    pub fn allow_imported_memory(&mut self, allow: bool) -> &mut Self {
+        self.allow_imported_memory = allow;
+        self
+    }
+
     /// First half of [`Self::run`] which instruments the provided `wasm` and
     /// produces a new wasm module which should be run by a runtime.
     ///
@@ -205,9 +226,10 @@ impl Wizer {
                 wasmparser::TypeRef::Table(_) => {
                     bail!("imported tables are not supported")
                 }
-                wasmparser::TypeRef::Memory(_) => {
+                wasmparser::TypeRef::Memory(_) if !self.allow_imported_memory => {
                     bail!("imported memories are not supported")
                 }
+                wasmparser::TypeRef::Memory(_) => {}
                 wasmparser::TypeRef::Func(_) => {}
                 wasmparser::TypeRef::FuncExact(_) => {}
                 wasmparser::TypeRef::Tag(_) => {}
@@ -307,7 +329,9 @@ impl Wizer {
                             wasmparser::Operator::ElemDrop { .. } => {
                                 bail!("unsupported `elem.drop` instruction")
                             }
-                            wasmparser::Operator::DataDrop { .. } => {
+                            wasmparser::Operator::DataDrop { .. }
+                                if !self.allow_imported_memory =>
+                            {
                                 bail!("unsupported `data.drop` instruction")
                             }
 
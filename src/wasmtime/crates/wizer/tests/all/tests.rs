@@ -191,6 +191,41 @@ async fn reject_imported_memory() -> Result<()> {
     .await
 }
 
+#[test]
+fn allow_imported_memory() -> Result<()> {
+    let wasm = wat_to_wasm(
+        r#"
+            (module
+              (import "env" "memory" (memory 1 1 shared))
+              (import "env" "__c_longjmp" (tag (param i32)))
+              (global $g (mut i32) (i32.const 0))
+              (data $data "hello, wizer!")
+              (func (export "wizer-initialize")
+                data.drop $data
+                i32.const 42
+                global.set $g))
+        "#,
+    )?;
+    assert!(get_wizer().instrument(&wasm).is_err());
+
+    let mut wizer = get_wizer();
+    wizer.allow_imported_memory(true);
+    let (_, instrumented) = wizer.instrument(&wasm)?;
+
+    // Only the defined global is exported for the snapshot, not the imported memory
+    let exports: Vec<_> = wasmparser::Parser::new(0)
+        .parse_all(&instrumented)
+        .filter_map(|payload| match payload {
+            Ok(wasmparser::Payload::ExportSection(exports)) => Some(exports),
+            _ => None,
+        })
+        .flatten()
+        .map(|export| export.unwrap().name.to_string())
+        .collect();
+    assert_eq!(exports, ["wizer-initialize", "__wizer_global_0"]);
+    Ok(())
+}
+
 #[tokio::test]
 async fn reject_imported_global() -> Result<()> {
     fails_wizening(
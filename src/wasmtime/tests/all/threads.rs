@@ -121,6 +121,50 @@ fn test_probe_shared_memory_size() -> Result<()> {
     Ok(())
 }
 
+// lind-wasm: the memory of a 64-bit cage is reserved whole like a 32-bit one, and wasm64 code
+// reaches all of it once it is grown to its maximum.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_lind_memory64_shared_memory() -> Result<()> {
+    const PAGES: u64 = 1 << 20; // 64 GiB
+    let wat = r#"(module
+        (import "env" "memory" (memory i64 1 1048576 shared))
+        (func (export "store") (param i64 i32) (i32.store (local.get 0) (local.get 1)))
+        (func (export "load") (param i64) (result i32) (i32.load (local.get 0)))
+    )"#;
+    let mut config = Config::new();
+    config.wasm_threads(true);
+    config.wasm_memory64(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, ());
+    let ty = MemoryTypeBuilder::new()
+        .shared(true)
+        .memory64(true)
+        .min(1)
+        .max(Some(PAGES))
+        .build()?;
+    let shared_memory = SharedMemory::new(&engine, ty)?;
+    shared_memory.grow(PAGES - 1)?;
+    assert_eq!(shared_memory.size(), PAGES);
+    assert_eq!(shared_memory.data_size(), 64 << 30);
+
+    let instance = Instance::new(&mut store, &module, &[shared_memory.clone().into()])?;
+    let store_fn = instance.get_typed_func::<(u64, i32), ()>(&mut store, "store")?;
+    let load_fn = instance.get_typed_func::<u64, i32>(&mut store, "load")?;
+    for (addr, value) in [(5 << 30, 5), ((64 << 30) - 4, 64)] {
+        store_fn.call(&mut store, (addr, value))?;
+        assert_eq!(load_fn.call(&mut store, addr)?, value);
+    }
+    // The host sees the same memory above 4 GiB
+    let byte = unsafe { *shared_memory.data()[5 << 30].get() };
+    assert_eq!(byte, 5);
+    // and nothing lies past its end
+    assert!(load_fn.call(&mut store, 64 << 30).is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_multi_memory() -> Result<()> {
     let wat = r#"(module
@@ -8,7 +8,9 @@
  *
  * The lookup workloads exercise getpid(), getppid(), getuid(), and geteuid().
  * The fork workload also exercises cage allocation, parent/child bookkeeping,
- * child exit, zombie recording, SIGCHLD delivery, waitpid(), and cleanup.
+ * child exit, zombie recording, SIGCHLD delivery, waitpid(), and cleanup. The
+ * heap fork workload repeats it with a large heap, to show how fork scales with
+ * the parent's memory.
  */
 #define _GNU_SOURCE
 #include "bench.h"
@@ -17,12 +19,15 @@
 #include <pthread.h>
 #include <stdio.h>
 #include <stdlib.h>
+#include <string.h>
 #include <sys/types.h>
 #include <sys/wait.h>
 #include <unistd.h>
 
 #define LOOKUP_LOOPS LOOPS_LARGE
 #define FORK_LOOPS 2000
+#define HEAP_FORK_LOOPS 200
+#define HEAP_MB 256
 #define THREAD_COUNT 4
 #define WARMUP_LOOPS LOOPS_SMALL
 
@@ -132,6 +137,55 @@ static long long bench_fork_wait(int loops) {
     return (end - start) / loops;
 }
 
+/*
+ * Forks with a large, fully touched heap. The parent dirties one page per MiB
+ * between forks, as a server handling requests would, so the cost reflects how
+ * fork scales with the parent's memory.
+ */
+static long long bench_fork_wait_heap(int heap_mb, int loops) {
+    size_t size = (size_t)heap_mb << 20;
+    char *heap = malloc(size);
+    if (heap == NULL) {
+        perror("malloc");
+        exit(1);
+    }
+    memset(heap, 0x5a, size);
+
+    long long start = gettimens();
+
+    for (int i = 0; i < loops; i++) {
+        for (size_t off = 0; off < size; off += 1 << 20) {
+            heap[off + (i & 0xff) * 4096] = (char)i;
+        }
+
+        pid_t pid = fork();
+        if (pid < 0) {
+            perror("fork");
+            fprintf(stderr, "failed at heap fork iteration %d\n", i);
+            exit(1);
+        }
+
+        if (pid == 0) {
+            _exit(heap[(i & 0xff) * 4096] == (char)i ? 0 : 1);
+        }
+
+        int status = 0;
+        if (waitpid(pid, &status, 0) < 0) {
+            perror("waitpid");
+            exit(1);
+        }
+
+        if (!WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+            fprintf(stderr, "child saw a stale heap at iteration %d\n", i);
+            exit(1);
+        }
+    }
+
+    long long end = gettimens();
+    free(heap);
+    return (end - start) / loops;
+}
+
 struct thread_arg {
     int loops;
     int tid;
@@ -230,6 +284,10 @@ int main(void) {
                        "-",
                        bench_fork_wait(FORK_LOOPS),
                        FORK_LOOPS);
+    emit_result("Cage fork + waitpid, MiB heap",
+                HEAP_MB,
+                bench_fork_wait_heap(HEAP_MB, HEAP_FORK_LOOPS),
+                HEAP_FORK_LOOPS);
 
     return 0;
 }
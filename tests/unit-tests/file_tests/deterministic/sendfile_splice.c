@@ -0,0 +1,133 @@
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/sendfile.h>
+#include <sys/stat.h>
+#include <sys/uio.h>
+#include <unistd.h>
+
+#define INPUT_FILE "testfiles/sendfile_splice_in.txt"
+#define OUTPUT_FILE "testfiles/sendfile_splice_out.txt"
+
+static void cleanup(void)
+{
+    unlink(INPUT_FILE);
+    unlink(OUTPUT_FILE);
+}
+
+static int fail(const char *what)
+{
+    fprintf(stderr, "%s failed: %s\n", what, strerror(errno));
+    cleanup();
+    return 1;
+}
+
+int main(void)
+{
+    cleanup();
+
+    int in = open(INPUT_FILE, O_CREAT | O_RDWR | O_TRUNC, 0644);
+    int out = open(OUTPUT_FILE, O_CREAT | O_RDWR | O_TRUNC, 0644);
+    if (in < 0 || out < 0)
+        return fail("open");
+
+    const char *msg = "0123456789";
+    size_t len = strlen(msg);
+    if (write(in, msg, len) != (ssize_t)len)
+        return fail("write");
+
+    /* sendfile with an explicit offset leaves the file position alone. */
+    off_t off = 3;
+    if (sendfile(out, in, &off, 4) != 4 || off != 7)
+        return fail("sendfile with offset");
+    if (lseek(in, 0, SEEK_CUR) != (off_t)len)
+        return fail("sendfile file position");
+
+    char buf[32] = {0};
+    if (pread(out, buf, sizeof(buf) - 1, 0) != 4 || strcmp(buf, "3456") != 0)
+        return fail("sendfile content");
+
+    /* splice file -> pipe, tee pipe -> pipe, splice pipe -> file. */
+    int p1[2], p2[2];
+    if (pipe(p1) < 0 || pipe(p2) < 0)
+        return fail("pipe");
+
+    loff_t in_off = 0;
+    if (splice(in, &in_off, p1[1], NULL, 5, 0) != 5 || in_off != 5)
+        return fail("splice file to pipe");
+    if (tee(p1[0], p2[1], 5, 0) != 5)
+        return fail("tee");
+
+    loff_t out_off = 4;
+    if (splice(p1[0], NULL, out, &out_off, 5, 0) != 5 || out_off != 9)
+        return fail("splice pipe to file");
+
+    memset(buf, 0, sizeof(buf));
+    if (read(p2[0], buf, sizeof(buf) - 1) != 5 || strcmp(buf, "01234") != 0)
+        return fail("tee content");
+
+    /* vmsplice user memory into a pipe. */
+    struct iovec iov[2] = {
+        {.iov_base = "ab", .iov_len = 2},
+        {.iov_base = "cd", .iov_len = 2},
+    };
+    if (vmsplice(p2[1], iov, 2, 0) != 4)
+        return fail("vmsplice");
+    memset(buf, 0, sizeof(buf));
+    if (read(p2[0], buf, sizeof(buf) - 1) != 4 || strcmp(buf, "abcd") != 0)
+        return fail("vmsplice content");
+
+    close(p1[0]);
+    close(p1[1]);
+    close(p2[0]);
+    close(p2[1]);
+
+    /* fallocate extends the file; posix_fadvise returns its error number. */
+    if (fallocate(out, 0, 0, 4096) != 0) {
+        if (errno != EOPNOTSUPP)
+            return fail("fallocate");
+    } else {
+        struct stat st;
+        if (fstat(out, &st) != 0 || st.st_size != 4096)
+            return fail("fallocate size");
+    }
+
+    if (posix_fadvise(out, 0, 0, POSIX_FADV_SEQUENTIAL) != 0)
+        return fail("posix_fadvise");
+    if (posix_fadvise(out, 0, 0, 12345) != EINVAL) {
+        fprintf(stderr, "posix_fadvise with bad advice should return EINVAL\n");
+        cleanup();
+        return 1;
+    }
+
+    /* statx agrees with fstat. */
+    struct statx stx;
+    struct stat st;
+    if (statx(AT_FDCWD, OUTPUT_FILE, 0, STATX_BASIC_STATS, &stx) != 0)
+        return fail("statx");
+    if (fstat(out, &st) != 0)
+        return fail("fstat");
+    if ((off_t)stx.stx_size != st.st_size || stx.stx_ino != st.st_ino ||
+        !(stx.stx_mask & STATX_SIZE))
+        return fail("statx content");
+
+    errno = 0;
+    if (statx(AT_FDCWD, "testfiles/sendfile_splice_missing", 0,
+              STATX_BASIC_STATS, &stx) != -1 || errno != ENOENT) {
+        fprintf(stderr, "statx on a missing file should fail with ENOENT\n");
+        cleanup();
+        return 1;
+    }
+
+    close(in);
+    close(out);
+    cleanup();
+
+    printf("sendfile/splice unit test passed\n");
+    fflush(stdout);
+
+    return 0;
+}
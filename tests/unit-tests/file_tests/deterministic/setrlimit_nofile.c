@@ -0,0 +1,93 @@
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <sys/resource.h>
+#include <unistd.h>
+
+#define LOW_LIMIT 16
+
+int main(void)
+{
+    struct rlimit orig, rl;
+    int fds[LOW_LIMIT];
+    int nfds = 0;
+
+    if (getrlimit(RLIMIT_NOFILE, &orig) != 0) {
+        perror("getrlimit");
+        return 1;
+    }
+
+    // Lower the soft limit and keep the hard limit.
+    rl.rlim_cur = LOW_LIMIT;
+    rl.rlim_max = orig.rlim_max;
+    if (setrlimit(RLIMIT_NOFILE, &rl) != 0) {
+        perror("setrlimit lower soft");
+        return 1;
+    }
+    if (getrlimit(RLIMIT_NOFILE, &rl) != 0 || rl.rlim_cur != LOW_LIMIT ||
+        rl.rlim_max != orig.rlim_max) {
+        fprintf(stderr, "getrlimit did not report the new soft limit\n");
+        return 1;
+    }
+
+    // Fill the table until we hit EMFILE; every fd must stay under the limit.
+    for (;;) {
+        int fd = open("/dev/null", O_RDONLY);
+        if (fd < 0) {
+            if (errno != EMFILE) {
+                perror("open");
+                return 1;
+            }
+            break;
+        }
+        if (fd >= LOW_LIMIT || nfds >= LOW_LIMIT) {
+            fprintf(stderr, "fd %d exceeds soft limit %d\n", fd, LOW_LIMIT);
+            return 1;
+        }
+        fds[nfds++] = fd;
+    }
+
+    if (nfds == 0) {
+        fprintf(stderr, "no fds could be opened\n");
+        return 1;
+    }
+
+    // Closing one fd makes room for exactly one more open.
+    close(fds[--nfds]);
+
+    if (dup2(0, LOW_LIMIT) != -1 || errno != EBADF) {
+        fprintf(stderr, "dup2 past soft limit should fail with EBADF\n");
+        return 1;
+    }
+    if (fcntl(0, F_DUPFD, LOW_LIMIT) != -1 || errno != EINVAL) {
+        fprintf(stderr, "F_DUPFD past soft limit should fail with EINVAL\n");
+        return 1;
+    }
+
+    while (nfds > 0)
+        close(fds[--nfds]);
+
+    // Soft limit may not exceed the hard limit.
+    rl.rlim_cur = LOW_LIMIT + 1;
+    rl.rlim_max = LOW_LIMIT;
+    if (setrlimit(RLIMIT_NOFILE, &rl) != -1 || errno != EINVAL) {
+        fprintf(stderr, "soft > hard should fail with EINVAL\n");
+        return 1;
+    }
+
+    // Lower the hard limit, then raising it again must be refused.
+    rl.rlim_cur = LOW_LIMIT;
+    rl.rlim_max = LOW_LIMIT * 2;
+    if (setrlimit(RLIMIT_NOFILE, &rl) != 0) {
+        perror("setrlimit lower hard");
+        return 1;
+    }
+    rl.rlim_max = LOW_LIMIT * 4;
+    if (setrlimit(RLIMIT_NOFILE, &rl) != -1 || errno != EPERM) {
+        fprintf(stderr, "raising the hard limit should fail with EPERM\n");
+        return 1;
+    }
+
+    printf("setrlimit_nofile unit test passed\n");
+    return 0;
+}
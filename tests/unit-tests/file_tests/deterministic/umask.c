@@ -0,0 +1,114 @@
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <sys/stat.h>
+#include <sys/un.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define FILE_PATH "testfiles/umask_file.txt"
+#define DIR_PATH "testfiles/umask_dir"
+#define FIFO_PATH "testfiles/umask_fifo"
+#define SOCK_PATH "testfiles/umask_sock"
+#define CHILD_PATH "testfiles/umask_child.txt"
+
+static void cleanup(void)
+{
+    unlink(FILE_PATH);
+    rmdir(DIR_PATH);
+    unlink(FIFO_PATH);
+    unlink(SOCK_PATH);
+    unlink(CHILD_PATH);
+}
+
+static int check_mode(const char *path, mode_t expected)
+{
+    struct stat st;
+    if (stat(path, &st) != 0) {
+        perror("stat");
+        return 1;
+    }
+    if ((st.st_mode & 0777) != expected) {
+        fprintf(stderr, "%s: mode %o, expected %o\n", path,
+                (unsigned)(st.st_mode & 0777), (unsigned)expected);
+        return 1;
+    }
+    return 0;
+}
+
+int main(void)
+{
+    cleanup();
+
+    umask(022);
+    if (umask(077) != 022) {
+        fprintf(stderr, "umask did not return the previous mask\n");
+        return 1;
+    }
+
+    int fd = open(FILE_PATH, O_CREAT | O_RDWR | O_TRUNC, 0666);
+    if (fd < 0) {
+        perror("open");
+        cleanup();
+        return 1;
+    }
+    close(fd);
+
+    if (mkdir(DIR_PATH, 0777) != 0 || mkfifo(FIFO_PATH, 0666) != 0) {
+        perror("mkdir/mkfifo");
+        cleanup();
+        return 1;
+    }
+
+    int sock = socket(AF_UNIX, SOCK_STREAM, 0);
+    struct sockaddr_un addr;
+    memset(&addr, 0, sizeof(addr));
+    addr.sun_family = AF_UNIX;
+    strcpy(addr.sun_path, SOCK_PATH);
+    if (sock < 0 || bind(sock, (struct sockaddr *)&addr, sizeof(addr)) != 0) {
+        perror("socket/bind");
+        cleanup();
+        return 1;
+    }
+    close(sock);
+
+    if (check_mode(FILE_PATH, 0600) || check_mode(DIR_PATH, 0700) ||
+        check_mode(FIFO_PATH, 0600) || check_mode(SOCK_PATH, 0700)) {
+        cleanup();
+        return 1;
+    }
+
+    /* The mask is inherited by a forked child. */
+    pid_t pid = fork();
+    if (pid == 0) {
+        if (umask(0) != 077)
+            _exit(1);
+        int cfd = open(CHILD_PATH, O_CREAT | O_RDWR | O_TRUNC, 0666);
+        if (cfd < 0)
+            _exit(1);
+        close(cfd);
+        _exit(0);
+    }
+
+    int status = 0;
+    if (pid < 0 || waitpid(pid, &status, 0) != pid || !WIFEXITED(status) ||
+        WEXITSTATUS(status) != 0) {
+        fprintf(stderr, "child did not inherit the umask\n");
+        cleanup();
+        return 1;
+    }
+
+    /* The child's umask(0) does not leak back into the parent. */
+    if (check_mode(CHILD_PATH, 0666) || umask(022) != 077) {
+        cleanup();
+        return 1;
+    }
+
+    cleanup();
+
+    printf("umask unit test passed\n");
+    fflush(stdout);
+
+    return 0;
+}
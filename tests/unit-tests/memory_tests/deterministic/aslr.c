@@ -0,0 +1,91 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <inttypes.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+/* With ASLR, the mmap area, the heap and thread stacks move by a random number of pages, drawn
+ * from the seed given with --aslr-seed. The first seeded run records where they landed, a second
+ * run with the same seed finds them at the same addresses, and a run with another seed finds them
+ * elsewhere. The run without arguments only checks that the layout is usable. */
+// lind-run: --aslr-seed 1 {wasm} record
+// lind-run: --aslr-seed 1 {wasm} same
+// lind-run: --aslr-seed 2 {wasm} differs
+
+#define LAYOUT "aslr_layout.txt"
+#define STACK_LEN (16 * 4096)
+
+struct layout {
+    uintptr_t mapping;
+    uintptr_t brk;
+    uintptr_t stack;
+};
+
+static struct layout current_layout(void) {
+    struct layout layout;
+
+    char *mapping = mmap(NULL, 4096, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(mapping != MAP_FAILED);
+    mapping[0] = 1;
+    layout.mapping = (uintptr_t)mapping;
+
+    layout.brk = (uintptr_t)sbrk(0);
+
+    /* What a thread library asks for to place a stack */
+    char *stack = mmap(NULL, STACK_LEN, PROT_READ | PROT_WRITE,
+                       MAP_PRIVATE | MAP_ANONYMOUS | MAP_STACK, -1, 0);
+    assert(stack != MAP_FAILED);
+    memset(stack, 's', STACK_LEN);
+    layout.stack = (uintptr_t)stack;
+
+    /* The stack is apart from the mapping before it, and the heap can grow */
+    assert(stack + STACK_LEN <= mapping || stack >= mapping + 4096);
+    assert(sbrk(4096) != (void *)-1);
+    assert(munmap(stack, STACK_LEN) == 0);
+    return layout;
+}
+
+static struct layout recorded_layout(void) {
+    struct layout layout;
+    FILE *f = fopen(LAYOUT, "r");
+    assert(f != NULL);
+    assert(fscanf(f, "%" SCNxPTR " %" SCNxPTR " %" SCNxPTR, &layout.mapping, &layout.brk,
+                  &layout.stack) == 3);
+    fclose(f);
+    return layout;
+}
+
+int main(int argc, char **argv) {
+    struct layout layout = current_layout();
+    const char *mode = argc > 1 ? argv[1] : NULL;
+
+    if (mode == NULL) {
+        printf("layout usable\n");
+    } else if (strcmp(mode, "record") == 0) {
+        FILE *f = fopen(LAYOUT, "w");
+        assert(f != NULL);
+        fprintf(f, "%" PRIxPTR " %" PRIxPTR " %" PRIxPTR "\n", layout.mapping, layout.brk,
+                layout.stack);
+        fclose(f);
+        printf("seed 1 recorded\n");
+    } else if (strcmp(mode, "same") == 0) {
+        struct layout seed1 = recorded_layout();
+        assert(layout.mapping == seed1.mapping);
+        assert(layout.brk == seed1.brk);
+        assert(layout.stack == seed1.stack);
+        printf("seed 1 repeats its layout\n");
+    } else if (strcmp(mode, "differs") == 0) {
+        struct layout seed1 = recorded_layout();
+        assert(layout.mapping != seed1.mapping || layout.brk != seed1.brk ||
+               layout.stack != seed1.stack);
+        assert(unlink(LAYOUT) == 0);
+        printf("seed 2 gives another layout\n");
+    } else {
+        fprintf(stderr, "unknown mode %s\n", mode);
+        return 1;
+    }
+    return 0;
+}
@@ -0,0 +1,119 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* Fork maps private memory copy-on-write: writes on either side after fork stay private, memory
+ * written between forks reaches the next child, memory discarded with MADV_DONTNEED reads as
+ * zeros again, and memory moved with mremap keeps its contents across fork. */
+
+#define SIZE (8 * 1024 * 1024)
+#define PAGE 4096
+
+static void wait_child(pid_t pid) {
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+
+static void check_isolation(char *buf) {
+    int to_child[2], to_parent[2];
+    assert(pipe(to_child) == 0 && pipe(to_parent) == 0);
+    memset(buf, 0x11, SIZE);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        char c;
+        for (int off = 0; off < SIZE; off += PAGE)
+            assert(buf[off] == 0x11);
+        memset(buf, 0x22, SIZE / 2);
+        assert(write(to_parent[1], "w", 1) == 1);
+        /* The parent's writes made meanwhile stay in the parent */
+        assert(read(to_child[0], &c, 1) == 1);
+        for (int off = SIZE / 2; off < SIZE; off += PAGE)
+            assert(buf[off] == 0x11);
+        _exit(0);
+    }
+    char c;
+    assert(read(to_parent[0], &c, 1) == 1);
+    for (int off = 0; off < SIZE; off += PAGE)
+        assert(buf[off] == 0x11);
+    memset(buf + SIZE / 2, 0x33, SIZE / 2);
+    assert(write(to_child[1], "r", 1) == 1);
+    wait_child(pid);
+    close(to_child[0]);
+    close(to_child[1]);
+    close(to_parent[0]);
+    close(to_parent[1]);
+
+    /* A second fork sees what the parent wrote since the first */
+    pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        assert(buf[0] == 0x11 && buf[SIZE - 1] == 0x33);
+        _exit(0);
+    }
+    wait_child(pid);
+}
+
+static void check_dontneed(void) {
+    char *map = mmap(NULL, SIZE, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(map != MAP_FAILED);
+    memset(map, 0x44, SIZE);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        assert(madvise(map, SIZE, MADV_DONTNEED) == 0);
+        for (int off = 0; off < SIZE; off += PAGE)
+            assert(map[off] == 0);
+        _exit(0);
+    }
+    wait_child(pid);
+    assert(madvise(map, SIZE / 2, MADV_DONTNEED) == 0);
+    assert(map[0] == 0 && map[SIZE - 1] == 0x44);
+    munmap(map, SIZE);
+}
+
+static void check_mremap(void) {
+    char *map = mmap(NULL, 4 * PAGE, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(map != MAP_FAILED);
+    memset(map, 0x55, 4 * PAGE);
+
+    /* Fork once so that the mapping comes from the fork snapshot, then move it */
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0)
+        _exit(0);
+    wait_child(pid);
+    char *moved = mremap(map, 4 * PAGE, 64 * PAGE, MREMAP_MAYMOVE);
+    assert(moved != MAP_FAILED);
+    moved[63 * PAGE] = 0x66;
+
+    pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        for (int off = 0; off < 4 * PAGE; off += PAGE)
+            assert(moved[off] == 0x55);
+        assert(moved[32 * PAGE] == 0 && moved[63 * PAGE] == 0x66);
+        _exit(0);
+    }
+    wait_child(pid);
+    munmap(moved, 64 * PAGE);
+}
+
+int main(void) {
+    char *buf = malloc(SIZE);
+    assert(buf != NULL);
+    check_isolation(buf);
+    check_dontneed();
+    check_mremap();
+    free(buf);
+    printf("fork_cow test passed\n");
+    return 0;
+}
@@ -0,0 +1,81 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define SHM_NAME "/lind-memfd-test"
+
+int main(void)
+{
+    long pagesize = sysconf(_SC_PAGESIZE);
+
+    // memfd_create: only the supported flags, close-on-exec on request
+    assert(memfd_create("bad", 0x100) == -1 && errno == EINVAL);
+    int memfd = memfd_create("lind-test", MFD_CLOEXEC | MFD_ALLOW_SEALING);
+    assert(memfd >= 0);
+    assert(fcntl(memfd, F_GETFD) & FD_CLOEXEC);
+    assert(ftruncate(memfd, pagesize) == 0);
+
+    char *map = mmap(NULL, pagesize, PROT_READ | PROT_WRITE, MAP_SHARED, memfd, 0);
+    assert(map != MAP_FAILED);
+    strcpy(map, "parent");
+
+    // shm_open: a named object in /dev/shm
+    shm_unlink(SHM_NAME);
+    int shmfd = shm_open(SHM_NAME, O_RDWR | O_CREAT | O_EXCL, 0600);
+    assert(shmfd >= 0);
+    assert(shm_open(SHM_NAME, O_RDWR | O_CREAT | O_EXCL, 0600) == -1 && errno == EEXIST);
+    assert(ftruncate(shmfd, pagesize) == 0);
+    struct stat st;
+    assert(fstat(shmfd, &st) == 0 && st.st_size == pagesize && (st.st_mode & 0777) == 0600);
+
+    char *shm = mmap(NULL, pagesize, PROT_READ | PROT_WRITE, MAP_SHARED, shmfd, 0);
+    assert(shm != MAP_FAILED);
+    strcpy(shm, "parent");
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        assert(strcmp(map, "parent") == 0 && strcmp(shm, "parent") == 0);
+        strcpy(map, "child");
+
+        // A second open of the name reaches the same object
+        int fd = shm_open(SHM_NAME, O_RDWR, 0);
+        assert(fd >= 0);
+        char *again = mmap(NULL, pagesize, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+        assert(again != MAP_FAILED);
+        strcpy(again, "child");
+        _exit(0);
+    }
+
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+    assert(strcmp(map, "child") == 0);
+    assert(strcmp(shm, "child") == 0);
+
+    // Seals: a sealed memfd can no longer grow or shrink
+    assert(fcntl(memfd, F_ADD_SEALS, F_SEAL_SHRINK | F_SEAL_GROW) == 0);
+    assert(fcntl(memfd, F_GET_SEALS) == (F_SEAL_SHRINK | F_SEAL_GROW));
+    assert(ftruncate(memfd, 2 * pagesize) == -1 && errno == EPERM);
+
+    // The object outlives its name while mapped
+    assert(shm_unlink(SHM_NAME) == 0);
+    assert(shm_open(SHM_NAME, O_RDWR, 0) == -1 && errno == ENOENT);
+    assert(shm_unlink(SHM_NAME) == -1 && errno == ENOENT);
+    assert(strcmp(shm, "child") == 0);
+
+    assert(munmap(map, pagesize) == 0);
+    assert(munmap(shm, pagesize) == 0);
+    close(memfd);
+    close(shmfd);
+
+    printf("memfd_shm_open unit test passed\n");
+    return 0;
+}
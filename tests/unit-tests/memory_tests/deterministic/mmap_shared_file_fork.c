@@ -0,0 +1,62 @@
+#include <assert.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define FILE_PATH "testfiles/mmap_shared_fork.txt"
+
+int main(void)
+{
+    long pagesize = sysconf(_SC_PAGESIZE);
+
+    int fd = open(FILE_PATH, O_RDWR | O_CREAT | O_TRUNC, 0666);
+    assert(fd >= 0);
+    assert(ftruncate(fd, 2 * pagesize) == 0);
+
+    char *map = mmap(NULL, 2 * pagesize, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+    assert(map != MAP_FAILED);
+    strcpy(map, "parent");
+
+    // The second page is PROT_NONE at fork time; it must still be shared
+    assert(mprotect(map + pagesize, pagesize, PROT_NONE) == 0);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        assert(strcmp(map, "parent") == 0);
+        strcpy(map, "child");
+        assert(mprotect(map + pagesize, pagesize, PROT_READ | PROT_WRITE) == 0);
+        strcpy(map + pagesize, "second page");
+        assert(msync(map, 2 * pagesize, MS_SYNC) == 0);
+        _exit(0);
+    }
+
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+    // The child's writes are visible through the mapping...
+    assert(strcmp(map, "child") == 0);
+    assert(mprotect(map + pagesize, pagesize, PROT_READ) == 0);
+    assert(strcmp(map + pagesize, "second page") == 0);
+
+    // ...and in the file itself
+    char buf[16] = {0};
+    assert(pread(fd, buf, sizeof("child"), 0) == sizeof("child"));
+    assert(strcmp(buf, "child") == 0);
+
+    // msync flags are validated
+    assert(msync(map, pagesize, MS_ASYNC | MS_SYNC) == -1 && errno == EINVAL);
+    assert(msync(map, pagesize, MS_ASYNC) == 0);
+
+    assert(munmap(map, 2 * pagesize) == 0);
+    close(fd);
+    unlink(FILE_PATH);
+
+    printf("mmap_shared_file_fork unit test passed\n");
+    return 0;
+}
@@ -0,0 +1,95 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <errno.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+int main(void)
+{
+    long pagesize = sysconf(_SC_PAGESIZE);
+
+    // Grow: contents are preserved and the new pages are usable
+    char *p = mmap(NULL, 2 * pagesize, PROT_READ | PROT_WRITE,
+                   MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(p != MAP_FAILED);
+    memset(p, 'a', 2 * pagesize);
+
+    char *q = mremap(p, 2 * pagesize, 8 * pagesize, MREMAP_MAYMOVE);
+    assert(q != MAP_FAILED);
+    for (long i = 0; i < 2 * pagesize; i++)
+        assert(q[i] == 'a');
+    memset(q + 2 * pagesize, 'b', 6 * pagesize);
+
+    // Shrink: stays at the same address
+    char *r = mremap(q, 8 * pagesize, pagesize, 0);
+    assert(r == q);
+    assert(r[0] == 'a' && r[pagesize - 1] == 'a');
+
+    // Move to a fixed address
+    char *target = mmap(NULL, pagesize, PROT_READ | PROT_WRITE,
+                        MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(target != MAP_FAILED);
+    char *s = mremap(r, pagesize, pagesize, MREMAP_MAYMOVE | MREMAP_FIXED, target);
+    assert(s == target);
+    assert(s[0] == 'a');
+
+    // MREMAP_FIXED requires MREMAP_MAYMOVE
+    assert(mremap(s, pagesize, pagesize, MREMAP_FIXED, target) == MAP_FAILED);
+    assert(errno == EINVAL);
+
+    // MADV_DONTNEED drops private anonymous pages, so they read back as zero
+    memset(s, 'c', pagesize);
+    assert(madvise(s, pagesize, MADV_DONTNEED) == 0);
+    assert(s[0] == 0 && s[pagesize - 1] == 0);
+    assert(madvise(s, pagesize, MADV_WILLNEED) == 0);
+
+    // mincore reports the page after touching it, and rejects unmapped ranges
+    unsigned char vec[1];
+    s[0] = 1;
+    assert(mincore(s, pagesize, vec) == 0);
+    assert(vec[0] & 1);
+
+    assert(mlock(s, pagesize) == 0);
+    assert(munlock(s, pagesize) == 0);
+
+    assert(munmap(s, pagesize) == 0);
+    assert(mincore(s, pagesize, vec) == -1 && errno == ENOMEM);
+    assert(madvise(s, pagesize, MADV_DONTNEED) == -1 && errno == ENOMEM);
+
+    // MAP_NORESERVE, MAP_STACK and MAP_GROWSDOWN are accepted
+    char *stack = mmap(NULL, 4 * pagesize, PROT_READ | PROT_WRITE,
+                       MAP_PRIVATE | MAP_ANONYMOUS | MAP_NORESERVE | MAP_STACK | MAP_GROWSDOWN,
+                       -1, 0);
+    assert(stack != MAP_FAILED);
+    stack[0] = 'x';
+    stack[4 * pagesize - 1] = 'y';
+
+    // A MAP_GROWSDOWN mapping keeps its contents when it is moved
+    char *moved = mremap(stack, 4 * pagesize, 8 * pagesize, MREMAP_MAYMOVE);
+    assert(moved != MAP_FAILED);
+    assert(moved[0] == 'x' && moved[4 * pagesize - 1] == 'y');
+    assert(munmap(moved, 8 * pagesize) == 0);
+
+    // Grow in place without MREMAP_MAYMOVE once the pages after the mapping are free
+    char *grow = mmap(NULL, 5 * pagesize, PROT_READ | PROT_WRITE,
+                      MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(grow != MAP_FAILED);
+    char *next = grow + 4 * pagesize;
+    next[0] = 'n';
+    assert(munmap(grow + pagesize, 3 * pagesize) == 0);
+    grow[0] = 'g';
+    assert(mremap(grow, pagesize, 4 * pagesize, 0) == grow);
+    assert(grow[0] == 'g' && grow[pagesize] == 0);
+    grow[4 * pagesize - 1] = 'h';
+
+    // Growing into a mapping that is in use fails and leaves both alone
+    assert(mremap(grow, 4 * pagesize, 5 * pagesize, 0) == MAP_FAILED);
+    assert(errno == ENOMEM);
+    assert(grow[0] == 'g' && grow[4 * pagesize - 1] == 'h' && next[0] == 'n');
+    assert(munmap(grow, 5 * pagesize) == 0);
+
+    printf("mremap unit test passed\n");
+    return 0;
+}
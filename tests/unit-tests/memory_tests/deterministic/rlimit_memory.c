@@ -0,0 +1,85 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <errno.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/mman.h>
+#include <sys/resource.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* RLIMIT_AS and RLIMIT_DATA are enforced per cage: mappings past the soft limit fail with ENOMEM,
+ * the soft limit can be raised back up to the hard limit, the hard limit can only be lowered, and
+ * a forked child inherits the limits. */
+
+#define MB (1024 * 1024)
+#define LIMIT (256 * MB)
+#define BIG (512 * MB)
+
+static void check_map_fails(int flags) {
+    void *map = mmap(NULL, BIG, PROT_READ | PROT_WRITE, flags, -1, 0);
+    assert(map == MAP_FAILED && errno == ENOMEM);
+}
+
+static void check_map_succeeds(int flags) {
+    void *map = mmap(NULL, BIG, PROT_READ | PROT_WRITE, flags, -1, 0);
+    assert(map != MAP_FAILED);
+    assert(munmap(map, BIG) == 0);
+}
+
+static void check_limit(int resource) {
+    struct rlimit orig, lim;
+    assert(getrlimit(resource, &orig) == 0);
+    assert(orig.rlim_cur <= orig.rlim_max && orig.rlim_cur > LIMIT);
+
+    lim.rlim_cur = LIMIT;
+    lim.rlim_max = orig.rlim_max;
+    assert(setrlimit(resource, &lim) == 0);
+    assert(getrlimit(resource, &lim) == 0 && lim.rlim_cur == LIMIT);
+    check_map_fails(MAP_PRIVATE | MAP_ANONYMOUS);
+
+    /* A forked child inherits the lowered limit */
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        assert(getrlimit(resource, &lim) == 0 && lim.rlim_cur == LIMIT);
+        check_map_fails(MAP_PRIVATE | MAP_ANONYMOUS);
+        _exit(0);
+    }
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+    /* The soft limit can go back up to the hard limit */
+    assert(setrlimit(resource, &orig) == 0);
+    check_map_succeeds(MAP_PRIVATE | MAP_ANONYMOUS);
+
+    /* A soft limit above the hard limit is invalid */
+    lim.rlim_cur = orig.rlim_max;
+    lim.rlim_max = LIMIT;
+    assert(setrlimit(resource, &lim) == -1 && errno == EINVAL);
+}
+
+int main(void) {
+    check_limit(RLIMIT_AS);
+    check_limit(RLIMIT_DATA);
+
+    /* Shared anonymous memory is still committed by the cage, so RLIMIT_DATA applies to it */
+    struct rlimit orig, lim;
+    assert(getrlimit(RLIMIT_DATA, &orig) == 0);
+    lim.rlim_cur = LIMIT;
+    lim.rlim_max = orig.rlim_max;
+    assert(setrlimit(RLIMIT_DATA, &lim) == 0);
+    check_map_fails(MAP_SHARED | MAP_ANONYMOUS);
+    assert(setrlimit(RLIMIT_DATA, &orig) == 0);
+
+    /* Lowering the hard limit cannot be undone */
+    lim.rlim_cur = LIMIT;
+    lim.rlim_max = LIMIT;
+    assert(setrlimit(RLIMIT_AS, &lim) == 0);
+    assert(setrlimit(RLIMIT_AS, &orig) == -1 && errno == EPERM);
+    check_map_fails(MAP_PRIVATE | MAP_ANONYMOUS);
+
+    printf("rlimit_memory test passed\n");
+    return 0;
+}
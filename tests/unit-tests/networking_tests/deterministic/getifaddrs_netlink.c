@@ -0,0 +1,183 @@
+#define _GNU_SOURCE
+#include <arpa/inet.h>
+#include <errno.h>
+#include <ifaddrs.h>
+#include <linux/netlink.h>
+#include <linux/rtnetlink.h>
+#include <net/if.h>
+#include <netinet/in.h>
+#include <sched.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* getifaddrs and if_nameindex work through the emulated NETLINK_ROUTE socket: lo with
+ * 127.0.0.1 is always listed, and a cage in its own network namespace sees only lo. */
+
+static int fail(const char *what) {
+    fprintf(stderr, "%s: %s\n", what, strerror(errno));
+    return 1;
+}
+
+/* Counts interfaces, checks that lo carries 127.0.0.1 and finds its index */
+static int check_ifaddrs(int *links, int *lo_v4, int *other_v4, unsigned int *lo_index) {
+    struct ifaddrs *ifaddr;
+    if (getifaddrs(&ifaddr) < 0)
+        return fail("getifaddrs");
+    *links = *lo_v4 = *other_v4 = 0;
+    for (struct ifaddrs *ifa = ifaddr; ifa != NULL; ifa = ifa->ifa_next) {
+        if (ifa->ifa_addr == NULL)
+            continue;
+        if (ifa->ifa_addr->sa_family == AF_PACKET) {
+            (*links)++;
+        } else if (ifa->ifa_addr->sa_family == AF_INET) {
+            struct sockaddr_in *sin = (struct sockaddr_in *)ifa->ifa_addr;
+            struct sockaddr_in *mask = (struct sockaddr_in *)ifa->ifa_netmask;
+            if (strcmp(ifa->ifa_name, "lo") == 0 && sin->sin_addr.s_addr == htonl(INADDR_LOOPBACK)) {
+                if (!(ifa->ifa_flags & IFF_LOOPBACK) || mask == NULL ||
+                    mask->sin_addr.s_addr != htonl(0xff000000)) {
+                    fprintf(stderr, "lo: flags %#x, bad netmask\n", ifa->ifa_flags);
+                    freeifaddrs(ifaddr);
+                    return 1;
+                }
+                (*lo_v4)++;
+            } else {
+                (*other_v4)++;
+            }
+        }
+    }
+    freeifaddrs(ifaddr);
+    if (*lo_v4 != 1) {
+        fprintf(stderr, "getifaddrs lists 127.0.0.1 on lo %d times\n", *lo_v4);
+        return 1;
+    }
+
+    /* if_nameindex asks for an RTM_GETLINK dump */
+    struct if_nameindex *names = if_nameindex();
+    if (names == NULL)
+        return fail("if_nameindex");
+    int found = 0;
+    for (struct if_nameindex *n = names; n->if_index != 0; n++) {
+        if (strcmp(n->if_name, "lo") == 0) {
+            *lo_index = n->if_index;
+            found++;
+        }
+    }
+    if_freenameindex(names);
+    if (found != 1) {
+        fprintf(stderr, "if_nameindex lists lo %d times\n", found);
+        return 1;
+    }
+    return 0;
+}
+
+/* Talks to the route socket directly: naming, a single-link request, and an ack */
+static int check_raw_socket(unsigned int lo_index) {
+    int fd = socket(AF_NETLINK, SOCK_RAW | SOCK_CLOEXEC, NETLINK_ROUTE);
+    if (fd < 0)
+        return fail("socket(AF_NETLINK)");
+    struct sockaddr_nl local = { .nl_family = AF_NETLINK };
+    socklen_t len = sizeof(local);
+    if (bind(fd, (struct sockaddr *)&local, sizeof(local)) < 0 ||
+        getsockname(fd, (struct sockaddr *)&local, &len) < 0)
+        return fail("bind/getsockname");
+    if (len != sizeof(local) || local.nl_family != AF_NETLINK || local.nl_pid == 0) {
+        fprintf(stderr, "getsockname: family %d pid %u len %u\n", local.nl_family, local.nl_pid,
+                len);
+        return 1;
+    }
+
+    struct {
+        struct nlmsghdr hdr;
+        struct ifinfomsg info;
+    } req = {
+        .hdr = { .nlmsg_len = sizeof(req), .nlmsg_type = RTM_GETLINK,
+                 .nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK, .nlmsg_seq = 7 },
+        .info = { .ifi_family = AF_UNSPEC, .ifi_index = lo_index },
+    };
+    struct sockaddr_nl kernel = { .nl_family = AF_NETLINK };
+    if (sendto(fd, &req, sizeof(req), 0, (struct sockaddr *)&kernel, sizeof(kernel)) < 0)
+        return fail("sendto");
+
+    char buf[4096];
+    struct sockaddr_nl from;
+    socklen_t fromlen = sizeof(from);
+    ssize_t n = recvfrom(fd, buf, sizeof(buf), 0, (struct sockaddr *)&from, &fromlen);
+    if (n < 0)
+        return fail("recvfrom");
+    struct nlmsghdr *hdr = (struct nlmsghdr *)buf;
+    if (from.nl_family != AF_NETLINK || from.nl_pid != 0 || !NLMSG_OK(hdr, n) ||
+        hdr->nlmsg_type != RTM_NEWLINK || hdr->nlmsg_seq != 7) {
+        fprintf(stderr, "RTM_GETLINK: reply type %d from pid %u\n", hdr->nlmsg_type, from.nl_pid);
+        return 1;
+    }
+    struct ifinfomsg *info = NLMSG_DATA(hdr);
+    if (!(info->ifi_flags & IFF_LOOPBACK)) {
+        fprintf(stderr, "RTM_GETLINK: lo flags %#x\n", info->ifi_flags);
+        return 1;
+    }
+
+    /* NLM_F_ACK gets an NLMSG_ERROR with error 0 after the reply */
+    hdr = NLMSG_NEXT(hdr, n);
+    if (!NLMSG_OK(hdr, n)) {
+        n = recv(fd, buf, sizeof(buf), 0);
+        hdr = (struct nlmsghdr *)buf;
+    }
+    struct nlmsgerr *ack = NLMSG_DATA(hdr);
+    if (n <= 0 || hdr->nlmsg_type != NLMSG_ERROR || ack->error != 0 || ack->msg.nlmsg_seq != 7) {
+        fprintf(stderr, "RTM_GETLINK: missing ack\n");
+        return 1;
+    }
+
+    /* Route sockets can't change interfaces */
+    req.hdr.nlmsg_type = RTM_NEWLINK;
+    req.hdr.nlmsg_seq = 8;
+    if (send(fd, &req, sizeof(req), 0) < 0 || (n = recv(fd, buf, sizeof(buf), 0)) < 0)
+        return fail("RTM_NEWLINK");
+    hdr = (struct nlmsghdr *)buf;
+    ack = NLMSG_DATA(hdr);
+    if (hdr->nlmsg_type != NLMSG_ERROR || ack->error != -EPERM) {
+        fprintf(stderr, "RTM_NEWLINK should fail with EPERM, got %d\n", ack->error);
+        return 1;
+    }
+    close(fd);
+    return 0;
+}
+
+int main(void) {
+    int links, lo_v4, other_v4;
+    unsigned int lo_index;
+    if (check_ifaddrs(&links, &lo_v4, &other_v4, &lo_index) || check_raw_socket(lo_index))
+        return 1;
+    if (links < 1) {
+        fprintf(stderr, "getifaddrs lists no links\n");
+        return 1;
+    }
+
+    pid_t pid = fork();
+    if (pid < 0)
+        return fail("fork");
+    if (pid == 0) {
+        if (unshare(CLONE_NEWNET) < 0)
+            exit(fail("child: unshare"));
+        if (check_ifaddrs(&links, &lo_v4, &other_v4, &lo_index) || check_raw_socket(lo_index))
+            exit(1);
+        if (links != 1 || other_v4 != 0) {
+            fprintf(stderr, "child: %d links and %d other addresses in a private namespace\n",
+                    links, other_v4);
+            exit(1);
+        }
+        exit(0);
+    }
+    int status;
+    if (waitpid(pid, &status, 0) < 0 || !WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+        fprintf(stderr, "child failed\n");
+        return 1;
+    }
+
+    printf("getifaddrs_netlink test passed\n");
+    return 0;
+}
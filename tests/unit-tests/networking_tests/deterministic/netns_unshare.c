@@ -0,0 +1,148 @@
+#define _GNU_SOURCE
+#include <arpa/inet.h>
+#include <errno.h>
+#include <sched.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* A child that calls unshare(CLONE_NEWNET) gets its own ports and loopback: it can bind a
+ * port the parent holds, reach its own listener through 127.0.0.1, and can't reach the
+ * parent's listener. */
+
+#define SHARED_PORT 45871
+#define PARENT_ONLY_PORT 45873
+
+static int listen_on(in_addr_t ip, int port) {
+    int fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (fd < 0) {
+        perror("socket");
+        exit(1);
+    }
+    int on = 1;
+    setsockopt(fd, SOL_SOCKET, SO_REUSEADDR, &on, sizeof(on));
+    struct sockaddr_in addr = { .sin_family = AF_INET, .sin_port = htons(port) };
+    addr.sin_addr.s_addr = ip;
+    if (bind(fd, (struct sockaddr *)&addr, sizeof(addr)) < 0 || listen(fd, 4) < 0) {
+        perror("bind/listen");
+        exit(1);
+    }
+    return fd;
+}
+
+static int connect_to(int port) {
+    int fd = socket(AF_INET, SOCK_STREAM, 0);
+    struct sockaddr_in addr = { .sin_family = AF_INET, .sin_port = htons(port) };
+    addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+    if (connect(fd, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+        int saved = errno;
+        close(fd);
+        errno = saved;
+        return -1;
+    }
+    return fd;
+}
+
+static int child(void) {
+    if (unshare(CLONE_NEWPID) != -1 || errno != EINVAL) {
+        fprintf(stderr, "child: unshare(CLONE_NEWPID) should fail with EINVAL\n");
+        return 1;
+    }
+    if (unshare(CLONE_NEWNET) < 0) {
+        perror("unshare");
+        return 1;
+    }
+
+    /* The parent holds this port on the host, but not in our namespace */
+    int lfd = listen_on(htonl(INADDR_ANY), SHARED_PORT);
+
+    struct sockaddr_in name;
+    socklen_t len = sizeof(name);
+    if (getsockname(lfd, (struct sockaddr *)&name, &len) < 0 || ntohs(name.sin_port) != SHARED_PORT) {
+        fprintf(stderr, "child: getsockname reports port %d\n", ntohs(name.sin_port));
+        return 1;
+    }
+
+    int cfd = connect_to(SHARED_PORT);
+    if (cfd < 0) {
+        perror("child: connect to own listener");
+        return 1;
+    }
+    struct sockaddr_in peer, local;
+    socklen_t peerlen = sizeof(peer), locallen = sizeof(local);
+    int afd = accept(lfd, (struct sockaddr *)&peer, &peerlen);
+    if (afd < 0 || getsockname(cfd, (struct sockaddr *)&local, &locallen) < 0) {
+        perror("child: accept");
+        return 1;
+    }
+    if (peer.sin_port != local.sin_port) {
+        fprintf(stderr, "child: accept reports port %d, client has %d\n", ntohs(peer.sin_port),
+                ntohs(local.sin_port));
+        return 1;
+    }
+    if (write(cfd, "ping", 4) != 4) {
+        perror("child: write");
+        return 1;
+    }
+    char buf[4];
+    if (read(afd, buf, 4) != 4 || memcmp(buf, "ping", 4) != 0) {
+        fprintf(stderr, "child: wrong data over loopback\n");
+        return 1;
+    }
+
+    /* The parent's listener is not on our loopback */
+    if (connect_to(PARENT_ONLY_PORT) != -1 || errno != ECONNREFUSED) {
+        fprintf(stderr, "child: reached the parent's listener\n");
+        return 1;
+    }
+
+    /* Only loopback and wildcard addresses exist in the namespace */
+    int fd = socket(AF_INET, SOCK_STREAM, 0);
+    struct sockaddr_in other = { .sin_family = AF_INET, .sin_port = htons(SHARED_PORT + 1) };
+    inet_pton(AF_INET, "10.1.2.3", &other.sin_addr);
+    if (bind(fd, (struct sockaddr *)&other, sizeof(other)) != -1 || errno != EADDRNOTAVAIL) {
+        fprintf(stderr, "child: bind to a foreign address should fail with EADDRNOTAVAIL\n");
+        return 1;
+    }
+
+    close(fd);
+    close(afd);
+    close(cfd);
+    close(lfd);
+    return 0;
+}
+
+int main(void) {
+    int shared = listen_on(htonl(INADDR_LOOPBACK), SHARED_PORT);
+    int parent_only = listen_on(htonl(INADDR_LOOPBACK), PARENT_ONLY_PORT);
+
+    pid_t pid = fork();
+    if (pid < 0) {
+        perror("fork");
+        return 1;
+    }
+    if (pid == 0)
+        exit(child());
+
+    int status;
+    if (waitpid(pid, &status, 0) < 0 || !WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+        fprintf(stderr, "child failed\n");
+        return 1;
+    }
+
+    /* The parent's namespace is unchanged */
+    int fd = connect_to(SHARED_PORT);
+    if (fd < 0) {
+        perror("parent: connect");
+        return 1;
+    }
+
+    close(fd);
+    close(parent_only);
+    close(shared);
+    printf("netns_unshare test passed\n");
+    return 0;
+}
@@ -0,0 +1,197 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <sys/uio.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* Passes a pipe's read end from parent to child over a socketpair with SCM_RIGHTS and
+ * checks that SCM_CREDENTIALS reports the sending process. */
+
+static int send_fd(int sock, int fd) {
+    char byte = 'x';
+    struct iovec iov = { .iov_base = &byte, .iov_len = 1 };
+    union {
+        char buf[CMSG_SPACE(sizeof(int))];
+        struct cmsghdr align;
+    } control;
+    memset(&control, 0, sizeof(control));
+
+    struct msghdr msg;
+    memset(&msg, 0, sizeof(msg));
+    msg.msg_iov = &iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.buf;
+    msg.msg_controllen = sizeof(control.buf);
+
+    struct cmsghdr *cmsg = CMSG_FIRSTHDR(&msg);
+    cmsg->cmsg_level = SOL_SOCKET;
+    cmsg->cmsg_type = SCM_RIGHTS;
+    cmsg->cmsg_len = CMSG_LEN(sizeof(int));
+    memcpy(CMSG_DATA(cmsg), &fd, sizeof(int));
+
+    return sendmsg(sock, &msg, 0);
+}
+
+static int send_creds(int sock) {
+    char byte = 'c';
+    struct iovec iov = { .iov_base = &byte, .iov_len = 1 };
+    union {
+        char buf[CMSG_SPACE(sizeof(struct ucred))];
+        struct cmsghdr align;
+    } control;
+    memset(&control, 0, sizeof(control));
+
+    struct msghdr msg;
+    memset(&msg, 0, sizeof(msg));
+    msg.msg_iov = &iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.buf;
+    msg.msg_controllen = sizeof(control.buf);
+
+    struct ucred cred = { .pid = getpid(), .uid = getuid(), .gid = getgid() };
+    struct cmsghdr *cmsg = CMSG_FIRSTHDR(&msg);
+    cmsg->cmsg_level = SOL_SOCKET;
+    cmsg->cmsg_type = SCM_CREDENTIALS;
+    cmsg->cmsg_len = CMSG_LEN(sizeof(struct ucred));
+    memcpy(CMSG_DATA(cmsg), &cred, sizeof(cred));
+
+    return sendmsg(sock, &msg, 0);
+}
+
+/* Receives one byte and its control messages. Returns the passed fd (or -1) and fills in
+ * the credentials when present. */
+static int recv_msg(int sock, struct ucred *cred, int *have_cred) {
+    char byte;
+    struct iovec iov = { .iov_base = &byte, .iov_len = 1 };
+    union {
+        char buf[CMSG_SPACE(sizeof(int)) + CMSG_SPACE(sizeof(struct ucred))];
+        struct cmsghdr align;
+    } control;
+
+    struct msghdr msg;
+    memset(&msg, 0, sizeof(msg));
+    msg.msg_iov = &iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.buf;
+    msg.msg_controllen = sizeof(control.buf);
+
+    if (recvmsg(sock, &msg, MSG_CMSG_CLOEXEC) != 1) {
+        perror("recvmsg");
+        exit(1);
+    }
+    if (msg.msg_flags & MSG_CTRUNC) {
+        fprintf(stderr, "control data truncated\n");
+        exit(1);
+    }
+
+    int fd = -1;
+    *have_cred = 0;
+    for (struct cmsghdr *cmsg = CMSG_FIRSTHDR(&msg); cmsg; cmsg = CMSG_NXTHDR(&msg, cmsg)) {
+        if (cmsg->cmsg_level != SOL_SOCKET)
+            continue;
+        if (cmsg->cmsg_type == SCM_RIGHTS && cmsg->cmsg_len == CMSG_LEN(sizeof(int)))
+            memcpy(&fd, CMSG_DATA(cmsg), sizeof(int));
+        if (cmsg->cmsg_type == SCM_CREDENTIALS) {
+            memcpy(cred, CMSG_DATA(cmsg), sizeof(*cred));
+            *have_cred = 1;
+        }
+    }
+    return fd;
+}
+
+int main(void) {
+    int sv[2];
+    if (socketpair(AF_UNIX, SOCK_DGRAM, 0, sv) < 0) {
+        perror("socketpair");
+        return 1;
+    }
+    int on = 1;
+    if (setsockopt(sv[1], SOL_SOCKET, SO_PASSCRED, &on, sizeof(on)) < 0) {
+        perror("setsockopt SO_PASSCRED");
+        return 1;
+    }
+
+    /* Passing something that isn't an open fd fails without sending anything */
+    if (send_fd(sv[0], 999) != -1 || errno != EBADF) {
+        fprintf(stderr, "sendmsg with a bad fd: expected EBADF\n");
+        return 1;
+    }
+
+    pid_t parent = getpid();
+    pid_t pid = fork();
+    if (pid < 0) {
+        perror("fork");
+        return 1;
+    }
+
+    if (pid == 0) {
+        close(sv[0]);
+        struct ucred cred;
+        int have_cred;
+
+        int fd = recv_msg(sv[1], &cred, &have_cred);
+        if (fd < 0) {
+            fprintf(stderr, "child: no fd received\n");
+            exit(1);
+        }
+        if (!(fcntl(fd, F_GETFD) & FD_CLOEXEC)) {
+            fprintf(stderr, "child: MSG_CMSG_CLOEXEC not applied\n");
+            exit(1);
+        }
+        char buf[16] = {0};
+        if (read(fd, buf, sizeof(buf) - 1) != 7 || strcmp(buf, "payload") != 0) {
+            fprintf(stderr, "child: wrong data through passed fd: '%s'\n", buf);
+            exit(1);
+        }
+        close(fd);
+
+        if (recv_msg(sv[1], &cred, &have_cred) != -1 || !have_cred) {
+            fprintf(stderr, "child: expected credentials only\n");
+            exit(1);
+        }
+        if (cred.pid != parent || cred.uid != getuid() || cred.gid != getgid()) {
+            fprintf(stderr, "child: credentials pid %d uid %d gid %d, expected pid %d\n",
+                    cred.pid, cred.uid, cred.gid, parent);
+            exit(1);
+        }
+        exit(0);
+    }
+
+    close(sv[1]);
+    int p[2];
+    if (pipe(p) < 0) {
+        perror("pipe");
+        return 1;
+    }
+    if (write(p[1], "payload", 7) != 7) {
+        perror("write");
+        return 1;
+    }
+    close(p[1]);
+
+    if (send_fd(sv[0], p[0]) != 1) {
+        perror("sendmsg SCM_RIGHTS");
+        return 1;
+    }
+    /* The child holds its own reference now */
+    close(p[0]);
+
+    if (send_creds(sv[0]) != 1) {
+        perror("sendmsg SCM_CREDENTIALS");
+        return 1;
+    }
+
+    int status;
+    if (waitpid(pid, &status, 0) < 0 || !WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+        fprintf(stderr, "child failed\n");
+        return 1;
+    }
+
+    printf("scm_rights test passed\n");
+    return 0;
+}
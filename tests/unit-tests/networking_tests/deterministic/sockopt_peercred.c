@@ -0,0 +1,210 @@
+#define _GNU_SOURCE
+#include <arpa/inet.h>
+#include <errno.h>
+#include <netinet/in.h>
+#include <netinet/tcp.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <sys/time.h>
+#include <sys/un.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* SO_PEERCRED reports the cage at the other end of an AF_UNIX socket, and options whose
+ * values need converting (32-bit timeval, linger, multicast requests) round-trip. */
+
+#define SOCK_PATH "sockopt_peercred.sock"
+
+/* The 32-bit struct timeval that goes with SO_RCVTIMEO_OLD */
+#define OLD_SO_RCVTIMEO 20
+struct old_timeval {
+    int tv_sec;
+    int tv_usec;
+};
+
+static int fail(const char *what) {
+    fprintf(stderr, "%s: %s\n", what, strerror(errno));
+    return 1;
+}
+
+static int check_peer(int fd, pid_t expected, const char *who) {
+    struct ucred cred;
+    socklen_t len = sizeof(cred);
+    if (getsockopt(fd, SOL_SOCKET, SO_PEERCRED, &cred, &len) < 0)
+        return fail(who);
+    if (len != sizeof(cred) || cred.pid != expected || cred.uid != getuid() ||
+        cred.gid != getgid()) {
+        fprintf(stderr, "%s: peer pid %d uid %d gid %d, expected pid %d\n", who, cred.pid,
+                cred.uid, cred.gid, expected);
+        return 1;
+    }
+    return 0;
+}
+
+static int test_peercred(void) {
+    unlink(SOCK_PATH);
+    int lfd = socket(AF_UNIX, SOCK_STREAM, 0);
+    struct sockaddr_un addr = { .sun_family = AF_UNIX };
+    strcpy(addr.sun_path, SOCK_PATH);
+    if (bind(lfd, (struct sockaddr *)&addr, sizeof(addr)) < 0 || listen(lfd, 1) < 0)
+        return fail("bind/listen");
+
+    int on = 1;
+    if (setsockopt(lfd, SOL_SOCKET, SO_PASSCRED, &on, sizeof(on)) < 0)
+        return fail("SO_PASSCRED");
+
+    pid_t parent = getpid();
+    pid_t pid = fork();
+    if (pid < 0)
+        return fail("fork");
+    if (pid == 0) {
+        int fd = socket(AF_UNIX, SOCK_STREAM, 0);
+        if (connect(fd, (struct sockaddr *)&addr, sizeof(addr)) < 0)
+            exit(fail("child: connect"));
+        char c;
+        /* Wait until the parent has accepted, so the peer socket belongs to it */
+        if (read(fd, &c, 1) != 1)
+            exit(fail("child: read"));
+        int ret = check_peer(fd, parent, "child: SO_PEERCRED");
+        close(fd);
+        exit(ret);
+    }
+
+    int afd = accept(lfd, NULL, NULL);
+    if (afd < 0)
+        return fail("accept");
+    if (check_peer(afd, pid, "parent: SO_PEERCRED"))
+        return 1;
+    if (write(afd, "x", 1) != 1)
+        return fail("write");
+
+    int status;
+    if (waitpid(pid, &status, 0) < 0 || !WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+        fprintf(stderr, "child failed\n");
+        return 1;
+    }
+
+    /* SO_PEERCRED is read-only and truncates to the buffer it is given */
+    struct ucred cred;
+    if (setsockopt(afd, SOL_SOCKET, SO_PEERCRED, &cred, sizeof(cred)) != -1 ||
+        errno != ENOPROTOOPT) {
+        fprintf(stderr, "setsockopt(SO_PEERCRED) should fail with ENOPROTOOPT\n");
+        return 1;
+    }
+    pid_t only_pid;
+    socklen_t len = sizeof(only_pid);
+    if (getsockopt(afd, SOL_SOCKET, SO_PEERCRED, &only_pid, &len) < 0 || len != sizeof(only_pid) ||
+        only_pid != pid) {
+        fprintf(stderr, "truncated SO_PEERCRED reports pid %d, len %u\n", only_pid, len);
+        return 1;
+    }
+
+    /* Both ends of a socketpair belong to the cage that created it */
+    int sv[2];
+    if (socketpair(AF_UNIX, SOCK_STREAM, 0, sv) < 0)
+        return fail("socketpair");
+    if (check_peer(sv[0], parent, "socketpair: SO_PEERCRED"))
+        return 1;
+
+    close(sv[0]);
+    close(sv[1]);
+    close(afd);
+    close(lfd);
+    unlink(SOCK_PATH);
+    return 0;
+}
+
+static int test_conversions(void) {
+    int tcp = socket(AF_INET, SOCK_STREAM, 0);
+    int udp = socket(AF_INET, SOCK_DGRAM, 0);
+    int udp6 = socket(AF_INET6, SOCK_DGRAM, 0);
+    if (tcp < 0 || udp < 0 || udp6 < 0)
+        return fail("socket");
+
+    struct timeval tv = { .tv_sec = 3, .tv_usec = 250000 }, tv_out;
+    socklen_t len = sizeof(tv_out);
+    if (setsockopt(tcp, SOL_SOCKET, SO_RCVTIMEO, &tv, sizeof(tv)) < 0 ||
+        getsockopt(tcp, SOL_SOCKET, SO_RCVTIMEO, &tv_out, &len) < 0)
+        return fail("SO_RCVTIMEO");
+    if (len != sizeof(tv_out) || tv_out.tv_sec != 3 || tv_out.tv_usec != 250000) {
+        fprintf(stderr, "SO_RCVTIMEO reads back %lld.%06lld\n", (long long)tv_out.tv_sec,
+                (long long)tv_out.tv_usec);
+        return 1;
+    }
+
+    struct old_timeval old = { .tv_sec = 7, .tv_usec = 500000 }, old_out;
+    len = sizeof(old_out);
+    if (setsockopt(tcp, SOL_SOCKET, OLD_SO_RCVTIMEO, &old, sizeof(old)) < 0 ||
+        getsockopt(tcp, SOL_SOCKET, OLD_SO_RCVTIMEO, &old_out, &len) < 0)
+        return fail("SO_RCVTIMEO_OLD");
+    if (len != sizeof(old_out) || old_out.tv_sec != 7 || old_out.tv_usec != 500000) {
+        fprintf(stderr, "SO_RCVTIMEO_OLD reads back %d.%06d\n", old_out.tv_sec, old_out.tv_usec);
+        return 1;
+    }
+    /* The old timeval set above is visible through the time64 option too */
+    len = sizeof(tv_out);
+    if (getsockopt(tcp, SOL_SOCKET, SO_RCVTIMEO, &tv_out, &len) < 0 || tv_out.tv_sec != 7)
+        return fail("SO_RCVTIMEO after SO_RCVTIMEO_OLD");
+    if (setsockopt(tcp, SOL_SOCKET, OLD_SO_RCVTIMEO, &old, sizeof(int)) != -1 || errno != EINVAL) {
+        fprintf(stderr, "short SO_RCVTIMEO_OLD should fail with EINVAL\n");
+        return 1;
+    }
+
+    struct linger lg = { .l_onoff = 1, .l_linger = 5 }, lg_out;
+    len = sizeof(lg_out);
+    if (setsockopt(tcp, SOL_SOCKET, SO_LINGER, &lg, sizeof(lg)) < 0 ||
+        getsockopt(tcp, SOL_SOCKET, SO_LINGER, &lg_out, &len) < 0)
+        return fail("SO_LINGER");
+    if (!lg_out.l_onoff || lg_out.l_linger != 5) {
+        fprintf(stderr, "SO_LINGER reads back %d/%d\n", lg_out.l_onoff, lg_out.l_linger);
+        return 1;
+    }
+
+    int on = 1, value = 0;
+    len = sizeof(value);
+    if (setsockopt(tcp, IPPROTO_TCP, TCP_NODELAY, &on, sizeof(on)) < 0 ||
+        getsockopt(tcp, IPPROTO_TCP, TCP_NODELAY, &value, &len) < 0 || !value)
+        return fail("TCP_NODELAY");
+    int idle = 42;
+    len = sizeof(value);
+    if (setsockopt(tcp, IPPROTO_TCP, TCP_KEEPIDLE, &idle, sizeof(idle)) < 0 ||
+        getsockopt(tcp, IPPROTO_TCP, TCP_KEEPIDLE, &value, &len) < 0 || value != 42)
+        return fail("TCP_KEEPIDLE");
+
+    len = sizeof(value);
+    if (setsockopt(udp6, IPPROTO_IPV6, IPV6_V6ONLY, &on, sizeof(on)) < 0 ||
+        getsockopt(udp6, IPPROTO_IPV6, IPV6_V6ONLY, &value, &len) < 0 || !value)
+        return fail("IPV6_V6ONLY");
+
+    struct ip_mreq mreq;
+    inet_pton(AF_INET, "239.1.2.3", &mreq.imr_multiaddr);
+    mreq.imr_interface.s_addr = htonl(INADDR_LOOPBACK);
+    if (setsockopt(udp, IPPROTO_IP, IP_ADD_MEMBERSHIP, &mreq, sizeof(mreq)) < 0 ||
+        setsockopt(udp, IPPROTO_IP, IP_DROP_MEMBERSHIP, &mreq, sizeof(mreq)) < 0)
+        return fail("IP_ADD_MEMBERSHIP");
+
+    /* Unknown options and levels */
+    len = sizeof(value);
+    if (getsockopt(tcp, SOL_SOCKET, 9999, &value, &len) != -1 || errno != ENOPROTOOPT) {
+        fprintf(stderr, "unknown option should fail with ENOPROTOOPT\n");
+        return 1;
+    }
+    if (setsockopt(tcp, 9999, SO_REUSEADDR, &on, sizeof(on)) != -1 || errno != EOPNOTSUPP) {
+        fprintf(stderr, "unknown level should fail with EOPNOTSUPP\n");
+        return 1;
+    }
+
+    close(udp6);
+    close(udp);
+    close(tcp);
+    return 0;
+}
+
+int main(void) {
+    if (test_peercred() || test_conversions())
+        return 1;
+    printf("sockopt_peercred test passed\n");
+    return 0;
+}
@@ -0,0 +1,260 @@
+/* UNIX domain sockets between cages: stream transfer larger than the socket buffer, datagram
+ * boundaries, SCM_RIGHTS over a stream socket, shutdown/EOF and poll readiness.
+ *
+ * With lind-boot built with the inproc-sockets feature (make test-inproc-sockets) the stream
+ * sockets here are in-process sockets; without it they are host kernel sockets, and the output
+ * is the same either way. */
+#include <assert.h>
+#include <errno.h>
+#include <poll.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <sys/un.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define BULK_LEN (1024 * 1024)
+
+static void wait_child(pid_t pid)
+{
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+
+static void send_fd(int sock, int fd, char tag)
+{
+    char cbuf[CMSG_SPACE(sizeof(int))];
+    memset(cbuf, 0, sizeof(cbuf));
+    struct iovec iov = { .iov_base = &tag, .iov_len = 1 };
+    struct msghdr msg = { 0 };
+    msg.msg_iov = &iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = cbuf;
+    msg.msg_controllen = sizeof(cbuf);
+    struct cmsghdr *cmsg = CMSG_FIRSTHDR(&msg);
+    cmsg->cmsg_level = SOL_SOCKET;
+    cmsg->cmsg_type = SCM_RIGHTS;
+    cmsg->cmsg_len = CMSG_LEN(sizeof(int));
+    memcpy(CMSG_DATA(cmsg), &fd, sizeof(int));
+    assert(sendmsg(sock, &msg, 0) == 1);
+}
+
+/* Receives into buf (at most len bytes) and returns the passed fd, or -1 if none came. */
+static int recv_fd(int sock, char *buf, size_t len, ssize_t *got)
+{
+    char cbuf[CMSG_SPACE(2 * sizeof(int))];
+    struct iovec iov = { .iov_base = buf, .iov_len = len };
+    struct msghdr msg = { 0 };
+    msg.msg_iov = &iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = cbuf;
+    msg.msg_controllen = sizeof(cbuf);
+    *got = recvmsg(sock, &msg, 0);
+    assert(*got >= 0);
+    assert((msg.msg_flags & MSG_CTRUNC) == 0);
+    struct cmsghdr *cmsg = CMSG_FIRSTHDR(&msg);
+    if (cmsg == NULL)
+        return -1;
+    assert(cmsg->cmsg_level == SOL_SOCKET && cmsg->cmsg_type == SCM_RIGHTS);
+    assert(cmsg->cmsg_len == CMSG_LEN(sizeof(int)));
+    int fd;
+    memcpy(&fd, CMSG_DATA(cmsg), sizeof(int));
+    return fd;
+}
+
+/* A megabyte through a listening socket, with the writer blocking on a full buffer. */
+static void test_stream(void)
+{
+    char path[64];
+    snprintf(path, sizeof(path), "/tmp/uds_inproc_%d", getpid());
+    unlink(path);
+
+    struct sockaddr_un addr = { .sun_family = AF_UNIX };
+    strncpy(addr.sun_path, path, sizeof(addr.sun_path) - 1);
+    int listener = socket(AF_UNIX, SOCK_STREAM, 0);
+    assert(listener >= 0);
+    assert(bind(listener, (struct sockaddr *)&addr, sizeof(addr)) == 0);
+    assert(listen(listener, 4) == 0);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        int fd = socket(AF_UNIX, SOCK_STREAM, 0);
+        assert(fd >= 0);
+        assert(connect(fd, (struct sockaddr *)&addr, sizeof(addr)) == 0);
+        char *buf = malloc(BULK_LEN);
+        for (int i = 0; i < BULK_LEN; i++)
+            buf[i] = (char)(i * 7);
+        size_t off = 0;
+        while (off < BULK_LEN) {
+            ssize_t n = write(fd, buf + off, BULK_LEN - off);
+            assert(n > 0);
+            off += n;
+        }
+        close(fd);
+        exit(0);
+    }
+
+    struct pollfd pfd = { .fd = listener, .events = POLLIN };
+    assert(poll(&pfd, 1, 5000) == 1 && (pfd.revents & POLLIN));
+    int conn = accept(listener, NULL, NULL);
+    assert(conn >= 0);
+
+    char *buf = malloc(65536);
+    size_t total = 0;
+    int ok = 1;
+    for (;;) {
+        ssize_t n = read(conn, buf, 65536);
+        assert(n >= 0);
+        if (n == 0)
+            break;
+        for (ssize_t i = 0; i < n; i++)
+            if (buf[i] != (char)((total + i) * 7))
+                ok = 0;
+        total += n;
+    }
+    free(buf);
+    wait_child(pid);
+    close(conn);
+    close(listener);
+    unlink(path);
+    printf("stream: %zu bytes, %s\n", total, ok ? "intact" : "corrupted");
+}
+
+/* Datagram sockets keep message boundaries. */
+static void test_dgram(void)
+{
+    int sv[2];
+    assert(socketpair(AF_UNIX, SOCK_DGRAM, 0, sv) == 0);
+    assert(send(sv[0], "one", 3, 0) == 3);
+    assert(send(sv[0], "three", 5, 0) == 5);
+    char buf[16];
+    ssize_t a = recv(sv[1], buf, sizeof(buf), 0);
+    ssize_t b = recv(sv[1], buf, sizeof(buf), 0);
+    printf("dgram: %zd then %zd bytes\n", a, b);
+    close(sv[0]);
+    close(sv[1]);
+}
+
+/* SCM_RIGHTS over a stream socket to another cage. */
+static void test_fd_passing(void)
+{
+    int sv[2];
+    assert(socketpair(AF_UNIX, SOCK_STREAM, 0, sv) == 0);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        close(sv[0]);
+        // Two sends with an fd each come out as two receives
+        char buf[8];
+        ssize_t got;
+        int first = recv_fd(sv[1], buf, sizeof(buf), &got);
+        assert(first >= 0 && got == 1 && buf[0] == 'a');
+        int second = recv_fd(sv[1], buf, sizeof(buf), &got);
+        assert(second >= 0 && got == 1 && buf[0] == 'b');
+        char msg[16] = { 0 };
+        assert(read(first, msg, sizeof(msg)) == 5);
+        assert(strcmp(msg, "pipe1") == 0);
+        memset(msg, 0, sizeof(msg));
+        assert(read(second, msg, sizeof(msg)) == 5);
+        assert(strcmp(msg, "pipe2") == 0);
+        // Plain data after the fds arrives without any
+        int none = recv_fd(sv[1], buf, sizeof(buf), &got);
+        assert(none == -1 && got == 4 && memcmp(buf, "done", 4) == 0);
+        close(first);
+        close(second);
+        close(sv[1]);
+        exit(0);
+    }
+
+    close(sv[1]);
+    int p1[2], p2[2];
+    assert(pipe(p1) == 0 && pipe(p2) == 0);
+    assert(write(p1[1], "pipe1", 5) == 5);
+    assert(write(p2[1], "pipe2", 5) == 5);
+    send_fd(sv[0], p1[0], 'a');
+    send_fd(sv[0], p2[0], 'b');
+    // The fds stay usable in flight even once the sender closed its copies
+    close(p1[0]);
+    close(p2[0]);
+    assert(send(sv[0], "done", 4, 0) == 4);
+    wait_child(pid);
+    close(p1[1]);
+    close(p2[1]);
+    close(sv[0]);
+    printf("fd passing: ok\n");
+}
+
+/* Half-close gives EOF to the reader, closing the reader gives EPIPE to the writer. */
+static void test_shutdown(void)
+{
+    int sv[2];
+    assert(socketpair(AF_UNIX, SOCK_STREAM, 0, sv) == 0);
+    assert(write(sv[0], "bye", 3) == 3);
+    assert(shutdown(sv[0], SHUT_WR) == 0);
+    char buf[8];
+    ssize_t a = read(sv[1], buf, sizeof(buf));
+    ssize_t b = read(sv[1], buf, sizeof(buf));
+    printf("shutdown: read %zd then %zd\n", a, b);
+
+    // The other direction still works
+    assert(write(sv[1], "ok", 2) == 2);
+    assert(read(sv[0], buf, sizeof(buf)) == 2);
+
+    close(sv[1]);
+    errno = 0;
+    ssize_t c = send(sv[0], "x", 1, MSG_NOSIGNAL);
+    printf("shutdown: send after peer close %zd (%s)\n", c, errno == EPIPE ? "EPIPE" : "?");
+    close(sv[0]);
+}
+
+/* poll reports readability as data arrives and hang-up once the peer is gone. */
+static void test_poll(void)
+{
+    int sv[2];
+    assert(socketpair(AF_UNIX, SOCK_STREAM, 0, sv) == 0);
+    struct pollfd pfd = { .fd = sv[1], .events = POLLIN | POLLOUT };
+    assert(poll(&pfd, 1, 0) == 1);
+    printf("poll: idle in=%d out=%d\n", !!(pfd.revents & POLLIN), !!(pfd.revents & POLLOUT));
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        close(sv[1]);
+        usleep(100000);
+        assert(write(sv[0], "ping", 4) == 4);
+        close(sv[0]);
+        exit(0);
+    }
+    close(sv[0]);
+
+    // Blocks until the child writes
+    pfd.events = POLLIN;
+    assert(poll(&pfd, 1, 5000) == 1);
+    printf("poll: after write in=%d\n", !!(pfd.revents & POLLIN));
+    char buf[8];
+    assert(read(sv[1], buf, sizeof(buf)) == 4);
+    wait_child(pid);
+
+    assert(poll(&pfd, 1, 5000) == 1);
+    printf("poll: after close in=%d hup=%d\n", !!(pfd.revents & POLLIN),
+           !!(pfd.revents & POLLHUP));
+    assert(read(sv[1], buf, sizeof(buf)) == 0);
+    close(sv[1]);
+}
+
+int main(void)
+{
+    setvbuf(stdout, NULL, _IONBF, 0);
+    test_stream();
+    test_dgram();
+    test_fd_passing();
+    test_shutdown();
+    test_poll();
+    return 0;
+}
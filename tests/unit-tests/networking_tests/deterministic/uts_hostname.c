@@ -0,0 +1,107 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <limits.h>
+#include <sched.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/utsname.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* sethostname renames the cage's UTS namespace, which uname and gethostname report; a child
+ * that calls unshare(CLONE_NEWUTS) can rename itself without affecting its parent. */
+
+static int fail(const char *what) {
+    fprintf(stderr, "%s: %s\n", what, strerror(errno));
+    return 1;
+}
+
+static int expect_hostname(const char *expected, const char *who) {
+    char name[256];
+    struct utsname uts;
+    if (gethostname(name, sizeof(name)) < 0 || uname(&uts) < 0)
+        return fail(who);
+    if (strcmp(name, expected) != 0 || strcmp(uts.nodename, expected) != 0) {
+        fprintf(stderr, "%s: gethostname '%s', uname '%s', expected '%s'\n", who, name,
+                uts.nodename, expected);
+        return 1;
+    }
+    return 0;
+}
+
+static int wait_child(pid_t pid) {
+    int status;
+    if (waitpid(pid, &status, 0) < 0 || !WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+        fprintf(stderr, "child failed\n");
+        return 1;
+    }
+    return 0;
+}
+
+int main(void) {
+    struct utsname uts;
+    if (uname(&uts) < 0)
+        return fail("uname");
+    if (strcmp(uts.sysname, "Linux") != 0 || uts.release[0] == '\0') {
+        fprintf(stderr, "uname: sysname '%s', release '%s'\n", uts.sysname, uts.release);
+        return 1;
+    }
+    char original[HOST_NAME_MAX + 1];
+    if (gethostname(original, sizeof(original)) < 0)
+        return fail("gethostname");
+    if (expect_hostname(original, "initial"))
+        return 1;
+
+    /* A buffer too small for the name and its NUL */
+    char small[2];
+    if (strlen(original) >= sizeof(small) &&
+        (gethostname(small, sizeof(small)) != -1 || errno != ENAMETOOLONG)) {
+        fprintf(stderr, "short gethostname should fail with ENAMETOOLONG\n");
+        return 1;
+    }
+
+    char toolong[HOST_NAME_MAX + 2];
+    memset(toolong, 'a', sizeof(toolong));
+    if (sethostname(toolong, sizeof(toolong)) != -1 || errno != EINVAL) {
+        fprintf(stderr, "sethostname with %zu bytes should fail with EINVAL\n", sizeof(toolong));
+        return 1;
+    }
+
+    /* A child in a UTS namespace of its own renames only itself */
+    pid_t pid = fork();
+    if (pid < 0)
+        return fail("fork");
+    if (pid == 0) {
+        if (unshare(CLONE_NEWUTS) < 0)
+            exit(fail("child: unshare"));
+        if (expect_hostname(original, "child: after unshare"))
+            exit(1);
+        /* The name need not be NUL-terminated */
+        if (sethostname("lind-childXXX", 10) < 0)
+            exit(fail("child: sethostname"));
+        exit(expect_hostname("lind-child", "child: after sethostname"));
+    }
+    if (wait_child(pid) || expect_hostname(original, "parent: after private child"))
+        return 1;
+
+    /* A child sharing the namespace renames its parent too */
+    pid = fork();
+    if (pid < 0)
+        return fail("fork");
+    if (pid == 0) {
+        if (sethostname("lind-shared", strlen("lind-shared")) < 0)
+            exit(fail("child: sethostname"));
+        exit(0);
+    }
+    if (wait_child(pid) || expect_hostname("lind-shared", "parent: after shared child"))
+        return 1;
+
+    if (sethostname(original, strlen(original)) < 0)
+        return fail("sethostname");
+    if (expect_hostname(original, "restored"))
+        return 1;
+
+    printf("uts_hostname test passed\n");
+    return 0;
+}
@@ -0,0 +1,69 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <errno.h>
+#include <lind_syscall.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* lind_checkpoint saves the cage to a file with mode 0600 and returns 0 to the running cage, and
+ * the cage keeps its memory afterwards. A cage with a pipe cannot be saved; a cage with a live
+ * child is saved together with the child, which keeps running afterwards.
+ *
+ * Resuming from the file needs `lind-boot --restore`, which checkpoint_restore.c and
+ * checkpoint_tree.c cover. */
+
+#define SNAPSHOT "checkpoint_test.img"
+
+static char pattern[64 * 1024];
+
+int main(void) {
+    for (size_t i = 0; i < sizeof(pattern); i++)
+        pattern[i] = (char)(i * 7);
+
+    char *heap = malloc(4096);
+    assert(heap != NULL);
+    strcpy(heap, "saved on the heap");
+
+    assert(lind_checkpoint(SNAPSHOT) == 0);
+
+    /* The running cage keeps its memory */
+    for (size_t i = 0; i < sizeof(pattern); i++)
+        assert(pattern[i] == (char)(i * 7));
+    assert(strcmp(heap, "saved on the heap") == 0);
+
+    struct stat st;
+    assert(stat(SNAPSHOT, &st) == 0);
+    assert(S_ISREG(st.st_mode) && (st.st_mode & 0777) == 0600 && st.st_size > 0);
+    assert(unlink(SNAPSHOT) == 0);
+
+    /* A pipe cannot be reopened by path */
+    int fds[2];
+    assert(pipe(fds) == 0);
+    assert(lind_checkpoint(SNAPSHOT) == -1 && errno == EOPNOTSUPP);
+    close(fds[0]);
+    close(fds[1]);
+
+    /* A child blocked in pause() is frozen and saved with its parent, then goes on waiting */
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        pause();
+        _exit(0);
+    }
+    assert(lind_checkpoint(SNAPSHOT) == 0);
+    assert(stat(SNAPSHOT, &st) == 0 && st.st_size > 0);
+    assert(unlink(SNAPSHOT) == 0);
+    assert(waitpid(pid, NULL, WNOHANG) == 0);
+    assert(kill(pid, SIGKILL) == 0);
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFSIGNALED(status) && WTERMSIG(status) == SIGKILL);
+
+    printf("checkpoint test passed\n");
+    return 0;
+}
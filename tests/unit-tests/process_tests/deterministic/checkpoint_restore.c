@@ -0,0 +1,90 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <lind_syscall.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+/* The first run saves the cage and exits; the second resumes it from the file, where
+ * lind_checkpoint returns 1 and the cage checks what it had before: globals, heap, stack, a
+ * file at its offset and a mapping it could not read when it was saved. */
+// lind-run: --restore checkpoint_restore.img {wasm}
+
+#define SNAPSHOT "checkpoint_restore.img"
+#define DATAFILE "checkpoint_restore.txt"
+
+static char pattern[64 * 1024];
+static int steps;
+
+int main(void) {
+    setvbuf(stdout, NULL, _IONBF, 0);
+    long pagesize = sysconf(_SC_PAGESIZE);
+
+    for (size_t i = 0; i < sizeof(pattern); i++)
+        pattern[i] = (char)(i * 7);
+    char *heap = malloc(4096);
+    assert(heap != NULL);
+    strcpy(heap, "saved on the heap");
+    volatile int local = 0x5eed;
+
+    int fd = open(DATAFILE, O_RDWR | O_CREAT | O_TRUNC, 0600);
+    assert(fd >= 0);
+    assert(write(fd, "0123456789", 10) == 10);
+    assert(lseek(fd, 4, SEEK_SET) == 4);
+
+    char *hidden = mmap(NULL, 2 * pagesize, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS,
+                        -1, 0);
+    assert(hidden != MAP_FAILED);
+    memset(hidden, 'h', 2 * pagesize);
+    assert(mprotect(hidden, 2 * pagesize, PROT_NONE) == 0);
+
+    printf("before checkpoint\n");
+    steps = 1;
+    int ret = lind_checkpoint(SNAPSHOT);
+    steps++;
+
+    if (ret == 0) {
+        /* Saving leaves the mapping as it was */
+        assert(mprotect(hidden, 2 * pagesize, PROT_READ) == 0);
+        assert(hidden[0] == 'h');
+        close(fd);
+        printf("checkpoint saved\n");
+        return 0;
+    }
+    assert(ret == 1);
+
+    /* Execution continues after the call, once */
+    assert(steps == 2);
+
+    for (size_t i = 0; i < sizeof(pattern); i++)
+        assert(pattern[i] == (char)(i * 7));
+    assert(strcmp(heap, "saved on the heap") == 0);
+    assert(local == 0x5eed);
+
+    /* The file is open again at the offset it had */
+    char buf[8] = { 0 };
+    assert(read(fd, buf, 3) == 3);
+    assert(memcmp(buf, "456", 3) == 0);
+    assert(lseek(fd, 0, SEEK_CUR) == 7);
+
+    /* The unreadable mapping kept its contents */
+    assert(mprotect(hidden, 2 * pagesize, PROT_READ) == 0);
+    for (long i = 0; i < 2 * pagesize; i++)
+        assert(hidden[i] == 'h');
+
+    /* The cage keeps working: it can allocate and map more */
+    char *more = malloc(1 << 20);
+    assert(more != NULL);
+    memset(more, 1, 1 << 20);
+    free(more);
+
+    close(fd);
+    assert(unlink(DATAFILE) == 0);
+    assert(unlink(SNAPSHOT) == 0);
+    printf("checkpoint restored\n");
+    return 0;
+}
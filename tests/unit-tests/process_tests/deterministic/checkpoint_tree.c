@@ -0,0 +1,82 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <errno.h>
+#include <lind_syscall.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* A cage saved with its children: one waiting in pause() for a signal and one that already
+ * exited. The first run saves the tree and kills the waiting child; the second resumes the whole
+ * tree from the file, wakes the child, which checks its own memory, and reaps both. */
+// lind-run: --restore checkpoint_tree.img {wasm}
+
+#define SNAPSHOT "checkpoint_tree.img"
+
+static volatile sig_atomic_t woken;
+
+static void on_usr1(int sig) {
+    (void)sig;
+    woken = 1;
+}
+
+int main(void) {
+    setvbuf(stdout, NULL, _IONBF, 0);
+
+    struct sigaction sa;
+    memset(&sa, 0, sizeof(sa));
+    sa.sa_handler = on_usr1;
+    assert(sigaction(SIGUSR1, &sa, NULL) == 0);
+
+    pid_t sleeper = fork();
+    assert(sleeper >= 0);
+    if (sleeper == 0) {
+        /* Memory of the child only, different from the parent's */
+        char *mine = malloc(16 * 4096);
+        assert(mine != NULL);
+        for (int i = 0; i < 16 * 4096; i++)
+            mine[i] = (char)(i * 13);
+        while (!woken)
+            pause();
+        for (int i = 0; i < 16 * 4096; i++)
+            if (mine[i] != (char)(i * 13))
+                _exit(1);
+        _exit(42);
+    }
+
+    pid_t exited = fork();
+    assert(exited >= 0);
+    if (exited == 0)
+        _exit(7);
+    // Let it exit first, so that it is saved as a zombie
+    usleep(100000);
+
+    int ret = lind_checkpoint(SNAPSHOT);
+    assert(ret == 0 || ret == 1);
+
+    int status;
+    if (ret == 0) {
+        assert(kill(sleeper, SIGKILL) == 0);
+        assert(waitpid(sleeper, &status, 0) == sleeper);
+        assert(WIFSIGNALED(status) && WTERMSIG(status) == SIGKILL);
+        assert(waitpid(exited, &status, 0) == exited);
+        assert(WIFEXITED(status) && WEXITSTATUS(status) == 7);
+        printf("tree saved\n");
+        return 0;
+    }
+
+    /* The children came back with their IDs */
+    assert(kill(sleeper, SIGUSR1) == 0);
+    assert(waitpid(sleeper, &status, 0) == sleeper);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 42);
+    assert(waitpid(exited, &status, 0) == exited);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 7);
+    assert(waitpid(-1, NULL, WNOHANG) == -1 && errno == ECHILD);
+
+    assert(unlink(SNAPSHOT) == 0);
+    printf("tree restored\n");
+    return 0;
+}
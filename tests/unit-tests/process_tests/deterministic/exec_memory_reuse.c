@@ -0,0 +1,138 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <fcntl.h>
+#include <inttypes.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* exec hands the cage's linear memory to the new program instead of mapping a fresh one, and
+ * keeps precompiled modules cached by path. The program dirties its globals, heap and a mapping,
+ * then execs itself: the new program must find the same addresses zeroed, its data segment
+ * rewritten, and the program break and the mmap area starting over. It then execs a copy of its
+ * module twice, rewriting the copy in place in between, and must see the new contents run. */
+
+#define DIRTY_LEN (64 * 1024)
+#define COPY "exec_memory_reuse_copy.cwasm"
+
+static char zeroed[DIRTY_LEN];
+static int initialized = 1234;
+/* Patched in the copy of the module; the last character tells which contents ran */
+static const char marker[] = "exec_memory_reuse generation A";
+
+static void dirty(char *p, size_t len) {
+    memset(p, 0xaa, len);
+}
+
+static int all_zero(const char *p, size_t len) {
+    for (size_t i = 0; i < len; i++)
+        if (p[i] != 0)
+            return 0;
+    return 1;
+}
+
+/* Copies the module at `from` to `to`, in place if `to` exists, with the last character of the
+ * marker set to `generation` */
+static void write_copy(const char *from, const char *to, char generation) {
+    int in = open(from, O_RDONLY);
+    assert(in >= 0);
+    struct stat st;
+    assert(fstat(in, &st) == 0);
+    char *module = malloc(st.st_size);
+    assert(module != NULL);
+    assert(read(in, module, st.st_size) == st.st_size);
+    close(in);
+
+    size_t len = sizeof(marker) - 1;
+    char *found = memmem(module, st.st_size, marker, len);
+    assert(found != NULL);
+    found[len - 1] = generation;
+
+    int out = open(to, O_WRONLY | O_CREAT | O_TRUNC, 0755);
+    assert(out >= 0);
+    assert(write(out, module, st.st_size) == st.st_size);
+    close(out);
+    free(module);
+}
+
+/* Runs the copy in a child and returns the generation it reports */
+static int run_copy(void) {
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        execl(COPY, COPY, "generation", NULL);
+        _exit(1);
+    }
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status));
+    return WEXITSTATUS(status);
+}
+
+static void check_after_exec(char **argv) {
+    uintptr_t old_brk = strtoull(argv[2], NULL, 16);
+    uintptr_t old_mapping = strtoull(argv[3], NULL, 16);
+
+    /* The old program's globals are gone: bss is zero and data is initialized again */
+    assert(all_zero(zeroed, DIRTY_LEN));
+    assert(initialized == 1234);
+
+    /* The program break starts where the old one started, over zeroed pages */
+    char *brk = sbrk(0);
+    assert((uintptr_t)brk == old_brk);
+    assert(sbrk(DIRTY_LEN) == brk);
+    assert(all_zero(brk, DIRTY_LEN));
+
+    /* The mmap area starts over too: the first mapping lands where the old one was */
+    char *mapping = mmap(NULL, DIRTY_LEN, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS,
+                         -1, 0);
+    assert(mapping != MAP_FAILED);
+    assert((uintptr_t)mapping == old_mapping);
+    assert(all_zero(mapping, DIRTY_LEN));
+    printf("memory starts clean after exec\n");
+
+    /* A module rewritten at the same path is loaded again, not taken from the cache */
+    write_copy(argv[0], COPY, 'A');
+    assert(run_copy() == 'A');
+    assert(run_copy() == 'A');
+    // So that the rewrite gets another modification time even with coarse timestamps
+    usleep(50000);
+    write_copy(argv[0], COPY, 'B');
+    assert(run_copy() == 'B');
+    assert(unlink(COPY) == 0);
+    printf("rewritten module reloaded\n");
+}
+
+int main(int argc, char **argv) {
+    if (argc > 1 && strcmp(argv[1], "generation") == 0) {
+        volatile const char *m = marker;
+        return m[sizeof(marker) - 2];
+    }
+    if (argc > 3 && strcmp(argv[1], "check") == 0) {
+        check_after_exec(argv);
+        return 0;
+    }
+
+    /* Dirty what the next program will get: globals, the heap and a mapping */
+    char *brk = sbrk(0);
+    char *mapping = mmap(NULL, DIRTY_LEN, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS,
+                         -1, 0);
+    assert(mapping != MAP_FAILED);
+    dirty(mapping, DIRTY_LEN);
+    dirty(zeroed, DIRTY_LEN);
+    initialized = 0;
+    char *heap = sbrk(DIRTY_LEN);
+    assert(heap == brk);
+    dirty(heap, DIRTY_LEN);
+
+    char brk_arg[32], mapping_arg[32];
+    snprintf(brk_arg, sizeof(brk_arg), "%" PRIxPTR, (uintptr_t)brk);
+    snprintf(mapping_arg, sizeof(mapping_arg), "%" PRIxPTR, (uintptr_t)mapping);
+    execl(argv[0], argv[0], "check", brk_arg, mapping_arg, NULL);
+    perror("exec failed");
+    return 1;
+}
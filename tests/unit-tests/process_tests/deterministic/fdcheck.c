@@ -0,0 +1,15 @@
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+/* Prints whether each descriptor named on the command line is open. Used by posix_spawn.c to
+ * look at the descriptors a spawned child inherited; without arguments it prints nothing. */
+int main(int argc, char *argv[]) {
+    for (int i = 1; i < argc; i++) {
+        int fd = atoi(argv[i]);
+        int open = fcntl(fd, F_GETFD) != -1 || errno != EBADF;
+        printf("%d %s\n", fd, open ? "open" : "closed");
+    }
+    return 0;
+}
@@ -0,0 +1,158 @@
+#include <assert.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <mqueue.h>
+#include <poll.h>
+#include <signal.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+/* POSIX message queues are shared by name between cages: messages come out highest priority
+ * first, empty and full queues block or fail with EAGAIN, descriptors poll like the queue, and
+ * mq_notify signals the registered cage when a message reaches an empty queue. */
+
+#define QNAME "/posix_mqueue_test"
+#define MSGSIZE 64
+
+static volatile sig_atomic_t notified = 0;
+
+static void on_notify(int sig) {
+    (void)sig;
+    notified = 1;
+}
+
+static mqd_t open_queue(int flags) {
+    struct mq_attr attr = {.mq_maxmsg = 4, .mq_msgsize = MSGSIZE};
+    mqd_t mq = mq_open(QNAME, O_RDWR | O_CREAT | flags, 0600, &attr);
+    assert(mq != (mqd_t)-1);
+    return mq;
+}
+
+static void check_priority_and_fork(void) {
+    mqd_t mq = open_queue(0);
+    assert(mq_send(mq, "low", 4, 1) == 0);
+    assert(mq_send(mq, "high", 5, 9) == 0);
+    assert(mq_send(mq, "mid", 4, 5) == 0);
+
+    char buf[MSGSIZE];
+    unsigned prio;
+    assert(mq_receive(mq, buf, sizeof(buf), &prio) == 5 && prio == 9 && !strcmp(buf, "high"));
+    assert(mq_receive(mq, buf, sizeof(buf), &prio) == 4 && prio == 5 && !strcmp(buf, "mid"));
+    assert(mq_receive(mq, buf, sizeof(buf), &prio) == 4 && prio == 1 && !strcmp(buf, "low"));
+
+    /* A buffer smaller than mq_msgsize is refused */
+    assert(mq_receive(mq, buf, MSGSIZE - 1, NULL) == -1 && errno == EMSGSIZE);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        /* Reopen by name rather than use the inherited descriptor */
+        mqd_t child = mq_open(QNAME, O_WRONLY);
+        assert(child != (mqd_t)-1);
+        usleep(50 * 1000);
+        assert(mq_send(child, "from child", 11, 3) == 0);
+        mq_close(child);
+        _exit(0);
+    }
+    /* Blocks until the child sends */
+    assert(mq_receive(mq, buf, sizeof(buf), &prio) == 11 && prio == 3);
+    assert(!strcmp(buf, "from child"));
+
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+    mq_close(mq);
+}
+
+static void check_nonblock_and_timeout(void) {
+    mqd_t mq = open_queue(O_NONBLOCK);
+    char buf[MSGSIZE];
+    assert(mq_receive(mq, buf, sizeof(buf), NULL) == -1 && errno == EAGAIN);
+    for (int i = 0; i < 4; i++)
+        assert(mq_send(mq, "x", 1, 0) == 0);
+    assert(mq_send(mq, "x", 1, 0) == -1 && errno == EAGAIN);
+
+    struct mq_attr attr;
+    assert(mq_getattr(mq, &attr) == 0);
+    assert(attr.mq_curmsgs == 4 && attr.mq_maxmsg == 4 && (attr.mq_flags & O_NONBLOCK));
+    while (mq_receive(mq, buf, sizeof(buf), NULL) >= 0)
+        ;
+
+    /* Clearing O_NONBLOCK makes an empty queue wait until the deadline */
+    struct mq_attr blocking = {0};
+    assert(mq_setattr(mq, &blocking, NULL) == 0);
+    struct timespec deadline;
+    clock_gettime(CLOCK_REALTIME, &deadline);
+    deadline.tv_nsec += 100 * 1000 * 1000;
+    if (deadline.tv_nsec >= 1000000000) {
+        deadline.tv_sec++;
+        deadline.tv_nsec -= 1000000000;
+    }
+    assert(mq_timedreceive(mq, buf, sizeof(buf), NULL, &deadline) == -1 && errno == ETIMEDOUT);
+    mq_close(mq);
+}
+
+static void check_poll(void) {
+    mqd_t mq = open_queue(0);
+    struct pollfd pfd = {.fd = mq, .events = POLLIN | POLLOUT};
+    assert(poll(&pfd, 1, 0) == 1);
+    assert(!(pfd.revents & POLLIN) && (pfd.revents & POLLOUT));
+
+    assert(mq_send(mq, "ready", 6, 0) == 0);
+    pfd.events = POLLIN;
+    pfd.revents = 0;
+    assert(poll(&pfd, 1, 1000) == 1 && (pfd.revents & POLLIN));
+
+    char buf[MSGSIZE];
+    assert(mq_receive(mq, buf, sizeof(buf), NULL) == 6);
+    pfd.revents = 0;
+    assert(poll(&pfd, 1, 0) == 0);
+    mq_close(mq);
+}
+
+static void check_notify(void) {
+    mqd_t mq = open_queue(0);
+    signal(SIGUSR1, on_notify);
+    struct sigevent sev = {.sigev_notify = SIGEV_SIGNAL, .sigev_signo = SIGUSR1};
+    assert(mq_notify(mq, &sev) == 0);
+    /* Only one cage may be registered at a time */
+    assert(mq_notify(mq, &sev) == -1 && errno == EBUSY);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        mqd_t child = mq_open(QNAME, O_WRONLY);
+        assert(child != (mqd_t)-1);
+        assert(mq_send(child, "wake", 5, 0) == 0);
+        _exit(0);
+    }
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+    for (int i = 0; i < 100 && !notified; i++)
+        usleep(10 * 1000);
+    assert(notified);
+
+    /* The registration was used up, so registering again succeeds */
+    assert(mq_notify(mq, &sev) == 0);
+    assert(mq_notify(mq, NULL) == 0);
+    mq_close(mq);
+}
+
+int main(void) {
+    mq_unlink(QNAME);
+    check_priority_and_fork();
+    check_nonblock_and_timeout();
+    check_poll();
+    check_notify();
+
+    assert(mq_unlink(QNAME) == 0);
+    assert(mq_unlink(QNAME) == -1 && errno == ENOENT);
+    assert(mq_open(QNAME, O_RDONLY) == (mqd_t)-1 && errno == ENOENT);
+    assert(mq_open("/bad/name", O_RDWR | O_CREAT, 0600, NULL) == (mqd_t)-1 && errno == EACCES);
+    printf("posix_mqueue test passed\n");
+    return 0;
+}
@@ -0,0 +1,175 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <spawn.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* posix_spawn creates the child cage directly: file actions apply to the child only, the child's
+ * output can be redirected into a pipe or a file, a chdir action applies to a relative program
+ * path, posix_spawnp searches PATH and runs the file actions once however many entries it tries,
+ * close-on-exec descriptors are closed in the child, and a missing program fails in the parent
+ * without leaving a child behind.
+ *
+ * Requires automated_tests/hello, which prints "wark", and automated_tests/fdcheck. */
+
+extern char **environ;
+
+static void wait_child(pid_t pid) {
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+
+static void check_output(int fd) {
+    char buf[16] = {0};
+    ssize_t n, len = 0;
+    while ((n = read(fd, buf + len, sizeof(buf) - 1 - len)) > 0)
+        len += n;
+    assert(strcmp(buf, "wark\n") == 0);
+}
+
+/* Spawns PATH with stdout redirected into a pipe, after running the chdir action if DIR is set,
+ * and checks what it printed. */
+static void spawn_to_pipe(const char *path, const char *dir, int use_path) {
+    int fds[2];
+    assert(pipe(fds) == 0);
+
+    posix_spawn_file_actions_t actions;
+    assert(posix_spawn_file_actions_init(&actions) == 0);
+    assert(posix_spawn_file_actions_adddup2(&actions, fds[1], STDOUT_FILENO) == 0);
+    assert(posix_spawn_file_actions_addclose(&actions, fds[0]) == 0);
+    assert(posix_spawn_file_actions_addclose(&actions, fds[1]) == 0);
+    if (dir)
+        assert(posix_spawn_file_actions_addchdir_np(&actions, dir) == 0);
+
+    char *argv[] = {"hello", NULL};
+    pid_t pid;
+    int ret = use_path ? posix_spawnp(&pid, path, &actions, NULL, argv, environ)
+                       : posix_spawn(&pid, path, &actions, NULL, argv, environ);
+    assert(ret == 0);
+    assert(posix_spawn_file_actions_destroy(&actions) == 0);
+
+    /* The actions ran in the child, so the parent's pipe is still open on both ends */
+    close(fds[1]);
+    check_output(fds[0]);
+    close(fds[0]);
+    wait_child(pid);
+}
+
+static void check_addopen(void) {
+    const char *out = "posix_spawn_out.txt";
+    posix_spawn_file_actions_t actions;
+    assert(posix_spawn_file_actions_init(&actions) == 0);
+    assert(posix_spawn_file_actions_addopen(&actions, STDOUT_FILENO, out,
+                                            O_WRONLY | O_CREAT | O_TRUNC, 0644) == 0);
+
+    char *argv[] = {"hello", NULL};
+    pid_t pid;
+    assert(posix_spawn(&pid, "automated_tests/hello", &actions, NULL, argv, environ) == 0);
+    assert(posix_spawn_file_actions_destroy(&actions) == 0);
+    wait_child(pid);
+
+    int fd = open(out, O_RDONLY);
+    assert(fd >= 0);
+    check_output(fd);
+    close(fd);
+    unlink(out);
+}
+
+/* The open action runs once even though PATH lists a directory without the program first, so
+ * O_EXCL does not trip over the file the action created. */
+static void check_addopen_path(void) {
+    const char *out = "posix_spawn_excl.txt";
+    unlink(out);
+    posix_spawn_file_actions_t actions;
+    assert(posix_spawn_file_actions_init(&actions) == 0);
+    assert(posix_spawn_file_actions_addopen(&actions, STDOUT_FILENO, out,
+                                            O_WRONLY | O_CREAT | O_EXCL, 0644) == 0);
+
+    char *argv[] = {"hello", NULL};
+    pid_t pid;
+    assert(posix_spawnp(&pid, "hello", &actions, NULL, argv, environ) == 0);
+    assert(posix_spawn_file_actions_destroy(&actions) == 0);
+    wait_child(pid);
+
+    int fd = open(out, O_RDONLY);
+    assert(fd >= 0);
+    check_output(fd);
+    close(fd);
+    unlink(out);
+}
+
+/* A close-on-exec descriptor is closed in the child unless a dup2 action onto itself clears the
+ * flag; other descriptors are inherited. */
+static void check_cloexec(void) {
+    int cloexec = open("/dev/null", O_RDONLY | O_CLOEXEC);
+    int inherited = open("/dev/null", O_RDONLY);
+    int kept = open("/dev/null", O_RDONLY | O_CLOEXEC);
+    assert(cloexec > STDERR_FILENO && inherited >= 0 && kept >= 0);
+
+    int fds[2];
+    assert(pipe(fds) == 0);
+    posix_spawn_file_actions_t actions;
+    assert(posix_spawn_file_actions_init(&actions) == 0);
+    assert(posix_spawn_file_actions_adddup2(&actions, fds[1], STDOUT_FILENO) == 0);
+    assert(posix_spawn_file_actions_adddup2(&actions, kept, kept) == 0);
+
+    char args[3][12];
+    snprintf(args[0], sizeof(args[0]), "%d", cloexec);
+    snprintf(args[1], sizeof(args[1]), "%d", inherited);
+    snprintf(args[2], sizeof(args[2]), "%d", kept);
+    char *argv[] = {"fdcheck", args[0], args[1], args[2], NULL};
+    pid_t pid;
+    assert(posix_spawn(&pid, "automated_tests/fdcheck", &actions, NULL, argv, environ) == 0);
+    assert(posix_spawn_file_actions_destroy(&actions) == 0);
+    close(fds[1]);
+
+    char buf[128] = {0};
+    ssize_t n, len = 0;
+    while ((n = read(fds[0], buf + len, sizeof(buf) - 1 - len)) > 0)
+        len += n;
+    close(fds[0]);
+    wait_child(pid);
+
+    char expected[128];
+    snprintf(expected, sizeof(expected), "%d closed\n%d open\n%d open\n", cloexec, inherited,
+             kept);
+    assert(strcmp(buf, expected) == 0);
+
+    /* The parent's flags are untouched */
+    assert(fcntl(kept, F_GETFD) == FD_CLOEXEC);
+    close(cloexec);
+    close(inherited);
+    close(kept);
+}
+
+static void check_missing(void) {
+    char *argv[] = {"no_such_program", NULL};
+    pid_t pid;
+    assert(posix_spawn(&pid, "automated_tests/no_such_program", NULL, NULL, argv, environ) ==
+           ENOENT);
+    assert(posix_spawnp(&pid, "no_such_program", NULL, NULL, argv, environ) == ENOENT);
+
+    /* The failed spawns left no child to reap */
+    assert(waitpid(-1, NULL, WNOHANG) == -1 && errno == ECHILD);
+}
+
+int main(void) {
+    spawn_to_pipe("automated_tests/hello", NULL, 0);
+    spawn_to_pipe("hello", "automated_tests", 0);
+
+    assert(setenv("PATH", "/no/such/dir:automated_tests", 1) == 0);
+    spawn_to_pipe("hello", NULL, 1);
+
+    check_addopen();
+    check_addopen_path();
+    check_cloexec();
+    check_missing();
+    printf("posix_spawn test passed\n");
+    return 0;
+}
@@ -0,0 +1,108 @@
+#define _GNU_SOURCE
+#include <assert.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+/* `lind-boot --preinit` runs wizer-initialize under lind and writes a module that starts with
+ * what it left: globals, heap and mappings. The first run calls init from main; the second
+ * pre-initializes the module, and the third starts the result, where main finds init done, the
+ * heap past what init allocated, and the address translation of libc set up for its own cage. */
+// lind-run: --preinit preinit_init.cwasm {module}
+// lind-run: preinit_init.cwasm
+
+#define TABLE_LEN (256 * 1024)
+#define MAPPED_LEN (4 * 4096)
+
+/* What libc caches for address translation, and what the host reports */
+extern uint64_t __lind_base;
+extern uint64_t __lind_cageid;
+unsigned long long host_memory_base(void)
+    __attribute__((__import_module__("lind"), __import_name__("lind-get-memory-base")));
+unsigned long long host_cage_id(void)
+    __attribute__((__import_module__("lind"), __import_name__("lind-get-cage-id")));
+
+/* The setup _start does, which init runs before on its own */
+void __lind_init_addr_translation(void);
+void __libc_setup_tls(void);
+void __wasi_init_tp(void);
+void __lind_initialize_environ(void);
+void __ctype_init(void);
+
+static int initialized;
+static int init_runs;
+static char *table;
+static char *mapped;
+static void *brk_after_init;
+
+__attribute__((export_name("wizer-initialize"))) void init(void) {
+    if (initialized)
+        return;
+    if (__lind_cageid == 0) {
+        __lind_init_addr_translation();
+        __libc_setup_tls();
+        __wasi_init_tp();
+        __lind_initialize_environ();
+        __ctype_init();
+    }
+
+    table = malloc(TABLE_LEN);
+    assert(table != NULL);
+    for (int i = 0; i < TABLE_LEN; i++)
+        table[i] = (char)(i * 31);
+    mapped = mmap(NULL, MAPPED_LEN, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(mapped != MAP_FAILED);
+    memset(mapped, 'm', MAPPED_LEN);
+    brk_after_init = sbrk(0);
+
+    init_runs++;
+    initialized = 1;
+    // Not through stdio, whose buffer would be saved with the memory
+    const char msg[] = "init ran\n";
+    write(STDOUT_FILENO, msg, sizeof(msg) - 1);
+}
+
+int main(void) {
+    setvbuf(stdout, NULL, _IONBF, 0);
+    int preinitialized = initialized;
+    init();
+    printf("main: init %s\n", preinitialized ? "was preinitialized" : "ran in this run");
+    assert(init_runs == 1);
+
+    /* What init left in memory */
+    for (int i = 0; i < TABLE_LEN; i++)
+        assert(table[i] == (char)(i * 31));
+    for (int i = 0; i < MAPPED_LEN; i++)
+        assert(mapped[i] == 'm');
+
+    /* The heap carries on after init's allocations instead of starting over them */
+    assert((char *)sbrk(0) >= (char *)brk_after_init);
+    char *more = malloc(TABLE_LEN);
+    assert(more != NULL);
+    assert(more + TABLE_LEN <= table || more >= table + TABLE_LEN);
+    memset(more, 0, TABLE_LEN);
+    for (int i = 0; i < TABLE_LEN; i++)
+        assert(table[i] == (char)(i * 31));
+    free(more);
+
+    /* New mappings do not land on init's */
+    char *other = mmap(NULL, MAPPED_LEN, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    assert(other != MAP_FAILED);
+    assert(other + MAPPED_LEN <= mapped || other >= mapped + MAPPED_LEN);
+    memset(other, 0, MAPPED_LEN);
+    assert(mapped[0] == 'm' && mapped[MAPPED_LEN - 1] == 'm');
+    munmap(other, MAPPED_LEN);
+
+    /* libc translates addresses for this cage, not the one that ran init */
+    assert(__lind_cageid == (uint64_t)getpid());
+    assert(__lind_cageid == host_cage_id());
+    assert(__lind_base == host_memory_base());
+
+    if (preinitialized)
+        assert(unlink("preinit_init.cwasm") == 0);
+    printf("preinit test passed\n");
+    return 0;
+}
@@ -0,0 +1,118 @@
+#include <assert.h>
+#include <fcntl.h>
+#include <pthread.h>
+#include <semaphore.h>
+#include <stdio.h>
+#include <sys/ipc.h>
+#include <sys/mman.h>
+#include <sys/shm.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* Process-shared primitives placed in shared memory work across cages even when each cage maps
+ * the memory at a different address: the child attaches the SysV segment and maps the file a
+ * second time, and synchronizes with the parent through its own mapping only. */
+
+#define SHM_SIZE 4096
+
+struct shared {
+    pthread_mutex_t lock;
+    pthread_cond_t cond;
+    int stage;
+};
+
+static void init_shared(struct shared *s) {
+    pthread_mutexattr_t mattr;
+    pthread_condattr_t cattr;
+    pthread_mutexattr_init(&mattr);
+    pthread_mutexattr_setpshared(&mattr, PTHREAD_PROCESS_SHARED);
+    pthread_mutex_init(&s->lock, &mattr);
+    pthread_mutexattr_destroy(&mattr);
+    pthread_condattr_init(&cattr);
+    pthread_condattr_setpshared(&cattr, PTHREAD_PROCESS_SHARED);
+    pthread_cond_init(&s->cond, &cattr);
+    pthread_condattr_destroy(&cattr);
+    s->stage = 0;
+}
+
+/* Waits until the other side moves `stage` to `want`, then moves it on */
+static void handshake(struct shared *s, int want) {
+    pthread_mutex_lock(&s->lock);
+    while (s->stage != want)
+        pthread_cond_wait(&s->cond, &s->lock);
+    s->stage = want + 1;
+    pthread_cond_broadcast(&s->cond);
+    pthread_mutex_unlock(&s->lock);
+}
+
+static void check_shm(void) {
+    key_t key = 4321;
+    int old_shmid = shmget(key, SHM_SIZE, 0666);
+    if (old_shmid >= 0)
+        shmctl(old_shmid, IPC_RMID, NULL);
+    int shmid = shmget(key, SHM_SIZE, IPC_CREAT | IPC_EXCL | 0666);
+    assert(shmid >= 0);
+    struct shared *s = shmat(shmid, NULL, 0);
+    assert(s != (void *)-1);
+    init_shared(s);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        struct shared *mine = shmat(shmid, NULL, 0);
+        assert(mine != (void *)-1 && mine != s);
+        handshake(mine, 0);
+        handshake(mine, 2);
+        _exit(0);
+    }
+    handshake(s, 1);
+    pthread_mutex_lock(&s->lock);
+    while (s->stage != 3)
+        pthread_cond_wait(&s->cond, &s->lock);
+    pthread_mutex_unlock(&s->lock);
+
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+    assert(shmdt(s) == 0);
+    assert(shmctl(shmid, IPC_RMID, NULL) == 0);
+}
+
+static void check_file(void) {
+    const char *path = "pshared_futex_shm.tmp";
+    int fd = open(path, O_RDWR | O_CREAT | O_TRUNC, 0666);
+    assert(fd >= 0);
+    assert(ftruncate(fd, SHM_SIZE) == 0);
+    sem_t *sem = mmap(NULL, SHM_SIZE, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+    assert(sem != MAP_FAILED);
+    assert(sem_init(sem, 1, 0) == 0);
+    assert(sem_init(sem + 1, 1, 0) == 0);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        sem_t *mine = mmap(NULL, SHM_SIZE, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+        assert(mine != MAP_FAILED && mine != sem);
+        /* Blocks until the parent posts through its own mapping */
+        assert(sem_wait(mine) == 0);
+        assert(sem_post(mine + 1) == 0);
+        _exit(0);
+    }
+    usleep(50 * 1000);
+    assert(sem_post(sem) == 0);
+    assert(sem_wait(sem + 1) == 0);
+
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+    munmap(sem, SHM_SIZE);
+    close(fd);
+    unlink(path);
+}
+
+int main(void) {
+    check_shm();
+    check_file();
+    printf("pshared_futex_shm test passed\n");
+    return 0;
+}
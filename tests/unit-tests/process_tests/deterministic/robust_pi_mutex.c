@@ -0,0 +1,139 @@
+#include <errno.h>
+#include <pthread.h>
+#include <semaphore.h>
+#include <stdio.h>
+#include <string.h>
+#include <time.h>
+#include <unistd.h>
+
+/* Robust mutexes held by a thread that exits are handed to the next locker with
+ * EOWNERDEAD, whether it was already waiting or locks later. PI mutexes track
+ * their owner, time out, and report EDEADLK/EPERM like Linux. Timed condition
+ * waits use absolute timeouts. */
+
+static pthread_mutex_t robust;
+static sem_t locked;
+
+static int check(int got, int want, const char *what) {
+    if (got != want) {
+        fprintf(stderr, "%s: got %s, expected %s\n", what, strerror(got), strerror(want));
+        return 1;
+    }
+    return 0;
+}
+
+static void init_mutex(pthread_mutex_t *m, int robustness, int protocol, int type) {
+    pthread_mutexattr_t attr;
+    pthread_mutexattr_init(&attr);
+    pthread_mutexattr_setrobust(&attr, robustness);
+    pthread_mutexattr_setprotocol(&attr, protocol);
+    pthread_mutexattr_settype(&attr, type);
+    pthread_mutex_init(m, &attr);
+    pthread_mutexattr_destroy(&attr);
+}
+
+/* Locks the robust mutex and exits without unlocking it */
+static void *die_holding(void *arg) {
+    pthread_mutex_lock(&robust);
+    sem_post(&locked);
+    if (arg != NULL)
+        usleep(100 * 1000); /* let the main thread block on the mutex */
+    return NULL;
+}
+
+static int check_owner_died(int protocol, int wait_first) {
+    pthread_t t;
+    init_mutex(&robust, PTHREAD_MUTEX_ROBUST, protocol, PTHREAD_MUTEX_NORMAL);
+    pthread_create(&t, NULL, die_holding, wait_first ? &robust : NULL);
+    sem_wait(&locked);
+    if (!wait_first)
+        pthread_join(t, NULL);
+
+    if (check(pthread_mutex_lock(&robust), EOWNERDEAD, "lock after owner died"))
+        return 1;
+    if (check(pthread_mutex_consistent(&robust), 0, "consistent") ||
+        check(pthread_mutex_unlock(&robust), 0, "unlock") ||
+        check(pthread_mutex_lock(&robust), 0, "relock") ||
+        check(pthread_mutex_unlock(&robust), 0, "unlock again"))
+        return 1;
+    if (wait_first)
+        pthread_join(t, NULL);
+    pthread_mutex_destroy(&robust);
+    return 0;
+}
+
+static pthread_mutex_t pi;
+
+static void *contend_pi(void *arg) {
+    (void)arg;
+    long ret = 0;
+    struct timespec deadline;
+    clock_gettime(CLOCK_REALTIME, &deadline);
+    deadline.tv_nsec += 50 * 1000 * 1000;
+    if (deadline.tv_nsec >= 1000000000) {
+        deadline.tv_sec++;
+        deadline.tv_nsec -= 1000000000;
+    }
+    ret |= check(pthread_mutex_trylock(&pi), EBUSY, "PI trylock while owned");
+    ret |= check(pthread_mutex_timedlock(&pi, &deadline), ETIMEDOUT, "PI timedlock");
+    ret |= check(pthread_mutex_unlock(&pi), EPERM, "PI unlock by non-owner");
+    sem_post(&locked);
+    /* Blocks until the main thread unlocks */
+    ret |= check(pthread_mutex_lock(&pi), 0, "PI lock");
+    ret |= check(pthread_mutex_unlock(&pi), 0, "PI unlock");
+    return (void *)ret;
+}
+
+static int check_pi(void) {
+    pthread_t t;
+    void *ret;
+    init_mutex(&pi, PTHREAD_MUTEX_STALLED, PTHREAD_PRIO_INHERIT, PTHREAD_MUTEX_ERRORCHECK);
+    if (check(pthread_mutex_lock(&pi), 0, "PI lock") ||
+        check(pthread_mutex_lock(&pi), EDEADLK, "PI relock"))
+        return 1;
+    pthread_create(&t, NULL, contend_pi, NULL);
+    sem_wait(&locked);
+    usleep(50 * 1000);
+    if (check(pthread_mutex_unlock(&pi), 0, "PI unlock with a waiter"))
+        return 1;
+    pthread_join(t, &ret);
+    pthread_mutex_destroy(&pi);
+    return ret != NULL;
+}
+
+static int check_cond_timeout(void) {
+    pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+    pthread_cond_t c = PTHREAD_COND_INITIALIZER;
+    struct timespec start, deadline, end;
+    clock_gettime(CLOCK_REALTIME, &start);
+    deadline = start;
+    deadline.tv_nsec += 50 * 1000 * 1000;
+    if (deadline.tv_nsec >= 1000000000) {
+        deadline.tv_sec++;
+        deadline.tv_nsec -= 1000000000;
+    }
+    pthread_mutex_lock(&m);
+    int err = pthread_cond_timedwait(&c, &m, &deadline);
+    pthread_mutex_unlock(&m);
+    clock_gettime(CLOCK_REALTIME, &end);
+    long elapsed_ms =
+        (end.tv_sec - start.tv_sec) * 1000 + (end.tv_nsec - start.tv_nsec) / 1000000;
+    if (check(err, ETIMEDOUT, "cond timedwait"))
+        return 1;
+    if (elapsed_ms < 45 || elapsed_ms > 5000) {
+        fprintf(stderr, "cond timedwait returned after %ld ms, expected 50\n", elapsed_ms);
+        return 1;
+    }
+    return 0;
+}
+
+int main(void) {
+    sem_init(&locked, 0, 0);
+    if (check_owner_died(PTHREAD_PRIO_NONE, 0) || check_owner_died(PTHREAD_PRIO_NONE, 1) ||
+        check_owner_died(PTHREAD_PRIO_INHERIT, 0) || check_owner_died(PTHREAD_PRIO_INHERIT, 1))
+        return 1;
+    if (check_pi() || check_cond_timeout())
+        return 1;
+    printf("robust_pi_mutex test passed\n");
+    return 0;
+}
@@ -0,0 +1,141 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <sched.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/resource.h>
+#include <sys/sysinfo.h>
+#include <sys/utsname.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* Cages see lind's virtual machine: uname reports wasm32, sysinfo counts the
+ * cage's 4 GiB linear memory, and the affinity mask and nice value are kept per
+ * cage and inherited on fork. */
+
+static int fail(const char *what) {
+    fprintf(stderr, "%s: %s\n", what, strerror(errno));
+    return 1;
+}
+
+static int check_sysinfo(void) {
+    struct sysinfo before, after;
+    if (sysinfo(&before) < 0)
+        return fail("sysinfo");
+    unsigned long long total = (unsigned long long)before.totalram * before.mem_unit;
+    if (total != 4ULL << 30 || before.freeram == 0 || before.freeram > before.totalram ||
+        before.procs < 1 || before.totalswap != 0) {
+        fprintf(stderr, "sysinfo: total %llu, free %lu, procs %u\n", total, before.freeram,
+                before.procs);
+        return 1;
+    }
+
+    /* A new mapping is no longer free */
+    size_t len = 16 * before.mem_unit;
+    void *p = mmap(NULL, len, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    if (p == MAP_FAILED)
+        return fail("mmap");
+    if (sysinfo(&after) < 0)
+        return fail("sysinfo");
+    munmap(p, len);
+    if (before.freeram - after.freeram < 16) {
+        fprintf(stderr, "sysinfo: free %lu after a 16-page mmap, %lu before\n", after.freeram,
+                before.freeram);
+        return 1;
+    }
+    if (get_phys_pages() <= 0 || get_avphys_pages() <= 0) {
+        fprintf(stderr, "get_phys_pages %ld, get_avphys_pages %ld\n", get_phys_pages(),
+                get_avphys_pages());
+        return 1;
+    }
+    return 0;
+}
+
+/* Checks the affinity mask has NCPUS CPUs, and that getcpu names one of them */
+static int check_affinity(int ncpus, const char *who) {
+    cpu_set_t set;
+    if (sched_getaffinity(0, sizeof(set), &set) < 0)
+        return fail(who);
+    if (CPU_COUNT(&set) != ncpus) {
+        fprintf(stderr, "%s: %d CPUs in mask, expected %d\n", who, CPU_COUNT(&set), ncpus);
+        return 1;
+    }
+    unsigned int cpu, node;
+    if (getcpu(&cpu, &node) < 0)
+        return fail(who);
+    if (!CPU_ISSET(cpu, &set) || node != 0 || sched_getcpu() != (int)cpu) {
+        fprintf(stderr, "%s: getcpu %u node %u not in mask\n", who, cpu, node);
+        return 1;
+    }
+    return 0;
+}
+
+int main(void) {
+    struct utsname uts;
+    if (uname(&uts) < 0)
+        return fail("uname");
+    if (strcmp(uts.machine, "wasm32") != 0) {
+        fprintf(stderr, "uname: machine '%s'\n", uts.machine);
+        return 1;
+    }
+    if (check_sysinfo())
+        return 1;
+
+    int ncpus = get_nprocs();
+    if (ncpus < 1 || sysconf(_SC_NPROCESSORS_ONLN) != ncpus)
+        return fail("get_nprocs");
+    if (check_affinity(ncpus, "initial"))
+        return 1;
+    if (sched_getscheduler(0) != SCHED_OTHER)
+        return fail("sched_getscheduler");
+
+    cpu_set_t set;
+    CPU_ZERO(&set);
+    if (sched_setaffinity(0, sizeof(set), &set) != -1 || errno != EINVAL) {
+        fprintf(stderr, "sched_setaffinity with an empty mask should fail with EINVAL\n");
+        return 1;
+    }
+    if (sched_getaffinity(0, 1, &set) != -1 || errno != EINVAL) {
+        fprintf(stderr, "sched_getaffinity into 1 byte should fail with EINVAL\n");
+        return 1;
+    }
+
+    /* The child narrows its own mask and raises its own nice value */
+    pid_t pid = fork();
+    if (pid < 0)
+        return fail("fork");
+    if (pid == 0) {
+        CPU_ZERO(&set);
+        CPU_SET(0, &set);
+        if (sched_setaffinity(0, sizeof(set), &set) < 0)
+            exit(fail("child: sched_setaffinity"));
+        if (check_affinity(1, "child"))
+            exit(1);
+        errno = 0;
+        if (setpriority(PRIO_PROCESS, 0, 5) < 0 || getpriority(PRIO_PROCESS, 0) != 5 ||
+            errno != 0)
+            exit(fail("child: setpriority"));
+        if (nice(2) != 7)
+            exit(fail("child: nice"));
+        exit(0);
+    }
+    int status;
+    if (waitpid(pid, &status, 0) < 0 || !WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+        fprintf(stderr, "child failed\n");
+        return 1;
+    }
+    if (check_affinity(ncpus, "parent"))
+        return 1;
+    errno = 0;
+    if (getpriority(PRIO_PROCESS, 0) != 0 || errno != 0)
+        return fail("parent: getpriority");
+    if (getpriority(PRIO_PROCESS, pid) != -1 || errno != ESRCH) {
+        fprintf(stderr, "getpriority of a reaped child should fail with ESRCH\n");
+        return 1;
+    }
+
+    printf("sched_sysinfo test passed\n");
+    return 0;
+}
@@ -0,0 +1,77 @@
+/* Named semaphores shared between cages: the child opens the semaphores by name on its own, so
+ * waiters and posters use separate mappings of the same /dev/shm object. */
+#include <assert.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <semaphore.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define ROUNDS 1000
+
+int main(void)
+{
+    setvbuf(stdout, NULL, _IONBF, 0);
+    sem_unlink("/lind_sem_ping");
+    sem_unlink("/lind_sem_pong");
+
+    sem_t *ping = sem_open("/lind_sem_ping", O_CREAT | O_EXCL, 0600, 0);
+    assert(ping != SEM_FAILED);
+    sem_t *pong = sem_open("/lind_sem_pong", O_CREAT | O_EXCL, 0600, 0);
+    assert(pong != SEM_FAILED);
+
+    // O_EXCL refuses an existing name, O_CREAT alone opens it
+    assert(sem_open("/lind_sem_ping", O_CREAT | O_EXCL, 0600, 0) == SEM_FAILED);
+    assert(errno == EEXIST);
+    sem_t *again = sem_open("/lind_sem_ping", O_CREAT, 0600, 5);
+    assert(again == ping);
+    int value;
+    assert(sem_getvalue(again, &value) == 0 && value == 0);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        sem_t *p = sem_open("/lind_sem_ping", 0);
+        sem_t *q = sem_open("/lind_sem_pong", 0);
+        assert(p != SEM_FAILED && q != SEM_FAILED);
+        for (int i = 0; i < ROUNDS; i++) {
+            if (sem_wait(p) != 0)
+                exit(1);
+            if (sem_post(q) != 0)
+                exit(1);
+        }
+        sem_close(p);
+        sem_close(q);
+        exit(0);
+    }
+
+    // Let the child block in sem_wait before the first post
+    usleep(100000);
+    for (int i = 0; i < ROUNDS; i++) {
+        assert(sem_post(ping) == 0);
+        assert(sem_wait(pong) == 0);
+    }
+    printf("%d round trips between cages\n", ROUNDS);
+
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+    assert(sem_getvalue(ping, &value) == 0 && value == 0);
+    assert(sem_getvalue(pong, &value) == 0 && value == 0);
+
+    // The semaphore keeps working after its name is gone
+    assert(sem_unlink("/lind_sem_ping") == 0);
+    assert(sem_open("/lind_sem_ping", 0) == SEM_FAILED && errno == ENOENT);
+    assert(sem_post(ping) == 0);
+    assert(sem_trywait(ping) == 0);
+    assert(sem_trywait(ping) == -1 && errno == EAGAIN);
+
+    assert(sem_close(ping) == 0);
+    assert(sem_close(pong) == 0);
+    assert(sem_unlink("/lind_sem_pong") == 0);
+    printf("named semaphores ok\n");
+    return 0;
+}
@@ -0,0 +1,136 @@
+#include <assert.h>
+#include <errno.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/ipc.h>
+#include <sys/msg.h>
+#include <sys/sem.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* SysV semaphore sets and message queues are shared by key between cages: semop blocks until
+ * another cage changes the set, SEM_UNDO adjustments are undone when a cage exits, IPC_RMID
+ * wakes blocked callers with EIDRM, and msgrcv selects messages by type. */
+
+union semun {
+    int val;
+    struct semid_ds *buf;
+    unsigned short *array;
+};
+
+struct message {
+    long mtype;
+    char mtext[32];
+};
+
+static void check_sem(void) {
+    key_t key = 5432;
+    int old_semid = semget(key, 0, 0666);
+    if (old_semid >= 0)
+        semctl(old_semid, 0, IPC_RMID);
+    int semid = semget(key, 2, IPC_CREAT | IPC_EXCL | 0666);
+    assert(semid >= 0);
+    assert(semget(key, 2, IPC_CREAT | IPC_EXCL | 0666) == -1 && errno == EEXIST);
+
+    unsigned short init[2] = {0, 5};
+    assert(semctl(semid, 0, SETALL, (union semun){.array = init}) == 0);
+    assert(semctl(semid, 1, GETVAL) == 5);
+
+    struct semid_ds ds;
+    assert(semctl(semid, 0, IPC_STAT, (union semun){.buf = &ds}) == 0);
+    assert(ds.sem_nsems == 2);
+
+    /* Nothing to take from semaphore 0 without waiting */
+    struct sembuf take = {.sem_num = 0, .sem_op = -1, .sem_flg = IPC_NOWAIT};
+    assert(semop(semid, &take, 1) == -1 && errno == EAGAIN);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        usleep(50 * 1000);
+        struct sembuf give = {.sem_num = 0, .sem_op = 1, .sem_flg = 0};
+        assert(semop(semid, &give, 1) == 0);
+        /* Held until exit, then undone */
+        struct sembuf hold = {.sem_num = 1, .sem_op = -2, .sem_flg = SEM_UNDO};
+        assert(semop(semid, &hold, 1) == 0);
+        _exit(0);
+    }
+    take.sem_flg = 0;
+    assert(semop(semid, &take, 1) == 0);
+
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+    assert(semctl(semid, 0, GETVAL) == 0);
+    assert(semctl(semid, 1, GETVAL) == 5);
+
+    /* Removing the set wakes a cage blocked on it */
+    pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        struct sembuf wait_op = {.sem_num = 0, .sem_op = -1, .sem_flg = 0};
+        assert(semop(semid, &wait_op, 1) == -1 && errno == EIDRM);
+        _exit(0);
+    }
+    usleep(50 * 1000);
+    assert(semctl(semid, 0, IPC_RMID) == 0);
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+    assert(semctl(semid, 0, GETVAL) == -1 && errno == EINVAL);
+}
+
+static void check_msg(void) {
+    key_t key = 6543;
+    int old_msqid = msgget(key, 0666);
+    if (old_msqid >= 0)
+        msgctl(old_msqid, IPC_RMID, NULL);
+    int msqid = msgget(key, IPC_CREAT | IPC_EXCL | 0666);
+    assert(msqid >= 0);
+
+    pid_t pid = fork();
+    assert(pid >= 0);
+    if (pid == 0) {
+        int child = msgget(key, 0);
+        assert(child == msqid);
+        struct message m = {.mtype = 1};
+        strcpy(m.mtext, "first");
+        assert(msgsnd(child, &m, strlen(m.mtext) + 1, 0) == 0);
+        m.mtype = 2;
+        strcpy(m.mtext, "second");
+        assert(msgsnd(child, &m, strlen(m.mtext) + 1, 0) == 0);
+        _exit(0);
+    }
+
+    struct message m;
+    /* Type 2 is taken ahead of the older type 1 message */
+    assert(msgrcv(msqid, &m, sizeof(m.mtext), 2, 0) == 7);
+    assert(m.mtype == 2 && !strcmp(m.mtext, "second"));
+    assert(msgrcv(msqid, &m, sizeof(m.mtext), 0, 0) == 6);
+    assert(m.mtype == 1 && !strcmp(m.mtext, "first"));
+    assert(msgrcv(msqid, &m, sizeof(m.mtext), 0, IPC_NOWAIT) == -1 && errno == ENOMSG);
+
+    int status;
+    assert(waitpid(pid, &status, 0) == pid);
+    assert(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+    /* A message longer than the buffer fails unless MSG_NOERROR truncates it */
+    m.mtype = 3;
+    strcpy(m.mtext, "truncated");
+    assert(msgsnd(msqid, &m, strlen(m.mtext) + 1, 0) == 0);
+    assert(msgrcv(msqid, &m, 4, 0, IPC_NOWAIT) == -1 && errno == E2BIG);
+    assert(msgrcv(msqid, &m, 4, 0, IPC_NOWAIT | MSG_NOERROR) == 4);
+    assert(!memcmp(m.mtext, "trun", 4));
+
+    struct msqid_ds ds;
+    assert(msgctl(msqid, IPC_STAT, &ds) == 0);
+    assert(ds.msg_qnum == 0);
+    assert(msgctl(msqid, IPC_RMID, NULL) == 0);
+    assert(msgsnd(msqid, &m, 1, 0) == -1 && errno == EINVAL);
+}
+
+int main(void) {
+    check_sem();
+    check_msg();
+    printf("sysv_sem_msg test passed\n");
+    return 0;
+}